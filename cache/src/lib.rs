@@ -2,6 +2,14 @@ use clap::{AppSettings, Parser};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 #[allow(deprecated)]
 use std::env::home_dir;
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
 
 use util::*;
 
@@ -40,6 +48,110 @@ pub enum Subcommands {
 
     #[clap(name = "size", about = "Prints the size of the cache in ~/.bifrost/cache")]
     Size(NoArguments),
+
+    #[clap(name = "config", about = "Display and edit the cache's settings")]
+    Config(CacheConfigArgs),
+
+    #[clap(
+        name = "prune",
+        about = "Removes cached objects by age or namespace from ~/.bifrost/cache"
+    )]
+    Prune(CachePruneArgs),
+}
+
+/// Clap argument parser for the cache config subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct CacheConfigArgs {
+    /// The target key to update, i.e. `max_size`.
+    #[clap(required = false, default_value = "")]
+    pub key: String,
+
+    /// The value to set the key to, i.e. `500MB`. A `max_size` of 0 disables the limit.
+    #[clap(required = false, default_value = "")]
+    pub value: String,
+}
+
+/// Clap argument parser for the cache prune subcommand
+#[derive(Debug, Clone, Parser)]
+pub struct CachePruneArgs {
+    /// Only remove objects which haven't been used within the given age, i.e. `30d`, `12h`.
+    #[clap(long = "older-than")]
+    pub older_than: Option<String>,
+
+    /// Only remove objects whose key matches the given glob, i.e. `selector.*`.
+    #[clap(long = "namespace")]
+    pub namespace: Option<String>,
+}
+
+/// The maximum size of the cache in bytes, which is read from the `cache_max_size` key of the
+/// configuration. When exceeded, the least recently used objects are evicted. 0 means the cache
+/// is unbounded.
+static MAX_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// The running size of the cache, as objects are stored by this process.
+static CACHE_SIZE: Mutex<SizeTracker> = Mutex::new(SizeTracker { size: None });
+
+/// Tracks the size of the cache as objects are stored, so the cache only needs to be measured on
+/// disk once per process, and again whenever it grows beyond its maximum size.
+#[derive(Debug, Default)]
+pub struct SizeTracker {
+    size: Option<u64>,
+}
+
+impl SizeTracker {
+    /// Record that an object of `written` bytes was stored, measuring the cache with `measure` if
+    /// it hasn't been measured yet. Returns true if the cache is now larger than `max_size`.
+    ///
+    /// ```
+    /// use heimdall_cache::SizeTracker;
+    ///
+    /// let mut tracker = SizeTracker::default();
+    /// assert!(!tracker.record(10, 100, || 50));
+    /// assert!(tracker.record(60, 100, || unreachable!()));
+    /// ```
+    pub fn record(&mut self, written: u64, max_size: u64, measure: impl FnOnce() -> u64) -> bool {
+        let size = match self.size {
+            Some(size) => size + written,
+            // the measurement already includes the object which was just stored
+            None => measure(),
+        };
+        self.size = Some(size);
+
+        max_size != 0 && size > max_size
+    }
+
+    /// Set the size of the cache, once it's been measured.
+    pub fn reset(&mut self, size: u64) {
+        self.size = Some(size);
+    }
+}
+
+/// A cached object on disk, used to determine which objects to evict
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheEntry {
+    pub key: String,
+    pub size: u64,
+    pub last_accessed: SystemTime,
+}
+
+impl CacheEntry {
+    /// The eviction priority of this entry, where lower values are evicted first: \
+    /// 0. negative signature lookups, which are cheap to repeat \
+    /// 1. contract bytecode, which can always be fetched again \
+    /// 2. everything else
+    pub fn eviction_priority(&self) -> u8 {
+        // negative lookups are cached under their own key, though older caches may still hold
+        // an empty list of signatures, which serializes to 16 bytes, or 32 hex characters
+        if self.key.starts_with("selector.negative.") ||
+            (self.key.starts_with("selector.") && self.size <= 32)
+        {
+            0
+        } else if self.key.starts_with("contract.") {
+            1
+        } else {
+            2
+        }
+    }
 }
 
 /// A simple cache object that stores a value and an expiry time \
@@ -50,6 +162,19 @@ pub struct Cache<T> {
     pub expiry: u64,
 }
 
+/// The directory cached objects are stored in, i.e. `$HOME/.bifrost/cache`, or `None` if the
+/// home directory can't be resolved.
+///
+/// ```
+/// use heimdall_cache::cache_dir;
+///
+/// assert!(cache_dir().unwrap().ends_with(".bifrost/cache"));
+/// ```
+#[allow(deprecated)]
+pub fn cache_dir() -> Option<PathBuf> {
+    home_dir().map(|home| home.join(".bifrost").join("cache"))
+}
+
 /// Clear the cache, removing all objects
 ///
 /// ```
@@ -67,10 +192,8 @@ pub struct Cache<T> {
 /// /// assert that the cache no longer contains the key
 /// assert!(!keys("*").contains(&"clear_cache_key".to_string()));
 /// ```
-#[allow(deprecated)]
 pub fn clear_cache() {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
 
     for entry in cache_dir.read_dir().unwrap() {
         let entry = entry.unwrap();
@@ -79,6 +202,148 @@ pub fn clear_cache() {
     }
 }
 
+/// Set the maximum size of the cache in bytes, from the configuration. 0 means the cache is
+/// unbounded.
+pub fn set_max_size(max_size: u64) {
+    MAX_SIZE.store(max_size, Ordering::Relaxed);
+}
+
+/// Get the maximum size of the cache in bytes, where 0 means the cache is unbounded.
+pub fn max_size() -> u64 {
+    MAX_SIZE.load(Ordering::Relaxed)
+}
+
+/// List all cached objects on disk, along with their size and last access time
+pub fn entries() -> Vec<CacheEntry> {
+    let cache_dir = cache_dir().unwrap();
+
+    let read_dir = match cache_dir.read_dir() {
+        Ok(read_dir) => read_dir,
+        Err(_) => return Vec::new(),
+    };
+
+    read_dir
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let metadata = entry.metadata().ok()?;
+
+            Some(CacheEntry {
+                key: entry.file_name().to_str()?.strip_suffix(".bin")?.to_string(),
+                size: metadata.len(),
+                last_accessed: metadata.modified().ok()?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the keys which must be evicted for the given entries to fit within `max_size` bytes,
+/// in the order they should be evicted. Negative signature lookups and bytecode are evicted
+/// first, followed by the least recently used objects.
+///
+/// ```
+/// use heimdall_cache::{eviction_order, CacheEntry};
+/// use std::time::{Duration, SystemTime};
+///
+/// let entries = vec![
+///     CacheEntry { key: String::from("a"), size: 100, last_accessed: SystemTime::now() },
+///     CacheEntry {
+///         key: String::from("b"),
+///         size: 100,
+///         last_accessed: SystemTime::now() - Duration::from_secs(60),
+///     },
+/// ];
+///
+/// assert_eq!(eviction_order(entries, 150), vec![String::from("b")]);
+/// ```
+pub fn eviction_order(mut entries: Vec<CacheEntry>, max_size: u64) -> Vec<String> {
+    let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    entries.sort_by(|a, b| {
+        a.eviction_priority()
+            .cmp(&b.eviction_priority())
+            .then(a.last_accessed.cmp(&b.last_accessed))
+    });
+
+    let mut evicted = Vec::new();
+    for entry in entries {
+        if size <= max_size {
+            break
+        }
+
+        size -= entry.size;
+        evicted.push(entry.key);
+    }
+
+    evicted
+}
+
+/// Evict cached objects until the cache fits within its configured maximum size, returning the
+/// evicted keys
+pub fn enforce_max_size() -> Vec<String> {
+    let (evicted, size) = evict(max_size());
+    if let Ok(mut tracker) = CACHE_SIZE.lock() {
+        tracker.reset(size);
+    }
+
+    evicted
+}
+
+/// Evict cached objects until the cache fits within `max_size` bytes, returning the evicted keys
+/// and the size of the objects which remain.
+fn evict(max_size: u64) -> (Vec<String>, u64) {
+    let entries = entries();
+    let size = entries.iter().map(|entry| entry.size).sum::<u64>();
+    if max_size == 0 {
+        return (Vec::new(), size)
+    }
+
+    let evicted = eviction_order(entries.clone(), max_size);
+    let evicted_size = entries
+        .iter()
+        .filter(|entry| evicted.contains(&entry.key))
+        .map(|entry| entry.size)
+        .sum::<u64>();
+    for key in &evicted {
+        delete_cache(key);
+    }
+
+    (evicted, size - evicted_size)
+}
+
+/// Remove cached objects which haven't been used within `older_than` seconds and whose keys
+/// match the `namespace` glob, returning the removed keys. Omitted filters match every object.
+///
+/// ```
+/// use heimdall_cache::{prune_cache, store_cache, keys};
+///
+/// /// add a value to the cache
+/// store_cache("prune.cache_key", "value", None);
+///
+/// /// prune the namespace
+/// prune_cache(None, Some("prune.*"));
+///
+/// /// assert that the cache no longer contains the key
+/// assert!(!keys("*").contains(&"prune.cache_key".to_string()));
+/// ```
+pub fn prune_cache(older_than: Option<u64>, namespace: Option<&str>) -> Vec<String> {
+    let now = SystemTime::now();
+    let mut pruned = Vec::new();
+
+    for entry in entries() {
+        let age = now.duration_since(entry.last_accessed).unwrap_or_default().as_secs();
+
+        if older_than.map(|older_than| age >= older_than).unwrap_or(true) &&
+            namespace.map(|namespace| glob_match(namespace, &entry.key)).unwrap_or(true)
+        {
+            delete_cache(&entry.key);
+            pruned.push(entry.key);
+        }
+    }
+
+    pruned.sort();
+    pruned
+}
+
 /// Check if a cached object exists
 ///
 /// ```
@@ -93,10 +358,8 @@ pub fn clear_cache() {
 /// /// assert that the cache does not contain a non-existent key
 /// assert!(!exists("non_existent_key"));
 /// ```
-#[allow(deprecated)]
 pub fn exists(key: &str) -> bool {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
     let cache_file = cache_dir.join(format!("{key}.bin"));
 
     cache_file.exists()
@@ -119,10 +382,8 @@ pub fn exists(key: &str) -> bool {
 /// /// assert that the cache contains the key
 /// assert!(keys("keys_*").contains(&"keys_key".to_string()));
 /// ```
-#[allow(deprecated)]
 pub fn keys(pattern: &str) -> Vec<String> {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
     let mut keys = Vec::new();
 
     // remove wildcard
@@ -132,6 +393,12 @@ pub fn keys(pattern: &str) -> Vec<String> {
         let entry = entry.unwrap();
         let path = entry.path();
         let key = path.file_name().unwrap().to_str().unwrap().to_string();
+
+        // skip temporary files which are still being written
+        if !key.ends_with(".bin") {
+            continue
+        }
+
         if pattern.is_empty() || key.contains(&pattern) {
             keys.push(key.replace(".bin", ""));
         }
@@ -159,14 +426,13 @@ pub fn keys(pattern: &str) -> Vec<String> {
 /// /// assert that the cache does not contain the key
 /// assert!(!keys("*").contains(&"delete_cache_key".to_string()));
 /// ```
-#[allow(deprecated)]
 pub fn delete_cache(key: &str) {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
     let cache_file = cache_dir.join(format!("{key}.bin"));
 
+    // another process may have already removed the object, which is fine
     if cache_file.exists() {
-        std::fs::remove_file(cache_file).unwrap();
+        let _ = std::fs::remove_file(cache_file);
     }
 }
 
@@ -181,12 +447,10 @@ pub fn delete_cache(key: &str) {
 /// /// read the cached object
 /// assert_eq!(read_cache::<String>("read_cache_key").unwrap(), "value");
 /// ```
-#[allow(deprecated)]
 pub fn read_cache<T>(key: &str) -> Option<T>
 where
     T: 'static + DeserializeOwned, {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
     let cache_file = cache_dir.join(format!("{key}.bin"));
 
     let binary_string = match read_file(cache_file.to_str().unwrap()) {
@@ -217,6 +481,12 @@ where
         }
         Err(_) => return None,
     };
+
+    // mark the object as recently used, so it isn't evicted
+    if let Ok(file) = std::fs::File::options().write(true).open(&cache_file) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+
     Some(*Box::new(cache.value))
 }
 
@@ -232,12 +502,10 @@ where
 /// /// add a value to the cache with an expiry time of 1 day
 /// store_cache("store_cache_key2", "value", Some(60 * 60 * 24));
 /// ```
-#[allow(deprecated)]
 pub fn store_cache<T>(key: &str, value: T, expiry: Option<u64>)
 where
     T: Serialize, {
-    let home = home_dir().unwrap();
-    let cache_dir = home.join(".bifrost").join("cache");
+    let cache_dir = cache_dir().unwrap();
     let cache_file = cache_dir.join(format!("{key}.bin"));
 
     // expire in 90 days
@@ -249,11 +517,26 @@ where
     let cache = Cache { value, expiry };
     let encoded: Vec<u8> = bincode::serialize(&cache).unwrap();
     let binary_string = encode_hex(encoded);
-    write_file(cache_file.to_str().unwrap(), &binary_string);
+
+    // write atomically, so parallel heimdall processes never read a partially written object
+    write_file_atomic(cache_file.to_str().unwrap(), &binary_string);
+
+    // the cache is only measured and evicted from once it grows beyond its maximum size
+    let max_size = max_size();
+    if max_size == 0 {
+        return
+    }
+    if let Ok(mut tracker) = CACHE_SIZE.lock() {
+        if tracker.record(binary_string.len() as u64, max_size, || {
+            entries().iter().map(|entry| entry.size).sum()
+        }) {
+            let (_, size) = evict(max_size);
+            tracker.reset(size);
+        }
+    }
 }
 
 /// Cache subcommand handler
-#[allow(deprecated)]
 pub fn cache(args: CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
     match args.sub {
         Subcommands::Clean(_) => {
@@ -269,8 +552,7 @@ pub fn cache(args: CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
             }
         }
         Subcommands::Size(_) => {
-            let home = home_dir().unwrap();
-            let cache_dir = home.join(".bifrost").join("cache");
+            let cache_dir = cache_dir().unwrap();
             let mut size = 0;
 
             for entry in cache_dir.read_dir().unwrap() {
@@ -283,6 +565,50 @@ pub fn cache(args: CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
             println!("Cached objects: {}", keys("*").len());
             println!("Cache size: {}", prettify_bytes(size));
         }
+        Subcommands::Config(args) => {
+            // the settings are persisted to the configuration by the caller, and applied here
+            match (args.key.as_str(), args.value.as_str()) {
+                ("", _) => {
+                    println!(
+                        "max_size = {}",
+                        match max_size() {
+                            0 => String::from("unlimited"),
+                            max_size => prettify_bytes(max_size),
+                        }
+                    );
+                }
+                ("max_size", "") => return Err("found key but no value to set.".into()),
+                ("max_size", value) => {
+                    match parse_bytes(value) {
+                        Some(max_size) => set_max_size(max_size),
+                        None => return Err(format!("invalid size '{value}' .").into()),
+                    };
+
+                    let evicted = enforce_max_size();
+                    println!("Set 'max_size' = '{value}' .");
+                    if !evicted.is_empty() {
+                        println!("Evicted {} cached objects.", evicted.len());
+                    }
+                }
+                (key, _) => return Err(format!("unknown cache setting '{key}' .").into()),
+            }
+        }
+        Subcommands::Prune(args) => {
+            if args.older_than.is_none() && args.namespace.is_none() {
+                return Err("either '--older-than' or '--namespace' must be specified. Use `heimdall cache clean` to remove all cached objects.".into())
+            }
+
+            let older_than = match &args.older_than {
+                Some(older_than) => match parse_duration(older_than) {
+                    Some(older_than) => Some(older_than),
+                    None => return Err(format!("invalid age '{older_than}' .").into()),
+                },
+                None => None,
+            };
+
+            let pruned = prune_cache(older_than, args.namespace.as_deref());
+            println!("Pruned {} cached objects.", pruned.len());
+        }
     }
 
     Ok(())
@@ -291,9 +617,64 @@ pub fn cache(args: CacheArgs) -> Result<(), Box<dyn std::error::Error>> {
 #[allow(deprecated)]
 #[cfg(test)]
 mod tests {
-    use crate::{delete_cache, exists, keys, read_cache, store_cache};
+    use crate::{delete_cache, eviction_order, exists, keys, read_cache, store_cache, CacheEntry};
     use serde::{Deserialize, Serialize};
-    use std::env::home_dir;
+    use std::{
+        env::home_dir,
+        time::{Duration, SystemTime},
+    };
+
+    #[test]
+    fn test_store_cache_concurrent() {
+        // parallel writers and readers must never observe a partially written object
+        let handles = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    for _ in 0..25 {
+                        store_cache("concurrent_key", vec![i as u8; 256], None);
+                        let value = read_cache::<Vec<u8>>("concurrent_key").unwrap();
+                        assert!(value.len() == 256 && value.iter().all(|v| *v == value[0]));
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert!(!keys("concurrent_key").iter().any(|key| key != "concurrent_key"));
+        delete_cache("concurrent_key");
+    }
+
+    #[test]
+    fn test_eviction_order() {
+        let now = SystemTime::now();
+        let entry = |key: &str, size: u64, age: u64| CacheEntry {
+            key: key.to_string(),
+            size,
+            last_accessed: now - Duration::from_secs(age),
+        };
+
+        let entries = vec![
+            entry("selector.00000000", 32, 0),
+            entry("selector.a9059cbb", 512, 100),
+            entry("contract.1.0x00", 1000, 0),
+            entry("chain_id.rpc", 100, 50),
+            entry("diff.1.0x01", 100, 10),
+        ];
+
+        // nothing is evicted if the cache is small enough
+        assert!(eviction_order(entries.clone(), 2000).is_empty());
+
+        // negative lookups and bytecode go first, then the least recently used objects
+        assert_eq!(
+            eviction_order(entries, 300),
+            vec!["selector.00000000", "contract.1.0x00", "selector.a9059cbb"]
+        );
+
+        // negative lookups cached under their own key are evicted first, regardless of size
+        assert_eq!(entry("selector.negative.a9059cbb", 64, 0).eviction_priority(), 0);
+    }
 
     #[test]
     fn test_store_cache() {
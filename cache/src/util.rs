@@ -64,6 +64,97 @@ pub fn prettify_bytes(bytes: u64) -> String {
     }
 }
 
+/// Parse a human-readable size into bytes \
+/// e.g. 1 KB -> 1024
+///
+/// ```
+/// use heimdall_cache::util::parse_bytes;
+///
+/// assert_eq!(parse_bytes("500"), Some(500));
+/// assert_eq!(parse_bytes("2KB"), Some(2048));
+/// assert_eq!(parse_bytes("1 gb"), Some(1024 * 1024 * 1024));
+/// assert_eq!(parse_bytes("lots"), None);
+/// ```
+pub fn parse_bytes(size: &str) -> Option<u64> {
+    let size = size.trim().to_uppercase();
+    let split = size.find(|c: char| !c.is_ascii_digit()).unwrap_or(size.len());
+    let (amount, unit) = size.split_at(split);
+
+    let multiplier = match unit.trim() {
+        "" | "B" => 1,
+        "KB" | "K" => 1024,
+        "MB" | "M" => 1024 * 1024,
+        "GB" | "G" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    amount.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Parse a human-readable duration into seconds \
+/// e.g. 30d -> 2592000
+///
+/// ```
+/// use heimdall_cache::util::parse_duration;
+///
+/// assert_eq!(parse_duration("45"), Some(45));
+/// assert_eq!(parse_duration("12h"), Some(12 * 60 * 60));
+/// assert_eq!(parse_duration("30d"), Some(30 * 24 * 60 * 60));
+/// assert_eq!(parse_duration("soon"), None);
+/// ```
+pub fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim().to_lowercase();
+    let split = duration.find(|c: char| !c.is_ascii_digit()).unwrap_or(duration.len());
+    let (amount, unit) = duration.split_at(split);
+
+    let multiplier = match unit.trim() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        "w" => 60 * 60 * 24 * 7,
+        _ => return None,
+    };
+
+    amount.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// Check if the given text matches a glob pattern, where `*` matches any sequence of characters
+/// and `?` matches any single character
+///
+/// ```
+/// use heimdall_cache::util::glob_match;
+///
+/// assert!(glob_match("selector.*", "selector.a9059cbb"));
+/// assert!(glob_match("contract.?.*", "contract.1.0x00"));
+/// assert!(!glob_match("selector.*", "contract.1.0x00"));
+/// ```
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+
+    // iterative matching with backtracking to the most recent '*'
+    let (mut p, mut t) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            last_star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = last_star {
+            p = star_p + 1;
+            t = star_t + 1;
+            last_star = Some((star_p, star_t + 1));
+        } else {
+            return false
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
 /// Write contents to a file on the disc
 ///
 /// ```no_run
@@ -93,6 +184,42 @@ pub fn write_file(_path: &str, contents: &str) -> Option<String> {
     Some(_path.to_string())
 }
 
+/// Atomically write contents to a file on the disc, by writing to a temporary file in the same
+/// directory and renaming it into place. Concurrent readers will either see the old contents or
+/// the new contents, but never a partially written file.
+///
+/// ```no_run
+/// use heimdall_cache::util::write_file_atomic;
+///
+/// let path = "/tmp/test.txt";
+/// let contents = "Hello, World!";
+/// let result = write_file_atomic(path, contents);
+/// ```
+pub fn write_file_atomic(_path: &str, contents: &str) -> Option<String> {
+    let path = std::path::Path::new(_path);
+    let file_name = path.file_name()?.to_str()?;
+
+    // the temporary file must be unique across processes and threads
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_nanos())
+        .unwrap_or(0);
+    let temp_path = path.with_file_name(format!(
+        ".{file_name}.{}.{:?}.{nonce}.tmp",
+        std::process::id(),
+        std::thread::current().id()
+    ));
+
+    write_file(temp_path.to_str()?, contents)?;
+    match std::fs::rename(&temp_path, path) {
+        Ok(_) => Some(_path.to_string()),
+        Err(_) => {
+            let _ = std::fs::remove_file(&temp_path);
+            None
+        }
+    }
+}
+
 /// Read contents from a file on the disc
 ///
 /// ```no_run
@@ -186,6 +313,16 @@ mod tests {
         assert_eq!(result, "4 GB");
     }
 
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("selector.*", "selector."));
+        assert!(glob_match("*.a9059cbb", "selector.a9059cbb"));
+        assert!(glob_match("diff.*.*", "diff.1.0x01"));
+        assert!(!glob_match("diff.*.*", "diff.1"));
+        assert!(!glob_match("selector", "selector.a9059cbb"));
+    }
+
     #[test]
     fn test_write_file_successful() {
         let path = "/tmp/test.txt";
@@ -203,6 +340,19 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    #[test]
+    fn test_write_file_atomic_successful() {
+        let path = "/tmp/heimdall_atomic/test.txt";
+        let result = write_file_atomic(path, "Hello, World!");
+        assert_eq!(result, Some(path.to_string()));
+        assert_eq!(read_file(path), Some("Hello, World!".to_string()));
+
+        // the temporary file must not be left behind
+        let files = std::fs::read_dir("/tmp/heimdall_atomic").unwrap().count();
+        assert_eq!(files, 1);
+        delete_path("/tmp/heimdall_atomic");
+    }
+
     #[test]
     fn test_read_file_successful() {
         let path = "/tmp/test.txt";
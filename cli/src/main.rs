@@ -1,33 +1,72 @@
 use backtrace::Backtrace;
-use std::{env, io, panic};
+use std::{io, panic};
 
 use clap::{Parser, Subcommand};
 use colored::Colorize;
 use crossterm::{
+    cursor::Show,
     event::DisableMouseCapture,
     execute,
     terminal::{disable_raw_mode, LeaveAlternateScreen},
 };
 
-use heimdall_cache::{cache, CacheArgs};
+use heimdall_cache::{cache, set_max_size, CacheArgs, Subcommands as CacheSubcommands};
 use heimdall_common::{
-    constants::ADDRESS_REGEX,
+    ether::{
+        evm::core::memory::set_memory_limits,
+        labels::{add_token_list, load_token_list},
+        rpc::set_rpc_headers,
+    },
     utils::{
+        crash::write_crash_report,
+        http::{set_proxy, set_retry_policy, RetryPolicy},
+        interrupt::{install_interrupt_handler, is_interrupted},
         io::{
-            file::{write_file, write_lines_to_file},
-            logging::Logger,
+            file::{
+                parse_delimiter, set_csv_delimiter, write_csv_to_file, write_file,
+                write_lines_to_file,
+            },
+            format::OutputFormat,
+            logging::{disable_colors, log_to_stderr, no_color_env, set_log_file, Logger},
+            output::OutputLocator,
         },
         version::{current_version, remote_version},
     },
 };
-use heimdall_config::{config, get_config, ConfigArgs};
+use heimdall_config::{config, get_config, update_config, ConfigArgs};
 use heimdall_core::{
-    cfg::{cfg, output::write_cfg_to_file, CFGArgs},
-    decode::{decode, DecodeArgs},
-    decompile::{decompile, out::abi::ABIStructure, DecompilerArgs},
+    abi::{abi, AbiArgs},
+    batch::{batch, BatchArgs},
+    bench::{bench, BenchArgs},
+    cfg::{
+        cfg,
+        output::{write_cfg_stats_to_file, write_cfg_to_file},
+        CFGArgs,
+    },
+    decode::{decode_with_calldata, DecodeArgs},
+    decompile::{
+        decompile,
+        out::{abi::ABIStructure, split::split_functions},
+        DecompileResult, DecompilerArgs,
+    },
+    diff::{diff, DiffArgs},
     disassemble::{disassemble, DisassemblerArgs},
-    dump::{dump, DumpArgs},
+    doctor::{doctor, DoctorArgs},
+    dump::{csv_rows, dump, storage_layout, DumpArgs},
+    fuzz::{fuzz, FuzzArgs},
+    metadata::{metadata, MetadataArgs},
+    profile::{profile, ProfileArgs},
+    report::{
+        batch_context, bench_context, cfg_context, decode_context, decompile_context,
+        disassemble_context, dump_context, fuzz_context, manifest_context, metadata_context,
+        profile_context, schema::write_json, sig_context, similar_context, snapshot_context,
+        trace_context, write_report,
+    },
+    sig::{sig, SigArgs},
+    similar::{similar, SimilarArgs},
     snapshot::{snapshot, util::csv::generate_and_write_contract_csv, SnapshotArgs},
+    trace::{trace, TraceArgs},
+    update::{update, UpdateArgs},
 };
 use tui::{backend::CrosstermBackend, Terminal};
 
@@ -36,6 +75,43 @@ use tui::{backend::CrosstermBackend, Terminal};
 pub struct Arguments {
     #[clap(subcommand)]
     pub sub: Subcommands,
+
+    /// Tee all log output to the given file, with timestamps and without colors.
+    #[clap(long = "log-file", global = true)]
+    pub log_file: Option<String>,
+
+    /// Disable colored output. Also honors the `NO_COLOR` environment variable.
+    #[clap(long = "no-color", global = true)]
+    pub no_color: bool,
+
+    /// A header to send with every RPC request, in the `Name: value` format, e.g.
+    /// `Authorization: Bearer <token>`. May be given multiple times, and replaces the configured
+    /// `rpc_headers`.
+    #[clap(long = "rpc-header", global = true)]
+    pub rpc_header: Vec<String>,
+
+    /// The directory to write output to, overriding the configured `output_dir`. Relative paths
+    /// are resolved against the current working directory. Defaults to `./output`.
+    #[clap(long = "output", global = true, default_value = "", hide_default_value = true)]
+    pub output: String,
+
+    /// The format to write output in, either `json`, `csv`, or `text`. JSON output follows each
+    /// subcommand's published schema. `dump` also supports `storage-layout`, which follows the
+    /// schema of solc's `storageLayout` output, and `snapshot` supports `manifest`, a capability
+    /// manifest for monitoring and wallet tooling. Defaults to each subcommand's own format.
+    #[clap(long = "format", global = true, default_value = "", hide_default_value = true)]
+    pub format: String,
+
+    /// The delimiter which separates the fields of CSV output, such as `;`, or `tab` for TSV.
+    /// Defaults to a comma.
+    #[clap(long = "delimiter", global = true, default_value = "", hide_default_value = true)]
+    pub delimiter: String,
+
+    /// A token list to label addresses with, either a file or a URL, in the Uniswap token list
+    /// format. May be given multiple times, in addition to the configured `token_lists` and the
+    /// bundled list of well-known tokens.
+    #[clap(long = "token-list", global = true)]
+    pub token_list: Vec<String>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -51,6 +127,9 @@ pub enum Subcommands {
     #[clap(name = "decompile", about = "Decompile EVM bytecode to Solidity")]
     Decompile(DecompilerArgs),
 
+    #[clap(name = "abi", about = "Extract a contract's ABI without decompiling it")]
+    Abi(AbiArgs),
+
     #[clap(name = "cfg", about = "Generate a visual control flow graph for EVM bytecode")]
     CFG(CFGArgs),
 
@@ -65,20 +144,82 @@ pub enum Subcommands {
 
     #[clap(name = "dump", about = "Dump the value of all storage slots accessed by a contract")]
     Dump(DumpArgs),
+
+    #[clap(
+        name = "fuzz",
+        about = "Fuzz a contract's recovered functions with random inputs via eth_call"
+    )]
+    Fuzz(FuzzArgs),
+
     #[clap(
         name = "snapshot",
         about = "Infer function information from bytecode, including access control, gas
     consumption, storage accesses, event emissions, and more"
     )]
     Snapshot(SnapshotArgs),
+
+    #[clap(
+        name = "sig",
+        about = "Compute selectors for signatures, or resolve selectors to signatures"
+    )]
+    Sig(SigArgs),
+
+    #[clap(name = "metadata", about = "Decode the compiler metadata appended to EVM bytecode")]
+    Metadata(MetadataArgs),
+
+    #[clap(
+        name = "diff",
+        about = "Compare a contract's deployments at the same address across chains"
+    )]
+    Diff(DiffArgs),
+
+    #[clap(
+        name = "similar",
+        about = "Find contracts similar to the target within a local index of fingerprints"
+    )]
+    Similar(SimilarArgs),
+
+    #[clap(name = "profile", about = "Profile the gas usage of a transaction")]
+    Profile(ProfileArgs),
+
+    #[clap(
+        name = "trace",
+        about = "Trace a transaction's calls, with resolved functions, arguments, and logs"
+    )]
+    Trace(TraceArgs),
+
+    #[clap(name = "bench", about = "Benchmark decompile and snapshot over a corpus of contracts")]
+    Bench(BenchArgs),
+
+    #[clap(name = "batch", about = "Run snapshot, decompile, or selectors over many targets")]
+    Batch(BatchArgs),
+
+    #[clap(name = "doctor", about = "Diagnose problems with heimdall's environment")]
+    Doctor(DoctorArgs),
+
+    #[clap(name = "update", about = "Update heimdall to the latest release")]
+    Update(UpdateArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Arguments::parse();
 
+    // decoded calldata is printed to stdout as json, so logs are moved out of its way
+    if let Subcommands::Decode(cmd) = &args.sub {
+        if cmd.json {
+            log_to_stderr();
+        }
+    }
+
+    let configuration = get_config();
+
+    // locate where artifacts are written, within the root output directory
+    let output = OutputLocator::resolve(&args.output, &configuration.output_dir)?;
+
     // handle catching panics with
-    panic::set_hook(Box::new(|panic_info| {
+    let crash_report_dir = output.root.clone();
+    panic::set_hook(Box::new(move |panic_info| {
         // cleanup the terminal (break out of alternate screen, disable mouse capture, and show the
         // cursor)
         let stdout = io::stdout();
@@ -96,16 +237,114 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             panic_info.to_string().bright_white().on_bright_red().bold(),
         ));
         logger.fatal(&format!("Stack Trace:\n\n{backtrace:#?}"));
+
+        // write a crash report which can be attached to bug reports
+        if let Some(path) = write_crash_report(
+            &crash_report_dir,
+            &panic_info.to_string(),
+            &format!("{backtrace:?}"),
+        ) {
+            logger.fatal(&format!("a crash report was written to '{path}' ."));
+        }
     }));
 
-    let configuration = get_config();
+    // on Ctrl-C, stop in-flight analysis and write partial output. a second Ctrl-C exits
+    // immediately, restoring the terminal first. only subcommands which check for interruption
+    // stop this way, the rest exit on the first Ctrl-C
+    if matches!(
+        args.sub,
+        Subcommands::Decompile(_) |
+            Subcommands::Snapshot(_) |
+            Subcommands::Dump(_) |
+            Subcommands::CFG(_)
+    ) {
+        install_interrupt_handler(|| {
+            let _ = disable_raw_mode();
+            let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show);
+        });
+    }
+
+    // strip colors from all output, i.e. when piping to a file or another program
+    if args.no_color || no_color_env() {
+        disable_colors();
+    }
 
-    // get the current working directory
-    let mut output_path = env::current_dir()?.into_os_string().into_string().unwrap();
-    output_path.push_str("/output");
+    // tee log output to a file, if requested
+    if let Some(log_file) = &args.log_file {
+        if let Err(e) = set_log_file(log_file) {
+            let (logger, _) = Logger::new("");
+            logger.error(&format!("failed to open log file '{log_file}': {e}"));
+            std::process::exit(1);
+        }
+    }
+
+    // apply the configured retry policy to every HTTP request
+    set_retry_policy(RetryPolicy {
+        max_retries: configuration.http_max_retries,
+        timeout: match configuration.http_timeout {
+            0 => None,
+            timeout => Some(timeout),
+        },
+        ..Default::default()
+    });
+
+    // evict the least recently used cached objects once the cache grows beyond its limit
+    set_max_size(configuration.cache_max_size);
+
+    // bound how much memory each EVM holds, and returns from a single read
+    set_memory_limits(
+        configuration.max_memory_size as usize,
+        configuration.max_memory_access as usize,
+    );
+
+    // route every HTTP request through the configured proxy. without one, the HTTP_PROXY,
+    // HTTPS_PROXY, and NO_PROXY environment variables are respected
+    set_proxy(Some(configuration.proxy.clone()));
+
+    // separate the fields of CSV output with the given delimiter
+    match parse_delimiter(&args.delimiter) {
+        Ok(delimiter) => set_csv_delimiter(delimiter),
+        Err(e) => {
+            let (logger, _) = Logger::new("");
+            logger.error(&format!("{e} ."));
+            std::process::exit(1);
+        }
+    }
+
+    // send the given headers with every RPC request, falling back to the configured headers
+    let rpc_headers = match args.rpc_header.is_empty() {
+        true => &configuration.rpc_headers,
+        false => &args.rpc_header,
+    };
+    if let Err(e) = set_rpc_headers(rpc_headers) {
+        let (logger, _) = Logger::new("");
+        logger.error(&format!("invalid RPC header: {e} ."));
+        std::process::exit(1);
+    }
+
+    // label the addresses of tokens in the given token lists, in addition to the bundled one
+    for source in configuration.token_lists.iter().chain(&args.token_list) {
+        match load_token_list(source).await {
+            Ok(token_list) => add_token_list(token_list),
+            Err(e) => {
+                let (logger, _) = Logger::new("");
+                logger.warn(&format!("failed to load token list '{source}': {e}"));
+            }
+        }
+    }
 
     match args.sub {
         Subcommands::Disassemble(mut cmd) => {
+            // a diff is only written as text
+            let format = match cmd.diff.is_empty() {
+                true => output_format(
+                    &args.format,
+                    "disassemble",
+                    &[OutputFormat::Text, OutputFormat::Json, OutputFormat::Csv],
+                ),
+                false => output_format(&args.format, "disassemble --diff", &[OutputFormat::Text]),
+            };
+
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
                 cmd.rpc_url = configuration.rpc_url;
@@ -114,71 +353,96 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let assembly = disassemble(cmd.clone()).await?;
 
             // write to file
-            if ADDRESS_REGEX.is_match(&cmd.target).unwrap() {
-                output_path.push_str(&format!("/{}/disassembled.asm", &cmd.target));
-            } else {
-                output_path.push_str("/local/disassembled.asm");
+            match format {
+                OutputFormat::Json => write_json(
+                    "disassemble",
+                    disassemble_context(&assembly, &cmd.target),
+                    &output.artifact(&cmd.target, "disassembled.json"),
+                ),
+                OutputFormat::Csv => {
+                    let mut rows = vec![vec![
+                        String::from("pc"),
+                        String::from("opcode"),
+                        String::from("operand"),
+                    ]];
+                    for instruction in disassemble_context(&assembly, &cmd.target)["instructions"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                    {
+                        rows.push(
+                            ["pc", "opcode", "operand"]
+                                .iter()
+                                .map(|key| {
+                                    instruction[key].as_str().unwrap_or_default().to_string()
+                                })
+                                .collect(),
+                        );
+                    }
+                    write_csv_to_file(&output.artifact(&cmd.target, "disassembled.csv"), &rows);
+                }
+                _ if !cmd.diff.is_empty() => {
+                    write_file(&output.artifact(&cmd.target, "disassembled.diff"), &assembly);
+                }
+                _ => {
+                    write_file(&output.artifact(&cmd.target, "disassembled.asm"), &assembly);
+                }
             }
-            write_file(&output_path, &assembly);
         }
 
         Subcommands::Decompile(mut cmd) => {
+            let format =
+                output_format(&args.format, "decompile", &[OutputFormat::Text, OutputFormat::Json]);
+
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
                 cmd.rpc_url = configuration.rpc_url;
             }
 
+            // if the user has not specified execution limits, use the defaults
+            if cmd.memory_limit == 0 {
+                cmd.memory_limit = configuration.memory_limit;
+            }
+            if cmd.max_branch_depth == 0 {
+                cmd.max_branch_depth = configuration.max_branch_depth;
+            }
+            if cmd.max_call_depth == 0 {
+                cmd.max_call_depth = configuration.max_call_depth;
+            }
+            if cmd.max_loop_iterations == 0 {
+                cmd.max_loop_iterations = configuration.max_loop_iterations;
+            }
+
+            // if the user has not specified an ipfs gateway, use the default
+            if cmd.ipfs_gateway.as_str() == "" {
+                cmd.ipfs_gateway = configuration.ipfs_gateway;
+            }
+
+            // if the user has not specified a openai api key, use the default
+            if cmd.openai_api_key.as_str() == "" {
+                cmd.openai_api_key = configuration.openai_api_key;
+            }
+
+            // configured plugins run before those passed on the command line
+            cmd.plugins = configuration.plugins.into_iter().chain(cmd.plugins).collect();
+
             let result = decompile(cmd.clone()).await?;
 
             // write to file
-            let abi_output_path;
-            let solidity_output_path;
-            let yul_output_path;
-            if ADDRESS_REGEX.is_match(&cmd.target).unwrap() {
-                abi_output_path = format!("{}/{}/abi.json", &output_path, &cmd.target);
-                solidity_output_path = format!("{}/{}/decompiled.sol", &output_path, &cmd.target);
-                yul_output_path = format!("{}/{}/decompiled.yul", &output_path, &cmd.target);
-            } else {
-                abi_output_path = format!("{}/local/abi.json", &output_path);
-                solidity_output_path = format!("{}/local/decompiled.sol", &output_path);
-                yul_output_path = format!("{}/local/decompiled.yul", &output_path);
-            }
-
-            if let Some(abi) = result.abi {
-                // write the ABI to a file
-                write_file(
-                    &abi_output_path,
-                    &format!(
-                        "[{}]",
-                        abi.iter()
-                            .map(|x| {
-                                match x {
-                                    ABIStructure::Function(x) => {
-                                        serde_json::to_string_pretty(x).unwrap()
-                                    }
-                                    ABIStructure::Error(x) => {
-                                        serde_json::to_string_pretty(x).unwrap()
-                                    }
-                                    ABIStructure::Event(x) => {
-                                        serde_json::to_string_pretty(x).unwrap()
-                                    }
-                                }
-                            })
-                            .collect::<Vec<String>>()
-                            .join(",\n")
-                    ),
-                );
-            }
-            if let Some(source) = result.source {
-                if cmd.include_solidity {
-                    write_file(&solidity_output_path, &source);
-                } else {
-                    write_file(&yul_output_path, &source);
-                }
+            match format {
+                OutputFormat::Json => write_json(
+                    "decompile",
+                    decompile_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "decompiled.json"),
+                ),
+                _ => write_decompile_result(result, &cmd, &output.target_dir(&cmd.target)),
             }
         }
 
         Subcommands::Decode(mut cmd) => {
+            let format =
+                output_format(&args.format, "decode", &[OutputFormat::Text, OutputFormat::Json]);
+
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
                 cmd.rpc_url = configuration.rpc_url;
@@ -189,10 +453,47 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 cmd.openai_api_key = configuration.openai_api_key;
             }
 
-            // set cmd.verbose to 6
-            cmd.verbose = clap_verbosity_flag::Verbosity::new(5, 0);
+            // if the user has not specified an ipfs gateway, use the default
+            if cmd.ipfs_gateway.as_str() == "" {
+                cmd.ipfs_gateway = configuration.ipfs_gateway;
+            }
+
+            // set cmd.verbose to 6, unless the decoded calldata is printed to stdout as json
+            if !cmd.json {
+                cmd.verbose = clap_verbosity_flag::Verbosity::new(5, 0);
+            }
+
+            let result = decode_with_calldata(cmd.clone()).await;
+
+            // write the decoded candidates as json, if requested
+            if let (Ok(result), OutputFormat::Json) = (&result, format) {
+                write_json(
+                    "decode",
+                    decode_context(&result.matches, &cmd.target, Some(&result.calldata)),
+                    &output.artifact(&cmd.target, "decoded.json"),
+                );
+            }
+
+            // render the user's report template, if one was given
+            if let (Ok(result), false) = (&result, cmd.template.is_empty()) {
+                write_report(
+                    &cmd.template,
+                    &decode_context(&result.matches, &cmd.target, Some(&result.calldata)),
+                    &output.target_dir(&cmd.target),
+                );
+            }
+        }
+
+        Subcommands::Abi(mut cmd) => {
+            output_format(&args.format, "abi", &[OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
 
-            let _ = decode(cmd).await;
+            let result = abi(cmd.clone()).await?;
+            write_file(&output.artifact(&cmd.target, "abi.json"), &abi_json(&result.abi));
         }
 
         Subcommands::CFG(mut cmd) => {
@@ -201,19 +502,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 cmd.rpc_url = configuration.rpc_url;
             }
 
-            let cfg = cfg(cmd.clone()).await?;
+            // the cfg module's own `--format` selects the image format graphviz renders, so only
+            // the output formats are taken from it
+            let format = match cmd.format.parse::<OutputFormat>() {
+                Ok(_) => {
+                    cmd.format = String::new();
+                    output_format(&args.format, "cfg", &[OutputFormat::Text, OutputFormat::Json])
+                }
+                Err(_) => OutputFormat::Text,
+            };
 
-            // write to file
-            if ADDRESS_REGEX.is_match(&cmd.target).unwrap() {
-                output_path.push_str(&format!("/{}", &cmd.target));
-            } else {
-                output_path.push_str("/local");
+            let mut cfg = cfg(cmd.clone()).await?;
+
+            // link the graph to the target's decompiled source, if it was decompiled with
+            // `--block-comments`
+            if let Ok(source) =
+                std::fs::read_to_string(output.artifact(&cmd.target, "decompiled.sol"))
+            {
+                let (logger, _) = Logger::new("");
+                match cfg.link_source(&source) {
+                    0 => logger.info(
+                        "decompile with `--block-comments` to link the graph to 'decompiled.sol' .",
+                    ),
+                    linked => logger.info(&format!("linked {linked} blocks to 'decompiled.sol' .")),
+                }
             }
 
-            write_cfg_to_file(&cfg, &cmd, output_path)
+            // write to file
+            match format {
+                OutputFormat::Json => write_json(
+                    "cfg",
+                    cfg_context(&cfg, &cmd.target),
+                    &output.artifact(&cmd.target, "cfg.json"),
+                ),
+                _ => {
+                    write_cfg_to_file(&cfg.annotated_graph(), &cmd, output.target_dir(&cmd.target))
+                }
+            }
+            write_cfg_stats_to_file(&cfg.stats, &output.artifact(&cmd.target, "cfg.stats.json"));
         }
 
         Subcommands::Dump(mut cmd) => {
+            let format = output_format(
+                &args.format,
+                "dump",
+                &[OutputFormat::Csv, OutputFormat::Json, OutputFormat::StorageLayout],
+            );
+
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
                 cmd.rpc_url = configuration.rpc_url;
@@ -224,62 +559,390 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 cmd.transpose_api_key = configuration.transpose_api_key;
             }
 
+            // if the user has not specified a dune api key or backend, use the default
+            if cmd.dune_api_key.as_str() == "" {
+                cmd.dune_api_key = configuration.dune_api_key;
+            }
+            if cmd.backend.as_str() == "" {
+                cmd.backend = configuration.dump_backend;
+            }
+
+            // write the dump's own files alongside dump.csv
+            cmd.output = output.target_dir(&cmd.target);
+
             let result = dump(cmd.clone()).await?;
-            let mut lines = Vec::new();
+            match format {
+                OutputFormat::Json => write_json(
+                    "dump",
+                    dump_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "dump.json"),
+                ),
+                OutputFormat::StorageLayout => {
+                    write_file(
+                        &output.artifact(&cmd.target, "storage-layout.json"),
+                        &serde_json::to_string_pretty(&storage_layout(
+                            &result,
+                            "DecompiledContract",
+                        ))?,
+                    );
+                }
+                _ => {
+                    write_csv_to_file(&output.artifact(&cmd.target, "dump.csv"), &csv_rows(&result))
+                }
+            }
+        }
 
-            // write to file
-            if ADDRESS_REGEX.is_match(&cmd.target).unwrap() {
-                output_path.push_str(&format!("/{}/dump.csv", &cmd.target));
-            } else {
-                output_path.push_str("/local/dump.csv");
+        Subcommands::Snapshot(mut cmd) => {
+            let format = output_format(
+                &args.format,
+                "snapshot",
+                &[OutputFormat::Csv, OutputFormat::Json, OutputFormat::Manifest],
+            );
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
             }
 
-            // add header
-            lines.push(String::from("last_modified,alias,slot,decoded_type,value"));
+            // if the user has not specified execution limits, use the defaults
+            if cmd.memory_limit == 0 {
+                cmd.memory_limit = configuration.memory_limit;
+            }
+            if cmd.max_branch_depth == 0 {
+                cmd.max_branch_depth = configuration.max_branch_depth;
+            }
+            if cmd.max_call_depth == 0 {
+                cmd.max_call_depth = configuration.max_call_depth;
+            }
+            if cmd.max_loop_iterations == 0 {
+                cmd.max_loop_iterations = configuration.max_loop_iterations;
+            }
 
-            // add rows
-            for row in result {
-                lines.push(format!(
-                    "{},{},{},{},{}",
-                    row.last_modified, row.alias, row.slot, row.decoded_type, row.value
-                ));
+            let snapshot = snapshot(cmd.clone()).await?;
+            match format {
+                OutputFormat::Json => write_json(
+                    "snapshot",
+                    snapshot_context(&snapshot, &cmd.target),
+                    &output.artifact(&cmd.target, "snapshot.json"),
+                ),
+                OutputFormat::Manifest => write_json(
+                    "manifest",
+                    manifest_context(&snapshot, &cmd.target),
+                    &output.artifact(&cmd.target, "manifest.json"),
+                ),
+                _ => generate_and_write_contract_csv(
+                    &snapshot.snapshots,
+                    &snapshot.resolved_errors,
+                    &snapshot.resolved_events,
+                    &output.artifact(&cmd.target, "snapshot.csv"),
+                ),
             }
 
-            // write to file
-            write_lines_to_file(&output_path, lines);
+            // render the user's report template, if one was given
+            if !cmd.template.is_empty() {
+                write_report(
+                    &cmd.template,
+                    &snapshot_context(&snapshot, &cmd.target),
+                    &output.target_dir(&cmd.target),
+                );
+            }
         }
 
-        Subcommands::Snapshot(mut cmd) => {
+        Subcommands::Sig(cmd) => {
+            let format =
+                output_format(&args.format, "sig", &[OutputFormat::Text, OutputFormat::Json]);
+
+            let result = sig(cmd.clone()).await?;
+            if format == OutputFormat::Json {
+                write_json(
+                    "sig",
+                    sig_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "sig.json"),
+                );
+            }
+        }
+
+        Subcommands::Metadata(mut cmd) => {
+            let format =
+                output_format(&args.format, "metadata", &[OutputFormat::Text, OutputFormat::Json]);
+
             // if the user has not specified a rpc url, use the default
             if cmd.rpc_url.as_str() == "" {
                 cmd.rpc_url = configuration.rpc_url;
             }
 
-            // write to file
-            if ADDRESS_REGEX.is_match(&cmd.target).unwrap() {
-                output_path.push_str(&format!("/{}/snapshot.csv", &cmd.target));
-            } else {
-                output_path.push_str("/local/snapshot.csv");
-            }
-
-            let snapshot = snapshot(cmd).await?;
-            generate_and_write_contract_csv(
-                &snapshot.snapshots,
-                &snapshot.resolved_errors,
-                &snapshot.resolved_events,
-                &output_path,
-            )
+            let result = metadata(cmd.clone()).await?;
+            if format == OutputFormat::Json {
+                write_json(
+                    "metadata",
+                    metadata_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "metadata.json"),
+                );
+            }
+        }
+
+        Subcommands::Diff(mut cmd) => {
+            output_format(&args.format, "diff", &[OutputFormat::Text]);
+
+            // chains which aren't given inline are read from the configuration
+            cmd.chain_rpc_urls = configuration.chains;
+
+            _ = diff(cmd).await?;
+        }
+
+        Subcommands::Similar(mut cmd) => {
+            let format =
+                output_format(&args.format, "similar", &[OutputFormat::Text, OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            let result = similar(cmd.clone()).await?;
+            if format == OutputFormat::Json {
+                write_json(
+                    "similar",
+                    similar_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "similar.json"),
+                );
+            }
+        }
+
+        Subcommands::Profile(mut cmd) => {
+            let format =
+                output_format(&args.format, "profile", &[OutputFormat::Text, OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            let profile = profile(cmd.clone()).await?;
+
+            // write the folded stacks, which can be rendered with flamegraph tools
+            match format {
+                OutputFormat::Json => write_json(
+                    "profile",
+                    profile_context(&profile, &cmd.target),
+                    &output.artifact(&cmd.target, "profile.json"),
+                ),
+                _ => write_lines_to_file(
+                    &output.artifact(&cmd.target, "profile.folded"),
+                    profile.folded,
+                ),
+            }
         }
+
+        Subcommands::Trace(mut cmd) => {
+            output_format(&args.format, "trace", &[OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            let call_frame = trace(cmd.clone()).await?;
+
+            // write the call tree as json
+            write_json(
+                "trace",
+                trace_context(&call_frame, &cmd.target),
+                &output.artifact(&cmd.target, "trace.json"),
+            );
+        }
+
         Subcommands::Config(cmd) => {
-            config(cmd);
+            config(cmd).await;
         }
 
         Subcommands::Cache(cmd) => {
-            _ = cache(cmd);
+            // the cache's maximum size is persisted to the configuration
+            if let CacheSubcommands::Config(args) = &cmd.sub {
+                if args.key == "max_size" && !args.value.is_empty() {
+                    update_config("cache_max_size", &args.value);
+                }
+            }
+
+            if let Err(e) = cache(cmd) {
+                let (logger, _) = Logger::new("");
+                logger.error(&e.to_string());
+                std::process::exit(1);
+            }
         }
+
+        Subcommands::Bench(mut cmd) => {
+            let format =
+                output_format(&args.format, "bench", &[OutputFormat::Csv, OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            let results = bench(cmd).await?;
+            match format {
+                OutputFormat::Json => {
+                    write_json("bench", bench_context(&results), &output.file("bench.json"))
+                }
+                _ => {
+                    // add header
+                    let mut rows = vec![[
+                        "target",
+                        "module",
+                        "bytecode_size",
+                        "mean_ms",
+                        "min_ms",
+                        "max_ms",
+                        "peak_memory_kb",
+                        "selectors",
+                        "functions",
+                        "coverage",
+                        "error",
+                    ]
+                    .iter()
+                    .map(|column| column.to_string())
+                    .collect::<Vec<String>>()];
+
+                    // add rows
+                    for result in results {
+                        rows.push(vec![
+                            result.target.clone(),
+                            result.module.clone(),
+                            result.bytecode_size.to_string(),
+                            result.mean_ms.to_string(),
+                            result.min_ms.to_string(),
+                            result.max_ms.to_string(),
+                            result.peak_memory_kb.map(|kb| kb.to_string()).unwrap_or_default(),
+                            result.selectors.to_string(),
+                            result.functions.to_string(),
+                            format!("{:.4}", result.coverage()),
+                            result.error.unwrap_or_default(),
+                        ]);
+                    }
+
+                    // write to file
+                    write_csv_to_file(&output.file("bench.csv"), &rows);
+                }
+            }
+        }
+
+        Subcommands::Batch(mut cmd) => {
+            let format =
+                output_format(&args.format, "batch", &[OutputFormat::Csv, OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            // if the user has not specified execution limits, use the defaults
+            if cmd.memory_limit == 0 {
+                cmd.memory_limit = configuration.memory_limit;
+            }
+            if cmd.max_branch_depth == 0 {
+                cmd.max_branch_depth = configuration.max_branch_depth;
+            }
+            if cmd.max_call_depth == 0 {
+                cmd.max_call_depth = configuration.max_call_depth;
+            }
+            if cmd.max_loop_iterations == 0 {
+                cmd.max_loop_iterations = configuration.max_loop_iterations;
+            }
+
+            // chain prefixes are resolved through the configured chains
+            cmd.chain_rpc_urls = configuration.chains;
+
+            let results = batch(cmd).await?;
+            match format {
+                OutputFormat::Json => {
+                    write_json("batch", batch_context(&results), &output.file("batch.json"))
+                }
+                _ => {
+                    // add header
+                    let mut rows = vec![[
+                        "target",
+                        "chain",
+                        "bytecode_size",
+                        "duplicate_of",
+                        "selectors",
+                        "functions",
+                        "warnings",
+                        "duration_ms",
+                        "error",
+                    ]
+                    .iter()
+                    .map(|column| column.to_string())
+                    .collect::<Vec<String>>()];
+
+                    // add rows, with lists separated by semicolons
+                    for result in results {
+                        rows.push(vec![
+                            result.target.clone(),
+                            result.chain.clone().unwrap_or_default(),
+                            result.bytecode_size.to_string(),
+                            result.duplicate_of.clone().unwrap_or_default(),
+                            result.selectors.join(";"),
+                            result.functions.to_string(),
+                            result.warnings.join(";"),
+                            result.duration_ms.to_string(),
+                            result.error.unwrap_or_default(),
+                        ]);
+                    }
+
+                    // write to file
+                    write_csv_to_file(&output.file("batch.csv"), &rows);
+                }
+            }
+        }
+
+        Subcommands::Fuzz(mut cmd) => {
+            let format =
+                output_format(&args.format, "fuzz", &[OutputFormat::Text, OutputFormat::Json]);
+
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            let result = fuzz(cmd.clone()).await?;
+            if format == OutputFormat::Json {
+                write_json(
+                    "fuzz",
+                    fuzz_context(&result, &cmd.target),
+                    &output.artifact(&cmd.target, "fuzz.json"),
+                );
+            }
+        }
+
+        Subcommands::Doctor(mut cmd) => {
+            // if the user has not specified a rpc url, use the default
+            if cmd.rpc_url.as_str() == "" {
+                cmd.rpc_url = configuration.rpc_url;
+            }
+
+            if doctor(cmd).await?.failed() > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Subcommands::Update(cmd) => {
+            _ = update(cmd).await?;
+            return Ok(())
+        }
+    }
+
+    // the output of an interrupted command is partial, so exit with the conventional status
+    if is_interrupted() {
+        let (logger, _) = Logger::new("");
+        logger.warn("output is incomplete, since analysis was interrupted.");
+        std::process::exit(130);
     }
 
     // check if the version is up to date
+    if configuration.disable_update_check {
+        return Ok(())
+    }
     let remote_version = remote_version().await;
     let current_version = current_version();
 
@@ -287,9 +950,115 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let (logger, _) = Logger::new("TRACE");
         println!();
         logger.info("great news! An update is available!");
-        logger
-            .info(&format!("you can update now by running: `bifrost --version {remote_version}`"));
+        logger.info("you can update now by running: `heimdall update`");
+        logger.info("to silence this check, run: `heimdall config disable_update_check true`");
     }
 
     Ok(())
 }
+
+/// Parse the `--format` argument for a subcommand which supports the given formats, exiting if
+/// the subcommand can't write the requested format. Without `--format`, the subcommand's own
+/// format, the first it supports, is used.
+fn output_format(format: &str, subcommand: &str, supported: &[OutputFormat]) -> OutputFormat {
+    if format.is_empty() {
+        return supported[0]
+    }
+
+    let (logger, _) = Logger::new("");
+    match format.parse::<OutputFormat>() {
+        Ok(format) if supported.contains(&format) => format,
+        Ok(format) => {
+            logger.error(&format!(
+                "'{subcommand}' doesn't support '--format {format}', expected one of: {} .",
+                supported
+                    .iter()
+                    .map(|format| format.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+            std::process::exit(1)
+        }
+        Err(e) => {
+            logger.error(&format!("{e} ."));
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Write the decompiled ABI, source, audit trail, original sources, and report to the output
+/// directory, followed by those of each embedded contract in its own `child_<n>` directory, and
+/// those of a proxy's implementation in the `implementation` directory.
+fn write_decompile_result(result: DecompileResult, cmd: &DecompilerArgs, output_dir: &str) {
+    // render the user's report template, if one was given
+    if !cmd.template.is_empty() {
+        write_report(&cmd.template, &decompile_context(&result, &cmd.target), output_dir);
+    }
+
+    if let Some(abi) = &result.abi {
+        // write the ABI to a file
+        write_file(&format!("{output_dir}/abi.json"), &abi_json(abi));
+    }
+    if let Some(source) = &result.source {
+        if cmd.include_solidity {
+            write_file(&format!("{output_dir}/decompiled.sol"), source);
+
+            // write each function to its own file, for reviewing large contracts
+            if cmd.split_functions {
+                for (name, function) in split_functions(source) {
+                    write_file(&format!("{output_dir}/functions/{name}"), &function);
+                }
+            }
+        } else {
+            write_file(&format!("{output_dir}/decompiled.yul"), source);
+        }
+    }
+    if let Some(audit) = &result.audit {
+        write_file(
+            &format!("{output_dir}/audit.json"),
+            &serde_json::to_string_pretty(audit).unwrap_or_default(),
+        );
+    }
+    if let Some(sources) = &result.sources {
+        // write the original sources alongside the decompilation, making sure they can't
+        // escape the output directory
+        for (path, source) in sources {
+            let path = path
+                .split('/')
+                .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+                .collect::<Vec<&str>>()
+                .join("/");
+            write_file(&format!("{output_dir}/sources/{path}"), source);
+        }
+    }
+
+    for (index, child) in result.children.into_iter().enumerate() {
+        write_decompile_result(child, cmd, &format!("{output_dir}/child_{index}"));
+    }
+
+    // write the proxy's implementation, and the merged view of both at the proxy's address
+    if let Some(implementation) = result.implementation {
+        write_file(&format!("{output_dir}/merged.abi.json"), &abi_json(&implementation.merged_abi));
+        if let Some(source) = &implementation.merged_source {
+            write_file(&format!("{output_dir}/merged.sol"), source);
+        }
+        write_decompile_result(implementation.result, cmd, &format!("{output_dir}/implementation"));
+    }
+}
+
+/// Serialize the ABI as pretty-printed JSON.
+fn abi_json(abi: &[ABIStructure]) -> String {
+    format!(
+        "[{}]",
+        abi.iter()
+            .map(|x| {
+                match x {
+                    ABIStructure::Function(x) => serde_json::to_string_pretty(x).unwrap(),
+                    ABIStructure::Error(x) => serde_json::to_string_pretty(x).unwrap(),
+                    ABIStructure::Event(x) => serde_json::to_string_pretty(x).unwrap(),
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(",\n")
+    )
+}
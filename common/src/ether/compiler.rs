@@ -1,4 +1,93 @@
-use crate::utils::io::logging::Logger;
+use ethers::types::U256;
+
+use crate::{ether::metadata::decode_metadata, utils::io::logging::Logger};
+
+/// Code generation traits of the compiler which produced a contract, used to adjust the
+/// decompiler's heuristics to the compiler era.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompilerHeuristics {
+    /// solc >= 0.8.0 checks arithmetic for over/underflows by default, reverting with a
+    /// `Panic(uint256)` when a check fails.
+    pub checked_arithmetic: bool,
+    /// The initial value of the free memory pointer, which is 0x60 for solc < 0.4.22 and 0x80
+    /// otherwise.
+    pub free_memory_pointer: U256,
+    /// Whether solc >= 0.8.13 compiled the contract via the IR pipeline, which produces
+    /// differently shaped code. This is detected from the code itself, since it isn't recorded
+    /// in the metadata.
+    pub via_ir: bool,
+}
+
+impl Default for CompilerHeuristics {
+    fn default() -> Self {
+        Self { checked_arithmetic: false, free_memory_pointer: U256::from(0x80), via_ir: false }
+    }
+}
+
+/// Parses a version such as `0.8.19` into a (major, minor, patch) tuple, ignoring anything
+/// following the patch version.
+fn parse_version(version: &str) -> Option<(u32, u32, u32)> {
+    let mut parts = version.trim().split('.').map(|part| {
+        part.chars().take_while(|c| c.is_ascii_digit()).collect::<String>().parse::<u32>().ok()
+    });
+
+    Some((parts.next()??, parts.next()??, parts.next().flatten().unwrap_or(0)))
+}
+
+/// Whether the runtime bytecode is shaped like the output of solc's IR pipeline. The legacy
+/// pipeline jumps to the fallback when calldata is shorter than a selector (`LT`, `JUMPI`), while
+/// the IR pipeline negates the check and jumps past it (`LT`, `ISZERO`, `JUMPI`).
+///
+/// ```
+/// use heimdall_common::ether::compiler::is_via_ir;
+///
+/// assert!(is_via_ir("608060405260043610156010575f80fd5b"));
+/// assert!(!is_via_ir("6080604052600436106100345760003560e01c80"));
+/// ```
+pub fn is_via_ir(bytecode: &str) -> bool {
+    let bytecode = bytecode.trim_start_matches("0x");
+
+    // the calldata size check directly follows the free memory pointer's initialization
+    let dispatcher = bytecode.get(..64).unwrap_or(bytecode);
+    dispatcher.starts_with("6080604052") && dispatcher.contains("6004361015")
+}
+
+/// Returns the [`CompilerHeuristics`] for the given output of [`detect_compiler`] and the
+/// bytecode it was detected from. Version ranges such as `0.4.11-0.4.21` are handled
+/// conservatively, so a trait is only enabled if every version in the range shares it.
+///
+/// ```
+/// use heimdall_common::ether::compiler::compiler_heuristics;
+///
+/// let heuristics = compiler_heuristics("solc", "0.8.19", "608060405260043610156010575f80fd5b");
+/// assert!(heuristics.checked_arithmetic);
+/// assert!(heuristics.via_ir);
+/// ```
+pub fn compiler_heuristics(compiler: &str, version: &str, bytecode: &str) -> CompilerHeuristics {
+    let mut heuristics = CompilerHeuristics::default();
+    if compiler != "solc" {
+        return heuristics
+    }
+
+    // ranges are formatted as `lower-upper`, and open ranges as `lower+`
+    let (lower, upper) = match version.split_once('-') {
+        Some((lower, upper)) => (parse_version(lower), parse_version(upper)),
+        None if version.ends_with('+') => (parse_version(version), None),
+        None => (parse_version(version), parse_version(version)),
+    };
+
+    if let Some(lower) = lower {
+        heuristics.checked_arithmetic = lower >= (0, 8, 0);
+        heuristics.via_ir = lower >= (0, 8, 13) && is_via_ir(bytecode);
+    }
+    if let Some(upper) = upper {
+        if upper < (0, 4, 22) {
+            heuristics.free_memory_pointer = U256::from(0x60);
+        }
+    }
+
+    heuristics
+}
 
 // returns the compiler version used to compile the contract.
 // for example: (solc, 0.8.10) or (vyper, 0.2.16)
@@ -37,8 +126,20 @@ pub fn detect_compiler(bytecode: &str) -> (&'static str, String) {
         compiler = "solc";
     }
 
-    // check for cbor encoded compiler metadata
+    // check for a valid cbor encoded metadata trailer, which contains the exact compiler version
     // https://cbor.io
+    if let Some((metadata_compiler, metadata_version)) =
+        decode_metadata(bytecode).and_then(|metadata| metadata.compiler())
+    {
+        logger.debug_max(&format!(
+            "exact compiler version match found due to cbor encoded metadata: {}",
+            metadata_version
+        ));
+
+        return (metadata_compiler, metadata_version)
+    }
+
+    // fall back to searching for partial cbor encoded compiler metadata
     if compiler == "solc" {
         let compiler_version = bytecode.split("736f6c6343").collect::<Vec<&str>>();
 
@@ -102,7 +203,40 @@ pub fn detect_compiler(bytecode: &str) -> (&'static str, String) {
 
 #[cfg(test)]
 mod test_compiler {
-    use crate::ether::compiler::detect_compiler;
+    use ethers::types::U256;
+
+    use crate::ether::compiler::{compiler_heuristics, detect_compiler};
+
+    #[test]
+    fn test_compiler_heuristics() {
+        let via_ir = "608060405260043610156010575f80fd5b";
+        let legacy = "6080604052348015600f57600080fd5b506004361060285760003560e01c80";
+
+        let heuristics = compiler_heuristics("solc", "0.8.10", via_ir);
+        assert!(heuristics.checked_arithmetic);
+        assert!(!heuristics.via_ir);
+        assert_eq!(heuristics.free_memory_pointer, U256::from(0x80));
+
+        // recent versions are only treated as via-IR if the code is shaped like it
+        assert!(compiler_heuristics("solc", "0.8.19", via_ir).via_ir);
+        assert!(!compiler_heuristics("solc", "0.8.19", legacy).via_ir);
+
+        let heuristics = compiler_heuristics("solc", "0.4.11-0.4.21", "");
+        assert!(!heuristics.checked_arithmetic);
+        assert_eq!(heuristics.free_memory_pointer, U256::from(0x60));
+
+        let heuristics = compiler_heuristics("solc", "0.4.10-0.4.24", "");
+        assert_eq!(heuristics.free_memory_pointer, U256::from(0x80));
+
+        let heuristics = compiler_heuristics("solc", "0.4.22+", "");
+        assert!(!heuristics.checked_arithmetic);
+
+        let heuristics = compiler_heuristics("vyper", "0.3.4", "");
+        assert!(!heuristics.checked_arithmetic);
+
+        let heuristics = compiler_heuristics("solc", "unknown", "");
+        assert!(!heuristics.checked_arithmetic);
+    }
 
     #[test]
     fn test_detect_compiler_proxy_minimal() {
@@ -174,6 +308,13 @@ mod test_compiler {
         assert_eq!(detect_compiler(bytecode), expected_result);
     }
 
+    #[test]
+    fn test_detect_compiler_metadata_trailer() {
+        let bytecode = "6080604052a164736f6c6343000813000a";
+        let expected_result = ("solc", "0.8.19".to_string());
+        assert_eq!(detect_compiler(bytecode), expected_result);
+    }
+
     #[test]
     fn test_detect_compiler_vyper_metadata() {
         let bytecode = "7679706572833135353030";
@@ -0,0 +1,221 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Serialize;
+
+use crate::{
+    ether::{
+        evm::{core::vm::VM, ext::exec::VMTrace},
+        metadata::decode_metadata,
+        selectors::MAX_DISPATCHER_STEPS,
+    },
+    utils::strings::{decode_hex, encode_hex},
+};
+
+/// A basic block which symbolic execution never reached. Only blocks which can be entered are
+/// reported, i.e. those starting with a `JUMPDEST`, or following a conditional jump, while other
+/// unreached bytes are usually data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnreachableBlock {
+    /// The program counter of the block's first instruction.
+    pub start: usize,
+    /// The size of the block in bytes.
+    pub size: usize,
+}
+
+/// Code which can never run, or never succeed. Such code is usually left behind by the compiler,
+/// or a function which was disabled, but can hide a backdoor behind a check which appears dead.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DeadCodeReport {
+    /// The basic blocks which symbolic execution never reached, by program counter.
+    pub unreachable_blocks: Vec<UnreachableBlock>,
+    /// The selectors of the functions whose every path reverts, sorted.
+    pub reverting_selectors: Vec<String>,
+    /// Whether symbolic execution exceeded its execution limits, in which case some of the
+    /// unreachable blocks may be reachable after all.
+    pub partial: bool,
+}
+
+impl DeadCodeReport {
+    /// Human-readable warnings for the dead code which was found.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.reverting_selectors.is_empty() {
+            warnings.push(format!(
+                "{} function(s) always revert, so they may be disabled or decoys: {}.",
+                self.reverting_selectors.len(),
+                self.reverting_selectors
+                    .iter()
+                    .map(|selector| format!("0x{selector}"))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        if !self.unreachable_blocks.is_empty() {
+            warnings.push(format!(
+                "{} block(s) are never reached by symbolic execution{}: {}.",
+                self.unreachable_blocks.len(),
+                match self.partial {
+                    true => ", though analysis was partial",
+                    false => "",
+                },
+                self.unreachable_blocks
+                    .iter()
+                    .map(|block| format!("{:#x}", block.start))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        warnings
+    }
+}
+
+/// Collects the instructions reached, and the functions which always revert, across the
+/// symbolic execution traces of each of a contract's functions.
+#[derive(Debug, Clone, Default)]
+pub struct DeadCodeAnalysis {
+    reached: HashSet<usize>,
+    reverting_selectors: Vec<String>,
+    partial: bool,
+}
+
+impl DeadCodeAnalysis {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a function's trace, which is either reached through its selector, or is the fallback
+    /// function if no selector is given.
+    pub fn add_trace(&mut self, selector: Option<&str>, trace: &VMTrace) {
+        let mut traces = vec![trace];
+        while let Some(trace) = traces.pop() {
+            self.reached.extend(
+                trace
+                    .operations
+                    .iter()
+                    .map(|state| state.last_instruction.instruction.saturating_sub(1) as usize),
+            );
+            traces.extend(trace.children.iter());
+        }
+
+        self.partial |= trace.is_pruned();
+        if let (Some(selector), true) = (selector, trace.always_reverts()) {
+            self.reverting_selectors.push(selector.to_string());
+        }
+    }
+
+    /// Add the paths the dispatcher takes for each selector, and for calls without a recognized
+    /// selector, which the traces of each function skip since they begin at its entry point.
+    pub fn add_dispatcher(&mut self, evm: &VM, selectors: &HashMap<String, u128>) {
+        let unknown_selector = (0u32..)
+            .map(|selector| selector.to_be_bytes().to_vec())
+            .find(|selector| !selectors.contains_key(&encode_hex(selector.clone())))
+            .unwrap_or_default();
+        let calldata = selectors
+            .keys()
+            .map(|selector| decode_hex(selector).unwrap_or_default())
+            .chain([Vec::new(), unknown_selector]);
+
+        for calldata in calldata {
+            let mut vm = evm.clone();
+            vm.calldata = calldata;
+
+            let mut steps = 0;
+            while vm.bytecode.len() >= vm.instruction as usize &&
+                vm.exitcode == 255 &&
+                steps < MAX_DISPATCHER_STEPS
+            {
+                self.reached.insert(vm.instruction.saturating_sub(1) as usize);
+                vm.step();
+                steps += 1;
+            }
+        }
+    }
+
+    /// Build the report, finding each unreached block within the bytecode's code, which
+    /// excludes its metadata trailer.
+    pub fn report(&self, bytecode: &str) -> DeadCodeReport {
+        let bytes = decode_hex(bytecode.trim_start_matches("0x")).unwrap_or_default();
+        let metadata_size = decode_metadata(bytecode).map(|metadata| metadata.size).unwrap_or(0);
+        let code_size = bytes.len().saturating_sub(metadata_size);
+
+        let mut unreachable_blocks: Vec<UnreachableBlock> = Vec::new();
+        let mut current: Option<UnreachableBlock> = None;
+        let mut follows_jumpi = false;
+        let mut pc = 0;
+        while pc < code_size {
+            let opcode = bytes[pc];
+            let size = match opcode {
+                0x60..=0x7f => 1 + (opcode - 0x5f) as usize,
+                _ => 1,
+            }
+            .min(code_size - pc);
+
+            // blocks end at a reached instruction, or where the next block starts
+            if self.reached.contains(&pc) || opcode == 0x5b {
+                unreachable_blocks.extend(current.take());
+            }
+            if !self.reached.contains(&pc) {
+                match current.as_mut() {
+                    Some(block) => block.size += size,
+                    None if opcode == 0x5b || follows_jumpi => {
+                        current = Some(UnreachableBlock { start: pc, size })
+                    }
+                    None => {}
+                }
+            }
+
+            // as do halting and jumping instructions
+            if matches!(opcode, 0x00 | 0x56 | 0x57 | 0xf3 | 0xfd | 0xfe | 0xff) {
+                unreachable_blocks.extend(current.take());
+            }
+            follows_jumpi = opcode == 0x57;
+            pc += size;
+        }
+        unreachable_blocks.extend(current);
+
+        let mut reverting_selectors = self.reverting_selectors.clone();
+        reverting_selectors.sort();
+        DeadCodeReport { unreachable_blocks, reverting_selectors, partial: self.partial }
+    }
+}
+
+/// Symbolically execute each of the contract's functions, given their selectors and entry
+/// points, along with its fallback function, and report its dead code.
+pub fn find_dead_code(evm: &VM, selectors: &HashMap<String, u128>) -> DeadCodeReport {
+    let mut analysis = DeadCodeAnalysis::new();
+    analysis.add_dispatcher(evm, selectors);
+    for (selector, entry_point) in selectors {
+        let (trace, _) = evm.clone().symbolic_exec_selector(selector, *entry_point);
+        analysis.add_trace(Some(selector), &trace);
+    }
+
+    // without calldata, the dispatcher hands off to the fallback function
+    let (trace, _) = evm.symbolic_exec();
+    analysis.add_trace(None, &trace);
+
+    analysis.report(&encode_hex(evm.bytecode.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unreachable_blocks() {
+        // JUMPI to 0x08, whose fallthrough at 0x05 isn't reached, and a JUMPDEST at 0x0a which is
+        // never jumped to
+        let analysis = DeadCodeAnalysis {
+            reached: [0, 2, 4, 8, 9].into(),
+            reverting_selectors: vec![String::from("22222222"), String::from("11111111")],
+            partial: false,
+        };
+
+        let report = analysis.report("60016008576000005b005b6000fd");
+        assert_eq!(
+            report.unreachable_blocks,
+            vec![UnreachableBlock { start: 5, size: 3 }, UnreachableBlock { start: 10, size: 4 }]
+        );
+        assert_eq!(report.reverting_selectors, vec!["11111111", "22222222"]);
+        assert_eq!(report.warnings().len(), 2);
+    }
+}
@@ -0,0 +1,425 @@
+use crate::ether::evm::core::opcodes::Opcode;
+
+/// The magic bytes which start an EOF container, followed by its version.
+pub const EOF_MAGIC: [u8; 2] = [0xef, 0x00];
+
+const KIND_TYPES: u8 = 0x01;
+const KIND_CODE: u8 = 0x02;
+const KIND_CONTAINER: u8 = 0x03;
+const KIND_DATA: u8 = 0xff;
+const TERMINATOR: u8 = 0x00;
+
+/// The `outputs` of a code section which never returns to its caller.
+pub const NON_RETURNING: u8 = 0x80;
+
+/// The type of a code section, i.e. its stack inputs and outputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeSection {
+    pub inputs: u8,
+    /// The number of stack items the section returns, or [`NON_RETURNING`].
+    pub outputs: u8,
+    pub max_stack_height: u16,
+}
+
+/// A section of an EOF container, along with its offset within the container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Section {
+    pub offset: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// An EOF container, as specified by EIP-3540. Unlike legacy bytecode, its code and data are
+/// split into sections by a header, so code can be analyzed without guessing where it ends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EofContainer {
+    pub version: u8,
+    /// The type of each code section, in order.
+    pub types: Vec<TypeSection>,
+    pub code_sections: Vec<Section>,
+    /// The EOF containers which the code may create, i.e. with `EOFCREATE`.
+    pub container_sections: Vec<Section>,
+    /// The data section, which may be shorter than declared if the container is init code which
+    /// appends to it when deploying.
+    pub data: Section,
+    /// The size of the data section declared by the header.
+    pub declared_data_size: usize,
+}
+
+/// A decoded instruction within an EOF code section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EofInstruction {
+    /// The offset of the instruction within its code section.
+    pub offset: usize,
+    pub opcode: u8,
+    pub name: &'static str,
+    pub immediate: Vec<u8>,
+}
+
+/// Whether the bytecode is an EOF container, rather than legacy bytecode.
+pub fn is_eof(bytes: &[u8]) -> bool {
+    bytes.starts_with(&EOF_MAGIC)
+}
+
+/// The name and immediate size of the opcodes which EOF adds, or `None` for other opcodes.
+fn eof_opcode(opcode: u8) -> Option<(&'static str, usize)> {
+    match opcode {
+        0xd0 => Some(("DATALOAD", 0)),
+        0xd1 => Some(("DATALOADN", 2)),
+        0xd2 => Some(("DATASIZE", 0)),
+        0xd3 => Some(("DATACOPY", 0)),
+        0xe0 => Some(("RJUMP", 2)),
+        0xe1 => Some(("RJUMPI", 2)),
+        // RJUMPV's immediate is sized by its first byte, see `decode_code_section`
+        0xe2 => Some(("RJUMPV", 1)),
+        0xe3 => Some(("CALLF", 2)),
+        0xe4 => Some(("RETF", 0)),
+        0xe5 => Some(("JUMPF", 2)),
+        0xe6 => Some(("DUPN", 1)),
+        0xe7 => Some(("SWAPN", 1)),
+        0xe8 => Some(("EXCHANGE", 1)),
+        0xec => Some(("EOFCREATE", 1)),
+        0xee => Some(("RETURNCONTRACT", 1)),
+        0xf7 => Some(("RETURNDATALOAD", 0)),
+        0xf8 => Some(("EXTCALL", 0)),
+        0xf9 => Some(("EXTDELEGATECALL", 0)),
+        0xfb => Some(("EXTSTATICCALL", 0)),
+        _ => None,
+    }
+}
+
+/// Legacy opcodes which are invalid within EOF code, since they observe or depend on code
+/// layout, gas, or the legacy call model.
+const DEPRECATED_OPCODES: [u8; 16] = [
+    0x38, 0x39, 0x3b, 0x3c, 0x3f, 0x56, 0x57, 0x58, 0x5a, 0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xfa, 0xff,
+];
+
+/// Opcodes which end a code section's execution, one of which must end each code section.
+const TERMINATING_OPCODES: [u8; 8] = [0x00, 0xf3, 0xfd, 0xfe, 0xe0, 0xe4, 0xe5, 0xee];
+
+/// A reader over the bytes of a container, which fails once they run out.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl Reader<'_> {
+    fn take(&mut self, size: usize, what: &str) -> Result<&[u8], String> {
+        let bytes =
+            self.bytes.get(self.offset..self.offset.saturating_add(size)).ok_or_else(|| {
+                format!("container is truncated, expected {what} at offset {}", self.offset)
+            })?;
+        self.offset += size;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self, what: &str) -> Result<u8, String> {
+        Ok(self.take(1, what)?[0])
+    }
+
+    fn u16(&mut self, what: &str) -> Result<usize, String> {
+        let bytes = self.take(2, what)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+
+    fn u32(&mut self, what: &str) -> Result<usize, String> {
+        let bytes = self.take(4, what)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+    }
+
+    /// Read a section kind, failing if it isn't the expected one.
+    fn kind(&mut self, kind: u8, what: &str) -> Result<(), String> {
+        let offset = self.offset;
+        match self.u8(what)? {
+            found if found == kind => Ok(()),
+            found => Err(format!(
+                "expected {what} (0x{kind:02x}) at offset {offset}, found 0x{found:02x}"
+            )),
+        }
+    }
+}
+
+/// Parse an EOF container, validating its header and the structure of its sections, per
+/// EIP-3540. Code sections aren't validated, see [`decode_code_section`].
+///
+/// ```
+/// use heimdall_common::{ether::eof::parse_eof, utils::strings::decode_hex};
+///
+/// // a single code section containing `STOP`, and two bytes of data
+/// let container =
+///     parse_eof(&decode_hex("ef00010100040200010001ff0002000080000000aabb").unwrap()).unwrap();
+/// assert_eq!(container.code_sections[0].bytes, vec![0x00]);
+/// assert_eq!(container.data.bytes, vec![0xaa, 0xbb]);
+/// ```
+pub fn parse_eof(bytes: &[u8]) -> Result<EofContainer, String> {
+    let mut reader = Reader { bytes, offset: 0 };
+    if reader.take(2, "the EOF magic")? != EOF_MAGIC {
+        return Err(String::from("bytecode doesn't start with the EOF magic 0xef00"))
+    }
+    let version = reader.u8("a version")?;
+    if version != 1 {
+        return Err(format!("unsupported EOF version {version}"))
+    }
+
+    // the header lists the size of each section
+    reader.kind(KIND_TYPES, "the type section header")?;
+    let types_size = reader.u16("the type section size")?;
+
+    reader.kind(KIND_CODE, "the code section header")?;
+    let code_count = reader.u16("the number of code sections")?;
+    if code_count == 0 || code_count > 1024 {
+        return Err(format!("invalid number of code sections {code_count}"))
+    }
+    let code_sizes = (0..code_count)
+        .map(|_| reader.u16("a code section size"))
+        .collect::<Result<Vec<usize>, String>>()?;
+    if code_sizes.contains(&0) {
+        return Err(String::from("code sections can't be empty"))
+    }
+    if types_size != code_count * 4 {
+        return Err(format!(
+            "type section is {types_size} bytes, but {code_count} code sections need {}",
+            code_count * 4
+        ))
+    }
+
+    let mut container_sizes = Vec::new();
+    if reader.bytes.get(reader.offset) == Some(&KIND_CONTAINER) {
+        reader.offset += 1;
+        let container_count = reader.u16("the number of container sections")?;
+        if container_count == 0 || container_count > 256 {
+            return Err(format!("invalid number of container sections {container_count}"))
+        }
+        container_sizes = (0..container_count)
+            .map(|_| reader.u32("a container section size"))
+            .collect::<Result<Vec<usize>, String>>()?;
+        if container_sizes.contains(&0) {
+            return Err(String::from("container sections can't be empty"))
+        }
+    }
+
+    reader.kind(KIND_DATA, "the data section header")?;
+    let declared_data_size = reader.u16("the data section size")?;
+    reader.kind(TERMINATOR, "the header terminator")?;
+
+    // followed by the sections themselves, in the same order
+    let types = (0..code_count)
+        .map(|_| {
+            let inputs = reader.u8("a code section's inputs")?;
+            let outputs = reader.u8("a code section's outputs")?;
+            let max_stack_height = reader.u16("a code section's max stack height")? as u16;
+            if inputs > 0x7f || (outputs > 0x7f && outputs != NON_RETURNING) {
+                return Err(format!(
+                    "invalid code section type ({inputs} inputs, {outputs} outputs)"
+                ))
+            }
+            if max_stack_height > 1023 {
+                return Err(format!("max stack height {max_stack_height} exceeds 1023"))
+            }
+            Ok(TypeSection { inputs, outputs, max_stack_height })
+        })
+        .collect::<Result<Vec<TypeSection>, String>>()?;
+    if types[0].inputs != 0 || types[0].outputs != NON_RETURNING {
+        return Err(String::from("the first code section must take no inputs and never return"))
+    }
+
+    let mut read_sections = |sizes: &[usize], what: &str| {
+        sizes
+            .iter()
+            .map(|size| {
+                let offset = reader.offset;
+                Ok(Section { offset, bytes: reader.take(*size, what)?.to_vec() })
+            })
+            .collect::<Result<Vec<Section>, String>>()
+    };
+    let code_sections = read_sections(&code_sizes, "a code section")?;
+    let container_sections = read_sections(&container_sizes, "a container section")?;
+
+    // the data section may be truncated, but nothing may follow it
+    let data_offset = reader.offset;
+    let data = bytes[data_offset..].to_vec();
+    if data.len() > declared_data_size {
+        return Err(format!(
+            "container has {} bytes after its {declared_data_size} byte data section",
+            data.len() - declared_data_size
+        ))
+    }
+
+    Ok(EofContainer {
+        version,
+        types,
+        code_sections,
+        container_sections,
+        data: Section { offset: data_offset, bytes: data },
+        declared_data_size,
+    })
+}
+
+impl EofInstruction {
+    /// Whether the instruction ends its code section's execution.
+    pub fn is_terminating(&self) -> bool {
+        TERMINATING_OPCODES.contains(&self.opcode)
+    }
+
+    /// The code section which a `CALLF` or `JUMPF` instruction transfers control to.
+    pub fn section_target(&self) -> Option<usize> {
+        match self.opcode {
+            0xe3 | 0xe5 => {
+                Some(u16::from_be_bytes([self.immediate[0], self.immediate[1]]) as usize)
+            }
+            _ => None,
+        }
+    }
+
+    /// The offsets within the code section which a relative jump may continue at, i.e. its
+    /// targets, followed by the next instruction if the jump is conditional.
+    pub fn jump_targets(&self) -> Vec<usize> {
+        let next = (self.offset + 1 + self.immediate.len()) as isize;
+        let relative = |bytes: &[u8]| i16::from_be_bytes([bytes[0], bytes[1]]) as isize;
+        let targets = match self.opcode {
+            0xe0 | 0xe1 => vec![next + relative(&self.immediate)],
+            0xe2 => self.immediate[1..].chunks(2).map(|bytes| next + relative(bytes)).collect(),
+            _ => return Vec::new(),
+        };
+
+        // targets before the section's start are invalid, so map them out of range
+        let mut targets = targets
+            .into_iter()
+            .map(|target| usize::try_from(target).unwrap_or(usize::MAX))
+            .collect::<Vec<_>>();
+        if self.opcode != 0xe0 {
+            targets.push(next as usize);
+        }
+        targets
+    }
+}
+
+/// Decode the instructions of a code section of the given container, validating them per
+/// EIP-3670 and EIP-4200: each opcode must be defined and valid within EOF, immediates can't be
+/// truncated, the section must end with a terminating instruction, relative jumps must target
+/// instructions within the section, and calls must target existing code sections.
+pub fn decode_code_section(
+    container: &EofContainer,
+    section: usize,
+) -> Result<Vec<EofInstruction>, String> {
+    let code = &container.code_sections[section].bytes;
+
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while offset < code.len() {
+        let opcode = code[offset];
+        let (name, immediate_size) = match eof_opcode(opcode) {
+            Some((name, _)) if opcode == 0xe2 => {
+                // RJUMPV's first immediate byte is the maximum index into its jump table
+                match code.get(offset + 1) {
+                    Some(max_index) => (name, 1 + 2 * (*max_index as usize + 1)),
+                    None => (name, 1),
+                }
+            }
+            Some(eof_opcode) => eof_opcode,
+            None => {
+                let legacy = Opcode::new(opcode);
+                if legacy.name == "unknown" || DEPRECATED_OPCODES.contains(&opcode) {
+                    return Err(format!(
+                        "code section {section} has an invalid opcode 0x{opcode:02x} at offset {offset}"
+                    ))
+                }
+                let immediate_size = match opcode {
+                    0x60..=0x7f => (opcode - 0x5f) as usize,
+                    _ => 0,
+                };
+                (legacy.name, immediate_size)
+            }
+        };
+
+        let immediate = code.get(offset + 1..offset + 1 + immediate_size).ok_or_else(|| {
+            format!("code section {section} has a truncated {name} immediate at offset {offset}")
+        })?;
+        instructions.push(EofInstruction { offset, opcode, name, immediate: immediate.to_vec() });
+        offset += 1 + immediate_size;
+    }
+
+    match instructions.last() {
+        Some(last) if last.is_terminating() => {}
+        _ => {
+            return Err(format!("code section {section} doesn't end with a terminating instruction"))
+        }
+    }
+
+    for instruction in &instructions {
+        for target in instruction.jump_targets() {
+            if !instructions.iter().any(|other| other.offset == target) {
+                return Err(format!(
+                    "code section {section} has a {} at offset {} to invalid offset {target}",
+                    instruction.name, instruction.offset
+                ))
+            }
+        }
+        if let Some(target) = instruction.section_target() {
+            if target >= container.code_sections.len() {
+                return Err(format!(
+                    "code section {section} has a {} at offset {} to missing code section {target}",
+                    instruction.name, instruction.offset
+                ))
+            }
+        }
+    }
+
+    Ok(instructions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::strings::decode_hex;
+
+    #[test]
+    fn test_parse_eof() {
+        // two code sections, where the first calls the second, and two bytes of data
+        let container = parse_eof(
+            &decode_hex(
+                "ef00010100080200020004000303000100000014ff000200\
+                 0080000000010001e30001006001e4\
+                 ef00010100040200010001ff00000000800000fe\
+                 aabb",
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(container.types.len(), 2);
+        assert_eq!(container.types[1], TypeSection { inputs: 0, outputs: 1, max_stack_height: 1 });
+        assert_eq!(container.code_sections[0].offset, 32);
+        assert_eq!(container.container_sections[0].bytes.len(), 0x14);
+        assert_eq!(container.data.bytes, vec![0xaa, 0xbb]);
+
+        let entry = decode_code_section(&container, 0).unwrap();
+        assert_eq!(entry[0].name, "CALLF");
+        assert_eq!(entry[0].immediate, vec![0x00, 0x01]);
+        assert!(decode_code_section(&container, 1).is_ok());
+        assert!(parse_eof(&container.container_sections[0].bytes).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_eof() {
+        let invalid = |hex: &str| parse_eof(&decode_hex(hex).unwrap()).unwrap_err();
+
+        assert!(invalid("ef0002").contains("version"));
+        assert!(invalid("ef000101000402000100010400000000800000").contains("data section header"));
+        assert!(invalid("ef00010100040200010001ff00000000800000").contains("truncated"));
+        assert!(invalid("ef00010100040200010001ff0000000000000000").contains("never return"));
+        assert!(invalid("ef00010100040200010001ff000000008000000000").contains("after"));
+
+        // code sections must be valid, and can't jump into an immediate
+        let code = |hex: &str| {
+            let header = format!("ef00010100040200010{:03x}ff0000000080000a", hex.len() / 2);
+            let container = parse_eof(&decode_hex(&format!("{header}{hex}")).unwrap()).unwrap();
+            decode_code_section(&container, 0)
+        };
+        assert!(code("6001").unwrap_err().contains("terminating"));
+        assert!(code("5600").unwrap_err().contains("invalid opcode 0x56"));
+        assert!(code("61ff").unwrap_err().contains("truncated"));
+        assert!(code("6001e1fffe00").unwrap_err().contains("invalid offset"));
+        assert_eq!(code("6001e1000100fe").unwrap()[1].jump_targets(), vec![6, 5]);
+        assert_eq!(code("6000e2010000000100fe00").unwrap()[1].jump_targets(), vec![8, 9, 8]);
+    }
+}
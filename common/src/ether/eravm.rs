@@ -0,0 +1,251 @@
+use std::{collections::BTreeSet, fmt};
+
+use ethers::types::U256;
+
+/// The size of an EraVM word. Bytecode is a whole, odd number of words.
+pub const WORD_SIZE: usize = 32;
+
+/// The size of an EraVM instruction, four of which are packed into each word.
+pub const INSTRUCTION_SIZE: usize = 8;
+
+/// The maximum number of words in EraVM bytecode, since the program counter is 16 bits.
+pub const MAX_WORDS: usize = 1 << 16;
+
+/// The condition under which an EraVM instruction executes, based on the flags set by previous
+/// instructions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Always,
+    Gt,
+    Lt,
+    Eq,
+    Ge,
+    Le,
+    Ne,
+    GtOrLt,
+}
+
+impl Condition {
+    fn from_bits(bits: u8) -> Self {
+        match bits & 0x7 {
+            0 => Condition::Always,
+            1 => Condition::Gt,
+            2 => Condition::Lt,
+            3 => Condition::Eq,
+            4 => Condition::Ge,
+            5 => Condition::Le,
+            6 => Condition::Ne,
+            _ => Condition::GtOrLt,
+        }
+    }
+
+    /// The suffix of an instruction with this condition in EraVM assembly, i.e. `.gt`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Condition::Always => "",
+            Condition::Gt => ".gt",
+            Condition::Lt => ".lt",
+            Condition::Eq => ".eq",
+            Condition::Ge => ".ge",
+            Condition::Le => ".le",
+            Condition::Ne => ".ne",
+            Condition::GtOrLt => ".gt_or_lt",
+        }
+    }
+}
+
+/// A decoded EraVM instruction. Each instruction is a big-endian 64-bit integer holding, from
+/// the least significant bits up, an 11-bit opcode, its 3-bit condition, two source and two
+/// destination registers of 4 bits each, and two 16-bit immediates.
+///
+/// The opcode is an index into the VM's table of opcode variants, which changes between VM
+/// versions, so it's kept as-is rather than named.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EraInstruction {
+    /// The index of the instruction, which the program counter addresses.
+    pub index: usize,
+    pub opcode: u16,
+    pub condition: Condition,
+    pub src0: u8,
+    pub src1: u8,
+    pub dst0: u8,
+    pub dst1: u8,
+    pub imm0: u16,
+    pub imm1: u16,
+}
+
+impl EraInstruction {
+    /// Decode the instruction at the given index from its 8 bytes.
+    pub fn decode(index: usize, bytes: &[u8; INSTRUCTION_SIZE]) -> Self {
+        let raw = u64::from_be_bytes(*bytes);
+        Self {
+            index,
+            opcode: (raw & 0x7ff) as u16,
+            condition: Condition::from_bits((raw >> 13) as u8),
+            src0: ((raw >> 16) & 0xf) as u8,
+            src1: ((raw >> 20) & 0xf) as u8,
+            dst0: ((raw >> 24) & 0xf) as u8,
+            dst1: ((raw >> 28) & 0xf) as u8,
+            imm0: (raw >> 32) as u16,
+            imm1: (raw >> 48) as u16,
+        }
+    }
+}
+
+impl fmt::Display for EraInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "op_{:03x}{} r{}, r{}, r{}, r{}, {:#06x}, {:#06x}",
+            self.opcode,
+            self.condition.suffix(),
+            self.src0,
+            self.src1,
+            self.dst0,
+            self.dst1,
+            self.imm0,
+            self.imm1
+        )
+    }
+}
+
+/// Validate the layout of EraVM bytecode, which must be a whole, odd number of words, and
+/// addressable by a 16-bit program counter.
+///
+/// ```
+/// use heimdall_common::ether::eravm::validate_eravm_bytecode;
+///
+/// assert!(validate_eravm_bytecode(&[0u8; 32]).is_ok());
+/// assert!(validate_eravm_bytecode(&[0u8; 64]).is_err());
+/// ```
+pub fn validate_eravm_bytecode(bytecode: &[u8]) -> Result<(), String> {
+    if bytecode.is_empty() || !bytecode.len().is_multiple_of(WORD_SIZE) {
+        return Err(format!(
+            "bytecode is {} bytes, which isn't a whole number of words",
+            bytecode.len()
+        ))
+    }
+
+    let words = bytecode.len() / WORD_SIZE;
+    if words.is_multiple_of(2) {
+        return Err(format!("bytecode has an even number of words ({words})"))
+    }
+    if words >= MAX_WORDS {
+        return Err(format!("bytecode has {words} words, more than the program counter can address"))
+    }
+
+    Ok(())
+}
+
+/// Decode each instruction of EraVM bytecode, after validating its layout. Since instructions
+/// are followed by the contract's constants without any marker, constants are decoded as
+/// instructions too.
+pub fn decode_eravm_bytecode(bytecode: &[u8]) -> Result<Vec<EraInstruction>, String> {
+    validate_eravm_bytecode(bytecode)?;
+
+    Ok(bytecode
+        .chunks_exact(INSTRUCTION_SIZE)
+        .enumerate()
+        .map(|(index, bytes)| {
+            EraInstruction::decode(index, bytes.try_into().expect("chunks are instruction-sized"))
+        })
+        .collect())
+}
+
+/// Find the function selectors which EraVM bytecode likely dispatches on. Since immediates are
+/// only 16 bits, the dispatcher compares the calldata's selector against constants, which are
+/// full words after the instructions. Words holding a value too large to be an immediate, but
+/// which fit in 4 bytes, are candidate selectors, unless they're a mask such as `0xffffffff` or
+/// `0xffffffe0`.
+///
+/// ```
+/// use heimdall_common::ether::eravm::candidate_selectors;
+///
+/// let mut bytecode = vec![0u8; 96];
+/// bytecode[60..64].copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+///
+/// assert_eq!(candidate_selectors(&bytecode).into_iter().collect::<Vec<_>>(), vec![0xa9059cbb]);
+/// ```
+pub fn candidate_selectors(bytecode: &[u8]) -> BTreeSet<u32> {
+    bytecode
+        .chunks_exact(WORD_SIZE)
+        .map(U256::from_big_endian)
+        .filter(|word| word.bits() > 16 && word.bits() <= 32)
+        .map(|word| word.as_u32())
+        .filter(|value| !is_mask(*value))
+        .collect()
+}
+
+/// Whether the value's set bits form a single contiguous run, as masks and powers of two do.
+/// Real selectors are hashes, so they essentially never have this shape.
+fn is_mask(value: u32) -> bool {
+    if value == 0 {
+        return false;
+    }
+
+    let shifted = value >> value.trailing_zeros();
+    shifted & shifted.wrapping_add(1) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_instruction() {
+        // imm1 = 0x0020, imm0 = 0x0004, dst1 = r0, dst0 = r3, src1 = r2, src0 = r1,
+        // condition = gt, opcode = 0x1a3
+        let instruction =
+            EraInstruction::decode(5, &[0x00, 0x20, 0x00, 0x04, 0x03, 0x21, 0x21, 0xa3]);
+
+        assert_eq!(instruction.index, 5);
+        assert_eq!(instruction.opcode, 0x1a3);
+        assert_eq!(instruction.condition, Condition::Gt);
+        assert_eq!((instruction.src0, instruction.src1), (1, 2));
+        assert_eq!((instruction.dst0, instruction.dst1), (3, 0));
+        assert_eq!((instruction.imm0, instruction.imm1), (0x4, 0x20));
+        assert_eq!(instruction.to_string(), "op_1a3.gt r1, r2, r3, r0, 0x0004, 0x0020");
+    }
+
+    #[test]
+    fn test_decode_eravm_bytecode() {
+        assert_eq!(decode_eravm_bytecode(&[0u8; 96]).unwrap().len(), 12);
+        assert!(decode_eravm_bytecode(&[0u8; 33]).unwrap_err().contains("whole number"));
+        assert!(decode_eravm_bytecode(&[]).is_err());
+        assert!(decode_eravm_bytecode(&vec![0u8; MAX_WORDS * WORD_SIZE + WORD_SIZE])
+            .unwrap_err()
+            .contains("program counter"));
+    }
+
+    #[test]
+    fn test_candidate_selectors() {
+        let mut bytecode = vec![0u8; 128];
+
+        // an immediate-sized constant, a selector, and a word which is too large
+        bytecode[30..32].copy_from_slice(&[0xff, 0xff]);
+        bytecode[60..64].copy_from_slice(&[0x09, 0x5e, 0xa7, 0xb3]);
+        bytecode[91..96].copy_from_slice(&[0x01, 0x00, 0x00, 0x00, 0x00]);
+
+        assert_eq!(
+            candidate_selectors(&bytecode).into_iter().collect::<Vec<_>>(),
+            vec![0x095ea7b3]
+        );
+    }
+
+    #[test]
+    fn test_candidate_selectors_skips_masks() {
+        let mut bytecode = vec![0u8; 192];
+
+        // masks and a power of two, around a single selector
+        bytecode[28..32].copy_from_slice(&[0xff, 0xff, 0xff, 0xff]);
+        bytecode[60..64].copy_from_slice(&[0xff, 0xff, 0xff, 0xe0]);
+        bytecode[92..96].copy_from_slice(&[0xff, 0xff, 0x00, 0x00]);
+        bytecode[124..128].copy_from_slice(&[0x00, 0x10, 0x00, 0x00]);
+        bytecode[156..160].copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+
+        assert_eq!(
+            candidate_selectors(&bytecode).into_iter().collect::<Vec<_>>(),
+            vec![0xa9059cbb]
+        );
+    }
+}
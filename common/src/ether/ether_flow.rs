@@ -0,0 +1,131 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::ether::{
+    evm::{core::opcodes::WrappedOpcode, ext::exec::VMTrace},
+    lexers::cleanup::Cleanup,
+};
+
+/// Where the ether sent by an external call comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EtherSource {
+    /// The value the function was called with, which is forwarded on.
+    CallValue,
+    /// The contract's entire balance.
+    Balance,
+    /// Any other amount, such as an argument or a stored value.
+    Other,
+}
+
+/// Find where the value sent by an external call comes from.
+///
+/// ```
+/// use heimdall_common::ether::{
+///     ether_flow::{ether_source, EtherSource},
+///     evm::core::opcodes::WrappedOpcode,
+/// };
+///
+/// assert_eq!(ether_source(&WrappedOpcode::new(0x34, vec![])), EtherSource::CallValue);
+/// assert_eq!(ether_source(&WrappedOpcode::new(0x47, vec![])), EtherSource::Balance);
+/// ```
+pub fn ether_source(value: &WrappedOpcode) -> EtherSource {
+    let value = value.to_string();
+    if value.contains("CALLVALUE") {
+        EtherSource::CallValue
+    } else if value.contains("SELFBALANCE") || value.contains("BALANCE(ADDRESS") {
+        EtherSource::Balance
+    } else {
+        EtherSource::Other
+    }
+}
+
+/// How ether flows through a function: who it forwards the call value to, who it sends the
+/// contract's balance to, and who it sends any other ether to. Whether the function can receive
+/// ether is its payability, which is found separately.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct EtherFlow {
+    /// The recipients which the function forwards the call value to.
+    pub forwards_to: BTreeSet<String>,
+    /// The recipients which the function sends the contract's balance to, including the
+    /// beneficiaries of a `selfdestruct`.
+    pub sends_balance_to: BTreeSet<String>,
+    /// The recipients of any other ether the function sends.
+    pub sends_to: BTreeSet<String>,
+}
+
+impl EtherFlow {
+    /// Find the ether sent by each `CALL`, `CALLCODE`, and `SELFDESTRUCT` on any path through
+    /// the function's trace.
+    pub fn from_trace(trace: &VMTrace) -> Self {
+        let mut flow = Self::default();
+        let mut traces = vec![trace];
+        while let Some(trace) = traces.pop() {
+            for operation in &trace.operations {
+                let instruction = &operation.last_instruction;
+                let (recipient, source) = match instruction.opcode {
+                    0xf1 | 0xf2 if instruction.input_operations.len() > 2 => {
+                        // calls with a constant zero value send no ether
+                        if instruction.inputs[2].is_zero() &&
+                            instruction.input_operations[2].opcode.name.starts_with("PUSH")
+                        {
+                            continue
+                        }
+                        (
+                            &instruction.input_operations[1],
+                            ether_source(&instruction.input_operations[2]),
+                        )
+                    }
+                    0xff if !instruction.input_operations.is_empty() => {
+                        (&instruction.input_operations[0], EtherSource::Balance)
+                    }
+                    _ => continue,
+                };
+
+                let recipient = recipient.solidify().cleanup();
+                match source {
+                    EtherSource::CallValue => flow.forwards_to.insert(recipient),
+                    EtherSource::Balance => flow.sends_balance_to.insert(recipient),
+                    EtherSource::Other => flow.sends_to.insert(recipient),
+                };
+            }
+            traces.extend(trace.children.iter());
+        }
+
+        flow
+    }
+
+    /// Whether the function sends ether on any path.
+    pub fn sends_ether(&self) -> bool {
+        !self.forwards_to.is_empty() ||
+            !self.sends_balance_to.is_empty() ||
+            !self.sends_to.is_empty()
+    }
+
+    /// Summarize the ether flow, given whether the function is payable, i.e. `receives ether,
+    /// forwards msg.value to arg0`.
+    pub fn summary(&self, payable: bool) -> String {
+        let join = |recipients: &BTreeSet<String>| {
+            recipients.iter().cloned().collect::<Vec<String>>().join(", ")
+        };
+
+        let mut parts = Vec::new();
+        if payable {
+            parts.push(String::from("receives ether"));
+        }
+        if !self.forwards_to.is_empty() {
+            parts.push(format!("forwards msg.value to {}", join(&self.forwards_to)));
+        }
+        if !self.sends_balance_to.is_empty() {
+            parts.push(format!("sends the contract's balance to {}", join(&self.sends_balance_to)));
+        }
+        if !self.sends_to.is_empty() {
+            parts.push(format!("sends ether to {}", join(&self.sends_to)));
+        }
+
+        match parts.is_empty() {
+            true => String::from("doesn't receive or send ether"),
+            false => parts.join(", "),
+        }
+    }
+}
@@ -1,14 +1,94 @@
+use std::{
+    collections::BTreeMap,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// The default number of bytes a [`Memory`] may hold.
+pub const DEFAULT_MAX_MEMORY_SIZE: usize = 1 << 24;
+
+/// The default number of bytes a single [`Memory::read`] may return.
+pub const DEFAULT_MAX_MEMORY_ACCESS: usize = 1 << 16;
+
+static MAX_MEMORY_SIZE: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_MEMORY_SIZE);
+static MAX_MEMORY_ACCESS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_MEMORY_ACCESS);
+
+/// Set the limits every [`Memory`] created with [`Memory::new`] uses, from the configuration. 0
+/// keeps the default for that limit.
+///
+/// ```
+/// use heimdall_common::ether::evm::core::memory::{
+///     set_memory_limits, Memory, DEFAULT_MAX_MEMORY_ACCESS,
+/// };
+///
+/// set_memory_limits(1 << 20, 0);
+/// let memory = Memory::new();
+/// assert_eq!(memory.max_size, 1 << 20);
+/// assert_eq!(memory.max_access, DEFAULT_MAX_MEMORY_ACCESS);
+/// ```
+pub fn set_memory_limits(max_size: usize, max_access: usize) {
+    MAX_MEMORY_SIZE.store(
+        match max_size {
+            0 => DEFAULT_MAX_MEMORY_SIZE,
+            max_size => max_size,
+        },
+        Ordering::Relaxed,
+    );
+    MAX_MEMORY_ACCESS.store(
+        match max_access {
+            0 => DEFAULT_MAX_MEMORY_ACCESS,
+            max_access => max_access,
+        },
+        Ordering::Relaxed,
+    );
+}
+
 /// The [`Memory`] struct represents the memory of an EVM.
+///
+/// Memory is sparse, holding only the byte ranges which were written as disjoint segments, keyed
+/// by their offset. Unwritten bytes read as zero, so contracts which write to huge offsets or copy
+/// large ranges don't allocate the memory in between.
 #[derive(Clone, Debug)]
 pub struct Memory {
-    pub memory: Vec<u8>,
+    segments: BTreeMap<usize, Vec<u8>>,
+    /// The size of the memory in bytes, i.e. the end of the highest word accessed, as returned by
+    /// `MSIZE`. This may be far larger than the number of bytes held.
+    size: u128,
+    /// The number of bytes held across all segments.
+    held: usize,
+
+    /// The maximum number of bytes which may be held. Writes beyond it are dropped, so the bytes
+    /// they would have written read as zero.
+    pub max_size: usize,
+
+    /// The maximum number of bytes a single read returns. Larger reads are truncated.
+    pub max_access: usize,
     // TODO: add bit-tracking for memory
 }
 
 impl Memory {
-    /// Creates a new [`Memory`] with an empty memory vector.
+    /// Creates a new [`Memory`] with an empty memory vector, using the limits set with
+    /// [`set_memory_limits`].
     pub fn new() -> Memory {
-        Memory { memory: Vec::new() }
+        Memory::with_limits(
+            MAX_MEMORY_SIZE.load(Ordering::Relaxed),
+            MAX_MEMORY_ACCESS.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Creates a new, empty [`Memory`] which holds at most `max_size` bytes, and returns at most
+    /// `max_access` bytes from a single read.
+    ///
+    /// ```
+    /// use heimdall_common::ether::evm::core::memory::Memory;
+    ///
+    /// let mut memory = Memory::with_limits(32, 32);
+    /// memory.store(0, 32, &[0xff; 32]);
+    /// memory.store(32, 32, &[0xff; 32]);
+    /// assert_eq!(memory.held(), 32);
+    /// assert_eq!(memory.read(32, 64), vec![0u8; 32]);
+    /// ```
+    pub fn with_limits(max_size: usize, max_access: usize) -> Memory {
+        Memory { segments: BTreeMap::new(), size: 0, held: 0, max_size, max_access }
     }
 
     /// Gets the current size of the memory in bytes.
@@ -20,7 +100,21 @@ impl Memory {
     /// assert_eq!(memory.size(), 0);
     /// ```
     pub fn size(&self) -> u128 {
-        self.memory.len() as u128
+        self.size
+    }
+
+    /// Gets the number of bytes the memory holds, which is at most its size.
+    ///
+    /// ```
+    /// use heimdall_common::ether::evm::core::memory::Memory;
+    ///
+    /// let mut memory = Memory::new();
+    /// memory.store(1 << 40, 32, &[0xff]);
+    /// assert_eq!(memory.held(), 1);
+    /// assert_eq!(memory.size(), (1 << 40) + 32);
+    /// ```
+    pub fn held(&self) -> usize {
+        self.held
     }
 
     /// Extends the memory to the given size, if necessary. \
@@ -37,13 +131,8 @@ impl Memory {
     /// ```
     pub fn extend(&mut self, offset: u128, size: u128) {
         // Calculate the new size of the memory
-        let new_mem_size = (offset + size + 31) / 32 * 32;
-
-        // If the new memory size is greater than the current size, extend the memory
-        if new_mem_size > self.size() {
-            let byte_difference = (new_mem_size - self.size()) as usize;
-            self.memory.resize(self.memory.len() + byte_difference, 0u8);
-        }
+        let new_mem_size = offset.saturating_add(size).saturating_add(31) / 32 * 32;
+        self.size = self.size.max(new_mem_size);
     }
 
     /// Store the given bytes in the memory at the given offset, with a fixed size.
@@ -56,31 +145,75 @@ impl Memory {
     /// memory.store(0, 32, &[0xff]);
     /// assert_eq!(memory.read(0, 32), vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff]);
     /// ```
-    pub fn store(&mut self, mut offset: usize, mut size: usize, value: &[u8]) {
-        // Cap offset and size to 2**16
-        offset = offset.min(65536);
-        size = size.min(65536);
+    pub fn store(&mut self, offset: usize, size: usize, value: &[u8]) {
+        let end = offset.saturating_add(size);
+        let size = end - offset;
 
-        let value_len = value.len();
+        // Extend the memory to allocate for the new space
+        self.extend(offset as u128, size as u128);
+        self.clear(offset, end);
 
-        // Truncate or extend value to the desired size
-        let value: Vec<u8> = if value_len >= size {
-            value[..size].to_vec()
-        } else {
-            let mut value = value.to_vec();
+        // Values shorter than the size are left-padded with null bytes, which are unwritten bytes
+        // ex, ff with size 4 -> 00 00 00 ff
+        match value.len() >= size {
+            true => self.insert(offset, value[..size].to_vec()),
+            false => self.insert(end - value.len(), value.to_vec()),
+        }
+    }
 
-            // prepend null bytes until the value is the desired size
-            // ex, ff with size 4 -> 00 00 00 ff
-            let null_bytes = vec![0u8; size - value_len];
-            value.splice(0..0, null_bytes);
-            value
-        };
+    /// Write the given bytes to the start of a range of the memory, zeroing the rest of it, as the
+    /// `*COPY` opcodes do. Values longer than the range are truncated.
+    ///
+    /// ```
+    /// use heimdall_common::ether::evm::core::memory::Memory;
+    ///
+    /// let mut memory = Memory::new();
+    /// memory.store(0, 32, &[0xff; 32]);
+    /// memory.write(0, 32, &[0xee]);
+    /// assert_eq!(memory.read(0, 2), vec![0xee, 0]);
+    /// ```
+    pub fn write(&mut self, offset: usize, size: usize, value: &[u8]) {
+        let end = offset.saturating_add(size);
+        let size = end - offset;
 
-        // Extend the memory to allocate for the new space
         self.extend(offset as u128, size as u128);
+        self.clear(offset, end);
+        self.insert(offset, value[..value.len().min(size)].to_vec());
+    }
 
-        // Store the value in memory by replacing bytes in the memory
-        self.memory.splice(offset..offset + size, value);
+    /// Copy the given number of bytes from one offset of the memory to another, as `MCOPY` does.
+    /// Only the bytes held are copied, so copying large ranges is cheap.
+    ///
+    /// ```
+    /// use heimdall_common::ether::evm::core::memory::Memory;
+    ///
+    /// let mut memory = Memory::new();
+    /// memory.store(0, 32, &[0xff]);
+    /// memory.copy(1 << 32, 0, 1 << 32);
+    /// assert_eq!(memory.read((1 << 32) + 31, 1), vec![0xff]);
+    /// assert_eq!(memory.held(), 2);
+    /// ```
+    pub fn copy(&mut self, dest_offset: usize, offset: usize, size: usize) {
+        if size == 0 {
+            return
+        }
+        self.extend(offset as u128, size as u128);
+        self.extend(dest_offset as u128, size as u128);
+
+        let end = offset.saturating_add(size);
+        let copied = self
+            .overlapping(offset, end)
+            .map(|(start, bytes)| {
+                let from = offset.max(start) - start;
+                let to = end.min(start + bytes.len()) - start;
+                (start + from - offset, bytes[from..to].to_vec())
+            })
+            .collect::<Vec<(usize, Vec<u8>)>>();
+
+        self.clear(dest_offset, dest_offset.saturating_add(size));
+        for (relative_offset, bytes) in copied {
+            self.insert(dest_offset.saturating_add(relative_offset), bytes);
+        }
     }
 
     /// Read the given number of bytes from the memory at the given offset.
@@ -95,22 +228,67 @@ impl Memory {
     /// assert_eq!(memory.read(0, 32), vec![0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff]);
     /// ```
     pub fn read(&self, offset: usize, size: usize) -> Vec<u8> {
-        // Cap size to 2**16 and offset to 2**16 for optimization
-        let size = size.min(65536);
-        let offset = offset.min(65536);
+        let size = size.min(self.max_access);
+        let end = offset.saturating_add(size);
 
-        // If the offset + size will be out of bounds, append null bytes until the size is met
-        if offset + size > self.size() as usize {
-            let mut value = Vec::with_capacity(size);
+        let mut value = vec![0u8; end - offset];
+        for (start, bytes) in self.overlapping(offset, end) {
+            let from = offset.max(start);
+            let to = end.min(start + bytes.len());
+            value[from - offset..to - offset].copy_from_slice(&bytes[from - start..to - start]);
+        }
+        value
+    }
+
+    /// The segments which hold any bytes between the given offsets.
+    fn overlapping(&self, start: usize, end: usize) -> impl Iterator<Item = (usize, &Vec<u8>)> {
+        self.segments
+            .range(..start)
+            .next_back()
+            .filter(|(offset, bytes)| *offset + bytes.len() > start)
+            .into_iter()
+            .chain(self.segments.range(start..end))
+            .map(|(offset, bytes)| (*offset, bytes))
+    }
 
-            if offset <= self.size() as usize {
-                value.extend_from_slice(&self.memory[offset..]);
+    /// Drop the bytes held between the given offsets, splitting the segments which overlap them.
+    fn clear(&mut self, start: usize, end: usize) {
+        let overlapping =
+            self.overlapping(start, end).map(|(offset, _)| offset).collect::<Vec<_>>();
+        for offset in overlapping {
+            let bytes = self.segments.remove(&offset).unwrap_or_default();
+            self.held -= bytes.len();
+
+            if offset < start {
+                self.held += start - offset;
+                self.segments.insert(offset, bytes[..start - offset].to_vec());
+            }
+            if offset + bytes.len() > end {
+                self.held += offset + bytes.len() - end;
+                self.segments.insert(end, bytes[end - offset..].to_vec());
             }
+        }
+    }
 
-            value.resize(size, 0u8);
-            value
-        } else {
-            self.memory[offset..offset + size].to_vec()
+    /// Hold the given bytes at an offset which was cleared, merging them with adjacent segments.
+    /// Bytes which would extend past the end of the address space are dropped.
+    fn insert(&mut self, offset: usize, mut bytes: Vec<u8>) {
+        bytes.truncate(usize::MAX - offset);
+        if bytes.is_empty() || self.held.saturating_add(bytes.len()) > self.max_size {
+            return
+        }
+        self.held += bytes.len();
+
+        if let Some(following) = self.segments.remove(&(offset + bytes.len())) {
+            bytes.extend(following);
+        }
+        match self.segments.range_mut(..offset).next_back() {
+            Some((start, preceding)) if *start + preceding.len() == offset => {
+                preceding.extend(bytes);
+            }
+            _ => {
+                self.segments.insert(offset, bytes);
+            }
         }
     }
 
@@ -126,7 +304,7 @@ impl Memory {
     pub fn memory_cost(&self) -> u128 {
         // Calculate the new size of the memory
         let memory_word_size = (self.size() + 31) / 32;
-        (memory_word_size.pow(2)) / 512 + (3 * memory_word_size)
+        memory_word_size.saturating_pow(2) / 512 + (3 * memory_word_size)
     }
 
     /// calculate the memory cost of extending the memory to a given size
@@ -141,8 +319,9 @@ impl Memory {
     /// ```
     pub fn expansion_cost(&self, offset: usize, size: usize) -> u128 {
         // Calculate the new size of the memory
-        let new_memory_word_size = ((offset + size + 31) / 32) as u128;
-        let new_memory_cost = (new_memory_word_size.pow(2)) / 512 + (3 * new_memory_word_size);
+        let new_memory_word_size = (offset as u128 + size as u128 + 31) / 32;
+        let new_memory_cost =
+            new_memory_word_size.saturating_pow(2) / 512 + (3 * new_memory_word_size);
         if new_memory_cost < self.memory_cost() {
             0
         } else {
@@ -165,7 +344,7 @@ mod tests {
                 .unwrap(),
         );
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("00000000000000000000000000000000000000000000000000000000000000ff").unwrap()
         );
     }
@@ -175,7 +354,7 @@ mod tests {
         let mut memory = Memory::new();
         memory.store(0, 32, &[0xff]);
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("00000000000000000000000000000000000000000000000000000000000000ff").unwrap()
         );
     }
@@ -185,7 +364,7 @@ mod tests {
         let mut memory = Memory::new();
         memory.store(4, 32, &[0xff]);
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("0000000000000000000000000000000000000000000000000000000000000000000000ff00000000000000000000000000000000000000000000000000000000").unwrap()
         );
     }
@@ -195,7 +374,7 @@ mod tests {
         let mut memory = Memory::new();
         memory.store(34, 32, &[0xff]);
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("0000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000ff000000000000000000000000000000000000000000000000000000000000").unwrap()
         );
     }
@@ -205,7 +384,7 @@ mod tests {
         let mut memory = Memory::new();
         memory.store(0, 1, &[0xff]);
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("ff00000000000000000000000000000000000000000000000000000000000000").unwrap()
         );
     }
@@ -215,7 +394,7 @@ mod tests {
         let mut memory = Memory::new();
         memory.store(255, 32, &[0xff]);
         assert_eq!(
-            memory.memory,
+            memory.read(0, memory.size() as usize),
             decode_hex("00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000ff00").unwrap()
         );
     }
@@ -300,4 +479,59 @@ mod tests {
         let memory = Memory::new();
         assert_eq!(memory.expansion_cost(32 * 32, 32), 101);
     }
+
+    #[test]
+    fn test_mstore_huge_offset() {
+        let mut memory = Memory::new();
+        memory.store(1 << 48, 32, &[0xff]);
+        memory.store(0, 32, &[0xee]);
+        assert_eq!(memory.held(), 2);
+        assert_eq!(memory.size(), (1 << 48) + 32);
+        assert_eq!(memory.read((1 << 48) + 31, 1), vec![0xff]);
+        assert_eq!(memory.read(31, 1), vec![0xee]);
+    }
+
+    #[test]
+    fn test_mstore_overwrite() {
+        let mut memory = Memory::new();
+        memory.store(0, 64, &[0x11; 64]);
+        memory.store(16, 32, &[0xff]);
+        assert_eq!(memory.read(0, 16), vec![0x11; 16]);
+        assert_eq!(memory.read(16, 32), [vec![0u8; 31], vec![0xff]].concat());
+        assert_eq!(memory.read(48, 16), vec![0x11; 16]);
+
+        // the zero padding isn't held, so only the value and what remains of the first store are
+        assert_eq!(memory.held(), 33);
+    }
+
+    #[test]
+    fn test_mcopy_overlapping() {
+        let mut memory = Memory::new();
+        memory.store(0, 4, &[1, 2, 3, 4]);
+        memory.copy(2, 0, 4);
+        assert_eq!(memory.read(0, 6), vec![1, 2, 1, 2, 3, 4]);
+        assert_eq!(memory.size(), 32);
+    }
+
+    #[test]
+    fn test_write_at_end_of_address_space() {
+        // only the bytes which fit below usize::MAX are written, rather than overflowing
+        let mut memory = Memory::new();
+        memory.write(usize::MAX - 4, 32, &[0xff; 32]);
+        assert_eq!(memory.held(), 4);
+        assert_eq!(memory.read(usize::MAX - 4, 4), vec![0xff; 4]);
+
+        memory.copy(usize::MAX - 2, usize::MAX - 4, 4);
+        assert_eq!(memory.read(usize::MAX - 2, 2), vec![0xff; 2]);
+    }
+
+    #[test]
+    fn test_memory_limits() {
+        let mut memory = Memory::with_limits(64, 16);
+        memory.store(0, 64, &[0x11; 64]);
+        memory.store(64, 32, &[0x22; 32]);
+        assert_eq!(memory.held(), 64);
+        assert_eq!(memory.read(0, 64), vec![0x11; 16]);
+        assert_eq!(memory.read(64, 16), vec![0u8; 16]);
+    }
 }
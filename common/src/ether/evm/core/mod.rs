@@ -1,6 +1,7 @@
 pub mod log;
 pub mod memory;
 pub mod opcodes;
+pub mod precompiles;
 pub mod stack;
 pub mod storage;
 pub mod types;
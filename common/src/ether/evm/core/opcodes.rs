@@ -87,6 +87,7 @@ impl Opcode {
             0x59 => Opcode { code, name: "MSIZE", mingas: 2, inputs: 0, outputs: 1 },
             0x5a => Opcode { code, name: "GAS", mingas: 2, inputs: 0, outputs: 1 },
             0x5b => Opcode { code, name: "JUMPDEST", mingas: 1, inputs: 0, outputs: 0 },
+            0x5e => Opcode { code, name: "MCOPY", mingas: 3, inputs: 3, outputs: 0 },
             0x5f => Opcode { code, name: "PUSH0", mingas: 3, inputs: 0, outputs: 1 },
             0x60 => Opcode { code, name: "PUSH1", mingas: 3, inputs: 0, outputs: 1 },
             0x61 => Opcode { code, name: "PUSH2", mingas: 3, inputs: 0, outputs: 1 },
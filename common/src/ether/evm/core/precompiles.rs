@@ -0,0 +1,415 @@
+use ethers::{
+    core::k256::sha2::{Digest, Sha256},
+    types::{U256, U512},
+};
+
+/// The precompiled contracts at addresses `0x01` through `0x09`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precompile {
+    EcRecover,
+    Sha256,
+    Ripemd160,
+    Identity,
+    ModExp,
+    EcAdd,
+    EcMul,
+    EcPairing,
+    Blake2f,
+}
+
+/// The prime modulus of the BN254 curve's base field, used by `ecAdd`, `ecMul`, and `ecPairing`.
+const BN254_MODULUS: &str = "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47";
+
+/// The most rounds `blake2f` is modeled with. Each round costs a unit of gas, so calls with more
+/// rounds than this would run out of gas in practice.
+const MAX_BLAKE2F_ROUNDS: u32 = 1 << 20;
+
+/// The largest `modexp` output which is modeled, in bytes.
+const MAX_MODEXP_LENGTH: usize = 1024;
+
+impl Precompile {
+    /// The precompile deployed at the given address, if any.
+    pub fn from_address(address: U256) -> Option<Precompile> {
+        if address > U256::from(9u8) {
+            return None
+        }
+
+        match address.as_u32() {
+            1 => Some(Precompile::EcRecover),
+            2 => Some(Precompile::Sha256),
+            3 => Some(Precompile::Ripemd160),
+            4 => Some(Precompile::Identity),
+            5 => Some(Precompile::ModExp),
+            6 => Some(Precompile::EcAdd),
+            7 => Some(Precompile::EcMul),
+            8 => Some(Precompile::EcPairing),
+            9 => Some(Precompile::Blake2f),
+            _ => None,
+        }
+    }
+
+    /// The name of the helper which calls to this precompile are decompiled to.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Precompile::EcRecover => "ecrecover",
+            Precompile::Sha256 => "sha256",
+            Precompile::Ripemd160 => "ripemd160",
+            Precompile::Identity => "identity",
+            Precompile::ModExp => "modexp",
+            Precompile::EcAdd => "ecAdd",
+            Precompile::EcMul => "ecMul",
+            Precompile::EcPairing => "ecPairing",
+            Precompile::Blake2f => "blake2f",
+        }
+    }
+
+    /// Execute the precompile on the given input, returning its output, or `None` if the call
+    /// fails.
+    ///
+    /// Most precompiles are modeled concretely. Since symbolic execution can't recover a signer
+    /// or check a pairing, `ecrecover` returns a placeholder address and `ecPairing` assumes the
+    /// check passes, so that paths through signature and proof verification are explored.
+    /// `ripemd160` returns a placeholder digest, as does `modexp` with operands wider than 256
+    /// bits.
+    ///
+    /// ```
+    /// use ethers::types::U256;
+    /// use heimdall_common::ether::evm::core::precompiles::Precompile;
+    ///
+    /// let identity = Precompile::from_address(U256::from(4)).unwrap();
+    /// assert_eq!(identity.execute(&[0xde, 0xad]), Some(vec![0xde, 0xad]));
+    /// ```
+    pub fn execute(&self, input: &[u8]) -> Option<Vec<u8>> {
+        match self {
+            Precompile::EcRecover => Some(placeholder("ecrecover")),
+            Precompile::Sha256 => Some(Sha256::digest(input).to_vec()),
+            Precompile::Ripemd160 => Some(placeholder("ripemd160")),
+            Precompile::Identity => Some(input.to_vec()),
+            Precompile::ModExp => modexp(input),
+            Precompile::EcAdd => {
+                let sum = bn254_add(bn254_point(input, 0)?, bn254_point(input, 64)?);
+                Some(encode_point(sum))
+            }
+            Precompile::EcMul => {
+                let product = bn254_mul(bn254_point(input, 0)?, word(input, 64));
+                Some(encode_point(product))
+            }
+            Precompile::EcPairing => match input.len() % 192 {
+                0 => Some(word_bytes(U256::one())),
+                _ => None,
+            },
+            Precompile::Blake2f => blake2f(input),
+        }
+    }
+}
+
+/// A placeholder for an output which can't be computed symbolically, i.e. the address
+/// `0x6865696d64616c6c000000...{name}`, which spells out "heimdall" and the precompile's name.
+fn placeholder(name: &str) -> Vec<u8> {
+    let mut output = vec![0u8; 32];
+    output[12..20].copy_from_slice(b"heimdall");
+    output[32 - name.len()..].copy_from_slice(name.as_bytes());
+    output
+}
+
+/// Read the 32-byte word at the given offset of the input, which is zero-padded to its length.
+fn word(input: &[u8], offset: usize) -> U256 {
+    let mut word = [0u8; 32];
+    for (index, byte) in word.iter_mut().enumerate() {
+        *byte = input.get(offset + index).copied().unwrap_or(0);
+    }
+    U256::from_big_endian(&word)
+}
+
+fn word_bytes(value: U256) -> Vec<u8> {
+    let mut bytes = vec![0u8; 32];
+    value.to_big_endian(&mut bytes);
+    bytes
+}
+
+fn mulmod(a: U256, b: U256, modulus: U256) -> U256 {
+    (a.full_mul(b) % U512::from(modulus)).try_into().unwrap_or_default()
+}
+
+fn powmod(base: U256, exponent: U256, modulus: U256) -> U256 {
+    if modulus <= U256::one() {
+        return U256::zero()
+    }
+
+    let mut result = U256::one();
+    let base = base % modulus;
+    for bit in (0..exponent.bits()).rev() {
+        result = mulmod(result, result, modulus);
+        if exponent.bit(bit) {
+            result = mulmod(result, base, modulus);
+        }
+    }
+    result
+}
+
+/// Model `modexp`, which computes `base ** exponent % modulus` for operands whose lengths are
+/// given by the input's first three words.
+fn modexp(input: &[u8]) -> Option<Vec<u8>> {
+    let lengths = (0..3)
+        .map(|index| usize::try_from(word(input, index * 32)).ok())
+        .collect::<Option<Vec<usize>>>()?;
+    let (base_length, exponent_length, modulus_length) = (lengths[0], lengths[1], lengths[2]);
+    if modulus_length > MAX_MODEXP_LENGTH {
+        return None
+    }
+    if base_length > 32 || exponent_length > 32 || modulus_length > 32 {
+        return Some(vec![0u8; modulus_length])
+    }
+
+    let operand = |offset: usize, length: usize| match length {
+        0 => U256::zero(),
+        _ => word(input, 96 + offset) >> (8 * (32 - length)),
+    };
+    let base = operand(0, base_length);
+    let exponent = operand(base_length, exponent_length);
+    let modulus = operand(base_length + exponent_length, modulus_length);
+
+    let result = word_bytes(powmod(base, exponent, modulus));
+    Some(result[32 - modulus_length..].to_vec())
+}
+
+/// A point on the BN254 curve in Jacobian coordinates, with `z` zero at the point at infinity.
+type JacobianPoint = (U256, U256, U256);
+
+fn bn254_modulus() -> U256 {
+    U256::from_str_radix(BN254_MODULUS, 16).expect("the BN254 modulus is valid")
+}
+
+fn addmod(a: U256, b: U256, p: U256) -> U256 {
+    // both operands are below the 254-bit modulus, so their sum can't overflow
+    (a + b) % p
+}
+
+fn submod(a: U256, b: U256, p: U256) -> U256 {
+    (a + p - b) % p
+}
+
+/// Read the affine point at the given offset of the input, failing if it isn't on the curve.
+fn bn254_point(input: &[u8], offset: usize) -> Option<JacobianPoint> {
+    let p = bn254_modulus();
+    let (x, y) = (word(input, offset), word(input, offset + 32));
+    if x >= p || y >= p {
+        return None
+    }
+    if x.is_zero() && y.is_zero() {
+        return Some((U256::one(), U256::one(), U256::zero()))
+    }
+
+    // y^2 = x^3 + 3
+    let curve = addmod(mulmod(mulmod(x, x, p), x, p), U256::from(3u8), p);
+    match mulmod(y, y, p) == curve {
+        true => Some((x, y, U256::one())),
+        false => None,
+    }
+}
+
+fn encode_point((x, y, z): JacobianPoint) -> Vec<u8> {
+    if z.is_zero() {
+        return vec![0u8; 64]
+    }
+
+    let p = bn254_modulus();
+    let z_inverse = powmod(z, p - 2, p);
+    let z_inverse_squared = mulmod(z_inverse, z_inverse, p);
+    let mut output = word_bytes(mulmod(x, z_inverse_squared, p));
+    output.extend(word_bytes(mulmod(y, mulmod(z_inverse_squared, z_inverse, p), p)));
+    output
+}
+
+fn bn254_double((x, y, z): JacobianPoint) -> JacobianPoint {
+    let p = bn254_modulus();
+    if z.is_zero() || y.is_zero() {
+        return (U256::one(), U256::one(), U256::zero())
+    }
+
+    let a = mulmod(x, x, p);
+    let b = mulmod(y, y, p);
+    let c = mulmod(b, b, p);
+    let x_plus_b = addmod(x, b, p);
+    let d = mulmod(U256::from(2u8), submod(submod(mulmod(x_plus_b, x_plus_b, p), a, p), c, p), p);
+    let e = mulmod(U256::from(3u8), a, p);
+    let f = mulmod(e, e, p);
+
+    let x3 = submod(f, addmod(d, d, p), p);
+    let y3 = submod(mulmod(e, submod(d, x3, p), p), mulmod(U256::from(8u8), c, p), p);
+    let z3 = mulmod(U256::from(2u8), mulmod(y, z, p), p);
+    (x3, y3, z3)
+}
+
+fn bn254_add(first: JacobianPoint, second: JacobianPoint) -> JacobianPoint {
+    let p = bn254_modulus();
+    let ((x1, y1, z1), (x2, y2, z2)) = (first, second);
+    if z1.is_zero() {
+        return second
+    }
+    if z2.is_zero() {
+        return first
+    }
+
+    let z1_squared = mulmod(z1, z1, p);
+    let z2_squared = mulmod(z2, z2, p);
+    let u1 = mulmod(x1, z2_squared, p);
+    let u2 = mulmod(x2, z1_squared, p);
+    let s1 = mulmod(y1, mulmod(z2_squared, z2, p), p);
+    let s2 = mulmod(y2, mulmod(z1_squared, z1, p), p);
+    if u1 == u2 {
+        return match s1 == s2 {
+            true => bn254_double(first),
+            false => (U256::one(), U256::one(), U256::zero()),
+        }
+    }
+
+    let h = submod(u2, u1, p);
+    let r = submod(s2, s1, p);
+    let h_squared = mulmod(h, h, p);
+    let h_cubed = mulmod(h_squared, h, p);
+    let u1_h_squared = mulmod(u1, h_squared, p);
+
+    let x3 = submod(submod(mulmod(r, r, p), h_cubed, p), addmod(u1_h_squared, u1_h_squared, p), p);
+    let y3 = submod(mulmod(r, submod(u1_h_squared, x3, p), p), mulmod(s1, h_cubed, p), p);
+    let z3 = mulmod(h, mulmod(z1, z2, p), p);
+    (x3, y3, z3)
+}
+
+fn bn254_mul(point: JacobianPoint, scalar: U256) -> JacobianPoint {
+    let mut result = (U256::one(), U256::one(), U256::zero());
+    for bit in (0..scalar.bits()).rev() {
+        result = bn254_double(result);
+        if scalar.bit(bit) {
+            result = bn254_add(result, point);
+        }
+    }
+    result
+}
+
+const BLAKE2B_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+const BLAKE2B_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Model `blake2f`, the BLAKE2b compression function, per EIP-152.
+fn blake2f(input: &[u8]) -> Option<Vec<u8>> {
+    if input.len() != 213 || input[212] > 1 {
+        return None
+    }
+    let rounds = u32::from_be_bytes(input[0..4].try_into().ok()?);
+    if rounds > MAX_BLAKE2F_ROUNDS {
+        return None
+    }
+
+    let words = |offset: usize, count: usize| {
+        input[offset..offset + count * 8]
+            .chunks(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap_or_default()))
+            .collect::<Vec<u64>>()
+    };
+    let mut h = words(4, 8);
+    let m = words(68, 16);
+    let t = words(196, 2);
+
+    let mut v = [0u64; 16];
+    v[..8].copy_from_slice(&h);
+    v[8..].copy_from_slice(&BLAKE2B_IV);
+    v[12] ^= t[0];
+    v[13] ^= t[1];
+    if input[212] == 1 {
+        v[14] = !v[14];
+    }
+
+    fn mix(v: &mut [u64; 16], (a, b, c, d): (usize, usize, usize, usize), x: u64, y: u64) {
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+        v[d] = (v[d] ^ v[a]).rotate_right(32);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(24);
+        v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+        v[d] = (v[d] ^ v[a]).rotate_right(16);
+        v[c] = v[c].wrapping_add(v[d]);
+        v[b] = (v[b] ^ v[c]).rotate_right(63);
+    }
+    for round in 0..rounds as usize {
+        let s = &BLAKE2B_SIGMA[round % 10];
+        mix(&mut v, (0, 4, 8, 12), m[s[0]], m[s[1]]);
+        mix(&mut v, (1, 5, 9, 13), m[s[2]], m[s[3]]);
+        mix(&mut v, (2, 6, 10, 14), m[s[4]], m[s[5]]);
+        mix(&mut v, (3, 7, 11, 15), m[s[6]], m[s[7]]);
+        mix(&mut v, (0, 5, 10, 15), m[s[8]], m[s[9]]);
+        mix(&mut v, (1, 6, 11, 12), m[s[10]], m[s[11]]);
+        mix(&mut v, (2, 7, 8, 13), m[s[12]], m[s[13]]);
+        mix(&mut v, (3, 4, 9, 14), m[s[14]], m[s[15]]);
+    }
+
+    for (index, word) in h.iter_mut().enumerate() {
+        *word ^= v[index] ^ v[index + 8];
+    }
+    Some(h.iter().flat_map(|word| word.to_le_bytes()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::strings::{decode_hex, encode_hex};
+
+    fn execute(address: u8, input: &str) -> Option<String> {
+        Precompile::from_address(U256::from(address))
+            .unwrap()
+            .execute(&decode_hex(input).unwrap())
+            .map(encode_hex)
+    }
+
+    #[test]
+    fn test_precompiles() {
+        assert_eq!(Precompile::from_address(U256::from(10)), None);
+        assert_eq!(
+            execute(2, "").unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+
+        // 3 ** 5 % 7
+        let lengths = format!("{:064x}{:064x}{:064x}", 1, 1, 1);
+        assert_eq!(execute(5, &format!("{lengths}030507")).unwrap(), "05");
+
+        // G + G and G * 2 are both 2G, where G = (1, 2)
+        let double = "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                      15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4";
+        let generator = format!("{:064x}{:064x}", 1, 2);
+        assert_eq!(execute(6, &format!("{generator}{generator}")).unwrap(), double);
+        assert_eq!(execute(7, &format!("{generator}{:064x}", 2)).unwrap(), double);
+        assert_eq!(execute(6, "").unwrap(), "00".repeat(64));
+        assert_eq!(execute(7, &format!("{:064x}{:064x}", 1, 3)), None);
+
+        // the EIP-152 test vector for BLAKE2b("abc"), with 12 rounds and the final block flag set
+        let state = "48c9bdf267e6096a3ba7ca8485ae67bb2bf894fe72f36e3cf1361d5f3af54fa5\
+                     d182e6ad7f520e511f6c3e2b8c68059b6bbd41fbabd9831f79217e1319cde05b";
+        let message = format!("616263{}", "00".repeat(125));
+        let offset = format!("03{}", "00".repeat(15));
+        assert_eq!(
+            execute(9, &format!("0000000c{state}{message}{offset}01")).unwrap(),
+            "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1\
+             7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+        );
+    }
+}
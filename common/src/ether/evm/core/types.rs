@@ -1,7 +1,14 @@
 use colored::Colorize;
-use ethers::abi::{AbiEncode, ParamType, Token};
+use ethers::{
+    abi::{AbiEncode, ParamType, Token},
+    utils::to_checksum,
+};
+use serde_json::{json, Value};
 
-use crate::{constants::TYPE_CAST_REGEX, utils::strings::find_balanced_encapsulator};
+use crate::{
+    constants::TYPE_CAST_REGEX,
+    utils::strings::{encode_hex, find_balanced_encapsulator, sign_uint},
+};
 
 use super::vm::Instruction;
 
@@ -284,6 +291,84 @@ pub fn display(inputs: Vec<Token>, prefix: &str) -> Vec<String> {
     output
 }
 
+/// Convert a decoded [`Token`] into a typed JSON value, given the [`ParamType`] it was decoded
+/// as. Every value is an object containing its ABI `type` and its `value`, where:
+/// - integers are decimal strings, so they don't lose precision in JSON parsers
+/// - addresses are EIP-55 checksummed
+/// - bytes are `0x`-prefixed hex strings
+/// - arrays and tuples contain a list of nested typed values
+///
+/// ```
+/// use heimdall_common::ether::evm::core::types::to_json_value;
+/// use ethers::{abi::{ParamType, Token}, types::U256};
+///
+/// let value = to_json_value(&Token::Uint(U256::from(1)), &ParamType::Uint(256));
+///
+/// assert_eq!(value.to_string(), r#"{"type":"uint256","value":"1"}"#);
+/// ```
+pub fn to_json_value(token: &Token, kind: &ParamType) -> Value {
+    let value = match token {
+        Token::Address(address) => json!(to_checksum(address, None)),
+        Token::Int(val) => json!(sign_uint(*val).to_string()),
+        Token::Uint(val) => json!(val.to_string()),
+        Token::Bool(val) => json!(val),
+        Token::String(val) => json!(val),
+        Token::FixedBytes(bytes) | Token::Bytes(bytes) => {
+            json!(format!("0x{}", encode_hex(bytes.to_vec())))
+        }
+        Token::FixedArray(tokens) | Token::Array(tokens) => {
+            let inner_kind = match kind {
+                ParamType::FixedArray(inner_kind, _) | ParamType::Array(inner_kind) => {
+                    *inner_kind.to_owned()
+                }
+                _ => kind.to_owned(),
+            };
+
+            Value::Array(tokens.iter().map(|token| to_json_value(token, &inner_kind)).collect())
+        }
+        Token::Tuple(tokens) => {
+            let inner_kinds = match kind {
+                ParamType::Tuple(inner_kinds) => inner_kinds.to_owned(),
+                _ => Vec::new(),
+            };
+
+            Value::Array(
+                tokens
+                    .iter()
+                    .enumerate()
+                    .map(|(i, token)| match inner_kinds.get(i) {
+                        Some(inner_kind) => to_json_value(token, inner_kind),
+                        None => to_json_value(token, &token_param_type(token)),
+                    })
+                    .collect(),
+            )
+        }
+    };
+
+    json!({ "type": kind.to_string(), "value": value })
+}
+
+/// Infer the [`ParamType`] of a [`Token`], used when no explicit type is known.
+fn token_param_type(token: &Token) -> ParamType {
+    match token {
+        Token::Address(_) => ParamType::Address,
+        Token::Int(_) => ParamType::Int(256),
+        Token::Uint(_) => ParamType::Uint(256),
+        Token::Bool(_) => ParamType::Bool,
+        Token::String(_) => ParamType::String,
+        Token::FixedBytes(bytes) => ParamType::FixedBytes(bytes.len()),
+        Token::Bytes(_) => ParamType::Bytes,
+        Token::FixedArray(tokens) => ParamType::FixedArray(
+            Box::new(tokens.first().map(token_param_type).unwrap_or(ParamType::Uint(256))),
+            tokens.len(),
+        ),
+        Token::Array(tokens) => ParamType::Array(Box::new(
+            tokens.first().map(token_param_type).unwrap_or(ParamType::Uint(256)),
+        )),
+        Token::Tuple(tokens) => ParamType::Tuple(tokens.iter().map(token_param_type).collect()),
+    }
+}
+
 /// Convert a bitwise masking operation to a tuple containing: \
 /// 1. The size of the type being masked \
 /// 2. Potential types that the type being masked could be.
@@ -361,9 +446,12 @@ pub fn find_cast(line: &str) -> (usize, usize, Option<String>) {
 
 #[cfg(test)]
 mod tests {
-    use ethers::abi::ParamType;
+    use ethers::{
+        abi::{ParamType, Token},
+        types::{Address, U256},
+    };
 
-    use crate::ether::evm::core::types::parse_function_parameters;
+    use crate::ether::evm::core::types::{parse_function_parameters, to_json_value};
 
     #[test]
     fn test_simple_signature() {
@@ -566,4 +654,32 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    fn test_to_json_value_typed() {
+        let kind = ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Int(256),
+            ParamType::Array(Box::new(ParamType::FixedBytes(2))),
+        ]);
+        let token = Token::Tuple(vec![
+            Token::Address(
+                "0xd8da6bf26964af9d7eed9e03e53415d37aa96045".parse::<Address>().unwrap(),
+            ),
+            Token::Int(U256::MAX),
+            Token::Array(vec![Token::FixedBytes(vec![0xde, 0xad])]),
+        ]);
+
+        assert_eq!(
+            to_json_value(&token, &kind),
+            serde_json::json!({
+                "type": "(address,int256,bytes2[])",
+                "value": [
+                    { "type": "address", "value": "0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045" },
+                    { "type": "int256", "value": "-1" },
+                    { "type": "bytes2[]", "value": [{ "type": "bytes2", "value": "0xdead" }] }
+                ]
+            })
+        );
+    }
 }
@@ -8,7 +8,13 @@ use std::{
 use ethers::{abi::AbiEncode, prelude::U256, types::I256, utils::keccak256};
 
 use crate::{
-    ether::evm::core::opcodes::{Opcode, WrappedInput, WrappedOpcode},
+    ether::evm::{
+        core::{
+            opcodes::{Opcode, WrappedInput, WrappedOpcode},
+            precompiles::Precompile,
+        },
+        ext::exec::ExecutionLimits,
+    },
     utils::strings::{decode_hex, sign_uint},
 };
 
@@ -36,6 +42,7 @@ pub struct VM {
     pub exitcode: u128,
     pub timestamp: Instant,
     pub address_access_set: HashSet<U256>,
+    pub limits: ExecutionLimits,
 }
 
 /// [`ExecutionResult`] is the result of a single contract execution.
@@ -122,6 +129,7 @@ impl VM {
             exitcode: 255,
             timestamp: Instant::now(),
             address_access_set: HashSet::new(),
+            limits: ExecutionLimits::default(),
         }
     }
 
@@ -186,6 +194,33 @@ impl VM {
         true
     }
 
+    /// Call the precompiled contract at the given address, if it is one, reading its input from
+    /// memory and writing its output back to memory. Returns whether the call succeeded, or
+    /// `None` if the address isn't a precompile, in which case the call isn't modeled.
+    fn call_precompile(
+        &mut self,
+        address: U256,
+        args_offset: U256,
+        args_size: U256,
+        ret_offset: U256,
+        ret_size: U256,
+    ) -> Option<bool> {
+        let precompile = Precompile::from_address(address)?;
+
+        // memory accesses are capped to 2**16, so larger offsets and sizes can saturate
+        let to_usize = |value: U256| usize::try_from(value).unwrap_or(usize::MAX);
+        let input = self.memory.read(to_usize(args_offset), to_usize(args_size));
+        let output = match precompile.execute(&input) {
+            Some(output) => output,
+            None => return Some(false),
+        };
+
+        // only as much of the output as fits in the return buffer is copied to memory
+        let size = to_usize(ret_size).min(output.len());
+        self.memory.store(to_usize(ret_offset), size, &output[..size]);
+        Some(true)
+    }
+
     /// Executes the next instruction in the bytecode. Returns information about the instruction
     /// executed.
     ///
@@ -761,7 +796,7 @@ impl VM {
                 let result = keccak256(data);
 
                 // consume dynamic gas
-                let minimum_word_size = ((size + 31) / 32) as u128;
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
                 let gas_cost = 6 * minimum_word_size + self.memory.expansion_cost(offset, size);
                 self.consume_gas(gas_cost);
 
@@ -915,21 +950,16 @@ impl VM {
                     }
                 };
 
-                let value_offset_safe = (offset + size).min(self.calldata.len());
-                let mut value =
-                    self.calldata.get(offset..value_offset_safe).unwrap_or(&[]).to_owned();
-
-                // pad value with 0x00
-                if value.len() < size {
-                    value.resize(size, 0u8);
-                }
+                let value_offset_safe = offset.saturating_add(size).min(self.calldata.len());
+                let value = self.calldata.get(offset..value_offset_safe).unwrap_or(&[]).to_owned();
 
                 // consume dynamic gas
-                let minimum_word_size = ((size + 31) / 32) as u128;
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
                 let gas_cost = 3 * minimum_word_size + self.memory.expansion_cost(offset, size);
                 self.consume_gas(gas_cost);
 
-                self.memory.store(dest_offset, size, &value);
+                // the value is padded with 0x00
+                self.memory.write(dest_offset, size, &value);
             }
 
             // CODESIZE
@@ -992,21 +1022,16 @@ impl VM {
                     }
                 };
 
-                let value_offset_safe = (offset + size).min(self.bytecode.len());
-                let mut value =
-                    self.bytecode.get(offset..value_offset_safe).unwrap_or(&[]).to_owned();
-
-                // pad value with 0x00
-                if value.len() < size {
-                    value.resize(size, 0u8);
-                }
+                let value_offset_safe = offset.saturating_add(size).min(self.bytecode.len());
+                let value = self.bytecode.get(offset..value_offset_safe).unwrap_or(&[]).to_owned();
 
                 // consume dynamic gas
-                let minimum_word_size = ((size + 31) / 32) as u128;
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
                 let gas_cost = 3 * minimum_word_size + self.memory.expansion_cost(offset, size);
                 self.consume_gas(gas_cost);
 
-                self.memory.store(dest_offset, size, &value);
+                // the value is padded with 0x00
+                self.memory.write(dest_offset, size, &value);
             }
 
             // GASPRICE
@@ -1068,11 +1093,11 @@ impl VM {
                     }
                 };
 
-                let mut value = Vec::with_capacity(size);
-                value.resize(size, 0xff);
+                // the copied bytes are unknown, so only as many as a read may return are filled
+                let value = vec![0xffu8; size.min(self.memory.max_access)];
 
                 // consume dynamic gas
-                let minimum_word_size = ((size + 31) / 32) as u128;
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
                 let gas_cost =
                     3 * minimum_word_size + self.memory.expansion_cost(dest_offset, size);
                 self.consume_gas(gas_cost);
@@ -1083,7 +1108,7 @@ impl VM {
                     self.consume_gas(100);
                 }
 
-                self.memory.store(dest_offset, size, &value);
+                self.memory.write(dest_offset, size, &value);
             }
 
             // RETURNDATASIZE
@@ -1129,16 +1154,16 @@ impl VM {
                     }
                 };
 
-                let mut value = Vec::with_capacity(size);
-                value.resize(size, 0xff);
+                // the copied bytes are unknown, so only as many as a read may return are filled
+                let value = vec![0xffu8; size.min(self.memory.max_access)];
 
                 // consume dynamic gas
-                let minimum_word_size = ((size + 31) / 32) as u128;
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
                 let gas_cost =
                     3 * minimum_word_size + self.memory.expansion_cost(dest_offset, size);
                 self.consume_gas(gas_cost);
 
-                self.memory.store(dest_offset, size, &value);
+                self.memory.write(dest_offset, size, &value);
             }
 
             // EXTCODEHASH and BLOCKHASH
@@ -1269,6 +1294,68 @@ impl VM {
                 self.memory.store(offset, 1, &[value.encode()[31]]);
             }
 
+            // MCOPY
+            0x5E => {
+                let dest_offset = self.stack.pop().value;
+                let offset = self.stack.pop().value;
+                let size = self.stack.pop().value;
+
+                // Safely convert U256 to usize
+                let dest_offset: usize = match dest_offset.try_into() {
+                    Ok(x) => x,
+                    Err(_) => {
+                        self.exit(2, Vec::new());
+                        return Instruction {
+                            instruction: last_instruction,
+                            opcode,
+                            opcode_details: Some(opcode_details),
+                            inputs,
+                            outputs: Vec::new(),
+                            input_operations,
+                            output_operations: Vec::new(),
+                        }
+                    }
+                };
+                let offset: usize = match offset.try_into() {
+                    Ok(x) => x,
+                    Err(_) => {
+                        self.exit(2, Vec::new());
+                        return Instruction {
+                            instruction: last_instruction,
+                            opcode,
+                            opcode_details: Some(opcode_details),
+                            inputs,
+                            outputs: Vec::new(),
+                            input_operations,
+                            output_operations: Vec::new(),
+                        }
+                    }
+                };
+                let size: usize = match size.try_into() {
+                    Ok(x) => x,
+                    Err(_) => {
+                        self.exit(2, Vec::new());
+                        return Instruction {
+                            instruction: last_instruction,
+                            opcode,
+                            opcode_details: Some(opcode_details),
+                            inputs,
+                            outputs: Vec::new(),
+                            input_operations,
+                            output_operations: Vec::new(),
+                        }
+                    }
+                };
+
+                // consume dynamic gas
+                let minimum_word_size = (size.saturating_add(31) / 32) as u128;
+                let gas_cost = 3 * minimum_word_size +
+                    self.memory.expansion_cost(dest_offset.max(offset), size);
+                self.consume_gas(gas_cost);
+
+                self.memory.copy(dest_offset, offset, size);
+            }
+
             // SLOAD
             0x54 => {
                 let key = self.stack.pop().value;
@@ -1501,8 +1588,8 @@ impl VM {
 
             // CALL, CALLCODE
             0xF1 | 0xF2 => {
-                let address = self.stack.pop().value;
-                self.stack.pop_n(6);
+                let frames = self.stack.pop_n(7);
+                let address = frames[1].value;
 
                 // consume dynamic gas
                 if !self.address_access_set.contains(&address) {
@@ -1512,7 +1599,16 @@ impl VM {
                     self.consume_gas(100);
                 }
 
-                self.stack.push(U256::from(1u8), operation);
+                let success = self
+                    .call_precompile(
+                        address,
+                        frames[3].value,
+                        frames[4].value,
+                        frames[5].value,
+                        frames[6].value,
+                    )
+                    .unwrap_or(true);
+                self.stack.push(U256::from(success as u8), operation);
             }
 
             // RETURN
@@ -1561,8 +1657,8 @@ impl VM {
 
             // DELEGATECALL, STATICCALL
             0xF4 | 0xFA => {
-                let address = self.stack.pop().value;
-                self.stack.pop_n(5);
+                let frames = self.stack.pop_n(6);
+                let address = frames[1].value;
 
                 // consume dynamic gas
                 if !self.address_access_set.contains(&address) {
@@ -1572,7 +1668,16 @@ impl VM {
                     self.consume_gas(100);
                 }
 
-                self.stack.push(U256::from(1u8), operation);
+                let success = self
+                    .call_precompile(
+                        address,
+                        frames[2].value,
+                        frames[3].value,
+                        frames[4].value,
+                        frames[5].value,
+                    )
+                    .unwrap_or(true);
+                self.stack.push(U256::from(success as u8), operation);
             }
 
             // CREATE2
@@ -2335,6 +2440,37 @@ mod tests {
         assert_eq!(vm.stack.peek(0).value, U256::from_str("0x07").unwrap());
     }
 
+    #[test]
+    fn test_mcopy_huge_offset() {
+        // copy the word at 0x00 to 0x0100000000, and return it from there. expanding memory this
+        // far costs more gas than the test VM has, but symbolic execution runs without a limit
+        let mut vm = new_test_vm("0x60ff600052602060006401000000005e6020640100000000f3");
+        vm.gas_remaining = u128::MAX;
+        vm.execute();
+
+        assert_eq!(vm.returndata, [vec![0u8; 31], vec![0xff]].concat());
+        assert_eq!(vm.memory.size(), 0x0100000020);
+        assert_eq!(vm.memory.held(), 64);
+    }
+
+    #[test]
+    fn test_staticcall_precompile() {
+        // return ecAdd((1, 2), (1, 2)), stored at 0x80 by the precompile
+        let mut vm = new_test_vm(
+            "0x6001600052600260205260016040526002606052604060806080600060065afa5060406080f3",
+        );
+        vm.execute();
+
+        assert_eq!(
+            vm.returndata,
+            decode_hex(
+                "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+                 15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4"
+            )
+            .unwrap()
+        );
+    }
+
     #[test]
     fn test_usdt_sim() {
         // this execution should return the name of the USDT contract
@@ -1,17 +1,20 @@
 mod util;
 
 use self::util::{
-    jump_condition_appears_recursive, jump_condition_contains_mutated_memory_access,
-    jump_condition_contains_mutated_storage_access,
+    estimate_state_size, jump_condition_appears_recursive,
+    jump_condition_contains_mutated_memory_access, jump_condition_contains_mutated_storage_access,
     jump_condition_historical_diffs_approximately_equal, stack_contains_too_many_of_the_same_item,
     stack_diff, stack_item_source_depth_too_deep,
 };
 use crate::{
-    ether::evm::core::{
-        stack::Stack,
-        vm::{State, VM},
+    ether::{
+        evm::core::{
+            stack::Stack,
+            vm::{State, VM},
+        },
+        selectors::{reads_selector, MAX_DISPATCHER_STEPS},
     },
-    utils::{io::logging::Logger, strings::decode_hex},
+    utils::{interrupt::is_interrupted, io::logging::Logger, strings::decode_hex},
 };
 use ethers::types::U256;
 use std::collections::HashMap;
@@ -22,6 +25,119 @@ pub struct VMTrace {
     pub gas_used: u128,
     pub operations: Vec<State>,
     pub children: Vec<VMTrace>,
+
+    /// Whether symbolic execution stopped exploring this path's branches early, because it
+    /// exceeded its [`ExecutionLimits`].
+    pub pruned: bool,
+
+    /// The instruction of the conditional jump at which symbolic execution stopped exploring this
+    /// path, because the path re-entered the loop which the jump controls.
+    pub loop_jump: Option<u128>,
+}
+
+impl VMTrace {
+    /// Whether any path within this trace was pruned, i.e. the function it belongs to was only
+    /// partially analyzed.
+    pub fn is_pruned(&self) -> bool {
+        self.pruned || self.children.iter().any(|child| child.is_pruned())
+    }
+
+    /// Whether this path ends by reverting without branching any further, such as the failing
+    /// branch of a `require` check.
+    pub fn reverts(&self) -> bool {
+        self.children.is_empty() &&
+            matches!(
+                self.operations.last().map(|operation| operation.last_instruction.opcode),
+                Some(0xfd | 0xfe)
+            )
+    }
+
+    /// Whether every path within this trace ends by reverting, i.e. the traced function can never
+    /// succeed, such as a function which was disabled.
+    pub fn always_reverts(&self) -> bool {
+        match self.children.is_empty() {
+            true => self.reverts(),
+            false => self.children.iter().all(|child| child.always_reverts()),
+        }
+    }
+
+    /// The cyclomatic complexity of the traced code, i.e. the number of linearly independent
+    /// paths through it. Since the trace is a tree, this is one more than the number of
+    /// additional paths each conditional jump creates.
+    pub fn cyclomatic_complexity(&self) -> u32 {
+        self.children.len().saturating_sub(1) as u32 +
+            self.children.iter().map(|child| child.cyclomatic_complexity() - 1).sum::<u32>() +
+            1
+    }
+
+    /// The maximum number of conditionals nested within each other along any path. Guards, whose
+    /// other paths only revert, don't count towards nesting, since the code following a `require`
+    /// check isn't nested within it.
+    pub fn max_nesting_depth(&self) -> u32 {
+        let nests = self.children.iter().filter(|child| !child.reverts()).count() > 1;
+        nests as u32 +
+            self.children.iter().map(|child| child.max_nesting_depth()).max().unwrap_or(0)
+    }
+}
+
+/// Limits on the resources symbolic execution may use, so pathological contracts are partially
+/// analyzed rather than exhausting the machine, and completeness can be traded for speed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExecutionLimits {
+    /// The approximate number of bytes the traced states of a single symbolic execution may
+    /// occupy. Once exceeded, no further branches are explored, so the deepest branches are
+    /// pruned. `0` means no limit.
+    pub memory_limit: usize,
+
+    /// The maximum number of nested conditional branches explored along a single path. Deeper
+    /// branches are pruned. `0` means no limit.
+    pub max_branch_depth: usize,
+
+    /// The maximum depth of the operations a stack value may be built from, i.e. through nested
+    /// internal calls, before the path is treated as unbounded recursion and stopped.
+    pub max_call_depth: u32,
+
+    /// The maximum number of times a single conditional jump is revisited with a different stack
+    /// before it's treated as a loop and stopped. `0` means no limit, relying only on loop
+    /// detection heuristics.
+    pub max_loop_iterations: usize,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self { memory_limit: 0, max_branch_depth: 0, max_call_depth: 16, max_loop_iterations: 0 }
+    }
+}
+
+impl ExecutionLimits {
+    /// Build execution limits from user-facing settings, where the memory limit is given in
+    /// megabytes and `0` selects the default for any setting.
+    ///
+    /// ```
+    /// use heimdall_common::ether::evm::ext::exec::ExecutionLimits;
+    ///
+    /// let limits = ExecutionLimits::new(64, 0, 0, 8);
+    /// assert_eq!(limits.memory_limit, 64 * 1024 * 1024);
+    /// assert_eq!(limits.max_call_depth, ExecutionLimits::default().max_call_depth);
+    /// assert_eq!(limits.max_loop_iterations, 8);
+    /// ```
+    pub fn new(
+        memory_limit_mb: usize,
+        max_branch_depth: usize,
+        max_call_depth: u32,
+        max_loop_iterations: usize,
+    ) -> Self {
+        let default = Self::default();
+        Self {
+            memory_limit: memory_limit_mb * 1024 * 1024,
+            max_branch_depth,
+            max_call_depth: match max_call_depth {
+                0 => default.max_call_depth,
+                max_call_depth => max_call_depth,
+            },
+            max_loop_iterations,
+        }
+    }
 }
 
 impl VM {
@@ -29,10 +145,15 @@ impl VM {
     pub fn symbolic_exec_selector(&mut self, selector: &str, entry_point: u128) -> (VMTrace, u32) {
         self.calldata = decode_hex(selector).unwrap();
 
-        // step through the bytecode until we reach the entry point
-        while self.bytecode.len() >= self.instruction as usize && (self.instruction <= entry_point)
+        // step through the dispatcher until we reach the entry point. the dispatcher isn't
+        // necessarily below the entry point, e.g. the buckets of a jump table dispatcher
+        let mut steps = 0;
+        while self.bytecode.len() >= self.instruction as usize &&
+            self.instruction != entry_point + 1 &&
+            steps < MAX_DISPATCHER_STEPS
         {
             self.step();
+            steps += 1;
 
             // this shouldn't be necessary, but it's safer to have it
             if self.exitcode != 255 || !self.returndata.is_empty() {
@@ -47,7 +168,10 @@ impl VM {
 
         // the VM is at the function entry point, begin tracing
         let mut branch_count = 0;
-        (self.recursive_map(&mut branch_count, &mut HashMap::new(), &logger), branch_count)
+        (
+            self.recursive_map(&mut branch_count, &mut HashMap::new(), &mut 0, 0, &logger),
+            branch_count,
+        )
     }
 
     // build a map of function jump possibilities from the EVM bytecode
@@ -61,13 +185,76 @@ impl VM {
 
         // the VM is at the function entry point, begin tracing
         let mut branch_count = 0;
-        (vm.recursive_map(&mut branch_count, &mut HashMap::new(), &logger), branch_count)
+        (vm.recursive_map(&mut branch_count, &mut HashMap::new(), &mut 0, 0, &logger), branch_count)
+    }
+
+    /// Step through the contract's dispatcher with the given calldata and call value until it
+    /// hands off control to the function it selects, returning the VM at that function's entry
+    /// point. This is used to find the `receive` and `fallback` functions, which have no selector.
+    /// Returns `None` if the dispatcher rejects the call, i.e. no such function exists.
+    pub fn dispatch(&self, calldata: &[u8], value: u128) -> Option<VM> {
+        let mut vm = self.clone();
+        vm.calldata = calldata.to_vec();
+        vm.value = value;
+
+        let mut entry_point = vm.clone();
+        let mut dispatching = false;
+        while vm.bytecode.len() >= vm.instruction as usize {
+            // the function selected by the dispatcher begins at the last jump target
+            if vm.bytecode.get((vm.instruction - 1) as usize) == Some(&0x5b) {
+                entry_point = vm.clone();
+            }
+
+            let state = vm.step();
+            if vm.exitcode != 255 {
+                // the dispatcher reverts without data when there's no function to hand off to
+                return match (state.last_instruction.opcode, vm.returndata.is_empty()) {
+                    (0x00, _) | (0xf3, _) | (0xfd, false) => Some(entry_point),
+                    _ => None,
+                }
+            }
+
+            if state.last_instruction.opcode == 0x57 {
+                // jumps on the calldata size or selector belong to the dispatcher. before the
+                // dispatcher begins, solidity may also reject call value for the whole contract
+                let condition = state.last_instruction.input_operations[1].to_string();
+                if condition.contains("CALLDATASIZE") ||
+                    reads_selector(&state.last_instruction.input_operations[1])
+                {
+                    dispatching = true;
+                } else if dispatching || !condition.contains("CALLVALUE") {
+                    return Some(entry_point)
+                }
+                entry_point = vm.clone();
+            }
+        }
+
+        Some(entry_point)
+    }
+
+    /// Whether the contract rejects call value before its dispatcher runs. Solidity does this
+    /// instead of checking in each function when none of the contract's functions are payable.
+    pub fn rejects_call_value(&self) -> bool {
+        let mut vm = self.clone();
+        vm.value = 1;
+
+        // the check is the first conditional jump in the contract
+        while vm.bytecode.len() >= vm.instruction as usize && vm.exitcode == 255 {
+            let state = vm.step();
+            if state.last_instruction.opcode == 0x57 {
+                return state.last_instruction.input_operations[1].to_string().contains("CALLVALUE")
+            }
+        }
+
+        false
     }
 
     fn recursive_map(
         &mut self,
         branch_count: &mut u32,
         handled_jumps: &mut HashMap<(u128, U256, usize, bool), Vec<Stack>>,
+        memory_used: &mut usize,
+        depth: usize,
         logger: &Logger,
     ) -> VMTrace {
         let mut vm = self.clone();
@@ -80,6 +267,8 @@ impl VM {
             gas_used: 21000,
             operations: Vec::new(),
             children: Vec::new(),
+            pruned: false,
+            loop_jump: None,
         };
 
         // step through the bytecode until we find a JUMPI instruction
@@ -87,6 +276,7 @@ impl VM {
             let state = vm.step();
 
             // update vm_trace
+            *memory_used += estimate_state_size(&state);
             vm_trace.operations.push(state.clone());
             vm_trace.gas_used = vm.gas_used;
 
@@ -109,6 +299,32 @@ impl VM {
                     state.last_instruction.inputs[1].is_zero(),
                 );
 
+                // stop exploring new branches once the user has interrupted analysis
+                if is_interrupted() {
+                    return vm_trace
+                }
+
+                // stop exploring new branches once the memory budget is exhausted, pruning
+                // everything deeper than this path
+                if vm.limits.memory_limit > 0 && *memory_used > vm.limits.memory_limit {
+                    logger.debug_max(&format!(
+                        "pruning branches at instruction {}, memory budget exceeded",
+                        state.last_instruction.instruction
+                    ));
+                    vm_trace.pruned = true;
+                    return vm_trace
+                }
+
+                // stop exploring branches nested deeper than the branch depth limit
+                if vm.limits.max_branch_depth > 0 && depth >= vm.limits.max_branch_depth {
+                    logger.debug_max(&format!(
+                        "pruning branches at instruction {}, branch depth limit reached",
+                        state.last_instruction.instruction
+                    ));
+                    vm_trace.pruned = true;
+                    return vm_trace
+                }
+
                 // if the stack has over 16 items of the same source, it's probably a loop
                 if stack_contains_too_many_of_the_same_item(&vm.stack) {
                     return vm_trace
@@ -116,13 +332,22 @@ impl VM {
 
                 // if any item on the stack has a depth > 16, it's probably a loop (because of stack
                 // too deep)
-                if stack_item_source_depth_too_deep(&vm.stack) {
+                if stack_item_source_depth_too_deep(&vm.stack, vm.limits.max_call_depth) {
                     return vm_trace
                 }
 
                 // break out of loops
                 match handled_jumps.get_mut(&jump_frame) {
                     Some(historical_stacks) => {
+                        // stop once this jump has been revisited too many times
+                        if vm.limits.max_loop_iterations > 0 &&
+                            historical_stacks.len() >= vm.limits.max_loop_iterations
+                        {
+                            logger.debug_max("jump terminated, loop iteration limit reached.");
+                            vm_trace.loop_jump = Some(state.last_instruction.instruction);
+                            return vm_trace
+                        }
+
                         // for every stack that we have encountered for this jump, perform some
                         // heuristic checks to determine if this might be a loop
                         if historical_stacks.iter().any(|hist_stack| {
@@ -183,6 +408,7 @@ impl VM {
 
                             // this key exists, but the stack is different, so the jump is new
                             historical_stacks.push(vm.stack.clone());
+                            vm_trace.loop_jump = Some(state.last_instruction.instruction);
                             return vm_trace
                         }
 
@@ -199,6 +425,7 @@ impl VM {
 
                             // this key exists, but the stack is different, so the jump is new
                             historical_stacks.push(vm.stack.clone());
+                            vm_trace.loop_jump = Some(state.last_instruction.instruction);
                             return vm_trace
                         } else {
                             logger.debug_max(&format!(
@@ -240,11 +467,19 @@ impl VM {
                     vm_trace.children.push(trace_vm.recursive_map(
                         branch_count,
                         handled_jumps,
+                        memory_used,
+                        depth + 1,
                         logger,
                     ));
 
                     // push the current path onto the stack
-                    vm_trace.children.push(vm.recursive_map(branch_count, handled_jumps, logger));
+                    vm_trace.children.push(vm.recursive_map(
+                        branch_count,
+                        handled_jumps,
+                        memory_used,
+                        depth + 1,
+                        logger,
+                    ));
                     break
                 } else {
                     // push a new vm trace to the children
@@ -253,11 +488,19 @@ impl VM {
                     vm_trace.children.push(trace_vm.recursive_map(
                         branch_count,
                         handled_jumps,
+                        memory_used,
+                        depth + 1,
                         logger,
                     ));
 
                     // push the current path onto the stack
-                    vm_trace.children.push(vm.recursive_map(branch_count, handled_jumps, logger));
+                    vm_trace.children.push(vm.recursive_map(
+                        branch_count,
+                        handled_jumps,
+                        memory_used,
+                        depth + 1,
+                        logger,
+                    ));
                     break
                 }
             }
@@ -271,3 +514,88 @@ impl VM {
         vm_trace
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::VMTrace;
+    use crate::ether::evm::core::vm::VM;
+
+    // dispatches 0x12345678, an empty calldata receive, and a non-payable fallback
+    const BYTECODE: &str = "60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500";
+
+    fn new_test_vm() -> VM {
+        VM::new(
+            String::from(BYTECODE),
+            String::from("0x"),
+            String::from("0x6865696d64616c6c000000000061646472657373"),
+            String::from("0x6865696d64616c6c0000000000006f726967696e"),
+            String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+            0,
+            u128::MAX,
+        )
+    }
+
+    #[test]
+    fn test_symbolic_exec_without_memory_limit() {
+        let (trace, branch_count) = new_test_vm().symbolic_exec();
+
+        assert!(!trace.is_pruned());
+        assert!(branch_count > 0);
+    }
+
+    #[test]
+    fn test_branch_metrics() {
+        let leaf = || VMTrace {
+            instruction: 0,
+            gas_used: 0,
+            operations: Vec::new(),
+            children: Vec::new(),
+            pruned: false,
+            loop_jump: None,
+        };
+        let branch = |children: Vec<VMTrace>| VMTrace { children, ..leaf() };
+
+        // a linear function has a single path
+        assert_eq!(leaf().cyclomatic_complexity(), 1);
+        assert_eq!(leaf().max_nesting_depth(), 0);
+
+        // an if/else within an if/else
+        let trace = branch(vec![branch(vec![leaf(), leaf()]), leaf()]);
+        assert_eq!(trace.cyclomatic_complexity(), 3);
+        assert_eq!(trace.max_nesting_depth(), 2);
+
+        // every path of a real trace is independent
+        fn leaves(trace: &VMTrace) -> u32 {
+            match trace.children.is_empty() {
+                true => 1,
+                false => trace.children.iter().map(leaves).sum(),
+            }
+        }
+        let (trace, _) = new_test_vm().symbolic_exec();
+        assert_eq!(trace.cyclomatic_complexity(), leaves(&trace));
+        assert!(trace.max_nesting_depth() < trace.cyclomatic_complexity());
+    }
+
+    #[test]
+    fn test_symbolic_exec_prunes_at_memory_limit() {
+        let mut vm = new_test_vm();
+        vm.limits.memory_limit = 1;
+        let (trace, branch_count) = vm.symbolic_exec();
+
+        assert!(trace.is_pruned());
+        assert_eq!(branch_count, 0);
+        assert!(trace.children.is_empty());
+    }
+
+    #[test]
+    fn test_symbolic_exec_prunes_at_branch_depth() {
+        let mut vm = new_test_vm();
+        vm.limits.max_branch_depth = 1;
+        let (trace, branch_count) = vm.symbolic_exec();
+
+        // only the first branch is explored, and both of its paths stop at their next branch
+        assert!(trace.is_pruned());
+        assert_eq!(branch_count, 1);
+        assert!(trace.children.iter().all(|child| child.children.is_empty()));
+    }
+}
@@ -2,7 +2,10 @@ use ethers::types::U256;
 
 use crate::{
     constants::{MEMORY_REGEX, STORAGE_REGEX},
-    ether::evm::core::stack::{Stack, StackFrame},
+    ether::evm::core::{
+        stack::{Stack, StackFrame},
+        vm::State,
+    },
     utils::io::logging::Logger,
 };
 
@@ -42,11 +45,11 @@ pub fn stack_contains_too_many_of_the_same_item(stack: &Stack) -> bool {
     false
 }
 
-/// Check if the stack contains any item with a source operation depth > 16. If so, it is considered
-/// a loop. This check originates from the `stack too deep` error in Solidity due to the `DUP16` and
-/// `SWAP16` operation limitations.
-pub fn stack_item_source_depth_too_deep(stack: &Stack) -> bool {
-    if stack.stack.iter().any(|frame| frame.operation.depth() > 16) {
+/// Check if the stack contains any item with a source operation depth greater than `max_depth`,
+/// which is 16 by default. If so, it is considered a loop. This check originates from the `stack
+/// too deep` error in Solidity due to the `DUP16` and `SWAP16` operation limitations.
+pub fn stack_item_source_depth_too_deep(stack: &Stack, max_depth: u32) -> bool {
+    if stack.stack.iter().any(|frame| frame.operation.depth() > max_depth) {
         // get a new logger
         let logger = Logger::default();
 
@@ -168,3 +171,23 @@ pub fn jump_condition_historical_diffs_approximately_equal(
 
     true
 }
+
+/// Approximate the number of bytes a traced [`State`] occupies, counting its stack frames and
+/// their source operations, memory, storage, and events. Each state owns a full copy of these, so
+/// this grows quickly on contracts with large stacks or memory.
+pub fn estimate_state_size(state: &State) -> usize {
+    let operations = state
+        .stack
+        .stack
+        .iter()
+        .map(|frame| frame.operation.depth() as usize)
+        .chain(state.last_instruction.input_operations.iter().map(|op| op.depth() as usize))
+        .chain(state.last_instruction.output_operations.iter().map(|op| op.depth() as usize))
+        .sum::<usize>();
+
+    std::mem::size_of::<State>() +
+        operations * std::mem::size_of::<StackFrame>() +
+        state.memory.held() +
+        state.storage.storage.len() * 64 +
+        state.events.iter().map(|log| log.topics.len() * 32 + log.data.len()).sum::<usize>()
+}
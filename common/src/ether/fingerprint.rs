@@ -0,0 +1,150 @@
+use std::collections::{BTreeSet, HashSet};
+
+use ethers::utils::keccak256;
+use serde::{Deserialize, Serialize};
+
+use crate::{ether::metadata::decode_metadata, utils::strings::decode_hex};
+
+/// How much the similarity of two contracts' code counts towards their overall similarity, with
+/// the rest from the similarity of their selectors.
+const BLOCK_WEIGHT: f64 = 0.7;
+
+/// A fingerprint of a contract's bytecode which changes little between forks and redeployments
+/// of the same source: the hashes of its basic blocks, normalized so they don't depend on where
+/// the block is, or on addresses and immutables written into it, along with its selectors.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub blocks: BTreeSet<u64>,
+    pub selectors: BTreeSet<String>,
+}
+
+/// How similar two fingerprints are, from 0 to 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Similarity {
+    /// The share of basic blocks the contracts have in common.
+    pub blocks: f64,
+    /// The share of selectors the contracts have in common.
+    pub selectors: f64,
+    /// The weighted similarity of their blocks and selectors, or of their blocks alone if
+    /// neither has selectors.
+    pub score: f64,
+}
+
+/// The Jaccard similarity of two sets, which is 1 if both are empty.
+fn jaccard<T: Ord>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> f64 {
+    let union = a.union(b).count();
+    match union {
+        0 => 1.0,
+        union => a.intersection(b).count() as f64 / union as f64,
+    }
+}
+
+/// Hash a normalized basic block, with a hash which is stable across versions of heimdall, since
+/// fingerprints are stored.
+fn hash_block(block: &[u8]) -> u64 {
+    let hash = keccak256(block);
+    u64::from_be_bytes(hash[..8].try_into().expect("keccak256 hashes are 32 bytes"))
+}
+
+impl Fingerprint {
+    /// Fingerprint the bytecode, given the selectors found in its dispatcher. The compiler
+    /// metadata trailer is ignored, as are the operands of `PUSH` instructions which are jump
+    /// targets, addresses, or 32-byte immutables, since they differ between otherwise identical
+    /// deployments.
+    ///
+    /// ```
+    /// use heimdall_common::ether::fingerprint::Fingerprint;
+    ///
+    /// // the same code, with a different address pushed, and its jump target shifted
+    /// let a = Fingerprint::new("600456005b73000000000000000000000000000000000000000100", Vec::new());
+    /// let b = Fingerprint::new("60055600005b73000000000000000000000000000000000000000200", Vec::new());
+    /// assert_eq!(a.similarity(&b).score, 1.0);
+    /// ```
+    pub fn new(bytecode: &str, selectors: Vec<String>) -> Self {
+        let bytecode = bytecode.trim_start_matches("0x");
+        let mut bytes = decode_hex(bytecode).unwrap_or_default();
+        let metadata_size = decode_metadata(bytecode).map(|metadata| metadata.size).unwrap_or(0);
+        bytes.truncate(bytes.len().saturating_sub(metadata_size));
+
+        // find each instruction, along with the program counter of each jump destination
+        let mut instructions = Vec::new();
+        let mut pc = 0;
+        while pc < bytes.len() {
+            let opcode = bytes[pc];
+            let size = match opcode {
+                0x60..=0x7f => (opcode - 0x5f) as usize,
+                _ => 0,
+            };
+            let operand = &bytes[(pc + 1).min(bytes.len())..(pc + 1 + size).min(bytes.len())];
+            instructions.push((pc, opcode, operand));
+            pc += 1 + size;
+        }
+        let jumpdests = instructions
+            .iter()
+            .filter(|(_, opcode, _)| *opcode == 0x5b)
+            .map(|(pc, ..)| *pc as u128)
+            .collect::<HashSet<u128>>();
+
+        let mut blocks = BTreeSet::new();
+        let mut block = Vec::new();
+        for (_, opcode, operand) in instructions {
+            if opcode == 0x5b && !block.is_empty() {
+                blocks.insert(hash_block(&block));
+                block.clear();
+            }
+
+            block.push(opcode);
+            let is_jumpdest = operand.len() <= 16 &&
+                jumpdests.contains(
+                    &operand.iter().fold(0u128, |value, byte| (value << 8) | *byte as u128),
+                );
+            if !(is_jumpdest || opcode == 0x73 || opcode == 0x7f) {
+                block.extend_from_slice(operand);
+            }
+
+            // halting and jumping instructions end the block
+            if matches!(opcode, 0x00 | 0x56 | 0x57 | 0xf3 | 0xfd | 0xfe | 0xff) {
+                blocks.insert(hash_block(&block));
+                block.clear();
+            }
+        }
+        if !block.is_empty() {
+            blocks.insert(hash_block(&block));
+        }
+
+        Self { blocks, selectors: selectors.into_iter().collect() }
+    }
+
+    /// How similar this fingerprint is to another.
+    pub fn similarity(&self, other: &Fingerprint) -> Similarity {
+        let blocks = jaccard(&self.blocks, &other.blocks);
+        let selectors = jaccard(&self.selectors, &other.selectors);
+        let score = match self.selectors.is_empty() && other.selectors.is_empty() {
+            true => blocks,
+            false => BLOCK_WEIGHT * blocks + (1.0 - BLOCK_WEIGHT) * selectors,
+        };
+
+        Similarity { blocks, selectors, score }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_similarity() {
+        let selectors = |selectors: &[&str]| selectors.iter().map(|s| s.to_string()).collect();
+        let a =
+            Fingerprint::new("6001600055005b600260005500", selectors(&["a9059cbb", "70a08231"]));
+        let b = Fingerprint::new("0x6001600055005b600360005500", selectors(&["a9059cbb"]));
+
+        assert_eq!(a.similarity(&a).score, 1.0);
+
+        // one of three distinct blocks, and one of two selectors, are shared
+        let similarity = a.similarity(&b);
+        assert_eq!(similarity.blocks, 1.0 / 3.0);
+        assert_eq!(similarity.selectors, 0.5);
+        assert!((similarity.score - (0.7 / 3.0 + 0.15)).abs() < 1e-9);
+    }
+}
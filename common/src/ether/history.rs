@@ -0,0 +1,275 @@
+use serde_json::{json, Value};
+
+use crate::{ether::rpc::rpc_request, utils::io::logging::Logger};
+
+/// RPC providers which offer extended APIs for fetching an address's transaction history.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HistoryProvider {
+    Alchemy,
+    QuickNode,
+}
+
+/// The maximum number of results requested per page of transaction history.
+const PAGE_SIZE: u64 = 1000;
+
+/// Detect whether the given RPC URL belongs to a provider with an extended history API.
+///
+/// ```
+/// use heimdall_common::ether::history::{detect_history_provider, HistoryProvider};
+///
+/// assert_eq!(
+///     detect_history_provider("https://eth-mainnet.g.alchemy.com/v2/KEY"),
+///     Some(HistoryProvider::Alchemy)
+/// );
+/// assert_eq!(
+///     detect_history_provider("https://example.quiknode.pro/KEY/"),
+///     Some(HistoryProvider::QuickNode)
+/// );
+/// assert_eq!(detect_history_provider("http://localhost:8545"), None);
+/// ```
+pub fn detect_history_provider(rpc_url: &str) -> Option<HistoryProvider> {
+    let host = rpc_url.split("://").last()?.split('/').next()?.to_lowercase();
+    if host.ends_with("alchemy.com") || host.ends_with("alchemyapi.io") {
+        Some(HistoryProvider::Alchemy)
+    } else if host.ends_with("quiknode.pro") || host.ends_with("quicknode.com") {
+        Some(HistoryProvider::QuickNode)
+    } else {
+        None
+    }
+}
+
+/// Parse a block number, which providers return as either a hex or decimal string, or a number.
+fn parse_block_number(value: &Value) -> Option<u128> {
+    match value {
+        Value::String(number) => match number.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => number.parse().ok(),
+        },
+        number => number.as_u64().map(|number| number as u128),
+    }
+}
+
+/// Parse the `(block_number, transaction_hash)` pairs from a page of `alchemy_getAssetTransfers`
+/// results, returning them with the key of the next page, if any.
+pub fn parse_alchemy_transfers(result: &Value) -> (Vec<(u128, String)>, Option<String>) {
+    let transactions = result
+        .get("transfers")
+        .and_then(|transfers| transfers.as_array())
+        .map(|transfers| {
+            transfers
+                .iter()
+                .filter_map(|transfer| {
+                    Some((
+                        parse_block_number(transfer.get("blockNum")?)?,
+                        transfer.get("hash")?.as_str()?.to_string(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let page_key = result.get("pageKey").and_then(|key| key.as_str()).map(String::from);
+
+    (transactions, page_key)
+}
+
+/// Parse the `(block_number, transaction_hash)` pairs from a page of `qn_getTransactionsByAddress`
+/// results, returning them with the total number of pages.
+pub fn parse_quicknode_transactions(result: &Value) -> (Vec<(u128, String)>, u64) {
+    let transactions = result
+        .get("paginatedItems")
+        .and_then(|items| items.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    Some((
+                        parse_block_number(item.get("blockNumber")?)?,
+                        item.get("transactionHash")?.as_str()?.to_string(),
+                    ))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let total_pages = result.get("totalPages").and_then(|pages| pages.as_u64()).unwrap_or(1);
+
+    (transactions, total_pages)
+}
+
+/// Fetch the transactions which transferred assets to the address, or which transferred the
+/// address's own tokens, with `alchemy_getAssetTransfers`.
+async fn get_alchemy_transaction_list(
+    rpc_url: &str,
+    address: &str,
+    bounds: (&u128, &u128),
+) -> Result<Vec<(u128, String)>, Box<dyn std::error::Error>> {
+    let mut transactions = Vec::new();
+
+    let filters = [
+        json!({ "toAddress": address, "category": ["external", "internal"] }),
+        json!({ "contractAddresses": [address], "category": ["erc20", "erc721", "erc1155"] }),
+    ];
+    for filter in filters {
+        let mut page_key: Option<String> = None;
+        loop {
+            let mut params = filter.clone();
+            params["fromBlock"] = json!(format!("{:#x}", bounds.0));
+            params["toBlock"] = json!(format!("{:#x}", (*bounds.1).min(u64::MAX as u128)));
+            params["maxCount"] = json!(format!("{PAGE_SIZE:#x}"));
+            params["excludeZeroValue"] = json!(false);
+            if let Some(page_key) = &page_key {
+                params["pageKey"] = json!(page_key);
+            }
+
+            let result = rpc_request(rpc_url, "alchemy_getAssetTransfers", json!([params])).await?;
+            let (page, next_page_key) = parse_alchemy_transfers(&result);
+            transactions.extend(page);
+
+            match next_page_key {
+                Some(next_page_key) => page_key = Some(next_page_key),
+                None => break,
+            }
+        }
+    }
+
+    Ok(transactions)
+}
+
+/// Fetch the transactions involving the address with `qn_getTransactionsByAddress`.
+async fn get_quicknode_transaction_list(
+    rpc_url: &str,
+    address: &str,
+    bounds: (&u128, &u128),
+) -> Result<Vec<(u128, String)>, Box<dyn std::error::Error>> {
+    let mut transactions = Vec::new();
+
+    let mut page = 1;
+    loop {
+        let result = rpc_request(
+            rpc_url,
+            "qn_getTransactionsByAddress",
+            json!([{ "address": address, "page": page, "perPage": 100 }]),
+        )
+        .await?;
+        let (items, total_pages) = parse_quicknode_transactions(&result);
+        transactions
+            .extend(items.into_iter().filter(|(block, _)| block >= bounds.0 && block <= bounds.1));
+
+        if page >= total_pages {
+            break
+        }
+        page += 1;
+    }
+
+    Ok(transactions)
+}
+
+/// Get the transactions which interacted with the given address using the RPC provider's extended
+/// history API, sorted by block number. Returns `None` if the provider doesn't offer one, or if
+/// the request fails.
+///
+/// ```no_run
+/// use heimdall_common::ether::history::get_transaction_list;
+///
+/// // let transactions = get_transaction_list("https://eth-mainnet.g.alchemy.com/v2/KEY", "0x0", (&0, &1)).await;
+/// ```
+pub async fn get_transaction_list(
+    rpc_url: &str,
+    address: &str,
+    bounds: (&u128, &u128),
+) -> Option<Vec<(u128, String)>> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let result = match detect_history_provider(rpc_url)? {
+        HistoryProvider::Alchemy => get_alchemy_transaction_list(rpc_url, address, bounds).await,
+        HistoryProvider::QuickNode => {
+            get_quicknode_transaction_list(rpc_url, address, bounds).await
+        }
+    };
+
+    match result {
+        Ok(mut transactions) => {
+            transactions.sort();
+            transactions.dedup();
+            Some(transactions)
+        }
+        Err(e) => {
+            logger.debug(&format!("failed to fetch transaction history for '{address}': {e}"));
+            None
+        }
+    }
+}
+
+/// Get the contract creation block and transaction hash for the given address. The creation block
+/// is found by binary searching `eth_getCode`, which requires historical state, and the creating
+/// transaction is found within it with `trace_block`.
+///
+/// ```no_run
+/// use heimdall_common::ether::history::get_contract_creation;
+///
+/// // let creation = get_contract_creation("https://eth-mainnet.g.alchemy.com/v2/KEY", "0x0").await;
+/// ```
+pub async fn get_contract_creation(rpc_url: &str, address: &str) -> Option<(u128, String)> {
+    let has_code = |block: u64| async move {
+        rpc_request(rpc_url, "eth_getCode", json!([address, format!("{block:#x}")]))
+            .await
+            .ok()
+            .and_then(|code| code.as_str().map(|code| code.len() > 2))
+    };
+
+    // find the first block at which the address has code
+    let latest = rpc_request(rpc_url, "eth_blockNumber", json!([])).await.ok()?;
+    let (mut low, mut high) = (0, parse_block_number(&latest)? as u64);
+    if !has_code(high).await? {
+        return None
+    }
+    while low < high {
+        let middle = low + (high - low) / 2;
+        match has_code(middle).await? {
+            true => high = middle,
+            false => low = middle + 1,
+        }
+    }
+
+    // find the transaction which created the address within that block
+    let traces = rpc_request(rpc_url, "trace_block", json!([format!("{low:#x}")])).await.ok()?;
+    traces.as_array()?.iter().find_map(|trace| {
+        let created = trace.get("result")?.get("address")?.as_str()?;
+        match trace.get("type")?.as_str()? == "create" && created.eq_ignore_ascii_case(address) {
+            true => Some((low as u128, trace.get("transactionHash")?.as_str()?.to_string())),
+            false => None,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_alchemy_transfers() {
+        let result = json!({
+            "transfers": [
+                { "blockNum": "0x10", "hash": "0xaa" },
+                { "blockNum": "0x11", "hash": "0xbb" }
+            ],
+            "pageKey": "next"
+        });
+
+        let (transactions, page_key) = parse_alchemy_transfers(&result);
+        assert_eq!(transactions, vec![(16, String::from("0xaa")), (17, String::from("0xbb"))]);
+        assert_eq!(page_key, Some(String::from("next")));
+    }
+
+    #[test]
+    fn test_parse_quicknode_transactions() {
+        let result = json!({
+            "paginatedItems": [{ "blockNumber": "123", "transactionHash": "0xcc" }],
+            "totalPages": 3
+        });
+
+        let (transactions, total_pages) = parse_quicknode_transactions(&result);
+        assert_eq!(transactions, vec![(123, String::from("0xcc"))]);
+        assert_eq!(total_pages, 3);
+    }
+}
@@ -0,0 +1,259 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::RwLock};
+
+use ethers::utils::keccak256;
+use heimdall_cache::{read_cache, store_cache};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+
+use crate::utils::{http::get_text_from_url, io::file::write_file, strings::encode_hex};
+
+/// The token list bundled with heimdall, in the Uniswap token list format.
+pub static DEFAULT_TOKEN_LIST: &str = include_str!("token_list.json");
+
+/// Well-known protocol contracts on Ethereum mainnet, which aren't tokens.
+pub const WELL_KNOWN_CONTRACTS: [(&str, &str); 12] = [
+    ("0x7a250d5630b4cf539739df2c5dacb4c659f2488d", "UniswapV2Router02"),
+    ("0x5c69bee701ef814a2b6a3edd4b1652cb9cc5aa6f", "UniswapV2Factory"),
+    ("0x1f98431c8ad98523631ae4a59f267346ea31f984", "UniswapV3Factory"),
+    ("0xe592427a0aece92de3edee1f18e0157c05861564", "UniswapV3SwapRouter"),
+    ("0x68b3465833fb72a70ecdf485e0e4c7bd8665fc45", "UniswapV3SwapRouter02"),
+    ("0x3fc91a3afd70395cd496c647d5a6cc9d4b2b7fad", "UniversalRouter"),
+    ("0x000000000022d473030f116ddee9f6b43ac78ba3", "Permit2"),
+    ("0x00000000000000adc04c56bf30ac9d3c0aaf14dc", "Seaport"),
+    ("0x1111111254eeb25477b68fb85ed929f73a960582", "1inchRouterV5"),
+    ("0x87870bca3f3fd6335c3f4ce8392d69350b4fa4e2", "AaveV3Pool"),
+    ("0xba12222222228d8ba445958a75a0704d566bf2c8", "BalancerVault"),
+    ("0xca11bde05977b3631167028862be2a173976ca11", "Multicall3"),
+];
+
+/// How long fetched token lists are cached for, in seconds.
+const TOKEN_LIST_EXPIRY: u64 = 60 * 60 * 24;
+
+lazy_static! {
+    static ref TOKENS: RwLock<BTreeMap<String, TokenInfo>> =
+        RwLock::new(parse_token_list(DEFAULT_TOKEN_LIST).unwrap_or_default());
+    static ref LABELS: RwLock<BTreeMap<String, String>> = RwLock::new(default_labels());
+}
+
+/// The details of a token from a token list.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TokenInfo {
+    pub symbol: String,
+    #[serde(default)]
+    pub decimals: Option<u8>,
+}
+
+/// A token within a token list. Only the fields needed for labelling are read.
+#[derive(Deserialize)]
+struct TokenListEntry {
+    address: String,
+    #[serde(flatten)]
+    info: TokenInfo,
+}
+
+#[derive(Deserialize)]
+struct TokenList {
+    tokens: Vec<TokenListEntry>,
+}
+
+/// Parse a token list in the Uniswap token list format, mapping each token's lowercased address
+/// to its symbol and decimals.
+///
+/// ```
+/// use heimdall_common::ether::labels::parse_token_list;
+///
+/// let tokens = parse_token_list(r#"{"name": "tokens", "tokens": [{"chainId": 1, "address": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48", "symbol": "USDC", "decimals": 6}]}"#).unwrap();
+/// let token = tokens.get("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48").unwrap();
+/// assert_eq!((token.symbol.as_str(), token.decimals), ("USDC", Some(6)));
+/// ```
+pub fn parse_token_list(contents: &str) -> Result<BTreeMap<String, TokenInfo>, serde_json::Error> {
+    let token_list: TokenList = serde_json::from_str(contents)?;
+    Ok(token_list
+        .tokens
+        .into_iter()
+        .map(|token| (token.address.to_lowercase(), token.info))
+        .collect())
+}
+
+/// The labels every address is looked up in: the bundled token list and well-known contracts,
+/// overridden by the user's address book.
+fn default_labels() -> BTreeMap<String, String> {
+    let mut labels = parse_token_list(DEFAULT_TOKEN_LIST)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(address, token)| (address, token.symbol))
+        .collect::<BTreeMap<String, String>>();
+    labels.extend(
+        WELL_KNOWN_CONTRACTS
+            .iter()
+            .map(|(address, label)| (address.to_string(), label.to_string())),
+    );
+    labels.extend(read_address_book());
+    labels
+}
+
+/// Read a token list from a file, or fetch it from a URL. Fetched lists are cached for a day.
+pub async fn load_token_list(
+    source: &str,
+) -> Result<BTreeMap<String, TokenInfo>, Box<dyn std::error::Error>> {
+    if !source.starts_with("http://") && !source.starts_with("https://") {
+        return Ok(parse_token_list(&std::fs::read_to_string(source)?)?)
+    }
+
+    let cache_key = format!("token_list.{}", &encode_hex(keccak256(source).to_vec())[..16]);
+    let contents = match read_cache::<String>(&cache_key) {
+        Some(contents) => contents,
+        None => {
+            let contents = get_text_from_url(source, 10)
+                .await?
+                .ok_or(format!("failed to fetch '{source}'"))?;
+            store_cache(&cache_key, contents.clone(), Some(TOKEN_LIST_EXPIRY));
+            contents
+        }
+    };
+
+    Ok(parse_token_list(&contents)?)
+}
+
+/// Label addresses with the given token list, in addition to the bundled one. Labels from the
+/// user's address book take precedence.
+pub fn add_token_list(token_list: BTreeMap<String, TokenInfo>) {
+    if let Ok(mut labels) = LABELS.write() {
+        labels.extend(
+            token_list.iter().map(|(address, token)| (address.clone(), token.symbol.clone())),
+        );
+        labels.extend(read_address_book());
+    }
+    if let Ok(mut tokens) = TOKENS.write() {
+        tokens.extend(token_list);
+    }
+}
+
+/// Get the details of the token at the given address from the token lists, if it's listed.
+pub fn get_token(address: &str) -> Option<TokenInfo> {
+    TOKENS.read().ok()?.get(&address.to_lowercase()).cloned()
+}
+
+#[allow(deprecated)]
+/// The path of the address book, at `$HOME/.bifrost/labels.json`.
+pub fn address_book_path() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".bifrost").join("labels.json"))
+}
+
+/// Read the user's address book, which maps lowercased addresses to their labels. A missing or
+/// malformed address book is treated as empty.
+pub fn read_address_book() -> BTreeMap<String, String> {
+    address_book_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Label the given address in the user's address book, replacing any existing label. An empty
+/// label removes the address from the address book.
+pub fn set_label(address: &str, label: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = address_book_path().ok_or("couldn't resolve the $HOME directory")?;
+
+    let mut address_book = read_address_book();
+    match label.is_empty() {
+        true => address_book.remove(&address.to_lowercase()),
+        false => address_book.insert(address.to_lowercase(), label.to_string()),
+    };
+
+    write_file(&path.to_string_lossy(), &serde_json::to_string_pretty(&address_book)?);
+    Ok(())
+}
+
+/// Get the label of the given address from the user's address book or the token lists, if it
+/// has one.
+pub fn get_label(address: &str) -> Option<String> {
+    LABELS.read().ok()?.get(&address.to_lowercase()).cloned()
+}
+
+/// Replace each labelled address within the text with the result of `format(address, label)`.
+/// Addresses are `0x`-prefixed runs of exactly 40 hex characters.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use heimdall_common::ether::labels::replace_addresses;
+///
+/// let address_book = BTreeMap::from([(
+///     String::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+///     String::from("WETH"),
+/// )]);
+///
+/// assert_eq!(
+///     replace_addresses("call(0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2)", &address_book, |_, label| label.to_string()),
+///     "call(WETH)"
+/// );
+/// ```
+pub fn replace_addresses(
+    text: &str,
+    address_book: &BTreeMap<String, String>,
+    format: impl Fn(&str, &str) -> String,
+) -> String {
+    if address_book.is_empty() {
+        return text.to_string()
+    }
+
+    let bytes = text.as_bytes();
+    let mut output = String::with_capacity(text.len());
+    let mut index = 0;
+    while index < text.len() {
+        let hex_length =
+            bytes[index..].iter().skip(2).take_while(|byte| byte.is_ascii_hexdigit()).count();
+        let is_address = text[index..].starts_with("0x") &&
+            hex_length == 40 &&
+            (index == 0 || !bytes[index - 1].is_ascii_alphanumeric());
+
+        if is_address {
+            let address = &text[index..index + 42];
+            match address_book.get(&address.to_lowercase()) {
+                Some(label) => output.push_str(&format(address, label)),
+                None => output.push_str(address),
+            }
+            index += 42;
+        } else {
+            // skip past the whole run of hex characters, so words aren't split
+            let length = match text[index..].starts_with("0x") {
+                true => 2 + hex_length,
+                false => text[index..].chars().next().map(|c| c.len_utf8()).unwrap_or(1),
+            };
+            output.push_str(&text[index..index + length]);
+            index += length;
+        }
+    }
+
+    output
+}
+
+/// Replace each labelled address within the text with its label from the user's address book or
+/// the token lists, followed by the address itself, such as `USDC (0xa0b8...)`.
+///
+/// ```
+/// use heimdall_common::ether::labels::substitute_labels;
+///
+/// assert_eq!(
+///     substitute_labels("to: 0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"),
+///     "to: USDC (0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48)"
+/// );
+/// ```
+pub fn substitute_labels(text: &str) -> String {
+    match LABELS.read() {
+        Ok(labels) => {
+            replace_addresses(text, &labels, |address, label| format!("{label} ({address})"))
+        }
+        Err(_) => text.to_string(),
+    }
+}
+
+/// Annotate each labelled address within the text with a trailing comment containing its label,
+/// keeping the address itself so source code remains valid.
+pub fn annotate_labels(text: &str) -> String {
+    match LABELS.read() {
+        Ok(labels) => {
+            replace_addresses(text, &labels, |address, label| format!("{address} /* {label} */"))
+        }
+        Err(_) => text.to_string(),
+    }
+}
@@ -4,17 +4,12 @@ use ethers::types::U256;
 
 use crate::{
     constants::{MEMLEN_REGEX, WORD_REGEX},
-    ether::evm::core::opcodes::*,
+    ether::evm::core::{opcodes::*, precompiles::Precompile},
     utils::strings::encode_hex_reduced,
 };
 
 pub fn is_ext_call_precompile(precompile_address: U256) -> bool {
-    let address: usize = match precompile_address.try_into() {
-        Ok(x) => x,
-        Err(_) => usize::MAX,
-    };
-
-    matches!(address, 1..=3)
+    Precompile::from_address(precompile_address).is_some()
 }
 
 impl WrappedOpcode {
@@ -177,6 +172,15 @@ impl WrappedOpcode {
                         .as_str(),
                 );
             }
+            "SIGNEXTEND" => {
+                // sign extending from byte `b` casts the value to a signed integer of `b + 1` bytes
+                let size = match U256::from_str(&self.inputs[0]._solidify()) {
+                    Ok(byte) if byte < U256::from(31) => (byte.as_usize() + 1) * 8,
+                    _ => 256,
+                };
+                solidified_wrapped_opcode
+                    .push_str(&format!("int{size}({})", self.inputs[1]._solidify()));
+            }
             "BYTE" => {
                 solidified_wrapped_opcode.push_str(self.inputs[1]._solidify().as_str());
             }
@@ -434,7 +438,9 @@ mod tests {
         assert!(is_ext_call_precompile(U256::from(1)));
         assert!(is_ext_call_precompile(U256::from(2)));
         assert!(is_ext_call_precompile(U256::from(3)));
-        assert!(!is_ext_call_precompile(U256::from(4)));
+        assert!(is_ext_call_precompile(U256::from(9)));
+        assert!(!is_ext_call_precompile(U256::from(0)));
+        assert!(!is_ext_call_precompile(U256::from(10)));
         assert!(!is_ext_call_precompile(U256::MAX));
     }
 
@@ -656,6 +662,15 @@ mod tests {
         assert_eq!(wrapped_opcode.solidify(), "0x01 >> 0x06");
     }
 
+    #[test]
+    fn test_wrapped_opcode_solidify_signextend() {
+        let opcode = Opcode { code: 0x0b, name: "SIGNEXTEND", mingas: 5, inputs: 2, outputs: 1 };
+        let inputs = vec![WrappedInput::Raw(U256::from(1u8)), WrappedInput::Raw(U256::from(255u8))];
+        let wrapped_opcode = WrappedOpcode { opcode, inputs };
+
+        assert_eq!(wrapped_opcode.solidify(), "int16(0xff)");
+    }
+
     #[test]
     fn test_wrapped_opcode_solidify_byte() {
         let opcode = Opcode { code: 0x1d, name: "BYTE", mingas: 1, inputs: 2, outputs: 1 };
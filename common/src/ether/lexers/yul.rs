@@ -8,9 +8,14 @@ impl WrappedOpcode {
         } else if self.opcode.name.starts_with("PUSH") {
             self.inputs[0]._yulify()
         } else {
+            // opcodes share their names with yul builtins, other than the renamed SHA3
+            let builtin = match self.opcode.name {
+                "SHA3" => "keccak256".to_string(),
+                name => name.to_lowercase(),
+            };
             format!(
                 "{}({})",
-                self.opcode.name.to_lowercase(),
+                builtin,
                 self.inputs.iter().map(|input| input._yulify()).collect::<Vec<String>>().join(", ")
             )
         }
@@ -49,6 +54,15 @@ mod tests {
         assert_eq!(add_operation_wrapped.yulify(), "0");
     }
 
+    #[test]
+    fn test_yulify_sha3() {
+        let sha3_operation_wrapped = WrappedOpcode::new(
+            0x20,
+            vec![WrappedInput::Raw(U256::from(0u8)), WrappedInput::Raw(U256::from(32u8))],
+        );
+        assert_eq!(sha3_operation_wrapped.yulify(), "keccak256(0, 0x20)");
+    }
+
     #[test]
     fn test_yulify_add() {
         // wraps an ADD operation with 2 raw inputs
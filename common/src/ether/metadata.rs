@@ -0,0 +1,604 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::Abi,
+    core::k256::sha2::{Digest, Sha256},
+};
+use heimdall_cache::{read_cache, store_cache};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    ether::signatures::ResolvedFunction,
+    utils::{
+        http::get_text_from_url,
+        io::logging::Logger,
+        strings::{decode_hex, encode_hex},
+    },
+};
+
+/// The decoded CBOR metadata which solc and vyper append to the end of runtime bytecode.
+/// <https://docs.soliditylang.org/en/latest/metadata.html#encoding-of-the-metadata-hash-in-the-bytecode>
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ContractMetadata {
+    /// The solc version which compiled the contract, if present.
+    pub solc: Option<String>,
+    /// The vyper version which compiled the contract, if present.
+    pub vyper: Option<String>,
+    /// The IPFS CIDv0 of the contract's metadata file, if present.
+    pub ipfs: Option<String>,
+    /// The swarm hash of the contract's metadata file, if present.
+    pub bzzr0: Option<String>,
+    /// The swarm hash of the contract's metadata file, if present.
+    pub bzzr1: Option<String>,
+    /// Whether the contract was compiled with experimental features enabled.
+    pub experimental: bool,
+    /// The size of the metadata trailer in bytes, including the two byte length suffix.
+    pub size: usize,
+}
+
+impl ContractMetadata {
+    /// Returns the compiler and version which produced this metadata, if present.
+    pub fn compiler(&self) -> Option<(&'static str, String)> {
+        match (&self.solc, &self.vyper) {
+            (Some(version), _) => Some(("solc", version.to_owned())),
+            (_, Some(version)) => Some(("vyper", version.to_owned())),
+            _ => None,
+        }
+    }
+
+    /// Returns the decoded metadata as a list of (name, value) pairs, for display.
+    pub fn fields(&self) -> Vec<(String, String)> {
+        let mut fields = Vec::new();
+
+        if let Some((compiler, version)) = self.compiler() {
+            fields.push((String::from("compiler"), format!("{compiler} {version}")));
+        }
+        if let Some(ipfs) = &self.ipfs {
+            fields.push((String::from("metadata"), format!("ipfs://{ipfs}")));
+        }
+        if let Some(bzzr0) = &self.bzzr0 {
+            fields.push((String::from("metadata"), format!("bzzr0://{bzzr0}")));
+        }
+        if let Some(bzzr1) = &self.bzzr1 {
+            fields.push((String::from("metadata"), format!("bzzr1://{bzzr1}")));
+        }
+        if self.experimental {
+            fields.push((String::from("experimental"), String::from("true")));
+        }
+
+        fields
+    }
+}
+
+/// A minimal representation of the CBOR values which may appear in contract metadata.
+#[derive(Debug, Clone, PartialEq)]
+enum CborValue {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    Map(Vec<(CborValue, CborValue)>),
+    Bool(bool),
+}
+
+/// Decode a single CBOR value from the given bytes, returning the value and the number of bytes
+/// consumed. Only the subset of CBOR used by compilers is supported.
+fn decode_cbor(bytes: &[u8]) -> Option<(CborValue, usize)> {
+    let initial = *bytes.first()?;
+    let major_type = initial >> 5;
+    let additional = initial & 0x1f;
+
+    // read the argument of this item, which is either a length or a value
+    let (argument, mut offset) = match additional {
+        0..=23 => (additional as u64, 1),
+        24..=27 => {
+            let length = 1 << (additional - 24);
+            let argument =
+                bytes.get(1..1 + length)?.iter().fold(0u64, |acc, byte| (acc << 8) | *byte as u64);
+            (argument, 1 + length)
+        }
+        _ => return None,
+    };
+
+    let value = match major_type {
+        0 => CborValue::Uint(argument),
+        2 | 3 => {
+            let data = bytes.get(offset..offset.checked_add(argument as usize)?)?.to_vec();
+            offset += argument as usize;
+
+            if major_type == 2 {
+                CborValue::Bytes(data)
+            } else {
+                CborValue::Text(String::from_utf8(data).ok()?)
+            }
+        }
+        4 => {
+            let mut items = Vec::new();
+            for _ in 0..argument {
+                let (item, consumed) = decode_cbor(bytes.get(offset..)?)?;
+                items.push(item);
+                offset += consumed;
+            }
+            CborValue::Array(items)
+        }
+        5 => {
+            let mut entries = Vec::new();
+            for _ in 0..argument {
+                let (key, consumed) = decode_cbor(bytes.get(offset..)?)?;
+                offset += consumed;
+                let (value, consumed) = decode_cbor(bytes.get(offset..)?)?;
+                offset += consumed;
+                entries.push((key, value));
+            }
+            CborValue::Map(entries)
+        }
+        7 => match additional {
+            20 => CborValue::Bool(false),
+            21 => CborValue::Bool(true),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some((value, offset))
+}
+
+/// Encode the given bytes as a base58 string, as used by IPFS CIDv0s.
+fn encode_base58(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+    let mut digits: Vec<u8> = Vec::new();
+    for byte in bytes {
+        let mut carry = *byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    // leading zero bytes are encoded as '1'
+    let leading_zeros = bytes.iter().take_while(|byte| **byte == 0).count();
+
+    let mut encoded = "1".repeat(leading_zeros);
+    encoded.extend(digits.iter().rev().map(|digit| ALPHABET[*digit as usize] as char));
+    encoded
+}
+
+/// The size of the chunks files are split into when added to IPFS.
+const IPFS_CHUNK_SIZE: usize = 256 * 1024;
+
+/// The maximum number of links in each node of an IPFS file's DAG.
+const IPFS_MAX_LINKS: usize = 174;
+
+/// Append a protobuf varint to the buffer.
+fn write_varint(buffer: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        buffer.push((value as u8 & 0x7f) | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Append a length-delimited protobuf field to the buffer.
+fn write_bytes_field(buffer: &mut Vec<u8>, tag: u8, bytes: &[u8]) {
+    buffer.push(tag);
+    write_varint(buffer, bytes.len());
+    buffer.extend_from_slice(bytes);
+}
+
+/// A node of an IPFS file's DAG: its multihash, the size of the file contents beneath it, and the
+/// total size of the encoded blocks beneath it.
+struct IpfsNode {
+    hash: Vec<u8>,
+    size: usize,
+    block_size: usize,
+}
+
+/// Hash an encoded dag-pb block into a node, given the sizes beneath it.
+fn ipfs_node(block: Vec<u8>, size: usize, child_block_size: usize) -> IpfsNode {
+    let mut hash = vec![0x12, 0x20];
+    hash.extend_from_slice(&Sha256::digest(&block));
+    IpfsNode { hash, size, block_size: child_block_size + block.len() }
+}
+
+/// Compute the IPFS CIDv0 of a file, as `ipfs add` and solc do: the file is split into 256 KiB
+/// UnixFS chunks, which are linked into a balanced DAG of dag-pb nodes.
+///
+/// ```
+/// use heimdall_common::ether::metadata::ipfs_hash;
+///
+/// assert_eq!(ipfs_hash(b""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+/// ```
+pub fn ipfs_hash(contents: &[u8]) -> String {
+    let mut level = contents
+        .chunks(IPFS_CHUNK_SIZE)
+        .chain(contents.is_empty().then_some(&[][..]))
+        .map(|chunk| {
+            // a UnixFS file, containing the chunk and its size
+            let mut data = vec![0x08, 0x02];
+            if !chunk.is_empty() {
+                write_bytes_field(&mut data, 0x12, chunk);
+            }
+            data.push(0x18);
+            write_varint(&mut data, chunk.len());
+
+            let mut block = Vec::new();
+            write_bytes_field(&mut block, 0x0a, &data);
+            ipfs_node(block, chunk.len(), 0)
+        })
+        .collect::<Vec<_>>();
+
+    // link the chunks together until a single root remains
+    while level.len() > 1 {
+        level = level
+            .chunks(IPFS_MAX_LINKS)
+            .map(|children| {
+                let mut links = Vec::new();
+                let mut data = vec![0x08, 0x02, 0x18];
+                write_varint(&mut data, children.iter().map(|child| child.size).sum());
+                for child in children {
+                    let mut link = Vec::new();
+                    write_bytes_field(&mut link, 0x0a, &child.hash);
+                    link.extend_from_slice(&[0x12, 0x00, 0x18]);
+                    write_varint(&mut link, child.block_size);
+                    write_bytes_field(&mut links, 0x12, &link);
+
+                    data.push(0x20);
+                    write_varint(&mut data, child.size);
+                }
+
+                let mut block = links;
+                write_bytes_field(&mut block, 0x0a, &data);
+                ipfs_node(
+                    block,
+                    children.iter().map(|child| child.size).sum(),
+                    children.iter().map(|child| child.block_size).sum(),
+                )
+            })
+            .collect();
+    }
+
+    encode_base58(&level[0].hash)
+}
+
+/// Converts a compiler version in the form of `[major, minor, patch]` into a string.
+fn version_to_string(version: &CborValue) -> Option<String> {
+    match version {
+        CborValue::Bytes(bytes) => {
+            Some(bytes.iter().map(|part| part.to_string()).collect::<Vec<String>>().join("."))
+        }
+        CborValue::Array(parts) => Some(
+            parts
+                .iter()
+                .map(|part| match part {
+                    CborValue::Uint(part) => Some(part.to_string()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<String>>>()?
+                .join("."),
+        ),
+        CborValue::Text(version) => Some(version.to_owned()),
+        _ => None,
+    }
+}
+
+/// Detect and decode the CBOR metadata appended to the given bytecode. Returns `None` if the
+/// bytecode doesn't end with valid compiler metadata.
+///
+/// ```
+/// use heimdall_common::ether::metadata::decode_metadata;
+///
+/// // {"solc": 0x000813}, followed by its length
+/// let metadata = decode_metadata("6080a164736f6c6343000813000a").unwrap();
+///
+/// assert_eq!(metadata.solc, Some(String::from("0.8.19")));
+/// assert_eq!(metadata.size, 12);
+/// ```
+pub fn decode_metadata(bytecode: &str) -> Option<ContractMetadata> {
+    let bytecode = bytecode.trim().trim_start_matches("0x");
+    if bytecode.len() % 2 == 1 {
+        return None
+    }
+
+    decode_metadata_bytes(&decode_hex(bytecode).ok()?)
+}
+
+/// Decode the CBOR metadata trailer at the end of the given raw bytecode, if present.
+pub(crate) fn decode_metadata_bytes(bytes: &[u8]) -> Option<ContractMetadata> {
+    if bytes.len() < 2 {
+        return None
+    }
+
+    // the last two bytes contain the length of the cbor encoded metadata
+    let length = u16::from_be_bytes([bytes[bytes.len() - 2], bytes[bytes.len() - 1]]) as usize;
+    if length == 0 || length + 2 > bytes.len() {
+        return None
+    }
+    let cbor = &bytes[bytes.len() - 2 - length..bytes.len() - 2];
+
+    // the metadata must be a single map which spans the entire trailer
+    let entries = match decode_cbor(cbor)? {
+        (CborValue::Map(entries), consumed) if consumed == cbor.len() => entries,
+        _ => return None,
+    };
+
+    let mut metadata = ContractMetadata { size: length + 2, ..Default::default() };
+    for (key, value) in entries {
+        let key = match key {
+            CborValue::Text(key) => key,
+            _ => return None,
+        };
+
+        match (key.as_str(), &value) {
+            ("solc", _) => metadata.solc = version_to_string(&value),
+            ("vyper", _) => metadata.vyper = version_to_string(&value),
+            ("ipfs", CborValue::Bytes(hash)) => metadata.ipfs = Some(encode_base58(hash)),
+            ("bzzr0", CborValue::Bytes(hash)) => metadata.bzzr0 = Some(encode_hex(hash.to_vec())),
+            ("bzzr1", CborValue::Bytes(hash)) => metadata.bzzr1 = Some(encode_hex(hash.to_vec())),
+            ("experimental", CborValue::Bool(experimental)) => {
+                metadata.experimental = *experimental
+            }
+            _ => {}
+        }
+    }
+
+    // we only consider the trailer valid if it contains something we recognize
+    if metadata.fields().is_empty() {
+        return None
+    }
+
+    Some(metadata)
+}
+
+/// Remove the CBOR metadata trailer from the given bytecode, if present.
+///
+/// ```
+/// use heimdall_common::ether::metadata::strip_metadata;
+///
+/// assert_eq!(strip_metadata("6080a164736f6c6343000813000a"), "6080");
+/// assert_eq!(strip_metadata("6080"), "6080");
+/// ```
+pub fn strip_metadata(bytecode: &str) -> String {
+    let bytecode = bytecode.trim().trim_start_matches("0x");
+
+    match decode_metadata(bytecode) {
+        Some(metadata) => bytecode[..bytecode.len() - metadata.size * 2].to_string(),
+        None => bytecode.to_string(),
+    }
+}
+
+/// The compiler metadata file which an IPFS metadata hash points to.
+/// <https://docs.soliditylang.org/en/latest/metadata.html>
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataFile {
+    /// The raw metadata JSON.
+    pub raw: Value,
+    /// The contract's ABI, if present.
+    pub abi: Option<Value>,
+    /// Whether the optimizer was enabled, and the number of optimizer runs, if present.
+    pub optimizer: Option<(bool, u64)>,
+    /// The original source files of the contract, keyed by their path. Only sources which could
+    /// be retrieved are included.
+    pub sources: HashMap<String, String>,
+}
+
+impl MetadataFile {
+    /// Returns the functions in the metadata file's ABI, keyed by their selector.
+    pub fn resolved_functions(&self) -> HashMap<String, ResolvedFunction> {
+        let abi: Abi = match self.abi.as_ref().map(|abi| serde_json::from_value(abi.clone())) {
+            Some(Ok(abi)) => abi,
+            _ => return HashMap::new(),
+        };
+
+        abi.functions()
+            .map(|function| {
+                let inputs = function
+                    .inputs
+                    .iter()
+                    .map(|input| input.kind.to_string())
+                    .collect::<Vec<String>>();
+
+                (
+                    encode_hex(function.short_signature().to_vec()),
+                    ResolvedFunction {
+                        name: function.name.to_owned(),
+                        signature: format!("{}({})", function.name, inputs.join(",")),
+                        inputs,
+                        decoded_inputs: None,
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Join an IPFS gateway and a CID into a URL.
+fn gateway_url(gateway: &str, cid: &str) -> String {
+    format!("{}/{}", gateway.trim_end_matches('/'), cid)
+}
+
+/// Fetch a file from IPFS via the given gateway, caching the result once it's been checked against
+/// the CID.
+async fn fetch_ipfs(cid: &str, gateway: &str) -> Option<String> {
+    if let Some(contents) = read_cache::<String>(&format!("ipfs.{cid}")) {
+        if ipfs_hash(contents.as_bytes()) == cid {
+            return Some(contents)
+        }
+    }
+
+    // gateways may return anything, so only trust contents which hash to the requested CID
+    let contents = get_text_from_url(&gateway_url(gateway, cid), 10).await.ok()??;
+    if ipfs_hash(contents.as_bytes()) != cid {
+        Logger::default().debug(&format!("contents returned for '{cid}' don't match its hash."));
+        return None
+    }
+    store_cache(&format!("ipfs.{cid}"), contents.clone(), None);
+
+    Some(contents)
+}
+
+/// Fetch the metadata file referenced by the given [`ContractMetadata`] from an IPFS gateway,
+/// along with the contract's original sources if `fetch_sources` is set. Returns `None` if the
+/// metadata doesn't contain an IPFS hash, or if the file couldn't be retrieved.
+///
+/// ```no_run
+/// use heimdall_common::ether::metadata::{decode_metadata, fetch_metadata_file};
+///
+/// // let metadata = decode_metadata("...").unwrap();
+/// // let file = fetch_metadata_file(&metadata, "https://ipfs.io/ipfs/", true).await;
+/// ```
+pub async fn fetch_metadata_file(
+    metadata: &ContractMetadata,
+    gateway: &str,
+    fetch_sources: bool,
+) -> Option<MetadataFile> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let cid = metadata.ipfs.as_ref()?;
+    logger.debug(&format!("fetching metadata file from {} .", gateway_url(gateway, cid)));
+
+    let raw: Value = match serde_json::from_str(&fetch_ipfs(cid, gateway).await?) {
+        Ok(raw) => raw,
+        Err(_) => {
+            logger.debug(&format!("metadata file for '{cid}' isn't valid JSON."));
+            return None
+        }
+    };
+
+    let abi = raw.get("output").and_then(|output| output.get("abi")).cloned();
+    let optimizer =
+        raw.get("settings").and_then(|settings| settings.get("optimizer")).map(|optimizer| {
+            (
+                optimizer.get("enabled").and_then(|enabled| enabled.as_bool()).unwrap_or(false),
+                optimizer.get("runs").and_then(|runs| runs.as_u64()).unwrap_or(0),
+            )
+        });
+
+    // sources are either included literally, or referenced by their own IPFS hash
+    let mut sources = HashMap::new();
+    if fetch_sources {
+        if let Some(Value::Object(entries)) = raw.get("sources") {
+            for (path, source) in entries {
+                if let Some(content) = source.get("content").and_then(|content| content.as_str()) {
+                    sources.insert(path.to_owned(), content.to_owned());
+                    continue
+                }
+
+                let source_cid =
+                    source.get("urls").and_then(|urls| urls.as_array()).and_then(|urls| {
+                        urls.iter()
+                            .filter_map(|url| url.as_str())
+                            .find_map(|url| url.strip_prefix("dweb:/ipfs/"))
+                    });
+
+                match source_cid {
+                    Some(source_cid) => match fetch_ipfs(source_cid, gateway).await {
+                        Some(content) => {
+                            sources.insert(path.to_owned(), content);
+                        }
+                        None => logger.debug(&format!("failed to fetch source '{path}' .")),
+                    },
+                    None => logger.debug(&format!("source '{path}' has no IPFS url.")),
+                }
+            }
+        }
+    }
+
+    Some(MetadataFile { raw, abi, optimizer, sources })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::ether::metadata::{
+        decode_metadata, encode_base58, gateway_url, ipfs_hash, strip_metadata, MetadataFile,
+    };
+
+    #[test]
+    fn test_decode_metadata_solc_ipfs() {
+        let bytecode = "6080604052a2646970667358221220c8bd1bd1a0ca30a9b2b8aa9b51d8ea7c0b5b74d69c0283aab45d63c1c8896d2964736f6c63430008110033";
+        let metadata = decode_metadata(bytecode).unwrap();
+
+        assert_eq!(metadata.solc, Some(String::from("0.8.17")));
+        assert_eq!(
+            metadata.ipfs,
+            Some(String::from("QmbrFVphU2ab1fB23KAuPTLVJ4C8yRSY4GdJmpb3cf6q9J"))
+        );
+        assert!(!metadata.experimental);
+        assert_eq!(metadata.size, 53);
+        assert_eq!(strip_metadata(bytecode), "6080604052");
+    }
+
+    #[test]
+    fn test_decode_metadata_vyper() {
+        let metadata = decode_metadata("6080a165767970657283000304000b").unwrap();
+
+        assert_eq!(metadata.vyper, Some(String::from("0.3.4")));
+        assert_eq!(metadata.compiler(), Some(("vyper", String::from("0.3.4"))));
+    }
+
+    #[test]
+    fn test_decode_metadata_invalid() {
+        assert_eq!(decode_metadata(""), None);
+        assert_eq!(decode_metadata("6080604052"), None);
+        assert_eq!(decode_metadata("6080ffff"), None);
+        assert_eq!(decode_metadata("a16161010004"), None);
+    }
+
+    #[test]
+    fn test_encode_base58() {
+        assert_eq!(encode_base58(&[0, 0, 1]), "112");
+        assert_eq!(encode_base58(b"hello world"), "StV1DL6CwTryKyV");
+    }
+
+    #[test]
+    fn test_gateway_url() {
+        assert_eq!(gateway_url("https://ipfs.io/ipfs/", "Qm"), "https://ipfs.io/ipfs/Qm");
+        assert_eq!(gateway_url("https://ipfs.io/ipfs", "Qm"), "https://ipfs.io/ipfs/Qm");
+    }
+
+    #[test]
+    fn test_metadata_file_resolved_functions() {
+        let raw = serde_json::json!({
+            "output": {
+                "abi": [{
+                    "type": "function",
+                    "name": "transfer",
+                    "inputs": [
+                        { "name": "to", "type": "address" },
+                        { "name": "amount", "type": "uint256" }
+                    ],
+                    "outputs": [{ "name": "", "type": "bool" }],
+                    "stateMutability": "nonpayable"
+                }]
+            }
+        });
+        let file = MetadataFile {
+            abi: raw.get("output").and_then(|output| output.get("abi")).cloned(),
+            raw,
+            optimizer: None,
+            sources: HashMap::new(),
+        };
+
+        let functions = file.resolved_functions();
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions.get("a9059cbb").unwrap().signature, "transfer(address,uint256)");
+    }
+
+    #[test]
+    fn test_ipfs_hash() {
+        assert_eq!(ipfs_hash(b""), "QmbFMke1KXqnYyBBWxB74N4c5SBnJMVAiMNRcGu6x1AwQH");
+        assert_eq!(ipfs_hash(b"hello world\n"), "QmT78zSuBmuS4z925WZfrqQ1qHaJ56DQaTfyMUF7F8ff5o");
+
+        // a gateway error page doesn't hash to the requested file
+        assert_ne!(ipfs_hash(b"404 page not found"), ipfs_hash(b"hello world\n"));
+    }
+}
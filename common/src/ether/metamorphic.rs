@@ -0,0 +1,158 @@
+use crate::utils::strings::decode_hex;
+
+use super::{metadata::decode_metadata_bytes, segments::find_embedded_contracts};
+
+/// The widely used metamorphic init code, which fetches the runtime code to deploy from its
+/// factory and copies it from that address, so a CREATE2 address can be redeployed with
+/// different code.
+const METAMORPHIC_INIT_CODE: &str = "5860208158601c335a63aaf10f428752fa158151803b80938091923cf3";
+
+/// Init code smaller than this which returns code copied with `EXTCODECOPY` is considered
+/// metamorphic init code.
+const MAX_METAMORPHIC_INIT_CODE_SIZE: usize = 64;
+
+/// Indicators of the metamorphic deployment pattern, where a contract deployed with CREATE2
+/// destroys itself so that different code can be deployed to the same address.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetamorphicReport {
+    /// Whether the contract can `SELFDESTRUCT`, after which its address can be redeployed.
+    pub selfdestructs: bool,
+    /// Whether the contract deploys other contracts with `CREATE2`.
+    pub deploys_with_create2: bool,
+    /// Whether any of the contracts embedded within the contract, i.e. those it deploys, can
+    /// `SELFDESTRUCT` or are metamorphic init code.
+    pub deploys_metamorphic_contracts: bool,
+    /// Whether the code is metamorphic init code, which deploys runtime code copied from another
+    /// contract rather than code of its own.
+    pub metamorphic_init_code: bool,
+}
+
+impl MetamorphicReport {
+    /// Whether the code may change between deployments to the same address.
+    pub fn is_metamorphic(&self) -> bool {
+        self.metamorphic_init_code ||
+            self.selfdestructs ||
+            (self.deploys_with_create2 && self.deploys_metamorphic_contracts)
+    }
+
+    /// Human-readable warnings for each indicator which is present.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.metamorphic_init_code {
+            warnings.push(String::from(
+                "this is metamorphic init code, which deploys code copied from another contract, so each deployment to the same address may have different code.",
+            ));
+        }
+        if self.selfdestructs {
+            warnings.push(String::from(
+                "this contract can selfdestruct. if it was deployed with CREATE2, different code may have been deployed to its address since, so prior versions can be analyzed with '--block'.",
+            ));
+        }
+        if self.deploys_with_create2 && self.deploys_metamorphic_contracts {
+            warnings.push(String::from(
+                "this contract deploys contracts with CREATE2 which can selfdestruct, so it may be a metamorphic contract factory.",
+            ));
+        }
+        warnings
+    }
+}
+
+/// The opcodes of the bytecode's instructions, skipping the data of `PUSH` instructions.
+fn opcodes(bytes: &[u8]) -> Vec<u8> {
+    let mut opcodes = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let opcode = bytes[offset];
+        opcodes.push(opcode);
+        offset += match opcode {
+            0x60..=0x7f => (opcode - 0x5f) as usize + 1,
+            _ => 1,
+        };
+    }
+    opcodes
+}
+
+/// Whether the raw bytecode is metamorphic init code.
+fn is_metamorphic_init_code(bytes: &[u8], opcodes: &[u8]) -> bool {
+    bytes.len() <= MAX_METAMORPHIC_INIT_CODE_SIZE &&
+        opcodes.contains(&0x3c) &&
+        opcodes.contains(&0xf3)
+}
+
+/// Detect the metamorphic deployment pattern in the given bytecode, which may be creation code,
+/// runtime code, or metamorphic init code. The code of embedded contracts and the metadata
+/// trailer are excluded when looking for the contract's own `SELFDESTRUCT` and `CREATE2`
+/// instructions.
+///
+/// ```
+/// use heimdall_common::ether::metamorphic::detect_metamorphic;
+///
+/// // selfdestruct(msg.sender)
+/// let report = detect_metamorphic("33ff");
+/// assert!(report.selfdestructs);
+/// assert!(report.is_metamorphic());
+///
+/// assert!(!detect_metamorphic("6080604052600080fd").is_metamorphic());
+/// ```
+pub fn detect_metamorphic(bytecode: &str) -> MetamorphicReport {
+    let bytecode = bytecode.trim().trim_start_matches("0x");
+    let mut bytes = match decode_hex(bytecode) {
+        Ok(bytes) => bytes,
+        Err(_) => return MetamorphicReport::default(),
+    };
+
+    // embedded contracts belong to the contracts this one deploys
+    let embedded_contracts = find_embedded_contracts(bytecode);
+    let deploys_metamorphic_contracts = bytecode.contains(METAMORPHIC_INIT_CODE) ||
+        embedded_contracts
+            .iter()
+            .any(|contract| detect_metamorphic(&contract.bytecode).is_metamorphic());
+    if let Some(first) = embedded_contracts.first() {
+        bytes.truncate(first.offset);
+    }
+    if let Some(metadata) = decode_metadata_bytes(&bytes) {
+        bytes.truncate(bytes.len() - metadata.size);
+    }
+
+    let opcodes = opcodes(&bytes);
+    MetamorphicReport {
+        selfdestructs: opcodes.contains(&0xff),
+        deploys_with_create2: opcodes.contains(&0xf5),
+        deploys_metamorphic_contracts,
+        metamorphic_init_code: bytecode == METAMORPHIC_INIT_CODE ||
+            is_metamorphic_init_code(&bytes, &opcodes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_metamorphic_init_code() {
+        let report = detect_metamorphic(METAMORPHIC_INIT_CODE);
+        assert!(report.metamorphic_init_code);
+        assert!(!report.selfdestructs);
+        assert!(report.is_metamorphic());
+    }
+
+    #[test]
+    fn test_detect_metamorphic_factory() {
+        // a factory which pushes the metamorphic init code and deploys it with CREATE2
+        let factory = format!("7f{METAMORPHIC_INIT_CODE:0<64}6000526000601e60006000f500");
+        let report = detect_metamorphic(&factory);
+        assert!(report.deploys_with_create2);
+        assert!(report.deploys_metamorphic_contracts);
+        assert!(!report.selfdestructs);
+        assert_eq!(report.warnings().len(), 1);
+    }
+
+    #[test]
+    fn test_detect_metamorphic_ignores_push_data_and_metadata() {
+        // PUSH1 0xff, and a metadata trailer containing 0xff
+        let report = detect_metamorphic("60ff50a164736f6c63430008ff000a");
+        assert!(!report.selfdestructs);
+        assert!(!report.is_metamorphic());
+        assert!(report.warnings().is_empty());
+    }
+}
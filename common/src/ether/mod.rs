@@ -1,6 +1,18 @@
 pub mod compiler;
+pub mod dead_code;
+pub mod eof;
+pub mod eravm;
+pub mod ether_flow;
 pub mod evm;
+pub mod fingerprint;
+pub mod history;
+pub mod labels;
 pub mod lexers;
+pub mod metadata;
+pub mod metamorphic;
 pub mod rpc;
+pub mod segments;
 pub mod selectors;
 pub mod signatures;
+pub mod storage;
+pub mod target;
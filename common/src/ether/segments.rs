@@ -0,0 +1,249 @@
+use crate::utils::strings::{decode_hex, encode_hex};
+
+use super::metadata::{decode_metadata_bytes, ContractMetadata};
+
+/// The runtime code of a contract which is embedded within other bytecode, such as the runtime
+/// code of a child contract within the init code of the factory which deploys it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddedContract {
+    /// The offset of the runtime code within the bytecode, in bytes.
+    pub offset: usize,
+    /// The runtime code of the embedded contract, including its metadata trailer.
+    pub bytecode: String,
+    /// The decoded metadata trailer which marks the end of the runtime code.
+    pub metadata: ContractMetadata,
+}
+
+/// A position of interest within bytecode which contains concatenated contracts.
+#[derive(Debug, Clone, PartialEq)]
+enum Boundary {
+    /// A runtime code prologue, which directly follows the `RETURN` of the init code which
+    /// deploys it.
+    RuntimeStart,
+    /// The end of a CBOR metadata trailer, which compilers append to runtime code.
+    MetadataEnd(ContractMetadata),
+}
+
+/// Whether a solc free memory pointer prologue, `PUSH1 0x80 PUSH1 0x40 MSTORE` or
+/// `PUSH1 0x60 PUSH1 0x40 MSTORE`, starts at the given offset.
+fn is_prologue(bytes: &[u8], offset: usize) -> bool {
+    matches!(bytes.get(offset..offset + 5), Some([0x60, 0x80 | 0x60, 0x60, 0x40, 0x52]))
+}
+
+/// A runtime code segment within bytecode, from its prologue to the end of its metadata trailer.
+struct Segment {
+    start: usize,
+    end: usize,
+    metadata: ContractMetadata,
+    /// Whether the segment isn't nested within another segment.
+    top_level: bool,
+}
+
+/// Find every runtime code segment within the bytecode, and whether the bytecode is itself
+/// runtime code, i.e. its own metadata trailer doesn't belong to any segment.
+///
+/// Runtime code begins with a solc prologue directly after the `RETURN` and trailing `INVALID`
+/// (or `STOP`) of its init code, and ends with its CBOR metadata trailer. Since a factory's own
+/// runtime code contains the full creation code of its children, segments are matched up like
+/// brackets.
+fn find_segments(bytes: &[u8]) -> (Vec<Segment>, Vec<usize>, bool) {
+    // find every runtime prologue and metadata trailer, in order. a trailer and the prologue of
+    // the next contract may share an offset, in which case the trailer must be handled first.
+    let mut boundaries = Vec::new();
+    for offset in 0..=bytes.len() {
+        if offset >= 2 {
+            let length = u16::from_be_bytes([bytes[offset - 2], bytes[offset - 1]]) as usize;
+
+            // only decode the candidates which are preceded by a cbor map
+            if length > 0 && length + 2 <= offset && bytes[offset - 2 - length] & 0xe0 == 0xa0 {
+                if let Some(metadata) = decode_metadata_bytes(&bytes[..offset]) {
+                    boundaries.push((offset, Boundary::MetadataEnd(metadata)));
+                }
+            }
+        }
+        if offset >= 2 &&
+            bytes[offset - 2] == 0xf3 &&
+            matches!(bytes[offset - 1], 0xfe | 0x00) &&
+            is_prologue(bytes, offset)
+        {
+            boundaries.push((offset, Boundary::RuntimeStart));
+        }
+    }
+
+    // match each trailer with the most recent unmatched prologue. a trailer without one closes
+    // the bytecode itself, which means it is runtime code rather than creation code.
+    let mut open: Vec<usize> = Vec::new();
+    let mut starts = Vec::new();
+    let mut segments = Vec::new();
+    let mut is_runtime = false;
+    for (offset, boundary) in boundaries {
+        match boundary {
+            Boundary::RuntimeStart => {
+                open.push(offset);
+                starts.push(offset);
+            }
+            Boundary::MetadataEnd(metadata) => match open.pop() {
+                Some(start) => segments.push(Segment {
+                    start,
+                    end: offset,
+                    metadata,
+                    top_level: open.is_empty(),
+                }),
+                None => is_runtime = true,
+            },
+        }
+    }
+    segments.sort_by_key(|segment| segment.start);
+
+    (segments, starts, is_runtime)
+}
+
+/// Find the runtime code of every contract embedded within the given bytecode, such as the child
+/// contracts a factory deploys. When the bytecode is creation code, its first top-level segment
+/// is the runtime code of the contract itself, and isn't considered embedded.
+///
+/// ```
+/// use heimdall_common::ether::segments::find_embedded_contracts;
+///
+/// let child = "6080604052600080fda164736f6c6343000813000a";
+/// let factory = format!("6080604052600080fd6080604052603f6000f3fe{child}a164736f6c6343000813000a");
+///
+/// let children = find_embedded_contracts(&factory);
+/// assert_eq!(children.len(), 1);
+/// assert_eq!(children[0].offset, 20);
+/// assert_eq!(children[0].bytecode, child);
+/// ```
+pub fn find_embedded_contracts(bytecode: &str) -> Vec<EmbeddedContract> {
+    let bytes = match decode_hex(bytecode.trim().trim_start_matches("0x")) {
+        Ok(bytes) => bytes,
+        Err(_) => return Vec::new(),
+    };
+    let (mut segments, _, is_runtime) = find_segments(&bytes);
+
+    // the first top-level segment of creation code is the contract's own runtime code
+    if !is_runtime {
+        if let Some(index) = segments.iter().position(|segment| segment.top_level) {
+            segments.remove(index);
+        }
+    }
+
+    segments
+        .into_iter()
+        .map(|segment| EmbeddedContract {
+            offset: segment.start,
+            bytecode: encode_hex(bytes[segment.start..segment.end].to_vec()),
+            metadata: segment.metadata,
+        })
+        .collect()
+}
+
+/// Creation code, split into the init code which deploys the contract, the runtime code it
+/// deploys, and the ABI-encoded constructor arguments appended to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CreationCode {
+    pub init_code: String,
+    pub runtime_code: String,
+    pub constructor_arguments: String,
+}
+
+impl CreationCode {
+    /// The offset of the runtime code within the creation code, in bytes.
+    pub fn runtime_offset(&self) -> usize {
+        self.init_code.len() / 2
+    }
+
+    /// The full creation code, as it was split.
+    pub fn bytecode(&self) -> String {
+        format!("{}{}{}", self.init_code, self.runtime_code, self.constructor_arguments)
+    }
+}
+
+/// Split creation code into its init code, runtime code, and constructor arguments. Returns
+/// `None` if the bytecode is runtime code. When the runtime code has no metadata trailer, it is
+/// assumed to extend to the end of the bytecode.
+///
+/// ```
+/// use heimdall_common::ether::segments::split_creation_code;
+///
+/// let runtime = "6080604052600080fda164736f6c6343000813000a";
+/// let argument = "00000000000000000000000000000000000000000000000000000000000000ff";
+/// let creation_code = split_creation_code(&format!("6080604052603f6000f3fe{runtime}{argument}")).unwrap();
+///
+/// assert_eq!(creation_code.init_code, "6080604052603f6000f3fe");
+/// assert_eq!(creation_code.runtime_code, runtime);
+/// assert_eq!(creation_code.constructor_arguments, argument);
+/// assert!(split_creation_code(runtime).is_none());
+/// ```
+pub fn split_creation_code(bytecode: &str) -> Option<CreationCode> {
+    let bytes = decode_hex(bytecode.trim().trim_start_matches("0x")).ok()?;
+    let (segments, starts, is_runtime) = find_segments(&bytes);
+    if is_runtime {
+        return None
+    }
+
+    let (start, end) = match segments.iter().find(|segment| segment.top_level) {
+        Some(segment) => (segment.start, segment.end),
+        None => (*starts.first()?, bytes.len()),
+    };
+
+    Some(CreationCode {
+        init_code: encode_hex(bytes[..start].to_vec()),
+        runtime_code: encode_hex(bytes[start..end].to_vec()),
+        constructor_arguments: encode_hex(bytes[end..].to_vec()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = "a164736f6c6343000813000a";
+
+    #[test]
+    fn test_find_embedded_contracts_in_creation_code() {
+        let child = format!("6080604052600080fd{METADATA}");
+        let child_creation = format!("6080604052603f80601d6000396000f3fe{child}");
+        let runtime = format!("6080604052600080fd{child_creation}{METADATA}");
+        let creation = format!("6080604052348015600f57600080fd5b50603f6000396000f3fe{runtime}");
+
+        let children = find_embedded_contracts(&creation);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].bytecode, child);
+        assert_eq!(children[0].metadata.solc, Some(String::from("0.8.19")));
+
+        // the factory's runtime code embeds the same child
+        let children = find_embedded_contracts(&runtime);
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].bytecode, child);
+    }
+
+    #[test]
+    fn test_find_embedded_contracts_without_children() {
+        let runtime = format!("6080604052600080fd{METADATA}");
+        assert!(find_embedded_contracts(&runtime).is_empty());
+        assert!(find_embedded_contracts(&format!("6080604052603f6000f3fe{runtime}")).is_empty());
+        assert!(find_embedded_contracts("6080604052").is_empty());
+        assert!(find_embedded_contracts("zz").is_empty());
+    }
+
+    #[test]
+    fn test_split_creation_code() {
+        let child = format!("6080604052600080fd{METADATA}");
+        let runtime = format!("6080604052600080fd6080604052603f6000f3fe{child}{METADATA}");
+        let init_code = "6080604052348015600f57600080fd5b50603f6000396000f3fe";
+        let argument = format!("{:0>64}", "cafe");
+
+        let creation_code =
+            split_creation_code(&format!("{init_code}{runtime}{argument}")).unwrap();
+        assert_eq!(creation_code.init_code, init_code);
+        assert_eq!(creation_code.runtime_code, runtime);
+        assert_eq!(creation_code.constructor_arguments, argument);
+        assert_eq!(creation_code.runtime_offset(), 26);
+
+        // runtime code without a metadata trailer extends to the end of the bytecode
+        let creation_code =
+            split_creation_code("6080604052603f6000f3fe6080604052600080fd").unwrap();
+        assert_eq!(creation_code.runtime_code, "6080604052600080fd");
+        assert!(creation_code.constructor_arguments.is_empty());
+    }
+}
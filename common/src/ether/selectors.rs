@@ -1,15 +1,330 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     sync::{Arc, Mutex},
     time::Duration,
 };
 
+use ethers::types::U256;
 use indicatif::ProgressBar;
+use serde::Serialize;
 use tokio::task;
 
-use crate::utils::{io::logging::Logger, strings::decode_hex};
+use crate::utils::{
+    io::logging::Logger,
+    strings::{decode_hex, encode_hex},
+};
+
+use super::{
+    evm::core::{
+        opcodes::WrappedOpcode,
+        vm::{Instruction, VM},
+    },
+    signatures::{ResolveSelector, MAX_BATCH_SIZE},
+};
+
+/// The maximum number of selectors probed while exploring a contract's dispatcher.
+const MAX_DISPATCHER_PROBES: usize = 1024;
+
+/// The maximum number of buckets explored within a jump table dispatcher.
+const MAX_JUMP_TABLE_BUCKETS: u64 = 256;
+
+/// The maximum number of instructions executed while following a single call through the
+/// dispatcher.
+pub(crate) const MAX_DISPATCHER_STEPS: usize = 16384;
+
+/// Whether the operation reads the selector, i.e. the first word of calldata.
+pub fn reads_selector(operation: &WrappedOpcode) -> bool {
+    let operation = operation.to_string();
+    ["CALLDATALOAD(PUSH1(0))", "CALLDATALOAD(PUSH0())", "CALLDATALOAD(0)"]
+        .iter()
+        .any(|selector| operation.contains(selector))
+}
+
+/// A comparison the dispatcher makes between the selector and a constant.
+#[derive(Debug, Clone, PartialEq)]
+enum DispatcherComparison {
+    /// The selector is checked for equality with the constant, which is a function's selector.
+    Equality(U256),
+    /// The selector is ordered against a pivot, as in a binary search dispatcher.
+    Ordering(U256),
+    /// The selector is split into buckets, as in a jump table dispatcher.
+    Buckets(u64),
+}
+
+/// Get the comparison between the selector and a constant the instruction makes, if any.
+fn dispatcher_comparison(instruction: &Instruction) -> Option<DispatcherComparison> {
+    if instruction.input_operations.len() < 2 {
+        return None
+    }
+
+    // exactly one of the operands must be derived from the selector
+    let (constant, selector_first) = match (
+        reads_selector(&instruction.input_operations[0]),
+        reads_selector(&instruction.input_operations[1]),
+    ) {
+        (true, false) => (instruction.inputs[1], true),
+        (false, true) => (instruction.inputs[0], false),
+        _ => return None,
+    };
+
+    match instruction.opcode {
+        // EQ, XOR, and SUB are all used to check a selector for equality
+        0x14 | 0x18 | 0x03 if constant <= U256::from(u32::MAX) => {
+            Some(DispatcherComparison::Equality(constant))
+        }
+        // LT, GT, SLT, and SGT
+        0x10..=0x13 if constant <= U256::from(u32::MAX) => {
+            Some(DispatcherComparison::Ordering(constant))
+        }
+        // MOD of the selector by the number of buckets
+        0x06 if selector_first && constant > U256::zero() => Some(DispatcherComparison::Buckets(
+            constant.min(U256::from(MAX_JUMP_TABLE_BUCKETS)).as_u64(),
+        )),
+        // AND of the selector with a bucket mask
+        0x16 if constant < U256::from(MAX_JUMP_TABLE_BUCKETS) => {
+            Some(DispatcherComparison::Buckets(constant.as_u64() + 1))
+        }
+        _ => None,
+    }
+}
+
+/// Follow a call with the given selector through the dispatcher, returning every comparison it
+/// makes between the selector and a constant.
+fn dispatcher_comparisons(evm: &VM, selector: u32) -> Vec<DispatcherComparison> {
+    let mut vm = evm.clone();
+    vm.calldata = selector.to_be_bytes().to_vec();
+
+    let mut comparisons = Vec::new();
+    let mut steps = 0;
+    while vm.bytecode.len() >= vm.instruction as usize && steps < MAX_DISPATCHER_STEPS {
+        let state = vm.step();
+        steps += 1;
+
+        if let Some(comparison) = dispatcher_comparison(&state.last_instruction) {
+            comparisons.push(comparison);
+        }
+        if vm.exitcode != 255 || !vm.returndata.is_empty() {
+            break
+        }
+    }
+
+    comparisons
+}
+
+/// Explore the contract's dispatcher by probing it with selectors, returning the selectors it
+/// checks for. Beyond the linear if/else dispatcher, this follows both sides of each pivot in
+/// binary search dispatchers, and each bucket of jump table dispatchers, so selectors which are
+/// computed or stored as data rather than pushed are found too.
+fn explore_dispatcher(evm: &VM) -> Vec<String> {
+    let mut selectors = Vec::new();
+    let mut probes = VecDeque::from([0u32, u32::MAX]);
+    let mut probed = HashSet::new();
+
+    while let Some(probe) = probes.pop_front() {
+        if probed.len() >= MAX_DISPATCHER_PROBES {
+            break
+        }
+        if !probed.insert(probe) {
+            continue
+        }
+
+        for comparison in dispatcher_comparisons(evm, probe) {
+            match comparison {
+                DispatcherComparison::Equality(selector) => {
+                    let selector = format!("{:08x}", selector.as_u32());
+                    if !selectors.contains(&selector) {
+                        selectors.push(selector);
+                    }
+                }
+                DispatcherComparison::Ordering(pivot) => {
+                    let pivot = pivot.as_u32();
+                    probes.extend([pivot, pivot.saturating_add(1), pivot.saturating_sub(1)]);
+                }
+                DispatcherComparison::Buckets(buckets) => {
+                    probes.extend((0..buckets).map(|bucket| bucket as u32));
+                }
+            }
+        }
+    }
+
+    selectors
+}
+
+/// The layout of a contract's dispatcher, i.e. how it routes a call to the function its selector
+/// belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DispatcherKind {
+    /// The selector is compared with each function's selector in turn.
+    Linear,
+    /// The selector is ordered against pivots before being compared, as vyper and optimized
+    /// solc dispatchers do for contracts with many functions.
+    BinarySearch,
+    /// The selector is split into buckets which are jumped to directly, as vyper's sparse and
+    /// dense dispatchers do.
+    JumpTable,
+}
+
+impl std::fmt::Display for DispatcherKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DispatcherKind::Linear => write!(f, "linear"),
+            DispatcherKind::BinarySearch => write!(f, "binary search"),
+            DispatcherKind::JumpTable => write!(f, "jump table"),
+        }
+    }
+}
+
+/// Classify the contract's dispatcher by the comparisons it makes between the selector and
+/// constants, when called with the lowest and highest possible selectors.
+pub fn dispatcher_kind(evm: &VM) -> DispatcherKind {
+    let comparisons = [0u32, u32::MAX]
+        .iter()
+        .flat_map(|probe| dispatcher_comparisons(evm, *probe))
+        .collect::<Vec<_>>();
+
+    if comparisons.iter().any(|comparison| matches!(comparison, DispatcherComparison::Buckets(_))) {
+        DispatcherKind::JumpTable
+    } else if comparisons
+        .iter()
+        .any(|comparison| matches!(comparison, DispatcherComparison::Ordering(_)))
+    {
+        DispatcherKind::BinarySearch
+    } else {
+        DispatcherKind::Linear
+    }
+}
+
+/// Unusual properties of a contract's dispatcher, which are often present in honeypots, where
+/// the function a call reaches isn't the one its selector suggests.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DispatcherAnomalies {
+    /// Whether the dispatcher reads the selector without first checking that calldata holds one,
+    /// so calls with short calldata are dispatched on a zero-padded selector.
+    pub missing_length_check: bool,
+    /// The selectors which the dispatcher checks for more than once, so the functions behind all
+    /// but the first check can never be called, sorted.
+    pub colliding_selectors: Vec<String>,
+    /// The selectors which the dispatcher checks for, but which fall through into the fallback
+    /// function rather than reaching a function of their own, sorted.
+    pub fallthrough_selectors: Vec<String>,
+}
+
+impl DispatcherAnomalies {
+    /// Human-readable warnings for each anomaly which was found.
+    pub fn warnings(&self) -> Vec<String> {
+        let list = |selectors: &[String]| {
+            selectors
+                .iter()
+                .map(|selector| format!("0x{selector}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        let mut warnings = Vec::new();
+        if self.missing_length_check {
+            warnings.push(String::from(
+                "the dispatcher doesn't check the calldata length, so short calldata is dispatched on a zero-padded selector.",
+            ));
+        }
+        if !self.colliding_selectors.is_empty() {
+            warnings.push(format!(
+                "the dispatcher checks for selector(s) more than once, shadowing the later functions: {}.",
+                list(&self.colliding_selectors)
+            ));
+        }
+        if !self.fallthrough_selectors.is_empty() {
+            warnings.push(format!(
+                "selector(s) fall through into the fallback function: {}.",
+                list(&self.fallthrough_selectors)
+            ));
+        }
+        warnings
+    }
+}
+
+/// Whether the dispatcher reads the selector before checking that calldata holds one, when
+/// called without calldata.
+fn missing_length_check(evm: &VM) -> bool {
+    let mut vm = evm.clone();
+    vm.calldata = Vec::new();
+
+    let mut steps = 0;
+    while vm.bytecode.len() >= vm.instruction as usize && steps < MAX_DISPATCHER_STEPS {
+        let state = vm.step();
+        steps += 1;
+
+        let instruction = &state.last_instruction;
+        match instruction.opcode {
+            // the selector is read before the length is checked
+            0x35 if instruction.inputs.first() == Some(&U256::zero()) => return true,
+            0x57 if instruction.input_operations[1].to_string().contains("CALLDATASIZE") => {
+                return false
+            }
+            _ => {}
+        }
+        if vm.exitcode != 255 || !vm.returndata.is_empty() {
+            break
+        }
+    }
+
+    false
+}
+
+/// Find the anomalies in the contract's dispatcher, given the selectors it dispatches and their
+/// entry points. Selectors which are checked more than once are found by following calls which
+/// pass each check, and selectors which fall through into the fallback function are those whose
+/// entry point is the fallback function's.
+pub fn find_dispatcher_anomalies(
+    evm: &VM,
+    selectors: &HashMap<String, u128>,
+) -> DispatcherAnomalies {
+    if selectors.is_empty() {
+        return DispatcherAnomalies::default()
+    }
+
+    // calls just past each selector reach the checks which follow it
+    let mut probes = selectors
+        .keys()
+        .filter_map(|selector| u32::from_str_radix(selector, 16).ok())
+        .map(|selector| selector.wrapping_add(1))
+        .chain([0, u32::MAX])
+        .collect::<Vec<u32>>();
+    probes.sort();
+    probes.dedup();
 
-use super::{evm::core::vm::VM, signatures::ResolveSelector};
+    let mut colliding_selectors = HashSet::new();
+    for probe in probes {
+        let mut checked = HashSet::new();
+        for comparison in dispatcher_comparisons(evm, probe) {
+            if let DispatcherComparison::Equality(selector) = comparison {
+                if !checked.insert(selector) {
+                    colliding_selectors.insert(format!("{:08x}", selector.as_u32()));
+                }
+            }
+        }
+    }
+
+    // any selector which the dispatcher doesn't recognize reaches the fallback function
+    let unknown_selector = (0u32..)
+        .map(|selector| selector.to_be_bytes())
+        .find(|selector| !selectors.contains_key(&encode_hex(selector.to_vec())))
+        .unwrap_or_default();
+    let fallback_entry_point = evm.dispatch(&unknown_selector, 0).map(|vm| vm.instruction - 1);
+    let mut fallthrough_selectors = selectors
+        .iter()
+        .filter(|(_, entry_point)| Some(**entry_point) == fallback_entry_point)
+        .map(|(selector, _)| selector.clone())
+        .collect::<Vec<String>>();
+    fallthrough_selectors.sort();
+
+    let mut colliding_selectors = colliding_selectors.into_iter().collect::<Vec<String>>();
+    colliding_selectors.sort();
+    DispatcherAnomalies {
+        missing_length_check: missing_length_check(evm),
+        colliding_selectors,
+        fallthrough_selectors,
+    }
+}
 
 /// find all function selectors in the given EVM assembly.
 pub fn find_function_selectors(evm: &VM, assembly: &str) -> HashMap<String, u128> {
@@ -19,81 +334,97 @@ pub fn find_function_selectors(evm: &VM, assembly: &str) -> HashMap<String, u128
     // get a new logger
     let logger = Logger::default();
 
-    // search through assembly for PUSHN (where N <= 4) instructions, optimistically assuming that
-    // they are function selectors
+    // search through assembly for PUSH3 and PUSH4 instructions, optimistically assuming that
+    // they are function selectors. selectors with a leading zero byte are pushed with PUSH3.
+    let mut candidates = Vec::new();
     let assembly: Vec<String> = assembly.split('\n').map(|line| line.trim().to_string()).collect();
     for line in assembly.iter() {
         let instruction_args: Vec<String> = line.split(' ').map(|arg| arg.to_string()).collect();
 
-        if instruction_args.len() >= 2 {
-            let instruction = instruction_args[1].clone();
-
-            if &instruction == "PUSH4" {
-                let function_selector = instruction_args[2].clone();
-
-                // check if this function selector has already been handled
-                if handled_selectors.contains(&function_selector) {
-                    continue
-                }
+        if instruction_args.len() >= 3 && ["PUSH3", "PUSH4"].contains(&instruction_args[1].as_str())
+        {
+            logger.debug_max(&format!(
+                "optimistically assuming instruction {} {} {} is a function selector",
+                instruction_args[0], instruction_args[1], instruction_args[2]
+            ));
+            candidates.push(format!("{:0>8}", instruction_args[2]));
+        }
+    }
 
-                logger.debug_max(&format!(
-                    "optimistically assuming instruction {} {} {} is a function selector",
-                    instruction_args[0], instruction_args[1], instruction_args[2]
-                ));
+    // selectors which non-linear dispatchers compute or store as data aren't pushed
+    candidates.extend(explore_dispatcher(evm));
 
-                // add the function selector to the handled selectors
-                handled_selectors.insert(function_selector.clone());
+    for function_selector in candidates {
+        // check if this function selector has already been handled
+        if !handled_selectors.insert(function_selector.clone()) {
+            continue
+        }
 
-                // get the function's entry point
-                let function_entry_point =
-                    match resolve_entry_point(&evm.clone(), &function_selector) {
-                        0 => continue,
-                        x => x,
-                    };
+        // get the function's entry point
+        let function_entry_point = match resolve_entry_point(&evm.clone(), &function_selector) {
+            0 => continue,
+            x => x,
+        };
 
-                logger.debug_max(&format!(
-                    "found function selector {} at entry point {}",
-                    function_selector, function_entry_point
-                ));
+        logger.debug_max(&format!(
+            "found function selector {} at entry point {}",
+            function_selector, function_entry_point
+        ));
 
-                function_selectors.insert(function_selector, function_entry_point);
-            }
-        }
+        function_selectors.insert(function_selector, function_entry_point);
     }
     function_selectors
 }
 
-/// resolve a selector's function entry point from the EVM bytecode
+/// resolve a selector's function entry point from the EVM bytecode. The entry point is where
+/// control goes after the dispatcher checks the selector for equality, whether by jumping to the
+/// function or by falling through to it.
 pub fn resolve_entry_point(evm: &VM, selector: &str) -> u128 {
     let mut vm = evm.clone();
     let mut handled_jumps = HashSet::new();
+    let selector_value = match U256::from_str_radix(selector, 16) {
+        Ok(selector_value) => selector_value,
+        Err(_) => return 0,
+    };
 
     // execute the EVM call to find the entry point for the given selector
     vm.calldata = decode_hex(selector).expect("Failed to decode selector.");
-    while vm.bytecode.len() >= vm.instruction as usize {
+    let mut matched = false;
+    let mut steps = 0;
+    while vm.bytecode.len() >= vm.instruction as usize && steps < MAX_DISPATCHER_STEPS {
         let call = vm.step();
+        steps += 1;
+        let instruction = &call.last_instruction;
 
-        // if the opcode is an JUMPI and it matched the selector, the next jumpi is the entry point
-        if call.last_instruction.opcode == 0x57 {
-            let jump_condition = call.last_instruction.input_operations[1].solidify();
-            let jump_taken = call.last_instruction.inputs[1].try_into().unwrap_or(1);
-
-            if jump_condition.contains(selector) &&
-                jump_condition.contains("msg.data[0]") &&
-                jump_condition.contains(" == ") &&
-                jump_taken == 1
-            {
-                return call.last_instruction.inputs[0].try_into().unwrap_or(0)
-            } else if jump_taken == 1 {
-                // if handled_jumps contains the jumpi, we have already handled this jump.
-                // loops aren't supported in the dispatcher, so we can just return 0
-                if handled_jumps.contains(&call.last_instruction.inputs[0].try_into().unwrap_or(0))
-                {
-                    return 0
-                } else {
-                    handled_jumps.insert(call.last_instruction.inputs[0].try_into().unwrap_or(0));
-                }
+        // the selector has matched once it is compared with its own value
+        if dispatcher_comparison(instruction) ==
+            Some(DispatcherComparison::Equality(selector_value))
+        {
+            matched = match instruction.opcode {
+                0x14 => instruction.outputs.first() == Some(&U256::one()),
+                _ => instruction.outputs.first() == Some(&U256::zero()),
+            };
+        }
+
+        // the first jump after the selector matches hands control to the function
+        if matched && instruction.opcode == 0x57 {
+            return match instruction.inputs[1].is_zero() {
+                true => instruction.instruction,
+                false => instruction.inputs[0].try_into().unwrap_or(0),
             }
+        } else if matched && instruction.opcode == 0x56 {
+            return instruction.inputs[0].try_into().unwrap_or(0)
+        }
+
+        // if handled_jumps contains the jump, we have already handled it. loops aren't
+        // supported in the dispatcher, so we can just return 0
+        let jump_taken = match instruction.opcode {
+            0x56 => true,
+            0x57 => !instruction.inputs[1].is_zero(),
+            _ => false,
+        };
+        if jump_taken && !handled_jumps.insert(instruction.inputs[0].try_into().unwrap_or(0u128)) {
+            return 0
         }
 
         if vm.exitcode != 255 || !vm.returndata.is_empty() {
@@ -104,7 +435,9 @@ pub fn resolve_entry_point(evm: &VM, selector: &str) -> u128 {
     0
 }
 
-/// Resolve a list of selectors to their function signatures.
+/// Resolve a list of selectors to their function signatures. Selectors are looked up in batches
+/// of [`MAX_BATCH_SIZE`] where the backend supports it, and the selectors of any batch which
+/// couldn't be resolved are looked up individually and concurrently.
 pub async fn resolve_selectors<T>(selectors: Vec<String>) -> HashMap<String, Vec<T>>
 where
     T: ResolveSelector + Send + Clone + 'static, {
@@ -132,7 +465,26 @@ where
         .expect("Could not obtain lock on resolve_progress.")
         .set_message("resolving selectors");
 
-    for selector in selectors {
+    // resolve as many selectors as possible with batch requests
+    let mut remaining = Vec::new();
+    for batch in selectors.chunks(MAX_BATCH_SIZE) {
+        match T::resolve_batch(batch).await {
+            Some(results) => {
+                let mut _resolved_functions = resolved_functions
+                    .lock()
+                    .expect("Could not obtain lock on resolved_functions.");
+                _resolved_functions
+                    .extend(results.into_iter().filter(|(_, functions)| !functions.is_empty()));
+                resolve_progress
+                    .lock()
+                    .expect("Could not obtain lock on resolve_progress.")
+                    .set_message(format!("resolved {} selectors", _resolved_functions.len()));
+            }
+            None => remaining.extend_from_slice(batch),
+        }
+    }
+
+    for selector in remaining {
         let function_clone = resolved_functions.clone();
         let resolve_progress = resolve_progress.clone();
 
@@ -164,3 +516,146 @@ where
         resolved_functions.lock().expect("Could not obtain lock on resolved_functions.").clone();
     x
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vm(bytecode: &str) -> VM {
+        VM::new(
+            bytecode.to_string(),
+            String::from("0x"),
+            String::from("0x6865696d64616c6c000000000061646472657373"),
+            String::from("0x6865696d64616c6c0000000000006f726967696e"),
+            String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+            0,
+            u128::MAX,
+        )
+    }
+
+    #[test]
+    fn test_find_dispatcher_anomalies() {
+        // the selector is read without a length check, 0x22222222 jumps to the fallback, and
+        // 0x11111111 is checked twice, the second time jumping to a shadowed function
+        let bytecode = [
+            "600035",
+            "60e01c",
+            "80",
+            "6311111111",
+            "14",
+            "602b",
+            "57",
+            "80",
+            "6322222222",
+            "14",
+            "6024",
+            "57",
+            "80",
+            "6311111111",
+            "14",
+            "6032",
+            "57",
+            "5b600260005500",
+            "5b600160005500",
+            "5b600360005500",
+        ]
+        .concat();
+        let evm = vm(&bytecode);
+        let selectors = find_function_selectors(&evm, "");
+
+        let anomalies = find_dispatcher_anomalies(&evm, &selectors);
+        assert!(anomalies.missing_length_check);
+        assert_eq!(anomalies.colliding_selectors, vec!["11111111"]);
+        assert_eq!(anomalies.fallthrough_selectors, vec!["22222222"]);
+        assert_eq!(anomalies.warnings().len(), 3);
+
+        // a solidity dispatcher, whose fallback reverts, has none
+        let evm = vm("6004361060175760003560e01c80631111111114601c575b600080fd5b600160005500");
+        let selectors = find_function_selectors(&evm, "");
+        assert_eq!(find_dispatcher_anomalies(&evm, &selectors), DispatcherAnomalies::default());
+    }
+
+    #[test]
+    fn test_find_function_selectors_binary_search_dispatcher() {
+        // selectors above the pivot are checked first, the rest after jumping to the low half
+        let bytecode = [
+            "600035",
+            "60e01c",
+            "80",
+            "6380000000",
+            "11",
+            "6029",
+            "57",
+            "80",
+            "63aaaaaaaa",
+            "14",
+            "6022",
+            "57",
+            "00",
+            "5b600260005500",
+            "5b600160005500",
+            "5b",
+            "80",
+            "6311111111",
+            "14",
+            "601b",
+            "57",
+            "00",
+        ]
+        .join("");
+
+        let selectors = find_function_selectors(&vm(&bytecode), "");
+        assert_eq!(selectors.len(), 2);
+        assert_eq!(selectors.get("aaaaaaaa"), Some(&34));
+        assert_eq!(selectors.get("11111111"), Some(&27));
+        assert_eq!(resolve_entry_point(&vm(&bytecode), "80000000"), 0);
+        assert_eq!(dispatcher_kind(&vm(&bytecode)), DispatcherKind::BinarySearch);
+    }
+
+    #[test]
+    fn test_find_function_selectors_jump_table_dispatcher() {
+        // the dispatcher jumps to one of two buckets by the selector's parity. the first bucket
+        // computes its selector rather than pushing it, and the second falls through to its
+        // function when the selector matches
+        let bytecode = [
+            "600035",
+            "60e01c",
+            "6002",
+            "81",
+            "06",
+            "6010",
+            "02",
+            "6020",
+            "01",
+            "56",
+            &"fe".repeat(15),
+            "5b",
+            "80",
+            "62abcdef",
+            "6008",
+            "1b",
+            "14",
+            "6048",
+            "57",
+            "00",
+            "fefe",
+            "5b",
+            "80",
+            "6312345679",
+            "18",
+            "6041",
+            "57",
+            "600260005500",
+            "5b00",
+            &"fe".repeat(5),
+            "5b600160005500",
+        ]
+        .join("");
+
+        let selectors = find_function_selectors(&vm(&bytecode), "");
+        assert_eq!(selectors.len(), 2);
+        assert_eq!(selectors.get("abcdef00"), Some(&72));
+        assert_eq!(selectors.get("12345679"), Some(&59));
+        assert_eq!(dispatcher_kind(&vm(&bytecode)), DispatcherKind::JumpTable);
+    }
+}
@@ -1,9 +1,17 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use ethers::abi::Token;
 use heimdall_cache::{read_cache, store_cache};
 
 use crate::utils::{http::get_json_from_url, io::logging::Logger, strings::replace_last};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+/// How long a selector which couldn't be resolved is remembered for, in seconds. This is much
+/// shorter than the lifetime of resolved signatures, so selectors are retried once the signature
+/// databases have had a chance to learn them.
+pub const NEGATIVE_CACHE_TTL: u64 = 60 * 60 * 24;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ResolvedFunction {
@@ -27,15 +35,170 @@ pub struct ResolvedLog {
     pub inputs: Vec<String>,
 }
 
+/// The maximum number of selectors looked up in a single batch request.
+pub const MAX_BATCH_SIZE: usize = 50;
+
+/// Read the cached signatures for the selector. Returns an empty list if the selector recently
+/// couldn't be resolved, and `None` if it should be looked up.
+fn read_cached_signatures<T>(selector: &str) -> Option<Vec<T>>
+where
+    T: 'static + DeserializeOwned, {
+    match read_cache::<Vec<T>>(&format!("selector.{selector}")) {
+        // empty results cached before negative lookups expired are ignored, so they're retried
+        Some(results) if !results.is_empty() => Some(results),
+        _ => read_cache::<bool>(&format!("selector.negative.{selector}")).map(|_| Vec::new()),
+    }
+}
+
+/// Cache the signatures resolved for the selector. Resolved signatures are long-lived, while
+/// selectors which couldn't be resolved are cached under a distinct key for
+/// [`NEGATIVE_CACHE_TTL`] seconds.
+fn cache_signatures<T>(selector: &str, signatures: &[T])
+where
+    T: Serialize, {
+    match signatures.is_empty() {
+        true => {
+            let expiry = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() +
+                NEGATIVE_CACHE_TTL;
+            store_cache(&format!("selector.negative.{selector}"), true, Some(expiry))
+        }
+        false => store_cache(&format!("selector.{selector}"), signatures, None),
+    }
+}
+
+/// Split a text signature, such as `transfer(address,uint256)`, into its name and inputs.
+fn split_text_signature(text_signature: &str) -> Option<(String, Vec<String>)> {
+    let (name, inputs) = text_signature.split_once('(')?;
+    Some((
+        name.to_string(),
+        replace_last(inputs, ")", "").split(',').map(|input| input.to_string()).collect(),
+    ))
+}
+
+/// Parse a batch lookup response from the OpenChain signature database into the text signatures
+/// found for each selector, within the given namespace (`function` or `event`). Selectors are
+/// returned without their `0x` prefix. Returns `None` if the lookup failed.
+fn parse_batch_response(response: &Value, namespace: &str) -> Option<HashMap<String, Vec<String>>> {
+    if response.get("ok")?.as_bool() != Some(true) {
+        return None
+    }
+
+    Some(
+        response
+            .get("result")?
+            .get(namespace)?
+            .as_object()?
+            .iter()
+            .map(|(selector, signatures)| {
+                (
+                    selector.replacen("0x", "", 1).to_lowercase(),
+                    signatures
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|signature| signature.get("name")?.as_str())
+                        .map(|signature| signature.to_string())
+                        .collect(),
+                )
+            })
+            .collect(),
+    )
+}
+
+/// Resolve a batch of selectors within the given namespace using the OpenChain signature
+/// database, building each signature with `build`. Cached selectors aren't looked up again, and
+/// both the resolved and the unresolved selectors are cached.
+async fn resolve_batch_with<T, F>(
+    selectors: &[String],
+    namespace: &str,
+    build: F,
+) -> Option<HashMap<String, Vec<T>>>
+where
+    T: 'static + Clone + Serialize + DeserializeOwned,
+    F: Fn(String, String, Vec<String>) -> T, {
+    // get a new logger
+    let logger = Logger::default();
+
+    let mut results = HashMap::new();
+    let mut uncached = Vec::new();
+    for selector in selectors {
+        match read_cached_signatures::<T>(selector) {
+            Some(cached_results) => {
+                results.insert(selector.to_owned(), cached_results);
+            }
+            None => uncached.push(selector.to_owned()),
+        }
+    }
+    if uncached.is_empty() {
+        return Some(results)
+    }
+
+    logger.debug_max(&format!("resolving {} {namespace} selectors in a batch", uncached.len()));
+
+    let response = get_json_from_url(
+        &format!(
+            "https://api.openchain.xyz/signature-database/v1/lookup?{namespace}={}&filter=false",
+            uncached
+                .iter()
+                .map(|selector| format!("0x{}", selector.replacen("0x", "", 1)))
+                .collect::<Vec<String>>()
+                .join(",")
+        ),
+        10,
+    )
+    .await
+    .ok()??;
+    let mut signatures = parse_batch_response(&response, namespace)?;
+
+    for selector in uncached {
+        let signature_list = signatures
+            .remove(&selector.replacen("0x", "", 1).to_lowercase())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|text_signature| {
+                let (name, inputs) = split_text_signature(&text_signature)?;
+                Some(build(name, text_signature, inputs))
+            })
+            .collect::<Vec<T>>();
+
+        cache_signatures(&selector, &signature_list);
+        results.insert(selector, signature_list);
+    }
+
+    Some(results)
+}
+
 #[async_trait]
 pub trait ResolveSelector {
     async fn resolve(selector: &str) -> Option<Vec<Self>>
     where
         Self: Sized;
+
+    /// Resolve many selectors with a single request, for backends which support batch queries.
+    /// Returns `None` if the batch couldn't be resolved, in which case each selector should be
+    /// resolved individually.
+    async fn resolve_batch(_selectors: &[String]) -> Option<HashMap<String, Vec<Self>>>
+    where
+        Self: Sized, {
+        None
+    }
 }
 
 #[async_trait]
 impl ResolveSelector for ResolvedError {
+    async fn resolve_batch(selectors: &[String]) -> Option<HashMap<String, Vec<Self>>> {
+        // errors share their selector namespace with functions
+        resolve_batch_with(selectors, "function", |name, signature, inputs| ResolvedError {
+            name,
+            signature,
+            inputs,
+        })
+        .await
+    }
+
     async fn resolve(selector: &str) -> Option<Vec<Self>> {
         // get a new logger
         let logger = Logger::default();
@@ -43,9 +206,7 @@ impl ResolveSelector for ResolvedError {
         logger.debug_max(&format!("resolving error selector {}", &selector));
 
         // get cached results
-        if let Some(cached_results) =
-            read_cache::<Vec<ResolvedError>>(&format!("selector.{selector}"))
-        {
+        if let Some(cached_results) = read_cached_signatures::<ResolvedError>(selector) {
             match cached_results.len() {
                 0 => return None,
                 _ => {
@@ -108,7 +269,7 @@ impl ResolveSelector for ResolvedError {
         }
 
         // cache the results
-        store_cache(&format!("selector.{selector}"), signature_list.clone(), None);
+        cache_signatures(selector, &signature_list);
 
         match signature_list.len() {
             0 => None,
@@ -119,6 +280,15 @@ impl ResolveSelector for ResolvedError {
 
 #[async_trait]
 impl ResolveSelector for ResolvedLog {
+    async fn resolve_batch(selectors: &[String]) -> Option<HashMap<String, Vec<Self>>> {
+        resolve_batch_with(selectors, "event", |name, signature, inputs| ResolvedLog {
+            name,
+            signature,
+            inputs,
+        })
+        .await
+    }
+
     async fn resolve(selector: &str) -> Option<Vec<Self>> {
         // get a new logger
         let logger = Logger::default();
@@ -126,9 +296,7 @@ impl ResolveSelector for ResolvedLog {
         logger.debug_max(&format!("resolving event selector {}", &selector));
 
         // get cached results
-        if let Some(cached_results) =
-            read_cache::<Vec<ResolvedLog>>(&format!("selector.{selector}"))
-        {
+        if let Some(cached_results) = read_cached_signatures::<ResolvedLog>(selector) {
             match cached_results.len() {
                 0 => return None,
                 _ => {
@@ -191,7 +359,7 @@ impl ResolveSelector for ResolvedLog {
         }
 
         // cache the results
-        store_cache(&format!("selector.{selector}"), signature_list.clone(), None);
+        cache_signatures(selector, &signature_list);
 
         match signature_list.len() {
             0 => None,
@@ -202,6 +370,16 @@ impl ResolveSelector for ResolvedLog {
 
 #[async_trait]
 impl ResolveSelector for ResolvedFunction {
+    async fn resolve_batch(selectors: &[String]) -> Option<HashMap<String, Vec<Self>>> {
+        resolve_batch_with(selectors, "function", |name, signature, inputs| ResolvedFunction {
+            name,
+            signature,
+            inputs,
+            decoded_inputs: None,
+        })
+        .await
+    }
+
     async fn resolve(selector: &str) -> Option<Vec<Self>> {
         // get a new logger
         let logger = Logger::default();
@@ -209,9 +387,7 @@ impl ResolveSelector for ResolvedFunction {
         logger.debug_max(&format!("resolving event selector {}", &selector));
 
         // get cached results
-        if let Some(cached_results) =
-            read_cache::<Vec<ResolvedFunction>>(&format!("selector.{selector}"))
-        {
+        if let Some(cached_results) = read_cached_signatures::<ResolvedFunction>(selector) {
             match cached_results.len() {
                 0 => return None,
                 _ => {
@@ -275,7 +451,7 @@ impl ResolveSelector for ResolvedFunction {
         }
 
         // cache the results
-        store_cache(&format!("selector.{selector}"), signature_list.clone(), None);
+        cache_signatures(selector, &signature_list);
 
         match signature_list.len() {
             0 => None,
@@ -298,12 +474,43 @@ pub fn score_signature(signature: &str) -> u32 {
     score
 }
 
+/// The confidence that the selected signature is the function's true signature, from 0 to 1,
+/// given every candidate signature which matched the function. Each candidate is weighted by its
+/// [`score_signature`], where every 10 points below the best score halves its weight, and the
+/// confidence is the selected signature's share of the total weight. A sole candidate is fully
+/// confident, and a signature which isn't a candidate has no confidence.
+///
+/// ```
+/// use heimdall_common::ether::signatures::signature_confidence;
+///
+/// let candidates = vec![String::from("transferOwnership(address)")];
+/// assert_eq!(signature_confidence("transferOwnership(address)", &candidates), 1.0);
+/// assert_eq!(signature_confidence("owner()", &candidates), 0.0);
+/// ```
+pub fn signature_confidence(selected: &str, candidates: &[String]) -> f64 {
+    let best_score = match candidates.iter().map(|signature| score_signature(signature)).max() {
+        Some(best_score) => best_score,
+        None => return 0.0,
+    };
+    let weight =
+        |signature: &str| 0.5f64.powf((best_score - score_signature(signature)) as f64 / 10.0);
+
+    match candidates.iter().any(|signature| signature == selected) {
+        true => {
+            weight(selected) / candidates.iter().map(|signature| weight(signature)).sum::<f64>()
+        }
+        false => 0.0,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use heimdall_cache::delete_cache;
 
     use crate::ether::signatures::{
-        score_signature, ResolveSelector, ResolvedError, ResolvedFunction, ResolvedLog,
+        cache_signatures, parse_batch_response, read_cached_signatures, score_signature,
+        signature_confidence, split_text_signature, ResolveSelector, ResolvedError,
+        ResolvedFunction, ResolvedLog,
     };
 
     #[tokio::test]
@@ -374,4 +581,79 @@ mod tests {
             (signature.matches(|c: char| c.is_numeric()).count() as u32) * 3;
         assert_eq!(score, expected_score);
     }
+
+    #[test]
+    fn test_signature_confidence() {
+        let candidates = vec![
+            String::from("transferOwnership(address)"),
+            String::from("transferOwnership_1234567(address)"),
+        ];
+
+        // the shorter, less numeric signature is preferred, but a rival lowers the confidence
+        let confidence = signature_confidence(&candidates[0], &candidates);
+        assert!(confidence > 0.5 && confidence < 1.0);
+        assert!(
+            (confidence + signature_confidence(&candidates[1], &candidates) - 1.0).abs() < 1e-9
+        );
+
+        // equally scored candidates split the confidence evenly
+        let candidates = vec![String::from("foo(uint256)"), String::from("bar(uint256)")];
+        assert_eq!(signature_confidence("foo(uint256)", &candidates), 0.5);
+        assert_eq!(signature_confidence("foo(uint256)", &[]), 0.0);
+    }
+
+    #[test]
+    fn test_negative_results_are_cached_separately() {
+        let selector = "test_negative_cache";
+        delete_cache(&format!("selector.{selector}"));
+        delete_cache(&format!("selector.negative.{selector}"));
+        assert!(read_cached_signatures::<ResolvedLog>(selector).is_none());
+
+        // an unresolved selector is remembered under its own key, without a positive entry
+        cache_signatures::<ResolvedLog>(selector, &[]);
+        assert_eq!(read_cached_signatures::<ResolvedLog>(selector), Some(Vec::new()));
+        assert!(heimdall_cache::read_cache::<Vec<ResolvedLog>>(&format!("selector.{selector}"))
+            .is_none());
+
+        // resolved signatures take precedence over the negative entry
+        let resolved = vec![ResolvedLog {
+            name: String::from("Transfer"),
+            signature: String::from("Transfer(address,address,uint256)"),
+            inputs: vec![String::from("address"), String::from("address"), String::from("uint256")],
+        }];
+        cache_signatures(selector, &resolved);
+        assert_eq!(read_cached_signatures::<ResolvedLog>(selector), Some(resolved));
+
+        delete_cache(&format!("selector.{selector}"));
+        delete_cache(&format!("selector.negative.{selector}"));
+    }
+
+    #[test]
+    fn test_parse_batch_response() {
+        let response = serde_json::json!({
+            "ok": true,
+            "result": {
+                "event": {},
+                "function": {
+                    "0xa9059cbb": [{ "name": "transfer(address,uint256)", "filtered": false }],
+                    "0x12345678": null
+                }
+            }
+        });
+
+        let results = parse_batch_response(&response, "function").unwrap();
+        assert_eq!(results["a9059cbb"], vec![String::from("transfer(address,uint256)")]);
+        assert!(results["12345678"].is_empty());
+        assert!(parse_batch_response(&response, "event").unwrap().is_empty());
+        assert!(parse_batch_response(&serde_json::json!({ "ok": false }), "function").is_none());
+
+        assert_eq!(
+            split_text_signature("transfer(address,uint256)"),
+            Some((
+                String::from("transfer"),
+                vec![String::from("address"), String::from("uint256")]
+            ))
+        );
+        assert_eq!(split_text_signature("transfer"), None);
+    }
 }
@@ -0,0 +1,301 @@
+use std::{collections::HashSet, fmt};
+
+use ethers::types::{H160, U256};
+
+use crate::ether::evm::core::opcodes::{WrappedInput, WrappedOpcode};
+
+/// The type of a storage variable, as inferred statically from the operations which write it, or
+/// dynamically from the values it has held. This is the storage model shared by `decompile` and
+/// `dump`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorageType {
+    Bool,
+    Address,
+    /// An unsigned integer of the given number of bits.
+    Uint(usize),
+    /// A fixed-size byte array of the given number of bytes.
+    Bytes(usize),
+    String,
+}
+
+impl StorageType {
+    /// Parse a Solidity value type, such as `uint8` or `address`. Returns `None` for types which
+    /// storage values aren't modeled as, such as signed integers and mappings.
+    ///
+    /// ```
+    /// use heimdall_common::ether::storage::StorageType;
+    ///
+    /// assert_eq!(StorageType::from_solidity("uint8"), Some(StorageType::Uint(8)));
+    /// assert_eq!(StorageType::from_solidity("string memory"), Some(StorageType::String));
+    /// assert_eq!(StorageType::from_solidity("int256"), None);
+    /// ```
+    pub fn from_solidity(solidity_type: &str) -> Option<Self> {
+        let solidity_type = solidity_type.trim().trim_end_matches(" memory");
+        match solidity_type {
+            "bool" => Some(StorageType::Bool),
+            "address" | "address payable" => Some(StorageType::Address),
+            "string" => Some(StorageType::String),
+            "uint" => Some(StorageType::Uint(256)),
+            _ => {
+                let (size, scale, max) = match solidity_type {
+                    t if t.starts_with("uint") => (&t[4..], 8, 256),
+                    t if t.starts_with("bytes") => (&t[5..], 1, 32),
+                    _ => return None,
+                };
+                let size = size.parse::<usize>().ok()?;
+                if size == 0 || size > max || size % scale != 0 {
+                    return None
+                }
+
+                match scale {
+                    8 => Some(StorageType::Uint(size)),
+                    _ => Some(StorageType::Bytes(size)),
+                }
+            }
+        }
+    }
+
+    /// Whether the type can hold the given storage value, assuming the variable isn't packed
+    /// with others. Short strings are stored with twice their length in the lowest byte, while
+    /// long strings store twice their length plus one.
+    ///
+    /// ```
+    /// use ethers::types::U256;
+    /// use heimdall_common::ether::storage::StorageType;
+    ///
+    /// assert!(StorageType::Bool.admits(U256::one()));
+    /// assert!(!StorageType::Bool.admits(U256::from(2)));
+    /// assert!(!StorageType::Address.admits(U256::MAX));
+    /// ```
+    pub fn admits(&self, value: U256) -> bool {
+        match self {
+            StorageType::Bool => value <= U256::one(),
+            StorageType::Address => value.bits() <= 160,
+            StorageType::Uint(bits) => value.bits() <= *bits,
+            StorageType::Bytes(size) => value.bits() <= size * 8,
+            StorageType::String => {
+                if value.bit(0) {
+                    return value.bits() <= 64
+                }
+
+                // the bytes following a short string are zeroed
+                let length = value.low_u64() as usize & 0xff;
+                length < 64 && {
+                    let mut word = [0u8; 32];
+                    value.to_big_endian(&mut word);
+                    word[length / 2..31].iter().all(|byte| *byte == 0)
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for StorageType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageType::Bool => write!(f, "bool"),
+            StorageType::Address => write!(f, "address"),
+            StorageType::Uint(bits) => write!(f, "uint{bits}"),
+            StorageType::Bytes(size) => write!(f, "bytes{size}"),
+            StorageType::String => write!(f, "string"),
+        }
+    }
+}
+
+/// The value of a constant input, either raw or pushed onto the stack.
+fn constant(input: &WrappedInput) -> Option<U256> {
+    match input {
+        WrappedInput::Raw(value) => Some(*value),
+        WrappedInput::Opcode(operation) if operation.opcode.name.starts_with("PUSH") => {
+            match operation.inputs.first()? {
+                WrappedInput::Raw(value) => Some(*value),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Statically infer the type of a storage variable from the operation which computes the value
+/// written to it. Values are typed by the mask they're cleaned with, by whether they're the
+/// result of a comparison, or by the environment value or argument they're copied from. The type
+/// of the `n`th argument is given by `argument_type`.
+///
+/// ```
+/// use heimdall_common::ether::{
+///     evm::core::opcodes::{WrappedInput, WrappedOpcode},
+///     storage::{infer_from_operation, StorageType},
+/// };
+///
+/// let caller = WrappedOpcode::new(0x33, vec![]);
+/// assert_eq!(infer_from_operation(&caller, |_| None), Some(StorageType::Address));
+///
+/// let argument = WrappedOpcode::new(0x35, vec![WrappedInput::Raw(36.into())]);
+/// assert_eq!(
+///     infer_from_operation(&argument, |n| (n == 1).then(|| String::from("uint64"))),
+///     Some(StorageType::Uint(64))
+/// );
+/// ```
+pub fn infer_from_operation(
+    operation: &WrappedOpcode,
+    argument_type: impl Fn(usize) -> Option<String>,
+) -> Option<StorageType> {
+    match operation.opcode.name {
+        "ISZERO" | "LT" | "GT" | "SLT" | "SGT" | "EQ" => Some(StorageType::Bool),
+        "CALLER" | "ORIGIN" | "ADDRESS" | "COINBASE" => Some(StorageType::Address),
+        "TIMESTAMP" | "NUMBER" | "CALLVALUE" | "CHAINID" | "BALANCE" | "SELFBALANCE" => {
+            Some(StorageType::Uint(256))
+        }
+        "CALLDATALOAD" => match constant(operation.inputs.first()?) {
+            Some(offset) if offset >= U256::from(4) && offset.bits() <= 32 => {
+                let index = (offset.as_usize() - 4) / 32;
+                StorageType::from_solidity(&argument_type(index)?)
+            }
+            _ => None,
+        },
+        "AND" => {
+            // values are cleaned with a mask of their size, i.e. `0xff` for a uint8
+            operation.inputs.iter().find_map(|input| match constant(input) {
+                Some(mask)
+                    if !mask.is_zero() &&
+                        mask.overflowing_add(1.into()).0 & mask == U256::zero() =>
+                {
+                    match mask.bits() {
+                        160 => Some(StorageType::Address),
+                        bits if bits % 8 == 0 => Some(StorageType::Uint(bits)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Dynamically infer the type of a storage variable from every value it has held, in the order
+/// they were written. Variables which only ever hold 0 or 1 are bools, those which only hold
+/// known addresses are addresses, and those which only increase are counters or timestamps.
+/// Returns `None` if the values don't suggest a type.
+pub fn infer_from_values(values: &[U256], known_addresses: &HashSet<H160>) -> Option<StorageType> {
+    if values.is_empty() {
+        return None
+    }
+
+    // only ever 0 or 1
+    if values.iter().all(|value| *value <= U256::one()) {
+        return Some(StorageType::Bool)
+    }
+
+    // only ever known addresses, or unset
+    if values.iter().all(|value| {
+        let mut word = [0u8; 32];
+        value.to_big_endian(&mut word);
+        value.is_zero() ||
+            (value.bits() <= 160 && known_addresses.contains(&H160::from_slice(&word[12..])))
+    }) {
+        return Some(StorageType::Address)
+    }
+
+    // monotonically increasing counters and timestamps
+    if values.len() > 1 &&
+        values.windows(2).all(|pair| pair[0] <= pair[1]) &&
+        values.first() != values.last()
+    {
+        return Some(StorageType::Uint(256))
+    }
+
+    None
+}
+
+/// The type of a storage variable, reconciled from its static and dynamic inferences.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StorageTyping {
+    pub storage_type: StorageType,
+    /// How likely the type is to be correct, from 0 to 1.
+    pub confidence: f64,
+    /// Whether the values observed in storage contradict the statically inferred type.
+    pub conflict: bool,
+}
+
+/// Reconcile the statically inferred type of a storage variable with the values it has been
+/// observed to hold. Observed values are ground truth, so a static type which can't hold them is
+/// overruled, while one which can is preferred over the narrower guesses values alone suggest.
+///
+/// ```
+/// use ethers::types::U256;
+/// use heimdall_common::ether::storage::{reconcile, StorageType};
+/// use std::collections::HashSet;
+///
+/// let values = vec![U256::from(1), U256::from(7)];
+/// let typing = reconcile(Some(StorageType::Uint(8)), &values, &HashSet::new()).unwrap();
+/// assert_eq!((typing.storage_type, typing.conflict), (StorageType::Uint(8), false));
+///
+/// let typing = reconcile(Some(StorageType::Bool), &values, &HashSet::new()).unwrap();
+/// assert_eq!((typing.storage_type, typing.conflict), (StorageType::Uint(256), true));
+/// ```
+pub fn reconcile(
+    static_type: Option<StorageType>,
+    values: &[U256],
+    known_addresses: &HashSet<H160>,
+) -> Option<StorageTyping> {
+    let dynamic_type = infer_from_values(values, known_addresses);
+    let typing = |storage_type, confidence, conflict| {
+        Some(StorageTyping { storage_type, confidence, conflict })
+    };
+
+    match (static_type, dynamic_type) {
+        (Some(static_type), _) if values.is_empty() => typing(static_type, 0.5, false),
+        (Some(static_type), dynamic_type)
+            if values.iter().all(|value| static_type.admits(*value)) =>
+        {
+            match dynamic_type == Some(static_type) {
+                true => typing(static_type, 0.95, false),
+                false => typing(static_type, 0.8, false),
+            }
+        }
+        (Some(_), dynamic_type) => {
+            typing(dynamic_type.unwrap_or(StorageType::Bytes(32)), 0.4, true)
+        }
+        (None, Some(dynamic_type)) => typing(dynamic_type, 0.6, false),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_infer_from_values() {
+        let known_addresses = HashSet::from([H160::from_low_u64_be(0xbeef)]);
+        let values = |values: &[u64]| values.iter().map(|v| U256::from(*v)).collect::<Vec<_>>();
+
+        assert_eq!(
+            infer_from_values(&values(&[0, 1, 0]), &known_addresses),
+            Some(StorageType::Bool)
+        );
+        assert_eq!(
+            infer_from_values(&values(&[0xbeef, 0]), &known_addresses),
+            Some(StorageType::Address)
+        );
+        assert_eq!(
+            infer_from_values(&values(&[2, 5, 9]), &known_addresses),
+            Some(StorageType::Uint(256))
+        );
+        assert_eq!(infer_from_values(&values(&[9, 5]), &known_addresses), None);
+    }
+
+    #[test]
+    fn test_admits_string() {
+        // "abc", stored with twice its length in the lowest byte
+        let mut word = [0u8; 32];
+        word[..3].copy_from_slice(b"abc");
+        word[31] = 6;
+        assert!(StorageType::String.admits(U256::from_big_endian(&word)));
+
+        // a long string stores only its length
+        assert!(StorageType::String.admits(U256::from(101)));
+        assert!(!StorageType::String.admits(U256::MAX - 1));
+    }
+}
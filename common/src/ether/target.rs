@@ -0,0 +1,72 @@
+use std::{fmt, str::FromStr};
+
+use crate::{constants::ADDRESS_REGEX, ether::rpc::chain_id};
+
+/// The chain ids of zkSync Era mainnet, and its Sepolia and Goerli testnets, whose contracts are
+/// compiled to EraVM bytecode rather than EVM bytecode.
+pub const ERAVM_CHAIN_IDS: [u64; 3] = [324, 300, 280];
+
+/// The virtual machine a target's bytecode is compiled for, which determines how it's decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetVm {
+    /// The Ethereum Virtual Machine, which most chains execute.
+    Evm,
+    /// zkSync Era's register-based virtual machine, see [`crate::ether::eravm`].
+    EraVm,
+}
+
+impl TargetVm {
+    /// The virtual machine which contracts on the given chain are compiled for.
+    ///
+    /// ```
+    /// use heimdall_common::ether::target::TargetVm;
+    ///
+    /// assert_eq!(TargetVm::from_chain_id(1), TargetVm::Evm);
+    /// assert_eq!(TargetVm::from_chain_id(324), TargetVm::EraVm);
+    /// ```
+    pub fn from_chain_id(chain_id: u64) -> Self {
+        match ERAVM_CHAIN_IDS.contains(&chain_id) {
+            true => TargetVm::EraVm,
+            false => TargetVm::Evm,
+        }
+    }
+}
+
+impl FromStr for TargetVm {
+    type Err = String;
+
+    fn from_str(vm: &str) -> Result<Self, Self::Err> {
+        match vm.to_lowercase().as_str() {
+            "evm" => Ok(TargetVm::Evm),
+            "eravm" | "zksync" => Ok(TargetVm::EraVm),
+            _ => Err(format!("unknown virtual machine '{vm}', expected 'evm' or 'eravm'")),
+        }
+    }
+}
+
+impl fmt::Display for TargetVm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetVm::Evm => write!(f, "evm"),
+            TargetVm::EraVm => write!(f, "eravm"),
+        }
+    }
+}
+
+/// Select the virtual machine to decode a target for. An explicit `--vm` takes precedence,
+/// otherwise contract addresses are decoded for the virtual machine of the RPC provider's chain,
+/// and all other targets default to the EVM.
+pub async fn select_target_vm(vm: &str, target: &str, rpc_url: &str) -> Result<TargetVm, String> {
+    if !vm.is_empty() {
+        return vm.parse()
+    }
+
+    if ADDRESS_REGEX.is_match(target).unwrap_or(false) && !rpc_url.is_empty() {
+        return Ok(match chain_id(rpc_url).await {
+            Ok(chain_id) => TargetVm::from_chain_id(chain_id),
+            Err(_) => TargetVm::Evm,
+        })
+    }
+
+    Ok(TargetVm::Evm)
+}
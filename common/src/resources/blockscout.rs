@@ -0,0 +1,28 @@
+use async_trait::async_trait;
+
+use super::explorer::{fetch_source_code, ExplorerClient, VerifiedSource};
+
+/// A Blockscout explorer, which serves an Etherscan-compatible API without requiring a key.
+#[derive(Debug, Clone)]
+pub struct BlockscoutClient {
+    api_url: String,
+}
+
+impl BlockscoutClient {
+    /// Create a client for the Blockscout instance at the given API URL, e.g.
+    /// `https://gnosis.blockscout.com/api`.
+    pub fn new(api_url: &str) -> Self {
+        BlockscoutClient { api_url: api_url.to_string() }
+    }
+}
+
+#[async_trait]
+impl ExplorerClient for BlockscoutClient {
+    fn name(&self) -> &str {
+        "blockscout"
+    }
+
+    async fn get_source_code(&self, address: &str) -> Option<VerifiedSource> {
+        fetch_source_code(&self.api_url, address, "").await
+    }
+}
@@ -0,0 +1,203 @@
+use indicatif::ProgressBar;
+use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::time::sleep as async_sleep;
+
+use crate::utils::{http::client_builder, io::logging::Logger};
+
+static DUNE_API_URL: &str = "https://api.dune.com/api/v1";
+
+/// executes a Dune SQL query, waits for it to complete, and returns the resulting rows
+async fn _call_dune(query: &str, api_key: &str) -> Option<Vec<Value>> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let client = client_builder().timeout(Duration::from_secs(60)).build().unwrap();
+
+    // start executing the query
+    let execution = match client
+        .post(format!("{DUNE_API_URL}/sql/execute"))
+        .header("X-Dune-API-Key", api_key)
+        .header("Content-Type", "application/json")
+        .body(serde_json::json!({ "sql": query, "performance": "medium" }).to_string())
+        .send()
+        .await
+    {
+        Ok(res) => serde_json::from_str::<Value>(&res.text().await.ok()?).ok()?,
+        Err(e) => {
+            logger.error("failed to call Dune .");
+            logger.error(&format!("error: {e}"));
+            std::process::exit(1)
+        }
+    };
+    let execution_id = match execution.get("execution_id").and_then(|id| id.as_str()) {
+        Some(execution_id) => execution_id.to_string(),
+        None => {
+            logger.error("Dune request unsucessful.");
+            logger.debug(&format!("response body: {execution:?}"));
+            return None
+        }
+    };
+
+    // poll the execution until it completes
+    loop {
+        let status = client
+            .get(format!("{DUNE_API_URL}/execution/{execution_id}/status"))
+            .header("X-Dune-API-Key", api_key)
+            .send()
+            .await
+            .ok()?;
+        let status = serde_json::from_str::<Value>(&status.text().await.ok()?).ok()?;
+
+        match status.get("state").and_then(|state| state.as_str()) {
+            Some("QUERY_STATE_COMPLETED") => break,
+            Some("QUERY_STATE_PENDING") | Some("QUERY_STATE_EXECUTING") => {
+                async_sleep(Duration::from_secs(2)).await
+            }
+            state => {
+                logger.error(&format!("Dune query failed with state {state:?} ."));
+                logger.debug(&format!("response body: {status:?}"));
+                return None
+            }
+        }
+    }
+
+    let results = client
+        .get(format!("{DUNE_API_URL}/execution/{execution_id}/results"))
+        .header("X-Dune-API-Key", api_key)
+        .send()
+        .await
+        .ok()?;
+    let results = serde_json::from_str::<Value>(&results.text().await.ok()?).ok()?;
+
+    parse_rows(&results)
+}
+
+/// Parse the rows of a Dune execution result.
+///
+/// ```
+/// use heimdall_common::resources::dune::parse_rows;
+///
+/// let results = serde_json::json!({ "result": { "rows": [{ "block_number": 1 }] } });
+/// assert_eq!(parse_rows(&results).unwrap().len(), 1);
+/// ```
+pub fn parse_rows(results: &Value) -> Option<Vec<Value>> {
+    results.get("result")?.get("rows")?.as_array().cloned()
+}
+
+/// Parse a `(block_number, transaction_hash)` pair from a row of a Dune execution result.
+fn parse_transaction(row: &Value) -> Option<(u128, String)> {
+    let block_number = row.get("block_number")?.as_u64()? as u128;
+    let transaction_hash = row.get("transaction_hash")?.as_str()?.to_string();
+    Some((block_number, transaction_hash))
+}
+
+/// Get all interactions with the given address using the Dune API. Includes transactions to, as
+/// well as internal transactions to the address.
+///
+/// ```
+/// use heimdall_common::resources::dune::get_transaction_list;
+///
+/// let chain = "ethereum";
+/// let address = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+/// let api_key = "YOUR_API_KEY";
+/// let bounds = (0, 1); // block number bounds
+///
+/// // let transactions = get_transaction_list(chain, address, api_key, bounds).await;
+/// ```
+pub async fn get_transaction_list(
+    chain: &str,
+    address: &str,
+    api_key: &str,
+    bounds: (&u128, &u128),
+) -> Vec<(u128, String)> {
+    // get a new logger
+    let logger = Logger::default();
+
+    // get a new progress bar
+    let transaction_list_progress = ProgressBar::new_spinner();
+    transaction_list_progress.enable_steady_tick(Duration::from_millis(100));
+    transaction_list_progress.set_style(logger.info_spinner());
+    transaction_list_progress.set_message(format!("fetching transactions from '{address}' ."));
+    let start_time = Instant::now();
+
+    // build the SQL query
+    let query = format!(
+        "SELECT block_number, CAST(hash AS VARCHAR) AS transaction_hash FROM {chain}.transactions WHERE \"to\" = {address} AND block_number BETWEEN {} AND {} UNION SELECT block_number, CAST(tx_hash AS VARCHAR) AS transaction_hash FROM {chain}.traces WHERE \"to\" = {address} AND block_number BETWEEN {} AND {}",
+        bounds.0, bounds.1, bounds.0, bounds.1
+    );
+
+    let rows = match _call_dune(&query, api_key).await {
+        Some(rows) => rows,
+        None => {
+            logger.error("failed to get transaction list from Dune");
+            std::process::exit(1)
+        }
+    };
+
+    transaction_list_progress.finish_and_clear();
+    logger.debug(&format!("fetching transactions took {:?}", start_time.elapsed()));
+
+    // parse the results
+    let mut transactions = Vec::new();
+    for row in rows {
+        match parse_transaction(&row) {
+            Some(transaction) => transactions.push(transaction),
+            None => {
+                logger.error("failed to parse transaction from Dune response");
+                std::process::exit(1)
+            }
+        }
+    }
+
+    // sort the transactions by block number
+    transactions.sort();
+    transactions.dedup();
+
+    transactions
+}
+
+/// Get the contract creation block and transaction hash for the given address using the Dune API.
+///
+/// ```
+/// use heimdall_common::resources::dune::get_contract_creation;
+///
+/// let chain = "ethereum";
+/// let address = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+/// let api_key = "YOUR_API_KEY";
+///
+/// // let contract_creation = get_contract_creation(chain, address, api_key).await;
+/// ```
+pub async fn get_contract_creation(
+    chain: &str,
+    address: &str,
+    api_key: &str,
+) -> Option<(u128, String)> {
+    // get a new logger
+    let logger = Logger::default();
+
+    // get a new progress bar
+    let transaction_list_progress = ProgressBar::new_spinner();
+    transaction_list_progress.enable_steady_tick(Duration::from_millis(100));
+    transaction_list_progress.set_style(logger.info_spinner());
+    transaction_list_progress.set_message(format!("fetching '{address}''s creation tx ."));
+    let start_time = Instant::now();
+
+    // build the SQL query
+    let query = format!(
+        "SELECT block_number, CAST(tx_hash AS VARCHAR) AS transaction_hash FROM {chain}.creation_traces WHERE address = {address} LIMIT 1"
+    );
+
+    let rows = match _call_dune(&query, api_key).await {
+        Some(rows) => rows,
+        None => {
+            logger.error("failed to get creation tx from Dune");
+            std::process::exit(1)
+        }
+    };
+
+    transaction_list_progress.finish_and_clear();
+    logger.debug(&format!("fetching contract creation took {:?}", start_time.elapsed()));
+
+    rows.first().and_then(parse_transaction)
+}
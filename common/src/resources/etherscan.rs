@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+
+use crate::utils::http::get_json_from_url;
+
+use super::explorer::{fetch_source_code, ExplorerClient, VerifiedSource};
+
+/// Check whether the given Etherscan API key is valid, by querying the current ether price.
+///
+/// ```
+/// use heimdall_common::resources::etherscan::validate_api_key;
+///
+/// let api_key = "YOUR_API_KEY";
+/// // let valid = validate_api_key(api_key).await;
+/// ```
+pub async fn validate_api_key(api_key: &str) -> bool {
+    let url =
+        format!("https://api.etherscan.io/api?module=stats&action=ethprice&apikey={}", api_key);
+
+    // etherscan responds with a status of "0" and an error message for invalid keys
+    match get_json_from_url(&url, 10).await {
+        Ok(Some(response)) => response["status"].as_str() == Some("1"),
+        _ => false,
+    }
+}
+
+/// An Etherscan-family explorer, such as etherscan, polygonscan, arbiscan, or basescan.
+#[derive(Debug, Clone)]
+pub struct EtherscanClient {
+    name: String,
+    api_url: String,
+    api_key: String,
+}
+
+impl EtherscanClient {
+    /// Create a client for the explorer with the given name and API URL, e.g.
+    /// `https://api.polygonscan.com/api`.
+    pub fn new(name: &str, api_url: &str, api_key: &str) -> Self {
+        EtherscanClient {
+            name: name.to_string(),
+            api_url: api_url.to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ExplorerClient for EtherscanClient {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn get_source_code(&self, address: &str) -> Option<VerifiedSource> {
+        fetch_source_code(&self.api_url, address, &self.api_key).await
+    }
+}
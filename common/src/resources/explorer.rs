@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::utils::{http::get_json_from_url, io::logging::Logger};
+
+use super::{blockscout::BlockscoutClient, etherscan::EtherscanClient};
+
+/// A contract's verified source code and ABI, as published on a block explorer.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerifiedSource {
+    pub contract_name: String,
+    pub compiler_version: String,
+    pub source_code: String,
+    pub abi: Value,
+}
+
+/// A block explorer which serves the verified source code and ABIs of contracts on a chain.
+#[async_trait]
+pub trait ExplorerClient: Send + Sync {
+    /// The name of the explorer, e.g. `etherscan`.
+    fn name(&self) -> &str;
+
+    /// Fetch the verified source code and ABI of the given contract, if it has been verified.
+    async fn get_source_code(&self, address: &str) -> Option<VerifiedSource>;
+
+    /// Fetch the verified ABI of the given contract, if it has been verified.
+    async fn get_abi(&self, address: &str) -> Option<Value> {
+        self.get_source_code(address).await.map(|source| source.abi)
+    }
+}
+
+/// Parse the response of an Etherscan-compatible `getsourcecode` request. Unverified contracts
+/// are returned with an empty ABI and source, which are treated as missing.
+///
+/// ```
+/// use heimdall_common::resources::explorer::parse_source_code_response;
+///
+/// let response = serde_json::json!({
+///     "status": "1",
+///     "result": [{
+///         "SourceCode": "contract Foo {}",
+///         "ABI": "[]",
+///         "ContractName": "Foo",
+///         "CompilerVersion": "v0.8.19+commit.7dd6d404"
+///     }]
+/// });
+///
+/// let source = parse_source_code_response(&response).unwrap();
+/// assert_eq!(source.contract_name, "Foo");
+/// assert_eq!(source.abi, serde_json::json!([]));
+/// ```
+pub fn parse_source_code_response(response: &Value) -> Option<VerifiedSource> {
+    let result = response.get("result")?.as_array()?.first()?;
+    let field = |key: &str| result.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string();
+
+    let source_code = field("SourceCode");
+    let abi = serde_json::from_str(&field("ABI")).ok()?;
+    if source_code.is_empty() {
+        return None
+    }
+
+    Some(VerifiedSource {
+        contract_name: field("ContractName"),
+        compiler_version: field("CompilerVersion"),
+        source_code,
+        abi,
+    })
+}
+
+/// Make an Etherscan-compatible `getsourcecode` request to the given API, which is shared by the
+/// Etherscan family of explorers and Blockscout.
+pub(super) async fn fetch_source_code(
+    api_url: &str,
+    address: &str,
+    api_key: &str,
+) -> Option<VerifiedSource> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let mut url = format!("{api_url}?module=contract&action=getsourcecode&address={address}");
+    if !api_key.is_empty() {
+        url.push_str(&format!("&apikey={api_key}"));
+    }
+
+    let response = match get_json_from_url(&url, 10).await {
+        Ok(Some(response)) => response,
+        _ => {
+            logger.debug(&format!("failed to fetch verified source for '{address}' ."));
+            return None
+        }
+    };
+
+    parse_source_code_response(&response)
+}
+
+/// Get the block explorer for the given chain, if heimdall knows of one. Etherscan-family
+/// explorers are authenticated with the given API key, while Blockscout explorers don't need one.
+///
+/// ```
+/// use heimdall_common::resources::explorer::explorer_for_chain;
+///
+/// assert_eq!(explorer_for_chain(137, "").unwrap().name(), "polygonscan");
+/// assert_eq!(explorer_for_chain(100, "").unwrap().name(), "blockscout");
+/// assert!(explorer_for_chain(0, "").is_none());
+/// ```
+pub fn explorer_for_chain(chain_id: u64, api_key: &str) -> Option<Box<dyn ExplorerClient>> {
+    let etherscan = |name: &str, api_url: &str| -> Option<Box<dyn ExplorerClient>> {
+        Some(Box::new(EtherscanClient::new(name, api_url, api_key)))
+    };
+    let blockscout = |api_url: &str| -> Option<Box<dyn ExplorerClient>> {
+        Some(Box::new(BlockscoutClient::new(api_url)))
+    };
+
+    match chain_id {
+        1 => etherscan("etherscan", "https://api.etherscan.io/api"),
+        11155111 => etherscan("etherscan", "https://api-sepolia.etherscan.io/api"),
+        10 => etherscan("etherscan", "https://api-optimistic.etherscan.io/api"),
+        56 => etherscan("bscscan", "https://api.bscscan.com/api"),
+        137 => etherscan("polygonscan", "https://api.polygonscan.com/api"),
+        42161 => etherscan("arbiscan", "https://api.arbiscan.io/api"),
+        8453 => etherscan("basescan", "https://api.basescan.org/api"),
+        100 => blockscout("https://gnosis.blockscout.com/api"),
+        324 => blockscout("https://zksync.blockscout.com/api"),
+        7777777 => blockscout("https://explorer.zora.energy/api"),
+        _ => None,
+    }
+}
@@ -1,2 +1,6 @@
+pub mod blockscout;
+pub mod dune;
+pub mod etherscan;
+pub mod explorer;
 pub mod openai;
 pub mod transpose;
@@ -1,5 +1,6 @@
-use crate::utils::io::logging::Logger;
+use crate::utils::{http::client_builder, io::logging::Logger};
 use async_openai::{types::CreateCompletionRequestArgs, Client};
+use std::time::Duration;
 
 /// Complete the given prompt using the OpenAI API.
 ///
@@ -44,3 +45,23 @@ pub async fn complete(prompt: &str, api_key: &str) -> Option<String> {
         }
     }
 }
+
+/// Check whether the given OpenAI API key is valid, by listing the models available to it.
+///
+/// ```
+/// use heimdall_common::resources::openai::validate_api_key;
+///
+/// let api_key = "your-api-key";
+/// // validate_api_key(api_key).await;
+/// ```
+pub async fn validate_api_key(api_key: &str) -> bool {
+    let client = match client_builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(_) => return false,
+    };
+
+    match client.get("https://api.openai.com/v1/models").bearer_auth(api_key).send().await {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
@@ -3,7 +3,7 @@ use reqwest::header::HeaderMap;
 use serde_json::Value;
 use std::time::{Duration, Instant};
 
-use crate::utils::io::logging::Logger;
+use crate::utils::{http::client_builder, io::logging::Logger};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -34,7 +34,7 @@ async fn _call_transpose(query: &str, api_key: &str) -> Option<TransposeResponse
     let query = query.to_owned();
 
     // make the request
-    let client = reqwest::Client::builder()
+    let client = client_builder()
         .redirect(reqwest::redirect::Policy::none())
         .timeout(Duration::from_secs(999999999))
         .build()
@@ -75,6 +75,33 @@ async fn _call_transpose(query: &str, api_key: &str) -> Option<TransposeResponse
     }
 }
 
+/// Check whether the given Transpose API key is valid, by executing a trivial SQL query.
+///
+/// ```
+/// use heimdall_common::resources::transpose::validate_api_key;
+///
+/// let api_key = "YOUR_API_KEY";
+/// // let valid = validate_api_key(api_key).await;
+/// ```
+pub async fn validate_api_key(api_key: &str) -> bool {
+    let client = match client_builder().timeout(Duration::from_secs(10)).build() {
+        Ok(client) => client,
+        Err(_) => return false,
+    };
+
+    match client
+        .post("https://api.transpose.io/sql")
+        .header("Content-Type", "application/json")
+        .header("X-API-KEY", api_key)
+        .body("{\"sql\":\"SELECT 1\"}")
+        .send()
+        .await
+    {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
 /// Get all interactions with the given address. Includes transactions to, from, as well as internal
 /// transactions to and from the address.
 ///
@@ -0,0 +1,125 @@
+use lazy_static::lazy_static;
+use serde::Serialize;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    mpsc::{channel, Receiver},
+    Arc, Mutex,
+};
+
+/// A typed progress event, emitted by heimdall's modules as they run. Embedders such as bots and
+/// web services can [`subscribe`] to these, rather than parsing heimdall's logging.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A phase of a module started, e.g. `decompile: symbolic execution`.
+    PhaseStarted { phase: String },
+    /// A function was analyzed, which was the `analyzed`th of `total` detected selectors.
+    SelectorAnalyzed { selector: String, analyzed: usize, total: usize },
+    /// A function, error, or event selector was resolved to the given signature.
+    SignatureResolved { selector: String, signature: String },
+}
+
+type Callback = Arc<dyn Fn(&ProgressEvent) + Send + Sync>;
+
+lazy_static! {
+    static ref SUBSCRIBERS: Mutex<Vec<(usize, Callback)>> = Mutex::new(Vec::new());
+}
+
+/// The id given to the next subscriber.
+static NEXT_SUBSCRIBER: AtomicUsize = AtomicUsize::new(0);
+
+/// Call `callback` with every [`ProgressEvent`] emitted from now on, by any module, until
+/// [`unsubscribe`] is called with the returned id. Callbacks run on the thread which emitted the
+/// event, so they should return quickly.
+///
+/// ```
+/// use heimdall_common::utils::{
+///     crash::set_phase,
+///     events::{subscribe, unsubscribe, ProgressEvent},
+/// };
+/// use std::sync::{Arc, Mutex};
+///
+/// let phases = Arc::new(Mutex::new(Vec::new()));
+/// let subscriber = {
+///     let phases = phases.clone();
+///     subscribe(move |event| {
+///         if let ProgressEvent::PhaseStarted { phase } = event {
+///             phases.lock().unwrap().push(phase.clone());
+///         }
+///     })
+/// };
+///
+/// set_phase("decompile: disassembling");
+/// unsubscribe(subscriber);
+/// set_phase("decompile: symbolic execution");
+///
+/// assert_eq!(*phases.lock().unwrap(), vec![String::from("decompile: disassembling")]);
+/// ```
+pub fn subscribe(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> usize {
+    let id = NEXT_SUBSCRIBER.fetch_add(1, Ordering::SeqCst);
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.push((id, Arc::new(callback)));
+    }
+    id
+}
+
+/// Receive every [`ProgressEvent`] emitted from now on through a channel, until [`unsubscribe`]
+/// is called with the returned id. This suits embedders which run heimdall on another task.
+pub fn subscribe_channel() -> (usize, Receiver<ProgressEvent>) {
+    let (sender, receiver) = channel();
+    let sender = Mutex::new(sender);
+    let id = subscribe(move |event| {
+        if let Ok(sender) = sender.lock() {
+            let _ = sender.send(event.clone());
+        }
+    });
+    (id, receiver)
+}
+
+/// Stop calling the subscriber with the given id.
+pub fn unsubscribe(id: usize) {
+    if let Ok(mut subscribers) = SUBSCRIBERS.lock() {
+        subscribers.retain(|(subscriber, _)| *subscriber != id);
+    }
+}
+
+/// Emit an event to every subscriber.
+pub fn emit(event: ProgressEvent) {
+    // subscribers are called without holding the lock, so they may unsubscribe themselves
+    let subscribers = match SUBSCRIBERS.lock() {
+        Ok(subscribers) => subscribers.iter().map(|(_, callback)| callback.clone()).collect(),
+        Err(_) => Vec::new(),
+    };
+    for callback in subscribers {
+        callback(&event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subscribe_channel() {
+        let (id, receiver) = subscribe_channel();
+        let event = ProgressEvent::SignatureResolved {
+            selector: String::from("a9059cbb"),
+            signature: String::from("transfer(address,uint256)"),
+        };
+        emit(event.clone());
+        unsubscribe(id);
+        emit(event.clone());
+
+        // other tests may emit events concurrently
+        let received = receiver.try_iter().filter(|received| *received == event).count();
+        assert_eq!(received, 1);
+        assert_eq!(
+            serde_json::to_value(&event).unwrap(),
+            serde_json::json!({
+                "event": "signature_resolved",
+                "selector": "a9059cbb",
+                "signature": "transfer(address,uint256)",
+            })
+        );
+    }
+}
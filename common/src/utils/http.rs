@@ -1,12 +1,115 @@
 use crate::utils::io::logging::Logger;
-use async_recursion::async_recursion;
-use reqwest::Client;
+use lazy_static::lazy_static;
+use reqwest::{header::RETRY_AFTER, Client, ClientBuilder, NoProxy, Proxy, Response, StatusCode};
 use serde_json::Value;
-use std::time::Duration;
+use std::{sync::RwLock, time::Duration};
 use tokio::time::sleep as async_sleep;
 
 static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
+/// How failed HTTP requests are retried. Connection errors, rate limits (`429`), and server
+/// errors are retried with exponential backoff and jitter, honoring `Retry-After` headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a failed request is retried.
+    pub max_retries: u8,
+    /// The backoff before the first retry, in milliseconds, which doubles with each retry.
+    pub initial_backoff_ms: u64,
+    /// The maximum delay between retries, in milliseconds.
+    pub max_backoff_ms: u64,
+    /// The timeout of every request, in seconds, overriding each request's own timeout.
+    pub timeout: Option<u64>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self { max_retries: 5, initial_backoff_ms: 500, max_backoff_ms: 30_000, timeout: None }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before the given retry, where `attempt` starts at 0 and `jitter` is a random
+    /// number in `[0, 1)`. The delay is at least half of the exponential backoff, with the rest
+    /// randomized, so concurrent requests don't retry in lockstep.
+    pub fn backoff(&self, attempt: u8, jitter: f64) -> Duration {
+        let backoff = self
+            .initial_backoff_ms
+            .saturating_mul(2u64.saturating_pow(attempt as u32))
+            .min(self.max_backoff_ms);
+
+        Duration::from_millis(backoff / 2 + (backoff as f64 / 2.0 * jitter) as u64)
+    }
+}
+
+lazy_static! {
+    static ref RETRY_POLICY: RwLock<RetryPolicy> = RwLock::new(RetryPolicy::default());
+    static ref PROXY: RwLock<Option<String>> = RwLock::new(None);
+}
+
+/// Route every HTTP request, including RPC requests, through the given proxy, such as
+/// `http://proxy.internal:3128`. Hosts listed in the `NO_PROXY` environment variable are still
+/// reached directly. Without a configured proxy, the `HTTP_PROXY`, `HTTPS_PROXY`, and `NO_PROXY`
+/// environment variables are respected.
+///
+/// ```
+/// use heimdall_common::utils::http::{proxy, set_proxy};
+///
+/// set_proxy(Some(String::from("http://localhost:3128")));
+/// assert_eq!(proxy(), Some(String::from("http://localhost:3128")));
+///
+/// // an empty proxy falls back to the environment
+/// set_proxy(Some(String::new()));
+/// assert_eq!(proxy(), None);
+/// ```
+pub fn set_proxy(proxy: Option<String>) {
+    if let Ok(mut current) = PROXY.write() {
+        *current = proxy.filter(|proxy| !proxy.is_empty());
+    }
+}
+
+/// Get the proxy configured with [`set_proxy`], if any.
+pub fn proxy() -> Option<String> {
+    PROXY.read().ok().and_then(|proxy| proxy.clone())
+}
+
+/// A [`ClientBuilder`] which identifies heimdall and routes requests through the configured
+/// proxy. Every HTTP client should be built from this.
+pub fn client_builder() -> ClientBuilder {
+    let builder = Client::builder().user_agent(APP_USER_AGENT);
+
+    // reqwest reads HTTP_PROXY, HTTPS_PROXY, and NO_PROXY from the environment by default
+    let proxy_url = match proxy() {
+        Some(proxy_url) => proxy_url,
+        None => return builder,
+    };
+    match Proxy::all(&proxy_url) {
+        Ok(proxy) => builder.proxy(proxy.no_proxy(NoProxy::from_env())),
+        Err(e) => {
+            Logger::default().warn(&format!("ignoring invalid proxy '{proxy_url}': {e}"));
+            builder
+        }
+    }
+}
+
+/// Set the [`RetryPolicy`] used by every HTTP request.
+///
+/// ```
+/// use heimdall_common::utils::http::{retry_policy, set_retry_policy, RetryPolicy};
+///
+/// set_retry_policy(RetryPolicy { max_retries: 2, ..Default::default() });
+/// assert_eq!(retry_policy().max_retries, 2);
+/// ```
+pub fn set_retry_policy(policy: RetryPolicy) {
+    if let Ok(mut current) = RETRY_POLICY.write() {
+        *current = policy;
+    }
+}
+
+/// Get the [`RetryPolicy`] used by every HTTP request.
+pub fn retry_policy() -> RetryPolicy {
+    RETRY_POLICY.read().map(|policy| policy.clone()).unwrap_or_default()
+}
+
 /// Make a GET request to the target URL and return the response body as JSON
 ///
 /// ```no_run
@@ -17,52 +120,164 @@ static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_P
 /// // get_json_from_url(url, timeout).await;
 /// ```
 pub async fn get_json_from_url(url: &str, timeout: u64) -> Result<Option<Value>, reqwest::Error> {
-    _get_json_from_url(url, 0, 5, timeout).await
+    match _get_text_from_url(url, timeout).await? {
+        Some(body) => match serde_json::from_str(&body) {
+            Ok(json) => Ok(Some(json)),
+            Err(_) => Ok(None),
+        },
+        None => Ok(None),
+    }
 }
 
-#[async_recursion]
-/// Internal function for making a GET request to the target URL and returning the response body as
-/// JSON
-async fn _get_json_from_url(
+/// Make a GET request to the target URL and return the response body as a string. Non-successful
+/// responses return `None`.
+///
+/// ```no_run
+/// use heimdall_common::utils::http::get_text_from_url;
+///
+/// let url = "https://example.com";
+/// let timeout = 5;
+/// // get_text_from_url(url, timeout).await;
+/// ```
+pub async fn get_text_from_url(url: &str, timeout: u64) -> Result<Option<String>, reqwest::Error> {
+    _get_text_from_url(url, timeout).await
+}
+
+/// Make a GET request to the target URL and return the raw response body, i.e. for downloading
+/// binary files. Non-successful responses return `None`.
+///
+/// ```no_run
+/// use heimdall_common::utils::http::get_bytes_from_url;
+///
+/// let url = "https://example.com";
+/// let timeout = 5;
+/// // get_bytes_from_url(url, timeout).await;
+/// ```
+pub async fn get_bytes_from_url(
     url: &str,
-    retry_count: u8,
-    retries_remaining: u8,
     timeout: u64,
-) -> Result<Option<Value>, reqwest::Error> {
+) -> Result<Option<Vec<u8>>, reqwest::Error> {
     // get a new logger
     let logger = Logger::default();
 
-    logger.debug_max(&format!("GET {}", &url));
+    let client = client_builder()
+        .timeout(Duration::from_secs(retry_policy().timeout.unwrap_or(timeout)))
+        .build()?;
+
+    let res = match send_with_retries(&client, url).await {
+        Some(res) => res,
+        None => return Ok(None),
+    };
+    if !res.status().is_success() {
+        logger.debug_max(&format!("GET {}: {}", &url, res.status()));
+        return Ok(None)
+    }
+
+    Ok(Some(res.bytes().await?.to_vec()))
+}
+
+/// Internal function for making a GET request to the target URL and returning the response body
+async fn _get_text_from_url(url: &str, timeout: u64) -> Result<Option<String>, reqwest::Error> {
+    // get a new logger
+    let logger = Logger::default();
 
-    let client = Client::builder()
+    let client = client_builder()
         .danger_accept_invalid_certs(true)
-        .user_agent(APP_USER_AGENT)
-        .timeout(Duration::from_secs(timeout))
+        .timeout(Duration::from_secs(retry_policy().timeout.unwrap_or(timeout)))
         .build()?;
 
-    let res = match client.get(url).send().await {
-        Ok(res) => {
-            logger.debug_max(&format!("GET {}: {:?}", &url, &res));
-            res
-        }
-        Err(e) => {
-            logger.debug_max(&format!("GET {}: {:?}", &url, &e));
-            if retries_remaining == 0 {
-                return Ok(None)
+    let res = match send_with_retries(&client, url).await {
+        Some(res) => res,
+        None => return Ok(None),
+    };
+    if !res.status().is_success() {
+        logger.debug_max(&format!("GET {}: {}", &url, res.status()));
+        return Ok(None)
+    }
+
+    Ok(Some(res.text().await?))
+}
+
+/// Send a GET request to the target URL, retrying connection errors, rate limits, and server
+/// errors according to the global [`RetryPolicy`]. Returns the last response received, or `None`
+/// if the request never succeeded in reaching the server.
+async fn send_with_retries(client: &Client, url: &str) -> Option<Response> {
+    // get a new logger
+    let logger = Logger::default();
+    let policy = retry_policy();
+
+    let mut attempt = 0;
+    loop {
+        logger.debug_max(&format!("GET {}", &url));
+
+        let delay = match client.get(url).send().await {
+            Ok(res) => {
+                logger.debug_max(&format!("GET {}: {:?}", &url, &res));
+                if !is_retryable(res.status()) || attempt >= policy.max_retries {
+                    return Some(res)
+                }
+
+                // rate limited responses may tell us how long to wait
+                retry_after(&res)
+                    .map(|delay| delay.min(Duration::from_millis(policy.max_backoff_ms)))
+                    .unwrap_or_else(|| policy.backoff(attempt, rand::random()))
+            }
+            Err(e) => {
+                logger.debug_max(&format!("GET {}: {:?}", &url, &e));
+                if attempt >= policy.max_retries {
+                    return None
+                }
+                policy.backoff(attempt, rand::random())
             }
+        };
 
-            // exponential backoff
-            let retry_count = retry_count + 1;
-            let retries_remaining = retries_remaining - 1;
-            let sleep_time = 2u64.pow(retry_count as u32) * 250;
-            async_sleep(Duration::from_millis(sleep_time)).await;
-            return _get_json_from_url(url, retry_count, retries_remaining, timeout).await
-        }
-    };
-    let body = res.text().await?;
+        logger.debug_max(&format!("GET {}: retrying in {}ms", &url, delay.as_millis()));
+        async_sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Whether a response with the given status is worth retrying, i.e. rate limits and server
+/// errors.
+fn is_retryable(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// The delay requested by a response's `Retry-After` header, if it is given in seconds.
+fn retry_after(res: &Response) -> Option<Duration> {
+    res.headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_exponentially_within_bounds() {
+        let policy = RetryPolicy::default();
+
+        // without jitter, the delay is half of the exponential backoff
+        assert_eq!(policy.backoff(0, 0.0), Duration::from_millis(250));
+        assert_eq!(policy.backoff(1, 0.0), Duration::from_millis(500));
+
+        // jitter spreads the delay up to the full backoff, which never exceeds the maximum
+        assert_eq!(policy.backoff(1, 0.5), Duration::from_millis(750));
+        assert_eq!(policy.backoff(20, 0.999), policy.backoff(40, 0.999));
+        assert!(policy.backoff(40, 0.999) < Duration::from_millis(policy.max_backoff_ms));
+    }
 
-    match serde_json::from_str(&body) {
-        Ok(json) => Ok(Some(json)),
-        Err(_) => Ok(None),
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable(StatusCode::BAD_GATEWAY));
+        assert!(!is_retryable(StatusCode::NOT_FOUND));
+        assert!(!is_retryable(StatusCode::OK));
     }
 }
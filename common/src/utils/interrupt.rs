@@ -0,0 +1,60 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::utils::io::logging::{is_prompting, Logger};
+
+/// Whether the user has interrupted heimdall, i.e. with Ctrl-C.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Request that in-flight analysis stops as soon as possible. Long-running modules check
+/// [`is_interrupted`] between units of work, and return whatever they completed so far.
+pub fn interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Whether analysis has been interrupted.
+///
+/// ```
+/// use heimdall_common::utils::interrupt::{interrupt, is_interrupted, reset_interrupt};
+///
+/// interrupt();
+/// assert!(is_interrupted());
+///
+/// reset_interrupt();
+/// assert!(!is_interrupted());
+/// ```
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Clear a previous interruption, so analysis can run again.
+pub fn reset_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// Listen for Ctrl-C in the background. The first Ctrl-C cancels in-flight analysis
+/// cooperatively, so partial results can still be written. A second Ctrl-C, or a Ctrl-C while a
+/// prompt is waiting for input, calls `cleanup`, which should restore the terminal, and exits
+/// immediately.
+pub fn install_interrupt_handler(cleanup: fn()) {
+    tokio::spawn(async move {
+        // get a new logger
+        let logger = Logger::default();
+
+        if tokio::signal::ctrl_c().await.is_err() {
+            return
+        }
+
+        // prompts block on user input, so they can't stop cooperatively
+        if is_prompting() {
+            cleanup();
+            std::process::exit(130);
+        }
+        interrupt();
+        logger.warn("interrupted, writing partial output. Press Ctrl-C again to exit immediately.");
+
+        if tokio::signal::ctrl_c().await.is_ok() {
+            cleanup();
+            std::process::exit(130);
+        }
+    });
+}
@@ -1,12 +1,18 @@
 use super::logging::Logger;
 
+use lazy_static::lazy_static;
 use std::{
     env,
     fs::File,
     io::{Read, Write},
     process::Command,
+    sync::RwLock,
 };
 
+lazy_static! {
+    static ref CSV_DELIMITER: RwLock<u8> = RwLock::new(b',');
+}
+
 /// Convert a long path to a short path.
 ///
 /// ```no_run
@@ -24,6 +30,39 @@ pub fn short_path(path: &str) -> String {
     path.replace(&current_dir, ".")
 }
 
+/// Resolve the root directory which output is written to. The `--output` argument takes
+/// precedence over the configured `output_dir`, and without either, output is written to
+/// `./output`. Relative paths are resolved against the current working directory, while absolute
+/// paths and paths within the home directory are used as-is.
+///
+/// ```
+/// use heimdall_common::utils::io::file::output_dir;
+///
+/// assert_eq!(output_dir("/tmp/heimdall/", "/var/heimdall").unwrap(), "/tmp/heimdall");
+/// assert_eq!(output_dir("", "/var/heimdall").unwrap(), "/var/heimdall");
+/// assert!(output_dir("", "").unwrap().ends_with("/output"));
+/// ```
+pub fn output_dir(output: &str, configured: &str) -> std::io::Result<String> {
+    let path = match (output.trim(), configured.trim()) {
+        ("", "") => "output",
+        ("", configured) => configured,
+        (output, _) => output,
+    };
+
+    // expand the home directory, since the path may not have been expanded by a shell
+    #[allow(deprecated)]
+    let path = match (path.strip_prefix("~/"), env::home_dir()) {
+        (Some(path), Some(home)) => home.join(path),
+        _ => std::path::PathBuf::from(path),
+    };
+    let path = match path.is_absolute() {
+        true => path,
+        false => env::current_dir()?.join(path),
+    };
+
+    Ok(path.to_string_lossy().trim_end_matches('/').to_string())
+}
+
 /// Write contents to a file on the disc
 ///
 /// ```no_run
@@ -71,6 +110,86 @@ pub fn write_lines_to_file(_path: &str, contents: Vec<String>) {
     write_file(_path, &contents.join("\n"));
 }
 
+/// Parse a CSV delimiter, which must be a single ASCII character. Tabs may be given as `tab` or
+/// `\t`, for TSV output, and an empty delimiter is a comma.
+///
+/// ```
+/// use heimdall_common::utils::io::file::parse_delimiter;
+///
+/// assert_eq!(parse_delimiter(""), Ok(b','));
+/// assert_eq!(parse_delimiter("tab"), Ok(b'\t'));
+/// assert_eq!(parse_delimiter("\\t"), Ok(b'\t'));
+/// assert_eq!(parse_delimiter(";"), Ok(b';'));
+/// assert!(parse_delimiter("::").is_err());
+/// ```
+pub fn parse_delimiter(delimiter: &str) -> Result<u8, String> {
+    match delimiter {
+        "" => Ok(b','),
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        delimiter if delimiter.len() == 1 && delimiter.is_ascii() && delimiter != "\"" => {
+            Ok(delimiter.as_bytes()[0])
+        }
+        delimiter => Err(format!(
+            "invalid delimiter '{delimiter}', expected a single character other than '\"'"
+        )),
+    }
+}
+
+/// Set the delimiter which separates the fields of every CSV file written, a comma by default.
+pub fn set_csv_delimiter(delimiter: u8) {
+    if let Ok(mut current) = CSV_DELIMITER.write() {
+        *current = delimiter;
+    }
+}
+
+/// The delimiter which separates the fields of every CSV file written.
+pub fn csv_delimiter() -> u8 {
+    CSV_DELIMITER.read().map(|delimiter| *delimiter).unwrap_or(b',')
+}
+
+/// Format the rows as CSV, separated by the given delimiter. Fields containing the delimiter,
+/// quotes, or newlines, such as revert strings, are quoted, so every row is read back intact.
+///
+/// ```
+/// use heimdall_common::utils::io::file::to_csv;
+///
+/// let rows = vec![
+///     vec![String::from("selector"), String::from("strings")],
+///     vec![String::from("0xa9059cbb"), String::from("Ownable: caller is not the owner,\n\"sender\"")],
+/// ];
+/// assert_eq!(
+///     to_csv(&rows, b','),
+///     "selector,strings\n0xa9059cbb,\"Ownable: caller is not the owner,\n\"\"sender\"\"\"\n"
+/// );
+/// assert_eq!(to_csv(&rows[..1], b'\t'), "selector\tstrings\n");
+/// ```
+pub fn to_csv(rows: &[Vec<String>], delimiter: u8) -> String {
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .terminator(csv::Terminator::Any(b'\n'))
+        .from_writer(Vec::new());
+    for row in rows {
+        // writing to memory can't fail
+        let _ = writer.write_record(row);
+    }
+
+    writer.into_inner().map(|bytes| String::from_utf8_lossy(&bytes).to_string()).unwrap_or_default()
+}
+
+/// Write the rows to a CSV file on the disc, separated by the configured delimiter.
+///
+/// ```no_run
+/// use heimdall_common::utils::io::file::write_csv_to_file;
+///
+/// let path = "/tmp/test.csv";
+/// let rows = vec![vec![String::from("Hello"), String::from("World!")]];
+/// write_csv_to_file(path, &rows);
+/// ```
+pub fn write_csv_to_file(_path: &str, rows: &[Vec<String>]) {
+    write_file(_path, &to_csv(rows, csv_delimiter()));
+}
+
 /// Read contents from a file on the disc
 ///
 /// ```no_run
@@ -0,0 +1,58 @@
+use std::{fmt, str::FromStr};
+
+/// The format a subcommand writes its output in. Each subcommand supports a subset of these,
+/// and has its own default, such as `text` for `decompile`'s Solidity source or `csv` for
+/// `snapshot`.
+///
+/// ```
+/// use heimdall_common::utils::io::format::OutputFormat;
+///
+/// assert_eq!("JSON".parse::<OutputFormat>(), Ok(OutputFormat::Json));
+/// assert_eq!(OutputFormat::Csv.to_string(), "csv");
+/// assert_eq!("storage-layout".parse::<OutputFormat>(), Ok(OutputFormat::StorageLayout));
+/// assert_eq!(OutputFormat::Manifest.to_string(), "manifest");
+/// assert!("svg".parse::<OutputFormat>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable output, such as assembly, Solidity source, or a graphviz `.dot` file.
+    Text,
+    /// Structured output, which follows the subcommand's published JSON schema.
+    Json,
+    /// Tabular output, with a header row.
+    Csv,
+    /// A storage layout, in the JSON schema of solc's `storageLayout` output.
+    StorageLayout,
+    /// A capability manifest, describing what each of a contract's functions can do and who can
+    /// call it, in its own published JSON schema.
+    Manifest,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(format: &str) -> Result<Self, Self::Err> {
+        match format.trim().to_lowercase().as_str() {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "csv" => Ok(OutputFormat::Csv),
+            "storage-layout" => Ok(OutputFormat::StorageLayout),
+            "manifest" => Ok(OutputFormat::Manifest),
+            format => Err(format!(
+                "unknown format '{format}', expected 'json', 'csv', 'text', 'storage-layout', or 'manifest'"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Csv => write!(f, "csv"),
+            OutputFormat::StorageLayout => write!(f, "storage-layout"),
+            OutputFormat::Manifest => write!(f, "manifest"),
+        }
+    }
+}
@@ -1,5 +1,13 @@
 use indicatif::ProgressStyle;
-use std::io::{stdin, stdout};
+use lazy_static::lazy_static;
+use std::{
+    fs::{File, OpenOptions},
+    io::{stdin, stdout, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
 
 use colored::*;
 
@@ -80,7 +88,7 @@ impl TraceFactory {
     /// display the trace to the console if the verbosity is high enough
     pub fn display(&self) {
         if self.level >= 3 {
-            println!("{}:", "trace".bright_blue().bold());
+            emit(&format!("{}:", "trace".bright_blue().bold()));
             for index in 0..self.traces.len() {
                 // safe to unwrap because we just iterated over the traces
                 let trace = self.traces.get(index).expect("Failed to build trace.");
@@ -104,12 +112,12 @@ impl TraceFactory {
         match trace.category {
             TraceCategory::Call => {
                 // print the trace title
-                println!(
+                emit(&format!(
                     "{} {} {}",
                     replace_last(prefix, "│ ", " ├─").bold().bright_white(),
                     format!("[{}]", trace.instruction).bold().bright_white(),
                     trace.message.get(0).expect("Failed to build trace.")
-                );
+                ));
 
                 // print the children
                 for child in &trace.children {
@@ -120,7 +128,7 @@ impl TraceFactory {
                 }
 
                 // print the return value
-                println!(
+                emit(&format!(
                     "{} ← {}",
                     format!("{prefix}   └─").bold().bright_white(),
                     match trace.message.get(1) {
@@ -130,14 +138,14 @@ impl TraceFactory {
                         ),
                         None => "()".dimmed().to_string(),
                     }
-                )
+                ))
             }
             TraceCategory::Log => {
-                println!(
+                emit(&format!(
                     "{} emit {}",
                     replace_last(prefix, "│ ", " ├─").bold().bright_white(),
                     trace.message.get(0).expect("Failed to build trace.")
-                );
+                ));
             }
             TraceCategory::LogUnknown => {
                 let log_size = trace.message.len();
@@ -145,7 +153,7 @@ impl TraceFactory {
                     for message_index in 0..trace.message.len() - 1 {
                         let message =
                             trace.message.get(message_index).expect("Failed to build trace.");
-                        println!(
+                        emit(&format!(
                             "{} {} {}: {}",
                             if message_index == 0 {
                                 replace_last(prefix, "│ ", " ├─").bold().bright_white()
@@ -155,27 +163,27 @@ impl TraceFactory {
                             if message_index == 0 { "emit" } else { "    " },
                             format!("topic {message_index}").purple(),
                             message
-                        );
+                        ));
                     }
-                    println!(
+                    emit(&format!(
                         "{}         {}: {}",
                         replace_last(prefix, "│ ", " │ ").bold().blue(),
                         "data".purple(),
                         trace.message.last().expect("Failed to build trace.")
-                    );
+                    ));
                 } else {
-                    println!(
+                    emit(&format!(
                         "{} emit {}: {}",
                         replace_last(prefix, "│ ", " ├─").bold().bright_white(),
                         "data".purple(),
                         trace.message.last().expect("Failed to build trace.")
-                    );
+                    ));
                 }
             }
             TraceCategory::Message => {
                 for message_index in 0..trace.message.len() {
                     let message = trace.message.get(message_index).expect("Failed to build trace.");
-                    println!(
+                    emit(&format!(
                         "{} {}",
                         if prefix.ends_with("└─") {
                             prefix.to_string().bold().bright_white()
@@ -185,7 +193,7 @@ impl TraceFactory {
                             replace_last(prefix, "│ ", " │ ").bold().bright_white()
                         },
                         message
-                    );
+                    ));
                 }
 
                 // print the children
@@ -204,15 +212,15 @@ impl TraceFactory {
                 }
             }
             TraceCategory::Empty => {
-                println!("{}", replace_last(prefix, "│ ", " │ ").bold().bright_white());
+                emit(&format!("{}", replace_last(prefix, "│ ", " │ ").bold().bright_white()));
             }
             TraceCategory::Create => {
-                println!(
+                emit(&format!(
                     "{} {} create → {}",
                     replace_last(prefix, "│ ", " ├─").bold().bright_white(),
                     format!("[{}]", trace.instruction).bold().bright_white(),
                     trace.message.get(0).expect("Failed to build trace.")
-                );
+                ));
 
                 // print the children
                 for child in &trace.children {
@@ -223,11 +231,11 @@ impl TraceFactory {
                 }
 
                 // print the return value
-                println!(
+                emit(&format!(
                     "{} ← {}",
                     format!("{prefix}   └─").bold().bright_white(),
                     trace.message.get(1).expect("Failed to build trace.").bold().green()
-                )
+                ))
             }
         }
     }
@@ -353,6 +361,115 @@ impl Trace {
     }
 }
 
+lazy_static! {
+    static ref LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+}
+
+/// Whether logs and traces are printed to stderr rather than stdout.
+static LOG_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Whether a prompt is waiting for user input.
+static PROMPTING: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if a prompt is waiting for user input. Prompts block until the user answers, so
+/// Ctrl-C should exit rather than wait for analysis to stop cooperatively.
+pub fn is_prompting() -> bool {
+    PROMPTING.load(Ordering::SeqCst)
+}
+
+/// Print all logs and traces to stderr rather than stdout, leaving stdout to machine-readable
+/// output, such as that of `decode --json`.
+pub fn log_to_stderr() {
+    LOG_TO_STDERR.store(true, Ordering::Relaxed);
+}
+
+/// Print a line to the console, on stderr if [`log_to_stderr`] was called.
+fn print_line(line: &str) {
+    match LOG_TO_STDERR.load(Ordering::Relaxed) {
+        true => eprintln!("{line}"),
+        false => println!("{line}"),
+    }
+}
+
+/// Tee all log output to the file at the given path, in addition to the terminal. Lines written
+/// to the file are timestamped and stripped of colors.
+pub fn set_log_file(path: &str) -> std::io::Result<()> {
+    let path = std::path::Path::new(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE.lock().expect("Failed to lock log file.") = Some(file);
+    Ok(())
+}
+
+/// Returns true if colored output has been disabled through the `NO_COLOR` environment variable.
+/// See <https://no-color.org>.
+pub fn no_color_env() -> bool {
+    no_color(std::env::var("NO_COLOR").ok().as_deref())
+}
+
+/// Returns true if the given value of the `NO_COLOR` environment variable disables colors, i.e.
+/// it's set and not empty.
+fn no_color(value: Option<&str>) -> bool {
+    value.is_some_and(|value| !value.is_empty())
+}
+
+/// Disable colors and text styles for all terminal output, including logs, traces, spinners, and
+/// the update banner.
+pub fn disable_colors() {
+    colored::control::set_override(false);
+}
+
+/// Remove all ANSI escape sequences from the given string.
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            // skip until the final byte of the control sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Append a line to the log file, if one is set.
+fn write_to_log_file(line: &str) {
+    if let Ok(mut log_file) = LOG_FILE.lock() {
+        if let Some(file) = log_file.as_mut() {
+            let _ = writeln!(file, "{}", strip_ansi(line));
+        }
+    }
+}
+
+/// Print a line of output to the console, and tee it to the log file with a timestamp.
+fn emit(line: &str) {
+    print_line(line);
+    write_to_log_file(&format!("{}  {}", pretty_timestamp(), line));
+}
+
+/// Returns true if heimdall is running unattended, i.e. the `CI` environment variable is set to
+/// `true`. In this mode, prompts will never wait for user input.
+pub fn is_non_interactive() -> bool {
+    is_ci(std::env::var("CI").ok().as_deref())
+}
+
+/// Returns true if the given value of the `CI` environment variable enables non-interactive mode.
+fn is_ci(value: Option<&str>) -> bool {
+    match value {
+        Some(ci) => ci.to_lowercase() == "true" || ci == "1",
+        None => false,
+    }
+}
+
 impl Default for Logger {
     fn default() -> Self {
         // get the environment variable RUST_LOG and parse it
@@ -391,90 +508,62 @@ impl Logger {
         }
     }
 
+    /// print a log message with the given label, and tee it to the log file
+    fn log(&self, label: ColoredString, message: &str) {
+        let timestamp = pretty_timestamp();
+        print_line(&format!("{}  {}: {}", timestamp.dimmed(), label, message));
+        write_to_log_file(&format!("{}  {}: {}", timestamp, &*label, message));
+    }
+
     /// log an error message
     pub fn error(&self, message: &str) {
         if self.level >= 0 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "error".bright_red().bold(),
-                message
-            );
+            self.log("error".bright_red().bold(), message);
         }
     }
 
     /// log a fatal error, typically an unhanded exception which causes the program to exit
     pub fn fatal(&self, message: &str) {
-        println!(
-            "{}  {}: {}",
-            pretty_timestamp().dimmed(),
-            "fatal".bright_white().on_bright_red().bold(),
-            message
-        );
+        self.log("fatal".bright_white().on_bright_red().bold(), message);
     }
 
     /// log a success message
     pub fn success(&self, message: &str) {
         if self.level >= 0 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "success".bright_green().bold(),
-                message
-            );
+            self.log("success".bright_green().bold(), message);
         }
     }
 
     /// log an info message
     pub fn info(&self, message: &str) {
         if self.level >= 1 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "info".bright_cyan().bold(),
-                message
-            );
+            self.log("info".bright_cyan().bold(), message);
         }
     }
 
     /// log a warning message
     pub fn warn(&self, message: &str) {
-        println!("{}  {}: {}", pretty_timestamp().dimmed(), "warn".bright_yellow().bold(), message);
+        self.log("warn".bright_yellow().bold(), message);
     }
 
     /// log a debug message
     pub fn debug(&self, message: &str) {
         if self.level >= 2 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "debug".bright_magenta().bold(),
-                message
-            );
+            self.log("debug".bright_magenta().bold(), message);
         }
     }
 
     /// log a trace message
     pub fn trace(&self, message: &str) {
         if self.level >= 4 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "trace".bright_blue().bold(),
-                message
-            );
+            self.log("trace".bright_blue().bold(), message);
         }
     }
 
     /// log a max message
     pub fn debug_max(&self, message: &str) {
         if self.level >= 6 {
-            println!(
-                "{}  {}: {}",
-                pretty_timestamp().dimmed(),
-                "debug".bright_white().bold(),
-                message
-            );
+            self.log("debug".bright_white().bold(), message);
         }
     }
 
@@ -502,7 +591,8 @@ impl Logger {
         .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
     }
 
-    /// prompt the user to select an option from the given list, or return the default
+    /// prompt the user to select an option from the given list, or return the default. if `skip`
+    /// is set or heimdall is running non-interactively, the default is selected automatically.
     pub fn option(
         &self,
         function: &str,
@@ -516,6 +606,9 @@ impl Logger {
             return default.expect("Failed to get default option.")
         }
 
+        // never block on stdin when running unattended
+        let skip = skip || is_non_interactive();
+
         // log the message with the given class
         match function {
             "error" => self.error(message),
@@ -527,7 +620,7 @@ impl Logger {
 
         // print the option tree
         for (i, option) in options.iter().enumerate() {
-            println!(
+            emit(&format!(
                 "                                      {} {}: {}",
                 if i == options.len() - 1 {
                     "└─".bold().bright_white()
@@ -536,7 +629,7 @@ impl Logger {
                 },
                 i,
                 option
-            );
+            ));
         }
 
         // flush output print prompt
@@ -557,11 +650,20 @@ impl Logger {
             } else {
                 println!();
             }
-            return default.expect("Failed to get default option.")
+
+            // log the decision, so unattended runs can be audited later
+            let default = default.expect("Failed to get default option.");
+            if let Some(selected) = options.get(default as usize) {
+                self.info(&format!("automatically selected option {default}: '{selected}' ."));
+            }
+            return default
         }
 
         // get input
-        match stdin().read_line(&mut selection) {
+        PROMPTING.store(true, Ordering::SeqCst);
+        let input = stdin().read_line(&mut selection);
+        PROMPTING.store(false, Ordering::SeqCst);
+        match input {
             Ok(_) => {
                 // check if default was selected
                 if selection.trim() == "" {
@@ -603,6 +705,36 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(strip_ansi(&"error".bright_red().bold().to_string()), "error");
+        assert_eq!(strip_ansi("no colors here"), "no colors here");
+    }
+
+    #[test]
+    fn test_no_color() {
+        assert!(no_color(Some("1")));
+        assert!(no_color(Some("true")));
+        assert!(!no_color(Some("")));
+        assert!(!no_color(None));
+    }
+
+    #[test]
+    fn test_log_file() {
+        let path = "/tmp/heimdall_log_file/heimdall.log";
+        set_log_file(path).unwrap();
+
+        let (logger, _) = Logger::new("TRACE");
+        logger.success("written to the log file");
+
+        let contents = std::fs::read_to_string(path).unwrap();
+        assert!(contents.contains("success: written to the log file"));
+        assert!(!contents.contains('\u{1b}'));
+
+        *LOG_FILE.lock().unwrap() = None;
+        std::fs::remove_dir_all("/tmp/heimdall_log_file").unwrap();
+    }
+
     #[test]
     fn test_raw_trace() {
         let start_time = Instant::now();
@@ -718,6 +850,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_is_ci() {
+        assert!(is_ci(Some("true")));
+        assert!(is_ci(Some("TRUE")));
+        assert!(is_ci(Some("1")));
+        assert!(!is_ci(Some("false")));
+        assert!(!is_ci(Some("")));
+        assert!(!is_ci(None));
+    }
+
     #[test]
     fn test_warn() {
         let (logger, _) = Logger::new("SILENT");
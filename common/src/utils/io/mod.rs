@@ -1,2 +1,5 @@
 pub mod file;
+pub mod format;
 pub mod logging;
+pub mod output;
+pub mod template;
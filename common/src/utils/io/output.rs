@@ -0,0 +1,84 @@
+use crate::constants::{ADDRESS_REGEX, TRANSACTION_HASH_REGEX};
+
+use super::file::output_dir;
+
+/// Locates where each subcommand writes its artifacts, so every artifact for a target lands in
+/// the same directory: `<root>/<address>/` for contracts, `<root>/<hash>/` for transactions, and
+/// `<root>/local/` for raw bytecode and calldata.
+///
+/// ```
+/// use heimdall_common::utils::io::output::OutputLocator;
+///
+/// let locator = OutputLocator::new("/tmp/output");
+/// assert_eq!(
+///     locator.artifact("0xC02AAA39B223FE8D0A0E5C4F27EAD9083C756CC2", "decompiled.sol"),
+///     "/tmp/output/0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2/decompiled.sol"
+/// );
+/// assert_eq!(locator.artifact("6080604052", "disassembled.asm"), "/tmp/output/local/disassembled.asm");
+/// assert_eq!(locator.file("bench.csv"), "/tmp/output/bench.csv");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputLocator {
+    /// The root output directory, without a trailing slash.
+    pub root: String,
+}
+
+impl OutputLocator {
+    pub fn new(root: &str) -> Self {
+        Self { root: root.trim_end_matches('/').to_string() }
+    }
+
+    /// Create a locator rooted at the `--output` argument, or the configured `output_dir`, as
+    /// resolved by [`output_dir`].
+    pub fn resolve(output: &str, configured: &str) -> std::io::Result<Self> {
+        Ok(Self::new(&output_dir(output, configured)?))
+    }
+
+    /// The directory the target's artifacts are written to. Addresses and transaction hashes are
+    /// lowercased and `0x`-prefixed, so every spelling of a target shares one directory.
+    pub fn target_dir(&self, target: &str) -> String {
+        let target = target.trim();
+        if ADDRESS_REGEX.is_match(target).unwrap_or(false) ||
+            TRANSACTION_HASH_REGEX.is_match(target).unwrap_or(false)
+        {
+            format!("{}/0x{}", self.root, target.trim_start_matches("0x").to_lowercase())
+        } else {
+            format!("{}/local", self.root)
+        }
+    }
+
+    /// The path of a named artifact for the target, such as `decompiled.sol`.
+    pub fn artifact(&self, target: &str, name: &str) -> String {
+        format!("{}/{name}", self.target_dir(target))
+    }
+
+    /// The path of a named artifact which doesn't belong to a single target, such as
+    /// `bench.csv`.
+    pub fn file(&self, name: &str) -> String {
+        format!("{}/{name}", self.root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_dir() {
+        let locator = OutputLocator::new("/tmp/output/");
+
+        // every spelling of an address shares a directory
+        let address = "/tmp/output/0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2";
+        assert_eq!(locator.target_dir("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"), address);
+        assert_eq!(locator.target_dir("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2"), address);
+        assert_eq!(locator.target_dir("c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"), address);
+
+        // transactions are located by their hash
+        let hash = format!("0x{}", "AB".repeat(32));
+        assert_eq!(locator.target_dir(&hash), format!("/tmp/output/{}", hash.to_lowercase()));
+
+        // bytecode, calldata, and files are local
+        assert_eq!(locator.target_dir("0x6080604052"), "/tmp/output/local");
+        assert_eq!(locator.target_dir("./contract.bin"), "/tmp/output/local");
+    }
+}
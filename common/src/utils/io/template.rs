@@ -0,0 +1,145 @@
+use std::path::Path;
+
+use handlebars::{no_escape, Handlebars};
+use serde_json::Value;
+
+/// Render a Handlebars template with the given JSON context, supporting the full Handlebars
+/// language, including its built-in helpers such as `#each`, `#if`, `#with`, `lookup`, and `eq`.
+/// Output isn't HTML-escaped, since reports are usually markdown or plain text.
+///
+/// ```
+/// use heimdall_common::utils::io::template::render_template;
+///
+/// let context = serde_json::json!({
+///     "target": "0x1234",
+///     "functions": [{ "name": "transfer" }, { "name": "approve" }]
+/// });
+///
+/// let report = render_template(
+///     "# {{target}}\n{{#each functions}}- {{@index}}: {{name}}\n{{/each}}",
+///     &context,
+/// )
+/// .unwrap();
+/// assert_eq!(report, "# 0x1234\n- 0: transfer\n- 1: approve\n");
+/// ```
+pub fn render_template(template: &str, context: &Value) -> Result<String, String> {
+    registry().render_template(template, context).map_err(|e| e.to_string())
+}
+
+/// Render the Handlebars template at the given path with the given JSON context, as with
+/// [`render_template`]. Every other `.hbs` file in the template's directory is registered as a
+/// partial named after its file stem, so `{{> header}}` includes `header.hbs`.
+pub fn render_template_file(path: &str, context: &Value) -> Result<String, String> {
+    let template = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let path = Path::new(path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let mut handlebars = registry();
+    for partial in std::fs::read_dir(dir).map_err(|e| e.to_string())?.flatten() {
+        let partial = partial.path();
+        if partial.extension().is_none_or(|extension| extension != "hbs") ||
+            partial.file_name() == path.file_name()
+        {
+            continue
+        }
+
+        if let Some(name) = partial.file_stem().and_then(|name| name.to_str()) {
+            let contents = std::fs::read_to_string(&partial).map_err(|e| e.to_string())?;
+            handlebars.register_partial(name, contents).map_err(|e| e.to_string())?;
+        }
+    }
+
+    handlebars.render_template(&template, context).map_err(|e| e.to_string())
+}
+
+/// A template registry which doesn't HTML-escape its output.
+fn registry() -> Handlebars<'static> {
+    let mut handlebars = Handlebars::new();
+    handlebars.register_escape_fn(no_escape);
+    handlebars
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_template_blocks() {
+        let context = json!({
+            "target": "0xabcd",
+            "functions": [
+                { "name": "transfer", "payable": false, "flags": ["transfers ether"] },
+                { "name": "deposit", "payable": true, "flags": [] }
+            ]
+        });
+
+        let template = "{{#each functions}}{{name}}{{#if payable}} (payable){{/if}}: \
+                        {{#each flags}}{{this}}{{else}}none{{/each}} in {{../target}};{{/each}}";
+        assert_eq!(
+            render_template(template, &context).unwrap(),
+            "transfer: transfers ether in 0xabcd;deposit (payable): none in 0xabcd;"
+        );
+    }
+
+    #[test]
+    fn test_render_template_helpers() {
+        let context = json!({
+            "functions": [{ "name": "transfer", "signature": "transfer(address,uint256)" }],
+            "owner": { "name": "owner()" },
+            "level": "high"
+        });
+
+        assert_eq!(
+            render_template(
+                "{{#with owner}}{{name}}{{/with}} {{lookup (lookup functions 0) \"name\"}} \
+                 {{#if (eq level \"low\")}}low{{else if (eq level \"high\")}}high{{/if}}",
+                &context
+            )
+            .unwrap(),
+            "owner() transfer high"
+        );
+    }
+
+    #[test]
+    fn test_render_template_no_escape() {
+        let context = json!({ "signature": "mapping(address => uint256) <balances>" });
+
+        assert_eq!(
+            render_template("{{signature}}|{{{signature}}}", &context).unwrap(),
+            "mapping(address => uint256) <balances>|mapping(address => uint256) <balances>"
+        );
+    }
+
+    #[test]
+    fn test_render_template_errors() {
+        assert!(render_template("{{#each functions}}", &json!({})).is_err());
+        assert!(render_template("{{/if}}", &json!({})).is_err());
+        assert!(render_template("{{name", &json!({})).is_err());
+        assert!(render_template("{{> partial}}", &json!({})).is_err());
+        assert!(render_template("{{unknown_helper functions}}", &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_render_template_file_partials() {
+        let dir = "/tmp/heimdall_template_partials";
+        std::fs::create_dir_all(dir).unwrap();
+        std::fs::write(
+            format!("{dir}/report.md"),
+            "{{> header}}{{#each functions}}{{> row}}{{/each}}",
+        )
+        .unwrap();
+        std::fs::write(format!("{dir}/header.hbs"), "# {{target}}\n").unwrap();
+        std::fs::write(format!("{dir}/row.hbs"), "- {{name}}\n").unwrap();
+
+        let context = json!({ "target": "0xabcd", "functions": [{ "name": "transfer" }] });
+        let report = render_template_file(&format!("{dir}/report.md"), &context);
+
+        std::fs::remove_dir_all(dir).unwrap();
+        assert_eq!(report.unwrap(), "# 0xabcd\n- transfer\n");
+        assert!(render_template_file(&format!("{dir}/report.md"), &context).is_err());
+    }
+}
@@ -1,5 +1,8 @@
+pub mod crash;
+pub mod events;
 pub mod http;
 pub mod integers;
+pub mod interrupt;
 pub mod io;
 pub mod strings;
 pub mod sync;
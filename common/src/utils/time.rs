@@ -1,4 +1,4 @@
-use chrono::Local;
+use chrono::{DateTime, Local};
 
 /// Calculate the ETA for a process based on the number of items processed per second
 ///
@@ -47,6 +47,17 @@ pub fn pretty_timestamp() -> String {
     now.format("%d-%m-%Y %H:%M:%S.%f").to_string()
 }
 
+/// Format a unix timestamp as an ISO 8601 date in UTC, or `None` if it's out of range.
+///
+/// ```
+/// use heimdall_common::utils::time::iso_timestamp;
+///
+/// assert_eq!(iso_timestamp(1700000000).unwrap(), "2023-11-14T22:13:20Z");
+/// ```
+pub fn iso_timestamp(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0).map(|date| date.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use crate::utils::time::*;
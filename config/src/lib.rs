@@ -1,17 +1,48 @@
 use clap::{AppSettings, Parser};
-use heimdall_common::utils::io::{
-    file::{delete_path, read_file, write_file},
-    logging::*,
+use heimdall_cache::util::parse_bytes;
+use heimdall_common::{
+    constants::ADDRESS_REGEX,
+    ether::{
+        labels::{read_address_book, set_label},
+        rpc::{parse_chain_rpc, parse_rpc_header},
+    },
+    utils::io::{
+        file::{delete_path, read_file, write_file},
+        logging::*,
+    },
 };
 use serde::{Deserialize, Serialize};
 #[allow(deprecated)]
 use std::env::home_dir;
 
+use diagnostics::{diagnose, log_diagnostics};
+
+pub mod diagnostics;
+
 pub static DEFAULT_CONFIG: &str = "rpc_url = \"\"
 local_rpc_url = \"http://localhost:8545\"
 etherscan_api_key = \"\"
 transpose_api_key = \"\"
+dune_api_key = \"\"
+dump_backend = \"\"
 openai_api_key = \"\"
+ipfs_gateway = \"https://ipfs.io/ipfs/\"
+disable_update_check = false
+memory_limit = 0
+max_branch_depth = 0
+max_call_depth = 0
+max_loop_iterations = 0
+http_max_retries = 5
+http_timeout = 0
+proxy = \"\"
+rpc_headers = []
+output_dir = \"\"
+token_lists = []
+plugins = []
+chains = []
+cache_max_size = 0
+max_memory_size = 0
+max_memory_access = 0
 ";
 
 #[derive(Debug, Clone, Parser)]
@@ -22,13 +53,18 @@ openai_api_key = \"\"
     override_usage = "heimdall config [OPTIONS]"
 )]
 pub struct ConfigArgs {
-    /// The target key to update.
+    /// The target key to update, `test` to validate the current configuration, or `label` to
+    /// label an address in the address book.
     #[clap(required = false, default_value = "")]
     key: String,
 
-    /// The value to set the key to.
+    /// The value to set the key to, or the address to label.
     #[clap(required = false, default_value = "")]
     value: String,
+
+    /// The label to give the address, when labelling an address. An empty label removes it.
+    #[clap(required = false)]
+    label: Option<String>,
 }
 
 /// The [`Configuration`] struct represents the configuration of the CLI. All heimdall core modules
@@ -40,6 +76,64 @@ pub struct Configuration {
     pub etherscan_api_key: String,
     pub transpose_api_key: String,
     pub openai_api_key: String,
+    #[serde(default)]
+    pub dune_api_key: String,
+    #[serde(default)]
+    pub dump_backend: String,
+    #[serde(default = "default_ipfs_gateway")]
+    pub ipfs_gateway: String,
+    #[serde(default)]
+    pub disable_update_check: bool,
+    #[serde(default)]
+    pub memory_limit: usize,
+    #[serde(default)]
+    pub max_branch_depth: usize,
+    #[serde(default)]
+    pub max_call_depth: u32,
+    #[serde(default)]
+    pub max_loop_iterations: usize,
+    #[serde(default = "default_http_max_retries")]
+    pub http_max_retries: u8,
+    #[serde(default)]
+    pub http_timeout: u64,
+    #[serde(default)]
+    pub proxy: String,
+    #[serde(default)]
+    pub rpc_headers: Vec<String>,
+    #[serde(default)]
+    pub output_dir: String,
+    #[serde(default)]
+    pub token_lists: Vec<String>,
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    /// The chains which `diff --chains` can compare deployments across, each in the
+    /// `name=rpc_url` format.
+    #[serde(default)]
+    pub chains: Vec<String>,
+    /// The maximum size of the cache in bytes, beyond which the least recently used objects are
+    /// evicted. 0 means the cache is unbounded.
+    #[serde(default)]
+    pub cache_max_size: u64,
+    /// The maximum number of bytes the EVM's memory holds, beyond which writes are dropped. 0
+    /// means the default of 16MB.
+    #[serde(default)]
+    pub max_memory_size: u64,
+    /// The maximum number of bytes a single EVM memory read returns, beyond which reads are
+    /// truncated. 0 means the default of 64KB.
+    #[serde(default)]
+    pub max_memory_access: u64,
+}
+
+/// The number of times failed HTTP requests are retried when no limit is configured, i.e. for
+/// configuration files created by older versions of heimdall.
+fn default_http_max_retries() -> u8 {
+    5
+}
+
+/// The IPFS gateway used when no gateway is configured, i.e. for configuration files created by
+/// older versions of heimdall.
+fn default_ipfs_gateway() -> String {
+    String::from("https://ipfs.io/ipfs/")
 }
 
 #[allow(deprecated)]
@@ -109,13 +203,18 @@ pub fn read_config() -> String {
     }
 }
 
+/// Parse the contents of a configuration file, without regenerating it if it's invalid.
+pub fn parse_config(contents: &str) -> Result<Configuration, String> {
+    toml::from_str(contents).map_err(|e| e.to_string())
+}
+
 /// Returns the [`Configuration`] struct after parsing the configuration file at
 /// `$HOME/.bifrost/config.toml`.
 pub fn get_config() -> Configuration {
     let contents = read_config();
 
     // toml parse from contents into Configuration
-    let config: Configuration = match toml::from_str(&contents) {
+    let config: Configuration = match parse_config(&contents) {
         Ok(config) => config,
         Err(e) => {
             let (logger, _) = Logger::new("");
@@ -149,6 +248,140 @@ pub fn update_config(key: &str, value: &str) {
         "openai_api_key" => {
             contents.openai_api_key = value.to_string();
         }
+        "dune_api_key" => {
+            contents.dune_api_key = value.to_string();
+        }
+        "dump_backend" => match value {
+            "" | "transpose" | "dune" | "rpc" => contents.dump_backend = value.to_string(),
+            _ => {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!("'{key}' must be one of 'transpose', 'dune', or 'rpc' ."));
+                std::process::exit(1)
+            }
+        },
+        "ipfs_gateway" => {
+            contents.ipfs_gateway = value.to_string();
+        }
+        "proxy" => {
+            contents.proxy = value.to_string();
+        }
+        "output_dir" => {
+            contents.output_dir = value.to_string();
+        }
+        "rpc_headers" => {
+            // headers are separated by semicolons, and an empty value removes them all
+            let headers = value
+                .split(';')
+                .map(|header| header.trim().to_string())
+                .filter(|header| !header.is_empty())
+                .collect::<Vec<String>>();
+            if let Some(Err(e)) =
+                headers.iter().map(|header| parse_rpc_header(header)).find(Result::is_err)
+            {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!("'{key}' is invalid: {e} ."));
+                std::process::exit(1)
+            }
+            contents.rpc_headers = headers;
+        }
+        "chains" => {
+            // chains are separated by semicolons, and an empty value removes them all
+            let chains = value
+                .split(';')
+                .map(|chain| chain.trim().to_string())
+                .filter(|chain| !chain.is_empty())
+                .collect::<Vec<String>>();
+            if let Some(Err(e)) =
+                chains.iter().map(|chain| parse_chain_rpc(chain)).find(Result::is_err)
+            {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!("'{key}' is invalid: {e} ."));
+                std::process::exit(1)
+            }
+            contents.chains = chains;
+        }
+        "token_lists" | "plugins" => {
+            // token lists and plugins are separated by semicolons, and an empty value removes
+            // them all
+            let sources = value
+                .split(';')
+                .map(|source| source.trim().to_string())
+                .filter(|source| !source.is_empty())
+                .collect();
+            match key {
+                "token_lists" => contents.token_lists = sources,
+                _ => contents.plugins = sources,
+            }
+        }
+        "disable_update_check" => match value.parse::<bool>() {
+            Ok(value) => contents.disable_update_check = value,
+            Err(_) => {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!("'{key}' must be either 'true' or 'false' ."));
+                std::process::exit(1)
+            }
+        },
+        "max_branch_depth" | "max_call_depth" | "max_loop_iterations" => {
+            match value.parse::<u32>() {
+                Ok(limit) if key == "max_branch_depth" => {
+                    contents.max_branch_depth = limit as usize
+                }
+                Ok(limit) if key == "max_call_depth" => contents.max_call_depth = limit,
+                Ok(limit) => contents.max_loop_iterations = limit as usize,
+                Err(_) => {
+                    let (logger, _) = Logger::new("");
+                    logger.error(&format!("'{key}' must be a number, or 0 for the default ."));
+                    std::process::exit(1)
+                }
+            }
+        }
+        "http_max_retries" => match value.parse::<u8>() {
+            Ok(value) => contents.http_max_retries = value,
+            Err(_) => {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!("'{key}' must be a number from 0 to 255 ."));
+                std::process::exit(1)
+            }
+        },
+        "http_timeout" => match value.parse::<u64>() {
+            Ok(value) => contents.http_timeout = value,
+            Err(_) => {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!(
+                    "'{key}' must be a number of seconds, or 0 for each request's default ."
+                ));
+                std::process::exit(1)
+            }
+        },
+        "cache_max_size" => match parse_bytes(value) {
+            Some(value) => contents.cache_max_size = value,
+            None => {
+                let (logger, _) = Logger::new("");
+                logger
+                    .error(&format!("'{key}' must be a size such as '500MB', or 0 for no limit ."));
+                std::process::exit(1)
+            }
+        },
+        "max_memory_size" | "max_memory_access" => match parse_bytes(value) {
+            Some(size) if key == "max_memory_size" => contents.max_memory_size = size,
+            Some(size) => contents.max_memory_access = size,
+            None => {
+                let (logger, _) = Logger::new("");
+                logger.error(&format!(
+                    "'{key}' must be a size such as '16MB', or 0 for the default ."
+                ));
+                std::process::exit(1)
+            }
+        },
+        "memory_limit" => match value.parse::<usize>() {
+            Ok(value) => contents.memory_limit = value,
+            Err(_) => {
+                let (logger, _) = Logger::new("");
+                logger
+                    .error(&format!("'{key}' must be a number of megabytes, or 0 for no limit ."));
+                std::process::exit(1)
+            }
+        },
         _ => {
             let (logger, _) = Logger::new("");
             logger.error(&format!("unknown configuration key \'{key}\' ."));
@@ -162,9 +395,51 @@ pub fn update_config(key: &str, value: &str) {
 }
 
 /// The `config` command is used to display and edit the current configuration.
-pub fn config(args: ConfigArgs) {
+pub async fn config(args: ConfigArgs) {
     let (logger, _) = Logger::new("");
-    if !args.key.is_empty() {
+    if args.key == "test" && args.value.is_empty() {
+        // validate the current configuration, reporting each item
+        let contents = read_config();
+        let rpc_url = parse_config(&contents).map(|c| c.rpc_url).unwrap_or_default();
+        let diagnostics = diagnose(&contents, &rpc_url).await;
+        log_diagnostics(&logger, &diagnostics);
+
+        let failed = diagnostics.iter().filter(|d| !d.passed && d.required).count();
+        if failed > 0 {
+            logger.error(&format!("{failed} configuration check(s) failed."));
+            std::process::exit(1);
+        }
+    } else if args.key == "label" {
+        match (args.value.as_str(), args.label) {
+            // no address is set, print the address book
+            ("", _) => {
+                for (address, label) in read_address_book() {
+                    println!("{address} {label}");
+                }
+                logger.info("use `heimdall config label <ADDRESS> <LABEL>` to label an address.");
+            }
+            (address, Some(label)) if ADDRESS_REGEX.is_match(address).unwrap_or(false) => {
+                let address = match address.starts_with("0x") {
+                    true => address.to_string(),
+                    false => format!("0x{address}"),
+                };
+                if let Err(e) = set_label(&address, &label) {
+                    logger.error(&format!("failed to update the address book: {e}"));
+                    std::process::exit(1);
+                }
+                logger
+                    .success(&format!("updated address book! Labelled '{address}' as '{label}' ."));
+            }
+            (address, Some(_)) => {
+                logger.error(&format!("'{address}' is not a valid address."));
+                std::process::exit(1);
+            }
+            (_, None) => {
+                logger.error("found address but no label to set. Use `heimdall config label <ADDRESS> <LABEL>` to label an address.");
+                std::process::exit(1);
+            }
+        }
+    } else if !args.key.is_empty() {
         if !args.value.is_empty() {
             // read the config file and update the key/value pair
             update_config(&args.key, &args.value);
@@ -181,5 +456,19 @@ pub fn config(args: ConfigArgs) {
         // no key is set, print the config file
         println!("{:#?}", get_config());
         logger.info("use `heimdall config <KEY> <VALUE>` to set a key/value pair.");
+        logger.info("use `heimdall config test` to validate the current configuration.");
+        logger.info("use `heimdall config label <ADDRESS> <LABEL>` to label an address.");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config_valid() {
+        let configuration = parse_config(DEFAULT_CONFIG).expect("default config should parse");
+        assert_eq!(configuration.rpc_url, "");
+        assert!(parse_config("rpc_url = ").is_err());
     }
 }
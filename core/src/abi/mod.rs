@@ -0,0 +1,534 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+};
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use ethers::types::U256;
+use heimdall_common::{
+    constants::{ADDRESS_REGEX, BYTECODE_REGEX},
+    ether::{
+        eravm::{candidate_selectors, validate_eravm_bytecode},
+        evm::core::vm::VM,
+        rpc::get_code_at_block,
+        selectors::{find_function_selectors, resolve_selectors},
+        signatures::{
+            score_signature, ResolveSelector, ResolvedError, ResolvedFunction, ResolvedLog,
+        },
+        target::{select_target_vm, TargetVm},
+    },
+    utils::{io::logging::Logger, strings::decode_hex},
+};
+
+use crate::{
+    decompile::out::abi::{ABIStructure, ABIToken, ErrorABI, EventABI, FunctionABI},
+    disassemble::{disassemble, DisassemblerArgs},
+};
+
+/// The number of zeroed argument words each function is called with when guessing its
+/// mutability. Unused arguments are ignored, so this only needs to cover most functions' inputs.
+const ARGUMENT_WORDS: usize = 16;
+
+/// The maximum number of instructions executed when guessing a function's mutability, so that
+/// unbounded loops don't stall extraction.
+const MAX_STEPS: usize = 50_000;
+
+/// The selectors of the built-in `Error(string)` and `Panic(uint256)` errors, which aren't part of
+/// a contract's ABI.
+const BUILTIN_ERRORS: [u32; 2] = [0x08c379a0, 0x4e487b71];
+
+/// Instructions which read the state or environment, so a function which executes them isn't
+/// pure.
+const STATE_READING_OPCODES: [&str; 19] = [
+    "BALANCE",
+    "ORIGIN",
+    "CALLER",
+    "GASPRICE",
+    "EXTCODESIZE",
+    "EXTCODECOPY",
+    "EXTCODEHASH",
+    "BLOCKHASH",
+    "COINBASE",
+    "TIMESTAMP",
+    "NUMBER",
+    "DIFFICULTY",
+    "GASLIMIT",
+    "CHAINID",
+    "SELFBALANCE",
+    "BASEFEE",
+    "SLOAD",
+    "ADDRESS",
+    "STATICCALL",
+];
+
+/// Instructions which modify the state, so a function which executes them isn't a view.
+const STATE_MODIFYING_OPCODES: [&str; 12] = [
+    "SSTORE",
+    "CREATE",
+    "CREATE2",
+    "SELFDESTRUCT",
+    "CALL",
+    "CALLCODE",
+    "DELEGATECALL",
+    "LOG0",
+    "LOG1",
+    "LOG2",
+    "LOG3",
+    "LOG4",
+];
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Extract a contract's ABI without decompiling it",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall abi <TARGET> [OPTIONS]"
+)]
+pub struct AbiArgs {
+    /// The target to extract the ABI of, either a file, bytecode, contract address, or ENS name.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching target bytecode.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// Whether to skip resolving function, event, and error selectors.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+
+    /// The block number to fetch the target's bytecode at, which requires an archive node.
+    /// Defaults to the latest block.
+    #[clap(long = "block", default_value = "0", hide_default_value = true)]
+    pub block: u64,
+
+    /// The virtual machine the target is compiled for, either `evm` or `eravm`. Defaults to the
+    /// virtual machine of the RPC provider's chain for contract addresses, and `evm` otherwise.
+    #[clap(long = "vm", default_value = "", hide_default_value = true)]
+    pub vm: String,
+}
+
+impl AbiArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            skip_resolving: Some(false),
+            block: Some(0),
+            vm: Some(String::new()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AbiResult {
+    /// The contract's functions, sorted by selector, followed by its errors and events.
+    pub abi: Vec<ABIStructure>,
+}
+
+/// What a function was observed to do when called with zeroed arguments.
+#[derive(Debug, Clone, Default)]
+struct CallObservation {
+    /// Whether the call returned or stopped, rather than reverting.
+    succeeded: bool,
+    reads_state: bool,
+    modifies_state: bool,
+    /// The topics of the events the function emitted.
+    events: BTreeSet<U256>,
+    /// The selector of the custom error the function reverted with, if any.
+    error: Option<u32>,
+}
+
+/// Call the function with the given selector with zeroed arguments, observing which instructions
+/// it executes.
+fn observe_call(evm: &VM, selector: &str) -> CallObservation {
+    let mut vm = evm.clone();
+    vm.reset();
+    vm.calldata =
+        decode_hex(&format!("{selector}{}", "00".repeat(ARGUMENT_WORDS * 32))).unwrap_or_default();
+
+    let mut observation = CallObservation::default();
+    let mut steps = 0;
+    while vm.bytecode.len() >= vm.instruction as usize && vm.exitcode == 255 && steps < MAX_STEPS {
+        let state = vm.step();
+        steps += 1;
+
+        let name = match state.last_instruction.opcode_details {
+            Some(opcode) => opcode.name,
+            None => continue,
+        };
+        observation.reads_state |= STATE_READING_OPCODES.contains(&name);
+        observation.modifies_state |= STATE_MODIFYING_OPCODES.contains(&name);
+    }
+
+    observation.succeeded = vm.exitcode == 0 || vm.exitcode == 10;
+    observation.events = vm.events.iter().filter_map(|log| log.topics.first().copied()).collect();
+    if vm.exitcode == 1 && vm.returndata.len() >= 4 {
+        let selector = u32::from_be_bytes([
+            vm.returndata[0],
+            vm.returndata[1],
+            vm.returndata[2],
+            vm.returndata[3],
+        ]);
+        if !BUILTIN_ERRORS.contains(&selector) {
+            observation.error = Some(selector);
+        }
+    }
+
+    observation
+}
+
+/// Whether the function at the entry point checks that no ether was sent with the call, i.e.
+/// whether its first conditional jump depends on `CALLVALUE`.
+fn checks_call_value(evm: &VM, selector: &str, entry_point: u128) -> bool {
+    let mut vm = evm.clone();
+    vm.reset();
+    vm.calldata =
+        decode_hex(&format!("{selector}{}", "00".repeat(ARGUMENT_WORDS * 32))).unwrap_or_default();
+    vm.value = 1;
+
+    let mut entered = false;
+    let mut steps = 0;
+    while vm.bytecode.len() >= vm.instruction as usize && vm.exitcode == 255 && steps < MAX_STEPS {
+        entered |= vm.instruction == entry_point;
+        let state = vm.step();
+        steps += 1;
+
+        if entered && state.last_instruction.opcode == 0x57 {
+            return state
+                .last_instruction
+                .input_operations
+                .get(1)
+                .is_some_and(|condition| condition.to_string().contains("CALLVALUE"))
+        }
+    }
+
+    false
+}
+
+/// Guess a function's state mutability from a single concrete call. The guess only reflects the
+/// path taken with zeroed arguments, so a function which reverts is conservatively assumed to
+/// modify the state.
+fn guess_mutability(
+    observation: &CallObservation,
+    rejects_call_value: bool,
+    checks_call_value: impl FnOnce() -> bool,
+) -> &'static str {
+    if observation.succeeded && !observation.modifies_state {
+        return match observation.reads_state {
+            true => "view",
+            false => "pure",
+        }
+    }
+
+    match rejects_call_value || checks_call_value() {
+        true => "nonpayable",
+        false => "payable",
+    }
+}
+
+/// Pick the best-scored signature of each resolved selector.
+async fn resolve_best<T>(selectors: Vec<String>, signature: fn(&T) -> &str) -> BTreeMap<String, T>
+where
+    T: ResolveSelector + Send + Clone + 'static, {
+    resolve_selectors::<T>(selectors)
+        .await
+        .into_iter()
+        .filter_map(|(selector, mut candidates)| {
+            candidates
+                .sort_by_key(|candidate| std::cmp::Reverse(score_signature(signature(candidate))));
+            candidates.into_iter().next().map(|candidate| (selector, candidate))
+        })
+        .collect()
+}
+
+/// Build the named ABI tokens of a resolved signature's inputs.
+fn abi_tokens(inputs: &[String]) -> Vec<ABIToken> {
+    inputs
+        .iter()
+        .filter(|input| !input.is_empty())
+        .enumerate()
+        .map(|(index, input)| ABIToken {
+            name: format!("arg{index}"),
+            internal_type: input.to_owned(),
+            type_: input.to_owned(),
+        })
+        .collect()
+}
+
+/// The constants pushed with the given `PUSHn` instruction throughout the bytecode.
+fn pushed_constants(bytecode: &[u8], opcode: u8) -> BTreeSet<U256> {
+    let mut constants = BTreeSet::new();
+    let mut pc = 0;
+    while pc < bytecode.len() {
+        let op = bytecode[pc];
+        if (0x60..=0x7f).contains(&op) {
+            let size = (op - 0x5f) as usize;
+            if op == opcode && pc + size < bytecode.len() {
+                constants.insert(U256::from_big_endian(&bytecode[pc + 1..pc + 1 + size]));
+            }
+            pc += size;
+        }
+        pc += 1;
+    }
+
+    constants
+}
+
+/// Extract the functions of EraVM bytecode. Since EraVM bytecode can't be symbolically executed,
+/// its functions are the candidate selectors found among its constants, of which only those which
+/// resolve are kept unless resolving is skipped. Their mutability is unknown, so they're assumed
+/// to be nonpayable.
+async fn eravm_abi(
+    bytecode: &[u8],
+    args: &AbiArgs,
+    logger: &Logger,
+) -> Result<AbiResult, Box<dyn std::error::Error>> {
+    if let Err(error) = validate_eravm_bytecode(bytecode) {
+        logger.error(&format!("invalid EraVM bytecode: {error}."));
+        std::process::exit(1)
+    }
+
+    let selectors = candidate_selectors(bytecode)
+        .into_iter()
+        .map(|selector| format!("{selector:08x}"))
+        .collect::<Vec<String>>();
+    logger.info(&format!("found {} candidate function selectors.", selectors.len()));
+
+    let resolved_functions = match args.skip_resolving {
+        true => BTreeMap::new(),
+        false => {
+            resolve_best::<ResolvedFunction>(selectors.clone(), |function| &function.signature)
+                .await
+        }
+    };
+
+    let mut abi = Vec::new();
+    for selector in &selectors {
+        let (name, inputs) = match resolved_functions.get(selector) {
+            Some(function) => (function.name.clone(), abi_tokens(&function.inputs)),
+            None if args.skip_resolving => (format!("Unresolved_{selector}"), Vec::new()),
+            None => continue,
+        };
+        abi.push(ABIStructure::Function(FunctionABI {
+            type_: String::from("function"),
+            constant: false,
+            name,
+            inputs,
+            outputs: Vec::new(),
+            state_mutability: String::from("nonpayable"),
+        }));
+    }
+
+    logger.success(&format!("extracted an ABI with {} functions from EraVM bytecode.", abi.len()));
+
+    Ok(AbiResult { abi })
+}
+
+/// The entrypoint for the abi module. This extracts the target's function selectors, events, and
+/// custom errors, along with a guess of each function's mutability, without symbolically
+/// executing or reconstructing the function bodies as `decompile` does.
+pub async fn abi(args: AbiArgs) -> Result<AbiResult, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    // parse the various formats that are accepted as targets
+    // i.e, file, bytecode, contract address
+    let contract_bytecode: String;
+    if ADDRESS_REGEX.is_match(&args.target)? {
+        // We are reading a contract address, so we need to fetch the bytecode from the RPC
+        // provider.
+        contract_bytecode =
+            get_code_at_block(&args.target, &args.rpc_url, (args.block > 0).then_some(args.block))
+                .await?;
+    } else if args.block > 0 {
+        logger.error("argument '--block' requires the target to be a contract address.");
+        std::process::exit(1);
+    } else if BYTECODE_REGEX.is_match(&args.target)? {
+        contract_bytecode = args.target.clone().replacen("0x", "", 1);
+    } else {
+        // We are reading a file, so we need to read the bytecode from the file.
+        contract_bytecode = match fs::read_to_string(&args.target) {
+            Ok(contents) => {
+                let _contents = contents.replace('\n', "");
+                if BYTECODE_REGEX.is_match(&_contents)? && _contents.len() % 2 == 0 {
+                    _contents.replacen("0x", "", 1)
+                } else {
+                    logger
+                        .error(&format!("file '{}' doesn't contain valid bytecode.", &args.target));
+                    std::process::exit(1)
+                }
+            }
+            Err(_) => {
+                logger.error(&format!("failed to open file '{}' .", &args.target));
+                std::process::exit(1)
+            }
+        };
+    }
+
+    let target_vm = match select_target_vm(&args.vm, &args.target, &args.rpc_url).await {
+        Ok(target_vm) => target_vm,
+        Err(error) => {
+            logger.error(&format!("{error}."));
+            std::process::exit(1)
+        }
+    };
+    if target_vm == TargetVm::EraVm {
+        return eravm_abi(&decode_hex(&contract_bytecode)?, &args, &logger).await
+    }
+
+    let disassembled_bytecode = disassemble(DisassemblerArgs {
+        target: contract_bytecode.clone(),
+        verbose: args.verbose.clone(),
+        rpc_url: args.rpc_url.clone(),
+        decimal_counter: false,
+        annotate: false,
+        vm: String::new(),
+        diff: String::new(),
+    })
+    .await?;
+    let bytecode = decode_hex(&contract_bytecode)?;
+
+    let evm = VM::new(
+        contract_bytecode.clone(),
+        String::from("0x"),
+        String::from("0x6865696d64616c6c000000000061646472657373"),
+        String::from("0x6865696d64616c6c0000000000006f726967696e"),
+        String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+        0,
+        u128::MAX,
+    );
+
+    // find the contract's functions, and observe each of them with a single concrete call
+    let selectors = find_function_selectors(&evm, &disassembled_bytecode);
+    let rejects_call_value = evm.rejects_call_value();
+    logger.info(&format!("found {} possible function selectors.", selectors.len()));
+
+    let mut selectors = selectors.into_iter().collect::<Vec<_>>();
+    selectors.sort();
+    let observations = selectors
+        .iter()
+        .map(|(selector, _)| (selector.clone(), observe_call(&evm, selector)))
+        .collect::<BTreeMap<_, _>>();
+
+    // candidate events are the topics which were emitted, or pushed as 32-byte constants. since
+    // most 32-byte constants aren't events, only those which resolve are kept
+    let emitted_events = observations
+        .values()
+        .flat_map(|observation| observation.events.clone())
+        .collect::<BTreeSet<_>>();
+    let pushed_events = pushed_constants(&bytecode, 0x7f)
+        .into_iter()
+        .filter(|value| {
+            value.bits() > 160 && value.overflowing_add(1.into()).0 & *value != U256::zero()
+        })
+        .collect::<BTreeSet<_>>();
+
+    // similarly, candidate errors are the selectors which were reverted with, or pushed as
+    // 4-byte constants other than function selectors
+    let reverted_errors =
+        observations.values().filter_map(|observation| observation.error).collect::<BTreeSet<_>>();
+    let pushed_errors = pushed_constants(&bytecode, 0x63)
+        .into_iter()
+        .map(|value| value.as_u32())
+        .filter(|value| {
+            *value != u32::MAX &&
+                !BUILTIN_ERRORS.contains(value) &&
+                !observations.contains_key(&format!("{value:08x}"))
+        })
+        .collect::<BTreeSet<_>>();
+
+    let (resolved_functions, resolved_events, resolved_errors) = match args.skip_resolving {
+        true => (BTreeMap::new(), BTreeMap::new(), BTreeMap::new()),
+        false => (
+            resolve_best::<ResolvedFunction>(observations.keys().cloned().collect(), |function| {
+                &function.signature
+            })
+            .await,
+            resolve_best::<ResolvedLog>(
+                emitted_events.union(&pushed_events).map(|topic| format!("{topic:064x}")).collect(),
+                |event| &event.signature,
+            )
+            .await,
+            resolve_best::<ResolvedError>(
+                reverted_errors.union(&pushed_errors).map(|error| format!("{error:08x}")).collect(),
+                |error| &error.signature,
+            )
+            .await,
+        ),
+    };
+
+    let mut abi = Vec::new();
+    for (selector, entry_point) in &selectors {
+        let observation = &observations[selector];
+        let state_mutability = guess_mutability(observation, rejects_call_value, || {
+            checks_call_value(&evm, selector, *entry_point)
+        });
+
+        let (name, inputs) = match resolved_functions.get(selector) {
+            Some(function) => (function.name.clone(), abi_tokens(&function.inputs)),
+            None => (format!("Unresolved_{selector}"), Vec::new()),
+        };
+        abi.push(ABIStructure::Function(FunctionABI {
+            type_: String::from("function"),
+            constant: state_mutability == "pure" && inputs.is_empty(),
+            name,
+            inputs,
+            outputs: Vec::new(),
+            state_mutability: state_mutability.to_string(),
+        }));
+    }
+
+    // unresolved errors and events are only included if they were observed
+    for error in reverted_errors.union(&pushed_errors) {
+        let selector = format!("{error:08x}");
+        let (name, inputs) = match resolved_errors.get(&selector) {
+            Some(resolved) => (resolved.name.clone(), abi_tokens(&resolved.inputs)),
+            None if reverted_errors.contains(error) => {
+                (format!("CustomError_{selector}"), Vec::new())
+            }
+            None => continue,
+        };
+        abi.push(ABIStructure::Error(ErrorABI { type_: String::from("error"), name, inputs }));
+    }
+    for topic in emitted_events.union(&pushed_events) {
+        let selector = format!("{topic:064x}");
+        let (name, inputs) = match resolved_events.get(&selector) {
+            Some(resolved) => (resolved.name.clone(), abi_tokens(&resolved.inputs)),
+            None if emitted_events.contains(topic) => {
+                (format!("Event_{}", &selector[0..8]), Vec::new())
+            }
+            None => continue,
+        };
+        abi.push(ABIStructure::Event(EventABI { type_: String::from("event"), name, inputs }));
+    }
+
+    logger.success(&format!(
+        "extracted an ABI with {} functions, {} errors, and {} events.",
+        selectors.len(),
+        abi.iter().filter(|x| matches!(x, ABIStructure::Error(_))).count(),
+        abi.iter().filter(|x| matches!(x, ABIStructure::Event(_))).count(),
+    ));
+
+    Ok(AbiResult { abi })
+}
@@ -0,0 +1,426 @@
+pub mod targets;
+
+use std::{
+    collections::HashMap,
+    fs,
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Instant,
+};
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    constants::ADDRESS_REGEX,
+    ether::{fingerprint::Fingerprint, rpc::parse_chain_rpc},
+    utils::io::logging::Logger,
+};
+
+use crate::{
+    bench::{dispatcher_selectors, get_target_bytecode},
+    decompile::{decompile, out::abi::ABIStructure, DecompilerArgsBuilder},
+    similar::index::{add_to_index, index_name, index_path},
+    snapshot::{snapshot, SnapshotArgsBuilder},
+};
+
+use self::targets::{parse_targets, BatchTarget};
+
+/// The operations which can be run over each target.
+const OPERATIONS: [&str; 3] = ["snapshot", "decompile", "selectors"];
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Run snapshot, decompile, or selectors over many targets",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall batch <TARGETS> [OPTIONS]"
+)]
+pub struct BatchArgs {
+    /// A file containing one target per line, either a contract address, bytecode, or a path to a
+    /// file containing bytecode. Each may be prefixed by the name of a configured chain, i.e.
+    /// `base:0x...`, to fetch it from that chain rather than the default RPC provider.
+    #[clap(required = true)]
+    pub targets: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching the bytecode of targets without a chain prefix.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The operation to run over each target, either `snapshot`, `decompile`, or `selectors`.
+    #[clap(long, short, default_value = "snapshot")]
+    pub operation: String,
+
+    /// The number of targets to analyze at once.
+    #[clap(long, short, default_value = "4")]
+    pub jobs: usize,
+
+    /// Whether to skip resolving function selectors.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+
+    /// Whether to add each target's fingerprint to the similarity index, so `heimdall similar`
+    /// can find them later.
+    #[clap(long = "add-to-index")]
+    pub add_to_index: bool,
+
+    /// The maximum memory, in megabytes, that symbolic execution of each target may use.
+    #[clap(long = "memory-limit", default_value = "0", hide_default_value = true)]
+    pub memory_limit: usize,
+
+    /// The maximum number of branches symbolic execution may explore in a single path.
+    #[clap(long = "max-branch-depth", default_value = "0", hide_default_value = true)]
+    pub max_branch_depth: usize,
+
+    /// The maximum depth of nested internal calls symbolic execution may follow.
+    #[clap(long = "max-call-depth", default_value = "0", hide_default_value = true)]
+    pub max_call_depth: u32,
+
+    /// The maximum number of times symbolic execution may revisit a loop.
+    #[clap(long = "max-loop-iterations", default_value = "0", hide_default_value = true)]
+    pub max_loop_iterations: usize,
+
+    /// The configured chains, each in the `name=rpc_url` format. From the CLI, this is set by the
+    /// configured `chains`.
+    #[clap(skip)]
+    pub chain_rpc_urls: Vec<String>,
+}
+
+impl BatchArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            targets: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            operation: Some(String::from("snapshot")),
+            jobs: Some(4),
+            skip_resolving: Some(false),
+            add_to_index: Some(false),
+            memory_limit: Some(0),
+            max_branch_depth: Some(0),
+            max_call_depth: Some(0),
+            max_loop_iterations: Some(0),
+            chain_rpc_urls: Some(Vec::new()),
+        }
+    }
+}
+
+/// The outcome of running the operation on a single target.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BatchResult {
+    /// The target, as given in the targets file.
+    pub target: String,
+    pub chain: Option<String>,
+
+    /// The size of the target's bytecode, in bytes.
+    pub bytecode_size: usize,
+
+    /// The earlier target with identical bytecode, whose result this one reuses, if any.
+    pub duplicate_of: Option<String>,
+
+    /// The selectors found in the contract's dispatcher, sorted, and the number of functions the
+    /// operation recovered.
+    pub selectors: Vec<String>,
+    pub functions: usize,
+
+    /// The warnings raised by the operation, such as honeypot tricks found by `snapshot`, or the
+    /// findings of analyzer plugins for `decompile`.
+    pub warnings: Vec<String>,
+
+    /// How long the operation took, in milliseconds, which is zero for duplicates.
+    pub duration_ms: u128,
+
+    /// The error encountered while fetching or analyzing the target, if any.
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    /// The target, prefixed by its chain, i.e. `base:0x...`.
+    pub fn name(&self) -> String {
+        match &self.chain {
+            Some(chain) => format!("{chain}:{}", self.target),
+            None => self.target.clone(),
+        }
+    }
+}
+
+/// Run the future built for each index up to `count` on a pool of `jobs` threads, each with its
+/// own runtime, so symbolic execution of several targets can run at once. Results are returned
+/// in the order of their indices.
+fn run_parallel<R, F, Fut>(count: usize, jobs: usize, f: F) -> Vec<R>
+where
+    R: Send,
+    F: Fn(usize) -> Fut + Sync,
+    Fut: Future<Output = R>, {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(count));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, count.max(1)) {
+            scope.spawn(|| {
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                    .expect("failed to build a runtime for the batch worker");
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= count {
+                        break
+                    }
+                    let result = runtime.block_on(f(index));
+                    results.lock().expect("a batch worker panicked").push((index, result));
+                }
+            });
+        }
+    });
+
+    let mut results = results.into_inner().expect("a batch worker panicked");
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Run the operation on the target, whose RPC provider is used to fetch it if it's an address,
+/// returning the number of functions it recovered and any warnings it raised.
+async fn run_operation(
+    args: &BatchArgs,
+    target: &str,
+    rpc_url: &str,
+    selectors: &[String],
+) -> Result<(usize, Vec<String>), Box<dyn std::error::Error>> {
+    match args.operation.as_str() {
+        "snapshot" => {
+            let result = snapshot(
+                SnapshotArgsBuilder::new()
+                    .target(target.to_string())
+                    .rpc_url(rpc_url.to_string())
+                    .skip_resolving(args.skip_resolving)
+                    .memory_limit(args.memory_limit)
+                    .max_branch_depth(args.max_branch_depth)
+                    .max_call_depth(args.max_call_depth)
+                    .max_loop_iterations(args.max_loop_iterations)
+                    .build()?,
+            )
+            .await?;
+            let mut warnings = result.dispatcher_anomalies.warnings();
+            warnings.extend(result.honeypot.warnings());
+            Ok((result.snapshots.len(), warnings))
+        }
+        "decompile" => {
+            let result = decompile(
+                DecompilerArgsBuilder::new()
+                    .target(target.to_string())
+                    .rpc_url(rpc_url.to_string())
+                    .skip_resolving(args.skip_resolving)
+                    .non_interactive(true)
+                    .include_solidity(true)
+                    .memory_limit(args.memory_limit)
+                    .max_branch_depth(args.max_branch_depth)
+                    .max_call_depth(args.max_call_depth)
+                    .max_loop_iterations(args.max_loop_iterations)
+                    .build()?,
+            )
+            .await?;
+            let functions = result
+                .abi
+                .unwrap_or_default()
+                .iter()
+                .filter(|structure| {
+                    matches!(structure, ABIStructure::Function(function) if function.type_ == "function")
+                })
+                .count();
+            let warnings = result
+                .findings
+                .iter()
+                .map(|finding| format!("{}: {}", finding.plugin, finding.title))
+                .collect();
+            Ok((functions, warnings))
+        }
+        _ => Ok((selectors.len(), Vec::new())),
+    }
+}
+
+/// The entrypoint for the batch module. This fetches every target in the targets file, then runs
+/// the operation over each distinct bytecode once, with up to `jobs` targets analyzed at once.
+/// Targets whose bytecode is identical to an earlier target's reuse its result, which is common
+/// when a deployer redeploys the same contract many times.
+pub async fn batch(args: BatchArgs) -> Result<Vec<BatchResult>, Box<dyn std::error::Error>> {
+    let now = Instant::now();
+
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !OPERATIONS.contains(&args.operation.as_str()) {
+        logger.error(&format!(
+            "unknown operation '{}', expected one of: {} .",
+            args.operation,
+            OPERATIONS.join(", ")
+        ));
+        std::process::exit(1)
+    }
+    let targets = match fs::read_to_string(&args.targets) {
+        Ok(contents) => parse_targets(&contents),
+        Err(e) => {
+            logger.error(&format!("failed to open targets file '{}': {e}", &args.targets));
+            std::process::exit(1)
+        }
+    };
+    logger.info(&format!("running {} over {} targets.", args.operation, targets.len()));
+
+    // resolve each target's RPC provider, either the default, or that of its chain
+    let configured = args
+        .chain_rpc_urls
+        .iter()
+        .filter_map(|chain| parse_chain_rpc(chain).ok())
+        .collect::<HashMap<String, String>>();
+    let rpc_url = |target: &BatchTarget| {
+        match &target.chain {
+        Some(chain) => configured.get(chain).cloned().ok_or_else(|| {
+            format!("chain '{chain}' isn't configured. use `heimdall config chains '{chain}=<rpc_url>'` to add it")
+        }),
+        None => Ok(args.rpc_url.clone()),
+    }
+    };
+
+    // fetch every target's bytecode and selectors, which is mostly waiting on the RPC provider
+    let fetched = run_parallel(targets.len(), args.jobs, |index| {
+        let (target, rpc_url) = (&targets[index], rpc_url(&targets[index]));
+        async move {
+            let rpc_url = rpc_url?;
+            let bytecode = get_target_bytecode(&target.target, &rpc_url).await?;
+            let selectors = dispatcher_selectors(&bytecode).await.map_err(|e| e.to_string())?;
+            Ok::<_, String>((rpc_url, bytecode, selectors))
+        }
+    });
+
+    // only the first target with each bytecode is analyzed, and the rest reuse its result
+    let mut results = Vec::new();
+    let mut first_with_bytecode: HashMap<&str, usize> = HashMap::new();
+    let mut analyzed = Vec::new();
+    for (index, (target, fetched)) in targets.iter().zip(&fetched).enumerate() {
+        let mut result = BatchResult {
+            target: target.target.clone(),
+            chain: target.chain.clone(),
+            ..Default::default()
+        };
+        match fetched {
+            Ok((_, bytecode, selectors)) => {
+                result.bytecode_size = bytecode.len() / 2;
+                result.selectors = selectors.clone();
+                match first_with_bytecode.get(bytecode.as_str()) {
+                    Some(first) => result.duplicate_of = Some(targets[*first].target.clone()),
+                    None => {
+                        first_with_bytecode.insert(bytecode.as_str(), index);
+                        analyzed.push(index);
+                    }
+                }
+            }
+            Err(e) => {
+                logger.warn(&format!("skipping '{}': {e}", result.name()));
+                result.error = Some(e.clone());
+            }
+        }
+        results.push(result);
+    }
+    logger.info(&format!(
+        "fetched {} targets, of which {} have distinct bytecode.",
+        targets.len(),
+        analyzed.len()
+    ));
+
+    if args.add_to_index {
+        let fingerprints = targets
+            .iter()
+            .zip(&fetched)
+            .filter_map(|(target, fetched)| {
+                let (_, bytecode, selectors) = fetched.as_ref().ok()?;
+                Some((
+                    index_name(&target.target, bytecode),
+                    Fingerprint::new(bytecode, selectors.clone()),
+                ))
+            })
+            .collect::<Vec<_>>();
+        let path = index_path("")?;
+        logger.info(&format!(
+            "adding {} fingerprints to the index at '{path}' .",
+            fingerprints.len()
+        ));
+        add_to_index(&path, fingerprints)?;
+    }
+
+    let outcomes = run_parallel(analyzed.len(), args.jobs, |index| {
+        let (args, target, fetched) = (&args, &targets[analyzed[index]], &fetched[analyzed[index]]);
+        async move {
+            let (rpc_url, bytecode, selectors) = fetched.as_ref().expect("only fetched targets");
+
+            // addresses are analyzed by address, so proxies and metadata can be resolved, while
+            // the bytecode of other targets was already read
+            let analyzed_target = match ADDRESS_REGEX.is_match(&target.target).unwrap_or(false) {
+                true => target.target.clone(),
+                false => bytecode.clone(),
+            };
+            let start_time = Instant::now();
+            let outcome = run_operation(args, &analyzed_target, rpc_url, selectors)
+                .await
+                .map_err(|e| e.to_string());
+            (outcome, start_time.elapsed().as_millis())
+        }
+    });
+    let outcomes = analyzed.iter().copied().zip(outcomes).collect::<HashMap<_, _>>();
+
+    // duplicates reuse the outcome of the first target with their bytecode
+    for index in 0..results.len() {
+        let first = match &results[index].duplicate_of {
+            Some(_) => {
+                let bytecode = fetched[index].as_ref().map(|(_, bytecode, _)| bytecode.as_str());
+                first_with_bytecode[bytecode.unwrap_or_default()]
+            }
+            None => index,
+        };
+        let ((outcome, duration_ms), result) = match outcomes.get(&first) {
+            Some(outcome) => (outcome, &mut results[index]),
+            None => continue,
+        };
+        match outcome {
+            Ok((functions, warnings)) => {
+                result.functions = *functions;
+                result.warnings = warnings.clone();
+            }
+            Err(e) => {
+                logger.warn(&format!("failed to {} '{}': {e}", args.operation, result.name()));
+                result.error = Some(e.clone());
+            }
+        }
+        if first == index {
+            result.duration_ms = *duration_ms;
+        }
+    }
+
+    logger.info(&format!(
+        "ran {} over {} targets, {} of which failed.",
+        args.operation,
+        results.len(),
+        results.iter().filter(|result| result.error.is_some()).count()
+    ));
+    logger.debug(&format!("batch completed in {} ms.", now.elapsed().as_millis()));
+
+    Ok(results)
+}
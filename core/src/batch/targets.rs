@@ -0,0 +1,43 @@
+use crate::bench::corpus::parse_corpus;
+
+/// A target to analyze, along with the chain it's deployed on, if one was given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchTarget {
+    /// The name of a configured chain, or `None` to use the default RPC provider.
+    pub chain: Option<String>,
+    pub target: String,
+}
+
+/// Parse a targets file, which contains one target per line, each optionally prefixed by the
+/// name of the chain it's deployed on, i.e. `base:0x...`. Targets may be contract addresses,
+/// bytecode, or paths to files containing bytecode. Blank lines and `#` comments are ignored.
+///
+/// ```
+/// use heimdall_core::batch::targets::{parse_targets, BatchTarget};
+///
+/// let targets = parse_targets("# tokens\nbase:0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\n6001600055\n");
+/// assert_eq!(
+///     targets,
+///     vec![
+///         BatchTarget {
+///             chain: Some(String::from("base")),
+///             target: String::from("0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+///         },
+///         BatchTarget { chain: None, target: String::from("6001600055") },
+///     ]
+/// );
+/// ```
+pub fn parse_targets(contents: &str) -> Vec<BatchTarget> {
+    parse_corpus(contents)
+        .into_iter()
+        .map(|line| match line.split_once(':') {
+            Some((chain, target))
+                if !chain.is_empty() &&
+                    chain.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                BatchTarget { chain: Some(chain.to_string()), target: target.trim().to_string() }
+            }
+            _ => BatchTarget { chain: None, target: line },
+        })
+        .collect()
+}
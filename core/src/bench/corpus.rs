@@ -0,0 +1,31 @@
+/// The built-in benchmark corpus, used when no corpus file is given. Contains a small contract
+/// which can be benchmarked offline, as well as a range of deployed contracts, which require an
+/// RPC provider.
+pub const BUILTIN_CORPUS: [&str; 4] = [
+    // dispatches 0x12345678, an empty calldata receive, and a non-payable fallback
+    "60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500",
+    // a simple contract which calls the ecrecover precompile
+    "0x1bf797219482a29013d804ad96d1c6f84fba4c45",
+    // WETH9
+    "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2",
+    // a large, complex contract
+    "0xE90d8Fb7B79C8930B5C8891e61c298b412a6e81a",
+];
+
+/// Parse a corpus file, which contains one target per line. Targets may be contract addresses,
+/// bytecode, or paths to files containing bytecode. Blank lines and `#` comments are ignored.
+///
+/// ```
+/// use heimdall_core::bench::corpus::parse_corpus;
+///
+/// let corpus = parse_corpus("# tokens\n0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2 # WETH\n\n6001600055\n");
+/// assert_eq!(corpus, vec!["0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "6001600055"]);
+/// ```
+pub fn parse_corpus(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
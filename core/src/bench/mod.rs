@@ -0,0 +1,310 @@
+pub mod corpus;
+
+use std::{fs, time::Instant};
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    constants::{ADDRESS_REGEX, BYTECODE_REGEX},
+    ether::{evm::core::vm::VM, rpc::get_code, selectors::find_function_selectors},
+    utils::io::logging::Logger,
+};
+
+use crate::{
+    decompile::{decompile, out::abi::ABIStructure, DecompilerArgsBuilder},
+    disassemble::{disassemble, DisassemblerArgs},
+    snapshot::{snapshot, SnapshotArgsBuilder},
+};
+
+use self::corpus::{parse_corpus, BUILTIN_CORPUS};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Benchmark decompile and snapshot over a corpus of contracts",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall bench [CORPUS] [OPTIONS]"
+)]
+pub struct BenchArgs {
+    /// A file containing one target per line, either a contract address, bytecode, or a path to a
+    /// file containing bytecode. Defaults to the built-in corpus.
+    #[clap(default_value = "", hide_default_value = true)]
+    pub corpus: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching target bytecode.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// A comma-separated list of the modules to benchmark.
+    #[clap(long, default_value = "decompile,snapshot")]
+    pub modules: String,
+
+    /// The number of times to run each module on each contract.
+    #[clap(long, default_value = "1")]
+    pub runs: usize,
+}
+
+impl BenchArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            corpus: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            modules: Some(String::from("decompile,snapshot")),
+            runs: Some(1),
+        }
+    }
+}
+
+/// The metrics gathered by benchmarking a single module on a single contract.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BenchResult {
+    pub target: String,
+    pub module: String,
+
+    /// The size of the contract's bytecode, in bytes.
+    pub bytecode_size: usize,
+
+    /// The mean, minimum, and maximum run times, in milliseconds.
+    pub mean_ms: u128,
+    pub min_ms: u128,
+    pub max_ms: u128,
+
+    /// The peak resident memory of the process during the runs, in kilobytes. Only available on
+    /// Linux.
+    pub peak_memory_kb: Option<u64>,
+
+    /// The number of selectors found in the contract's dispatcher, and the number of functions
+    /// the module recovered.
+    pub selectors: usize,
+    pub functions: usize,
+
+    /// The error encountered while benchmarking, if any.
+    pub error: Option<String>,
+}
+
+impl BenchResult {
+    /// The ratio of recovered functions to dispatched selectors, from 0 to 1. A contract without
+    /// any selectors is fully covered.
+    pub fn coverage(&self) -> f64 {
+        match self.selectors {
+            0 => 1.0,
+            selectors => (self.functions as f64 / selectors as f64).min(1.0),
+        }
+    }
+}
+
+/// Reset the process's peak resident memory, so the next read reflects only what follows.
+fn reset_peak_memory() {
+    let _ = fs::write("/proc/self/clear_refs", "5");
+}
+
+/// Read the process's peak resident memory, in kilobytes.
+fn peak_memory_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status.lines().find(|line| line.starts_with("VmHWM:"))?.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// Get the bytecode of a corpus target, which may be a contract address, bytecode, or a path to
+/// a file containing bytecode.
+pub(crate) async fn get_target_bytecode(target: &str, rpc_url: &str) -> Result<String, String> {
+    if ADDRESS_REGEX.is_match(target).unwrap_or(false) {
+        if rpc_url.is_empty() {
+            return Err(String::from("an RPC URL is required to fetch the bytecode of addresses"))
+        }
+        get_code(target, rpc_url).await.map_err(|e| e.to_string())
+    } else if BYTECODE_REGEX.is_match(target).unwrap_or(false) {
+        Ok(target.replacen("0x", "", 1))
+    } else {
+        let contents = fs::read_to_string(target)
+            .map_err(|e| format!("failed to open file '{target}': {e}"))?
+            .replace('\n', "");
+        match BYTECODE_REGEX.is_match(&contents).unwrap_or(false) && contents.len() % 2 == 0 {
+            true => Ok(contents.replacen("0x", "", 1)),
+            false => Err(format!("file '{target}' doesn't contain valid bytecode")),
+        }
+    }
+}
+
+/// Find the selectors in the contract's dispatcher, sorted.
+pub(crate) async fn dispatcher_selectors(
+    bytecode: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let assembly = disassemble(DisassemblerArgs {
+        target: bytecode.to_string(),
+        verbose: clap_verbosity_flag::Verbosity::new(0, 1),
+        rpc_url: String::new(),
+        decimal_counter: false,
+        annotate: false,
+        vm: String::new(),
+        diff: String::new(),
+    })
+    .await?;
+    let evm = VM::new(
+        bytecode.to_string(),
+        String::from("0x"),
+        String::from("0x6865696d64616c6c000000000061646472657373"),
+        String::from("0x6865696d64616c6c0000000000006f726967696e"),
+        String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+        0,
+        u128::MAX,
+    );
+
+    let mut selectors = find_function_selectors(&evm, &assembly).into_keys().collect::<Vec<_>>();
+    selectors.sort();
+    Ok(selectors)
+}
+
+/// Run a module on the bytecode once, returning the number of functions it recovered.
+async fn run_module(module: &str, bytecode: &str) -> Result<usize, Box<dyn std::error::Error>> {
+    match module {
+        "decompile" => {
+            let result = decompile(
+                DecompilerArgsBuilder::new()
+                    .target(bytecode.to_string())
+                    .skip_resolving(true)
+                    .non_interactive(true)
+                    .include_solidity(true)
+                    .build()?,
+            )
+            .await?;
+            Ok(result
+                .abi
+                .unwrap_or_default()
+                .iter()
+                .filter(|structure| {
+                    matches!(structure, ABIStructure::Function(function) if function.type_ == "function")
+                })
+                .count())
+        }
+        "snapshot" => {
+            let result = snapshot(
+                SnapshotArgsBuilder::new()
+                    .target(bytecode.to_string())
+                    .skip_resolving(true)
+                    .build()?,
+            )
+            .await?;
+            Ok(result.snapshots.len())
+        }
+        module => Err(format!("unknown module '{module}'").into()),
+    }
+}
+
+/// The entrypoint for the bench module. This will run each module over every contract in the
+/// corpus, measuring run time, peak memory, and how many of the contract's selectors the module
+/// recovered, so performance regressions and heuristic improvements can be measured.
+pub async fn bench(args: BenchArgs) -> Result<Vec<BenchResult>, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    // read the corpus, falling back to the built-in corpus
+    let corpus = match args.corpus.is_empty() {
+        true => BUILTIN_CORPUS.iter().map(|target| target.to_string()).collect(),
+        false => match fs::read_to_string(&args.corpus) {
+            Ok(contents) => parse_corpus(&contents),
+            Err(e) => {
+                logger.error(&format!("failed to open corpus '{}': {e}", &args.corpus));
+                std::process::exit(1)
+            }
+        },
+    };
+
+    let modules = args.modules.split(',').map(|module| module.trim()).collect::<Vec<&str>>();
+    if let Some(module) = modules.iter().find(|module| !["decompile", "snapshot"].contains(module))
+    {
+        logger.error(&format!("unknown module '{module}', expected 'decompile' or 'snapshot'."));
+        std::process::exit(1)
+    }
+    if args.runs == 0 {
+        logger.error("the number of runs must be at least 1.");
+        std::process::exit(1)
+    }
+
+    let mut results = Vec::new();
+    for target in corpus {
+        logger.info(&format!("benchmarking '{target}' ."));
+
+        let bytecode = match get_target_bytecode(&target, &args.rpc_url).await {
+            Ok(bytecode) => bytecode,
+            Err(e) => {
+                logger.warn(&format!("skipping '{target}': {e}"));
+                results.extend(modules.iter().map(|module| BenchResult {
+                    target: target.clone(),
+                    module: module.to_string(),
+                    error: Some(e.clone()),
+                    ..Default::default()
+                }));
+                continue
+            }
+        };
+        let selectors = dispatcher_selectors(&bytecode).await?.len();
+
+        for module in &modules {
+            let mut result = BenchResult {
+                target: target.clone(),
+                module: module.to_string(),
+                bytecode_size: bytecode.len() / 2,
+                min_ms: u128::MAX,
+                selectors,
+                ..Default::default()
+            };
+
+            reset_peak_memory();
+            let mut total_ms = 0;
+            for _ in 0..args.runs {
+                let start_time = Instant::now();
+                let functions = run_module(module, &bytecode).await;
+                let elapsed = start_time.elapsed().as_millis();
+
+                match functions {
+                    Ok(functions) => result.functions = functions,
+                    Err(e) => {
+                        logger.warn(&format!("failed to {module} '{target}': {e}"));
+                        result.error = Some(e.to_string());
+                        break
+                    }
+                }
+                total_ms += elapsed;
+                result.min_ms = result.min_ms.min(elapsed);
+                result.max_ms = result.max_ms.max(elapsed);
+            }
+            result.mean_ms = total_ms / args.runs as u128;
+            result.peak_memory_kb = peak_memory_kb();
+            if result.error.is_some() {
+                result.min_ms = 0;
+            }
+
+            logger.info(&format!(
+                "{module}: {}ms mean ({}ms - {}ms), {} functions from {} selectors ({:.2}% coverage).",
+                result.mean_ms,
+                result.min_ms,
+                result.max_ms,
+                result.functions,
+                result.selectors,
+                result.coverage() * 100.0
+            ));
+            results.push(result);
+        }
+    }
+
+    Ok(results)
+}
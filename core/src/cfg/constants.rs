@@ -0,0 +1,37 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::cfg::structures::state::State;
+
+lazy_static! {
+    /// global state for the cfg TUI
+    pub static ref STATE: Mutex<State> = Mutex::new(State::new());
+
+    /// constant about text
+    pub static ref ABOUT_TEXT: Vec<String> = vec![
+        format!("heimdall-rs v{}", env!("CARGO_PKG_VERSION")),
+        "By Jonathan Becker <jonathan@jbecker.dev>".to_string(),
+        "The cfg module allows users to explore the control flow graph of a contract's bytecode, following jumps between blocks and viewing each block's decompiled logic.".to_string(),
+    ];
+
+    /// constant help menu text
+    pub static ref HELP_MENU_COMMANDS: Vec<String> = vec![
+        ":q, :quit                              exit the program".to_string(),
+        ":h, :help                              display this help menu".to_string(),
+        ":f, :find <OPCODE|PC>                  select the next block containing the opcode or pc".to_string(),
+        ":g, :goto <PC>                         select the block containing the pc".to_string(),
+        ":d, :decompile                         toggle the decompiled view of the selected block".to_string(),
+    ];
+
+    /// constant help menu text
+    pub static ref HELP_MENU_CONTROLS: Vec<String> = vec![
+        "↑, Scroll Up                           move the cursor up".to_string(),
+        "↓, Scroll Down                         move the cursor down".to_string(),
+        "←, →                                   switch scrolling context between block list and block information".to_string(),
+        "1-9                                    follow the nth edge of the selected block".to_string(),
+        "Backspace                              return to the previously selected block".to_string(),
+        "d                                      toggle the decompiled view of the selected block".to_string(),
+        "ESC                                    return to the main view".to_string(),
+    ];
+}
@@ -1,7 +1,16 @@
-use std::{collections::HashMap, sync::Mutex};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    sync::Mutex,
+};
 
 use ethers::prelude::U256;
-use heimdall_common::{ether::evm::ext::exec::VMTrace, utils::strings::encode_hex_reduced};
+use heimdall_common::{
+    ether::{
+        eof::{decode_code_section, EofContainer, EofInstruction},
+        evm::{core::vm::Instruction, ext::exec::VMTrace},
+    },
+    utils::strings::{encode_hex, encode_hex_reduced},
+};
 use petgraph::{matrix_graph::NodeIndex, Graph};
 
 use lazy_static::lazy_static;
@@ -96,3 +105,228 @@ pub fn build_cfg(
         );
     }
 }
+
+/// convert the code sections of an EOF container into a [`Graph`] of blocks. Since EOF code can
+/// only jump to the targets encoded in its relative jumps, the graph is found statically rather
+/// than by symbolic execution, starting at the first code section and following its jumps, and
+/// its calls into other code sections. Returns the number of bytes the graph's blocks cover.
+pub fn build_eof_cfg(
+    container: &EofContainer,
+    contract_cfg: &mut Graph<String, String>,
+) -> Result<usize, String> {
+    let sections = (0..container.code_sections.len())
+        .map(|section| decode_code_section(container, section))
+        .collect::<Result<Vec<Vec<EofInstruction>>, String>>()?;
+
+    // blocks start at each section's entry, at each jump target, and after each jump or
+    // terminating instruction
+    let leaders = sections
+        .iter()
+        .map(|instructions| {
+            let mut leaders = BTreeSet::from([0]);
+            for instruction in instructions {
+                let targets = instruction.jump_targets();
+                if !targets.is_empty() || instruction.is_terminating() {
+                    leaders.extend(targets);
+                    leaders.insert(instruction.offset + 1 + instruction.immediate.len());
+                }
+            }
+            leaders
+        })
+        .collect::<Vec<BTreeSet<usize>>>();
+
+    let mut nodes: HashMap<(usize, usize), NodeIndex<u32>> = HashMap::new();
+    let mut edges = Vec::new();
+    let mut reached_bytes = 0;
+    let mut queue = VecDeque::from([(0, 0)]);
+    while let Some((section, start)) = queue.pop_front() {
+        if nodes.contains_key(&(section, start)) {
+            continue
+        }
+
+        let block = sections[section]
+            .iter()
+            .skip_while(|instruction| instruction.offset < start)
+            .take_while(|instruction| {
+                instruction.offset == start || !leaders[section].contains(&instruction.offset)
+            })
+            .collect::<Vec<&EofInstruction>>();
+        let last = match block.last() {
+            Some(last) => *last,
+            None => continue,
+        };
+
+        // instructions are labelled by their offset within the container
+        let mut cfg_node = String::new();
+        for instruction in &block {
+            let operand = match instruction.name.starts_with("PUSH") {
+                true if !instruction.immediate.is_empty() => {
+                    encode_hex_reduced(U256::from_big_endian(&instruction.immediate))
+                }
+                false if !instruction.immediate.is_empty() => {
+                    format!("0x{}", encode_hex(instruction.immediate.clone()))
+                }
+                _ => String::new(),
+            };
+            cfg_node.push_str(&format!(
+                "{} {} {}\n",
+                encode_hex_reduced(U256::from(
+                    container.code_sections[section].offset + instruction.offset
+                )),
+                instruction.name,
+                operand
+            ));
+            reached_bytes += 1 + instruction.immediate.len();
+        }
+        nodes.insert((section, start), contract_cfg.add_node(cfg_node));
+
+        // calls enter the called section at its first instruction
+        let mut successors = block
+            .iter()
+            .filter_map(|instruction| instruction.section_target())
+            .map(|target| ((target, 0), String::from("call")))
+            .collect::<Vec<((usize, usize), String)>>();
+
+        // conditional jumps fall through when not taken, which is their last target
+        let targets = last.jump_targets();
+        let falls_through = targets.is_empty() && !last.is_terminating();
+        let taken = match last.opcode {
+            0xe0 => targets.len(),
+            _ => targets.len().saturating_sub(1),
+        };
+        for (index, target) in targets.into_iter().enumerate() {
+            successors.push(((section, target), (index < taken).to_string()));
+        }
+        if falls_through {
+            successors.push(((section, last.offset + 1 + last.immediate.len()), false.to_string()));
+        }
+
+        for (successor, weight) in successors {
+            queue.push_back(successor);
+            edges.push(((section, start), successor, weight));
+        }
+    }
+
+    let mut connecting_edges = HashSet::new();
+    for (from, to, weight) in edges {
+        if let (Some(from), Some(to)) = (nodes.get(&from), nodes.get(&to)) {
+            if connecting_edges.insert((*from, *to)) {
+                contract_cfg.add_edge(*from, *to, weight);
+            }
+        }
+    }
+
+    Ok(reached_bytes)
+}
+
+/// A block of the control flow graph, as explored in the CFG TUI.
+#[derive(Debug, Clone, Default)]
+pub struct CFGBlock {
+    /// The instruction which starts this block.
+    pub start: u128,
+    /// The block's instructions, as `(instruction, assembly)` pairs.
+    pub assembly: Vec<(u128, String)>,
+    /// The block's statements, lifted to solidity-like pseudocode.
+    pub decompiled: Vec<String>,
+    /// The blocks which this block flows into, and whether the jump is taken to reach them.
+    pub successors: Vec<(u128, bool)>,
+    /// The blocks which flow into this block.
+    pub predecessors: Vec<u128>,
+}
+
+/// Lift a single statement-like instruction into solidity-like pseudocode.
+fn decompile_instruction(instruction: &Instruction) -> Option<String> {
+    let opcode_name = instruction.opcode_details.as_ref()?.name;
+    let inputs = instruction
+        .input_operations
+        .iter()
+        .map(|operation| operation.solidify())
+        .collect::<Vec<String>>();
+
+    Some(match opcode_name {
+        "SSTORE" => format!("storage[{}] = {};", inputs[0], inputs[1]),
+        "MSTORE" | "MSTORE8" => format!("memory[{}] = {};", inputs[0], inputs[1]),
+        "JUMPI" => format!("if ({}) jump {};", inputs[1], inputs[0]),
+        "RETURN" => format!("return memory[{}:{}];", inputs[0], inputs[1]),
+        "REVERT" => format!("revert(memory[{}:{}]);", inputs[0], inputs[1]),
+        "SELFDESTRUCT" => format!("selfdestruct({});", inputs[0]),
+        "STOP" => String::from("stop;"),
+        "INVALID" => String::from("invalid;"),
+        name if name.starts_with("LOG") => {
+            format!("emit {}(memory[{}:{}]);", name, inputs[0], inputs[1..].join(", "))
+        }
+        "CALL" | "CALLCODE" | "DELEGATECALL" | "STATICCALL" | "CREATE" | "CREATE2" => {
+            format!("{}({});", opcode_name.to_lowercase(), inputs.join(", "))
+        }
+        _ => return None,
+    })
+}
+
+/// convert a symbolic execution [`VMTrace`] into [`CFGBlock`]s keyed by their starting
+/// instruction, which can be navigated by following their edges.
+pub fn build_blocks(
+    vm_trace: &VMTrace,
+    blocks: &mut BTreeMap<u128, CFGBlock>,
+    parent_block: Option<u128>,
+    jump_taken: bool,
+) {
+    let start = match vm_trace.operations.first() {
+        Some(operation) => operation.last_instruction.instruction,
+        None => return,
+    };
+
+    // connect this block to its parent
+    if let Some(parent_block) = parent_block {
+        if let Some(parent) = blocks.get_mut(&parent_block) {
+            if !parent.successors.iter().any(|(successor, _)| *successor == start) {
+                parent.successors.push((start, jump_taken));
+            }
+        }
+    }
+
+    // blocks which have already been explored only need the new edge
+    if let Some(block) = blocks.get_mut(&start) {
+        if let Some(parent_block) = parent_block {
+            if !block.predecessors.contains(&parent_block) {
+                block.predecessors.push(parent_block);
+            }
+        }
+        return
+    }
+
+    let mut block =
+        CFGBlock { start, predecessors: parent_block.into_iter().collect(), ..Default::default() };
+    for operation in &vm_trace.operations {
+        let instruction = &operation.last_instruction;
+        let opcode_name = instruction.opcode_details.clone().unwrap().name;
+
+        block.assembly.push((
+            instruction.instruction,
+            format!(
+                "{} {} {}",
+                encode_hex_reduced(U256::from(instruction.instruction)),
+                opcode_name,
+                if opcode_name.contains("PUSH") {
+                    encode_hex_reduced(*instruction.outputs.clone().first().unwrap())
+                } else {
+                    String::from("")
+                }
+            )
+            .trim_end()
+            .to_string(),
+        ));
+        block.decompiled.extend(decompile_instruction(instruction));
+    }
+    blocks.insert(start, block);
+
+    // recurse into the children of the VMTrace map
+    for child in vm_trace.children.iter() {
+        build_blocks(
+            child,
+            blocks,
+            Some(start),
+            child.operations.first().unwrap().last_instruction.opcode_details.clone().unwrap().name ==
+                "JUMPDEST",
+        );
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+
+use crate::decompile::constants::BLOCK_COMMENT_PREFIX;
+
+/// The range of lines, 1-indexed and inclusive, which a block's statements span in a decompiled
+/// source file.
+pub type LineRange = (usize, usize);
+
+/// Find the lines of a decompiled source which each control flow graph block's statements span,
+/// keyed by the instruction which starts the block. The source must have been decompiled with
+/// `--block-comments`, which names the block above each group of statements.
+///
+/// A block's statements run until the next block comment or function, ignoring trailing lines
+/// which only close the enclosing scopes. Blocks shared between functions span multiple ranges.
+pub fn block_source_lines(source: &str) -> BTreeMap<u128, Vec<LineRange>> {
+    let lines = source.lines().map(|line| line.trim()).collect::<Vec<&str>>();
+
+    let mut blocks: BTreeMap<u128, Vec<LineRange>> = BTreeMap::new();
+    for (index, line) in lines.iter().enumerate() {
+        let start = match line
+            .strip_prefix(BLOCK_COMMENT_PREFIX)
+            .and_then(|start| u128::from_str_radix(start.trim_start_matches("0x"), 16).ok())
+        {
+            Some(start) => start,
+            None => continue,
+        };
+
+        let statements = lines[index + 1..]
+            .iter()
+            .take_while(|line| {
+                !line.starts_with(BLOCK_COMMENT_PREFIX) && !line.starts_with("function ")
+            })
+            .collect::<Vec<_>>();
+        let count = statements
+            .iter()
+            .rposition(|line| !line.is_empty() && !line.chars().all(|c| c == '}'))
+            .map(|last| last + 1)
+            .unwrap_or(0);
+
+        // the file's lines are 1-indexed, and the comment's index is its line above the block
+        if count > 0 {
+            blocks.entry(start).or_default().push((index + 2, index + 1 + count));
+        }
+    }
+
+    blocks
+}
+
+/// Format a line range as a location within `decompiled.sol`, e.g. `decompiled.sol:12-15`.
+pub fn format_line_range((start, end): LineRange) -> String {
+    match start == end {
+        true => format!("decompiled.sol:{start}"),
+        false => format!("decompiled.sol:{start}-{end}"),
+    }
+}
@@ -0,0 +1,76 @@
+use ethers::prelude::U256;
+use heimdall_common::utils::strings::encode_hex_reduced;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::Span,
+    widgets::{Block, Borders, Paragraph, Table},
+    Frame,
+};
+
+use crate::cfg::{
+    menus::main::build_block_detail, structures::state::State, util::table::build_rows,
+};
+
+/// Render the TUI command palette
+pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut State) {
+    // creates a new block with the given title
+    // https://github.com/fdehau/tui-rs/blob/master/examples/paragraph.rs
+    let create_block = |title, borders| {
+        Block::default()
+            .borders(borders)
+            .style(Style::default().fg(Color::White))
+            .title(Span::styled(title, Style::default().add_modifier(Modifier::BOLD)))
+    };
+
+    // build main layout
+    let main_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Percentage(100)].as_ref())
+        .split(f.size());
+
+    let sub_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(14), Constraint::Percentage(100)].as_ref())
+        .split(main_layout[1]);
+
+    // add command paragraph input
+    let input_buffer = state.input_buffer.clone();
+    let command_input = Paragraph::new(input_buffer)
+        .style(Style::default().fg(Color::White))
+        .block(Block::default().title(" Command ").borders(Borders::ALL));
+
+    // build rows
+    let rows = build_rows(state, main_layout[1].height as usize - 4);
+
+    // build table
+    let table = Table::new(rows)
+        .block(
+            Block::default()
+                .title(" Blocks ")
+                .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+                .borders(Borders::ALL),
+        )
+        .widths(&[Constraint::Length(12), Constraint::Percentage(100)]);
+
+    // build block info
+    let block_header = format!(
+        " Block {} ",
+        state
+            .blocks
+            .get(state.block_index)
+            .map(|block| encode_hex_reduced(U256::from(block.start)))
+            .unwrap_or_default(),
+    );
+    let block_detail = Paragraph::new(build_block_detail(state))
+        .style(Style::default().fg(Color::White))
+        .block(create_block(block_header, Borders::ALL))
+        .alignment(Alignment::Left)
+        .scroll((state.scroll_index as u16, 0));
+
+    f.render_widget(command_input, main_layout[0]);
+    f.render_widget(table, sub_layout[0]);
+    f.render_widget(block_detail, sub_layout[1]);
+}
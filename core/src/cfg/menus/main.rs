@@ -0,0 +1,154 @@
+use ethers::prelude::U256;
+use heimdall_common::utils::strings::encode_hex_reduced;
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Table, Wrap},
+    Frame,
+};
+
+use crate::cfg::{structures::state::State, util::table::build_rows};
+
+/// Build the detail pane for the selected block, containing its edges and either its assembly or
+/// its decompiled logic.
+pub fn build_block_detail(state: &State) -> Vec<Spans<'static>> {
+    let block = match state.blocks.get(state.block_index) {
+        Some(block) => block,
+        None => return vec![Spans::from(" No blocks found ")],
+    };
+    let bold = Style::default().fg(Color::White).add_modifier(Modifier::BOLD);
+
+    // add the block's incoming edges
+    let mut text = vec![
+        Spans::from(""), // buffer
+        Spans::from(Span::styled(" Predecessors ", bold)),
+        Spans::from(format!(
+            " {}",
+            match block.predecessors.is_empty() {
+                true => String::from("None"),
+                false => block
+                    .predecessors
+                    .iter()
+                    .map(|start| encode_hex_reduced(U256::from(*start)))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            }
+        )),
+    ];
+
+    // add the block's outgoing edges, numbered so they can be followed
+    text.append(&mut vec![Spans::from(""), Spans::from(Span::styled(" Successors ", bold))]);
+    if block.successors.is_empty() {
+        text.push(Spans::from(" None"));
+    }
+    text.append(
+        &mut block
+            .successors
+            .iter()
+            .enumerate()
+            .map(|(index, (start, jump_taken))| {
+                Spans::from(format!(
+                    " [{}] {} ({})",
+                    index + 1,
+                    encode_hex_reduced(U256::from(*start)),
+                    if *jump_taken { "jump" } else { "fallthrough" }
+                ))
+            })
+            .collect::<Vec<_>>(),
+    );
+
+    // add the block's assembly or decompiled logic
+    text.push(Spans::from(""));
+    if state.show_decompiled {
+        text.push(Spans::from(Span::styled(" Decompiled ", bold)));
+        if block.decompiled.is_empty() {
+            text.push(Spans::from(" No statements"));
+        }
+        text.append(
+            &mut block.decompiled.iter().map(|line| Spans::from(format!(" {line}"))).collect(),
+        );
+    } else {
+        text.push(Spans::from(Span::styled(" Assembly ", bold)));
+        text.append(
+            &mut block
+                .assembly
+                .iter()
+                .map(|(_, assembly)| Spans::from(format!(" {assembly}")))
+                .collect(),
+        );
+    }
+
+    text
+}
+
+/// Render the TUI main view
+pub fn render_tui_view_main<B: Backend>(f: &mut Frame<B>, state: &mut State) {
+    // creates a new block with the given title
+    // https://github.com/fdehau/tui-rs/blob/master/examples/paragraph.rs
+    let create_block = |title, borders| {
+        Block::default()
+            .borders(borders)
+            .style(Style::default().fg(Color::White))
+            .title(Span::styled(title, Style::default().add_modifier(Modifier::BOLD)))
+    };
+
+    // build main layout
+    let main_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(1)
+        .constraints([Constraint::Length(3), Constraint::Percentage(100)].as_ref())
+        .split(f.size());
+
+    let sub_layout = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(14), Constraint::Percentage(100)].as_ref())
+        .split(main_layout[1]);
+
+    // about text
+    let header = Paragraph::new(format!(
+        "heimdall-rs v{}{:>space$}",
+        env!("CARGO_PKG_VERSION"),
+        "type :q to exit",
+        space = f.size().width as usize - 20
+    ))
+    .style(Style::default().fg(Color::White))
+    .block(create_block(format!("Control Flow Graph of {}", state.target), Borders::BOTTOM))
+    .alignment(Alignment::Left)
+    .wrap(Wrap { trim: true });
+
+    // build rows
+    let rows = build_rows(state, main_layout[1].height as usize - 4);
+
+    // build table
+    let table = Table::new(rows)
+        .block(
+            Block::default()
+                .title(" Blocks ")
+                .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+                .borders(Borders::ALL),
+        )
+        .widths(&[Constraint::Length(12), Constraint::Percentage(100)]);
+
+    // build block info
+    let block_header = format!(
+        " {}Block {}{} ",
+        if state.scroll { "> " } else { "" },
+        state
+            .blocks
+            .get(state.block_index)
+            .map(|block| encode_hex_reduced(U256::from(block.start)))
+            .unwrap_or_default(),
+        if state.scroll { " <" } else { "" },
+    );
+    let block_detail = Paragraph::new(build_block_detail(state))
+        .style(Style::default().fg(Color::White))
+        .block(create_block(block_header, Borders::ALL))
+        .alignment(Alignment::Left)
+        .scroll((state.scroll_index as u16, 0));
+
+    f.render_widget(header, main_layout[0]);
+    f.render_widget(table, sub_layout[0]);
+    f.render_widget(block_detail, sub_layout[1]);
+}
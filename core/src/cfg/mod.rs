@@ -1,22 +1,36 @@
+pub mod constants;
 pub mod graph;
+pub mod link;
+pub mod menus;
 pub mod output;
+pub mod stats;
+pub mod structures;
+pub mod util;
 use derive_builder::Builder;
 use heimdall_common::ether::{
-    compiler::detect_compiler, rpc::get_code, selectors::find_function_selectors,
+    compiler::detect_compiler,
+    eof::{is_eof, parse_eof},
+    rpc::get_code,
+    selectors::find_function_selectors,
 };
 use indicatif::ProgressBar;
-use std::{fs, time::Duration};
+use std::{collections::BTreeMap, fs, time::Duration};
 
 use clap::{AppSettings, Parser};
 use heimdall_common::{
     constants::{ADDRESS_REGEX, BYTECODE_REGEX},
     ether::evm::core::vm::VM,
-    utils::io::logging::*,
+    utils::{crash::set_phase, io::logging::*, strings::decode_hex},
 };
 use petgraph::Graph;
 
 use crate::{
-    cfg::graph::build_cfg,
+    cfg::{
+        graph::{build_blocks, build_cfg, build_eof_cfg},
+        link::{block_source_lines, format_line_range, LineRange},
+        stats::CFGStats,
+        util::tui,
+    },
     disassemble::{disassemble, DisassemblerArgs},
 };
 
@@ -45,7 +59,8 @@ pub struct CFGArgs {
     pub default: bool,
 
     /// Specify a format (other than dot) to output the CFG in.
-    /// For example, `--format svg` will output a SVG image of the CFG.
+    /// For example, `--format svg` will output a SVG image of the CFG, while `--format json`
+    /// writes the graph as JSON, following its published schema.
     #[clap(long = "format", short, default_value = "", hide_default_value = true)]
     pub format: String,
 
@@ -53,6 +68,11 @@ pub struct CFGArgs {
     /// This is useful for visualizing the flow of if statements.
     #[clap(long = "color-edges", short)]
     pub color_edges: bool,
+
+    /// Open an interactive TUI to explore the CFG, following jumps between blocks, searching for
+    /// opcodes and PCs, and viewing each block's decompiled logic.
+    #[clap(long = "tui")]
+    pub tui: bool,
 }
 
 impl CFGArgsBuilder {
@@ -64,13 +84,59 @@ impl CFGArgsBuilder {
             default: Some(true),
             format: Some(String::new()),
             color_edges: Some(false),
+            tui: Some(false),
+        }
+    }
+}
+
+/// The control flow graph of the target, along with its statistics.
+#[derive(Debug, Clone)]
+pub struct CFGResult {
+    pub graph: Graph<String, String>,
+    pub stats: CFGStats,
+    /// The lines of the target's decompiled source which each node's statements span, keyed by
+    /// the node's index. Empty unless [`CFGResult::link_source`] was called.
+    pub source_lines: BTreeMap<usize, Vec<LineRange>>,
+}
+
+impl CFGResult {
+    /// Link the graph's nodes to the lines of the target's decompiled source, which must have
+    /// been decompiled with `--block-comments`, returning the number of nodes linked.
+    pub fn link_source(&mut self, source: &str) -> usize {
+        let blocks = block_source_lines(source);
+
+        self.source_lines = self
+            .graph
+            .node_indices()
+            .filter_map(|index| {
+                // each node's code starts with the instruction which starts its block
+                let start = self.graph[index].split_whitespace().next()?;
+                let start = u128::from_str_radix(start.trim_start_matches("0x"), 16).ok()?;
+                Some((index.index(), blocks.get(&start)?.clone()))
+            })
+            .collect();
+
+        self.source_lines.len()
+    }
+
+    /// The graph, with the decompiled source lines of each linked node appended to its code.
+    pub fn annotated_graph(&self) -> Graph<String, String> {
+        let mut graph = self.graph.clone();
+        for index in graph.node_indices() {
+            if let Some(ranges) = self.source_lines.get(&index.index()) {
+                for range in ranges {
+                    graph[index].push_str(&format!("{}\n", format_line_range(*range)));
+                }
+            }
         }
+
+        graph
     }
 }
 
 /// The main entry point for the CFG module. Will generate a control flow graph of the target
 /// bytecode, after performing symbolic execution and discovering all possible execution paths.
-pub async fn cfg(args: CFGArgs) -> Result<Graph<String, String>, Box<dyn std::error::Error>> {
+pub async fn cfg(args: CFGArgs) -> Result<CFGResult, Box<dyn std::error::Error>> {
     use std::time::Instant;
     let now = Instant::now();
 
@@ -139,12 +205,47 @@ pub async fn cfg(args: CFGArgs) -> Result<Graph<String, String>, Box<dyn std::er
         };
     }
 
+    // EOF containers can't be symbolically executed, but their jumps are static, so their graph
+    // is built directly from their code sections
+    let bytecode = decode_hex(&contract_bytecode).unwrap_or_default();
+    if is_eof(&bytecode) {
+        set_phase("cfg: building EOF graph");
+        let mut contract_cfg = Graph::<String, String>::new();
+        let reached_bytes = match parse_eof(&bytecode)
+            .and_then(|container| build_eof_cfg(&container, &mut contract_cfg))
+        {
+            Ok(reached_bytes) => reached_bytes,
+            Err(error) => {
+                logger.error(&format!("invalid EOF container: {error}."));
+                std::process::exit(1)
+            }
+        };
+        if args.tui {
+            logger.warn("the CFG TUI doesn't support EOF containers, skipping.");
+        }
+
+        // the instructions' immediates aren't sized as in legacy bytecode
+        let mut stats = CFGStats::new(&contract_cfg, &contract_bytecode);
+        stats.unreachable_bytes = bytecode.len() - reached_bytes;
+        logger.info(&format!(
+            "found {} blocks, {} edges, and {} loops within the EOF container.",
+            stats.nodes, stats.edges, stats.loops
+        ));
+
+        trace.display();
+        return Ok(CFGResult { graph: contract_cfg, stats, source_lines: BTreeMap::new() })
+    }
+
     // disassemble the bytecode
+    set_phase("cfg: disassembling");
     let disassembled_bytecode = disassemble(DisassemblerArgs {
         target: contract_bytecode.clone(),
         verbose: args.verbose.clone(),
         rpc_url: args.rpc_url.clone(),
         decimal_counter: false,
+        annotate: false,
+        vm: String::new(),
+        diff: String::new(),
     })
     .await?;
 
@@ -177,6 +278,7 @@ pub async fn cfg(args: CFGArgs) -> Result<Graph<String, String>, Box<dyn std::er
     }
 
     // create a new EVM instance
+    set_phase("cfg: symbolic execution");
     let evm = VM::new(
         contract_bytecode.clone(),
         String::from("0x"),
@@ -241,7 +343,24 @@ pub async fn cfg(args: CFGArgs) -> Result<Graph<String, String>, Box<dyn std::er
     progress.finish_and_clear();
     logger.info("symbolic execution completed.");
     logger.debug(&format!("Control flow graph generated in {:?}.", now.elapsed()));
+
+    // open the tui
+    if args.tui {
+        let mut blocks = BTreeMap::new();
+        build_blocks(map, &mut blocks, None, false);
+        tui::handle(
+            blocks.into_values().collect(),
+            if args.target.len() > 64 { &shortened_target } else { args.target.as_str() },
+        );
+    }
+
+    let stats = CFGStats::new(&contract_cfg, &contract_bytecode);
+    logger.info(&format!(
+        "found {} blocks, {} edges, and {} loops.",
+        stats.nodes, stats.edges, stats.loops
+    ));
+
     trace.display();
 
-    Ok(contract_cfg)
+    Ok(CFGResult { graph: contract_cfg, stats, source_lines: BTreeMap::new() })
 }
@@ -4,7 +4,7 @@ use heimdall_common::utils::io::{file::write_file, logging::Logger};
 use indicatif::ProgressBar;
 use petgraph::{dot::Dot, graph::Graph};
 
-use super::CFGArgs;
+use super::{stats::CFGStats, CFGArgs};
 
 /// Write the generated CFG to a file in the `dot` graphviz format.
 pub fn write_cfg_to_file(contract_cfg: &Graph<String, String>, args: &CFGArgs, output_dir: String) {
@@ -95,3 +95,17 @@ pub fn write_cfg_to_file(contract_cfg: &Graph<String, String>, args: &CFGArgs, o
 
     progress_bar.finish_and_clear();
 }
+
+/// Write the statistics of the generated CFG to a file as JSON.
+pub fn write_cfg_stats_to_file(stats: &CFGStats, path: &str) {
+    // get a new logger
+    let logger = Logger::default();
+
+    match serde_json::to_string_pretty(stats) {
+        Ok(output) => {
+            write_file(path, &output);
+            logger.success(&format!("wrote CFG statistics to '{path}' ."));
+        }
+        Err(e) => logger.error(&format!("failed to serialize CFG statistics: {e}")),
+    }
+}
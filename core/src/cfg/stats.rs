@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use heimdall_common::utils::strings::decode_hex;
+use petgraph::{algo::tarjan_scc, Graph};
+use serde::Serialize;
+
+/// A quantitative fingerprint of a contract's control flow graph.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CFGStats {
+    /// The number of basic blocks in the graph.
+    pub nodes: usize,
+    /// The number of jumps between basic blocks.
+    pub edges: usize,
+    /// The number of loops, i.e. cycles of blocks which can jump back to themselves.
+    pub loops: usize,
+    /// The size of the bytecode which symbolic execution never reached, in bytes. This includes
+    /// data, such as the metadata trailer.
+    pub unreachable_bytes: usize,
+    /// The number of instructions in the largest basic block.
+    pub largest_block: usize,
+    /// The number of function selectors the dispatcher compares the calldata against.
+    pub dispatcher_fanout: usize,
+}
+
+/// The program counters of every instruction within a block's label, which lists one
+/// `<pc> <opcode> <operand>` instruction per line.
+fn block_pcs(block: &str) -> impl Iterator<Item = usize> + '_ {
+    block.lines().filter_map(|line| {
+        let pc = line.split_whitespace().next()?;
+        usize::from_str_radix(pc.trim_start_matches("0x"), 16).ok()
+    })
+}
+
+impl CFGStats {
+    /// Compute the statistics of the control flow graph built from the given bytecode.
+    ///
+    /// ```
+    /// use heimdall_core::cfg::stats::CFGStats;
+    /// use petgraph::Graph;
+    ///
+    /// let mut graph = Graph::<String, String>::new();
+    /// let entry = graph.add_node(String::from("0 PUSH1 0x03\n0x02 JUMP \n"));
+    /// let body = graph.add_node(String::from("0x03 JUMPDEST \n0x04 PUSH1 0x03\n0x06 JUMP \n"));
+    /// graph.add_edge(entry, body, String::new());
+    /// graph.add_edge(body, body, String::new());
+    ///
+    /// let stats = CFGStats::new(&graph, "6003565b600356fe00");
+    /// assert_eq!((stats.nodes, stats.edges, stats.loops), (2, 2, 1));
+    /// assert_eq!((stats.largest_block, stats.unreachable_bytes), (3, 2));
+    /// ```
+    pub fn new(graph: &Graph<String, String>, bytecode: &str) -> Self {
+        let reached =
+            graph.node_weights().flat_map(|block| block_pcs(block)).collect::<HashSet<usize>>();
+
+        // walk the bytecode instruction by instruction, summing the size of those never reached
+        let bytes = decode_hex(bytecode.trim_start_matches("0x")).unwrap_or_default();
+        let mut unreachable_bytes = 0;
+        let mut pc = 0;
+        while pc < bytes.len() {
+            let size = match bytes[pc] {
+                opcode @ 0x60..=0x7f => 1 + (opcode - 0x5f) as usize,
+                _ => 1,
+            };
+            if !reached.contains(&pc) {
+                unreachable_bytes += size.min(bytes.len() - pc);
+            }
+            pc += size;
+        }
+
+        // a loop is either a cycle of several blocks, or a block which jumps to itself
+        let loops = tarjan_scc(graph)
+            .iter()
+            .filter(|component| {
+                component.len() > 1 || graph.contains_edge(component[0], component[0])
+            })
+            .count();
+
+        // the dispatcher compares the selector against each `PUSH4` constant with `EQ`
+        let dispatcher_fanout = graph
+            .node_weights()
+            .flat_map(|block| {
+                let instructions = block.lines().collect::<Vec<&str>>();
+                instructions
+                    .windows(2)
+                    .filter(|pair| pair[1].split_whitespace().nth(1) == Some("EQ"))
+                    .filter_map(
+                        |pair| match pair[0].split_whitespace().collect::<Vec<&str>>()[..] {
+                            [_, "PUSH4", selector] => Some(selector.to_string()),
+                            _ => None,
+                        },
+                    )
+                    .collect::<Vec<String>>()
+            })
+            .collect::<HashSet<String>>()
+            .len();
+
+        Self {
+            nodes: graph.node_count(),
+            edges: graph.edge_count(),
+            loops,
+            unreachable_bytes,
+            largest_block: graph
+                .node_weights()
+                .map(|block| block.lines().count())
+                .max()
+                .unwrap_or(0),
+            dispatcher_fanout,
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::cfg::{graph::CFGBlock, menus::TUIView};
+
+/// The state of the CFG TUI, which is passed to the TUI renderer as a mutable reference.
+#[derive(Debug, Clone)]
+pub struct State {
+    pub blocks: Vec<CFGBlock>,
+    pub block_index: usize,
+    pub scroll_index: usize,
+    pub view: TUIView,
+    pub input_buffer: String,
+    pub history: Vec<usize>,
+    pub show_decompiled: bool,
+    pub target: String,
+    pub scroll: bool,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            block_index: 0,
+            scroll_index: 0,
+            view: TUIView::Main,
+            input_buffer: String::new(),
+            history: Vec::new(),
+            show_decompiled: false,
+            target: String::new(),
+            scroll: false,
+        }
+    }
+
+    /// Select the block at the given index, remembering the current block so it can be returned
+    /// to with [`State::back`].
+    pub fn select(&mut self, index: usize) {
+        if index != self.block_index {
+            self.history.push(self.block_index);
+        }
+        self.block_index = index;
+        self.scroll_index = 0;
+    }
+
+    /// Return to the previously selected block.
+    pub fn back(&mut self) {
+        if let Some(index) = self.history.pop() {
+            self.block_index = index;
+            self.scroll_index = 0;
+        }
+    }
+
+    /// Follow the nth (zero-indexed) edge out of the selected block.
+    pub fn follow(&mut self, edge: usize) -> bool {
+        let target =
+            match self.blocks.get(self.block_index).and_then(|block| block.successors.get(edge)) {
+                Some((target, _)) => *target,
+                None => return false,
+            };
+
+        match self.blocks.iter().position(|block| block.start == target) {
+            Some(index) => {
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select the block containing the given pc.
+    pub fn goto(&mut self, pc: u128) -> bool {
+        match self.blocks.iter().position(|block| block.assembly.iter().any(|(i, _)| *i == pc)) {
+            Some(index) => {
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Select the next block, wrapping around, which contains the given opcode or pc.
+    pub fn find(&mut self, query: &str) -> bool {
+        let pc = parse_pc(query);
+        let query = query.to_uppercase();
+        let matches = |block: &CFGBlock| {
+            block.assembly.iter().any(|(instruction, assembly)| {
+                Some(*instruction) == pc ||
+                    assembly
+                        .split_whitespace()
+                        .nth(1)
+                        .map(|opcode| opcode == query)
+                        .unwrap_or(false)
+            })
+        };
+
+        let count = self.blocks.len();
+        match (1..=count)
+            .map(|offset| (self.block_index + offset) % count)
+            .find(|index| matches(&self.blocks[*index]))
+        {
+            Some(index) => {
+                self.select(index);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Parse a pc given in either hexadecimal (`0x` prefixed) or decimal.
+pub fn parse_pc(pc: &str) -> Option<u128> {
+    match pc.strip_prefix("0x") {
+        Some(hex) => u128::from_str_radix(hex, 16).ok(),
+        None => pc.parse::<u128>().ok(),
+    }
+}
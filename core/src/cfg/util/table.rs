@@ -0,0 +1,54 @@
+use ethers::prelude::U256;
+use heimdall_common::utils::strings::encode_hex_reduced;
+use tui::{
+    style::{Color, Modifier, Style},
+    widgets::{Cell, Row},
+};
+
+use crate::cfg::structures::state::State;
+
+/// A helper function used in the CFG TUI views for rendering the block list, as well as handling
+/// scrolling and selection.
+pub fn build_rows(state: &mut State, max_row_height: usize) -> Vec<Row<'static>> {
+    // ensure block index is within bounds
+    if state.block_index >= state.blocks.len() && state.block_index != 0 {
+        state.block_index = state.blocks.len() - 1;
+    }
+
+    // keep the selected block visible, pinned to the top of the list where possible
+    let blocks = &state.blocks;
+    let num_items = std::cmp::min(max_row_height, blocks.len());
+    let first = std::cmp::min(state.block_index, blocks.len() - num_items);
+
+    let mut rows = Vec::new();
+    for (i, block) in blocks[first..first + num_items].iter().enumerate() {
+        rows.push(
+            Row::new(vec![Cell::from(format!(
+                " {} ",
+                encode_hex_reduced(U256::from(block.start))
+            ))])
+            .style(if first + i == state.block_index {
+                if state.scroll {
+                    Style::default().fg(Color::White)
+                } else {
+                    Style::default().fg(Color::White).bg(Color::DarkGray)
+                }
+            } else {
+                Style::default().fg(Color::White).remove_modifier(Modifier::BOLD)
+            })
+            .height(1)
+            .bottom_margin(0),
+        );
+    }
+
+    if rows.is_empty() {
+        rows.push(
+            Row::new(vec![Cell::from(" None Found ")])
+                .style(Style::default().fg(Color::DarkGray))
+                .height(1)
+                .bottom_margin(0),
+        );
+    }
+
+    rows
+}
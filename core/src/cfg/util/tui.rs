@@ -0,0 +1,214 @@
+use std::{io, time::Duration};
+
+use crossterm::{
+    event::EnableMouseCapture,
+    execute,
+    terminal::{enable_raw_mode, EnterAlternateScreen},
+};
+use tui::{backend::CrosstermBackend, Terminal};
+
+use crate::{
+    cfg::{
+        constants::STATE,
+        graph::CFGBlock,
+        menus::{render_ui, TUIView},
+        structures::state::parse_pc,
+    },
+    snapshot::util::tui::cleanup_terminal,
+};
+
+/// The TUI thread handler, which will be called from the main thread.
+pub fn handle(blocks: Vec<CFGBlock>, target: &str) {
+    // create new TUI terminal
+    enable_raw_mode().unwrap();
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture).unwrap();
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).unwrap();
+
+    // initialize state
+    let mut state = STATE.lock().unwrap();
+    state.blocks = blocks;
+    state.target = target.to_string();
+    drop(state);
+
+    loop {
+        let mut state = STATE.lock().unwrap();
+        terminal
+            .draw(|f| {
+                render_ui(f, &mut state);
+            })
+            .unwrap();
+        drop(state);
+
+        // check for user input
+        if crossterm::event::poll(Duration::from_millis(10)).unwrap() {
+            if let Ok(event) = crossterm::event::read() {
+                match event {
+                    crossterm::event::Event::Key(key) => {
+                        let mut state = STATE.lock().unwrap();
+
+                        // ignore key events if command palette is open
+                        if state.view == TUIView::CommandPalette {
+                            match key.code {
+                                // handle keys in command palette
+                                crossterm::event::KeyCode::Char(c) => {
+                                    state.input_buffer.push(c);
+                                }
+
+                                // handle backspace
+                                crossterm::event::KeyCode::Backspace => {
+                                    state.input_buffer.pop();
+                                }
+
+                                // enter command
+                                crossterm::event::KeyCode::Enter => {
+                                    let input_buffer = state.input_buffer.clone();
+                                    let mut split = input_buffer.split(' ');
+                                    let command = split.next().unwrap();
+                                    let args = split.collect::<Vec<&str>>();
+
+                                    state.view = TUIView::Main;
+                                    match command {
+                                        ":q" | ":quit" => {
+                                            state.view = TUIView::Killed;
+                                            break
+                                        }
+                                        ":h" | ":help" => {
+                                            state.view = TUIView::Help;
+                                        }
+                                        ":f" | ":find" => {
+                                            if let Some(query) = args.first() {
+                                                state.find(query);
+                                            }
+                                        }
+                                        ":g" | ":goto" => {
+                                            if let Some(pc) =
+                                                args.first().and_then(|pc| parse_pc(pc))
+                                            {
+                                                state.goto(pc);
+                                            }
+                                        }
+                                        ":d" | ":decompile" => {
+                                            state.show_decompiled = !state.show_decompiled;
+                                        }
+                                        _ => {}
+                                    }
+                                }
+
+                                // handle escape
+                                crossterm::event::KeyCode::Esc => {
+                                    state.view = TUIView::Main;
+                                }
+
+                                _ => {}
+                            }
+
+                            drop(state);
+                            continue
+                        }
+
+                        match key.code {
+                            // main on escape
+                            crossterm::event::KeyCode::Esc => {
+                                state.view = TUIView::Main;
+                            }
+
+                            // select block
+                            crossterm::event::KeyCode::Right => {
+                                state.scroll = true;
+                            }
+
+                            // deselect block
+                            crossterm::event::KeyCode::Left => {
+                                state.scroll = false;
+                            }
+
+                            // scroll down
+                            crossterm::event::KeyCode::Down => {
+                                if state.scroll {
+                                    state.scroll_index += 1;
+                                } else {
+                                    state.scroll_index = 0;
+                                    state.block_index += 1;
+                                }
+                            }
+
+                            // scroll up
+                            crossterm::event::KeyCode::Up => {
+                                if state.scroll {
+                                    if state.scroll_index > 0 {
+                                        state.scroll_index -= 1;
+                                    }
+                                } else if state.block_index > 0 {
+                                    state.scroll_index = 0;
+                                    state.block_index -= 1;
+                                }
+                            }
+
+                            // follow the nth edge out of the selected block
+                            crossterm::event::KeyCode::Char(c @ '1'..='9') => {
+                                state.follow(c as usize - '1' as usize);
+                            }
+
+                            // return to the previously selected block
+                            crossterm::event::KeyCode::Backspace => {
+                                state.back();
+                            }
+
+                            // toggle the decompiled view
+                            crossterm::event::KeyCode::Char('d') => {
+                                state.show_decompiled = !state.show_decompiled;
+                            }
+
+                            // toggle command palette on ":"
+                            crossterm::event::KeyCode::Char(':') => match state.view {
+                                TUIView::CommandPalette => {
+                                    state.view = TUIView::Main;
+                                }
+                                _ => {
+                                    state.input_buffer = String::from(":");
+                                    state.view = TUIView::CommandPalette;
+                                }
+                            },
+
+                            _ => {}
+                        }
+                        drop(state)
+                    }
+                    crossterm::event::Event::Mouse(mouse) => {
+                        let mut state = STATE.lock().unwrap();
+                        match mouse.kind {
+                            // scroll down
+                            crossterm::event::MouseEventKind::ScrollDown => {
+                                if state.scroll {
+                                    state.scroll_index += 1;
+                                } else {
+                                    state.scroll_index = 0;
+                                    state.block_index += 1;
+                                }
+                            }
+
+                            // scroll up
+                            crossterm::event::MouseEventKind::ScrollUp => {
+                                if state.scroll {
+                                    if state.scroll_index > 0 {
+                                        state.scroll_index -= 1;
+                                    }
+                                } else if state.block_index > 0 {
+                                    state.scroll_index = 0;
+                                    state.block_index -= 1;
+                                }
+                            }
+                            _ => {}
+                        }
+                        drop(state);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    cleanup_terminal();
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::{decode as decode_abi, ParamType, Token},
+    types::{Address, U256},
+};
+use heimdall_cache::{read_cache, store_cache};
+use heimdall_common::{
+    ether::{labels::get_token, rpc::rpc_request},
+    utils::strings::decode_hex,
+};
+use serde_json::json;
+
+/// The selectors of the ERC-20 `decimals()` and `symbol()` view functions.
+const DECIMALS_SELECTOR: &str = "0x313ce567";
+const SYMBOL_SELECTOR: &str = "0x95d89b41";
+
+/// Render a raw token amount in whole tokens, given the token's decimals. Trailing zeros of the
+/// fraction are trimmed, keeping at least one digit.
+///
+/// ```
+/// use ethers::types::U256;
+/// use heimdall_core::decode::amounts::format_token_amount;
+///
+/// assert_eq!(format_token_amount(U256::from(1_000_000), 6), "1.0");
+/// assert_eq!(format_token_amount(U256::from(1_234_500), 6), "1.2345");
+/// assert_eq!(format_token_amount(U256::from(5), 18), "0.000000000000000005");
+/// assert_eq!(format_token_amount(U256::from(42), 0), "42.0");
+/// ```
+pub fn format_token_amount(amount: U256, decimals: u8) -> String {
+    let digits = amount.to_string();
+    let decimals = decimals as usize;
+    let digits = format!("{digits:0>width$}", width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    format!("{whole}.{}", if fraction.is_empty() { "0" } else { fraction })
+}
+
+/// Call a view function with no arguments on `to`, returning the raw result.
+async fn call(rpc_url: &str, to: Address, selector: &str) -> Option<Vec<u8>> {
+    let result = rpc_request(
+        rpc_url,
+        "eth_call",
+        json!([{ "to": format!("{to:?}"), "data": selector }, "latest"]),
+    )
+    .await
+    .ok()?;
+
+    decode_hex(result.as_str()?.trim_start_matches("0x")).ok().filter(|data| !data.is_empty())
+}
+
+/// The symbol and decimals of the token at the address, from the token lists, or by calling its
+/// `symbol()` and `decimals()` functions if it isn't listed. Returns `None` if the address isn't
+/// a token.
+pub async fn token_details(address: Address, rpc_url: &str) -> Option<(String, u8)> {
+    if let Some(token) = get_token(&format!("{address:?}")) {
+        if let Some(decimals) = token.decimals {
+            return Some((token.symbol, decimals))
+        }
+    }
+
+    if rpc_url.is_empty() {
+        return None
+    }
+
+    // check the cache for the token's details
+    let cache_key = format!("token.{address:?}");
+    if let Some(details) = read_cache::<(String, u8)>(&cache_key) {
+        return Some(details)
+    }
+
+    let decimals = call(rpc_url, address, DECIMALS_SELECTOR).await?;
+    let decimals = U256::from_big_endian(decimals.get(..32)?);
+    if decimals > U256::from(u8::MAX) {
+        return None
+    }
+
+    // some tokens, such as MKR, return their symbol as bytes32 rather than a string
+    let symbol =
+        call(rpc_url, address, SYMBOL_SELECTOR).await.and_then(|data| {
+            match decode_abi(&[ParamType::String], &data).ok().and_then(|mut tokens| tokens.pop()) {
+                Some(Token::String(symbol)) => Some(symbol),
+                _ => data
+                    .get(..32)
+                    .map(|bytes| String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()),
+            }
+        })?;
+
+    let details = (symbol, decimals.as_u32() as u8);
+    store_cache(&cache_key, details.clone(), None);
+    Some(details)
+}
+
+/// Render each uint input as a human-readable amount of the token it's paired with, i.e. the
+/// nearest token address preceding it within the same call, or the target if it's a token.
+/// Returns the rendered amounts by input index, such as `1.0 USDC`.
+pub async fn token_amounts(
+    inputs: &[Token],
+    target: Option<Address>,
+    rpc_url: &str,
+) -> HashMap<usize, String> {
+    let mut amounts = HashMap::new();
+    if !inputs.iter().any(|input| matches!(input, Token::Uint(_))) {
+        return amounts
+    }
+
+    let target_token = match target {
+        Some(target) => token_details(target, rpc_url).await,
+        None => None,
+    };
+
+    let mut paired_token = None;
+    for (index, input) in inputs.iter().enumerate() {
+        match input {
+            Token::Address(address) => {
+                if let Some(token) = token_details(*address, rpc_url).await {
+                    paired_token = Some(token);
+                }
+            }
+            Token::Uint(amount) => {
+                if let Some((symbol, decimals)) = paired_token.as_ref().or(target_token.as_ref()) {
+                    amounts.insert(
+                        index,
+                        format!("{} {symbol}", format_token_amount(*amount, *decimals)),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    amounts
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::Token,
+    types::{Address, U256},
+};
+use heimdall_common::{
+    ether::signatures::{score_signature, ResolveSelector, ResolvedFunction},
+    utils::{strings::encode_hex, time::iso_timestamp},
+};
+
+use super::amounts::{format_token_amount, token_amounts};
+
+/// The range of values which are rendered as dates, from 2000 to 2100.
+const TIMESTAMP_RANGE: std::ops::Range<u64> = 946_684_800..4_102_444_800;
+
+/// The smallest value which is also rendered in ether, i.e. 0.001 ether.
+const MIN_WEI: u64 = 1_000_000_000_000_000;
+
+/// Annotate a decoded value with a human-friendly rendering, if it looks like a well-known kind
+/// of value: the maximum of a uint type, a unix timestamp, or an amount of wei.
+///
+/// ```
+/// use ethers::{abi::Token, types::U256};
+/// use heimdall_core::decode::annotate::annotate_value;
+///
+/// assert_eq!(annotate_value(&Token::Uint(U256::MAX)).unwrap(), "type(uint256).max");
+/// assert_eq!(annotate_value(&Token::Uint(U256::from(1700000000))).unwrap(), "2023-11-14T22:13:20Z");
+/// assert_eq!(annotate_value(&Token::Uint(U256::exp10(18) * 3 / 2)).unwrap(), "1.5 ether");
+/// assert_eq!(annotate_value(&Token::Uint(U256::from(42))), None);
+/// ```
+pub fn annotate_value(value: &Token) -> Option<String> {
+    let value = match value {
+        Token::Uint(value) => *value,
+        _ => return None,
+    };
+
+    // the maximum of a uint type is one less than a power of two, i.e. 2^bits - 1
+    let bits = match value {
+        U256::MAX => Some(256),
+        value if (value + 1) & value == U256::zero() => Some((value + 1).trailing_zeros()),
+        _ => None,
+    };
+    if let Some(bits) = bits.filter(|bits| *bits >= 32 && bits % 8 == 0) {
+        return Some(format!("type(uint{bits}).max"))
+    }
+
+    if value < U256::from(TIMESTAMP_RANGE.end) {
+        let value = value.as_u64();
+        if TIMESTAMP_RANGE.contains(&value) {
+            return iso_timestamp(value as i64)
+        }
+    }
+
+    if value >= U256::from(MIN_WEI) {
+        return Some(format!("{} ether", format_token_amount(value, 18)))
+    }
+
+    None
+}
+
+/// The best-scored signature of the function with the given 4-byte selector, from the selector
+/// database.
+async fn resolve_selector(selector: &[u8]) -> Option<String> {
+    let mut functions = ResolvedFunction::resolve(&encode_hex(selector.to_vec())).await?;
+    functions.sort_by_key(|function| std::cmp::Reverse(score_signature(&function.signature)));
+    functions.into_iter().next().map(|function| function.signature)
+}
+
+/// Annotate each decoded input with a human-friendly rendering of its value, by input index.
+/// Uints paired with a token are rendered as an amount of that token, while other values are
+/// annotated with [`annotate_value`], and 4-byte values are resolved as function selectors.
+pub async fn annotate_inputs(
+    inputs: &[Token],
+    target: Option<Address>,
+    rpc_url: &str,
+) -> HashMap<usize, String> {
+    let mut annotations = token_amounts(inputs, target, rpc_url).await;
+
+    for (index, input) in inputs.iter().enumerate() {
+        if annotations.contains_key(&index) {
+            continue
+        }
+
+        let annotation = match input {
+            Token::FixedBytes(bytes) if bytes.len() == 4 => resolve_selector(bytes).await,
+            input => annotate_value(input),
+        };
+        if let Some(annotation) = annotation {
+            annotations.insert(index, annotation);
+        }
+    }
+
+    annotations
+}
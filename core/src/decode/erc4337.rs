@@ -0,0 +1,117 @@
+use ethers::{
+    abi::Token,
+    types::{Address, U256},
+};
+
+/// The signature of the v0.6 EntryPoint's `handleOps` function.
+pub const HANDLE_OPS_V06_SIGNATURE: &str =
+    "handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)";
+
+/// The signature of the v0.7 EntryPoint's `handleOps` function, which takes packed user
+/// operations.
+pub const HANDLE_OPS_V07_SIGNATURE: &str =
+    "handleOps((address,uint256,bytes,bytes,bytes32,uint256,bytes32,bytes,bytes)[],address)";
+
+/// A single ERC-4337 user operation, normalized across EntryPoint versions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Vec<u8>,
+    pub call_data: Vec<u8>,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub paymaster_and_data: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Split a packed `bytes32` into its high and low 128-bit halves.
+fn unpack_u128s(packed: &[u8]) -> (U256, U256) {
+    (U256::from_big_endian(&packed[0..16]), U256::from_big_endian(&packed[16..32]))
+}
+
+impl UserOperation {
+    /// Build a [`UserOperation`] from a decoded v0.6 `UserOperation` or v0.7
+    /// `PackedUserOperation` tuple.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        let fields = match token {
+            Token::Tuple(fields) => fields,
+            _ => return None,
+        };
+
+        match fields.as_slice() {
+            [Token::Address(sender), Token::Uint(nonce), Token::Bytes(init_code), Token::Bytes(call_data), Token::Uint(call_gas_limit), Token::Uint(verification_gas_limit), Token::Uint(pre_verification_gas), Token::Uint(max_fee_per_gas), Token::Uint(max_priority_fee_per_gas), Token::Bytes(paymaster_and_data), Token::Bytes(signature)] => {
+                Some(UserOperation {
+                    sender: *sender,
+                    nonce: *nonce,
+                    init_code: init_code.clone(),
+                    call_data: call_data.clone(),
+                    call_gas_limit: *call_gas_limit,
+                    verification_gas_limit: *verification_gas_limit,
+                    pre_verification_gas: *pre_verification_gas,
+                    max_fee_per_gas: *max_fee_per_gas,
+                    max_priority_fee_per_gas: *max_priority_fee_per_gas,
+                    paymaster_and_data: paymaster_and_data.clone(),
+                    signature: signature.clone(),
+                })
+            }
+            [Token::Address(sender), Token::Uint(nonce), Token::Bytes(init_code), Token::Bytes(call_data), Token::FixedBytes(account_gas_limits), Token::Uint(pre_verification_gas), Token::FixedBytes(gas_fees), Token::Bytes(paymaster_and_data), Token::Bytes(signature)]
+                if account_gas_limits.len() == 32 && gas_fees.len() == 32 =>
+            {
+                let (verification_gas_limit, call_gas_limit) = unpack_u128s(account_gas_limits);
+                let (max_priority_fee_per_gas, max_fee_per_gas) = unpack_u128s(gas_fees);
+                Some(UserOperation {
+                    sender: *sender,
+                    nonce: *nonce,
+                    init_code: init_code.clone(),
+                    call_data: call_data.clone(),
+                    call_gas_limit,
+                    verification_gas_limit,
+                    pre_verification_gas: *pre_verification_gas,
+                    max_fee_per_gas,
+                    max_priority_fee_per_gas,
+                    paymaster_and_data: paymaster_and_data.clone(),
+                    signature: signature.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The nonce's key and sequence number. The EntryPoint keeps an independent sequence for
+    /// each 192-bit key.
+    pub fn nonce_key_and_sequence(&self) -> (U256, u64) {
+        (self.nonce >> 64, self.nonce.low_u64())
+    }
+
+    /// The factory which deploys the sender, and the calldata sent to it, if the operation
+    /// deploys its account.
+    pub fn factory(&self) -> Option<(Address, &[u8])> {
+        if self.init_code.len() < 20 {
+            return None
+        }
+        Some((Address::from_slice(&self.init_code[0..20]), &self.init_code[20..]))
+    }
+
+    /// The paymaster sponsoring this operation and its paymaster-specific data, if any.
+    pub fn paymaster(&self) -> Option<(Address, &[u8])> {
+        if self.paymaster_and_data.len() < 20 {
+            return None
+        }
+        Some((Address::from_slice(&self.paymaster_and_data[0..20]), &self.paymaster_and_data[20..]))
+    }
+}
+
+/// Parse the decoded inputs of a `handleOps` call into its user operations and beneficiary.
+pub fn parse_handle_ops(tokens: &[Token]) -> Option<(Vec<UserOperation>, Address)> {
+    match tokens {
+        [Token::Array(operations), Token::Address(beneficiary)] => Some((
+            operations.iter().map(UserOperation::from_token).collect::<Option<Vec<_>>>()?,
+            *beneficiary,
+        )),
+        _ => None,
+    }
+}
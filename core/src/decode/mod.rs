@@ -1,28 +1,41 @@
+pub mod amounts;
+pub mod annotate;
+pub mod erc4337;
+pub mod safe;
+pub mod seaport;
+pub mod summary;
+pub mod uniswap;
 mod util;
 
-use std::time::Duration;
+use std::{collections::HashMap, time::Duration};
 
 use clap::{AppSettings, Parser};
 use derive_builder::Builder;
-use ethers::{
-    abi::{decode as decode_abi, AbiEncode, Function, Param, ParamType, StateMutability},
-    types::Transaction,
-};
+use ethers::types::Transaction;
 
 use heimdall_common::{
-    constants::TRANSACTION_HASH_REGEX,
+    constants::{ADDRESS_REGEX, TRANSACTION_HASH_REGEX},
     ether::{
-        evm::core::types::{display, parse_function_parameters},
-        rpc::get_transaction,
+        metadata::{decode_metadata, fetch_metadata_file},
+        rpc::{get_code, get_transaction},
         signatures::{score_signature, ResolveSelector, ResolvedFunction},
     },
     utils::{io::logging::Logger, strings::decode_hex},
 };
 
 use indicatif::ProgressBar;
-use strsim::normalized_damerau_levenshtein as similarity;
 
-use crate::decode::util::get_explanation;
+use crate::{
+    decode::{
+        annotate::annotate_inputs,
+        summary::{summarize_transaction, TransactionSummary},
+        util::{
+            format_inputs, get_explanation, match_signatures, recover_contract_functions,
+            trace_known_formats, with_known_functions, with_recovered_function, DecodeContext,
+        },
+    },
+    report::{decode_context, schema::with_schema},
+};
 
 #[derive(Debug, Clone, Parser, Builder)]
 #[clap(
@@ -56,9 +69,46 @@ pub struct DecodeArgs {
     #[clap(long, short)]
     pub default: bool,
 
+    /// Never prompt for input, automatically selecting the highest-scored option instead. This
+    /// is also enabled when the `CI` environment variable is set to `true`.
+    #[clap(long = "non-interactive")]
+    pub non_interactive: bool,
+
     /// Whether to truncate nonstandard sized calldata.
     #[clap(long, short)]
     pub truncate_calldata: bool,
+
+    /// Whether to prefer the ABI from the target contract's IPFS metadata file when decoding a
+    /// transaction, if the contract's bytecode contains an IPFS metadata hash.
+    #[clap(long = "fetch-metadata")]
+    pub fetch_metadata: bool,
+
+    /// The IPFS gateway to use for fetching metadata.
+    #[clap(long = "ipfs-gateway", default_value = "", hide_default_value = true)]
+    pub ipfs_gateway: String,
+
+    /// A Handlebars template to render the results with, written alongside the other
+    /// outputs as `report.<ext>`, where `<ext>` is taken from the template's filename.
+    #[clap(long = "template", default_value = "", hide_default_value = true)]
+    pub template: String,
+
+    /// The address of the contract the calldata is sent to. The contract is decompiled, and its
+    /// recovered function for the selector is preferred over the signature databases, which are
+    /// only used to name it.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub contract: String,
+
+    /// Whether to show decoded values as-is, without annotating them with human-friendly
+    /// renderings such as token amounts, amounts of ether, dates, and resolved selectors.
+    #[clap(long)]
+    pub raw: bool,
+
+    /// Whether to print the decoded calldata to stdout as JSON, with its selector, candidate
+    /// signatures, typed parameters, and raw words, rather than as a trace, so it can be piped
+    /// into other tools. The JSON is the same as the `decoded.json` written by `--format json`,
+    /// but no file is written. Implies `--non-interactive`, and logs are printed to stderr.
+    #[clap(long)]
+    pub json: bool,
 }
 
 impl DecodeArgsBuilder {
@@ -70,15 +120,39 @@ impl DecodeArgsBuilder {
             openai_api_key: Some(String::new()),
             explain: Some(false),
             default: Some(true),
+            non_interactive: Some(false),
             truncate_calldata: Some(false),
+            fetch_metadata: Some(false),
+            ipfs_gateway: Some(String::new()),
+            template: Some(String::new()),
+            contract: Some(String::new()),
+            raw: Some(false),
+            json: Some(false),
         }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct DecodeResult {
+    /// The decoded calldata, without a `0x` prefix. For transaction hashes, this is the calldata
+    /// fetched from the RPC provider.
+    pub calldata: String,
+    /// The candidate functions the calldata matches.
+    pub matches: Vec<ResolvedFunction>,
+}
+
 /// The entrypoint for the decode module. This will attempt to decode the arguments of the target
 /// calldata, without the ABI of the target contract.
-#[allow(deprecated)]
 pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn std::error::Error>> {
+    decode_with_calldata(args).await.map(|result| result.matches)
+}
+
+/// Decode the target calldata as with [`decode`], also returning the calldata which was decoded,
+/// which is fetched from the RPC provider when the target is a transaction hash.
+#[allow(deprecated)]
+pub async fn decode_with_calldata(
+    args: DecodeArgs,
+) -> Result<DecodeResult, Box<dyn std::error::Error>> {
     // set logger environment variable if not already set
     if std::env::var("RUST_LOG").is_err() {
         std::env::set_var(
@@ -98,6 +172,7 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
 
     // init variables
     let mut raw_transaction: Transaction = Transaction::default();
+    let mut summary: Option<TransactionSummary> = None;
     let calldata;
 
     // check if we require an OpenAI API key
@@ -106,11 +181,24 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
         std::process::exit(1);
     }
 
+    // explanations are printed alongside the trace, so they can't be part of the json output
+    if args.explain && args.json {
+        logger.error("argument '--explain' can't be used with '--json'.");
+        std::process::exit(1);
+    }
+
+    // the contract to decode against must be an address
+    if !args.contract.is_empty() && !ADDRESS_REGEX.is_match(&args.contract).unwrap() {
+        logger.error("argument '--contract' must be a contract address.");
+        std::process::exit(1);
+    }
+
     // determine whether or not the target is a transaction hash
     if TRANSACTION_HASH_REGEX.is_match(&args.target).unwrap() {
         // We are decoding a transaction hash, so we need to fetch the calldata from the RPC
         // provider.
         raw_transaction = get_transaction(&args.target, &args.rpc_url).await.unwrap();
+        summary = Some(summarize_transaction(&raw_transaction, &args.rpc_url).await);
 
         calldata = raw_transaction.input.to_string().replacen("0x", "", 1);
     } else {
@@ -132,12 +220,17 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
         std::process::exit(1);
     }
 
-    // if calldata isn't a multiple of 64, it may be harder to decode.
-    if (calldata[8..].len() % 64 != 0) && !args.truncate_calldata {
+    // if calldata isn't a multiple of 64, it may be harder to decode. warnings are left out of
+    // json output, since it's printed to stdout.
+    if (calldata[8..].len() % 64 != 0) && !args.truncate_calldata && !args.json {
         logger.warn("calldata is not a standard size. decoding may fail since each word is not exactly 32 bytes long.");
         logger.warn("if decoding fails, try using the --truncate-calldata flag to truncate the calldata to a standard size.");
     } else if args.truncate_calldata {
-        logger.warn("calldata is not a standard size. truncating the calldata to a standard size.");
+        if !args.json {
+            logger.warn(
+                "calldata is not a standard size. truncating the calldata to a standard size.",
+            );
+        }
 
         // get the selector
         let selector = calldata[0..8].to_owned();
@@ -151,101 +244,72 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
 
     // parse the two parts of calldata, inputs and selector
     let function_selector = calldata[0..8].to_owned();
-    let byte_args = match decode_hex(&calldata[8..]) {
-        Ok(byte_args) => byte_args,
-        Err(_) => {
-            logger.error("failed to parse bytearray from calldata.");
-            std::process::exit(1)
+    if decode_hex(&calldata[8..]).is_err() {
+        logger.error("failed to parse bytearray from calldata.");
+        std::process::exit(1)
+    }
+
+    // if the contract's original ABI is available, prefer it over resolving the selector
+    let mut abi_function = None;
+    if let (true, Some(to)) = (args.fetch_metadata, raw_transaction.to) {
+        let contract_bytecode = get_code(&format!("{to:?}"), &args.rpc_url).await?;
+
+        if let Some(metadata) = decode_metadata(&contract_bytecode) {
+            abi_function =
+                fetch_metadata_file(&metadata, &args.ipfs_gateway, false).await.and_then(
+                    |metadata_file| metadata_file.resolved_functions().remove(&function_selector),
+                );
         }
-    };
 
-    // get the function signature possibilities
-    let potential_matches = match ResolvedFunction::resolve(&function_selector).await {
-        Some(signatures) => signatures,
-        None => Vec::new(),
-    };
-    let mut matches: Vec<ResolvedFunction> = Vec::new();
-
-    for potential_match in &potential_matches {
-        // convert the string inputs into a vector of decoded types
-        let mut inputs: Vec<ParamType> = Vec::new();
-        if let Some(type_) = parse_function_parameters(&potential_match.signature) {
-            for input in type_ {
-                inputs.push(input);
-            }
+        if abi_function.is_some() {
+            logger.debug("using function signature from the contract's metadata file.");
         }
+    }
 
-        match decode_abi(&inputs, &byte_args) {
-            Ok(result) => {
-                // convert tokens to params
-                let mut params: Vec<Param> = Vec::new();
-                for (i, input) in inputs.iter().enumerate() {
-                    params.push(Param {
-                        name: format!("arg{i}"),
-                        kind: input.to_owned(),
-                        internal_type: None,
-                    });
-                }
-                // build the decoded function to verify it's a match
-                let decoded_function_call = Function {
-                    name: potential_match.name.to_string(),
-                    inputs: params,
-                    outputs: Vec::new(),
-                    constant: None,
-                    state_mutability: StateMutability::NonPayable,
-                }
-                .encode_input(&result);
-                match decoded_function_call {
-                    Ok(decoded_function_call) => {
-                        // decode the function call in trimmed bytes, removing 0s, because contracts
-                        // can use nonstandard sized words and padding is
-                        // hard
-                        let cleaned_bytes = decoded_function_call.encode_hex().replace('0', "");
-                        let decoded_function_call = match cleaned_bytes
-                            .split_once(&function_selector.replace('0', ""))
-                        {
-                            Some(decoded_function_call) => decoded_function_call.1,
-                            None => {
-                                logger.debug(&format!("potential match '{}' ignored. decoded inputs differed from provided calldata.", &potential_match.signature).to_string());
-                                continue
-                            }
-                        };
-
-                        // if the decoded function call matches (95%) the function signature, add it
-                        // to the list of matches
-                        if similarity(decoded_function_call, &calldata[8..].replace('0', "")).abs() >=
-                            0.90
-                        {
-                            let mut found_match = potential_match.clone();
-                            found_match.decoded_inputs = Some(result);
-                            matches.push(found_match);
-                        } else {
-                            logger.debug(&format!("potential match '{}' ignored. decoded inputs differed from provided calldata.", &potential_match.signature).to_string());
-                        }
-                    }
-                    Err(_) => {
-                        logger.debug(
-                            &format!(
-                                "potential match '{}' ignored. type checking failed",
-                                &potential_match.signature
-                            )
-                            .to_string(),
-                        );
-                    }
-                }
-            }
-            Err(_) => {
-                logger.debug(
-                    &format!(
-                        "potential match '{}' ignored. decoding types failed",
-                        &potential_match.signature
-                    )
-                    .to_string(),
-                );
-            }
+    // if the target contract was given, decompile it to recover the function for the selector
+    let mut recovered_function = None;
+    if abi_function.is_none() && !args.contract.is_empty() {
+        recovered_function = recover_contract_functions(&args.contract, &args.rpc_url)
+            .await?
+            .remove(&function_selector);
+
+        match recovered_function {
+            Some(_) => logger
+                .debug("using function signature recovered by decompiling the target contract."),
+            None if !args.json => logger.warn(&format!(
+                "the target contract doesn't dispatch selector '0x{function_selector}', falling back to the signature databases."
+            )),
+            None => {}
         }
     }
 
+    // get the function signature possibilities
+    let potential_matches = match (abi_function, recovered_function) {
+        (Some(abi_function), _) => vec![abi_function],
+        (None, Some(recovered_function)) => with_recovered_function(
+            recovered_function,
+            ResolvedFunction::resolve(&function_selector).await.unwrap_or_default(),
+        ),
+        (None, None) => with_known_functions(
+            &function_selector,
+            ResolvedFunction::resolve(&function_selector).await.unwrap_or_default(),
+        ),
+    };
+    let mut matches = match_signatures(&potential_matches, &calldata);
+
+    // print the candidates as json instead of a trace, most likely first
+    if args.json {
+        matches.sort_by_key(|function| std::cmp::Reverse(score_signature(&function.signature)));
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&with_schema(
+                "decode",
+                decode_context(&matches, &args.target, Some(&calldata))
+            ))?
+        );
+        return Ok(DecodeResult { calldata, matches })
+    }
+
     // truncate target for prettier display
     let mut shortened_target = args.target;
     if shortened_target.len() > 66 {
@@ -267,6 +331,13 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
             "()".to_string(),
         );
         trace.br(decode_call);
+
+        // summarize the transaction above its decoded calldata
+        if let Some(summary) = &summary {
+            trace.add_message(decode_call, line!(), summary.lines());
+            trace.br(decode_call);
+        }
+
         trace.add_message(
             decode_call,
             line!(),
@@ -329,7 +400,7 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
                 "multiple possible matches found. select an option below",
                 matches.iter().map(|x| x.signature.clone()).collect(),
                 Some(0u8),
-                args.default,
+                args.default || args.non_interactive,
             );
         }
 
@@ -350,6 +421,13 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
             "()".to_string(),
         );
         trace.br(decode_call);
+
+        // summarize the transaction above its decoded calldata
+        if let Some(summary) = &summary {
+            trace.add_message(decode_call, line!(), summary.lines());
+            trace.br(decode_call);
+        }
+
         trace.add_message(
             decode_call,
             line!(),
@@ -377,34 +455,48 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
             format!("calldata: {} bytes", calldata.len() / 2usize)
         );
 
-        // build inputs
-        for (i, input) in selected_match.decoded_inputs.as_ref().unwrap().iter().enumerate() {
-            let mut decoded_inputs_as_message = display(vec![input.to_owned()], "           ");
-            if decoded_inputs_as_message.is_empty() {
-                break
+        // annotate values with human-friendly renderings, i.e. `1000000 (1.0 USDC)`
+        let decoded_inputs = selected_match.decoded_inputs.clone().unwrap_or_default();
+        let annotations = match args.raw {
+            true => HashMap::new(),
+            false => {
+                annotate_inputs(
+                    &decoded_inputs,
+                    raw_transaction.to.or(args.contract.parse().ok()),
+                    &args.rpc_url,
+                )
+                .await
             }
+        };
 
-            if i == 0 {
-                decoded_inputs_as_message[0] = format!(
-                    "input {}:{}{}",
-                    i,
-                    " ".repeat(4 - i.to_string().len()),
-                    decoded_inputs_as_message[0].replacen("           ", "", 1)
-                )
-            } else {
-                decoded_inputs_as_message[0] = format!(
-                    "      {}:{}{}",
-                    i,
-                    " ".repeat(4 - i.to_string().len()),
-                    decoded_inputs_as_message[0].replacen("           ", "", 1)
-                )
+        // build inputs
+        for (index, mut decoded_inputs_as_message) in
+            format_inputs(&decoded_inputs).into_iter().enumerate()
+        {
+            if let Some(annotation) = annotations.get(&index) {
+                decoded_inputs_as_message[0].push_str(&format!(" ({annotation})"));
             }
 
             // add to trace and decoded string
             trace.add_message(decode_call, 1, decoded_inputs_as_message.clone());
-            decoded_string.push_str(&format!("\n{}", decoded_inputs_as_message.clone().join("\n")));
+            decoded_string.push_str(&format!("\n{}", decoded_inputs_as_message.join("\n")));
         }
 
+        // decode well-known call formats, i.e. the inner call of a Safe transaction
+        let context = DecodeContext {
+            rpc_url: args.rpc_url.clone(),
+            block_number: raw_transaction.block_number.map(|block| block.as_u64()),
+        };
+        trace_known_formats(
+            &mut trace,
+            decode_call,
+            selected_match,
+            raw_transaction.to,
+            &context,
+            0,
+        )
+        .await;
+
         // display trace (pretty print decoded calldata)
         trace.display();
 
@@ -430,5 +522,5 @@ pub async fn decode(args: DecodeArgs) -> Result<Vec<ResolvedFunction>, Box<dyn s
         }
     }
 
-    Ok(matches)
+    Ok(DecodeResult { calldata, matches })
 }
@@ -0,0 +1,252 @@
+use ethers::{
+    abi::{decode as decode_abi, encode, ParamType, Token},
+    types::{Address, Signature, H256, U256},
+    utils::keccak256,
+};
+use heimdall_common::ether::rpc::{chain_id, rpc_request};
+use serde_json::json;
+
+/// The signature of a Safe's `execTransaction` function.
+pub const EXEC_TRANSACTION_SIGNATURE: &str =
+    "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
+
+/// The EIP-712 type of a Safe transaction.
+const SAFE_TX_TYPE: &str = "SafeTx(address to,uint256 value,bytes data,uint8 operation,uint256 safeTxGas,uint256 baseGas,uint256 gasPrice,address gasToken,address refundReceiver,uint256 nonce)";
+
+/// The parameters of a Safe's `execTransaction` call.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafeTransaction {
+    pub to: Address,
+    pub value: U256,
+    pub data: Vec<u8>,
+    pub operation: u8,
+    pub safe_tx_gas: U256,
+    pub base_gas: U256,
+    pub gas_price: U256,
+    pub gas_token: Address,
+    pub refund_receiver: Address,
+    pub signatures: Vec<u8>,
+}
+
+/// The ways a Safe owner can sign a transaction, determined by the signature's `v` value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SafeSignatureKind {
+    /// An EIP-1271 contract signature, where `r` holds the signing contract's address.
+    Contract,
+    /// A hash pre-approved on-chain via `approveHash`, where `r` holds the approver's address.
+    ApprovedHash,
+    /// An `eth_sign` signature over the prefixed transaction hash.
+    EthSign,
+    /// A plain ECDSA signature over the transaction hash.
+    Ecdsa,
+}
+
+/// A single owner signature packed into a Safe transaction's `signatures`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafeSignature {
+    pub kind: SafeSignatureKind,
+    pub r: U256,
+    pub s: U256,
+    pub v: u8,
+    /// The owner which produced this signature, if it's known or could be recovered.
+    pub signer: Option<Address>,
+}
+
+impl SafeTransaction {
+    /// Build a [`SafeTransaction`] from the decoded inputs of an `execTransaction` call.
+    pub fn from_tokens(tokens: &[Token]) -> Option<Self> {
+        match tokens {
+            [Token::Address(to), Token::Uint(value), Token::Bytes(data), Token::Uint(operation), Token::Uint(safe_tx_gas), Token::Uint(base_gas), Token::Uint(gas_price), Token::Address(gas_token), Token::Address(refund_receiver), Token::Bytes(signatures)] => {
+                Some(SafeTransaction {
+                    to: *to,
+                    value: *value,
+                    data: data.clone(),
+                    operation: operation.low_u32() as u8,
+                    safe_tx_gas: *safe_tx_gas,
+                    base_gas: *base_gas,
+                    gas_price: *gas_price,
+                    gas_token: *gas_token,
+                    refund_receiver: *refund_receiver,
+                    signatures: signatures.clone(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The human-readable name of the transaction's operation.
+    pub fn operation_name(&self) -> &'static str {
+        match self.operation {
+            0 => "call",
+            1 => "delegatecall",
+            _ => "unknown",
+        }
+    }
+
+    /// Compute the EIP-712 hash owners sign for this transaction. Safes older than v1.3.0 don't
+    /// include the chain id in their domain, in which case `chain_id` should be `None`.
+    pub fn hash(&self, safe: Address, chain_id: Option<u64>, nonce: U256) -> H256 {
+        let domain_separator = match chain_id {
+            Some(chain_id) => keccak256(encode(&[
+                Token::FixedBytes(
+                    keccak256("EIP712Domain(uint256 chainId,address verifyingContract)").to_vec(),
+                ),
+                Token::Uint(U256::from(chain_id)),
+                Token::Address(safe),
+            ])),
+            None => keccak256(encode(&[
+                Token::FixedBytes(keccak256("EIP712Domain(address verifyingContract)").to_vec()),
+                Token::Address(safe),
+            ])),
+        };
+        let struct_hash = keccak256(encode(&[
+            Token::FixedBytes(keccak256(SAFE_TX_TYPE).to_vec()),
+            Token::Address(self.to),
+            Token::Uint(self.value),
+            Token::FixedBytes(keccak256(&self.data).to_vec()),
+            Token::Uint(U256::from(self.operation)),
+            Token::Uint(self.safe_tx_gas),
+            Token::Uint(self.base_gas),
+            Token::Uint(self.gas_price),
+            Token::Address(self.gas_token),
+            Token::Address(self.refund_receiver),
+            Token::Uint(nonce),
+        ]));
+
+        let mut preimage = vec![0x19, 0x01];
+        preimage.extend_from_slice(&domain_separator);
+        preimage.extend_from_slice(&struct_hash);
+        H256::from(keccak256(preimage))
+    }
+}
+
+/// Split a Safe's packed `signatures` into individual [`SafeSignature`]s. Contract and approved
+/// hash signatures carry their signer, while ECDSA signers must be recovered from the transaction
+/// hash with [`recover_signers`].
+///
+/// Only the static 65-byte slots are parsed. Contract signatures (`v == 0`) store the offset of
+/// their dynamic data in `s`, and that data is appended after the slots, so parsing stops at the
+/// smallest such offset rather than reading the dynamic data as more signatures.
+pub fn parse_signatures(signatures: &[u8]) -> Vec<SafeSignature> {
+    let mut parsed = Vec::new();
+    let mut end = signatures.len();
+    let mut offset = 0;
+
+    while offset + 65 <= end {
+        let signature = &signatures[offset..offset + 65];
+        let r = U256::from_big_endian(&signature[0..32]);
+        let s = U256::from_big_endian(&signature[32..64]);
+        let v = signature[64];
+        let kind = match v {
+            0 => SafeSignatureKind::Contract,
+            1 => SafeSignatureKind::ApprovedHash,
+            v if v > 30 => SafeSignatureKind::EthSign,
+            _ => SafeSignatureKind::Ecdsa,
+        };
+
+        // contract and approved hash signatures encode the signer in `r`
+        let signer = match kind {
+            SafeSignatureKind::Contract | SafeSignatureKind::ApprovedHash => {
+                Some(Address::from_slice(&signature[12..32]))
+            }
+            _ => None,
+        };
+
+        // the static slots end where the first contract signature's data begins
+        if kind == SafeSignatureKind::Contract && s < U256::from(end) {
+            end = s.as_usize();
+        }
+
+        parsed.push(SafeSignature { kind, r, s, v, signer });
+        offset += 65;
+    }
+
+    parsed
+}
+
+/// Recover the signers of ECDSA and `eth_sign` signatures over the given Safe transaction hash.
+pub fn recover_signers(signatures: &mut [SafeSignature], hash: H256) {
+    for signature in signatures.iter_mut() {
+        let (message, v) = match signature.kind {
+            SafeSignatureKind::Ecdsa => (hash, signature.v),
+            SafeSignatureKind::EthSign => {
+                let mut prefixed = b"\x19Ethereum Signed Message:\n32".to_vec();
+                prefixed.extend_from_slice(hash.as_bytes());
+                (H256::from(keccak256(prefixed)), signature.v - 4)
+            }
+            _ => continue,
+        };
+
+        signature.signer =
+            Signature { r: signature.r, s: signature.s, v: v as u64 }.recover(message).ok();
+    }
+}
+
+/// Call a view function with no arguments on `to` at the given block, returning the raw result.
+async fn call_at_block(
+    rpc_url: &str,
+    to: Address,
+    selector: &str,
+    block_number: u64,
+) -> Option<Vec<u8>> {
+    let result = rpc_request(
+        rpc_url,
+        "eth_call",
+        json!([{ "to": format!("{to:?}"), "data": selector }, format!("{block_number:#x}")]),
+    )
+    .await
+    .ok()?;
+
+    let result = result.as_str()?.trim_start_matches("0x");
+    heimdall_common::utils::strings::decode_hex(result).ok()
+}
+
+/// Recover and verify the signers of a Safe transaction executed by `safe`, by reading the Safe's
+/// nonce and owners from the block before the transaction was included. Returns the signatures
+/// along with the Safe's owners, if they could be read.
+pub async fn verify_signatures(
+    transaction: &SafeTransaction,
+    safe: Address,
+    rpc_url: &str,
+    block_number: u64,
+) -> (Vec<SafeSignature>, Option<Vec<Address>>) {
+    let mut signatures = parse_signatures(&transaction.signatures);
+    let state_block = block_number.saturating_sub(1);
+
+    // read the nonce this transaction was signed with, and the safe's owners
+    let nonce = match call_at_block(rpc_url, safe, "0xaffed0e0", state_block).await {
+        Some(nonce) if nonce.len() == 32 => U256::from_big_endian(&nonce),
+        _ => return (signatures, None),
+    };
+    let owners: Option<Vec<Address>> =
+        call_at_block(rpc_url, safe, "0xa0e67e2b", state_block).await.and_then(|owners| {
+            match decode_abi(&[ParamType::Array(Box::new(ParamType::Address))], &owners).ok()?.pop()
+            {
+                Some(Token::Array(owners)) => {
+                    Some(owners.into_iter().filter_map(|owner| owner.into_address()).collect())
+                }
+                _ => None,
+            }
+        });
+
+    // try the current domain first, falling back to the legacy domain without a chain id
+    let chain_id = chain_id(rpc_url).await.ok();
+    let mut candidates = [chain_id, None].into_iter().map(|domain_chain_id| {
+        let mut recovered = signatures.clone();
+        recover_signers(&mut recovered, transaction.hash(safe, domain_chain_id, nonce));
+        recovered
+    });
+    let current = candidates.next().unwrap_or_default();
+    let signed_by_owners = |signatures: &[SafeSignature]| match &owners {
+        Some(owners) => signatures
+            .iter()
+            .all(|signature| signature.signer.map(|s| owners.contains(&s)).unwrap_or(false)),
+        None => true,
+    };
+    signatures = match candidates.next() {
+        Some(legacy) if !signed_by_owners(&current) && signed_by_owners(&legacy) => legacy,
+        _ => current,
+    };
+
+    (signatures, owners)
+}
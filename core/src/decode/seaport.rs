@@ -0,0 +1,311 @@
+use ethers::{
+    abi::Token,
+    types::{Address, U256},
+};
+
+/// The signature of Seaport's `fulfillOrder` function.
+pub const FULFILL_ORDER_SIGNATURE: &str = "fulfillOrder(((address,address,(uint8,address,uint256,uint256,uint256)[],(uint8,address,uint256,uint256,uint256,address)[],uint8,uint256,uint256,bytes32,uint256,bytes32,uint256),bytes),bytes32)";
+
+/// The signature of Seaport's `fulfillAdvancedOrder` function.
+pub const FULFILL_ADVANCED_ORDER_SIGNATURE: &str = "fulfillAdvancedOrder(((address,address,(uint8,address,uint256,uint256,uint256)[],(uint8,address,uint256,uint256,uint256,address)[],uint8,uint256,uint256,bytes32,uint256,bytes32,uint256),uint120,uint120,bytes,bytes),(uint256,uint8,uint256,uint256,bytes32[])[],bytes32,address)";
+
+/// The signature of Seaport's `matchOrders` function.
+pub const MATCH_ORDERS_SIGNATURE: &str = "matchOrders(((address,address,(uint8,address,uint256,uint256,uint256)[],(uint8,address,uint256,uint256,uint256,address)[],uint8,uint256,uint256,bytes32,uint256,bytes32,uint256),bytes)[],((uint256,uint256)[],(uint256,uint256)[])[])";
+
+/// A single offer or consideration item of a Seaport order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderItem {
+    pub item_type: u8,
+    pub token: Address,
+    /// The token id, or the merkle root of valid token ids for criteria-based items.
+    pub identifier_or_criteria: U256,
+    pub start_amount: U256,
+    pub end_amount: U256,
+    /// The recipient of a consideration item. Offer items are received by the fulfiller.
+    pub recipient: Option<Address>,
+}
+
+/// A single Seaport order, normalized across basic and advanced orders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeaportOrder {
+    pub offerer: Address,
+    pub zone: Address,
+    pub offer: Vec<OrderItem>,
+    pub consideration: Vec<OrderItem>,
+    pub order_type: u8,
+    pub start_time: U256,
+    pub end_time: U256,
+    /// The fraction of the order to fill, for advanced orders.
+    pub fraction: Option<(U256, U256)>,
+    pub signature: Vec<u8>,
+}
+
+/// Resolves a criteria-based item of an order to a specific token id.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CriteriaResolver {
+    pub order_index: U256,
+    /// Whether the resolved item is an offer (0) or consideration (1) item.
+    pub side: u8,
+    pub index: U256,
+    pub identifier: U256,
+    pub proof_length: usize,
+}
+
+/// Matches offer items against consideration items across orders, by `(order, item)` index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fulfillment {
+    pub offer_components: Vec<(U256, U256)>,
+    pub consideration_components: Vec<(U256, U256)>,
+}
+
+/// The decoded parameters of a Seaport fulfillment call.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SeaportCall {
+    pub orders: Vec<SeaportOrder>,
+    pub criteria_resolvers: Vec<CriteriaResolver>,
+    pub fulfillments: Vec<Fulfillment>,
+    /// The recipient of the offer items, for advanced fulfillments.
+    pub recipient: Option<Address>,
+}
+
+/// The human-readable name of a Seaport item type.
+pub fn item_type_name(item_type: u8) -> &'static str {
+    match item_type {
+        0 => "NATIVE",
+        1 => "ERC20",
+        2 => "ERC721",
+        3 => "ERC1155",
+        4 => "ERC721_CRITERIA",
+        5 => "ERC1155_CRITERIA",
+        _ => "UNKNOWN",
+    }
+}
+
+/// The human-readable name of a Seaport order type.
+pub fn order_type_name(order_type: u8) -> &'static str {
+    match order_type {
+        0 => "FULL_OPEN",
+        1 => "PARTIAL_OPEN",
+        2 => "FULL_RESTRICTED",
+        3 => "PARTIAL_RESTRICTED",
+        4 => "CONTRACT",
+        _ => "UNKNOWN",
+    }
+}
+
+impl OrderItem {
+    /// Build an [`OrderItem`] from a decoded `OfferItem` or `ConsiderationItem` tuple.
+    pub fn from_token(token: &Token) -> Option<Self> {
+        let fields = match token {
+            Token::Tuple(fields) => fields,
+            _ => return None,
+        };
+
+        match fields.as_slice() {
+            [Token::Uint(item_type), Token::Address(token), Token::Uint(identifier_or_criteria), Token::Uint(start_amount), Token::Uint(end_amount), recipient @ ..] => {
+                Some(OrderItem {
+                    item_type: item_type.low_u32() as u8,
+                    token: *token,
+                    identifier_or_criteria: *identifier_or_criteria,
+                    start_amount: *start_amount,
+                    end_amount: *end_amount,
+                    recipient: match recipient {
+                        [Token::Address(recipient)] => Some(*recipient),
+                        [] => None,
+                        _ => return None,
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Render the item's identifier. Criteria items with a zero root accept any token id.
+    pub fn identifier(&self) -> String {
+        match self.item_type {
+            0 | 1 => "-".to_string(),
+            4 | 5 if self.identifier_or_criteria.is_zero() => "any".to_string(),
+            4 | 5 => format!("root {:#x}", self.identifier_or_criteria),
+            _ => self.identifier_or_criteria.to_string(),
+        }
+    }
+
+    /// Render the item's amount, which ascends or descends over the order's lifetime if its
+    /// start and end amounts differ.
+    pub fn amount(&self) -> String {
+        if self.start_amount == self.end_amount {
+            self.start_amount.to_string()
+        } else {
+            format!("{} → {}", self.start_amount, self.end_amount)
+        }
+    }
+}
+
+/// Build a [`SeaportOrder`] from a decoded `Order` or `AdvancedOrder` tuple.
+fn parse_order(token: &Token) -> Option<SeaportOrder> {
+    let fields = match token {
+        Token::Tuple(fields) => fields,
+        _ => return None,
+    };
+    let (parameters, fraction, signature) = match fields.as_slice() {
+        [Token::Tuple(parameters), Token::Bytes(signature)] => (parameters, None, signature),
+        [Token::Tuple(parameters), Token::Uint(numerator), Token::Uint(denominator), Token::Bytes(signature), Token::Bytes(_)] => {
+            (parameters, Some((*numerator, *denominator)), signature)
+        }
+        _ => return None,
+    };
+
+    match parameters.as_slice() {
+        [Token::Address(offerer), Token::Address(zone), Token::Array(offer), Token::Array(consideration), Token::Uint(order_type), Token::Uint(start_time), Token::Uint(end_time), ..] => {
+            Some(SeaportOrder {
+                offerer: *offerer,
+                zone: *zone,
+                offer: offer.iter().map(OrderItem::from_token).collect::<Option<Vec<_>>>()?,
+                consideration: consideration
+                    .iter()
+                    .map(OrderItem::from_token)
+                    .collect::<Option<Vec<_>>>()?,
+                order_type: order_type.low_u32() as u8,
+                start_time: *start_time,
+                end_time: *end_time,
+                fraction,
+                signature: signature.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parse a list of `(orderIndex, itemIndex)` fulfillment components.
+fn parse_components(token: &Token) -> Option<Vec<(U256, U256)>> {
+    match token {
+        Token::Array(components) => components
+            .iter()
+            .map(|component| match component {
+                Token::Tuple(fields) => match fields.as_slice() {
+                    [Token::Uint(order_index), Token::Uint(item_index)] => {
+                        Some((*order_index, *item_index))
+                    }
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Parse the decoded inputs of a `fulfillOrder`, `fulfillAdvancedOrder`, or `matchOrders` call.
+pub fn parse_seaport_call(signature: &str, tokens: &[Token]) -> Option<SeaportCall> {
+    match (signature, tokens) {
+        (FULFILL_ORDER_SIGNATURE, [order, _]) => {
+            Some(SeaportCall { orders: vec![parse_order(order)?], ..Default::default() })
+        }
+        (
+            FULFILL_ADVANCED_ORDER_SIGNATURE,
+            [order, Token::Array(resolvers), _, Token::Address(recipient)],
+        ) => Some(SeaportCall {
+            orders: vec![parse_order(order)?],
+            criteria_resolvers: resolvers
+                .iter()
+                .map(|resolver| match resolver {
+                    Token::Tuple(fields) => match fields.as_slice() {
+                        [Token::Uint(order_index), Token::Uint(side), Token::Uint(index), Token::Uint(identifier), Token::Array(proof)] => {
+                            Some(CriteriaResolver {
+                                order_index: *order_index,
+                                side: side.low_u32() as u8,
+                                index: *index,
+                                identifier: *identifier,
+                                proof_length: proof.len(),
+                            })
+                        }
+                        _ => None,
+                    },
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?,
+            recipient: Some(*recipient),
+            ..Default::default()
+        }),
+        (MATCH_ORDERS_SIGNATURE, [Token::Array(orders), Token::Array(fulfillments)]) => {
+            Some(SeaportCall {
+                orders: orders.iter().map(parse_order).collect::<Option<Vec<_>>>()?,
+                fulfillments: fulfillments
+                    .iter()
+                    .map(|fulfillment| match fulfillment {
+                        Token::Tuple(fields) if fields.len() == 2 => Some(Fulfillment {
+                            offer_components: parse_components(&fields[0])?,
+                            consideration_components: parse_components(&fields[1])?,
+                        }),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?,
+                ..Default::default()
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Render rows of cells as a table with left-aligned, space-padded columns.
+///
+/// ```
+/// use heimdall_core::decode::seaport::format_table;
+///
+/// let rows = vec![
+///     vec!["side".to_string(), "type".to_string()],
+///     vec!["offer".to_string(), "ERC721".to_string()],
+/// ];
+/// assert_eq!(format_table(&rows), vec!["side   type", "offer  ERC721"]);
+/// ```
+pub fn format_table(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let widths = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<usize>>();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(column, cell)| {
+                    format!("{cell}{}", " ".repeat(widths[column] - cell.chars().count()))
+                })
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Render an order's offer and consideration items as a table.
+pub fn format_items_table(order: &SeaportOrder) -> Vec<String> {
+    let mut rows = vec![["side", "#", "type", "token", "identifier", "amount", "recipient"]
+        .iter()
+        .map(|header| header.to_string())
+        .collect::<Vec<String>>()];
+    for (side, items) in [("offer", &order.offer), ("consideration", &order.consideration)] {
+        for (i, item) in items.iter().enumerate() {
+            rows.push(vec![
+                side.to_string(),
+                i.to_string(),
+                item_type_name(item.item_type).to_string(),
+                format!("{:?}", item.token),
+                item.identifier(),
+                item.amount(),
+                match item.recipient {
+                    Some(recipient) => format!("{recipient:?}"),
+                    None => "fulfiller".to_string(),
+                },
+            ]);
+        }
+    }
+    format_table(&rows)
+}
@@ -0,0 +1,154 @@
+use ethers::{
+    providers::{ProviderError, RpcError},
+    types::{Transaction, TransactionReceipt, U256},
+};
+use heimdall_common::{
+    ether::rpc::{get_transaction_receipt, rpc_capabilities, rpc_request},
+    utils::strings::encode_hex,
+};
+use serde_json::json;
+
+use crate::{
+    decode::amounts::format_token_amount,
+    trace::frame::{decode_revert_reason, CallFrame},
+};
+
+/// The context of a decoded transaction, displayed above its decoded calldata so its basic
+/// details don't need to be looked up separately.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionSummary {
+    pub from: String,
+    /// The recipient of the transaction, or `None` if it created a contract.
+    pub to: Option<String>,
+    /// The ether sent with the transaction, in wei.
+    pub value: U256,
+    /// The block the transaction was included in, or `None` if it's pending.
+    pub block: Option<u64>,
+    /// The gas used by the transaction, or `None` if it's pending.
+    pub gas_used: Option<u64>,
+    /// Whether the transaction succeeded, or `None` if it's pending.
+    pub success: Option<bool>,
+    /// The reason the transaction reverted with, if it failed and the reason could be recovered.
+    pub revert_reason: Option<String>,
+}
+
+impl TransactionSummary {
+    /// Summarize a transaction, given its receipt if it has been included in a block.
+    pub fn new(transaction: &Transaction, receipt: Option<&TransactionReceipt>) -> Self {
+        Self {
+            from: format!("{:?}", transaction.from),
+            to: transaction.to.map(|to| format!("{to:?}")),
+            value: transaction.value,
+            block: transaction.block_number.map(|block| block.as_u64()),
+            gas_used: receipt.and_then(|receipt| receipt.gas_used).map(|gas| gas.as_u64()),
+            success: receipt.and_then(|receipt| receipt.status).map(|status| status.as_u64() == 1),
+            revert_reason: None,
+        }
+    }
+
+    /// The lines of the summary, aligned with the rest of the decoded output.
+    ///
+    /// ```
+    /// use ethers::types::U256;
+    /// use heimdall_core::decode::summary::TransactionSummary;
+    ///
+    /// let summary = TransactionSummary {
+    ///     from: String::from("0x00000000000000000000000000000000000000aa"),
+    ///     value: U256::exp10(18),
+    ///     block: Some(17000000),
+    ///     gas_used: Some(21000),
+    ///     success: Some(false),
+    ///     revert_reason: Some(String::from("insufficient balance")),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let lines = summary.lines();
+    /// assert_eq!(lines[1], "to:        contract creation");
+    /// assert_eq!(lines[2], "value:     1.0 ether");
+    /// assert_eq!(lines[5], "status:    reverted: insufficient balance");
+    /// ```
+    pub fn lines(&self) -> Vec<String> {
+        let status = match (self.success, &self.revert_reason) {
+            (None, _) => String::from("pending"),
+            (Some(true), _) => String::from("success"),
+            (Some(false), Some(reason)) => format!("reverted: {reason}"),
+            (Some(false), None) => String::from("reverted"),
+        };
+
+        vec![
+            format!("from:      {}", self.from),
+            format!(
+                "to:        {}",
+                self.to.clone().unwrap_or_else(|| String::from("contract creation"))
+            ),
+            format!("value:     {} ether", format_token_amount(self.value, 18)),
+            format!(
+                "block:     {}",
+                self.block
+                    .map(|block| block.to_string())
+                    .unwrap_or_else(|| String::from("pending"))
+            ),
+            format!(
+                "gas used:  {}",
+                self.gas_used.map(|gas| gas.to_string()).unwrap_or_else(|| String::from("pending"))
+            ),
+            format!("status:    {status}"),
+        ]
+    }
+}
+
+/// Recover the reason a failed transaction reverted with. The transaction is traced if the RPC
+/// provider supports `debug_traceTransaction`, otherwise it's replayed with `eth_call` at the
+/// state of the block before it, which may differ from the state it was executed against.
+async fn revert_reason(transaction: &Transaction, rpc_url: &str) -> Option<String> {
+    if rpc_capabilities(rpc_url).await.debug {
+        let result = rpc_request(
+            rpc_url,
+            "debug_traceTransaction",
+            json!([
+                format!("{:?}", transaction.hash),
+                { "tracer": "callTracer", "tracerConfig": { "onlyTopCall": true } }
+            ]),
+        )
+        .await
+        .ok()?;
+        let frame = CallFrame::from_value(&result)?;
+
+        return decode_revert_reason(&frame.output).or(frame.error)
+    }
+
+    let block = transaction.block_number?.as_u64().checked_sub(1)?;
+    let error = rpc_request(
+        rpc_url,
+        "eth_call",
+        json!([{
+            "from": format!("{:?}", transaction.from),
+            "to": transaction.to.map(|to| format!("{to:?}")),
+            "value": format!("{:#x}", transaction.value),
+            "gas": format!("{:#x}", transaction.gas),
+            "data": transaction.input.to_string(),
+        }, format!("{block:#x}")]),
+    )
+    .await
+    .err()?;
+
+    // the revert data is nested within the JSON-RPC error response
+    let data = error
+        .downcast_ref::<ProviderError>()
+        .and_then(|error| error.as_error_response())
+        .and_then(|response| response.as_revert_data())?;
+    decode_revert_reason(&encode_hex(data.to_vec()))
+}
+
+/// Summarize the transaction, fetching its receipt and, if it failed, its revert reason.
+pub async fn summarize_transaction(transaction: &Transaction, rpc_url: &str) -> TransactionSummary {
+    let receipt =
+        get_transaction_receipt(&format!("{:?}", transaction.hash), rpc_url).await.ok().flatten();
+    let mut summary = TransactionSummary::new(transaction, receipt.as_ref());
+
+    if summary.success == Some(false) {
+        summary.revert_reason = revert_reason(transaction, rpc_url).await;
+    }
+
+    summary
+}
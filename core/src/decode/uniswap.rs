@@ -0,0 +1,231 @@
+use ethers::{
+    abi::{decode as decode_abi, ParamType, Token},
+    types::Address,
+};
+
+/// The signatures of the Universal Router's `execute` functions.
+pub const EXECUTE_SIGNATURES: [&str; 2] =
+    ["execute(bytes,bytes[])", "execute(bytes,bytes[],uint256)"];
+
+/// The signatures of the V3 SwapRouter and SwapRouter02 multi-hop swap functions, which take an
+/// encoded path as their first parameter.
+pub const V3_PATH_SIGNATURES: [&str; 4] = [
+    "exactInput((bytes,address,uint256,uint256,uint256))",
+    "exactOutput((bytes,address,uint256,uint256,uint256))",
+    "exactInput((bytes,address,uint256,uint256))",
+    "exactOutput((bytes,address,uint256,uint256))",
+];
+
+/// The signatures of the V2 router's swap functions, which take an `address[]` path.
+pub const V2_PATH_SIGNATURES: [&str; 11] = [
+    "swapExactTokensForTokens(uint256,uint256,address[],address,uint256)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokens(uint256,address[],address,uint256)",
+    "swapTokensForExactETH(uint256,uint256,address[],address,uint256)",
+    "swapExactTokensForETH(uint256,uint256,address[],address,uint256)",
+    "swapETHForExactTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForTokensSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactETHForTokensSupportingFeeOnTransferTokens(uint256,address[],address,uint256)",
+    "swapExactTokensForETHSupportingFeeOnTransferTokens(uint256,uint256,address[],address,uint256)",
+    "swapExactTokensForTokens(uint256,uint256,address[],address)",
+    "swapTokensForExactTokens(uint256,uint256,address[],address)",
+];
+
+/// The mask of a Universal Router command byte which selects the command type.
+const COMMAND_TYPE_MASK: u8 = 0x3f;
+
+/// The flag of a Universal Router command byte which allows the command to revert without
+/// reverting the whole transaction.
+const ALLOW_REVERT_FLAG: u8 = 0x80;
+
+/// A single decoded Universal Router command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouterCommand {
+    pub command: u8,
+    pub name: &'static str,
+    pub allow_revert: bool,
+    /// The command's decoded inputs, if the command's input layout is known.
+    pub inputs: Option<Vec<Token>>,
+    /// The command's raw ABI-encoded inputs.
+    pub raw_inputs: Vec<u8>,
+}
+
+impl RouterCommand {
+    /// Render the swap path of a V2 or V3 swap command. V3 exact output paths are encoded from
+    /// the output token to the input token, and are rendered in that order.
+    pub fn swap_path(&self) -> Option<String> {
+        match self.command & COMMAND_TYPE_MASK {
+            0x00 | 0x01 | 0x08 | 0x09 => find_swap_path(self.inputs.as_ref()?),
+            _ => None,
+        }
+    }
+}
+
+/// A single hop of a V3 swap path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathHop {
+    pub token_in: Address,
+    /// The pool's fee, in hundredths of a basis point.
+    pub fee: u32,
+    pub token_out: Address,
+}
+
+/// Get the name of the given Universal Router command type.
+pub fn command_name(command: u8) -> &'static str {
+    match command & COMMAND_TYPE_MASK {
+        0x00 => "V3_SWAP_EXACT_IN",
+        0x01 => "V3_SWAP_EXACT_OUT",
+        0x02 => "PERMIT2_TRANSFER_FROM",
+        0x03 => "PERMIT2_PERMIT_BATCH",
+        0x04 => "SWEEP",
+        0x05 => "TRANSFER",
+        0x06 => "PAY_PORTION",
+        0x08 => "V2_SWAP_EXACT_IN",
+        0x09 => "V2_SWAP_EXACT_OUT",
+        0x0a => "PERMIT2_PERMIT",
+        0x0b => "WRAP_ETH",
+        0x0c => "UNWRAP_WETH",
+        0x0d => "PERMIT2_TRANSFER_FROM_BATCH",
+        0x0e => "BALANCE_CHECK_ERC20",
+        0x10 => "SEAPORT_V1_5",
+        0x11 => "LOOKS_RARE_V2",
+        0x12 => "NFTX",
+        0x13 => "CRYPTOPUNKS",
+        0x15 => "OWNER_CHECK_721",
+        0x16 => "OWNER_CHECK_1155",
+        0x17 => "SWEEP_ERC721",
+        0x18 => "X2Y2_721",
+        0x19 => "SUDOSWAP",
+        0x1a => "NFT20",
+        0x1b => "X2Y2_1155",
+        0x1c => "FOUNDATION",
+        0x1d => "SWEEP_ERC1155",
+        0x1e => "ELEMENT_MARKET",
+        0x20 => "SEAPORT_V1_4",
+        0x21 => "EXECUTE_SUB_PLAN",
+        0x22 => "APPROVE_ERC20",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Get the input layout of the given Universal Router command type, if it's known.
+fn command_parameters(command: u8) -> Option<Vec<ParamType>> {
+    let address = || ParamType::Address;
+    let uint = || ParamType::Uint(256);
+    Some(match command & COMMAND_TYPE_MASK {
+        0x00 | 0x01 => vec![address(), uint(), uint(), ParamType::Bytes, ParamType::Bool],
+        0x02 => vec![address(), address(), ParamType::Uint(160)],
+        0x04..=0x06 => vec![address(), address(), uint()],
+        0x08 | 0x09 => {
+            vec![address(), uint(), uint(), ParamType::Array(Box::new(address())), ParamType::Bool]
+        }
+        0x0b | 0x0c => vec![address(), uint()],
+        0x0d => vec![ParamType::Array(Box::new(ParamType::Tuple(vec![
+            address(),
+            address(),
+            ParamType::Uint(160),
+            address(),
+        ])))],
+        0x0e => vec![address(), address(), uint()],
+        0x10 | 0x20 => vec![uint(), ParamType::Bytes],
+        0x21 => vec![ParamType::Bytes, ParamType::Array(Box::new(ParamType::Bytes))],
+        0x22 => vec![address(), uint()],
+        _ => return None,
+    })
+}
+
+/// Decode a Universal Router command string and its inputs into [`RouterCommand`]s.
+///
+/// ```
+/// use heimdall_core::decode::uniswap::parse_commands;
+///
+/// let commands = parse_commands(&[0x0b, 0x80], &[vec![], vec![]]);
+/// assert_eq!(commands[0].name, "WRAP_ETH");
+/// assert_eq!(commands[1].name, "V3_SWAP_EXACT_IN");
+/// assert!(commands[1].allow_revert);
+/// ```
+pub fn parse_commands(commands: &[u8], inputs: &[Vec<u8>]) -> Vec<RouterCommand> {
+    commands
+        .iter()
+        .zip(inputs.iter().map(Some).chain(std::iter::repeat(None)))
+        .map(|(command, input)| {
+            let raw_inputs = input.cloned().unwrap_or_default();
+            RouterCommand {
+                command: *command,
+                name: command_name(*command),
+                allow_revert: command & ALLOW_REVERT_FLAG != 0,
+                inputs: command_parameters(*command)
+                    .and_then(|parameters| decode_abi(&parameters, &raw_inputs).ok()),
+                raw_inputs,
+            }
+        })
+        .collect()
+}
+
+/// Decode a V3 encoded swap path, which packs `token (fee token)*` as 20 and 3 byte values.
+pub fn parse_v3_path(path: &[u8]) -> Option<Vec<PathHop>> {
+    if path.len() < 43 || !(path.len() - 20).is_multiple_of(23) {
+        return None
+    }
+
+    Some(
+        path.windows(43)
+            .step_by(23)
+            .map(|hop| PathHop {
+                token_in: Address::from_slice(&hop[0..20]),
+                fee: u32::from_be_bytes([0, hop[20], hop[21], hop[22]]),
+                token_out: Address::from_slice(&hop[23..43]),
+            })
+            .collect(),
+    )
+}
+
+/// Format a pool fee, in hundredths of a basis point, as a percentage.
+fn format_fee(fee: u32) -> String {
+    let fee = format!("{:.4}", fee as f64 / 10000.0);
+    format!("{}%", fee.trim_end_matches('0').trim_end_matches('.'))
+}
+
+/// Render a V3 encoded swap path as a readable series of token hops.
+///
+/// ```
+/// use heimdall_core::decode::uniswap::format_v3_path;
+///
+/// let mut path = vec![0x11; 20];
+/// path.extend_from_slice(&[0x00, 0x01, 0xf4]); // 0.05%
+/// path.extend_from_slice(&[0x22; 20]);
+/// assert_eq!(
+///     format_v3_path(&path),
+///     Some(String::from("0x1111111111111111111111111111111111111111 → (0.05%) → 0x2222222222222222222222222222222222222222"))
+/// );
+/// ```
+pub fn format_v3_path(path: &[u8]) -> Option<String> {
+    let hops = parse_v3_path(path)?;
+    let mut formatted = format!("{:?}", hops.first()?.token_in);
+    for hop in hops {
+        formatted.push_str(&format!(" → ({}) → {:?}", format_fee(hop.fee), hop.token_out));
+    }
+    Some(formatted)
+}
+
+/// Render a V2 swap path as a readable series of token hops.
+pub fn format_v2_path(path: &[Address]) -> String {
+    path.iter().map(|token| format!("{token:?}")).collect::<Vec<String>>().join(" → ")
+}
+
+/// Find and render the swap path within a router call's decoded inputs, either as a V3 encoded
+/// path or a V2 `address[]` path.
+pub fn find_swap_path(inputs: &[Token]) -> Option<String> {
+    inputs.iter().find_map(|input| match input {
+        Token::Bytes(path) => format_v3_path(path),
+        Token::Array(tokens) if tokens.len() > 1 => {
+            let path = tokens
+                .iter()
+                .map(|token| token.clone().into_address())
+                .collect::<Option<Vec<_>>>()?;
+            Some(format_v2_path(&path))
+        }
+        Token::Tuple(fields) => find_swap_path(fields),
+        _ => None,
+    })
+}
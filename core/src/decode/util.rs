@@ -1,5 +1,716 @@
-use ethers::types::Transaction;
-use heimdall_cache::util::encode_hex;
+use std::collections::HashMap;
+
+use async_recursion::async_recursion;
+use ethers::{
+    abi::{decode as decode_abi, AbiEncode, Function, Param, ParamType, StateMutability, Token},
+    types::{Address, Transaction, U256},
+    utils::keccak256,
+};
+use heimdall_cache::{read_cache, store_cache, util::encode_hex};
+use heimdall_common::{
+    ether::{
+        evm::core::types::{display, parse_function_parameters},
+        labels::substitute_labels,
+        rpc::chain_id,
+        signatures::{score_signature, ResolveSelector, ResolvedFunction},
+    },
+    utils::{
+        io::logging::{Logger, TraceFactory},
+        strings::decode_hex,
+    },
+};
+use strsim::normalized_damerau_levenshtein as similarity;
+
+use crate::{
+    decode::{
+        erc4337::{
+            parse_handle_ops, UserOperation, HANDLE_OPS_V06_SIGNATURE, HANDLE_OPS_V07_SIGNATURE,
+        },
+        safe::{
+            parse_signatures, verify_signatures, SafeSignatureKind, SafeTransaction,
+            EXEC_TRANSACTION_SIGNATURE,
+        },
+        seaport::{
+            format_items_table, format_table, order_type_name, parse_seaport_call, SeaportCall,
+            FULFILL_ADVANCED_ORDER_SIGNATURE, FULFILL_ORDER_SIGNATURE, MATCH_ORDERS_SIGNATURE,
+        },
+        uniswap::{
+            find_swap_path, parse_commands, RouterCommand, EXECUTE_SIGNATURES, V2_PATH_SIGNATURES,
+            V3_PATH_SIGNATURES,
+        },
+    },
+    decompile::{decompile, out::abi::ABIStructure, DecompilerArgsBuilder},
+};
+
+/// The maximum depth nested calls are decoded to.
+const MAX_NESTED_CALL_DEPTH: u8 = 8;
+
+/// Signatures of the well-known call formats which are decoded in detail. These, along with the
+/// Uniswap router signatures, are always considered as matches, even if the signature databases
+/// don't know them.
+const KNOWN_SIGNATURES: [&str; 9] = [
+    EXEC_TRANSACTION_SIGNATURE,
+    HANDLE_OPS_V06_SIGNATURE,
+    HANDLE_OPS_V07_SIGNATURE,
+    FULFILL_ORDER_SIGNATURE,
+    FULFILL_ADVANCED_ORDER_SIGNATURE,
+    MATCH_ORDERS_SIGNATURE,
+    "execute(address,uint256,bytes)",
+    "executeBatch(address[],bytes[])",
+    "executeBatch(address[],uint256[],bytes[])",
+];
+
+/// Context about the transaction being decoded, used when decoding well-known call formats.
+#[derive(Debug, Clone, Default)]
+pub struct DecodeContext {
+    /// The RPC provider to use for on-chain lookups. Empty if none is available.
+    pub rpc_url: String,
+    /// The block the decoded transaction was included in, if decoding a transaction hash.
+    pub block_number: Option<u64>,
+}
+
+#[allow(deprecated)]
+/// Filter the potential matches for the given calldata down to the signatures which the calldata
+/// can be decoded with, populating their `decoded_inputs`.
+pub fn match_signatures(
+    potential_matches: &[ResolvedFunction],
+    calldata: &str,
+) -> Vec<ResolvedFunction> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let mut matches: Vec<ResolvedFunction> = Vec::new();
+    if calldata.len() < 8 {
+        return matches
+    }
+    let function_selector = &calldata[0..8];
+    let byte_args = match decode_hex(&calldata[8..]) {
+        Ok(byte_args) => byte_args,
+        Err(_) => return matches,
+    };
+
+    for potential_match in potential_matches {
+        // convert the string inputs into a vector of decoded types
+        let mut inputs: Vec<ParamType> = Vec::new();
+        if let Some(type_) = parse_function_parameters(&potential_match.signature) {
+            for input in type_ {
+                inputs.push(input);
+            }
+        }
+
+        match decode_abi(&inputs, &byte_args) {
+            Ok(result) => {
+                // convert tokens to params
+                let mut params: Vec<Param> = Vec::new();
+                for (i, input) in inputs.iter().enumerate() {
+                    params.push(Param {
+                        name: format!("arg{i}"),
+                        kind: input.to_owned(),
+                        internal_type: None,
+                    });
+                }
+                // build the decoded function to verify it's a match
+                let decoded_function_call = Function {
+                    name: potential_match.name.to_string(),
+                    inputs: params,
+                    outputs: Vec::new(),
+                    constant: None,
+                    state_mutability: StateMutability::NonPayable,
+                }
+                .encode_input(&result);
+                match decoded_function_call {
+                    Ok(decoded_function_call) => {
+                        // decode the function call in trimmed bytes, removing 0s, because contracts
+                        // can use nonstandard sized words and padding is
+                        // hard
+                        let cleaned_bytes = decoded_function_call.encode_hex().replace('0', "");
+                        let decoded_function_call = match cleaned_bytes
+                            .split_once(&function_selector.replace('0', ""))
+                        {
+                            Some(decoded_function_call) => decoded_function_call.1,
+                            None => {
+                                logger.debug(&format!("potential match '{}' ignored. decoded inputs differed from provided calldata.", &potential_match.signature).to_string());
+                                continue
+                            }
+                        };
+
+                        // if the decoded function call matches (95%) the function signature, add it
+                        // to the list of matches
+                        if similarity(decoded_function_call, &calldata[8..].replace('0', "")).abs() >=
+                            0.90
+                        {
+                            let mut found_match = potential_match.clone();
+                            found_match.decoded_inputs = Some(result);
+                            matches.push(found_match);
+                        } else {
+                            logger.debug(&format!("potential match '{}' ignored. decoded inputs differed from provided calldata.", &potential_match.signature).to_string());
+                        }
+                    }
+                    Err(_) => {
+                        logger.debug(
+                            &format!(
+                                "potential match '{}' ignored. type checking failed",
+                                &potential_match.signature
+                            )
+                            .to_string(),
+                        );
+                    }
+                }
+            }
+            Err(_) => {
+                logger.debug(
+                    &format!(
+                        "potential match '{}' ignored. decoding types failed",
+                        &potential_match.signature
+                    )
+                    .to_string(),
+                );
+            }
+        }
+    }
+
+    matches
+}
+
+/// Get the well-known functions matching the given selector.
+pub fn known_functions(selector: &str) -> Vec<ResolvedFunction> {
+    let selector = selector.replacen("0x", "", 1).to_lowercase();
+    KNOWN_SIGNATURES
+        .iter()
+        .chain(EXECUTE_SIGNATURES.iter())
+        .chain(V3_PATH_SIGNATURES.iter())
+        .chain(V2_PATH_SIGNATURES.iter())
+        .filter(|signature| encode_hex(keccak256(signature)[0..4].to_vec()) == selector)
+        .map(|signature| ResolvedFunction {
+            name: signature.split('(').next().unwrap_or_default().to_string(),
+            signature: signature.to_string(),
+            inputs: parse_function_parameters(signature)
+                .unwrap_or_default()
+                .iter()
+                .map(|input| input.to_string())
+                .collect(),
+            decoded_inputs: None,
+        })
+        .collect()
+}
+
+/// Add the well-known functions matching the given selector to `potential_matches`, if they
+/// aren't already present.
+pub fn with_known_functions(
+    selector: &str,
+    mut potential_matches: Vec<ResolvedFunction>,
+) -> Vec<ResolvedFunction> {
+    for function in known_functions(selector) {
+        if !potential_matches.iter().any(|m| m.signature == function.signature) {
+            potential_matches.push(function);
+        }
+    }
+    potential_matches
+}
+
+/// Recover the functions of the contract at the given address by decompiling it, keyed by
+/// selector. Since the decompiler doesn't resolve selectors, the recovered functions are named
+/// `Unresolved_<selector>`, but their argument types reflect how the contract actually reads its
+/// calldata. Recovered ABIs are cached per chain.
+pub async fn recover_contract_functions(
+    address: &str,
+    rpc_url: &str,
+) -> Result<HashMap<String, ResolvedFunction>, Box<dyn std::error::Error>> {
+    let cache_key =
+        format!("decompiled_abi.{}.{}", chain_id(rpc_url).await?, address.to_lowercase());
+    if let Some(functions) = read_cache::<HashMap<String, ResolvedFunction>>(&cache_key) {
+        return Ok(functions)
+    }
+
+    let result = decompile(
+        DecompilerArgsBuilder::new()
+            .target(address.to_string())
+            .rpc_url(rpc_url.to_string())
+            .skip_resolving(true)
+            .non_interactive(true)
+            .build()?,
+    )
+    .await?;
+
+    let functions = result
+        .abi
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|structure| match structure {
+            ABIStructure::Function(function) if function.type_ == "function" => Some(function),
+            _ => None,
+        })
+        .map(|function| {
+            (
+                function.selector(),
+                ResolvedFunction {
+                    name: function.name.clone(),
+                    signature: function.signature(),
+                    inputs: function.inputs.iter().map(|input| input.type_.clone()).collect(),
+                    decoded_inputs: None,
+                },
+            )
+        })
+        .collect::<HashMap<String, ResolvedFunction>>();
+
+    store_cache(&cache_key, &functions, None);
+    Ok(functions)
+}
+
+/// Choose the potential matches for a selector which the target contract's recovered function
+/// also dispatches. Resolved signatures whose argument types agree with the recovered function
+/// are preferred, since they carry a name; otherwise the recovered function itself is used.
+pub fn with_recovered_function(
+    recovered: ResolvedFunction,
+    potential_matches: Vec<ResolvedFunction>,
+) -> Vec<ResolvedFunction> {
+    let agreeing = potential_matches
+        .into_iter()
+        .filter(|function| function.inputs == recovered.inputs)
+        .collect::<Vec<ResolvedFunction>>();
+
+    match agreeing.is_empty() {
+        true => vec![recovered],
+        false => agreeing,
+    }
+}
+
+/// Decode the given calldata without an ABI, returning the highest-scored matching signature.
+/// This never prompts, so it's used for calldata nested within other calls.
+pub async fn decode_calldata(calldata: &str) -> Option<ResolvedFunction> {
+    let calldata = calldata.replacen("0x", "", 1);
+    if calldata.len() < 8 {
+        return None
+    }
+
+    let potential_matches = with_known_functions(
+        &calldata[0..8],
+        ResolvedFunction::resolve(&calldata[0..8]).await.unwrap_or_default(),
+    );
+    let mut matches = match_signatures(&potential_matches, &calldata);
+    matches.sort_by_key(|m| std::cmp::Reverse(score_signature(&m.signature)));
+    matches.into_iter().next()
+}
+
+/// Format decoded inputs into trace messages, one group of lines per input.
+pub fn format_inputs(inputs: &[Token]) -> Vec<Vec<String>> {
+    let mut messages = Vec::new();
+    for (i, input) in inputs.iter().enumerate() {
+        let mut decoded_inputs_as_message = display(vec![input.to_owned()], "           ")
+            .iter()
+            .map(|line| substitute_labels(line))
+            .collect::<Vec<String>>();
+        if decoded_inputs_as_message.is_empty() {
+            break
+        }
+
+        decoded_inputs_as_message[0] = format!(
+            "{} {}:{}{}",
+            if i == 0 { "input" } else { "     " },
+            i,
+            " ".repeat(4 - i.to_string().len()),
+            decoded_inputs_as_message[0].replacen("           ", "", 1)
+        );
+        messages.push(decoded_inputs_as_message);
+    }
+    messages
+}
+
+/// Add the details of well-known call formats to the trace under `parent`, i.e. a Safe's
+/// `execTransaction`, recursively decoding any calls nested within them.
+#[async_recursion]
+pub async fn trace_known_formats(
+    trace: &mut TraceFactory,
+    parent: u32,
+    function: &ResolvedFunction,
+    target: Option<Address>,
+    context: &DecodeContext,
+    depth: u8,
+) {
+    let inputs = match &function.decoded_inputs {
+        Some(inputs) => inputs,
+        None => return,
+    };
+
+    match function.signature.as_str() {
+        EXEC_TRANSACTION_SIGNATURE => {
+            if let Some(transaction) = SafeTransaction::from_tokens(inputs) {
+                trace_safe_transaction(trace, parent, &transaction, target, context, depth).await;
+            }
+        }
+        HANDLE_OPS_V06_SIGNATURE | HANDLE_OPS_V07_SIGNATURE => {
+            if let Some((operations, beneficiary)) = parse_handle_ops(inputs) {
+                trace.br(parent);
+                trace.add_message(parent, line!(), vec![format!("beneficiary: {beneficiary:?}")]);
+                for (i, operation) in operations.iter().enumerate() {
+                    trace_user_operation(trace, parent, i, operation, context, depth).await;
+                }
+            }
+        }
+
+        // smart account entrypoints, i.e. the calldata of a user operation
+        "execute(address,uint256,bytes)" => {
+            if let [Token::Address(to), _, Token::Bytes(calldata)] = inputs.as_slice() {
+                trace_nested_call(trace, parent, *to, calldata, context, depth + 1).await;
+            }
+        }
+        "executeBatch(address[],bytes[])" | "executeBatch(address[],uint256[],bytes[])" => {
+            if let (Some(Token::Array(targets)), Some(Token::Array(calldatas))) =
+                (inputs.first(), inputs.last())
+            {
+                for (to, calldata) in targets.iter().zip(calldatas) {
+                    if let (Token::Address(to), Token::Bytes(calldata)) = (to, calldata) {
+                        trace_nested_call(trace, parent, *to, calldata, context, depth + 1).await;
+                    }
+                }
+            }
+        }
+        FULFILL_ORDER_SIGNATURE | FULFILL_ADVANCED_ORDER_SIGNATURE | MATCH_ORDERS_SIGNATURE => {
+            if let Some(call) = parse_seaport_call(&function.signature, inputs) {
+                trace_seaport_call(trace, parent, &call);
+            }
+        }
+
+        // uniswap routers, whose swap paths and commands are packed into bytes
+        signature if EXECUTE_SIGNATURES.contains(&signature) => {
+            if let [Token::Bytes(commands), Token::Array(command_inputs), ..] = inputs.as_slice() {
+                let command_inputs = command_inputs
+                    .iter()
+                    .filter_map(|input| input.clone().into_bytes())
+                    .collect::<Vec<Vec<u8>>>();
+                trace.br(parent);
+                trace_router_commands(
+                    trace,
+                    parent,
+                    &parse_commands(commands, &command_inputs),
+                    depth,
+                );
+            }
+        }
+        signature
+            if V3_PATH_SIGNATURES.contains(&signature) ||
+                V2_PATH_SIGNATURES.contains(&signature) =>
+        {
+            if let Some(path) = find_swap_path(inputs) {
+                trace.br(parent);
+                trace.add_message(parent, line!(), vec![format!("path: {path}")]);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Add a Seaport call's orders to the trace, rendering their items, criteria resolvers, and
+/// fulfillments as tables.
+fn trace_seaport_call(trace: &mut TraceFactory, parent: u32, call: &SeaportCall) {
+    if let Some(recipient) = call.recipient {
+        trace.br(parent);
+        trace.add_message(parent, line!(), vec![format!("recipient: {recipient:?}")]);
+    }
+
+    for (i, order) in call.orders.iter().enumerate() {
+        let order_trace = trace.add_call(
+            parent,
+            line!(),
+            "Seaport".to_string(),
+            "order".to_string(),
+            vec![i.to_string()],
+            "()".to_string(),
+        );
+        let mut details = vec![
+            format!("offerer:    {:?}", order.offerer),
+            format!("zone:       {:?}", order.zone),
+            format!("order type: {}", order_type_name(order.order_type)),
+            format!("valid:      {} to {}", order.start_time, order.end_time),
+        ];
+        if let Some((numerator, denominator)) = order.fraction {
+            details.push(format!("fraction:   {numerator}/{denominator}"));
+        }
+        details.push(format!("signature:  {} bytes", order.signature.len()));
+        trace.add_message(order_trace, line!(), details);
+        trace.add_message(order_trace, line!(), format_items_table(order));
+    }
+
+    if !call.criteria_resolvers.is_empty() {
+        let mut rows = vec![["order", "side", "item", "identifier", "proof"]
+            .iter()
+            .map(|header| header.to_string())
+            .collect::<Vec<String>>()];
+        for resolver in &call.criteria_resolvers {
+            rows.push(vec![
+                resolver.order_index.to_string(),
+                if resolver.side == 0 { "offer" } else { "consideration" }.to_string(),
+                resolver.index.to_string(),
+                resolver.identifier.to_string(),
+                format!("{} nodes", resolver.proof_length),
+            ]);
+        }
+        trace.br(parent);
+        trace.add_message(parent, line!(), vec!["criteria resolvers:".to_string()]);
+        trace.add_message(parent, line!(), format_table(&rows));
+    }
+
+    if !call.fulfillments.is_empty() {
+        let components = |components: &[(U256, U256)]| {
+            components
+                .iter()
+                .map(|(order, item)| format!("order {order} item {item}"))
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+        trace.br(parent);
+        trace.add_message(
+            parent,
+            line!(),
+            call.fulfillments
+                .iter()
+                .enumerate()
+                .map(|(i, fulfillment)| {
+                    format!(
+                        "{} {}:{}{} → {}",
+                        if i == 0 { "fulfillment" } else { "           " },
+                        i,
+                        " ".repeat(3 - i.to_string().len().min(3)),
+                        components(&fulfillment.offer_components),
+                        components(&fulfillment.consideration_components),
+                    )
+                })
+                .collect(),
+        );
+    }
+}
+
+/// Add a Universal Router's commands to the trace, with their decoded inputs and swap paths.
+fn trace_router_commands(
+    trace: &mut TraceFactory,
+    parent: u32,
+    commands: &[RouterCommand],
+    depth: u8,
+) {
+    for (i, command) in commands.iter().enumerate() {
+        let command_trace = trace.add_call(
+            parent,
+            line!(),
+            "UniversalRouter".to_string(),
+            command.name.to_string(),
+            vec![i.to_string()],
+            "()".to_string(),
+        );
+        if command.allow_revert {
+            trace.add_message(command_trace, line!(), vec!["allow revert: true".to_string()]);
+        }
+
+        let inputs = match &command.inputs {
+            Some(inputs) => inputs,
+            None => {
+                trace.add_message(
+                    command_trace,
+                    line!(),
+                    vec![format!("inputs: {} bytes", command.raw_inputs.len())],
+                );
+                continue
+            }
+        };
+        for message in format_inputs(inputs) {
+            trace.add_message(command_trace, line!(), message);
+        }
+        if let Some(path) = command.swap_path() {
+            trace.add_message(command_trace, line!(), vec![format!("path:    {path}")]);
+        }
+
+        // sub-plans are a nested list of commands, executed together
+        if let [Token::Bytes(sub_commands), Token::Array(sub_inputs)] = inputs.as_slice() {
+            if depth < MAX_NESTED_CALL_DEPTH {
+                let sub_inputs = sub_inputs
+                    .iter()
+                    .filter_map(|input| input.clone().into_bytes())
+                    .collect::<Vec<Vec<u8>>>();
+                trace_router_commands(
+                    trace,
+                    command_trace,
+                    &parse_commands(sub_commands, &sub_inputs),
+                    depth + 1,
+                );
+            }
+        }
+    }
+}
+
+/// Add an ERC-4337 user operation to the trace, decoding its account deployment and calldata.
+async fn trace_user_operation(
+    trace: &mut TraceFactory,
+    parent: u32,
+    index: usize,
+    operation: &UserOperation,
+    context: &DecodeContext,
+    depth: u8,
+) {
+    let (nonce_key, nonce_sequence) = operation.nonce_key_and_sequence();
+    let operation_trace = trace.add_call(
+        parent,
+        line!(),
+        "EntryPoint".to_string(),
+        "userOperation".to_string(),
+        vec![index.to_string()],
+        "()".to_string(),
+    );
+    trace.add_message(
+        operation_trace,
+        line!(),
+        vec![
+            format!("sender:       {:?}", operation.sender),
+            format!("nonce:        {nonce_sequence} (key {nonce_key})"),
+            format!(
+                "gas:          {} call, {} verification, {} pre-verification",
+                operation.call_gas_limit,
+                operation.verification_gas_limit,
+                operation.pre_verification_gas
+            ),
+            format!(
+                "fees:         {} max, {} priority",
+                operation.max_fee_per_gas, operation.max_priority_fee_per_gas
+            ),
+            format!("signature:    {} bytes", operation.signature.len()),
+        ],
+    );
+    if let Some((paymaster, data)) = operation.paymaster() {
+        trace.add_message(
+            operation_trace,
+            line!(),
+            vec![format!("paymaster:    {paymaster:?} ({} bytes of data)", data.len())],
+        );
+    }
+
+    // the account is deployed by calling the factory before the operation is executed
+    if let Some((factory, calldata)) = operation.factory() {
+        trace.add_message(operation_trace, line!(), vec![format!("factory:      {factory:?}")]);
+        trace_nested_call(trace, operation_trace, factory, calldata, context, depth + 1).await;
+    }
+
+    trace_nested_call(
+        trace,
+        operation_trace,
+        operation.sender,
+        &operation.call_data,
+        context,
+        depth + 1,
+    )
+    .await;
+}
+
+/// Add a Safe transaction's parameters, signers, and inner call to the trace.
+async fn trace_safe_transaction(
+    trace: &mut TraceFactory,
+    parent: u32,
+    transaction: &SafeTransaction,
+    safe: Option<Address>,
+    context: &DecodeContext,
+    depth: u8,
+) {
+    trace.br(parent);
+    trace.add_message(
+        parent,
+        line!(),
+        vec![
+            format!("safe to:    {:?}", transaction.to),
+            format!("safe value: {}", transaction.value),
+            format!("operation:  {}", transaction.operation_name()),
+        ],
+    );
+
+    // the signers can only be verified against the safe's state when it was executed
+    let (signatures, owners) = match (safe, context.block_number, context.rpc_url.is_empty()) {
+        (Some(safe), Some(block_number), false) => {
+            verify_signatures(transaction, safe, &context.rpc_url, block_number).await
+        }
+        _ => (parse_signatures(&transaction.signatures), None),
+    };
+    let signers = signatures
+        .iter()
+        .enumerate()
+        .map(|(i, signature)| {
+            let kind = match signature.kind {
+                SafeSignatureKind::Contract => "contract",
+                SafeSignatureKind::ApprovedHash => "approved hash",
+                SafeSignatureKind::EthSign => "eth_sign",
+                SafeSignatureKind::Ecdsa => "ecdsa",
+            };
+            let status = match (&signature.signer, &owners) {
+                (Some(signer), Some(owners)) if owners.contains(signer) => "owner",
+                (Some(_), Some(_)) => "not an owner",
+                _ => "unverified",
+            };
+            format!(
+                "{} {}:{}{} ({kind}, {status})",
+                if i == 0 { "signer" } else { "      " },
+                i,
+                " ".repeat(3 - i.to_string().len().min(3)),
+                match signature.signer {
+                    Some(signer) => format!("{signer:?}"),
+                    None => "unknown".to_string(),
+                },
+            )
+        })
+        .collect::<Vec<String>>();
+    if !signers.is_empty() {
+        trace.add_message(parent, line!(), signers);
+    }
+
+    trace_nested_call(trace, parent, transaction.to, &transaction.data, context, depth + 1).await;
+}
+
+/// Decode calldata sent from within another call, adding it to the trace as a call to `to`.
+#[async_recursion]
+pub async fn trace_nested_call(
+    trace: &mut TraceFactory,
+    parent: u32,
+    to: Address,
+    calldata: &[u8],
+    context: &DecodeContext,
+    depth: u8,
+) {
+    if calldata.is_empty() || depth > MAX_NESTED_CALL_DEPTH {
+        return
+    }
+
+    let calldata = encode_hex(calldata.to_vec());
+    match decode_calldata(&calldata).await {
+        Some(function) => {
+            let call = trace.add_call(
+                parent,
+                line!(),
+                format!("{to:?}"),
+                function.name.clone(),
+                Vec::new(),
+                "()".to_string(),
+            );
+            trace.add_message(call, line!(), vec![format!("signature: {}", function.signature)]);
+            for message in format_inputs(function.decoded_inputs.as_ref().unwrap_or(&Vec::new())) {
+                trace.add_message(call, line!(), message);
+            }
+
+            trace_known_formats(trace, call, &function, Some(to), context, depth).await;
+        }
+        None => {
+            let call = trace.add_call(
+                parent,
+                line!(),
+                format!("{to:?}"),
+                "unknown".to_string(),
+                Vec::new(),
+                "()".to_string(),
+            );
+            trace.add_message(
+                call,
+                line!(),
+                vec![
+                    format!("selector: 0x{}", calldata.get(0..8).unwrap_or(&calldata)),
+                    format!("calldata: {} bytes", calldata.len() / 2),
+                ],
+            );
+        }
+    }
+}
 
 /// Get an explanation of the decoded transaction using the OpenAI API
 pub async fn get_explanation(
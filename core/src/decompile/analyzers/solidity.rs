@@ -18,8 +18,12 @@ use heimdall_common::{
 
 use super::super::{constants::AND_BITMASK_REGEX, precompile::decode_precompile};
 use crate::decompile::{
-    constants::VARIABLE_SIZE_CHECK_REGEX,
-    util::{CalldataFrame, Function, StorageFrame},
+    constants::{BLOCK_COMMENT_PREFIX, VARIABLE_SIZE_CHECK_REGEX, VIA_IR_SIZE_CHECK_REGEX},
+    loops::{classify_loop, truncate_loop_body, LoopShape, RecoveredLoop},
+    util::{
+        is_legacy_throw, is_zero_call_value_check, jump_target_reverts, CalldataFrame, Function,
+        StorageFrame,
+    },
 };
 
 /// Converts a VMTrace to a Function through lexical and syntactic analysis
@@ -47,7 +51,14 @@ pub fn analyze_sol(
 ) -> Function {
     // make a clone of the recursed analysis function
     let mut function = function;
+    let logic_start = function.logic.len();
     let mut jumped_conditional: Option<String> = None;
+    let mut recovered_loop: Option<(RecoveredLoop, String)> = None;
+    let mut call_failure: Option<usize> = None;
+
+    // the length of the function's logic when each JUMPDEST was reached, so loops can be opened
+    // once their shape is known
+    let mut jumpdests: Vec<(u128, usize)> = Vec::new();
 
     // perform analysis on the operations of the current VMTrace branch
     for operation in &vm_trace.operations {
@@ -58,6 +69,10 @@ pub fn analyze_sol(
         let opcode_name = instruction.opcode_details.clone().unwrap().name;
         let opcode_number = instruction.opcode;
 
+        if opcode_name == "JUMPDEST" {
+            jumpdests.push((instruction.instruction, function.logic.len()));
+        }
+
         // if the instruction is a state-accessing instruction, the function is no longer pure
         if function.pure &&
             vec![
@@ -193,10 +208,32 @@ pub fn analyze_sol(
             }
         } else if opcode_name == "JUMPI" {
             // this is an if conditional for the children branches
-            let conditional = instruction.input_operations[1].solidify();
+            let mut conditional = instruction.input_operations[1].solidify();
+
+            // a branch on an external call's success flag handles the call's failure
+            if conditional.replace('!', "") == "success" && vm_trace.children.len() == 2 {
+                let jumps_on_success = conditional.matches('!').count().is_multiple_of(2);
+                if let Some(jump_child) = vm_trace
+                    .children
+                    .iter()
+                    .position(|child| U256::from(child.instruction) == instruction.inputs[0] + 1)
+                {
+                    call_failure = Some(match jumps_on_success {
+                        true => 1 - jump_child,
+                        false => jump_child,
+                    });
+                    continue
+                }
+            }
+
+            // via-IR jumps to a shared revert block when a check fails, so the checked condition
+            // is the negation of the jump condition
+            if function.compiler.via_ir && jump_target_reverts(vm_trace, instruction.inputs[0]) {
+                conditional = negate_conditional(&conditional);
+            }
 
             // remove non-payable check and mark function as non-payable
-            if conditional == "!msg.value" {
+            if is_zero_call_value_check(&conditional) {
                 // this is marking the start of a non-payable function
                 trace.add_info(
                     trace_parent,
@@ -214,21 +251,113 @@ pub fn analyze_sol(
             // is added by the compiler and can be ignored
             if (conditional.contains("msg.data.length") && conditional.contains("0x04")) ||
                 VARIABLE_SIZE_CHECK_REGEX.is_match(&conditional).unwrap_or(false) ||
+                VIA_IR_SIZE_CHECK_REGEX.is_match(&conditional).unwrap_or(false) ||
                 (conditional.replace('!', "") == "success")
             {
                 continue
             }
 
+            // if one of the branches re-enters this jump, it controls a loop
+            if let Some(recovered) = classify_loop(vm_trace) {
+                let continue_conditional = match recovered.continues_on_jump {
+                    true => conditional.clone(),
+                    false => negate_conditional(&conditional),
+                };
+                let start = jumpdests
+                    .iter()
+                    .rev()
+                    .find(|(jumpdest, _)| *jumpdest == recovered.header)
+                    .map(|(_, start)| *start);
+
+                match (&recovered.shape, start) {
+                    (LoopShape::For(counted), _) => {
+                        let counter = loop_counter_name(&function.logic);
+                        let step = match (counted.step == U256::one(), counted.increments) {
+                            (true, true) => format!("{counter}++"),
+                            (true, false) => format!("{counter}--"),
+                            (false, true) => {
+                                format!("{counter} += {}", encode_hex_reduced(counted.step))
+                            }
+                            (false, false) => {
+                                format!("{counter} -= {}", encode_hex_reduced(counted.step))
+                            }
+                        };
+                        function.logic.push(format!(
+                            "for (uint256 {counter} = {}; {counter} {} {}; {step}) {{",
+                            counted.start, counted.comparison, counted.bound
+                        ));
+                    }
+                    (LoopShape::While, _) => {
+                        function.logic.push(format!("while ({continue_conditional}) {{"));
+                    }
+                    (LoopShape::DoWhile, Some(start)) => {
+                        function.logic.insert(start, "do {".to_string());
+                    }
+                    (LoopShape::Infinite, Some(start)) => {
+                        function.logic.insert(start, "while (true) {".to_string());
+                        function
+                            .logic
+                            .push(format!("if ({}) {{", negate_conditional(&continue_conditional)));
+                        function.logic.push("break;".to_string());
+                        function.logic.push("}".to_string());
+                    }
+
+                    // the loop started in an earlier branch, so it can't be structured
+                    _ => {
+                        function.logic.push(format!("if ({conditional}) {{").to_string());
+                        jumped_conditional = Some(conditional.clone());
+                        conditional_map.push(conditional);
+                        continue
+                    }
+                }
+
+                trace.add_info(
+                    trace_parent,
+                    instruction.instruction.try_into().unwrap(),
+                    &format!(
+                        "conditional at instruction {} controls a {} loop.",
+                        instruction.instruction,
+                        match recovered.shape {
+                            LoopShape::For(_) => "for",
+                            LoopShape::While => "while",
+                            LoopShape::DoWhile => "do-while",
+                            LoopShape::Infinite => "infinite",
+                        }
+                    ),
+                );
+                recovered_loop = Some((recovered, continue_conditional));
+                continue
+            }
+
             function.logic.push(format!("if ({conditional}) {{").to_string());
 
             // save a copy of the conditional and add it to the conditional map
             jumped_conditional = Some(conditional.clone());
             conditional_map.push(conditional);
-        } else if opcode_name == "REVERT" {
-            // Safely convert U256 to usize
-            let offset: usize = instruction.inputs[0].try_into().unwrap_or(0);
-            let size: usize = instruction.inputs[1].try_into().unwrap_or(0);
-            let revert_data = memory.read(offset, size);
+        } else if opcode_name == "REVERT" || is_legacy_throw(vm_trace, operation) {
+            // a legacy `throw` reverts without any data
+            let revert_data = match opcode_name == "REVERT" {
+                true => {
+                    // Safely convert U256 to usize
+                    let offset: usize = instruction.inputs[0].try_into().unwrap_or(0);
+                    let size: usize = instruction.inputs[1].try_into().unwrap_or(0);
+                    memory.read(offset, size)
+                }
+                false => Vec::new(),
+            };
+
+            // a failed call's revert is bubbled up by reverting with its returndata
+            if jumped_conditional.is_none() &&
+                opcode_name == "REVERT" &&
+                instruction.input_operations[1].to_string().contains("RETURNDATASIZE") &&
+                function.logic.iter().rev().find(|line| line.starts_with("if")) ==
+                    Some(&String::from("if (!success) {"))
+            {
+                function
+                    .logic
+                    .push(String::from("assembly { revert(add(ret0, 0x20), mload(ret0)) }"));
+                continue
+            }
 
             // (1) if revert_data starts with 0x08c379a0, the folling is an error string
             // abiencoded (2) if revert_data starts with 0x4e487b71, the
@@ -272,6 +401,21 @@ pub fn analyze_sol(
             }
             // handle case with panics
             else if revert_data.starts_with(&decode_hex("4e487b71").unwrap()) {
+                // with checked arithmetic, the conditional guarding the panic was inserted by
+                // the compiler, so we can remove it entirely
+                if function.compiler.checked_arithmetic && jumped_conditional.is_none() {
+                    if let Some(i) = function.logic.iter().rposition(|line| line.starts_with("if"))
+                    {
+                        if let Some(conditional) = conditional_map.pop() {
+                            function.logic.remove(i);
+
+                            // panic code 0x11 marks an arithmetic overflow check
+                            if revert_data.get(35) == Some(&0x11) {
+                                function.overflow_checks.push(conditional);
+                            }
+                        }
+                    }
+                }
                 continue
             }
             // handle case with custom error OR empty revert
@@ -400,6 +544,12 @@ pub fn analyze_sol(
 
             // add the mstore to the function's memory map
             function.memory.insert(key, StorageFrame { value, operations: operation });
+
+            // initializing the free memory pointer is compiler boilerplate
+            if key == U256::from(0x40) && value == function.compiler.free_memory_pointer {
+                continue
+            }
+
             function.logic.push(format!(
                 "memory[{}] = {};",
                 encode_hex_reduced(key),
@@ -460,7 +610,7 @@ pub fn analyze_sol(
             match decode_precompile(
                 instruction.inputs[1],
                 extcalldata_memory.clone(),
-                instruction.input_operations[2].clone(),
+                instruction.input_operations[4].clone(),
             ) {
                 (true, precompile_logic) => {
                     function.logic.push(precompile_logic);
@@ -495,7 +645,7 @@ pub fn analyze_sol(
             match decode_precompile(
                 instruction.inputs[1],
                 extcalldata_memory.clone(),
-                instruction.input_operations[2].clone(),
+                instruction.input_operations[4].clone(),
             ) {
                 (true, precompile_logic) => {
                     function.logic.push(precompile_logic);
@@ -662,6 +812,20 @@ pub fn analyze_sol(
             };
         }
 
+        // record arithmetic on user-controlled values, which checked-arithmetic compilers guard
+        // with overflow checks. arithmetic lacking these checks is unchecked.
+        if function.compiler.checked_arithmetic &&
+            ["ADD", "SUB", "MUL"].contains(&opcode_name) &&
+            is_user_arithmetic(&instruction.input_operations, &instruction.inputs)
+        {
+            if let Some(expression) = instruction.output_operations.first() {
+                function.arithmetic.insert(
+                    expression.solidify(),
+                    instruction.input_operations.iter().map(|operand| operand.solidify()).collect(),
+                );
+            }
+        }
+
         // handle type heuristics
         if [
             "MUL",
@@ -728,6 +892,84 @@ pub fn analyze_sol(
         }
     }
 
+    // name the block which the statements lifted from it came from
+    if function.block_comments && function.logic.len() > logic_start {
+        if let Some(operation) = vm_trace.operations.first() {
+            function.logic.insert(
+                logic_start,
+                format!(
+                    "{BLOCK_COMMENT_PREFIX}{}",
+                    encode_hex_reduced(U256::from(operation.last_instruction.instruction))
+                ),
+            );
+        }
+    }
+
+    // analyze a loop's body once, within the loop, and then the branch which exits it
+    if let Some((recovered, continue_conditional)) = recovered_loop {
+        let jump =
+            vm_trace.operations.last().map(|operation| operation.last_instruction.instruction);
+        let exit = 1 - recovered.body;
+
+        if recovered.shape == LoopShape::DoWhile {
+            function.logic.push(format!("}} while ({continue_conditional});"));
+        } else {
+            let body = truncate_loop_body(
+                &vm_trace.children[recovered.body],
+                jump.unwrap_or_default(),
+                recovered.header,
+            );
+            function = analyze_sol(
+                &body,
+                function,
+                trace,
+                trace_parent,
+                conditional_map,
+                (branch.0 + 1, recovered.body as u8),
+            );
+            function.logic.push("}".to_string());
+        }
+
+        return analyze_sol(
+            &vm_trace.children[exit],
+            function,
+            trace,
+            trace_parent,
+            conditional_map,
+            (branch.0 + 1, exit as u8),
+        )
+    }
+
+    // analyze a call's failure handling within a branch on its success flag, and then the
+    // branch where it succeeded. a failure which only reverts becomes a `require(success)`
+    if let Some(failure) = call_failure {
+        let start = function.logic.len();
+        function.logic.push(String::from("if (!success) {"));
+        function = analyze_sol(
+            &vm_trace.children[failure],
+            function,
+            trace,
+            trace_parent,
+            &mut vec![String::from("success")],
+            (branch.0 + 1, failure as u8),
+        );
+        if function.logic[start] == "if (!success) {" {
+            match function.logic.len() == start + 1 {
+                true => function.logic.truncate(start),
+                false => function.logic.push("}".to_string()),
+            }
+        }
+
+        return analyze_sol(
+            &vm_trace.children[1 - failure],
+            function,
+            trace,
+            trace_parent,
+            conditional_map,
+            (branch.0 + 1, (1 - failure) as u8),
+        )
+    }
+
     // recurse into the children of the VMTrace map
     for (i, child) in vm_trace.children.iter().enumerate() {
         function = analyze_sol(
@@ -757,3 +999,65 @@ pub fn analyze_sol(
 
     function
 }
+
+/// Whether the operands of an arithmetic instruction are user-controlled values, rather than
+/// memory or calldata offsets computed by the compiler.
+fn is_user_arithmetic(operands: &[WrappedOpcode], values: &[U256]) -> bool {
+    let operands = operands.iter().map(|operand| operand.to_string()).collect::<Vec<String>>();
+
+    // calldata offsets of dynamic arguments are computed from the 4byte selector's length
+    if operands
+        .iter()
+        .zip(values)
+        .any(|(operand, value)| operand.starts_with("PUSH") && *value == U256::from(4))
+    {
+        return false
+    }
+
+    operands.iter().any(|operand| {
+        ["SLOAD", "CALLDATALOAD", "CALLVALUE"].iter().any(|opcode| operand.contains(opcode))
+    }) && !operands.iter().any(|operand| {
+        ["SHA3", "MLOAD", "MSIZE", "CALLDATASIZE"].iter().any(|opcode| operand.contains(opcode))
+    })
+}
+
+/// The name of a counter for a new for-loop, i.e. `i`, or `j` within a for-loop over `i`.
+fn loop_counter_name(logic: &[String]) -> String {
+    // track which of the blocks open at the end of the logic are for-loops
+    let mut blocks: Vec<bool> = Vec::new();
+    for line in logic {
+        if line.starts_with('}') {
+            blocks.pop();
+        }
+        if line.ends_with('{') {
+            blocks.push(line.starts_with("for ("));
+        }
+    }
+
+    let depth = blocks.iter().filter(|is_for| **is_for).count().min(17) as u8;
+    char::from(b'i' + depth).to_string()
+}
+
+/// Negate a solidified conditional, removing a leading negation rather than nesting another.
+fn negate_conditional(conditional: &str) -> String {
+    if let Some(inner) = conditional.strip_prefix("!(").and_then(|inner| inner.strip_suffix(')')) {
+        // only strip the parentheses if they enclose the entire conditional
+        let mut depth = 0;
+        if inner.chars().all(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+            depth >= 0
+        }) {
+            return inner.to_string()
+        }
+    }
+
+    match conditional.strip_prefix('!') {
+        Some(inner) if !inner.contains(' ') => inner.to_string(),
+        _ if conditional.contains(' ') => format!("!({conditional})"),
+        _ => format!("!{conditional}"),
+    }
+}
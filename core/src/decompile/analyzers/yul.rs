@@ -146,6 +146,16 @@ pub fn analyze_yul(
             // this is an if conditional for the children branches
             let conditional = instruction.input_operations[1].yulify();
 
+            // this is marking the start of a non-payable function. via-IR instead jumps to a
+            // shared revert block when there is call value
+            if conditional == "iszero(callvalue())" ||
+                (function.compiler.via_ir &&
+                    conditional == "callvalue()" &&
+                    jump_target_reverts(vm_trace, instruction.inputs[0]))
+            {
+                function.payable = false;
+            }
+
             function.logic.push(format!("if {conditional} {{").to_string());
             jumped_conditional = Some(conditional.clone());
             conditional_map.push(conditional);
@@ -179,12 +189,10 @@ pub fn analyze_yul(
                             .collect::<Vec<&str>>()[0]
                             .to_string();
 
-                        // we can negate the conditional to get the revert logic
-                        // TODO: make this a require statement, if revert is rlly gross but its
-                        // technically correct       I just ran into
-                        // issues with ending bracket matching
+                        // yul has no else branch, so the remaining logic is moved into a
+                        // plain block following the revert
                         function.logic[i] = format!(
-                            "if {conditional} {{ revert({}, {}); }} else {{",
+                            "if {conditional} {{ revert({}, {}) }} {{",
                             instruction.input_operations[0].yulify(),
                             instruction.input_operations[1].yulify()
                         );
@@ -234,19 +242,22 @@ pub fn analyze_yul(
                 encode_hex_reduced(key),
                 instruction.input_operations[1].yulify()
             ));
-        } else if [
-            "STATICCALL",
-            "CALL",
-            "DELEGATECALL",
-            "CALLCODE",
-            "CREATE",
-            "CREATE2",
-            "CALLDATACOPY",
-            "CODECOPY",
-            "EXTCODECOPY",
-            "RETURNDATACOPY",
-        ]
-        .contains(&opcode_name)
+        } else if ["STATICCALL", "CALL", "DELEGATECALL", "CALLCODE", "CREATE", "CREATE2"]
+            .contains(&opcode_name)
+        {
+            // the returned value must be discarded when used as a statement
+            function.logic.push(format!(
+                "pop({}({}))",
+                opcode_name.to_lowercase(),
+                instruction
+                    .input_operations
+                    .iter()
+                    .map(|x| x.yulify())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        } else if ["CALLDATACOPY", "CODECOPY", "EXTCODECOPY", "RETURNDATACOPY"]
+            .contains(&opcode_name)
         {
             function.logic.push(format!(
                 "{}({})",
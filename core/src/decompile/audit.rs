@@ -0,0 +1,140 @@
+use ethers::utils::keccak256;
+use heimdall_common::utils::strings::{decode_hex, encode_hex};
+use serde::Serialize;
+
+/// The kind of decision recorded in an [`AuditTrail`].
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditKind {
+    /// A setting the decompilation ran with, needed to reproduce it.
+    Input,
+    /// A heuristic which fired, changing how the contract was analyzed.
+    Heuristic,
+    /// A signature chosen for a function, error, or event selector, or left unresolved.
+    Signature,
+    /// A function whose symbolic execution was cut short by the execution limits.
+    Pruning,
+    /// A type inferred for a storage slot.
+    StorageType,
+}
+
+/// A single decision made during decompilation.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub kind: AuditKind,
+    /// What the decision concerns, e.g. a selector or storage slot.
+    pub subject: String,
+    /// The outcome, e.g. the chosen signature.
+    pub decision: String,
+    /// Why the outcome was chosen over the alternatives.
+    pub reason: String,
+}
+
+/// A machine-readable record of the nontrivial decisions made while decompiling a contract, such
+/// as which signatures were chosen and why, which functions were pruned, and which heuristics
+/// fired, so that decompiled output can be explained and reproduced.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditTrail {
+    /// The version of heimdall which made the decisions.
+    pub version: String,
+    pub target: String,
+    /// The keccak256 hash of the decompiled bytecode, i.e. its on-chain code hash.
+    pub code_hash: String,
+    pub entries: Vec<AuditEntry>,
+}
+
+impl AuditTrail {
+    pub fn new(target: &str, bytecode: &str) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            target: target.to_string(),
+            code_hash: format!(
+                "0x{}",
+                encode_hex(keccak256(decode_hex(bytecode).unwrap_or_default()).to_vec())
+            ),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Record a decision.
+    pub fn record(&mut self, kind: AuditKind, subject: &str, decision: &str, reason: &str) {
+        self.entries.push(AuditEntry {
+            kind,
+            subject: subject.to_string(),
+            decision: decision.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Record the signature chosen for a selector from its `candidates`, which are ordered by
+    /// preference, or that it was left unresolved if there were none.
+    pub fn record_signature(
+        &mut self,
+        selector: &str,
+        candidates: &[String],
+        chosen: Option<&String>,
+        interactive: bool,
+    ) {
+        let (decision, reason) = match (chosen, candidates.len()) {
+            (None, 0) => (String::from("unresolved"), String::from("no signatures were found")),
+            (None, _) => (
+                String::from("unresolved"),
+                format!("none of {} signatures were selected", candidates.len()),
+            ),
+            (Some(chosen), 1) => (chosen.clone(), String::from("the only matching signature")),
+            (Some(chosen), count) if interactive => {
+                (chosen.clone(), format!("selected by the user from {count} matching signatures"))
+            }
+            (Some(chosen), count) => (
+                chosen.clone(),
+                format!(
+                    "the highest scoring of {count} matching signatures, over {}",
+                    candidates
+                        .iter()
+                        .filter(|candidate| *candidate != chosen)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            ),
+        };
+        self.record(AuditKind::Signature, selector, &decision, &reason);
+    }
+
+    /// The entries of the given kind.
+    pub fn entries_of(&self, kind: AuditKind) -> Vec<&AuditEntry> {
+        self.entries.iter().filter(|entry| entry.kind == kind).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_signature() {
+        let mut audit = AuditTrail::new("0x00", "00");
+        let candidates = vec![
+            String::from("transfer(address,uint256)"),
+            String::from("many_msg_babbage(bytes1)"),
+        ];
+        audit.record_signature("a9059cbb", &candidates, candidates.first(), false);
+        audit.record_signature("a9059cbb", &candidates, candidates.get(1), true);
+        audit.record_signature("deadbeef", &[], None, false);
+
+        let signatures = audit.entries_of(AuditKind::Signature);
+        assert_eq!(signatures[0].decision, "transfer(address,uint256)");
+        assert_eq!(
+            signatures[0].reason,
+            "the highest scoring of 2 matching signatures, over many_msg_babbage(bytes1)"
+        );
+        assert!(signatures[1].reason.starts_with("selected by the user"));
+        assert_eq!(signatures[2].decision, "unresolved");
+
+        // the hash of empty code
+        assert_eq!(
+            AuditTrail::new("0x00", "").code_hash,
+            "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+}
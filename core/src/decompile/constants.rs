@@ -1,6 +1,10 @@
 use fancy_regex::Regex;
 use lazy_static::lazy_static;
 
+/// The prefix of the comments which name the control flow graph block the statements below them
+/// were lifted from, e.g. `// block 0x1d`.
+pub const BLOCK_COMMENT_PREFIX: &str = "// block ";
+
 lazy_static! {
 
     /// The following regex is used as a detector for AND bitmasks
@@ -39,6 +43,12 @@ lazy_static! {
     /// used to detect compiler size checks
     pub static ref VARIABLE_SIZE_CHECK_REGEX: Regex = Regex::new(r"!?\(?0(x01)? < [a-zA-Z0-9_\[\]]+\.length\)?").unwrap();
 
+    /// used to detect via-IR calldata size checks, which add ~0x03 to msg.data.length
+    pub static ref VIA_IR_SIZE_CHECK_REGEX: Regex = Regex::new(r"0xf{63}c \+ msg\.data\.length|msg\.data\.length \+ 0xf{63}c").unwrap();
+
+    /// used to detect checks that no call value was sent, i.e. `!msg.value` or `0 == msg.value`
+    pub static ref ZERO_CALL_VALUE_CHECK_REGEX: Regex = Regex::new(r"!msg\.value\b|!\(msg\.value\)|\b0(x0+)? == msg\.value\b|\bmsg\.value == 0(x0+)?\b").unwrap();
+
     /// the static header for decompiled solidity contracts
     pub static ref DECOMPILED_SOURCE_HEADER_SOL: String =
 "// SPDX-License-Identifier: MIT
@@ -74,7 +84,6 @@ pragma solidity >=0.8.0;
 ///                       https://heimdall.rs
 
 object \"DecompiledContract\" {
-object \"runtime\" {
 code {
 
 function selector() -> s {
@@ -82,9 +91,18 @@ s := div(calldataload(0), 0x1000000000000000000000000000000000000000000000000000
 }
 
 function castToAddress(x) -> a {
-a := and(x, 0xffffffffffffffffffffffffffffffffffffffff)
+a := and(x, sub(shl(160, 1), 1))
 }
 
 switch selector()".to_string();
 
+    /// detects hardcoded addresses, which are redacted before sending code to an LLM
+    pub static ref REDACT_ADDRESS_REGEX: Regex = Regex::new(r"0x[a-fA-F0-9]{40}(?![a-fA-F0-9])").unwrap();
+
+    /// detects string literals, which are redacted before sending code to an LLM
+    pub static ref REDACT_STRING_REGEX: Regex = Regex::new(r#""(?:[^"\\]|\\.)*""#).unwrap();
+
+    /// detects constants longer than 8 bytes, such as hashes, which are redacted before sending
+    /// code to an LLM
+    pub static ref REDACT_CONSTANT_REGEX: Regex = Regex::new(r"0x[a-fA-F0-9]{17,}").unwrap();
 }
@@ -0,0 +1,223 @@
+use std::collections::{BTreeMap, HashMap};
+
+use ethers::prelude::U256;
+use heimdall_common::{
+    ether::{
+        compiler::CompilerHeuristics,
+        evm::{
+            core::vm::VM,
+            ext::exec::{ExecutionLimits, VMTrace},
+        },
+        segments::CreationCode,
+        signatures::ResolvedFunction,
+    },
+    utils::{
+        io::logging::TraceFactory,
+        strings::{decode_hex, encode_hex, encode_hex_reduced},
+    },
+};
+
+use super::{analyzers::solidity::analyze_sol, util::Function, PARTIAL_ANALYSIS_NOTICE};
+
+/// A single word of the ABI-encoded arguments appended to creation code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConstructorArgument {
+    pub index: usize,
+    /// The offset of the word within the constructor arguments, in bytes.
+    pub offset: usize,
+    pub value: String,
+    pub potential_types: Vec<String>,
+}
+
+/// Guess the types a single ABI-encoded word may hold, from most to least likely.
+fn potential_types(word: &[u8]) -> Vec<String> {
+    let types: &[&str] = if word.iter().all(|byte| *byte == 0) {
+        &["uint256"]
+    } else if word[..31].iter().all(|byte| *byte == 0) && word[31] == 1 {
+        &["bool", "uint256"]
+    } else if word[..12].iter().all(|byte| *byte == 0) && word[12..16].iter().any(|byte| *byte != 0)
+    {
+        &["address", "uint160"]
+    } else if word[0] != 0 && word[31] == 0 {
+        &["bytes32", "uint256"]
+    } else {
+        &["uint256", "bytes32"]
+    };
+
+    types.iter().map(|kind| kind.to_string()).collect()
+}
+
+/// Split the ABI-encoded constructor arguments into words, guessing the type of each. Trailing
+/// bytes which don't form a complete word are ignored.
+///
+/// ```
+/// use heimdall_core::decompile::constructor::constructor_argument_layout;
+///
+/// let layout = constructor_argument_layout(&format!(
+///     "{:0>64}{:0>64}",
+///     "c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2", "01"
+/// ));
+///
+/// assert_eq!(layout[0].potential_types[0], "address");
+/// assert_eq!(layout[1].offset, 32);
+/// assert_eq!(layout[1].potential_types[0], "bool");
+/// ```
+pub fn constructor_argument_layout(arguments: &str) -> Vec<ConstructorArgument> {
+    let bytes = decode_hex(arguments).unwrap_or_default();
+
+    bytes
+        .chunks_exact(32)
+        .enumerate()
+        .map(|(index, word)| ConstructorArgument {
+            index,
+            offset: index * 32,
+            value: format!("0x{}", encode_hex(word.to_vec())),
+            potential_types: potential_types(word),
+        })
+        .collect()
+}
+
+/// Find the memory offsets which are written to within the copy of the runtime code the init
+/// code returns, mapped to their offset within the runtime code. These are the contract's
+/// immutable variables.
+fn find_immutables(
+    vm_trace: &VMTrace,
+    runtime_offset: usize,
+    mut runtime_copy: Option<(U256, U256)>,
+    immutables: &mut BTreeMap<U256, U256>,
+) {
+    for state in &vm_trace.operations {
+        let instruction = &state.last_instruction;
+        if instruction.opcode == 0x39 && instruction.inputs[1] == U256::from(runtime_offset) {
+            runtime_copy = Some((instruction.inputs[0], instruction.inputs[2]));
+        } else if let (0x52, Some((destination, size))) = (instruction.opcode, runtime_copy) {
+            let key = instruction.inputs[0];
+            if key >= destination && key < destination.saturating_add(size) {
+                immutables.insert(key, key - destination);
+            }
+        }
+    }
+
+    for child in &vm_trace.children {
+        find_immutables(child, runtime_offset, runtime_copy, immutables);
+    }
+}
+
+/// Symbolically execute the init code of the creation code, and recover the constructor's logic:
+/// its storage initialization, immutable assignments, and access checks. The constructor's
+/// parameters are taken from the layout of the arguments appended to the creation code.
+pub fn analyze_constructor(
+    creation_code: &CreationCode,
+    limits: &ExecutionLimits,
+    heuristics: CompilerHeuristics,
+    trace: &mut TraceFactory,
+    trace_parent: u32,
+) -> (Function, Vec<ConstructorArgument>) {
+    let mut evm = VM::new(
+        creation_code.bytecode(),
+        String::from("0x"),
+        String::from("0x6865696d64616c6c000000000061646472657373"),
+        String::from("0x6865696d64616c6c0000000000006f726967696e"),
+        String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+        0,
+        u128::MAX,
+    );
+    evm.limits = limits.clone();
+    let rejects_call_value = evm.rejects_call_value();
+
+    let func_analysis_trace = trace.add_call(
+        trace_parent,
+        line!(),
+        "heimdall".to_string(),
+        "analyze".to_string(),
+        vec![String::from("constructor")],
+        "()".to_string(),
+    );
+
+    let (map, _) = evm.symbolic_exec();
+    let mut function = analyze_sol(
+        &map,
+        Function {
+            selector: String::from("constructor"),
+            entry_point: 0,
+            arguments: HashMap::new(),
+            storage: HashMap::new(),
+            memory: HashMap::new(),
+            returns: None,
+            logic: Vec::new(),
+            events: HashMap::new(),
+            errors: HashMap::new(),
+            resolved_function: None,
+            indent_depth: 0,
+            notices: Vec::new(),
+            pure: true,
+            view: true,
+            payable: true,
+            compiler: heuristics,
+            arithmetic: HashMap::new(),
+            overflow_checks: Vec::new(),
+
+            // the constructor isn't part of the runtime code's control flow graph
+            block_comments: false,
+        },
+        trace,
+        func_analysis_trace,
+        &mut Vec::new(),
+        (0, 0),
+    );
+    function.resolve_payable(rejects_call_value);
+    if map.is_pruned() {
+        function.notices.push(PARTIAL_ANALYSIS_NOTICE.to_string());
+    }
+
+    // name the immutable variables written into the runtime code before it is returned
+    let mut immutables = BTreeMap::new();
+    find_immutables(&map, creation_code.runtime_offset(), None, &mut immutables);
+    let immutables = immutables
+        .into_iter()
+        .enumerate()
+        .map(|(index, (key, runtime_offset))| {
+            function.notices.push(format!(
+                "immutable_{index} is embedded at offset {runtime_offset} of the runtime code."
+            ));
+            (format!("memory[{}] = ", encode_hex_reduced(key)), format!("immutable_{index} = "))
+        })
+        .collect::<Vec<(String, String)>>();
+
+    // copying and returning the runtime code is deployment boilerplate, not constructor logic
+    let runtime_copy = format!("this.code[{}:", creation_code.runtime_offset());
+    function.returns = None;
+    function.logic = function
+        .logic
+        .into_iter()
+        .filter(|line| !line.contains(&runtime_copy) && !line.trim().starts_with("return "))
+        .map(|line| {
+            match immutables.iter().find(|(memory_write, _)| line.trim().starts_with(memory_write))
+            {
+                Some((memory_write, immutable_write)) => {
+                    line.replacen(memory_write, immutable_write, 1)
+                }
+                None => line,
+            }
+        })
+        .collect();
+
+    // the constructor's parameters are the words of its appended arguments
+    let layout = constructor_argument_layout(&creation_code.constructor_arguments);
+    let inputs =
+        layout.iter().map(|argument| argument.potential_types[0].clone()).collect::<Vec<String>>();
+    for argument in &layout {
+        function.notices.push(format!(
+            "arg{} is encoded at offset {} of the constructor arguments, with value {}.",
+            argument.index, argument.offset, argument.value
+        ));
+    }
+    function.resolved_function = Some(ResolvedFunction {
+        name: String::from("constructor"),
+        signature: format!("constructor({})", inputs.join(",")),
+        inputs,
+        decoded_inputs: None,
+    });
+
+    (function, layout)
+}
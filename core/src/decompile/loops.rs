@@ -0,0 +1,300 @@
+use ethers::types::U256;
+use heimdall_common::{
+    ether::evm::{
+        core::opcodes::{WrappedInput, WrappedOpcode},
+        ext::exec::VMTrace,
+    },
+    utils::strings::encode_hex_reduced,
+};
+
+/// Opcodes which are always decompiled to a statement, rather than only feeding into one. A loop
+/// which executes any of these before checking its condition isn't condition-first.
+const STATEMENT_OPCODES: [u8; 13] =
+    [0x55, 0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xf0, 0xf1, 0xf2, 0xf4, 0xf5, 0xfa, 0xff];
+
+/// A loop whose condition compares a counter against a bound, with the counter stepped by a
+/// constant each iteration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CountedLoop {
+    /// The counter's value before the first iteration.
+    pub start: String,
+    /// The comparison which keeps the loop running, with the counter on its left-hand side.
+    pub comparison: String,
+    /// The value the counter is compared against.
+    pub bound: String,
+    /// The constant the counter is stepped by each iteration.
+    pub step: U256,
+    /// Whether the counter is incremented, rather than decremented.
+    pub increments: bool,
+}
+
+/// The shape of a loop, which determines the construct it is decompiled to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LoopShape {
+    /// A condition-first loop over a counter, i.e. `for (uint256 i = 0; i < n; i++) { .. }`.
+    For(CountedLoop),
+    /// A loop which checks its condition before each iteration, i.e. `while (..) { .. }`.
+    While,
+    /// A loop which checks its condition after each iteration, i.e. `do { .. } while (..);`.
+    DoWhile,
+    /// A loop without a condition of its own, which is exited by a `break` within its body, i.e.
+    /// `while (true) { .. }`.
+    Infinite,
+}
+
+/// A loop recovered from a conditional jump which symbolic execution re-entered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredLoop {
+    pub shape: LoopShape,
+    /// The index of the branch which continues the loop, rather than exiting it.
+    pub body: usize,
+    /// Whether the loop continues when the jump is taken, rather than when it falls through.
+    pub continues_on_jump: bool,
+    /// The instruction each iteration starts at.
+    pub header: u128,
+}
+
+/// Classify the loop controlled by the conditional jump which ends `vm_trace`, if symbolic
+/// execution found that one of its branches re-enters it.
+///
+/// The jump is re-entered by one branch, the loop's body, while the other exits the loop. If the
+/// jump targets an earlier instruction which its body starts at, the condition is checked after
+/// each iteration. Otherwise, each iteration starts where the paths entering the loop and
+/// re-entering it converge, and the loop is condition-first unless statements are executed before
+/// the jump, in which case the jump is a `break` out of an infinite loop.
+pub fn classify_loop(vm_trace: &VMTrace) -> Option<RecoveredLoop> {
+    let jump = &vm_trace.operations.last()?.last_instruction;
+    if jump.opcode != 0x57 || vm_trace.children.len() != 2 {
+        return None
+    }
+
+    let body = vm_trace.children.iter().position(|child| reenters(child, jump.instruction))?;
+    let continues_on_jump = U256::from(vm_trace.children[body].instruction) == jump.inputs[0] + 1;
+
+    // a backwards jump into the body is checked after each iteration
+    if continues_on_jump && jump.inputs[0] < U256::from(jump.instruction) {
+        return Some(RecoveredLoop {
+            shape: LoopShape::DoWhile,
+            body,
+            continues_on_jump,
+            header: jump.inputs[0].as_u128() + 1,
+        })
+    }
+
+    // find where the paths entering and re-entering the loop converge
+    let reentry = find_reentry(&vm_trace.children[body], jump.instruction)?;
+    let converged = vm_trace
+        .operations
+        .iter()
+        .rev()
+        .zip(reentry.operations.iter().rev())
+        .take_while(|(entry, reentry)| {
+            entry.last_instruction.instruction == reentry.last_instruction.instruction
+        })
+        .count();
+    if converged >= vm_trace.operations.len() || converged >= reentry.operations.len() {
+        return None
+    }
+    let iteration = &vm_trace.operations[vm_trace.operations.len() - converged..];
+    let header = iteration[0].last_instruction.instruction;
+
+    let shape = if iteration
+        .iter()
+        .any(|operation| STATEMENT_OPCODES.contains(&operation.last_instruction.opcode))
+    {
+        LoopShape::Infinite
+    } else {
+        let reentry_jump = &reentry.operations.last()?.last_instruction;
+        match counted_loop(
+            &jump.input_operations[1],
+            &reentry_jump.input_operations[1],
+            continues_on_jump,
+        ) {
+            Some(counted) => LoopShape::For(counted),
+            None => LoopShape::While,
+        }
+    };
+
+    Some(RecoveredLoop { shape, body, continues_on_jump, header })
+}
+
+/// Copy the body of a loop, cutting each path which re-enters the loop at the start of its next
+/// iteration, so that the body is decompiled once.
+pub fn truncate_loop_body(body: &VMTrace, jump: u128, header: u128) -> VMTrace {
+    if ends_with_jump(body, jump) {
+        let end = body
+            .operations
+            .iter()
+            .rposition(|operation| operation.last_instruction.instruction == header)
+            .unwrap_or(0);
+        return VMTrace {
+            operations: body.operations[..end].to_vec(),
+            children: Vec::new(),
+            loop_jump: None,
+            ..body.clone()
+        }
+    }
+
+    VMTrace {
+        operations: body.operations.clone(),
+        children: body
+            .children
+            .iter()
+            .map(|child| truncate_loop_body(child, jump, header))
+            .collect(),
+        ..body.clone()
+    }
+}
+
+/// Whether symbolic execution stopped any path within `vm_trace` because it re-entered the loop
+/// controlled by the jump at the given instruction.
+fn reenters(vm_trace: &VMTrace, jump: u128) -> bool {
+    vm_trace.loop_jump == Some(jump) || vm_trace.children.iter().any(|child| reenters(child, jump))
+}
+
+/// Whether the path ends at the jump at the given instruction.
+fn ends_with_jump(vm_trace: &VMTrace, jump: u128) -> bool {
+    vm_trace.operations.last().is_some_and(|operation| {
+        operation.last_instruction.opcode == 0x57 && operation.last_instruction.instruction == jump
+    })
+}
+
+/// Find the first path within the loop's body which reaches its jump again.
+fn find_reentry(vm_trace: &VMTrace, jump: u128) -> Option<&VMTrace> {
+    if ends_with_jump(vm_trace, jump) {
+        return Some(vm_trace)
+    }
+    vm_trace.children.iter().find_map(|child| find_reentry(child, jump))
+}
+
+/// Detect a counter within a loop's condition, by comparing the condition when the loop is
+/// entered with the condition when it's re-entered. A counter is the side of the comparison which
+/// was stepped by a constant, while the bound is the side which didn't change.
+fn counted_loop(
+    entry: &WrappedOpcode,
+    reentry: &WrappedOpcode,
+    continues_on_jump: bool,
+) -> Option<CountedLoop> {
+    // the loop continues on the comparison, or its negation
+    let (mut entry, mut reentry, mut negated) = (entry, reentry, !continues_on_jump);
+    while entry.opcode.code == 0x15 && reentry.opcode.code == 0x15 {
+        entry = as_opcode(entry.inputs.first()?)?;
+        reentry = as_opcode(reentry.inputs.first()?)?;
+        negated = !negated;
+    }
+    if !(0x10..=0x13).contains(&entry.opcode.code) || entry.opcode.code != reentry.opcode.code {
+        return None
+    }
+
+    let counter = (0..2).find(|&side| {
+        entry.inputs.get(1 - side) == reentry.inputs.get(1 - side) &&
+            entry.inputs.get(side) != reentry.inputs.get(side)
+    })?;
+    let (start, next) = (&entry.inputs[counter], &reentry.inputs[counter]);
+    let (step, increments) = match (constant(start), constant(next)) {
+        (Some(start), Some(next)) if next > start => (next - start, true),
+        (Some(start), Some(next)) => (start - next, false),
+        _ => {
+            let next = as_opcode(next)?;
+            match (next.opcode.code, next.inputs.as_slice()) {
+                (0x01, [a, b]) if a == start => (constant(b)?, true),
+                (0x01, [a, b]) if b == start => (constant(a)?, true),
+                (0x03, [a, b]) if a == start => (constant(b)?, false),
+                _ => return None,
+            }
+        }
+    };
+
+    // LT and SLT keep the loop running while the left-hand side is the smaller
+    let less_than = matches!(entry.opcode.code, 0x10 | 0x12) == (counter == 0);
+    let comparison = match (less_than, negated) {
+        (true, false) => "<",
+        (true, true) => ">=",
+        (false, false) => ">",
+        (false, true) => "<=",
+    };
+
+    Some(CountedLoop {
+        start: solidify_input(start),
+        comparison: comparison.to_string(),
+        bound: solidify_input(&entry.inputs[1 - counter]),
+        step,
+        increments,
+    })
+}
+
+fn as_opcode(input: &WrappedInput) -> Option<&WrappedOpcode> {
+    match input {
+        WrappedInput::Opcode(opcode) => Some(opcode),
+        WrappedInput::Raw(_) => None,
+    }
+}
+
+/// The value of a constant input, i.e. a raw value or a PUSH instruction.
+fn constant(input: &WrappedInput) -> Option<U256> {
+    match input {
+        WrappedInput::Raw(value) => Some(*value),
+        WrappedInput::Opcode(opcode) if (0x5f..=0x7f).contains(&opcode.opcode.code) => {
+            match opcode.inputs.first() {
+                Some(WrappedInput::Raw(value)) => Some(*value),
+                _ => Some(U256::zero()),
+            }
+        }
+        WrappedInput::Opcode(_) => None,
+    }
+}
+
+fn solidify_input(input: &WrappedInput) -> String {
+    match input {
+        WrappedInput::Raw(value) => encode_hex_reduced(*value),
+        WrappedInput::Opcode(opcode) => {
+            let solidified = opcode.solidify();
+            match solidified.contains(' ') {
+                true => format!("({solidified})"),
+                false => solidified,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(value: u64) -> WrappedInput {
+        WrappedInput::Opcode(WrappedOpcode::new(0x60, vec![WrappedInput::Raw(U256::from(value))]))
+    }
+
+    fn calldataload() -> WrappedInput {
+        WrappedInput::Opcode(WrappedOpcode::new(0x35, vec![push(4)]))
+    }
+
+    #[test]
+    fn test_counted_loop() {
+        // !(i < arg0), where the loop continues when the jump falls through
+        let condition = |counter: WrappedInput| {
+            WrappedOpcode::new(
+                0x15,
+                vec![WrappedInput::Opcode(WrappedOpcode::new(0x10, vec![counter, calldataload()]))],
+            )
+        };
+        let counted = counted_loop(&condition(push(0)), &condition(push(1)), false).unwrap();
+        assert_eq!(counted.start, "0");
+        assert_eq!(counted.comparison, "<");
+        assert_eq!(counted.bound, "arg0");
+        assert_eq!((counted.step, counted.increments), (U256::one(), true));
+
+        // arg0 > i, stepped down by 2 from arg1
+        let arg1 = WrappedInput::Opcode(WrappedOpcode::new(0x35, vec![push(0x24)]));
+        let condition =
+            |counter: WrappedInput| WrappedOpcode::new(0x11, vec![calldataload(), counter]);
+        let next = WrappedInput::Opcode(WrappedOpcode::new(0x03, vec![arg1.clone(), push(2)]));
+        let counted = counted_loop(&condition(arg1), &condition(next), true).unwrap();
+        assert_eq!(counted.comparison, "<");
+        assert_eq!((counted.step, counted.increments), (U256::from(2), false));
+
+        // a condition which didn't change isn't counted
+        let condition = condition(push(0));
+        assert!(counted_loop(&condition, &condition, true).is_none());
+    }
+}
@@ -1,28 +1,59 @@
 pub mod analyzers;
+pub mod audit;
 pub mod constants;
+pub mod constructor;
+pub mod loops;
 pub mod out;
 pub mod precompile;
+pub mod proxy;
 pub mod resolve;
 pub mod util;
+pub mod verify;
 
 use crate::{
     decompile::{
         analyzers::{solidity::analyze_sol, yul::analyze_yul},
-        out::{abi::build_abi, solidity::build_solidity_output, yul::build_yul_output},
+        audit::{AuditKind, AuditTrail},
+        constructor::analyze_constructor,
+        out::{
+            abi::build_abi,
+            add_warnings_to_header,
+            naming::{suggest_function_names, suggest_heuristic_names},
+            readability::improve_readability,
+            solidity::build_solidity_output,
+            yul::build_yul_output,
+        },
+        proxy::{find_implementation, merge_proxy_abi, merge_proxy_source, ProxyImplementation},
         resolve::*,
         util::*,
+        verify::{verify_output, VerificationReport},
     },
     disassemble::{disassemble, DisassemblerArgs},
+    plugins::{load_plugins, run_plugins, AnalysisInput, Finding},
 };
 
 use derive_builder::Builder;
+use ethers::types::U256;
 use heimdall_common::{
     ether::{
-        compiler::detect_compiler,
-        rpc::get_code,
+        compiler::{compiler_heuristics, detect_compiler},
+        dead_code::DeadCodeAnalysis,
+        eof::{is_eof, parse_eof},
+        ether_flow::EtherFlow,
+        metadata::{decode_metadata, fetch_metadata_file, ContractMetadata},
+        metamorphic::{detect_metamorphic, MetamorphicReport},
+        rpc::get_code_at_block,
+        segments::{find_embedded_contracts, split_creation_code},
         selectors::{find_function_selectors, resolve_selectors},
+        storage::StorageType,
+        target::{select_target_vm, TargetVm},
+    },
+    utils::{
+        crash::set_phase,
+        events::{emit, ProgressEvent},
+        interrupt::is_interrupted,
+        strings::{decode_hex, encode_hex_reduced},
     },
-    utils::strings::encode_hex_reduced,
 };
 use indicatif::ProgressBar;
 use std::{collections::HashMap, fs, time::Duration};
@@ -30,7 +61,10 @@ use std::{collections::HashMap, fs, time::Duration};
 use clap::{AppSettings, Parser};
 use heimdall_common::{
     constants::{ADDRESS_REGEX, BYTECODE_REGEX},
-    ether::{evm::core::vm::VM, signatures::*},
+    ether::{
+        evm::{core::vm::VM, ext::exec::ExecutionLimits},
+        signatures::*,
+    },
     utils::io::logging::*,
 };
 
@@ -60,6 +94,11 @@ pub struct DecompilerArgs {
     #[clap(long, short)]
     pub default: bool,
 
+    /// Never prompt for input, automatically selecting the highest-scored option instead. This
+    /// is also enabled when the `CI` environment variable is set to `true`.
+    #[clap(long = "non-interactive")]
+    pub non_interactive: bool,
+
     /// Whether to skip resolving function selectors.
     #[clap(long = "skip-resolving")]
     pub skip_resolving: bool,
@@ -71,6 +110,124 @@ pub struct DecompilerArgs {
     /// Whether to include yul source code in the output (in beta).
     #[clap(long = "include-yul")]
     pub include_yul: bool,
+
+    /// Whether to fetch the contract's metadata file and original sources from IPFS, if the
+    /// bytecode contains an IPFS metadata hash.
+    #[clap(long = "fetch-metadata")]
+    pub fetch_metadata: bool,
+
+    /// The IPFS gateway to use for fetching metadata.
+    #[clap(long = "ipfs-gateway", default_value = "", hide_default_value = true)]
+    pub ipfs_gateway: String,
+
+    /// Whether to recompile the decompiled source with solc and compare it against the original
+    /// bytecode, reporting a correctness score. Requires `--include-sol` or `--include-yul`.
+    #[clap(long = "verify-output")]
+    pub verify_output: bool,
+
+    /// A Handlebars template to render the results with, written alongside the other
+    /// outputs as `report.<ext>`, where `<ext>` is taken from the template's filename.
+    #[clap(long = "template", default_value = "", hide_default_value = true)]
+    pub template: String,
+
+    /// The approximate memory budget for symbolic execution of each function, in megabytes.
+    /// Once exceeded, deeper branches are pruned and the function is marked as partially
+    /// analyzed. Defaults to no limit.
+    #[clap(long = "memory-limit", default_value = "0", hide_default_value = true)]
+    pub memory_limit: usize,
+
+    /// The maximum number of nested branches symbolic execution explores along a single path.
+    /// Deeper branches are pruned and the function is marked as partially analyzed. Defaults to
+    /// no limit.
+    #[clap(long = "max-branch-depth", default_value = "0", hide_default_value = true)]
+    pub max_branch_depth: usize,
+
+    /// The maximum depth of nested operations a stack value may be built from before symbolic
+    /// execution treats the path as unbounded recursion. Defaults to 16.
+    #[clap(long = "max-call-depth", default_value = "0", hide_default_value = true)]
+    pub max_call_depth: u32,
+
+    /// The maximum number of times symbolic execution revisits a single jump before treating it
+    /// as a loop. Defaults to no limit, relying only on loop detection heuristics.
+    #[clap(long = "max-loop-iterations", default_value = "0", hide_default_value = true)]
+    pub max_loop_iterations: usize,
+
+    /// Whether to also decompile the runtime code of contracts embedded within the target, such
+    /// as the children a factory deploys. Each is written to `child_<n>` in the output directory.
+    #[clap(long = "children")]
+    pub children: bool,
+
+    /// Whether the target is creation code, in which case its runtime code is decompiled along
+    /// with the constructor recovered from its init code, and the layout of any constructor
+    /// arguments appended to it. The constructor is included in the ABI and Solidity output.
+    #[clap(long = "constructor")]
+    pub constructor: bool,
+
+    /// The block number to fetch the target's bytecode at, which requires an archive node. This
+    /// allows analyzing prior versions of contracts whose code has changed, such as metamorphic
+    /// contracts. Defaults to the latest block.
+    #[clap(long = "block", default_value = "0", hide_default_value = true)]
+    pub block: u64,
+
+    /// Whether to name unresolved functions in the Solidity output from their code, such as
+    /// `setOwner_<selector>` for a function which writes the owner, or `transferLike_<selector>`
+    /// for one which emits `Transfer`. Doesn't send anything over the network.
+    #[clap(long = "heuristic-names")]
+    pub heuristic_names: bool,
+
+    /// Whether to ask OpenAI for a descriptive name and a one-line summary of each unresolved
+    /// function in the Solidity output, renaming `Unresolved_<selector>` to `<name>_<selector>`.
+    /// Each function's decompiled code is sent to OpenAI.
+    #[clap(long = "llm-names")]
+    pub llm_names: bool,
+
+    /// Whether to redact hardcoded addresses, strings, and long constants from the code sent to
+    /// OpenAI by `--llm-names`.
+    #[clap(long = "llm-redact")]
+    pub llm_redact: bool,
+
+    /// Your OpenAI API key, used for naming functions.
+    #[clap(long = "openai-api-key", default_value = "", hide_default_value = true)]
+    pub openai_api_key: String,
+
+    /// Whether to also write each decompiled function to its own file under `functions/`, with
+    /// the contract's storage, events, and errors as a shared header. Requires `--include-sol`.
+    #[clap(long = "split-functions")]
+    pub split_functions: bool,
+
+    /// Whether to also decompile the implementation behind a proxy target, read from its
+    /// EIP-1967 or EIP-1822 implementation slot, and write a merged source and ABI presenting the
+    /// implementation's functions under the proxy's address.
+    #[clap(long = "follow-proxy")]
+    pub follow_proxy: bool,
+
+    /// The path to a WASM analyzer plugin to run over the decompiled functions. May be given
+    /// multiple times, and is added to the configured `plugins`.
+    #[clap(long = "plugin", value_name = "PATH", multiple_occurrences = true)]
+    pub plugins: Vec<String>,
+
+    /// Whether to write an audit trail of the decisions made during decompilation, such as which
+    /// signatures were chosen and why, and which functions were pruned, to `audit.json`.
+    #[clap(long = "audit")]
+    pub audit: bool,
+
+    /// Whether to annotate the decompiled source with comments naming the control flow graph
+    /// block each group of statements was lifted from. `heimdall cfg` links its nodes to these
+    /// lines. Requires `--include-sol`.
+    #[clap(long = "block-comments")]
+    pub block_comments: bool,
+
+    /// Whether to make the Solidity output more readable, by inlining temporary variables which
+    /// are used once and whose expression is at most this many characters, and naming the rest
+    /// from where their values come from, such as `balance_` or `newOwner_`. Requires
+    /// `--include-sol`. Defaults to leaving temporaries as-is.
+    #[clap(long = "inline-threshold", default_value = "0", hide_default_value = true)]
+    pub inline_threshold: usize,
+
+    /// The virtual machine the target is compiled for, either `evm` or `eravm`. Defaults to the
+    /// virtual machine of the RPC provider's chain for contract addresses, and `evm` otherwise.
+    #[clap(long = "vm", default_value = "", hide_default_value = true)]
+    pub vm: String,
 }
 
 impl DecompilerArgsBuilder {
@@ -80,17 +237,60 @@ impl DecompilerArgsBuilder {
             verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
             rpc_url: Some(String::new()),
             default: Some(true),
+            non_interactive: Some(false),
             skip_resolving: Some(false),
             include_solidity: Some(false),
             include_yul: Some(false),
+            fetch_metadata: Some(false),
+            ipfs_gateway: Some(String::new()),
+            verify_output: Some(false),
+            template: Some(String::new()),
+            memory_limit: Some(0),
+            max_branch_depth: Some(0),
+            max_call_depth: Some(0),
+            max_loop_iterations: Some(0),
+            children: Some(false),
+            constructor: Some(false),
+            block: Some(0),
+            heuristic_names: Some(false),
+            llm_names: Some(false),
+            llm_redact: Some(false),
+            openai_api_key: Some(String::new()),
+            split_functions: Some(false),
+            follow_proxy: Some(false),
+            plugins: Some(vec![]),
+            audit: Some(false),
+            block_comments: Some(false),
+            inline_threshold: Some(0),
+            vm: Some(String::new()),
         }
     }
 }
 
+/// The notice added to functions whose every path reverts, such as disabled functions.
+const ALWAYS_REVERTS_NOTICE: &str =
+    "this function always reverts, so it may be disabled, or a decoy hiding behind a dead check.";
+
+/// The notice added to functions whose symbolic execution exceeded its execution limits.
+const PARTIAL_ANALYSIS_NOTICE: &str =
+    "this function was only partially analyzed, since symbolic execution exceeded its execution limits.";
+
 #[derive(Debug, Clone)]
 pub struct DecompileResult {
     pub source: Option<String>,
     pub abi: Option<Vec<ABIStructure>>,
+    pub metadata: Option<ContractMetadata>,
+    pub sources: Option<HashMap<String, String>>,
+    pub verification: Option<VerificationReport>,
+    pub children: Vec<DecompileResult>,
+    pub metamorphic: MetamorphicReport,
+    pub implementation: Option<Box<ProxyImplementation>>,
+    /// The statically inferred type of each storage slot the contract writes to.
+    pub storage_types: HashMap<U256, StorageType>,
+    /// The findings reported by analyzer plugins, ordered by descending severity.
+    pub findings: Vec<Finding>,
+    /// The decisions made during decompilation, if `--audit` was given.
+    pub audit: Option<AuditTrail>,
 }
 
 pub async fn decompile(
@@ -125,6 +325,39 @@ pub async fn decompile(
         std::process::exit(1);
     }
 
+    // ensure there is decompiled source to verify
+    if args.verify_output && !args.include_solidity && !args.include_yul {
+        logger.error("argument '--verify-output' requires '--include-sol' or '--include-yul'.");
+        std::process::exit(1);
+    }
+
+    // naming functions requires solidity output and an OpenAI API key
+    if args.heuristic_names && !args.include_solidity {
+        logger.error("argument '--heuristic-names' requires '--include-sol'.");
+        std::process::exit(1);
+    }
+    if args.llm_names && !args.include_solidity {
+        logger.error("argument '--llm-names' requires '--include-sol'.");
+        std::process::exit(1);
+    }
+    if args.inline_threshold > 0 && !args.include_solidity {
+        logger.error("argument '--inline-threshold' requires '--include-sol'.");
+        std::process::exit(1);
+    }
+    if args.follow_proxy && !ADDRESS_REGEX.is_match(&args.target)? {
+        logger.error("argument '--follow-proxy' requires the target to be a contract address.");
+        std::process::exit(1);
+    }
+    if args.split_functions && !args.include_solidity {
+        logger.error("argument '--split-functions' requires '--include-sol'.");
+        std::process::exit(1);
+    }
+    if args.llm_names && args.openai_api_key.is_empty() {
+        logger.error("OpenAI API key is required for naming functions. Use `heimdall decompile --help` for more information.");
+        std::process::exit(1);
+    }
+    let plugins = load_plugins(&args.plugins);
+
     // truncate target for prettier display
     let mut shortened_target = args.target.clone();
     if shortened_target.len() > 66 {
@@ -143,11 +376,17 @@ pub async fn decompile(
 
     // parse the various formats that are accepted as targets
     // i.e, file, bytecode, contract address
-    let contract_bytecode: String;
+    set_phase("decompile: fetching bytecode");
+    let mut contract_bytecode: String;
     if ADDRESS_REGEX.is_match(&args.target)? {
         // We are decompiling a contract address, so we need to fetch the bytecode from the RPC
         // provider
-        contract_bytecode = get_code(&args.target, &args.rpc_url).await?;
+        contract_bytecode =
+            get_code_at_block(&args.target, &args.rpc_url, (args.block > 0).then_some(args.block))
+                .await?;
+    } else if args.block > 0 {
+        logger.error("argument '--block' requires the target to be a contract address.");
+        std::process::exit(1);
     } else if BYTECODE_REGEX.is_match(&args.target)? {
         logger.debug_max("using provided bytecode for decompilation");
         contract_bytecode = args.target.clone().replacen("0x", "", 1);
@@ -173,12 +412,63 @@ pub async fn decompile(
         };
     }
 
+    // symbolic execution only runs legacy bytecode, so EOF containers can't be decompiled yet
+    let bytecode = decode_hex(&contract_bytecode).unwrap_or_default();
+    if is_eof(&bytecode) {
+        match parse_eof(&bytecode) {
+            Ok(container) => logger.error(&format!(
+                "decompiling EOF containers isn't supported yet. use `heimdall disassemble` or `heimdall cfg` to analyze its {} code sections.",
+                container.code_sections.len()
+            )),
+            Err(error) => logger.error(&format!("invalid EOF container: {error}.")),
+        }
+        std::process::exit(1)
+    }
+
+    // nor can EraVM bytecode, which contracts on zkSync Era are compiled to
+    if select_target_vm(&args.vm, &args.target, &args.rpc_url).await? == TargetVm::EraVm {
+        logger.error("decompiling EraVM bytecode isn't supported yet. use `heimdall disassemble` or `heimdall abi` to analyze it.");
+        std::process::exit(1)
+    }
+
+    // warn about code which may change between deployments to the same address
+    let metamorphic = detect_metamorphic(&contract_bytecode);
+    for warning in metamorphic.warnings() {
+        logger.warn(&warning);
+    }
+
+    // split creation code into its init code, which holds the constructor, and the runtime
+    // code it deploys
+    let mut creation_code = None;
+    if args.constructor {
+        match split_creation_code(&contract_bytecode) {
+            Some(split) => {
+                logger.debug(&format!(
+                    "found {} bytes of init code, {} bytes of runtime code, and {} bytes of constructor arguments.",
+                    split.init_code.len() / 2,
+                    split.runtime_code.len() / 2,
+                    split.constructor_arguments.len() / 2
+                ));
+                contract_bytecode = split.runtime_code.clone();
+                creation_code = Some(split);
+            }
+            None => {
+                logger.error("argument '--constructor' requires the target to be creation code.");
+                std::process::exit(1)
+            }
+        }
+    }
+
     // disassemble the bytecode
+    set_phase("decompile: disassembling");
     let disassembled_bytecode = disassemble(DisassemblerArgs {
         target: contract_bytecode.clone(),
         verbose: args.verbose.clone(),
         rpc_url: args.rpc_url.clone(),
         decimal_counter: false,
+        annotate: false,
+        vm: String::new(),
+        diff: String::new(),
     })
     .await?;
     trace.add_call(
@@ -208,8 +498,45 @@ pub async fn decompile(
             .warn(&format!("detected compiler {compiler} {version} is not supported by heimdall."));
     }
 
+    // adjust the decompiler's heuristics to the compiler which produced the contract
+    let heuristics = compiler_heuristics(compiler, &version, &contract_bytecode);
+    let heuristics_summary = format!(
+        "checked arithmetic: {}, free memory pointer: {}, via-ir: {}",
+        heuristics.checked_arithmetic,
+        encode_hex_reduced(heuristics.free_memory_pointer),
+        heuristics.via_ir
+    );
+    trace.add_debug(decompile_call, line!(), &format!("compiler heuristics: {heuristics_summary}"));
+
+    // decode the compiler metadata trailer, if present
+    let metadata = decode_metadata(&contract_bytecode);
+    if let Some(metadata) = &metadata {
+        trace.add_message(
+            decompile_call,
+            line!(),
+            metadata.fields().iter().map(|(name, value)| format!("{name}: {value}")).collect(),
+        );
+    }
+
+    // find the runtime code of any contracts embedded within the target, and offer to
+    // decompile them as well
+    let embedded_contracts = find_embedded_contracts(&contract_bytecode);
+    let mut decompile_children = args.children;
+    if !embedded_contracts.is_empty() && !decompile_children {
+        decompile_children = logger.option(
+            "info",
+            &format!(
+                "found {} contracts embedded within the target, would you like to decompile them?",
+                embedded_contracts.len()
+            ),
+            vec![String::from("no"), String::from("yes")],
+            Some(0),
+            args.default || args.non_interactive,
+        ) == 1;
+    }
+
     // create a new EVM instance
-    let evm = VM::new(
+    let mut evm = VM::new(
         contract_bytecode.clone(),
         String::from("0x"),
         String::from("0x6865696d64616c6c000000000061646472657373"),
@@ -218,6 +545,41 @@ pub async fn decompile(
         0,
         u128::max_value(),
     );
+    evm.limits = ExecutionLimits::new(
+        args.memory_limit,
+        args.max_branch_depth,
+        args.max_call_depth,
+        args.max_loop_iterations,
+    );
+
+    // record the settings and heuristics which the decisions below depend on
+    let mut audit = AuditTrail::new(&args.target, &contract_bytecode);
+    let limits = format!(
+        "memory limit: {}, max branch depth: {}, max call depth: {}, max loop iterations: {}",
+        evm.limits.memory_limit,
+        evm.limits.max_branch_depth,
+        evm.limits.max_call_depth,
+        evm.limits.max_loop_iterations
+    );
+    audit.record(AuditKind::Input, "execution limits", &limits, "0 means no limit");
+    if args.block != 0 {
+        audit.record(
+            AuditKind::Input,
+            "block",
+            &args.block.to_string(),
+            "bytecode was fetched at this block",
+        );
+    }
+    audit.record(
+        AuditKind::Heuristic,
+        "compiler",
+        &heuristics_summary,
+        &format!("detected compiler {compiler} {version}"),
+    );
+    for warning in metamorphic.warnings() {
+        audit.record(AuditKind::Heuristic, "metamorphic", "warned", &warning);
+    }
+
     let mut shortened_target = contract_bytecode.clone();
     if shortened_target.len() > 66 {
         shortened_target = shortened_target.chars().take(66).collect::<String>() +
@@ -233,11 +595,63 @@ pub async fn decompile(
     );
 
     // find and resolve all selectors in the bytecode
+    set_phase("decompile: resolving selectors");
     let selectors = find_function_selectors(&evm, &disassembled_bytecode);
+    let fallback_functions = find_fallback_functions(&evm, &selectors);
+    let rejects_call_value = evm.rejects_call_value();
+    if rejects_call_value {
+        audit.record(
+            AuditKind::Heuristic,
+            "dispatcher",
+            "every function is nonpayable",
+            "the dispatcher reverts when sent call value, before selecting a function",
+        );
+    }
+    for (name, ..) in &fallback_functions {
+        audit.record(
+            AuditKind::Heuristic,
+            name,
+            "recovered",
+            "the dispatcher falls through to code which isn't reached through a selector",
+        );
+    }
+
+    // fetch the original metadata file from IPFS, if requested
+    let mut metadata_file = None;
+    if let (true, Some(metadata)) = (args.fetch_metadata, &metadata) {
+        metadata_file = fetch_metadata_file(metadata, &args.ipfs_gateway, true).await;
+        match &metadata_file {
+            Some(metadata_file) => logger.info(&format!(
+                "retrieved metadata file with {} source files.",
+                metadata_file.sources.len()
+            )),
+            None => logger.warn("failed to retrieve the contract's metadata file from IPFS."),
+        }
+    }
 
     let mut resolved_selectors = HashMap::new();
+    let mut abi_selectors = Vec::new();
     if !args.skip_resolving {
-        resolved_selectors = resolve_selectors(selectors.keys().cloned().collect()).await;
+        // prefer signatures from the original ABI, only resolving selectors which aren't in it
+        let abi_functions = match &metadata_file {
+            Some(metadata_file) => metadata_file.resolved_functions(),
+            None => HashMap::new(),
+        };
+        resolved_selectors = resolve_selectors(
+            selectors
+                .keys()
+                .filter(|selector| !abi_functions.contains_key(*selector))
+                .cloned()
+                .collect(),
+        )
+        .await;
+        abi_selectors = abi_functions.keys().cloned().collect();
+        resolved_selectors.extend(
+            abi_functions
+                .into_iter()
+                .filter(|(selector, _)| selectors.contains_key(selector))
+                .map(|(selector, function)| (selector, vec![function])),
+        );
 
         // if resolved selectors are empty, we can't perform symbolic execution
         if resolved_selectors.is_empty() {
@@ -254,6 +668,7 @@ pub async fn decompile(
         ));
     } else {
         logger.info(&format!("found {} possible function selectors.", selectors.len()));
+        audit.record(AuditKind::Input, "signatures", "not resolved", "--skip-resolving was given");
     }
 
     logger.info(&format!("performing symbolic execution on '{shortened_target}' ."));
@@ -264,8 +679,24 @@ pub async fn decompile(
     decompilation_progress.set_style(logger.info_spinner());
 
     // perform EVM analysis
+    set_phase("decompile: symbolic execution");
     let mut analyzed_functions = Vec::new();
+    let mut dead_code = DeadCodeAnalysis::new();
+    dead_code.add_dispatcher(&evm, &selectors);
+    let total_selectors = selectors.len();
     for (selector, function_entry_point) in selectors {
+        // stop analyzing functions once the user has interrupted analysis, keeping those which
+        // were already analyzed
+        if is_interrupted() {
+            decompilation_progress.suspend(|| {
+                logger.warn(&format!(
+                    "analysis interrupted, continuing with {} analyzed functions.",
+                    analyzed_functions.len()
+                ));
+            });
+            break
+        }
+
         decompilation_progress.set_message(format!("executing '0x{selector}'"));
 
         let func_analysis_trace = trace.add_call(
@@ -328,6 +759,10 @@ pub async fn decompile(
                     pure: true,
                     view: true,
                     payable: true,
+                    compiler: heuristics,
+                    arithmetic: HashMap::new(),
+                    overflow_checks: Vec::new(),
+                    block_comments: args.block_comments,
                 },
                 &mut trace,
                 func_analysis_trace,
@@ -356,6 +791,10 @@ pub async fn decompile(
                     pure: true,
                     view: true,
                     payable: true,
+                    compiler: heuristics,
+                    arithmetic: HashMap::new(),
+                    overflow_checks: Vec::new(),
+                    block_comments: args.block_comments,
                 },
                 &mut trace,
                 func_analysis_trace,
@@ -364,6 +803,31 @@ pub async fn decompile(
             );
         }
 
+        analyzed_function.resolve_payable(rejects_call_value);
+        if map.is_pruned() {
+            analyzed_function.notices.push(PARTIAL_ANALYSIS_NOTICE.to_string());
+            audit.record(
+                AuditKind::Pruning,
+                &selector,
+                "partially analyzed",
+                &format!("symbolic execution exceeded its execution limits ({limits})"),
+            );
+        }
+        if map.always_reverts() {
+            analyzed_function.notices.push(ALWAYS_REVERTS_NOTICE.to_string());
+            audit.record(
+                AuditKind::Heuristic,
+                &selector,
+                "always reverts",
+                "every path through the function reverts",
+            );
+        }
+        let ether_flow = EtherFlow::from_trace(map);
+        if ether_flow.sends_ether() {
+            analyzed_function.notices.push(ether_flow.summary(analyzed_function.payable));
+        }
+        dead_code.add_trace(Some(&selector), map);
+
         let argument_count = analyzed_function.arguments.len();
 
         if argument_count != 0 {
@@ -413,11 +877,23 @@ pub async fn decompile(
                 }
             };
 
+            let candidate_count = resolved_functions.len();
             let mut matched_resolved_functions =
                 match_parameters(resolved_functions, &analyzed_function);
 
             trace.br(func_analysis_trace);
             if matched_resolved_functions.is_empty() {
+                audit.record(
+                    AuditKind::Signature,
+                    &selector,
+                    "unresolved",
+                    &match candidate_count {
+                        0 => String::from("no signatures were found"),
+                        count => {
+                            format!("none of {count} signatures matched the function's parameters")
+                        }
+                    },
+                );
                 trace.add_warn(
                     func_analysis_trace,
                     line!(),
@@ -443,7 +919,7 @@ pub async fn decompile(
                                 .map(|x| x.signature.clone())
                                 .collect(),
                             Some(0u8),
-                            args.default,
+                            args.default || args.non_interactive,
                         );
                     });
                 }
@@ -454,7 +930,30 @@ pub async fn decompile(
                         None => continue,
                     };
 
+                let interactive =
+                    matched_resolved_functions.len() > 1 && !(args.default || args.non_interactive);
+                match abi_selectors.contains(&selector) {
+                    true => audit.record(
+                        AuditKind::Signature,
+                        &selector,
+                        &selected_match.signature,
+                        "from the ABI in the contract's metadata file",
+                    ),
+                    false => audit.record_signature(
+                        &selector,
+                        &matched_resolved_functions
+                            .iter()
+                            .map(|x| x.signature.clone())
+                            .collect::<Vec<_>>(),
+                        Some(&selected_match.signature),
+                        interactive,
+                    ),
+                }
                 analyzed_function.resolved_function = Some(selected_match.clone());
+                emit(ProgressEvent::SignatureResolved {
+                    selector: selector.clone(),
+                    signature: selected_match.signature.clone(),
+                });
 
                 let match_trace = trace.add_info(
                     func_analysis_trace,
@@ -506,19 +1005,32 @@ pub async fn decompile(
                             "multiple possible matches found. select an option below",
                             resolved_error_selectors.iter().map(|x| x.signature.clone()).collect(),
                             Some(0u8),
-                            args.default,
+                            args.default || args.non_interactive,
                         );
                     });
                 }
 
-                let selected_match =
-                    match resolved_error_selectors.get(selected_error_index as usize) {
-                        Some(selected_match) => selected_match,
-                        None => continue,
-                    };
+                let selected_match = resolved_error_selectors.get(selected_error_index as usize);
+                audit.record_signature(
+                    &error_selector_str,
+                    &resolved_error_selectors
+                        .iter()
+                        .map(|x| x.signature.clone())
+                        .collect::<Vec<_>>(),
+                    selected_match.map(|x| &x.signature),
+                    resolved_error_selectors.len() > 1 && !(args.default || args.non_interactive),
+                );
+                let selected_match = match selected_match {
+                    Some(selected_match) => selected_match,
+                    None => continue,
+                };
 
                 resolved_counter += 1;
                 analyzed_function.errors.insert(error_selector, Some(selected_match.clone()));
+                emit(ProgressEvent::SignatureResolved {
+                    selector: error_selector_str.clone(),
+                    signature: selected_match.signature.clone(),
+                });
                 all_resolved_errors.insert(error_selector_str, selected_match.clone());
             }
 
@@ -572,21 +1084,34 @@ pub async fn decompile(
                             "multiple possible matches found. select an option below",
                             resolved_event_selectors.iter().map(|x| x.signature.clone()).collect(),
                             Some(0u8),
-                            args.default,
+                            args.default || args.non_interactive,
                         );
                     });
                 }
 
-                let selected_match =
-                    match resolved_event_selectors.get(selected_event_index as usize) {
-                        Some(selected_match) => selected_match,
-                        None => continue,
-                    };
+                let selected_match = resolved_event_selectors.get(selected_event_index as usize);
+                audit.record_signature(
+                    &event_selector_str,
+                    &resolved_event_selectors
+                        .iter()
+                        .map(|x| x.signature.clone())
+                        .collect::<Vec<_>>(),
+                    selected_match.map(|x| &x.signature),
+                    resolved_event_selectors.len() > 1 && !(args.default || args.non_interactive),
+                );
+                let selected_match = match selected_match {
+                    Some(selected_match) => selected_match,
+                    None => continue,
+                };
 
                 resolved_counter += 1;
                 analyzed_function
                     .events
                     .insert(event_selector, (Some(selected_match.clone()), raw_event));
+                emit(ProgressEvent::SignatureResolved {
+                    selector: event_selector_str.clone(),
+                    signature: selected_match.signature.clone(),
+                });
                 all_resolved_events.insert(event_selector_str, selected_match.clone());
             }
 
@@ -613,37 +1138,299 @@ pub async fn decompile(
         decompilation_progress.set_style(logger.info_spinner());
 
         analyzed_functions.push(analyzed_function.clone());
+        emit(ProgressEvent::SelectorAnalyzed {
+            selector,
+            analyzed: analyzed_functions.len(),
+            total: total_selectors,
+        });
+    }
+
+    // analyze the receive and fallback functions, which aren't reached through a selector
+    for (name, entry_vm, accepts_value) in fallback_functions {
+        decompilation_progress.set_message(format!("executing '{name}'"));
+
+        let func_analysis_trace = trace.add_call(
+            vm_trace,
+            line!(),
+            "heimdall".to_string(),
+            "analyze".to_string(),
+            vec![name.clone()],
+            "()".to_string(),
+        );
+        trace.add_info(
+            func_analysis_trace,
+            entry_vm.instruction.try_into()?,
+            &format!("discovered entry point: {}", entry_vm.instruction),
+        );
+
+        let (map, _) = entry_vm.symbolic_exec();
+        dead_code.add_trace(None, &map);
+        let function = Function {
+            selector: name,
+            entry_point: entry_vm.instruction,
+            arguments: HashMap::new(),
+            storage: HashMap::new(),
+            memory: HashMap::new(),
+            returns: None,
+            logic: Vec::new(),
+            events: HashMap::new(),
+            errors: HashMap::new(),
+            resolved_function: None,
+            indent_depth: 0,
+            notices: Vec::new(),
+            pure: true,
+            view: true,
+            payable: true,
+            compiler: heuristics,
+            arithmetic: HashMap::new(),
+            overflow_checks: Vec::new(),
+            block_comments: args.block_comments,
+        };
+        let mut analyzed_function = if args.include_yul {
+            analyze_yul(&map, function, &mut trace, func_analysis_trace, &mut Vec::new())
+        } else {
+            analyze_sol(&map, function, &mut trace, func_analysis_trace, &mut Vec::new(), (0, 0))
+        };
+
+        // these functions take no arguments, and only accept call value if the dispatcher
+        // doesn't reject it before handing off control
+        analyzed_function.arguments.clear();
+        analyzed_function.resolve_payable(rejects_call_value);
+        analyzed_function.payable &= accepts_value;
+        if map.is_pruned() {
+            analyzed_function.notices.push(PARTIAL_ANALYSIS_NOTICE.to_string());
+            audit.record(
+                AuditKind::Pruning,
+                &analyzed_function.selector,
+                "partially analyzed",
+                &format!("symbolic execution exceeded its execution limits ({limits})"),
+            );
+        }
+        let ether_flow = EtherFlow::from_trace(&map);
+        if ether_flow.sends_ether() {
+            analyzed_function.notices.push(ether_flow.summary(analyzed_function.payable));
+        }
+        analyzed_functions.push(analyzed_function);
+    }
+
+    // recover the constructor from the init code, which comes first in the output
+    if let Some(creation_code) = &creation_code {
+        decompilation_progress.set_message("executing 'constructor'");
+        let (constructor, layout) =
+            analyze_constructor(creation_code, &evm.limits, heuristics, &mut trace, vm_trace);
+        decompilation_progress.suspend(|| {
+            logger
+                .info(&format!("recovered constructor with {} appended arguments.", layout.len()));
+        });
+        for argument in &layout {
+            logger.debug(&format!(
+                "constructor argument {} at offset {}: {} ({}).",
+                argument.index,
+                argument.offset,
+                argument.value,
+                argument.potential_types.join(", ")
+            ));
+        }
+        analyzed_functions.insert(0, constructor);
     }
     decompilation_progress.finish_and_clear();
     logger.info("symbolic execution completed.");
+
+    // report the code which can never run, or never succeed
+    let dead_code = dead_code.report(&contract_bytecode);
+    for warning in dead_code.warnings() {
+        logger.warn(&warning);
+    }
     logger.info("building decompilation output.");
+    set_phase("decompile: building output");
 
     let abi = build_abi(&args, analyzed_functions.clone(), &mut trace, decompile_call)?;
+    let storage_types = infer_storage_types(&analyzed_functions);
+    let mut slots = storage_types.iter().collect::<Vec<_>>();
+    slots.sort_by_key(|(slot, _)| **slot);
+    for (slot, storage_type) in slots {
+        audit.record(
+            AuditKind::StorageType,
+            &format!("{slot:#x}"),
+            &storage_type.to_string(),
+            "inferred from the operations which write to the slot",
+        );
+    }
+
+    // run analyzer plugins over the recovered functions
+    let mut findings = Vec::new();
+    if !plugins.is_empty() {
+        set_phase("decompile: running plugins");
+        findings = run_plugins(
+            &plugins,
+            &AnalysisInput::new(
+                &args.target,
+                &contract_bytecode,
+                metadata.clone(),
+                &analyzed_functions,
+            ),
+        );
+        for finding in &findings {
+            logger.warn(&format!(
+                "{} ({:?}, from {}){}",
+                finding.title,
+                finding.severity,
+                finding.plugin,
+                finding
+                    .selector
+                    .as_ref()
+                    .map(|selector| format!(" in 0x{selector}"))
+                    .unwrap_or_default()
+            ));
+        }
+        logger.info(&format!("plugins reported {} finding(s).", findings.len()));
+    }
     trace.display();
     logger.debug(&format!("decompilation completed in {:?}.", now.elapsed()));
 
+    let source = if args.include_solidity {
+        Some(build_solidity_output(
+            &args,
+            &abi,
+            analyzed_functions,
+            all_resolved_errors,
+            all_resolved_events,
+            metadata.as_ref(),
+            &mut trace,
+            decompile_call,
+        )?)
+    } else if args.include_yul {
+        Some(build_yul_output(
+            &args,
+            analyzed_functions,
+            all_resolved_events,
+            metadata.as_ref(),
+            &mut trace,
+            decompile_call,
+        )?)
+    } else {
+        None
+    };
+
+    let warnings = [metamorphic.warnings(), dead_code.warnings()].concat();
+    let mut source = source.map(|source| add_warnings_to_header(&source, &warnings));
+
+    // inline and name temporaries, before naming functions from their now simpler code
+    if let (true, Some(solidity)) = (args.inline_threshold > 0, &source) {
+        set_phase("decompile: improving readability");
+        source = Some(improve_readability(solidity, args.inline_threshold));
+    }
+
+    // name the functions which couldn't be resolved from their code, leaving the rest to the LLM
+    if let (true, Some(solidity)) = (args.heuristic_names, &source) {
+        set_phase("decompile: naming functions");
+        source = Some(suggest_heuristic_names(solidity));
+    }
+
+    // ask the LLM to name the functions which couldn't be resolved
+    if let (true, Some(solidity)) = (args.llm_names, &source) {
+        set_phase("decompile: naming functions");
+        logger.info("naming unresolved functions with OpenAI.");
+        source =
+            Some(suggest_function_names(solidity, &args.openai_api_key, args.llm_redact).await);
+    }
+
+    // recompile the decompiled source and compare it against the original bytecode
+    let mut verification = None;
+    if let (true, Some(source)) = (args.verify_output, &source) {
+        set_phase("decompile: verifying output");
+        let report = verify_output(&contract_bytecode, source, args.include_yul).await;
+        match &report.compile_error {
+            Some(e) => logger.warn(&format!("failed to recompile the decompiled source: {e}")),
+            None => logger.info(&format!(
+                "verification score: {:.2}% ({}/{} selectors, {}/{} events, {:.2}% cfg similarity).",
+                report.score * 100.0,
+                report.matched_selectors,
+                report.total_selectors,
+                report.matched_events,
+                report.total_events,
+                report.cfg_similarity * 100.0
+            )),
+        }
+        verification = Some(report);
+    }
+
+    // decompile each embedded contract as its own target
+    let mut children = Vec::new();
+    if decompile_children {
+        for (index, embedded_contract) in embedded_contracts.into_iter().enumerate() {
+            logger.info(&format!(
+                "decompiling child_{index}, embedded at offset {} .",
+                embedded_contract.offset
+            ));
+            let child_args = DecompilerArgs {
+                target: embedded_contract.bytecode,
+                children: false,
+                block: 0,
+                ..args.clone()
+            };
+            children.push(Box::pin(decompile(child_args)).await?);
+        }
+    }
+
+    // decompile the implementation behind a proxy, to present it under the proxy's address
+    let mut implementation = None;
+    if args.follow_proxy {
+        set_phase("decompile: following proxy");
+        let block = match args.block {
+            0 => String::from("latest"),
+            block => format!("{block:#x}"),
+        };
+        match find_implementation(&args.target, &args.rpc_url, &block).await {
+            Ok(Some((standard, address))) => {
+                logger.info(&format!("decompiling {standard} proxy implementation '{address}' ."));
+                audit.record(
+                    AuditKind::Heuristic,
+                    "proxy",
+                    &address,
+                    &format!("read from the {standard} implementation slot"),
+                );
+                let implementation_args = DecompilerArgs {
+                    target: address.clone(),
+                    children: false,
+                    follow_proxy: false,
+                    ..args.clone()
+                };
+                let result = Box::pin(decompile(implementation_args)).await?;
+                let merged_abi = merge_proxy_abi(&abi, result.abi.as_deref().unwrap_or_default());
+                let merged_source = match (args.include_solidity, &source, &result.source) {
+                    (true, Some(proxy_source), Some(implementation_source)) => merge_proxy_source(
+                        proxy_source,
+                        implementation_source,
+                        &args.target,
+                        &address,
+                    ),
+                    _ => None,
+                };
+                implementation = Some(Box::new(ProxyImplementation {
+                    address,
+                    standard,
+                    result,
+                    merged_abi,
+                    merged_source,
+                }));
+            }
+            Ok(None) => logger.warn("target doesn't store an implementation in any proxy slot."),
+            Err(e) => logger.warn(&format!("failed to read the target's proxy slots: {e}")),
+        }
+    }
+
     Ok(DecompileResult {
-        source: if args.include_solidity {
-            Some(build_solidity_output(
-                &args,
-                &abi,
-                analyzed_functions,
-                all_resolved_errors,
-                all_resolved_events,
-                &mut trace,
-                decompile_call,
-            )?)
-        } else if args.include_yul {
-            Some(build_yul_output(
-                &args,
-                analyzed_functions,
-                all_resolved_events,
-                &mut trace,
-                decompile_call,
-            )?)
-        } else {
-            None
-        },
+        source,
         abi: Some(abi),
+        metadata,
+        sources: metadata_file.map(|metadata_file| metadata_file.sources),
+        verification,
+        children,
+        metamorphic,
+        implementation,
+        storage_types,
+        findings,
+        audit: args.audit.then_some(audit),
     })
 }
@@ -1,9 +1,12 @@
 use std::time::Duration;
 
-use ethers::abi::AbiEncode;
-use heimdall_common::utils::io::{
-    file::short_path,
-    logging::{Logger, TraceFactory},
+use ethers::{abi::AbiEncode, utils::keccak256};
+use heimdall_common::utils::{
+    io::{
+        file::short_path,
+        logging::{Logger, TraceFactory},
+    },
+    strings::encode_hex,
 };
 use indicatif::ProgressBar;
 use serde::{Deserialize, Serialize};
@@ -25,6 +28,7 @@ pub struct ABIToken {
 pub struct FunctionABI {
     #[serde(rename = "type")]
     pub type_: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
     pub inputs: Vec<ABIToken>,
     pub outputs: Vec<ABIToken>,
@@ -33,6 +37,40 @@ pub struct FunctionABI {
     pub constant: bool,
 }
 
+impl FunctionABI {
+    /// The function's signature, e.g. `transfer(address,uint256)`.
+    pub fn signature(&self) -> String {
+        format!(
+            "{}({})",
+            self.name,
+            self.inputs.iter().map(|input| input.type_.as_str()).collect::<Vec<&str>>().join(",")
+        )
+    }
+
+    /// The function's 4byte selector, without a `0x` prefix. Unresolved functions are named after
+    /// their selector, which is returned as-is.
+    ///
+    /// ```
+    /// use heimdall_core::decompile::out::abi::FunctionABI;
+    ///
+    /// let function = FunctionABI {
+    ///     type_: String::from("function"),
+    ///     name: String::from("totalSupply"),
+    ///     inputs: vec![],
+    ///     outputs: vec![],
+    ///     state_mutability: String::from("view"),
+    ///     constant: false,
+    /// };
+    /// assert_eq!(function.selector(), "18160ddd");
+    /// ```
+    pub fn selector(&self) -> String {
+        match self.name.strip_prefix("Unresolved_") {
+            Some(selector) => selector.to_string(),
+            None => encode_hex(keccak256(self.signature())[0..4].to_vec()),
+        }
+    }
+}
+
 /// ABI structure for a single contract's custom error.
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct ErrorABI {
@@ -141,9 +179,10 @@ pub fn build_abi(
                 let mut inputs = Vec::new();
                 let mut outputs = Vec::new();
 
-                for (index, (_, (_, potential_types))) in
-                    function.arguments.clone().iter().enumerate()
-                {
+                // arguments are ordered by their calldata slot
+                let mut arguments = function.arguments.iter().collect::<Vec<_>>();
+                arguments.sort_by_key(|(slot, _)| **slot);
+                for (index, (_, (_, potential_types))) in arguments.into_iter().enumerate() {
                     inputs.push(ABIToken {
                         name: format!("arg{index}"),
                         internal_type: potential_types[0].to_owned(),
@@ -180,14 +219,35 @@ pub fn build_abi(
 
         let constant = state_mutability == "pure" && function_inputs.is_empty();
 
-        // add the function to the ABI
-        abi.push(ABIStructure::Function(FunctionABI {
-            type_: "function".to_string(),
-            name: function_name,
-            inputs: function_inputs,
-            outputs: function_outputs,
-            state_mutability: state_mutability.to_string(),
-            constant,
+        // add the function to the ABI. receive and fallback functions have no name or parameters,
+        // and constructors have no name
+        abi.push(ABIStructure::Function(match function.is_fallback() {
+            _ if function.is_constructor() => FunctionABI {
+                type_: function.selector.clone(),
+                name: String::new(),
+                inputs: function_inputs,
+                outputs: Vec::new(),
+                state_mutability: if function.payable { "payable" } else { "nonpayable" }
+                    .to_string(),
+                constant: false,
+            },
+            true => FunctionABI {
+                type_: function.selector.clone(),
+                name: String::new(),
+                inputs: Vec::new(),
+                outputs: Vec::new(),
+                state_mutability: if function.payable { "payable" } else { "nonpayable" }
+                    .to_string(),
+                constant: false,
+            },
+            false => FunctionABI {
+                type_: "function".to_string(),
+                name: function_name,
+                inputs: function_inputs,
+                outputs: function_outputs,
+                state_mutability: state_mutability.to_string(),
+                constant,
+            },
         }));
 
         // build the function's custom errors
@@ -1,4 +1,55 @@
+use heimdall_common::ether::metadata::ContractMetadata;
+
 pub mod abi;
+pub mod naming;
 pub mod postprocessers;
+pub mod readability;
 pub mod solidity;
+pub mod split;
 pub mod yul;
+
+/// Add the decoded compiler metadata to a decompiled source header, directly below the
+/// `@custom:version` tag.
+pub fn add_metadata_to_header(header: &str, metadata: Option<&ContractMetadata>) -> String {
+    let metadata = match metadata {
+        Some(metadata) => metadata,
+        None => return header.to_string(),
+    };
+
+    let mut lines = Vec::new();
+    for line in header.split('\n') {
+        lines.push(line.to_string());
+
+        if line.starts_with("/// @custom:version") {
+            lines.extend(
+                metadata
+                    .fields()
+                    .iter()
+                    .map(|(name, value)| format!("/// @custom:{name:<9} {value}")),
+            );
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Add warnings about the contract to the header of decompiled source, directly above the
+/// `@notice` tag, so they can't be missed.
+pub fn add_warnings_to_header(source: &str, warnings: &[String]) -> String {
+    if warnings.is_empty() {
+        return source.to_string()
+    }
+
+    let mut lines = Vec::new();
+    let mut added = false;
+    for line in source.split('\n') {
+        if !added && line.trim_start().starts_with("/// @notice") {
+            lines.extend(warnings.iter().map(|warning| format!("/// @custom:warning   {warning}")));
+            lines.push(String::from("///"));
+            added = true;
+        }
+        lines.push(line.to_string());
+    }
+
+    lines.join("\n")
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+
+use heimdall_common::{resources::openai::complete, utils::io::logging::Logger};
+
+use crate::decompile::constants::{
+    REDACT_ADDRESS_REGEX, REDACT_CONSTANT_REGEX, REDACT_STRING_REGEX,
+};
+
+/// A descriptive name suggested for an unresolved function, along with a one-line summary of
+/// what it does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuggestedName {
+    pub selector: String,
+    pub name: String,
+    pub summary: String,
+}
+
+/// Find every unresolved function within decompiled Solidity source, returning each function's
+/// selector and its full definition, from the function header to its closing brace.
+pub fn unresolved_functions(source: &str) -> Vec<(String, String)> {
+    let mut functions = Vec::new();
+    let mut current: Option<(String, Vec<&str>, i32)> = None;
+
+    for line in source.lines() {
+        if current.is_none() {
+            let selector = match line.trim_start().strip_prefix("function Unresolved_") {
+                Some(rest) => rest.chars().take_while(|c| c.is_ascii_hexdigit()).collect(),
+                None => continue,
+            };
+            current = Some((selector, Vec::new(), 0));
+        }
+
+        if let Some((selector, lines, depth)) = current.as_mut() {
+            lines.push(line);
+            *depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+
+            if *depth <= 0 && line.contains('}') {
+                functions.push((selector.clone(), lines.join("\n")));
+                current = None;
+            }
+        }
+    }
+
+    functions
+}
+
+/// Redact the hardcoded addresses, string literals, and long constants, such as hashes, within
+/// decompiled code, so it can be shared without revealing them.
+///
+/// ```
+/// use heimdall_core::decompile::out::naming::redact;
+///
+/// assert_eq!(
+///     redact("require(msg.sender == 0x00000000000000000000000000000000000000aa, \"Ownable\");"),
+///     "require(msg.sender == <address>, \"<string>\");"
+/// );
+/// ```
+pub fn redact(code: &str) -> String {
+    let code = REDACT_STRING_REGEX.replace_all(code, "\"<string>\"");
+    let code = REDACT_ADDRESS_REGEX.replace_all(&code, "<address>");
+    REDACT_CONSTANT_REGEX.replace_all(&code, "<constant>").to_string()
+}
+
+/// Parse an LLM's response in the `name: <name>` and `summary: <summary>` format. The name is
+/// reduced to a valid identifier in camelCase, and `None` is returned if either part is missing.
+pub fn parse_suggested_name(selector: &str, response: &str) -> Option<SuggestedName> {
+    let mut name = None;
+    let mut summary = None;
+    for line in response.lines() {
+        let (key, value) = match line.split_once(':') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+            None => continue,
+        };
+        match key.as_str() {
+            "name" => name = Some(value),
+            "summary" => summary = Some(value),
+            _ => {}
+        }
+    }
+
+    let name = name?
+        .trim_matches(|c: char| c == '`' || c == '"' || c == '\'')
+        .split('(')
+        .next()?
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .skip_while(|c| !c.is_ascii_alphabetic())
+        .collect::<String>();
+    let summary = summary?.to_string();
+    if name.is_empty() || summary.is_empty() {
+        return None
+    }
+
+    Some(SuggestedName {
+        selector: selector.to_string(),
+        name: name[..1].to_lowercase() + &name[1..],
+        summary,
+    })
+}
+
+/// Rename an unresolved function throughout decompiled Solidity source, from
+/// `Unresolved_<selector>` to `<name>_<selector>`, keeping the selector so the name is clearly a
+/// suggestion. The summary is added to the function's NatSpec header.
+pub fn apply_suggested_name(source: &str, suggestion: &SuggestedName) -> String {
+    let unresolved_name = format!("Unresolved_{}", suggestion.selector);
+    let suggested_name = format!("{}_{}", suggestion.name, suggestion.selector);
+
+    let mut lines = Vec::new();
+    for line in source.lines() {
+        lines.push(line.replace(&unresolved_name, &suggested_name));
+
+        if line.trim_start().starts_with("/// @custom:name") && line.ends_with(&unresolved_name) {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            lines.push(format!("{indent}/// @custom:summary     {}", suggestion.summary));
+        }
+    }
+
+    lines.join("\n")
+}
+
+/// Find the storage variables which are compared against `msg.sender`, such as an owner, since
+/// functions which write them are likely to transfer control of the contract.
+pub fn owner_variables(source: &str) -> HashSet<String> {
+    source
+        .lines()
+        .filter(|line| line.contains("msg.sender ==") || line.contains("== msg.sender"))
+        .flat_map(|line| {
+            line.match_indices("stor_")
+                .map(|(start, _)| {
+                    line[start..]
+                        .chars()
+                        .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                        .collect::<String>()
+                })
+                .filter(|variable| !variable.starts_with("stor_map_"))
+                .collect::<Vec<String>>()
+        })
+        .collect()
+}
+
+/// Whether the decompiled call sends a nonzero amount of ether.
+fn sends_value(line: &str) -> bool {
+    let value = match line.split_once("value: ") {
+        Some((_, rest)) => rest.split(" }").next().unwrap_or_default().trim(),
+        None => return false,
+    };
+    !value.trim_start_matches("0x").chars().all(|c| c == '0')
+}
+
+/// `stor_a` becomes `StorA`, for use within a function or variable name.
+pub(crate) fn to_pascal_case(variable: &str) -> String {
+    variable
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| part[..1].to_uppercase() + &part[1..])
+        .collect()
+}
+
+/// Suggest a name for an unresolved function from its decompiled code alone, along with the
+/// evidence the name is based on. For example, a function which writes the owner becomes
+/// `setOwner`, and one which emits `Transfer` becomes `transferLike`.
+pub fn heuristic_name(
+    selector: &str,
+    function: &str,
+    owner_variables: &HashSet<String>,
+) -> Option<SuggestedName> {
+    let mut lines = function.lines().map(|line| line.trim());
+    let header = lines.next()?;
+    let body = lines.filter(|line| !line.is_empty() && *line != "}").collect::<Vec<&str>>();
+    let writes = body
+        .iter()
+        .filter_map(|line| line.split_once(" = ").map(|(variable, _)| variable))
+        .filter(|variable| variable.starts_with("stor_"))
+        .collect::<Vec<&str>>();
+    let emits = |event: &str, topic: &str| {
+        body.iter().any(|line| {
+            line.starts_with(&format!("emit {event}(")) ||
+                line.starts_with(&format!("emit Event_{topic}("))
+        })
+    };
+
+    let (name, summary) = if let Some(owner) = writes.iter().find(|v| owner_variables.contains(**v))
+    {
+        (String::from("setOwner"), format!("writes {owner}, which is checked against msg.sender"))
+    } else if body.iter().any(|line| line.starts_with("selfdestruct(")) {
+        (String::from("destroy"), String::from("can selfdestruct the contract"))
+    } else if emits("Transfer", "ddf252ad") {
+        (String::from("transferLike"), String::from("emits a Transfer event"))
+    } else if emits("Approval", "8c5be1e5") {
+        (String::from("approveLike"), String::from("emits an Approval event"))
+    } else if body.iter().any(|line| line.contains(").delegatecall")) {
+        (String::from("delegateLike"), String::from("delegates execution to another contract"))
+    } else if body.iter().any(|line| line.contains(").call{") && sends_value(line)) {
+        (String::from("withdrawLike"), String::from("sends ether"))
+    } else if body.iter().any(|line| line.contains(":= create(")) {
+        (String::from("deployLike"), String::from("deploys a contract"))
+    } else if header.contains(" payable ") && !writes.is_empty() {
+        (String::from("depositLike"), String::from("accepts ether and writes to storage"))
+    } else if let ([line], true) =
+        (body.as_slice(), header.contains(" view ") || header.contains(" pure "))
+    {
+        let variable = line.strip_prefix("return stor_")?.strip_suffix(';')?;
+        (
+            format!("get{}", to_pascal_case(&format!("stor_{variable}"))),
+            format!("returns stor_{variable}"),
+        )
+    } else if let ([variable], true) = (writes.as_slice(), body.len() == 1) {
+        (format!("set{}", to_pascal_case(variable)), format!("writes {variable}"))
+    } else {
+        return None
+    };
+
+    Some(SuggestedName {
+        selector: selector.to_string(),
+        name,
+        summary: format!("named heuristically, since it {summary}."),
+    })
+}
+
+/// Rename every unresolved function in the decompiled Solidity source whose purpose can be
+/// inferred from its code with [`heuristic_name`].
+pub fn suggest_heuristic_names(source: &str) -> String {
+    // get a new logger
+    let logger = Logger::default();
+
+    let owner_variables = owner_variables(source);
+    let mut source = source.to_string();
+    for (selector, function) in unresolved_functions(&source) {
+        if let Some(suggestion) = heuristic_name(&selector, &function, &owner_variables) {
+            logger.debug(&format!(
+                "named 'Unresolved_{selector}' as '{}_{selector}' .",
+                suggestion.name
+            ));
+            source = apply_suggested_name(&source, &suggestion);
+        }
+    }
+
+    source
+}
+
+/// Ask the configured LLM for a descriptive name and a one-line summary of every unresolved
+/// function in the decompiled Solidity source, and apply the suggestions. Hardcoded values are
+/// redacted from each function before it is sent, if `redact_code` is set.
+pub async fn suggest_function_names(source: &str, api_key: &str, redact_code: bool) -> String {
+    // get a new logger
+    let logger = Logger::default();
+
+    let mut source = source.to_string();
+    for (selector, function) in unresolved_functions(&source) {
+        let function = match redact_code {
+            true => redact(&function),
+            false => function,
+        };
+        let prompt = format!(
+            "Below is a function decompiled from an Ethereum smart contract. Suggest a descriptive
+            camelCase name for it, and summarize what it does in one line.
+            Respond in exactly this format, without any other text:
+            name: <name>
+            summary: <summary>
+
+            {function}"
+        );
+
+        match complete(&prompt, api_key).await.and_then(|r| parse_suggested_name(&selector, &r)) {
+            Some(suggestion) => {
+                logger.debug(&format!(
+                    "named 'Unresolved_{selector}' as '{}_{selector}' .",
+                    suggestion.name
+                ));
+                source = apply_suggested_name(&source, &suggestion);
+            }
+            None => logger.warn(&format!("failed to name 'Unresolved_{selector}' .")),
+        }
+    }
+
+    source
+}
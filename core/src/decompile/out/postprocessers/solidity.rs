@@ -30,6 +30,13 @@ lazy_static! {
     static ref MEMORY_TYPE_DECLARATION_SET: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
 }
 
+/// Whether the line defines a function, including the `receive` and `fallback` functions which
+/// aren't declared with the `function` keyword.
+pub(crate) fn is_function_definition(line: &str) -> bool {
+    let line = line.trim_start();
+    line.contains("function") || line.starts_with("receive() ") || line.starts_with("fallback() ")
+}
+
 /// Convert bitwise operations to a variable type cast
 fn convert_bitmask_to_casting(line: &str) -> String {
     let mut cleaned = line.to_owned();
@@ -64,7 +71,7 @@ fn convert_bitmask_to_casting(line: &str) -> String {
                     if cleaned.contains("if") {
                         String::new()
                     } else {
-                        "bytes1".to_string()
+                        "uint8".to_string()
                     }
                 }
                 _ => cast_types[0].to_owned(),
@@ -117,7 +124,7 @@ fn convert_bitmask_to_casting(line: &str) -> String {
                         if cleaned.contains("if") {
                             String::new()
                         } else {
-                            "bytes1".to_string()
+                            "uint8".to_string()
                         }
                     }
                     _ => cast_types[0].to_owned(),
@@ -213,7 +220,7 @@ fn simplify_parentheses(line: &str, paren_index: usize) -> String {
     let mut cleaned: String = line.to_owned();
 
     // skip lines that are defining a function
-    if cleaned.contains("function") {
+    if is_function_definition(&cleaned) {
         return cleaned
     }
 
@@ -282,7 +289,7 @@ fn convert_access_to_variable(line: &str) -> String {
     let mut cleaned = line.to_owned();
 
     // reset the mem_map if the line is a function definition
-    if cleaned.contains("function") {
+    if is_function_definition(&cleaned) {
         let mut mem_map = MEM_LOOKUP_MAP.lock().unwrap();
         *mem_map = HashMap::new();
         drop(mem_map);
@@ -412,8 +419,13 @@ fn convert_access_to_variable(line: &str) -> String {
 
 /// Checks if the current line contains an unnecessary assignment
 fn contains_unnecessary_assignment(line: &str, lines: &Vec<&str>) -> bool {
-    // skip lines that don't contain an assignment, or contain a return or external calls
-    if !line.contains(" = ") || line.contains("bool success") || line.contains("return") {
+    // skip lines that don't contain an assignment, or contain a return, external calls, or a
+    // for-loop's counter
+    if !line.contains(" = ") ||
+        line.contains("bool success") ||
+        line.contains("return") ||
+        line.starts_with("for (")
+    {
         return false
     }
 
@@ -421,15 +433,15 @@ fn contains_unnecessary_assignment(line: &str, lines: &Vec<&str>) -> bool {
     let var_name = line.split(" = ").collect::<Vec<&str>>()[0].split(' ').collect::<Vec<&str>>()
         [line.split(" = ").collect::<Vec<&str>>()[0].split(' ').collect::<Vec<&str>>().len() - 1];
 
-    // skip lines that contain assignments to storage
-    if var_name.contains("stor_") {
+    // skip lines that contain assignments to storage or to the constructor's immutables
+    if var_name.contains("stor_") || var_name.starts_with("immutable_") {
         return false
     }
 
     //remove unused vars
     for x in lines {
         // break if the line contains a function definition
-        if x.contains("function") {
+        if is_function_definition(x) {
             break
         }
 
@@ -454,7 +466,7 @@ fn move_casts_to_declaration(line: &str) -> String {
     let mut type_declaration_set = MEMORY_TYPE_DECLARATION_SET.lock().unwrap();
 
     // if line contains "function" wipe the set
-    if cleaned.contains("function") {
+    if is_function_definition(cleaned) {
         type_declaration_set.clear();
         return cleaned.to_owned()
     }
@@ -466,6 +478,11 @@ fn move_casts_to_declaration(line: &str) -> String {
 
     let instantiation = cleaned.split(" = ").collect::<Vec<&str>>();
 
+    // storage variables are declared with their type at the contract level
+    if instantiation[0].trim_start().starts_with("stor_") {
+        return cleaned.to_owned()
+    }
+
     // get the outermost cast
     match TYPE_CAST_REGEX.find(instantiation[1]).unwrap() {
         Some(x) => {
@@ -514,7 +531,7 @@ fn replace_expression_with_var(line: &str) -> String {
     let var_map = VARIABLE_MAP.lock().unwrap();
 
     // skip function definitions
-    if cleaned.contains("function") {
+    if is_function_definition(&cleaned) {
         return cleaned
     }
 
@@ -554,7 +571,7 @@ fn inherit_infer_mem_type(line: &str) -> String {
     let mut type_map = MEMORY_TYPE_MAP.lock().unwrap();
 
     // if the line contains a function definition, wipe the type map and get arg types
-    if line.contains("function") {
+    if is_function_definition(line) {
         type_map.clear();
         let args = line.split('(').collect::<Vec<&str>>()[1].split(')').collect::<Vec<&str>>()[0]
             .split(',')
@@ -868,7 +885,7 @@ fn finalize(lines: Vec<String>, bar: &ProgressBar) -> Vec<String> {
         }
 
         // update progress bar
-        if line.contains("function") {
+        if is_function_definition(line) {
             function_count += 1;
             bar.set_message(format!("postprocessed {function_count} functions"));
         }
@@ -923,7 +940,7 @@ pub fn postprocess(
     // clean up each line using postprocessing techniques
     for line in lines {
         // update progress bar
-        if line.contains("function") {
+        if is_function_definition(&line) {
             function_count += 1;
             bar.set_message(format!("postprocessed {function_count} functions"));
         }
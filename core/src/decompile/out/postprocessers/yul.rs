@@ -1,5 +1,5 @@
 use heimdall_common::{
-    ether::{evm::core::types::find_cast, signatures::ResolvedLog},
+    ether::signatures::ResolvedLog,
     utils::strings::{find_balanced_encapsulator, split_string_by_regex},
 };
 use indicatif::ProgressBar;
@@ -46,7 +46,8 @@ fn remove_double_negation(line: &str) -> String {
     cleaned
 }
 
-/// Convert bitwise operations to a variable type cast
+/// Convert address bitmasks to the castToAddress helper. Other bitmasks are kept, as yul has no
+/// types to cast to.
 fn convert_bitmask_to_casting(line: &str) -> String {
     let mut cleaned = line.to_owned();
 
@@ -58,89 +59,34 @@ fn convert_bitmask_to_casting(line: &str) -> String {
         // get indices of arguments
         let (start_index, end_index, _) = find_balanced_encapsulator(&cleaned[index..], ('(', ')'));
         let args = &cleaned[start_index + index + 1..end_index + index - 1];
-        let args_vec: Vec<&str> = args.split(", ").collect();
-        let arg1 = args_vec[0];
-        let arg2 = args_vec[1..].join(", ");
-
-        // check if arg1 or arg2 is a bitmask of all 1's
-        let is_lhs_all_ones = arg1.replacen("0x", "", 1).chars().all(|c| c == 'f' || c == 'F');
-        let is_rhs_all_ones = arg2.replacen("0x", "", 1).chars().all(|c| c == 'f' || c == 'F');
-        if !is_lhs_all_ones && !is_rhs_all_ones {
-            index += end_index + 1;
-            continue // skip if LHS and RHS are not bitwise masks
-        }
+        let args_vec = split_string_by_regex(args, ARGS_SPLIT_REGEX.clone());
+        let (arg1, arg2) = match args_vec.as_slice() {
+            [arg1, arg2] => (arg1.clone(), arg2.clone()),
+            _ => {
+                index += 4;
+                continue
+            }
+        };
 
-        // determine size of bytes based on argument 1
-        let size_bytes = if is_lhs_all_ones {
-            (arg1.replacen("0x", "", 1).len() / 2) as u32
+        // check if arg1 or arg2 is a 20 byte bitmask of all 1's
+        let is_address_mask = |arg: &str| {
+            let mask = arg.replacen("0x", "", 1);
+            mask.len() == 40 && mask.chars().all(|c| c == 'f' || c == 'F')
+        };
+        let subject = if is_address_mask(&arg1) {
+            arg2
+        } else if is_address_mask(&arg2) {
+            arg1
         } else {
-            (arg2.replacen("0x", "", 1).len() / 2) as u32
+            index += 4;
+            continue // skip if neither argument is an address mask
         };
 
-        // construct new string with casting
-        let new_str = format!("bytes{size_bytes}({arg2})");
-
-        // replace old string with new string
-        cleaned.replace_range(index..end_index + index, &new_str);
+        // replace the bitmask with the helper
+        cleaned.replace_range(index..end_index + index, &format!("castToAddress({subject})"));
 
         // set index for next iteration of loop
-        index += format!("bytes{size_bytes}(").len();
-    }
-
-    cleaned
-}
-
-/// Removes unnecessary casts
-fn simplify_casts(line: &str) -> String {
-    let mut cleaned = line.to_owned();
-
-    // remove unnecessary casts
-    let (cast_start, cast_end, cast_type) = find_cast(&cleaned);
-
-    if let Some(cast) = cast_type {
-        let cleaned_cast_pre = cleaned[0..cast_start].to_string();
-        let cleaned_cast_post = cleaned[cast_end..].to_string();
-        let cleaned_cast = cleaned[cast_start..cast_end].to_string().replace(&cast, "");
-
-        cleaned = format!("{cleaned_cast_pre}{cleaned_cast}{cleaned_cast_post}");
-
-        // check if there are remaining casts
-        let (_, _, remaining_cast_type) = find_cast(&cleaned_cast_post);
-        if remaining_cast_type.is_some() {
-            // a cast is remaining, simplify it
-            cleaned = format!(
-                "{}{}{}",
-                cleaned_cast_pre,
-                cleaned_cast,
-                simplify_casts(&cleaned_cast_post)
-            );
-        }
-    }
-
-    cleaned
-}
-
-/// Removes or replaces casts with helper functions
-fn remove_replace_casts(line: &str) -> String {
-    let mut cleaned = line.to_owned();
-
-    // remove casts to bytes32
-    cleaned = cleaned.replace("bytes32", "");
-
-    // casts to bytes20 are replaced with the helper castToAddress
-    cleaned = cleaned.replace("bytes20", "castToAddress");
-
-    // convert casts to their yul reprs, for example, bytes1(x) -> (x):bytes1
-    loop {
-        let (cast_start, cast_end, cast_type) = find_cast(&cleaned);
-        if let Some(cast_type) = cast_type {
-            let cast_arg = &cleaned[cast_start + 1..cast_end - 1];
-            let yul_cast = format!("({cast_arg}) : {cast_type}");
-
-            cleaned.replace_range(cast_start - cast_type.len()..=cast_end - 1, &yul_cast);
-        } else {
-            break
-        }
+        index += "castToAddress(".len();
     }
 
     cleaned
@@ -305,15 +251,9 @@ fn cleanup(line: &str, all_resolved_events: HashMap<String, ResolvedLog>) -> Str
     // remove double negations
     cleaned = remove_double_negation(&cleaned);
 
-    // find and replace casts
+    // replace address bitmasks with the castToAddress helper
     cleaned = convert_bitmask_to_casting(&cleaned);
 
-    // remove unnecessary casts
-    cleaned = simplify_casts(&cleaned);
-
-    // remove or replace casts with helper functions
-    cleaned = remove_replace_casts(&cleaned);
-
     // remove unnecessary parentheses
     cleaned = simplify_parentheses(&cleaned, 0);
 
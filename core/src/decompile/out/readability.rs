@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+
+use heimdall_common::utils::io::logging::Logger;
+
+use crate::decompile::out::{
+    naming::{owner_variables, to_pascal_case},
+    postprocessers::solidity::is_function_definition,
+};
+
+/// A temporary variable's assignment within a function, i.e. `address var_a = arg0;`.
+struct Assignment {
+    line: usize,
+    /// The declared type of the temporary, if the assignment declares it.
+    declared_type: Option<String>,
+    name: String,
+    expression: String,
+}
+
+/// Whether the character can be part of an identifier.
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Find each occurrence of the identifier within the line, by byte offset, skipping occurrences
+/// which are only part of a longer identifier, such as `var_a` within `var_ab`.
+fn identifier_offsets(line: &str, identifier: &str) -> Vec<usize> {
+    line.match_indices(identifier)
+        .map(|(start, _)| start)
+        .filter(|start| {
+            !line[..*start].ends_with(is_identifier_char) &&
+                !line[start + identifier.len()..].starts_with(is_identifier_char)
+        })
+        .collect()
+}
+
+/// Replace every occurrence of the identifier within the line.
+///
+/// ```
+/// use heimdall_core::decompile::out::readability::replace_identifier;
+///
+/// assert_eq!(replace_identifier("var_a + var_ab", "var_a", "balance_"), "balance_ + var_ab");
+/// ```
+pub fn replace_identifier(line: &str, identifier: &str, replacement: &str) -> String {
+    let mut replaced = line.to_string();
+    for start in identifier_offsets(line, identifier).into_iter().rev() {
+        replaced.replace_range(start..start + identifier.len(), replacement);
+    }
+    replaced
+}
+
+/// The identifiers referenced by an expression, such as `stor_a` and `arg0`.
+fn identifiers(expression: &str) -> HashSet<String> {
+    expression
+        .split(|c: char| !is_identifier_char(c))
+        .filter(|token| token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'))
+        .map(String::from)
+        .collect()
+}
+
+/// Parse a temporary variable's assignment, i.e. `uint256 var_a = arg0 + 0x01;`.
+fn parse_assignment(line: usize, code: &str) -> Option<Assignment> {
+    let (target, expression) = code.trim().strip_suffix(';')?.split_once(" = ")?;
+    let (declared_type, name) = match target.rsplit_once(' ') {
+        Some((declared_type, name)) => (Some(declared_type.to_string()), name),
+        None => (None, target),
+    };
+    if !name.starts_with("var_") || !name[4..].chars().all(|c| c.is_ascii_lowercase()) {
+        return None
+    }
+
+    Some(Assignment {
+        line,
+        declared_type,
+        name: name.to_string(),
+        expression: expression.to_string(),
+    })
+}
+
+/// The root of the variable assigned to by the line, such as `stor_map_a` for
+/// `stor_map_a[arg0] = 0x01;`.
+fn assigned_variable(code: &str) -> Option<&str> {
+    let (target, _) = code.trim().split_once(" = ")?;
+    target.rsplit(' ').next()?.split('[').next()
+}
+
+/// Whether the line calls another contract, which may change storage before returning.
+fn makes_external_call(code: &str) -> bool {
+    code.contains(").call") || code.contains(").delegatecall") || code.contains(").staticcall")
+}
+
+/// Whether an inlined expression with the declared type needs an explicit cast to keep it, since
+/// the postprocessor moves the outermost cast of an assignment into the declaration.
+fn is_cast_type(declared_type: &str) -> bool {
+    let size = |prefix: &str| {
+        declared_type.strip_prefix(prefix).map(|size| size.chars().all(|c| c.is_ascii_digit()))
+    };
+    declared_type == "address" ||
+        size("uint").unwrap_or(false) ||
+        size("int").unwrap_or(false) ||
+        size("bytes").map(|sized| sized && declared_type.len() > 5).unwrap_or(false)
+}
+
+/// The expression which replaces a use of the temporary, cast to the temporary's declared type,
+/// and parenthesized unless it's a single term, or the use is an entire statement's value.
+fn inlined_expression(assignment: &Assignment, use_line: &str) -> String {
+    let expression = assignment.expression.as_str();
+    let standalone = identifier_offsets(use_line, &assignment.name).first().is_some_and(|start| {
+        let (before, after) = (&use_line[..*start], &use_line[start + assignment.name.len()..]);
+        (before.ends_with(" = ") || before.trim_start() == "return ") && after == ";"
+    });
+    match &assignment.declared_type {
+        Some(declared_type) if is_cast_type(declared_type) => {
+            format!("{declared_type}({expression})")
+        }
+        _ if expression.contains(' ') && !standalone => format!("({expression})"),
+        _ => expression.to_string(),
+    }
+}
+
+/// Inline the temporaries within the function which are assigned once and used once, on a later
+/// line within the same block, if the assigned expression is at most `threshold` characters and
+/// nothing it references may change in between.
+fn inline_temporaries(lines: &mut Vec<String>, threshold: usize) {
+    loop {
+        let assignments = lines
+            .iter()
+            .enumerate()
+            .filter_map(|(i, line)| parse_assignment(i, line))
+            .collect::<Vec<_>>();
+
+        let inlinable = assignments.iter().find_map(|assignment| {
+            let referenced = identifiers(&assignment.expression);
+            if assignment.expression.len() > threshold ||
+                referenced.contains(&assignment.name) ||
+                makes_external_call(&assignment.expression) ||
+                assignments.iter().filter(|other| other.name == assignment.name).count() > 1
+            {
+                return None
+            }
+
+            let uses = lines
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != assignment.line)
+                .flat_map(|(i, line)| {
+                    identifier_offsets(line, &assignment.name).into_iter().map(move |_| i)
+                })
+                .collect::<Vec<usize>>();
+            let use_line = match uses.as_slice() {
+                [use_line] if *use_line > assignment.line => *use_line,
+                _ => return None,
+            };
+            if lines[use_line].trim_start().starts_with("for (") ||
+                lines[use_line].trim_start().starts_with("while (")
+            {
+                return None
+            }
+
+            // the expression must evaluate to the same value where it's used
+            let reads_storage = referenced.iter().any(|identifier| identifier.starts_with("stor_"));
+            let unchanged = lines[assignment.line + 1..use_line].iter().all(|line| {
+                !line.contains('{') &&
+                    !line.contains('}') &&
+                    !assigned_variable(line).is_some_and(|v| referenced.contains(v)) &&
+                    (!reads_storage || !makes_external_call(line))
+            });
+
+            unchanged.then_some((assignment, use_line))
+        });
+
+        let (assignment, use_line) = match inlinable {
+            Some(inlinable) => inlinable,
+            None => break,
+        };
+        lines[use_line] = replace_identifier(
+            &lines[use_line],
+            &assignment.name,
+            &inlined_expression(assignment, &lines[use_line]),
+        );
+        lines.remove(assignment.line);
+    }
+}
+
+/// `stor_map_a` becomes `storMapA`, for use as a variable name.
+fn to_camel_case(variable: &str) -> String {
+    let pascal = to_pascal_case(variable);
+    pascal[..1].to_lowercase() + &pascal[1..]
+}
+
+/// Suggest a name for a temporary from where its value comes from, such as `balance_` for
+/// `address(this).balance`, or otherwise where it's written to, such as `newOwner_` for a value
+/// written to the owner. Names end with an underscore, so they never shadow a builtin or a
+/// keyword.
+fn semantic_name(
+    assignment: &Assignment,
+    lines: &[String],
+    owner_variables: &HashSet<String>,
+) -> Option<String> {
+    let expression = assignment.expression.as_str();
+    let referenced = identifiers(expression);
+    let source = if expression.contains(".balance") || expression.contains("selfbalance()") {
+        Some(String::from("balance_"))
+    } else if expression.contains("ecrecover(") {
+        Some(String::from("signer_"))
+    } else if expression.contains("keccak256(") {
+        Some(String::from("hash_"))
+    } else if expression.contains("block.timestamp") {
+        Some(String::from("timestamp_"))
+    } else if expression.contains("block.number") {
+        Some(String::from("blockNumber_"))
+    } else if expression.contains("msg.value") {
+        Some(String::from("value_"))
+    } else if expression.contains("msg.sender") {
+        Some(String::from("sender_"))
+    } else {
+        referenced.iter().filter(|identifier| identifier.starts_with("stor_")).min().map(
+            |variable| match owner_variables.contains(variable) {
+                true => String::from("owner_"),
+                false => format!("{}_", to_camel_case(variable)),
+            },
+        )
+    };
+    if source.is_some() {
+        return source
+    }
+
+    // otherwise, a value which is written to storage is named after the variable it's written to
+    let written_to = lines.iter().find_map(|line| {
+        let (_, value) = line.trim().strip_suffix(';')?.split_once(" = ")?;
+        let value = value.trim_end_matches(')').rsplit('(').next()?;
+        match value == assignment.name {
+            true => assigned_variable(line).filter(|variable| variable.starts_with("stor_")),
+            false => None,
+        }
+    });
+    if let Some(variable) = written_to {
+        return Some(match owner_variables.contains(variable) {
+            true => String::from("newOwner_"),
+            false => format!("new{}_", to_pascal_case(variable)),
+        })
+    }
+
+    // and a value derived from a single argument is named after it
+    let mut arguments = referenced.iter().filter(|identifier| {
+        identifier.starts_with("arg") && identifier[3..].chars().all(|c| c.is_ascii_digit())
+    });
+    let name = match (arguments.next(), arguments.next()) {
+        (Some(argument), None) => format!("{argument}_"),
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Rename the temporaries within the function whose value has a recognizable source or
+/// destination, numbering names which would otherwise collide, i.e. `balance_` and `balance2_`.
+fn name_temporaries(lines: &mut [String], owner_variables: &HashSet<String>) {
+    let mut taken = lines.iter().flat_map(|line| identifiers(line)).collect::<HashSet<String>>();
+
+    let mut seen = HashSet::new();
+    let assignments = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(i, line)| parse_assignment(i, line))
+        .filter(|assignment| seen.insert(assignment.name.clone()))
+        .collect::<Vec<_>>();
+
+    for assignment in assignments {
+        let base = match semantic_name(&assignment, lines, owner_variables) {
+            Some(base) => base,
+            None => continue,
+        };
+        let stem = base.trim_end_matches('_');
+        let name = (1..)
+            .map(|n| match n {
+                1 => base.clone(),
+                n => format!("{stem}{n}_"),
+            })
+            .find(|name| !taken.contains(name))
+            .expect("names are unbounded");
+
+        taken.insert(name.clone());
+        for line in lines.iter_mut() {
+            *line = replace_identifier(line, &assignment.name, &name);
+        }
+    }
+}
+
+/// Improve the readability of decompiled Solidity source. Within each function, temporaries
+/// which are assigned once and used once are inlined if their expression is at most
+/// `inline_threshold` characters, and the remaining temporaries are named from the storage,
+/// calldata, or environment their value comes from, such as `balance_`, `owner_`, or `arg0_`,
+/// or from the storage variable they're written to, such as `newOwner_`.
+pub fn improve_readability(source: &str, inline_threshold: usize) -> String {
+    // get a new logger
+    let logger = Logger::default();
+
+    // split the source into functions, each from its header up to the next function's header
+    let mut functions: Vec<Vec<String>> = vec![Vec::new()];
+    for line in source.lines() {
+        if is_function_definition(line) && !line.trim_start().starts_with("//") {
+            functions.push(Vec::new());
+        }
+        functions.last_mut().expect("there is always a function").push(line.to_string());
+    }
+    logger.debug(&format!("improving the readability of {} functions .", functions.len() - 1));
+
+    for lines in functions.iter_mut() {
+        inline_temporaries(lines, inline_threshold);
+    }
+
+    // owners are found once temporaries are inlined, since they're usually compared directly
+    let owner_variables = owner_variables(&functions.concat().join("\n"));
+    for lines in functions.iter_mut() {
+        name_temporaries(lines, &owner_variables);
+    }
+
+    functions.concat().join("\n")
+}
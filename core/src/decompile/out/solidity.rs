@@ -1,7 +1,11 @@
 use std::{collections::HashMap, time::Duration};
 
 use heimdall_common::{
-    ether::signatures::{ResolvedError, ResolvedLog},
+    ether::{
+        labels::annotate_labels,
+        metadata::ContractMetadata,
+        signatures::{ResolvedError, ResolvedLog},
+    },
     utils::{
         io::{
             file::short_path,
@@ -19,6 +23,7 @@ use super::{
         DecompilerArgs,
     },
     abi::ABIStructure,
+    add_metadata_to_header,
     postprocessers::solidity::postprocess,
 };
 
@@ -31,6 +36,7 @@ pub fn build_solidity_output(
     functions: Vec<Function>,
     all_resolved_errors: HashMap<String, ResolvedError>,
     all_resolved_events: HashMap<String, ResolvedLog>,
+    metadata: Option<&ContractMetadata>,
     trace: &mut TraceFactory,
     trace_parent: u32,
 ) -> Result<String, Box<dyn std::error::Error>> {
@@ -66,7 +72,10 @@ pub fn build_solidity_output(
     );
 
     // write the header to the output file
-    decompiled_output.push(DECOMPILED_SOURCE_HEADER_SOL.replace("{}", env!("CARGO_PKG_VERSION")));
+    decompiled_output.push(add_metadata_to_header(
+        &DECOMPILED_SOURCE_HEADER_SOL.replace("{}", env!("CARGO_PKG_VERSION")),
+        metadata,
+    ));
     decompiled_output.push(String::from("contract DecompiledContract {"));
 
     // add blank line if there are events
@@ -140,6 +149,7 @@ pub fn build_solidity_output(
 
     for function in functions {
         progress_bar.set_message(format!("writing logic for '0x{}'", function.selector));
+        let logic = function.logic_with_unchecked_blocks();
 
         // build the function's header and parameters
         let function_modifiers = format!(
@@ -162,7 +172,24 @@ pub fn build_solidity_output(
             }
         );
 
+        let is_fallback = function.is_fallback() || function.is_constructor();
         let function_header = match function.resolved_function {
+            Some(resolved_function) if function.is_constructor() => format!(
+                "constructor({}) {}{{",
+                resolved_function
+                    .inputs
+                    .iter()
+                    .enumerate()
+                    .map(|(index, solidity_type)| format!("{solidity_type} arg{index}"))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                if function.payable { "payable " } else { "" }
+            ),
+            _ if is_fallback => format!(
+                "{}() external {}{{",
+                function.selector,
+                if function.payable { "payable " } else { "" }
+            ),
             Some(resolved_function) => {
                 format!(
                     "function {}({}) {}{}",
@@ -226,14 +253,16 @@ pub fn build_solidity_output(
         };
 
         // print natspec header for the function
-        decompiled_output.extend(vec![
-            String::new(),
-            format!("/// @custom:selector    0x{}", function.selector),
-            format!(
-                "/// @custom:name        {}",
-                function_header.replace("function ", "").split('(').next().unwrap()
-            ),
-        ]);
+        decompiled_output.push(String::new());
+        if !is_fallback {
+            decompiled_output.extend(vec![
+                format!("/// @custom:selector    0x{}", function.selector),
+                format!(
+                    "/// @custom:name        {}",
+                    function_header.replace("function ", "").split('(').next().unwrap()
+                ),
+            ]);
+        }
 
         for notice in function.notices {
             decompiled_output.push(format!("/// @notice             {notice}"));
@@ -250,7 +279,7 @@ pub fn build_solidity_output(
         decompiled_output.push(function_header);
 
         // build the function's body
-        decompiled_output.extend(function.logic);
+        decompiled_output.extend(logic);
 
         decompiled_output.push(String::from("}"));
     }
@@ -258,6 +287,9 @@ pub fn build_solidity_output(
     decompiled_output.push(String::from("}"));
 
     progress_bar.finish_and_clear();
-    Ok(postprocess(decompiled_output, all_resolved_errors, all_resolved_events, &progress_bar)
-        .join("\n"))
+    // annotate hardcoded addresses with their labels from the user's address book
+    Ok(annotate_labels(
+        &postprocess(decompiled_output, all_resolved_errors, all_resolved_events, &progress_bar)
+            .join("\n"),
+    ))
 }
@@ -0,0 +1,129 @@
+use std::collections::HashSet;
+
+/// Whether the line starts a function definition, including constructors and the `receive` and
+/// `fallback` functions.
+fn is_function_header(line: &str) -> bool {
+    ["function ", "constructor(", "receive() ", "fallback() "]
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+}
+
+/// The name of the function defined by the given header, such as `transfer` for
+/// `function transfer(address arg0, uint256 arg1) public {`.
+fn function_name(header: &str) -> String {
+    header.trim_start_matches("function ").split('(').next().unwrap_or_default().trim().to_string()
+}
+
+/// A function within decompiled Solidity source, along with its NatSpec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFunction {
+    pub name: String,
+    /// The function's selector from its NatSpec, with a `0x` prefix. Fallback functions and
+    /// constructors have none.
+    pub selector: Option<String>,
+    pub lines: Vec<String>,
+}
+
+/// Split decompiled Solidity source into the header shared by all of its functions, from the
+/// license to the contract's storage, events, and errors, and each of its functions. Returns
+/// `None` if the source doesn't define a contract.
+pub fn parse_contract(source: &str) -> Option<(Vec<String>, Vec<SourceFunction>)> {
+    let lines = source.lines().collect::<Vec<&str>>();
+    let contract_start = lines.iter().position(|line| line.starts_with("contract "))?;
+
+    // sort the contract's members into the shared header and its functions
+    let mut header =
+        lines[..=contract_start].iter().map(|line| line.to_string()).collect::<Vec<_>>();
+    let mut functions: Vec<SourceFunction> = Vec::new();
+    let mut natspec: Vec<String> = Vec::new();
+    let mut depth = 1;
+    for line in &lines[contract_start + 1..] {
+        let trimmed = line.trim();
+        if depth == 1 {
+            if trimmed == "}" {
+                break
+            } else if trimmed.starts_with("///") {
+                natspec.push(line.to_string());
+                continue
+            } else if is_function_header(trimmed) {
+                let selector = natspec.iter().find_map(|line| {
+                    line.trim().strip_prefix("/// @custom:selector").map(|s| s.trim().to_string())
+                });
+                let mut function = std::mem::take(&mut natspec);
+                function.push(line.to_string());
+                functions.push(SourceFunction {
+                    name: function_name(trimmed),
+                    selector,
+                    lines: function,
+                });
+            } else if !trimmed.is_empty() {
+                header.append(&mut natspec);
+                header.push(line.to_string());
+            } else if functions.is_empty() && header.last().is_some_and(|l| !l.trim().is_empty()) {
+                header.push(String::new());
+            }
+        } else if let Some(function) = functions.last_mut() {
+            function.lines.push(line.to_string());
+        }
+
+        depth += line.matches('{').count() as i32 - line.matches('}').count() as i32;
+    }
+
+    // drop the blank line which separated the header from the first function
+    while header.last().is_some_and(|line| line.trim().is_empty()) {
+        header.pop();
+    }
+
+    Some((header, functions))
+}
+
+/// Split decompiled Solidity source into one standalone file per function, returning each
+/// file's name and contents. Every file starts with the same shared header, containing the
+/// contract's NatSpec, storage, events, and errors, followed by the function itself.
+///
+/// Overloaded functions are told apart by their selector, such as `transfer_a9059cbb.sol`.
+///
+/// ```
+/// use heimdall_core::decompile::out::split::split_functions;
+///
+/// let source = "contract DecompiledContract {\n    event Transfer(address, address, uint256);\n\n    /// @custom:selector    0x06fdde03\n    function name() public view returns (string memory) {\n        return stor_a;\n    }\n}";
+/// let files = split_functions(source);
+///
+/// assert_eq!(files.len(), 1);
+/// assert_eq!(files[0].0, "name.sol");
+/// assert!(files[0].1.starts_with("contract DecompiledContract {\n    event Transfer("));
+/// assert!(files[0].1.ends_with("        return stor_a;\n    }\n}"));
+/// ```
+pub fn split_functions(source: &str) -> Vec<(String, String)> {
+    let (header, functions) = match parse_contract(source) {
+        Some(contract) => contract,
+        None => return Vec::new(),
+    };
+
+    // overloaded functions share a name, so they're told apart by their selector
+    let overloaded = {
+        let mut seen = HashSet::new();
+        functions
+            .iter()
+            .filter(|function| !seen.insert(function.name.clone()))
+            .map(|function| function.name.clone())
+            .collect::<HashSet<String>>()
+    };
+
+    functions
+        .into_iter()
+        .enumerate()
+        .map(|(index, function)| {
+            let name = &function.name;
+            let file_name = match (overloaded.contains(name), &function.selector) {
+                (false, _) => format!("{name}.sol"),
+                (true, Some(selector)) => {
+                    format!("{name}_{}.sol", selector.trim_start_matches("0x"))
+                }
+                (true, None) => format!("{name}_{index}.sol"),
+            };
+            let contents = format!("{}\n\n{}\n}}", header.join("\n"), function.lines.join("\n"));
+            (file_name, contents)
+        })
+        .collect()
+}
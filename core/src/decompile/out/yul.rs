@@ -2,7 +2,7 @@ use std::{collections::HashMap, time::Duration};
 
 use crate::decompile::{constants::DECOMPILED_SOURCE_HEADER_YUL, util::Function, DecompilerArgs};
 use heimdall_common::{
-    ether::signatures::ResolvedLog,
+    ether::{labels::annotate_labels, metadata::ContractMetadata, signatures::ResolvedLog},
     utils::io::{
         file::short_path,
         logging::{Logger, TraceFactory},
@@ -10,7 +10,7 @@ use heimdall_common::{
 };
 use indicatif::ProgressBar;
 
-use super::postprocessers::yul::postprocess;
+use super::{add_metadata_to_header, postprocessers::yul::postprocess};
 
 /// Build the decompiled Yul source code from the given functions. Will piece together decompiled
 /// [`Function`]s and [`ResolvedLog`]s into a Yul contract.
@@ -18,6 +18,7 @@ pub fn build_yul_output(
     args: &DecompilerArgs,
     functions: Vec<Function>,
     all_resolved_events: HashMap<String, ResolvedLog>,
+    metadata: Option<&ContractMetadata>,
     trace: &mut TraceFactory,
     trace_parent: u32,
 ) -> Result<String, Box<dyn std::error::Error>> {
@@ -52,12 +53,21 @@ pub fn build_yul_output(
 
     // add the header to the output
     decompiled_output.extend(
-        DECOMPILED_SOURCE_HEADER_YUL
-            .replace("{}", env!("CARGO_PKG_VERSION"))
-            .split('\n')
-            .map(|x| x.to_string()),
+        add_metadata_to_header(
+            &DECOMPILED_SOURCE_HEADER_YUL.replace("{}", env!("CARGO_PKG_VERSION")),
+            metadata,
+        )
+        .split('\n')
+        .map(|x| x.to_string()),
     );
 
+    // the receive and fallback functions are reached through the switch's default case. the
+    // constructor isn't part of the runtime code, so it isn't included.
+    let (fallback_functions, functions): (Vec<Function>, Vec<Function>) = functions
+        .into_iter()
+        .filter(|function| !function.is_constructor())
+        .partition(|function| function.is_fallback());
+
     // build contract logic
     for function in functions {
         progress_bar.set_message(format!("building logic for '0x{}'", function.selector));
@@ -129,14 +139,30 @@ pub fn build_yul_output(
         decompiled_output.push(String::from("}"));
     }
 
+    // build the default case from the receive and fallback functions
+    decompiled_output.push(String::from("default {"));
+    let mut has_fallback = false;
+    for function in fallback_functions {
+        if function.selector == "receive" {
+            decompiled_output.push(String::from("if iszero(calldatasize()) {"));
+            decompiled_output.extend(function.logic);
+            decompiled_output.push(String::from("stop()"));
+            decompiled_output.push(String::from("}"));
+        } else {
+            has_fallback = true;
+            decompiled_output.extend(function.logic);
+        }
+    }
+    if !has_fallback {
+        decompiled_output.push(String::from("revert(0, 0)"));
+    }
+
     // closing brackets
-    decompiled_output.append(&mut vec![
-        "default { revert(0, 0) }".to_string(),
-        "}".to_string(),
-        "}".to_string(),
-        "}".to_string(),
-    ]);
+    decompiled_output.append(&mut vec!["}".to_string(), "}".to_string(), "}".to_string()]);
 
     progress_bar.finish_and_clear();
-    Ok(postprocess(decompiled_output, all_resolved_events, &progress_bar).join("\n"))
+    // annotate hardcoded addresses with their labels from the user's address book
+    Ok(annotate_labels(
+        &postprocess(decompiled_output, all_resolved_events, &progress_bar).join("\n"),
+    ))
 }
@@ -1,5 +1,5 @@
 use ethers::types::U256;
-use heimdall_common::ether::evm::core::opcodes::WrappedOpcode;
+use heimdall_common::ether::evm::core::{opcodes::WrappedOpcode, precompiles::Precompile};
 
 use super::util::StorageFrame;
 
@@ -8,59 +8,39 @@ use super::util::StorageFrame;
 /// precompiled contract. It is relatively trivial to do this, as calls to specific addresses (i.e,
 /// `0x..01`), are precompiled contracts.
 /// Once a precompile has been detected, this function attempts to format it in a solidity-like
-/// format.
+/// format, as a call to a named helper, i.e. `uint256[2] memory[0x80] = ecAdd(...);`.
 pub fn decode_precompile(
     precompile_address: U256,
     extcalldata_memory: Vec<StorageFrame>,
     return_data_offset: WrappedOpcode,
 ) -> (bool, String) {
-    // safely convert the precompile address to a usize.
-    let address: usize = match precompile_address.try_into() {
-        Ok(x) => x,
-        Err(_) => usize::MAX,
+    let precompile = match Precompile::from_address(precompile_address) {
+        Some(precompile) => precompile,
+        None => return (false, String::new()),
     };
-    let mut is_ext_call_precompile = false;
-    let mut ext_call_logic = String::new();
 
-    match address {
-        1 => {
-            is_ext_call_precompile = true;
-            ext_call_logic = format!(
-                "address memory[{}] = ecrecover({});",
-                return_data_offset.solidify(),
-                extcalldata_memory
-                    .iter()
-                    .map(|x| x.operations.solidify())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            );
-        }
-        2 => {
-            is_ext_call_precompile = true;
-            ext_call_logic = format!(
-                "bytes memory[{}] = sha256({});",
-                return_data_offset.solidify(),
-                extcalldata_memory
-                    .iter()
-                    .map(|x| x.operations.solidify())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            );
-        }
-        3 => {
-            is_ext_call_precompile = true;
-            ext_call_logic = format!(
-                "bytes memory[{}] = ripemd160({});",
-                return_data_offset.solidify(),
-                extcalldata_memory
-                    .iter()
-                    .map(|x| x.operations.solidify())
-                    .collect::<Vec<String>>()
-                    .join(", ")
-            );
-        }
-        _ => {}
-    }
+    // the type of the precompile's output, as it's written to memory
+    let return_type = match precompile {
+        Precompile::EcRecover => "address",
+        Precompile::Sha256 | Precompile::Ripemd160 => "bytes",
+        Precompile::Identity | Precompile::ModExp => "bytes",
+        Precompile::EcAdd | Precompile::EcMul => "uint256[2]",
+        Precompile::EcPairing => "bool",
+        Precompile::Blake2f => "bytes32[2]",
+    };
 
-    (is_ext_call_precompile, ext_call_logic)
+    (
+        true,
+        format!(
+            "{} memory[{}] = {}({});",
+            return_type,
+            return_data_offset.solidify(),
+            precompile.name(),
+            extcalldata_memory
+                .iter()
+                .map(|x| x.operations.solidify())
+                .collect::<Vec<String>>()
+                .join(", ")
+        ),
+    )
 }
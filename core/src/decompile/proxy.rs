@@ -0,0 +1,176 @@
+use std::{collections::HashSet, str::FromStr};
+
+use ethers::types::H256;
+use heimdall_common::{ether::rpc::get_storage_at_batch, utils::strings::encode_hex};
+
+use super::{
+    out::{abi::ABIStructure, split::parse_contract},
+    DecompileResult,
+};
+
+/// The storage slots which proxy standards store their implementation's address in, along with
+/// the standard's name.
+pub const IMPLEMENTATION_SLOTS: [(&str, &str); 2] = [
+    ("EIP-1967", "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc"),
+    ("EIP-1822", "c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7"),
+];
+
+/// The implementation behind a proxy, decompiled as its own target.
+#[derive(Debug, Clone)]
+pub struct ProxyImplementation {
+    /// The address of the implementation contract.
+    pub address: String,
+    /// The proxy standard whose storage slot the implementation's address was read from.
+    pub standard: String,
+    /// The decompilation of the implementation contract.
+    pub result: DecompileResult,
+    /// The implementation's ABI, along with the functions only the proxy defines.
+    pub merged_abi: Vec<ABIStructure>,
+    /// The implementation's Solidity source, presented as the proxy, if both were decompiled
+    /// to Solidity.
+    pub merged_source: Option<String>,
+}
+
+/// The address stored within a proxy's implementation slot, or `None` if the slot doesn't hold
+/// an address.
+///
+/// ```
+/// use ethers::types::H256;
+/// use heimdall_core::decompile::proxy::implementation_address;
+/// use std::str::FromStr;
+///
+/// let word = H256::from_str("0x000000000000000000000000e90d8fb7b79c8930b5c8891e61c298b412a6e81a").unwrap();
+/// assert_eq!(implementation_address(word), Some(String::from("0xe90d8fb7b79c8930b5c8891e61c298b412a6e81a")));
+/// assert_eq!(implementation_address(H256::zero()), None);
+/// ```
+pub fn implementation_address(word: H256) -> Option<String> {
+    let bytes = word.as_bytes();
+    if bytes[..12].iter().any(|byte| *byte != 0) || bytes[12..].iter().all(|byte| *byte == 0) {
+        return None
+    }
+
+    Some(format!("0x{}", encode_hex(bytes[12..].to_vec())))
+}
+
+/// Read the implementation slots of the target, returning the standard and the address of the
+/// first one which is set.
+pub async fn find_implementation(
+    target: &str,
+    rpc_url: &str,
+    block: &str,
+) -> Result<Option<(String, String)>, Box<dyn std::error::Error>> {
+    let slots = IMPLEMENTATION_SLOTS
+        .iter()
+        .map(|(_, slot)| H256::from_str(slot))
+        .collect::<Result<Vec<H256>, _>>()?;
+    let values = get_storage_at_batch(rpc_url, target, &slots, block).await?;
+
+    Ok(IMPLEMENTATION_SLOTS.iter().zip(values).find_map(|((standard, _), value)| {
+        implementation_address(value).map(|address| (standard.to_string(), address))
+    }))
+}
+
+/// The selector of each function in the ABI, such as those defined by the implementation.
+fn abi_selectors(abi: &[ABIStructure]) -> HashSet<String> {
+    abi.iter()
+        .filter_map(|structure| match structure {
+            ABIStructure::Function(function) if function.type_ == "function" => {
+                Some(function.selector())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Merge the ABIs of a proxy and its implementation into the ABI users interact with at the
+/// proxy's address. Every function of the implementation is included, followed by the functions
+/// only the proxy defines, such as its admin functions. Events and errors of both are included
+/// once.
+pub fn merge_proxy_abi(
+    proxy: &[ABIStructure],
+    implementation: &[ABIStructure],
+) -> Vec<ABIStructure> {
+    let implementation_selectors = abi_selectors(implementation);
+    let mut merged = implementation.to_vec();
+
+    for structure in proxy {
+        let included = match structure {
+            ABIStructure::Function(function) => {
+                function.type_ != "function" ||
+                    implementation_selectors.contains(&function.selector())
+            }
+            structure => merged.contains(structure),
+        };
+        if !included {
+            merged.push(structure.clone());
+        }
+    }
+
+    merged
+}
+
+/// Merge the decompiled Solidity sources of a proxy and its implementation into a single source,
+/// presenting the implementation's functions under the proxy's address, whose storage they read
+/// and write. Functions which only the proxy defines, such as its admin functions, are appended,
+/// while the proxy's fallback, which delegates to the implementation, is omitted.
+pub fn merge_proxy_source(
+    proxy_source: &str,
+    implementation_source: &str,
+    proxy: &str,
+    implementation: &str,
+) -> Option<String> {
+    let (_, proxy_functions) = parse_contract(proxy_source)?;
+    let (header, functions) = parse_contract(implementation_source)?;
+    let implementation_selectors =
+        functions.iter().filter_map(|function| function.selector.clone()).collect::<HashSet<_>>();
+
+    // note where the storage lives directly below the version, and above any warnings
+    let mut lines = Vec::new();
+    for line in header {
+        let is_version = line.starts_with("/// @custom:version");
+        lines.push(line);
+
+        if is_version {
+            lines.extend([
+                format!("/// @custom:proxy     {proxy}"),
+                format!("/// @custom:impl      {implementation}"),
+                String::from("///"),
+                format!(
+                    "/// @notice           Storage is read from the proxy at {proxy}, which delegates"
+                ),
+                String::from("///                     calls to this implementation."),
+            ]);
+        }
+    }
+
+    // note the functions which only the proxy defines, above their header
+    let proxy_only = proxy_functions
+        .into_iter()
+        .filter(|function| {
+            function.selector.as_ref().is_some_and(|selector| !implementation_selectors.contains(selector))
+        })
+        .map(|mut function| {
+            let header = function
+                .lines
+                .iter()
+                .position(|line| !line.trim_start().starts_with("///"))
+                .unwrap_or_default();
+            let indent = function.lines[header]
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .collect::<String>();
+            function.lines.insert(
+                header,
+                format!("{indent}/// @notice             defined by the proxy, rather than its implementation."),
+            );
+            function
+        });
+
+    for function in functions.into_iter().chain(proxy_only) {
+        lines.push(String::new());
+        lines.extend(function.lines);
+    }
+    lines.push(String::from("}"));
+
+    Some(lines.join("\n"))
+}
@@ -1,11 +1,32 @@
 use std::collections::HashMap;
 
 use ethers::prelude::U256;
-use heimdall_common::ether::{
-    evm::core::{log::Log, opcodes::WrappedOpcode},
-    signatures::{ResolvedError, ResolvedFunction, ResolvedLog},
+use heimdall_common::{
+    ether::{
+        compiler::CompilerHeuristics,
+        evm::{
+            core::{
+                log::Log,
+                opcodes::WrappedOpcode,
+                vm::{State, VM},
+            },
+            ext::exec::VMTrace,
+        },
+        signatures::{ResolvedError, ResolvedFunction, ResolvedLog},
+        storage::{infer_from_operation, StorageType},
+    },
+    utils::strings::encode_hex,
 };
 
+use crate::decompile::constants::ZERO_CALL_VALUE_CHECK_REGEX;
+
+/// Whether the given conditional only checks that no call value was sent, e.g. `!msg.value` or
+/// `0 == msg.value`. This is the guard solc emits at the start of non-payable functions.
+pub fn is_zero_call_value_check(conditional: &str) -> bool {
+    conditional.contains("msg.value") &&
+        ZERO_CALL_VALUE_CHECK_REGEX.replace_all(conditional, "").trim().is_empty()
+}
+
 /// The [`Function`] struct represents a decompiled function found in the contract's bytecode.
 /// Throughout the decompilation process, we will build up this function's structure, and eventually
 /// write it to a file.
@@ -61,6 +82,22 @@ pub struct Function {
     pub pure: bool,
     pub view: bool,
     pub payable: bool,
+
+    // the code generation traits of the compiler which produced this function, used to
+    // recognize compiler-inserted logic.
+    pub compiler: CompilerHeuristics,
+
+    // arithmetic which checked-arithmetic compilers guard against overflow:
+    //   - key : the solidified expression.
+    //   - value : the solidified operands of the expression.
+    pub arithmetic: HashMap<String, Vec<String>>,
+
+    // the solidified conditionals which guard the compiler's overflow panics.
+    pub overflow_checks: Vec<String>,
+
+    // whether to annotate the logic with comments naming the block each group of statements was
+    // lifted from.
+    pub block_comments: bool,
 }
 
 ///
@@ -79,6 +116,72 @@ pub struct CalldataFrame {
 }
 
 impl Function {
+    /// Settle whether the function is payable once it has been analyzed. Functions rejecting call
+    /// value, either in their own prologue or before the contract's dispatcher, are non-payable,
+    /// while functions which read the call value in their logic must be payable. Checks that no
+    /// call value was sent, such as `require(0 == msg.value);`, don't count as reads.
+    pub fn resolve_payable(&mut self, rejects_call_value: bool) {
+        if rejects_call_value {
+            self.payable = false;
+        }
+        if self.logic.iter().any(|line| {
+            ZERO_CALL_VALUE_CHECK_REGEX.replace_all(line, "").contains("msg.value") ||
+                (line.contains("callvalue()") && line.trim() != "if iszero(callvalue()) {")
+        }) {
+            self.payable = true;
+        }
+    }
+
+    /// Whether this is the contract's `receive` or `fallback` function, which the dispatcher
+    /// reaches without a selector.
+    pub fn is_fallback(&self) -> bool {
+        self.selector == "receive" || self.selector == "fallback"
+    }
+
+    /// Whether this is the contract's constructor, recovered from its init code.
+    pub fn is_constructor(&self) -> bool {
+        self.selector == "constructor"
+    }
+
+    /// Whether the given arithmetic expression is guarded by one of the compiler's overflow
+    /// checks, which compare either the expression itself or all of its operands.
+    fn is_overflow_checked(&self, expression: &str, operands: &[String]) -> bool {
+        self.overflow_checks.iter().any(|check| {
+            check.contains(expression) || operands.iter().all(|operand| check.contains(operand))
+        })
+    }
+
+    /// The function's logic, with statements performing arithmetic that lacks the compiler's
+    /// overflow checks wrapped in `unchecked` blocks.
+    pub fn logic_with_unchecked_blocks(&self) -> Vec<String> {
+        let unchecked = self
+            .arithmetic
+            .iter()
+            .filter(|(expression, operands)| !self.is_overflow_checked(expression, operands))
+            .map(|(expression, _)| expression)
+            .collect::<Vec<&String>>();
+        if unchecked.is_empty() {
+            return self.logic.clone()
+        }
+
+        let mut logic = Vec::new();
+        let mut in_unchecked_block = false;
+        for line in &self.logic {
+            let is_unchecked = line.ends_with(';') &&
+                unchecked.iter().any(|expression| line.contains(expression.as_str()));
+            if is_unchecked != in_unchecked_block {
+                logic.push(if is_unchecked { "unchecked {" } else { "}" }.to_string());
+                in_unchecked_block = is_unchecked;
+            }
+            logic.push(line.clone());
+        }
+        if in_unchecked_block {
+            logic.push("}".to_string());
+        }
+
+        logic
+    }
+
     // get a specific memory slot
     pub fn get_memory_range(&self, _offset: U256, _size: U256) -> Vec<StorageFrame> {
         let mut memory_slice: Vec<StorageFrame> = Vec::new();
@@ -99,3 +202,108 @@ impl Function {
         memory_slice
     }
 }
+
+/// Find the contract's `receive` and `fallback` functions by dispatching calls without a
+/// recognized selector. Returns each function's name, the VM at its entry point, and whether it
+/// accepts call value.
+pub fn find_fallback_functions(
+    evm: &VM,
+    selectors: &HashMap<String, u128>,
+) -> Vec<(String, VM, bool)> {
+    let mut functions = Vec::new();
+
+    // any selector which the dispatcher doesn't recognize reaches the fallback function
+    let unknown_selector = (0u32..)
+        .map(|selector| selector.to_be_bytes())
+        .find(|selector| !selectors.contains_key(&encode_hex(selector.to_vec())))
+        .unwrap_or_default();
+    let fallback = evm.dispatch(&unknown_selector, 0);
+    let fallback_entry_point = fallback.as_ref().map(|vm| vm.instruction);
+    if let Some(fallback) = fallback {
+        let payable = evm
+            .dispatch(&unknown_selector, 1)
+            .map(|vm| Some(vm.instruction) == fallback_entry_point)
+            .unwrap_or(false);
+        functions.push(("fallback".to_string(), fallback, payable));
+    }
+
+    // plain transfers reach the receive function, or the fallback function if there isn't one
+    if let Some(receive) = evm.dispatch(&[], 1) {
+        if Some(receive.instruction) != fallback_entry_point {
+            functions.insert(0, ("receive".to_string(), receive, true));
+        }
+    }
+
+    functions
+}
+
+/// Whether the branch of a `JUMPI` which jumps to the given destination reverts without any side
+/// effects. The via-IR pipeline jumps to shared revert blocks when a check fails, where legacy
+/// codegen jumps past an inline revert when the check succeeds, so the jump condition of a via-IR
+/// check is the negation of the checked condition.
+pub fn jump_target_reverts(vm_trace: &VMTrace, destination: U256) -> bool {
+    vm_trace.children.iter().any(|child| {
+        U256::from(child.instruction) == destination + 1 &&
+            child.children.is_empty() &&
+            child.operations.last().map(|state| state.last_instruction.opcode) == Some(0xfd) &&
+            !child.operations.iter().any(|state| {
+                matches!(
+                    state.last_instruction.opcode,
+                    0x55 | 0xa0..=0xa4 | 0xf0..=0xf2 | 0xf4 | 0xf5 | 0xfa | 0xff
+                )
+            })
+    })
+}
+
+/// Whether the state halts its branch the way solc < 0.4.10 compiled `throw`: by jumping to an
+/// invalid destination, or with the designated `INVALID` opcode. Either way, the call reverts
+/// without any data.
+pub fn is_legacy_throw(vm_trace: &VMTrace, state: &State) -> bool {
+    match state.last_instruction.opcode {
+        0xfe => true,
+        // valid jumps continue the branch, so a jump which ends it must have been invalid
+        0x56 => {
+            vm_trace.children.is_empty() &&
+                !vm_trace.pruned &&
+                vm_trace.operations.last().is_some_and(|last| std::ptr::eq(last, state))
+        }
+        _ => false,
+    }
+}
+
+/// The statically inferred type of each storage slot the functions write to, from the values
+/// they store. Arguments are typed by the function's resolved signature, if any, or else by
+/// their most likely potential type. Slots written with values of different types are left out.
+pub fn infer_storage_types(functions: &[Function]) -> HashMap<U256, StorageType> {
+    let mut storage_types: HashMap<U256, Option<StorageType>> = HashMap::new();
+
+    for function in functions {
+        let argument_type = |index: usize| match &function.resolved_function {
+            Some(resolved_function) => resolved_function.inputs.get(index).cloned(),
+            None => function
+                .arguments
+                .get(&index)
+                .and_then(|(_, potential_types)| potential_types.first().cloned()),
+        };
+
+        for (slot, frame) in &function.storage {
+            let storage_type = match infer_from_operation(&frame.operations, argument_type) {
+                Some(storage_type) => storage_type,
+                None => continue,
+            };
+            storage_types
+                .entry(*slot)
+                .and_modify(|existing| {
+                    if *existing != Some(storage_type) {
+                        *existing = None;
+                    }
+                })
+                .or_insert(Some(storage_type));
+        }
+    }
+
+    storage_types
+        .into_iter()
+        .filter_map(|(slot, storage_type)| storage_type.map(|storage_type| (slot, storage_type)))
+        .collect()
+}
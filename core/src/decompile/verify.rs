@@ -0,0 +1,167 @@
+use std::{collections::HashSet, fs, process::Command};
+
+use heimdall_common::ether::{evm::core::vm::VM, selectors::find_function_selectors};
+
+use crate::disassemble::{disassemble, DisassemblerArgsBuilder};
+
+/// The weights of the selector, event, and CFG comparisons in the correctness score.
+const SELECTOR_WEIGHT: f64 = 0.5;
+const EVENT_WEIGHT: f64 = 0.2;
+const CFG_WEIGHT: f64 = 0.3;
+
+/// The result of recompiling decompiled output and comparing it against the original bytecode.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VerificationReport {
+    /// The error encountered while recompiling the decompiled output, if any.
+    pub compile_error: Option<String>,
+
+    /// The original contract's selectors, and those which the recompiled contract also has.
+    pub total_selectors: usize,
+    pub matched_selectors: usize,
+
+    /// The original contract's event topics, and those which the recompiled contract also has.
+    pub total_events: usize,
+    pub matched_events: usize,
+
+    /// The similarity of the original and recompiled contracts' basic block counts, from 0 to 1.
+    pub cfg_similarity: f64,
+
+    /// The weighted correctness score, from 0 to 1.
+    pub score: f64,
+}
+
+/// The features of a contract which are compared during verification.
+struct BytecodeFeatures {
+    selectors: HashSet<String>,
+    event_topics: HashSet<String>,
+    blocks: usize,
+}
+
+/// The ratio of matched to total items, where having no items to match is a perfect match.
+fn ratio(matched: usize, total: usize) -> f64 {
+    match total {
+        0 => 1.0,
+        total => matched as f64 / total as f64,
+    }
+}
+
+/// Extract the selectors, event topics, and basic block count of the given bytecode.
+async fn extract_features(bytecode: &str) -> Result<BytecodeFeatures, Box<dyn std::error::Error>> {
+    let assembly =
+        disassemble(DisassemblerArgsBuilder::new().target(bytecode.to_string()).build()?).await?;
+
+    let evm = VM::new(
+        bytecode.to_string(),
+        String::from("0x"),
+        String::from("0x6865696d64616c6c000000000061646472657373"),
+        String::from("0x6865696d64616c6c0000000000006f726967696e"),
+        String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+        0,
+        u128::MAX,
+    );
+    let selectors = find_function_selectors(&evm, &assembly).into_keys().collect();
+
+    let mut event_topics = HashSet::new();
+    let mut blocks = 1;
+    for line in assembly.lines() {
+        let instruction = line.split_whitespace().collect::<Vec<&str>>();
+        match instruction.as_slice() {
+            // event topics are pushed as full words, unlike masks which are all 0x00 or 0xff bytes
+            [_, "PUSH32", value]
+                if value.len() == 64 &&
+                    !value.as_bytes().chunks(2).all(|byte| byte == b"00" || byte == b"ff") =>
+            {
+                event_topics.insert(value.to_string());
+            }
+            [_, "JUMPDEST", ..] |
+            [_, "JUMP", ..] |
+            [_, "JUMPI", ..] |
+            [_, "STOP", ..] |
+            [_, "RETURN", ..] |
+            [_, "REVERT", ..] |
+            [_, "INVALID", ..] |
+            [_, "SELFDESTRUCT", ..] => blocks += 1,
+            _ => {}
+        }
+    }
+
+    Ok(BytecodeFeatures { selectors, event_topics, blocks })
+}
+
+/// Compare the selectors, event topics, and control flow of the original bytecode against the
+/// recompiled bytecode, producing a [`VerificationReport`].
+pub async fn compare_bytecode(
+    original: &str,
+    recompiled: &str,
+) -> Result<VerificationReport, Box<dyn std::error::Error>> {
+    let original = extract_features(original).await?;
+    let recompiled = extract_features(recompiled).await?;
+
+    let mut report = VerificationReport {
+        compile_error: None,
+        total_selectors: original.selectors.len(),
+        matched_selectors: original.selectors.intersection(&recompiled.selectors).count(),
+        total_events: original.event_topics.len(),
+        matched_events: original.event_topics.intersection(&recompiled.event_topics).count(),
+        cfg_similarity: original.blocks.min(recompiled.blocks) as f64 /
+            original.blocks.max(recompiled.blocks) as f64,
+        score: 0.0,
+    };
+    report.score = SELECTOR_WEIGHT * ratio(report.matched_selectors, report.total_selectors) +
+        EVENT_WEIGHT * ratio(report.matched_events, report.total_events) +
+        CFG_WEIGHT * report.cfg_similarity;
+
+    Ok(report)
+}
+
+/// Compile the decompiled Solidity or Yul source with `solc`, returning the runtime bytecode.
+pub fn compile_source(source: &str, is_yul: bool) -> Result<String, String> {
+    let path = std::env::temp_dir().join(format!(
+        "heimdall-verify-{}.{}",
+        std::process::id(),
+        if is_yul { "yul" } else { "sol" }
+    ));
+    fs::write(&path, source).map_err(|e| format!("failed to write source file: {e}"))?;
+
+    let mut command = Command::new("solc");
+    if is_yul {
+        command.args(["--strict-assembly", "--bin"]);
+    } else {
+        command.arg("--bin-runtime");
+    }
+    let output = command.arg(&path).output();
+    let _ = fs::remove_file(&path);
+
+    let output = output.map_err(|e| format!("failed to run solc: {e}"))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+
+    // the bytecode follows the `Binary of the runtime part:` or `Binary representation:` line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let mut bytecode = None;
+    while let Some(line) = lines.next() {
+        if line.starts_with("Binary") {
+            bytecode = lines.next().map(|line| line.trim().to_string());
+        }
+    }
+
+    bytecode
+        .filter(|bytecode| !bytecode.is_empty())
+        .ok_or_else(|| String::from("solc didn't produce any bytecode for the decompiled contract"))
+}
+
+/// Recompile the decompiled source and compare it against the original bytecode. Sources which
+/// fail to compile are reported with a score of 0.
+pub async fn verify_output(bytecode: &str, source: &str, is_yul: bool) -> VerificationReport {
+    let compiled = match compile_source(source, is_yul) {
+        Ok(compiled) => compiled,
+        Err(e) => return VerificationReport { compile_error: Some(e), ..Default::default() },
+    };
+
+    compare_bytecode(bytecode, &compiled).await.unwrap_or_else(|e| VerificationReport {
+        compile_error: Some(format!("failed to analyze recompiled bytecode: {e}")),
+        ..Default::default()
+    })
+}
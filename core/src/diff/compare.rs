@@ -0,0 +1,122 @@
+use heimdall_common::{ether::metadata::decode_metadata, utils::strings::decode_hex};
+
+/// How a deployment's code compares to the reference deployment's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeploymentMatch {
+    /// The bytecode is byte-for-byte identical.
+    Identical,
+    /// The code is identical once the metadata trailer, and the values of immutables, which are
+    /// written into `PUSH32` operands when deploying, are ignored.
+    SameCode {
+        /// Whether the metadata trailers differ, i.e. the contracts were compiled from different
+        /// sources or settings which didn't affect the code.
+        metadata_differs: bool,
+        /// The program counters of the `PUSH32` instructions whose values differ.
+        immutables: Vec<usize>,
+    },
+    /// The code itself differs.
+    Different,
+    /// There is no code at the address.
+    NotDeployed,
+}
+
+impl DeploymentMatch {
+    /// A short description of the comparison, i.e. `same code, 2 immutables differ`.
+    pub fn describe(&self) -> String {
+        match self {
+            DeploymentMatch::Identical => String::from("identical"),
+            DeploymentMatch::SameCode { metadata_differs, immutables } => {
+                match (metadata_differs, immutables.len()) {
+                    (true, 0) => String::from("same code, metadata differs"),
+                    (false, count) => format!("same code, {count} immutables differ"),
+                    (true, count) => format!("same code, metadata and {count} immutables differ"),
+                }
+            }
+            DeploymentMatch::Different => String::from("different code"),
+            DeploymentMatch::NotDeployed => String::from("not deployed"),
+        }
+    }
+}
+
+/// Split bytecode into its code and its metadata trailer, which is empty if none was found.
+fn split_metadata(bytecode: &str) -> (Vec<u8>, Vec<u8>) {
+    let mut code = decode_hex(bytecode).unwrap_or_default();
+    let size = decode_metadata(bytecode).map(|metadata| metadata.size).unwrap_or(0);
+    let trailer = code.split_off(code.len() - size.min(code.len()));
+
+    (code, trailer)
+}
+
+/// Compare a deployment's bytecode against the reference deployment's. Both are compared
+/// instruction by instruction, where only the operands of `PUSH32` instructions may differ for
+/// the code to be the same, since that's where solidity writes the values of immutables.
+pub fn compare_deployments(reference: &str, other: &str) -> DeploymentMatch {
+    let (reference, other) = (reference.trim_start_matches("0x"), other.trim_start_matches("0x"));
+    if other.is_empty() {
+        return DeploymentMatch::NotDeployed
+    }
+    if reference.eq_ignore_ascii_case(other) {
+        return DeploymentMatch::Identical
+    }
+
+    let (reference_code, reference_metadata) = split_metadata(reference);
+    let (other_code, other_metadata) = split_metadata(other);
+    if reference_code.len() != other_code.len() {
+        return DeploymentMatch::Different
+    }
+
+    let mut immutables = Vec::new();
+    let mut pc = 0;
+    while pc < reference_code.len() {
+        let opcode = reference_code[pc];
+        let size = match opcode {
+            0x60..=0x7f => 1 + (opcode - 0x5f) as usize,
+            _ => 1,
+        }
+        .min(reference_code.len() - pc);
+
+        if reference_code[pc..pc + size] != other_code[pc..pc + size] {
+            match opcode == 0x7f && other_code[pc] == opcode {
+                true => immutables.push(pc),
+                false => return DeploymentMatch::Different,
+            }
+        }
+        pc += size;
+    }
+
+    DeploymentMatch::SameCode { metadata_differs: reference_metadata != other_metadata, immutables }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_deployments() {
+        // PUSH32 <immutable> PUSH1 0x00 SSTORE STOP, followed by a solc metadata trailer
+        let code = |immutable: &str, metadata: &str| {
+            format!("7f{immutable:0>64}60005500a164736f6c6343{metadata}000a")
+        };
+
+        assert_eq!(
+            compare_deployments(&code("01", "000813"), &code("01", "000813")),
+            DeploymentMatch::Identical
+        );
+        assert_eq!(
+            compare_deployments(&code("01", "000813"), &code("01", "000814")),
+            DeploymentMatch::SameCode { metadata_differs: true, immutables: vec![] }
+        );
+        assert_eq!(
+            compare_deployments(&code("01", "000813"), &code("02", "000813")),
+            DeploymentMatch::SameCode { metadata_differs: false, immutables: vec![0] }
+        );
+        assert_eq!(
+            compare_deployments(
+                &code("01", "000813"),
+                &code("01", "000813").replace("6000", "6001")
+            ),
+            DeploymentMatch::Different
+        );
+        assert_eq!(compare_deployments(&code("01", "000813"), "0x"), DeploymentMatch::NotDeployed);
+    }
+}
@@ -0,0 +1,196 @@
+pub mod compare;
+
+use std::collections::HashMap;
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    constants::ADDRESS_REGEX,
+    ether::rpc::{get_code, parse_chain_rpc},
+    utils::io::logging::Logger,
+};
+
+use self::compare::{compare_deployments, DeploymentMatch};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Compare a contract's deployments at the same address across chains",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall diff <TARGET> --chains <CHAINS> [OPTIONS]"
+)]
+pub struct DiffArgs {
+    /// The address of the contract to compare across chains.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The chains to compare, separated by commas, e.g. `mainnet,arbitrum,base`. Each is either
+    /// the name of a configured chain, or a `name=rpc_url` pair. The first chain the contract is
+    /// deployed on is the reference the others are compared against.
+    #[clap(long = "chains", required = true)]
+    pub chains: String,
+
+    /// The configured chains, each in the `name=rpc_url` format. From the CLI, this is set by the
+    /// configured `chains`.
+    #[clap(skip)]
+    pub chain_rpc_urls: Vec<String>,
+}
+
+impl DiffArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            chains: Some(String::new()),
+            chain_rpc_urls: Some(Vec::new()),
+        }
+    }
+}
+
+/// A contract's deployment on a single chain.
+#[derive(Debug, Clone)]
+pub struct ChainDeployment {
+    pub chain: String,
+    /// The size of the deployed bytecode in bytes, which is zero if it isn't deployed.
+    pub size: usize,
+    /// How the deployment compares to the reference deployment.
+    pub comparison: DeploymentMatch,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffResult {
+    /// The chain whose deployment the others are compared against.
+    pub reference: String,
+    /// The deployment on each chain, in the order they were given.
+    pub deployments: Vec<ChainDeployment>,
+}
+
+impl DiffResult {
+    /// Whether the contract is deployed with identical bytecode on every chain.
+    pub fn identical(&self) -> bool {
+        self.deployments
+            .iter()
+            .all(|deployment| deployment.comparison == DeploymentMatch::Identical)
+    }
+}
+
+/// The entrypoint for the diff module. This fetches the target's bytecode from each chain, and
+/// compares each deployment against the first, reporting whether it's identical, differs only in
+/// its metadata or immutables, or is different code entirely.
+pub async fn diff(args: DiffArgs) -> Result<DiffResult, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !ADDRESS_REGEX.is_match(&args.target)? {
+        logger.error("argument '--chains' requires the target to be a contract address.");
+        std::process::exit(1);
+    }
+
+    // resolve each chain to its RPC provider, either given inline or from the configuration
+    let configured = args
+        .chain_rpc_urls
+        .iter()
+        .filter_map(|chain| parse_chain_rpc(chain).ok())
+        .collect::<HashMap<String, String>>();
+    let mut chains = Vec::new();
+    for chain in args.chains.split(',').map(str::trim).filter(|chain| !chain.is_empty()) {
+        let chain = match chain.contains('=') {
+            true => parse_chain_rpc(chain),
+            false => configured
+                .get(chain)
+                .map(|rpc_url| (chain.to_string(), rpc_url.clone()))
+                .ok_or_else(|| {
+                    format!("chain '{chain}' isn't configured. use `heimdall config chains '{chain}=<rpc_url>'` to add it")
+                }),
+        };
+        match chain {
+            Ok(chain) => chains.push(chain),
+            Err(e) => {
+                logger.error(&format!("{e} ."));
+                std::process::exit(1)
+            }
+        }
+    }
+    if chains.len() < 2 {
+        logger.error("argument '--chains' requires at least two chains to compare.");
+        std::process::exit(1);
+    }
+
+    let mut bytecodes = Vec::new();
+    for (chain, rpc_url) in &chains {
+        logger.debug(&format!("fetching bytecode from '{chain}' ."));
+        let bytecode = get_code(&args.target, rpc_url).await?.replacen("0x", "", 1);
+        bytecodes.push((chain.clone(), bytecode));
+    }
+
+    let (reference, reference_bytecode) = match bytecodes
+        .iter()
+        .find(|(_, bytecode)| !bytecode.is_empty())
+    {
+        Some(reference) => reference.clone(),
+        None => {
+            logger.error(&format!("'{}' isn't deployed on any of the given chains.", &args.target));
+            std::process::exit(1)
+        }
+    };
+
+    let deployments = bytecodes
+        .into_iter()
+        .map(|(chain, bytecode)| ChainDeployment {
+            comparison: compare_deployments(&reference_bytecode, &bytecode),
+            size: bytecode.len() / 2,
+            chain,
+        })
+        .collect::<Vec<_>>();
+
+    for deployment in &deployments {
+        let message = match deployment.chain == reference {
+            true => format!("{}: {} bytes (reference)", deployment.chain, deployment.size),
+            false => format!(
+                "{}: {} bytes, {}",
+                deployment.chain,
+                deployment.size,
+                deployment.comparison.describe()
+            ),
+        };
+        match deployment.comparison {
+            DeploymentMatch::Identical | DeploymentMatch::SameCode { .. } => {
+                logger.success(&message)
+            }
+            DeploymentMatch::Different | DeploymentMatch::NotDeployed => logger.warn(&message),
+        }
+    }
+
+    let result = DiffResult { reference, deployments };
+    match result.identical() {
+        true => logger.success(&format!(
+            "'{}' is identical on all {} chains.",
+            &args.target,
+            chains.len()
+        )),
+        false => logger.info(&format!(
+            "'{}' differs across chains, compared against its deployment on '{}'.",
+            &args.target, result.reference
+        )),
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+/// The number of unchanged lines shown around each change, as with `diff -u`.
+const CONTEXT_LINES: usize = 3;
+
+/// Instructions which end a basic block, since execution doesn't continue to the next
+/// instruction, or may not.
+const TERMINATORS: [&str; 12] = [
+    "STOP",
+    "JUMP",
+    "JUMPI",
+    "RETURN",
+    "REVERT",
+    "INVALID",
+    "SELFDESTRUCT",
+    "RJUMP",
+    "RJUMPI",
+    "RJUMPV",
+    "RETF",
+    "JUMPF",
+];
+
+/// A basic block of a disassembly, or a section header, along with a hash of its contents which
+/// ignores program counters, so a block which only moved hashes the same.
+#[derive(Debug)]
+struct Block<'a> {
+    lines: Vec<&'a str>,
+    hash: u64,
+}
+
+/// How a block of one disassembly aligns with the other's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Alignment {
+    /// The blocks at each index are the same.
+    Unchanged(usize, usize),
+    /// The block at the index only appears in the first disassembly.
+    Removed(usize),
+    /// The block at the index only appears in the second disassembly.
+    Inserted(usize),
+}
+
+/// The contents of a line which don't depend on where it is, i.e. `PUSH2 <jumpdest>` for
+/// `000012 PUSH2 0042` if `0x42` is a `JUMPDEST`, since jump targets shift whenever code before
+/// them changes size. Section headers are reduced to their name, dropping sizes and offsets.
+fn normalize(line: &str, jumpdests: &HashSet<u64>) -> String {
+    if line.starts_with(';') {
+        return line.split('(').next().unwrap_or_default().trim().to_string()
+    }
+
+    let line = line.split(" // ").next().unwrap_or_default();
+    let mut parts = line.split_whitespace().skip(1);
+    let (name, operand) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    match u64::from_str_radix(operand, 16) {
+        Ok(value) if name.starts_with("PUSH") && jumpdests.contains(&value) => {
+            format!("{name} <jumpdest>")
+        }
+        _ => format!("{name} {operand}"),
+    }
+}
+
+/// Split a disassembly into its basic blocks, which begin at a `JUMPDEST` and end after a
+/// terminating instruction. Each section header is a block of its own.
+fn split_blocks(assembly: &str, decimal_counter: bool) -> Vec<Block<'_>> {
+    let radix = if decimal_counter { 10 } else { 16 };
+    let jumpdests = assembly
+        .lines()
+        .filter_map(|line| {
+            let (counter, rest) = line.split_once(' ')?;
+            match rest.trim() == "JUMPDEST" {
+                true => u64::from_str_radix(counter, radix).ok(),
+                false => None,
+            }
+        })
+        .collect::<HashSet<u64>>();
+
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    let mut ended = true;
+    for line in assembly.lines().filter(|line| !line.trim().is_empty()) {
+        let name = line.split_whitespace().nth(1).unwrap_or_default();
+        let header = line.starts_with(';');
+        if ended || header || name == "JUMPDEST" {
+            blocks.push(Vec::new());
+        }
+        blocks.last_mut().expect("a block was pushed").push(line);
+        ended = header || TERMINATORS.contains(&name);
+    }
+
+    blocks
+        .into_iter()
+        .map(|lines| {
+            let mut hasher = DefaultHasher::new();
+            for line in &lines {
+                normalize(line, &jumpdests).hash(&mut hasher);
+            }
+            Block { lines, hash: hasher.finish() }
+        })
+        .collect()
+}
+
+/// Align the blocks of two disassemblies by their longest common subsequence of hashes, after
+/// skipping their common prefix and suffix.
+fn align(old: &[Block], new: &[Block]) -> Vec<Alignment> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a.hash == b.hash).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a.hash == b.hash)
+        .count();
+    let (old_middle, new_middle) =
+        (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let width = new_middle.len() + 1;
+    let mut lengths = vec![0u32; (old_middle.len() + 1) * width];
+    for i in (0..old_middle.len()).rev() {
+        for j in (0..new_middle.len()).rev() {
+            lengths[i * width + j] = match old_middle[i].hash == new_middle[j].hash {
+                true => lengths[(i + 1) * width + j + 1] + 1,
+                false => lengths[(i + 1) * width + j].max(lengths[i * width + j + 1]),
+            };
+        }
+    }
+
+    let mut alignment = (0..prefix).map(|i| Alignment::Unchanged(i, i)).collect::<Vec<_>>();
+    let (mut i, mut j) = (0, 0);
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i].hash == new_middle[j].hash
+        {
+            alignment.push(Alignment::Unchanged(prefix + i, prefix + j));
+            (i, j) = (i + 1, j + 1);
+        } else if j == new_middle.len() ||
+            (i < old_middle.len() && lengths[(i + 1) * width + j] >= lengths[i * width + j + 1])
+        {
+            alignment.push(Alignment::Removed(prefix + i));
+            i += 1;
+        } else {
+            alignment.push(Alignment::Inserted(prefix + j));
+            j += 1;
+        }
+    }
+    alignment.extend(
+        (0..suffix).map(|k| Alignment::Unchanged(old.len() - suffix + k, new.len() - suffix + k)),
+    );
+
+    alignment
+}
+
+/// A line of the diff, with its prefix, and the run of changed blocks it belongs to, if any.
+struct DiffLine<'a> {
+    prefix: char,
+    text: &'a str,
+    run: Option<usize>,
+}
+
+/// Describe the changed blocks within a hunk, i.e. `1 changed block, 2 inserted blocks`, where a
+/// removed block followed by an inserted one counts as a changed block.
+fn describe_runs(runs: &[(usize, usize)]) -> String {
+    let (removed, inserted) =
+        runs.iter().fold((0, 0), |(removed, inserted), run| (removed + run.0, inserted + run.1));
+    let changed = runs.iter().map(|(removed, inserted)| removed.min(inserted)).sum::<usize>();
+    [(changed, "changed"), (inserted - changed, "inserted"), (removed - changed, "removed")]
+        .iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, kind)| match count {
+            1 => format!("1 {kind} block"),
+            count => format!("{count} {kind} blocks"),
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Diff two disassemblies, aligning them by basic block so blocks which only moved, or whose
+/// jump targets only shifted, aren't reported. Returns a unified diff from the first disassembly
+/// to the second, whose hunk headers describe the blocks which changed, or were inserted or
+/// removed, or an empty string if there are no differences. `names` are the names of the
+/// disassembled targets.
+pub fn diff_disassembly(
+    old: &str,
+    new: &str,
+    names: (&str, &str),
+    decimal_counter: bool,
+) -> String {
+    let (old_blocks, new_blocks) =
+        (split_blocks(old, decimal_counter), split_blocks(new, decimal_counter));
+
+    // flatten the alignment into lines, grouping consecutive changed blocks into runs
+    let mut lines: Vec<DiffLine> = Vec::new();
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    let mut in_run = false;
+    for alignment in align(&old_blocks, &new_blocks) {
+        let (prefix, block) = match alignment {
+            Alignment::Unchanged(_, j) => (' ', &new_blocks[j]),
+            Alignment::Removed(i) => ('-', &old_blocks[i]),
+            Alignment::Inserted(j) => ('+', &new_blocks[j]),
+        };
+        let run = match prefix {
+            ' ' => None,
+            _ => {
+                if !in_run {
+                    runs.push((0, 0));
+                }
+                let run = runs.last_mut().expect("a run was pushed");
+                match prefix {
+                    '-' => run.0 += 1,
+                    _ => run.1 += 1,
+                }
+                Some(runs.len() - 1)
+            }
+        };
+        in_run = run.is_some();
+        lines.extend(block.lines.iter().map(|text| DiffLine { prefix, text, run }));
+    }
+
+    if runs.is_empty() {
+        return String::new()
+    }
+
+    // each hunk spans its changed lines, with context around them, merging hunks which overlap
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, _) in lines.iter().enumerate().filter(|(_, line)| line.prefix != ' ') {
+        let (start, end) =
+            (index.saturating_sub(CONTEXT_LINES), (index + CONTEXT_LINES + 1).min(lines.len()));
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", names.0, names.1);
+    let line_number = |index: usize, side: char| {
+        lines[..index].iter().filter(|line| line.prefix == ' ' || line.prefix == side).count()
+    };
+    for (start, end) in hunks {
+        let hunk = &lines[start..end];
+        let range = |side: char| {
+            let length =
+                hunk.iter().filter(|line| line.prefix == ' ' || line.prefix == side).count();
+            match length {
+                0 => format!("{},0", line_number(start, side)),
+                length => format!("{},{length}", line_number(start, side) + 1),
+            }
+        };
+        let mut hunk_runs = hunk.iter().filter_map(|line| line.run).collect::<Vec<usize>>();
+        hunk_runs.dedup();
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@ {}\n",
+            range('-'),
+            range('+'),
+            describe_runs(&hunk_runs.iter().map(|run| runs[*run]).collect::<Vec<_>>())
+        ));
+        for line in hunk {
+            output.push_str(&format!("{}{}\n", line.prefix, line.text));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_disassembly() {
+        // a block is inserted before the second, shifting its jump target, while the last block
+        // changes which value it pushes
+        let old = "000002 PUSH1 05\n000003 JUMP \n000004 STOP \n000005 JUMPDEST \n000007 PUSH1 01\n000008 STOP \n";
+        let new = "000002 PUSH1 07\n000003 JUMP \n000004 STOP \n000005 JUMPDEST \n000006 STOP \n000007 JUMPDEST \n000009 PUSH1 02\n00000a STOP \n";
+
+        assert_eq!(
+            diff_disassembly(old, new, ("old", "new"), false),
+            "--- old\n+++ new\n@@ -1,6 +1,8 @@ 1 changed block, 1 inserted block\n 000002 PUSH1 07\n 000003 JUMP \n 000004 STOP \n-000005 JUMPDEST \n-000007 PUSH1 01\n-000008 STOP \n+000005 JUMPDEST \n+000006 STOP \n+000007 JUMPDEST \n+000009 PUSH1 02\n+00000a STOP \n"
+        );
+        assert_eq!(diff_disassembly(old, old, ("old", "old"), false), "");
+    }
+}
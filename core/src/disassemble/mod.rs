@@ -1,10 +1,27 @@
-use std::fs;
+pub mod diff;
+
+use std::{collections::HashMap, fs};
 
 use clap::{AppSettings, Parser};
 use derive_builder::Builder;
+use diff::diff_disassembly;
 use heimdall_common::{
     constants::{ADDRESS_REGEX, BYTECODE_REGEX},
-    ether::{evm::core::opcodes::Opcode, rpc::get_code},
+    ether::{
+        eof::{decode_code_section, is_eof, parse_eof, EofContainer, NON_RETURNING},
+        eravm::{decode_eravm_bytecode, WORD_SIZE},
+        evm::{
+            core::{
+                opcodes::Opcode,
+                vm::{Instruction, VM},
+            },
+            ext::exec::VMTrace,
+        },
+        metadata::decode_metadata,
+        rpc::get_code,
+        segments::split_creation_code,
+        target::{select_target_vm, TargetVm},
+    },
     utils::{
         io::logging::Logger,
         strings::{decode_hex, encode_hex},
@@ -32,6 +49,23 @@ pub struct DisassemblerArgs {
     /// Whether to use base-10 for the program counter.
     #[clap(long = "decimal-counter", short = 'd')]
     pub decimal_counter: bool,
+
+    /// Whether to annotate each instruction with the symbolic stack items it consumes and
+    /// produces, e.g. `SSTORE  // key=arg0, value=(arg1 + 0x01)`, as found by symbolic execution.
+    #[clap(long = "annotate")]
+    pub annotate: bool,
+
+    /// The virtual machine the target is compiled for, either `evm` or `eravm`. Defaults to the
+    /// virtual machine of the RPC provider's chain for contract addresses, and `evm` otherwise.
+    /// EraVM opcodes are printed by their index, e.g. `op_1a3`, since the opcode table they
+    /// index changes between VM versions.
+    #[clap(long = "vm", default_value = "", hide_default_value = true)]
+    pub vm: String,
+
+    /// Another target to compare against, printing a unified diff from the target's disassembly
+    /// to the other's, aligned by basic block, so blocks which only moved aren't reported.
+    #[clap(long = "diff", default_value = "", hide_default_value = true)]
+    pub diff: String,
 }
 
 impl DisassemblerArgsBuilder {
@@ -41,6 +75,9 @@ impl DisassemblerArgsBuilder {
             verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
             rpc_url: Some(String::new()),
             decimal_counter: Some(false),
+            annotate: Some(false),
+            vm: Some(String::new()),
+            diff: Some(String::new()),
         }
     }
 }
@@ -67,41 +104,281 @@ pub async fn disassemble(args: DisassemblerArgs) -> Result<String, Box<dyn std::
         None => "SILENT",
     });
 
+    let assembly = disassemble_target(&args.target, &args, &logger).await?;
+    if args.diff.is_empty() {
+        logger.debug(&format!("disassembly completed in {} ms.", now.elapsed().as_millis()));
+        return Ok(assembly)
+    }
+
+    // the other target is disassembled the same way, so its blocks can be aligned with ours
+    let other_assembly = disassemble_target(&args.diff, &args, &logger).await?;
+    let diff = diff_disassembly(
+        &assembly,
+        &other_assembly,
+        (&args.target, &args.diff),
+        args.decimal_counter,
+    );
+    match diff.is_empty() {
+        true => logger.info("disassemblies are identical once program counters are ignored."),
+        false => logger.info(&format!("found {} differing hunks.", diff.matches("\n@@ ").count())),
+    }
+    logger.debug(&format!("disassembly diff completed in {} ms.", now.elapsed().as_millis()));
+
+    Ok(diff)
+}
+
+/// Fetch the target's bytecode, either from a file, bytecode, or contract address, and
+/// disassemble it to assembly.
+async fn disassemble_target(
+    target: &str,
+    args: &DisassemblerArgs,
+    logger: &Logger,
+) -> Result<String, Box<dyn std::error::Error>> {
     let contract_bytecode: String;
-    if ADDRESS_REGEX.is_match(&args.target)? {
+    if ADDRESS_REGEX.is_match(target)? {
         // We are disassembling a contract address, so we need to fetch the bytecode from the RPC
         // provider.
-        contract_bytecode = get_code(&args.target, &args.rpc_url).await?;
-    } else if BYTECODE_REGEX.is_match(&args.target)? {
-        contract_bytecode = args.target;
+        contract_bytecode = get_code(target, &args.rpc_url).await?;
+    } else if BYTECODE_REGEX.is_match(target)? {
+        contract_bytecode = target.to_string();
     } else {
         // We are disassembling a file, so we need to read the bytecode from the file.
-        contract_bytecode = match fs::read_to_string(&args.target) {
+        contract_bytecode = match fs::read_to_string(target) {
             Ok(contents) => {
                 let _contents = contents.replace('\n', "");
                 if BYTECODE_REGEX.is_match(&_contents)? && _contents.len() % 2 == 0 {
                     _contents
                 } else {
-                    logger
-                        .error(&format!("file '{}' doesn't contain valid bytecode.", &args.target));
+                    logger.error(&format!("file '{}' doesn't contain valid bytecode.", target));
                     std::process::exit(1)
                 }
             }
             Err(_) => {
-                logger.error(&format!("failed to open file '{}' .", &args.target));
+                logger.error(&format!("failed to open file '{}' .", target));
                 std::process::exit(1)
             }
         };
     }
 
-    let mut program_counter = 0;
     let mut output: String = String::new();
+    let contract_bytecode = contract_bytecode.replacen("0x", "", 1);
+
+    // creation code is split into its init code, the runtime code it deploys, and any constructor
+    // arguments, each under its own header. the runtime code is numbered from zero, matching the
+    // program counter it will have once deployed.
+    let target_vm = match select_target_vm(&args.vm, target, &args.rpc_url).await {
+        Ok(target_vm) => target_vm,
+        Err(error) => {
+            logger.error(&format!("{error}."));
+            std::process::exit(1)
+        }
+    };
+
+    let bytecode = decode_hex(&contract_bytecode)?;
+    let program_counter = if target_vm == TargetVm::EraVm {
+        if args.annotate {
+            logger.warn("argument '--annotate' doesn't support EraVM bytecode, skipping.");
+        }
+        match disassemble_eravm(&bytecode, args, &mut output) {
+            Ok(program_counter) => program_counter,
+            Err(error) => {
+                logger.error(&format!("invalid EraVM bytecode: {error}."));
+                std::process::exit(1)
+            }
+        }
+    } else if is_eof(&bytecode) {
+        // EOF containers declare their sections, so each is disassembled under its own header
+        if args.annotate {
+            logger.warn("argument '--annotate' doesn't support EOF containers, skipping.");
+        }
+        match parse_eof(&bytecode)
+            .and_then(|container| disassemble_eof(&container, args, &mut output))
+        {
+            Ok(program_counter) => program_counter,
+            Err(error) => {
+                logger.error(&format!("invalid EOF container: {error}."));
+                std::process::exit(1)
+            }
+        }
+    } else {
+        match split_creation_code(&contract_bytecode) {
+            Some(creation_code) => {
+                let init_code = decode_hex(&creation_code.init_code)?;
+                output.push_str(&format!("; init code ({} bytes)\n", init_code.len()));
+                let mut program_counter = disassemble_bytes(&init_code, args, &mut output)?;
+
+                output.push_str(&format!(
+                    "\n; runtime code ({} bytes at offset {})\n",
+                    creation_code.runtime_code.len() / 2,
+                    format_counter(creation_code.runtime_offset(), args)
+                ));
+                program_counter += disassemble_bytes(
+                    &strip_metadata_bytes(&creation_code.runtime_code, logger)?,
+                    args,
+                    &mut output,
+                )?;
+
+                let constructor_arguments = decode_hex(&creation_code.constructor_arguments)?;
+                if !constructor_arguments.is_empty() {
+                    output.push_str(&format!(
+                        "\n; constructor arguments ({} bytes at offset {})\n",
+                        constructor_arguments.len(),
+                        format_counter(
+                            creation_code.runtime_offset() + creation_code.runtime_code.len() / 2,
+                            args
+                        )
+                    ));
+                    disassemble_data(&constructor_arguments, args, &mut output);
+                }
+
+                program_counter
+            }
+            None => disassemble_bytes(
+                &strip_metadata_bytes(&contract_bytecode, logger)?,
+                args,
+                &mut output,
+            )?,
+        }
+    };
+
+    logger.info(&format!("disassembled {program_counter} bytes successfully."));
+
+    Ok(output)
+}
+
+/// Annotations longer than this are truncated, since items derived from loops or deep call
+/// chains can grow to thousands of characters.
+const MAX_ANNOTATION_LENGTH: usize = 96;
+
+/// Format a program counter, in base-10 if requested.
+fn format_counter(program_counter: usize, args: &DisassemblerArgs) -> String {
+    if args.decimal_counter {
+        program_counter.to_string()
+    } else {
+        format!("{:06x}", program_counter)
+    }
+}
 
-    // Iterate over the bytecode, disassembling each instruction.
-    let byte_array = decode_hex(&contract_bytecode.replacen("0x", "", 1))?;
+/// Decode the bytecode, stripping the compiler metadata trailer, since it isn't executable code.
+fn strip_metadata_bytes(
+    bytecode: &str,
+    logger: &Logger,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut byte_array = decode_hex(bytecode)?;
+    if let Some(metadata) = decode_metadata(bytecode) {
+        logger.debug(&format!("stripped {} bytes of compiler metadata.", metadata.size));
+        byte_array.truncate(byte_array.len() - metadata.size);
+    }
+
+    Ok(byte_array)
+}
+
+/// Append the bytes to the output as data, in 32-byte words.
+fn disassemble_data(bytes: &[u8], args: &DisassemblerArgs, output: &mut String) {
+    for (index, word) in bytes.chunks(32).enumerate() {
+        output.push_str(&format!(
+            "{} DATA {}\n",
+            format_counter(index * 32, args),
+            encode_hex(word.to_vec())
+        ));
+    }
+}
+
+/// Disassemble each code section of an EOF container, validating its instructions, followed by
+/// its container and data sections. Instructions are numbered from the start of their code
+/// section, which relative jumps are encoded against. Returns the number of bytes disassembled.
+fn disassemble_eof(
+    container: &EofContainer,
+    args: &DisassemblerArgs,
+    output: &mut String,
+) -> Result<usize, String> {
+    for (index, section) in container.code_sections.iter().enumerate() {
+        let section_type = &container.types[index];
+        output.push_str(&format!(
+            "{}; code section {index} ({} bytes at offset {}, {} inputs, {})\n",
+            if index == 0 { "" } else { "\n" },
+            section.bytes.len(),
+            format_counter(section.offset, args),
+            section_type.inputs,
+            match section_type.outputs {
+                NON_RETURNING => String::from("non-returning"),
+                outputs => format!("{outputs} outputs"),
+            }
+        ));
+        for instruction in decode_code_section(container, index)? {
+            output.push_str(&format!(
+                "{} {} {}\n",
+                format_counter(instruction.offset, args),
+                instruction.name,
+                encode_hex(instruction.immediate)
+            ));
+        }
+    }
+
+    for (index, section) in container.container_sections.iter().enumerate() {
+        output.push_str(&format!(
+            "\n; container section {index} ({} bytes at offset {})\n",
+            section.bytes.len(),
+            format_counter(section.offset, args)
+        ));
+        disassemble_data(&section.bytes, args, output);
+    }
+
+    if container.declared_data_size > 0 {
+        output.push_str(&format!(
+            "\n; data section ({} of {} bytes at offset {})\n",
+            container.data.bytes.len(),
+            container.declared_data_size,
+            format_counter(container.data.offset, args)
+        ));
+        disassemble_data(&container.data.bytes, args, output);
+    }
+
+    Ok(container.data.offset + container.data.bytes.len())
+}
+
+/// Disassemble each instruction of EraVM bytecode, numbered by the program counter which
+/// addresses it. Returns the number of bytes disassembled.
+///
+/// Mnemonics aren't supported: each opcode is an index into a table of opcode variants which
+/// changes between EraVM versions, so naming them would be wrong for some contracts.
+fn disassemble_eravm(
+    bytecode: &[u8],
+    args: &DisassemblerArgs,
+    output: &mut String,
+) -> Result<usize, String> {
+    let instructions = decode_eravm_bytecode(bytecode)?;
+    output.push_str(&format!(
+        "; EraVM bytecode ({} words, {} instructions)\n; opcodes are version-specific table indices, not mnemonics\n",
+        bytecode.len() / WORD_SIZE,
+        instructions.len()
+    ));
+    for instruction in instructions {
+        output.push_str(&format!("{} {}\n", format_counter(instruction.index, args), instruction));
+    }
+
+    Ok(bytecode.len())
+}
 
+/// Disassemble each instruction in the bytecode, appending it to the output. Returns the number
+/// of bytes disassembled.
+fn disassemble_bytes(
+    byte_array: &[u8],
+    args: &DisassemblerArgs,
+    output: &mut String,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    let annotations = match args.annotate {
+        true => stack_annotations(byte_array),
+        false => HashMap::new(),
+    };
+
+    let mut program_counter = 0;
     while program_counter < byte_array.len() {
         let operation = Opcode::new(byte_array[program_counter]);
+        let annotation = match annotations.get(&program_counter) {
+            Some(annotation) => format!(" // {annotation}"),
+            None => String::new(),
+        };
         let mut pushed_bytes: String = String::new();
 
         if operation.name.contains("PUSH") {
@@ -118,22 +395,115 @@ pub async fn disassemble(args: DisassemblerArgs) -> Result<String, Box<dyn std::
 
         output.push_str(
             format!(
-                "{} {} {}\n",
-                if args.decimal_counter {
-                    program_counter.to_string()
-                } else {
-                    format!("{:06x}", program_counter)
-                },
+                "{} {} {}{}\n",
+                format_counter(program_counter, args),
                 operation.name,
-                pushed_bytes
+                pushed_bytes,
+                annotation
             )
             .as_str(),
         );
         program_counter += 1;
     }
 
-    logger.info(&format!("disassembled {program_counter} bytes successfully."));
-    logger.debug(&format!("disassembly completed in {} ms.", now.elapsed().as_millis()));
+    Ok(program_counter)
+}
 
-    Ok(output)
+/// The names of the stack items an opcode consumes, from the top of the stack down.
+fn input_names(opcode: u8) -> &'static [&'static str] {
+    match opcode {
+        0x08 | 0x09 => &["a", "b", "n"],
+        0x0a => &["base", "exponent"],
+        0x0b => &["b", "x"],
+        0x15 | 0x19 => &["a"],
+        0x1a => &["i", "x"],
+        0x1b..=0x1d => &["shift", "value"],
+        0x01..=0x1d => &["a", "b"],
+        0x20 | 0xf3 | 0xfd => &["offset", "size"],
+        0x31 | 0x3b | 0x3f | 0xff => &["address"],
+        0x35 | 0x51 => &["offset"],
+        0x37 | 0x39 | 0x3e | 0x5e => &["destOffset", "offset", "size"],
+        0x3c => &["address", "destOffset", "offset", "size"],
+        0x40 => &["blockNumber"],
+        0x50 => &["value"],
+        0x52 | 0x53 => &["offset", "value"],
+        0x54 | 0x5c => &["key"],
+        0x55 | 0x5d => &["key", "value"],
+        0x56 => &["dest"],
+        0x57 => &["dest", "condition"],
+        0xa0..=0xa4 => &["offset", "size", "topic0", "topic1", "topic2", "topic3"],
+        0xf0 => &["value", "offset", "size"],
+        0xf1 | 0xf2 => {
+            &["gas", "address", "value", "argsOffset", "argsSize", "retOffset", "retSize"]
+        }
+        0xf4 | 0xfa => &["gas", "address", "argsOffset", "argsSize", "retOffset", "retSize"],
+        0xf5 => &["value", "offset", "size", "salt"],
+        _ => &[],
+    }
+}
+
+/// Symbolically execute the bytecode, describing the stack items each instruction consumes and
+/// produces, keyed by its program counter. Instructions reached along multiple paths are
+/// described as they were first reached, while PUSH, DUP, and SWAP instructions, which only
+/// move existing items, aren't described.
+fn stack_annotations(byte_array: &[u8]) -> HashMap<usize, String> {
+    let evm = VM::new(
+        encode_hex(byte_array.to_vec()),
+        String::from("0x"),
+        String::from("0x6865696d64616c6c000000000061646472657373"),
+        String::from("0x6865696d64616c6c0000000000006f726967696e"),
+        String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+        0,
+        u128::MAX,
+    );
+    let (vm_trace, _) = evm.symbolic_exec();
+
+    fn collect(vm_trace: &VMTrace, instructions: &mut HashMap<usize, Instruction>) {
+        for operation in &vm_trace.operations {
+            let instruction = &operation.last_instruction;
+            instructions
+                .entry(instruction.instruction as usize - 1)
+                .or_insert_with(|| instruction.clone());
+        }
+        for child in &vm_trace.children {
+            collect(child, instructions);
+        }
+    }
+    let mut instructions = HashMap::new();
+    collect(&vm_trace, &mut instructions);
+
+    let truncate = |item: String| match item.char_indices().nth(MAX_ANNOTATION_LENGTH) {
+        Some((end, _)) => format!("{}...", &item[..end]),
+        None => item,
+    };
+    instructions
+        .into_iter()
+        .filter(|(_, instruction)| !(0x5f..=0x9f).contains(&instruction.opcode))
+        .filter_map(|(program_counter, instruction)| {
+            let names = input_names(instruction.opcode);
+            let consumed = instruction
+                .input_operations
+                .iter()
+                .enumerate()
+                .map(|(index, operation)| match names.get(index) {
+                    Some(name) => format!("{name}={}", truncate(operation.solidify())),
+                    None => truncate(operation.solidify()),
+                })
+                .collect::<Vec<String>>()
+                .join(", ");
+            let produced = instruction
+                .output_operations
+                .first()
+                .map(|operation| format!("-> {}", truncate(operation.solidify())));
+
+            match (consumed.is_empty(), produced) {
+                (true, None) => None,
+                (true, Some(produced)) => Some((program_counter, produced)),
+                (false, None) => Some((program_counter, consumed)),
+                (false, Some(produced)) => {
+                    Some((program_counter, format!("{consumed} {produced}")))
+                }
+            }
+        })
+        .collect()
 }
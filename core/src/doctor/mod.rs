@@ -0,0 +1,125 @@
+use std::{io::IsTerminal, time::Instant};
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::utils::{http::get_json_from_url, io::logging::Logger};
+use heimdall_config::{
+    diagnostics::{diagnose, log_diagnostics, Diagnostic},
+    read_config,
+};
+
+/// The smallest terminal the TUI can be drawn in, in columns and rows.
+const MIN_TERMINAL_SIZE: (u16, u16) = (80, 24);
+
+/// A selector which is always present in the signature database, i.e. `transfer(address,uint256)`.
+const PROBE_SELECTOR: &str = "0xa9059cbb";
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Diagnose problems with heimdall's environment",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall doctor [OPTIONS]"
+)]
+pub struct DoctorArgs {
+    /// The RPC provider to diagnose. Defaults to the configured `rpc_url`.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+}
+
+impl DoctorArgsBuilder {
+    pub fn new() -> Self {
+        Self { rpc_url: Some(String::new()) }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DoctorResult {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DoctorResult {
+    /// The number of required checks which failed.
+    pub fn failed(&self) -> usize {
+        self.diagnostics.iter().filter(|d| !d.passed && d.required).count()
+    }
+}
+
+/// Check that the signature database is reachable, since selectors can't be resolved without it.
+async fn check_signature_api() -> Diagnostic {
+    let start = Instant::now();
+    let response = get_json_from_url(
+        &format!(
+            "https://api.openchain.xyz/signature-database/v1/lookup?function={PROBE_SELECTOR}&filter=false"
+        ),
+        10,
+    )
+    .await;
+    let latency = start.elapsed().as_millis();
+
+    match response {
+        Ok(Some(_)) => Diagnostic::passed(
+            "signature api",
+            &format!("api.openchain.xyz responded in {latency}ms"),
+        ),
+        _ => Diagnostic::failed(
+            "signature api",
+            "api.openchain.xyz is unreachable",
+            "check your network and `proxy` settings, or pass `--skip-resolving` to skip \
+                resolving selectors",
+        )
+        .optional(),
+    }
+}
+
+/// Check that the terminal can display the TUI and colored output.
+fn check_terminal() -> Diagnostic {
+    let fix = "run snapshot with `--no-tui` and decompile with `--non-interactive`";
+    if !std::io::stdout().is_terminal() {
+        return Diagnostic::failed("terminal", "output isn't a terminal", fix).optional()
+    }
+    if std::env::var("TERM").is_ok_and(|term| term == "dumb") {
+        return Diagnostic::failed("terminal", "$TERM is 'dumb'", fix).optional()
+    }
+
+    match crossterm::terminal::size() {
+        Ok((columns, rows)) if columns >= MIN_TERMINAL_SIZE.0 && rows >= MIN_TERMINAL_SIZE.1 => {
+            Diagnostic::passed("terminal", &format!("{columns}x{rows}, supports the TUI"))
+        }
+        Ok((columns, rows)) => Diagnostic::failed(
+            "terminal",
+            &format!(
+                "{columns}x{rows} is smaller than the TUI's minimum of {}x{}",
+                MIN_TERMINAL_SIZE.0, MIN_TERMINAL_SIZE.1
+            ),
+            &format!("enlarge the terminal, or {fix}"),
+        )
+        .optional(),
+        Err(e) => Diagnostic::failed("terminal", &format!("couldn't query the terminal: {e}"), fix)
+            .optional(),
+    }
+}
+
+/// The entrypoint for the doctor module. This checks the environment heimdall runs in, i.e. the
+/// configuration, RPC provider, API keys, signature database, cache, and terminal, printing each
+/// outcome along with how to fix any problems found.
+pub async fn doctor(args: DoctorArgs) -> Result<DoctorResult, Box<dyn std::error::Error>> {
+    let (logger, _) = Logger::new("");
+
+    let mut diagnostics = diagnose(&read_config(), &args.rpc_url).await;
+    diagnostics.push(check_signature_api().await);
+    diagnostics.push(check_terminal());
+    log_diagnostics(&logger, &diagnostics);
+
+    let result = DoctorResult { diagnostics };
+    let warnings = result.diagnostics.iter().filter(|d| !d.passed && !d.required).count();
+    match (result.failed(), warnings) {
+        (0, 0) => logger.success("no problems found."),
+        (0, warnings) => logger.warn(&format!(
+            "{warnings} optional check(s) failed, which only affect some features."
+        )),
+        (failed, _) => logger.error(&format!("{failed} check(s) failed.")),
+    }
+
+    Ok(result)
+}
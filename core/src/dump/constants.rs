@@ -17,6 +17,14 @@ lazy_static! {
         "uint256".to_string()
     ];
 
+    /// The columns of the storage table, which can be sorted by.
+    pub static ref STORAGE_COLUMNS: Vec<String> = vec![
+        "Last Modified".to_string(),
+        "Slot".to_string(),
+        "As Type".to_string(),
+        "Value".to_string(),
+    ];
+
     /// The default decoding types.
     pub static ref ABOUT_TEXT: Vec<String> = vec![
         format!("heimdall-rs v{}", env!("CARGO_PKG_VERSION")),
@@ -28,7 +36,8 @@ lazy_static! {
     pub static ref HELP_MENU_COMMANDS: Vec<String> = vec![
         ":q, :quit                              exit the program".to_string(),
         ":h, :help                              display this help menu".to_string(),
-        ":f, :find      <VALUE>                 search for a storage slot by slot or value".to_string(),
+        ":f, :find      <QUERY>                 fuzzy search storage slots by slot, value, or alias".to_string(),
+        ":o, :sort      <COLUMN> [asc|desc]     sort the storage slots by a column".to_string(),
         ":e, :export    <FILENAME>              export the current storage dump to a file, preserving decoded values".to_string(),
         ":s, :seek      <DIRECTION> <AMOUNT>    move the cusor up or down by a specified amount".to_string(),
     ];
@@ -39,6 +48,9 @@ lazy_static! {
         "↓, Scroll Down                         move the cursor down one slot".to_string(),
         "←, →                                   change the decoding type of the selected slot".to_string(),
         "CTRL + ↑, CTRL + ↓                     move the cursor up or down by 10 slots".to_string(),
+        "/                                      search storage slots as you type".to_string(),
+        "s, S                                   sort by the next column, or reverse the sort order".to_string(),
+        "v                                      toggle raw values between hex, decimal, and ASCII".to_string(),
         "ESC                                    clear the search filter".to_string(),
     ];
 }
@@ -1,12 +1,15 @@
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout},
-    style::{Color, Modifier, Style},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    style::{Color, Style},
+    widgets::{Block, Borders, Paragraph, Table},
     Frame,
 };
 
-use crate::dump::{structures::dump_state::DumpState, util::table::build_rows};
+use crate::dump::{
+    structures::dump_state::DumpState,
+    util::table::{build_header, build_rows},
+};
 
 /// Render the TUI command palette
 pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut DumpState) {
@@ -23,16 +26,8 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Dump
         .style(Style::default().fg(Color::White))
         .block(Block::default().title(" Command ").borders(Borders::ALL));
 
-    // build header cells
-    let header_cells = ["Last Modified", "Slot", "As Type", "Value"].iter().map(|h| {
-        Cell::from(*h).style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
-    });
-
     // build header row
-    let header = Row::new(header_cells)
-        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
-        .height(1)
-        .bottom_margin(1);
+    let header = build_header(state);
 
     let rows = build_rows(state, main_layout[1].height as usize - 4);
 
@@ -5,18 +5,34 @@ mod util;
 
 use clap::{AppSettings, Parser};
 use derive_builder::Builder;
-use ethers::types::H160;
+use ethers::types::{H160, H256};
 use heimdall_common::{
-    resources::transpose::{get_contract_creation, get_transaction_list},
-    utils::io::logging::*,
+    ether::{
+        history::{self, detect_history_provider},
+        rpc::rpc_capabilities,
+        storage::StorageType,
+    },
+    resources::{dune, transpose},
+    utils::io::{logging::*, output::OutputLocator},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Instant,
+};
+
+pub use self::util::{
+    csv::{csv_rows, DumpRow},
+    layout::storage_layout,
 };
-use std::{collections::HashMap, env, str::FromStr, time::Instant};
+
+use crate::decompile::{decompile, DecompilerArgsBuilder};
 
 use self::{
     constants::DUMP_STATE,
     menus::TUIView,
-    structures::{dump_state::DumpState, transaction::Transaction},
-    util::csv::{build_csv, DumpRow},
+    structures::{dump_state::DumpState, storage_slot::ValueFormat, transaction::Transaction},
+    util::{csv::build_csv, scan::scan_storage},
 };
 
 #[derive(Debug, Clone, Parser, Builder)]
@@ -36,8 +52,9 @@ pub struct DumpArgs {
     #[clap(flatten)]
     pub verbose: clap_verbosity_flag::Verbosity,
 
-    /// The output directory to write the output to
-    #[clap(long = "output", short, default_value = "", hide_default_value = true)]
+    /// The output directory to write the output to. From the CLI, this is set by the global
+    /// `--output` argument.
+    #[clap(skip)]
     pub output: String,
 
     /// The RPC URL to use for fetching data.
@@ -48,6 +65,17 @@ pub struct DumpArgs {
     #[clap(long = "transpose-api-key", short, default_value = "", hide_default_value = true)]
     pub transpose_api_key: String,
 
+    /// Your Dune API Key, used by the `dune` backend.
+    #[clap(long = "dune-api-key", default_value = "", hide_default_value = true)]
+    pub dune_api_key: String,
+
+    /// The backend used to find the transactions which accessed the target's storage. Valid
+    /// backends are transpose, dune, and rpc, which uses the history APIs of Alchemy and QuickNode
+    /// RPC URLs. Defaults to the configured backend, or rpc for those providers and transpose
+    /// otherwise.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub backend: String,
+
     /// The number of threads to use when fetching data.
     #[clap(long, default_value = "4", hide_default_value = true)]
     pub threads: usize,
@@ -64,9 +92,19 @@ pub struct DumpArgs {
     #[clap(long)]
     pub no_tui: bool,
 
+    /// Skip indexing, and instead read slots 0 through N, along with well-known proxy slots, at
+    /// the latest block. Requires no API key or trace support.
+    #[clap(long = "scan-slots", default_value = "0", hide_default_value = true)]
+    pub scan_slots: u128,
+
     /// The chain of the target. Valid chains are ethereum, polygon, goerli, canto, and arbitrum.
     #[clap(long, default_value = "ethereum", hide_default_value = true)]
     pub chain: String,
+
+    /// Whether to decompile the target to infer the type of each slot it writes statically, and
+    /// reconcile it with the values observed in its storage.
+    #[clap(long = "static-types")]
+    pub static_types: bool,
 }
 
 impl DumpArgsBuilder {
@@ -77,11 +115,15 @@ impl DumpArgsBuilder {
             output: Some(String::new()),
             rpc_url: Some(String::new()),
             transpose_api_key: Some(String::new()),
+            dune_api_key: Some(String::new()),
+            backend: Some(String::from("transpose")),
             threads: Some(8),
             from_block: Some(0),
             to_block: Some(9999999999),
             no_tui: Some(true),
+            scan_slots: Some(0),
             chain: Some(String::from("ethereum")),
+            static_types: Some(false),
         }
     }
 }
@@ -105,37 +147,90 @@ pub async fn dump(args: DumpArgs) -> Result<Vec<DumpRow>, Box<dyn std::error::Er
         None => "SILENT",
     });
 
-    // parse the output directory
+    // parse the output directory, defaulting to the target's directory within `./output`
     let mut output_dir = args.output.clone();
     if args.output.is_empty() {
-        output_dir = match env::current_dir() {
-            Ok(dir) => dir.into_os_string().into_string().unwrap(),
+        output_dir = match OutputLocator::resolve("", "") {
+            Ok(locator) => locator.target_dir(&args.target),
             Err(_) => {
                 logger.error("failed to get current directory.");
                 std::process::exit(1);
             }
         };
-        output_dir.push_str("/output");
     }
 
-    // check if transpose api key is set
-    if args.transpose_api_key.is_empty() {
-        logger.error("you must provide a Transpose API key, which is used to fetch all normal and internal transactions for your target.");
-        logger.info("you can get a free API key at https://app.transpose.io/?utm_medium=organic&utm_source=heimdall-rs");
+    // a sequential slot scan reads storage directly, without an indexer or trace support
+    if args.scan_slots > 0 {
+        return scan(args, output_dir).await
+    }
+
+    // check if the selected backend's api key is set. without a configured backend, RPC
+    // providers with an extended history API are used directly
+    let backend = match args.backend.as_str() {
+        "" if detect_history_provider(&args.rpc_url).is_some() => "rpc",
+        "" => "transpose",
+        backend => backend,
+    };
+    match backend {
+        "transpose" if args.transpose_api_key.is_empty() => {
+            logger.error("you must provide a Transpose API key, which is used to fetch all normal and internal transactions for your target.");
+            logger.info("you can get a free API key at https://app.transpose.io/?utm_medium=organic&utm_source=heimdall-rs");
+            logger.info("alternatively, use `--backend dune` with a Dune API key.");
+            std::process::exit(1);
+        }
+        "dune" if args.dune_api_key.is_empty() => {
+            logger.error("you must provide a Dune API key, which is used to fetch all normal and internal transactions for your target.");
+            logger.info("you can get an API key at https://dune.com/settings/api");
+            std::process::exit(1);
+        }
+        "rpc" if detect_history_provider(&args.rpc_url).is_none() => {
+            logger.error("the rpc backend requires an Alchemy or QuickNode RPC URL, whose extended APIs are used to fetch all normal and internal transactions for your target.");
+            std::process::exit(1);
+        }
+        "transpose" | "dune" | "rpc" => {}
+        backend => {
+            logger.error(&format!(
+                "unknown backend '{backend}'. Valid backends are transpose, dune, and rpc."
+            ));
+            std::process::exit(1);
+        }
+    }
+
+    // storage diffs are replayed with `trace_replayTransaction`, so check for support before
+    // indexing begins
+    if args.rpc_url.is_empty() {
+        logger.error("fetching an on-chain transaction requires an RPC provider. Use `heimdall dump --help` for more information.");
+        std::process::exit(1);
+    }
+    if !rpc_capabilities(&args.rpc_url).await.trace {
+        logger.error(&format!(
+            "the RPC provider '{}' doesn't support the `trace_*` namespace, which is required to dump storage.",
+            &args.rpc_url
+        ));
+        logger.info(
+            "try an RPC provider which supports `trace_replayTransaction`, such as Erigon or Reth.",
+        );
         std::process::exit(1);
     }
 
     // get the contract creation tx
-    let contract_creation_tx =
-        match get_contract_creation(&args.chain, &args.target, &args.transpose_api_key).await {
-            Some(tx) => tx,
-            None => {
-                logger.error(
+    let contract_creation_tx = match backend {
+        "dune" => dune::get_contract_creation(&args.chain, &args.target, &args.dune_api_key).await,
+        "rpc" => history::get_contract_creation(&args.rpc_url, &args.target).await,
+        _ => {
+            transpose::get_contract_creation(&args.chain, &args.target, &args.transpose_api_key)
+                .await
+        }
+    };
+    let contract_creation_tx = match contract_creation_tx {
+        Some(tx) => tx,
+        None => {
+            logger.error(
                 "failed to get contract creation transaction. Is the target a contract address?",
             );
-                std::process::exit(1);
-            }
-        };
+            std::process::exit(1);
+        }
+    };
 
     // add the contract creation tx to the transactions list to be indexed
     let mut transactions: Vec<Transaction> = Vec::new();
@@ -154,19 +249,29 @@ pub async fn dump(args: DumpArgs) -> Result<Vec<DumpRow>, Box<dyn std::error::Er
         }
     };
 
-    // push the address to the output directory
-    if output_dir != args.output {
-        output_dir.push_str(&format!("/{}", &args.target));
-    }
-
     // fetch transactions
-    let transaction_list = get_transaction_list(
-        &args.chain,
-        &args.target,
-        &args.transpose_api_key,
-        (&args.from_block, &args.to_block),
-    )
-    .await;
+    let bounds = (&args.from_block, &args.to_block);
+    let transaction_list = match backend {
+        "dune" => {
+            dune::get_transaction_list(&args.chain, &args.target, &args.dune_api_key, bounds).await
+        }
+        "rpc" => match history::get_transaction_list(&args.rpc_url, &args.target, bounds).await {
+            Some(transaction_list) => transaction_list,
+            None => {
+                logger.error("failed to fetch transactions from the RPC provider's history API.");
+                std::process::exit(1);
+            }
+        },
+        _ => {
+            transpose::get_transaction_list(
+                &args.chain,
+                &args.target,
+                &args.transpose_api_key,
+                bounds,
+            )
+            .await
+        }
+    };
 
     // convert to vec of Transaction
     for transaction in transaction_list {
@@ -177,6 +282,9 @@ pub async fn dump(args: DumpArgs) -> Result<Vec<DumpRow>, Box<dyn std::error::Er
         });
     }
 
+    // infer slot types statically before indexing, so they can be reconciled once it's done
+    let static_types = static_types(&args).await;
+
     // update state
     let mut state = DUMP_STATE.lock().unwrap();
     *state = DumpState {
@@ -185,10 +293,15 @@ pub async fn dump(args: DumpArgs) -> Result<Vec<DumpRow>, Box<dyn std::error::Er
         scroll_index: 0,
         selection_size: 1,
         storage: HashMap::new(),
+        accounts: HashSet::new(),
         view: TUIView::Main,
         start_time: Instant::now(),
         input_buffer: String::new(),
         filter: String::new(),
+        sort_column: 1,
+        sort_descending: false,
+        value_format: ValueFormat::Hex,
+        static_types,
     };
     drop(state);
 
@@ -238,3 +351,121 @@ pub async fn dump(args: DumpArgs) -> Result<Vec<DumpRow>, Box<dyn std::error::Er
     ));
     Ok(csv)
 }
+
+/// Dump the target's storage with a sequential scan of slots `0..args.scan_slots`, displaying the
+/// results in the TUI unless it's disabled.
+async fn scan(
+    args: DumpArgs,
+    output_dir: String,
+) -> Result<Vec<DumpRow>, Box<dyn std::error::Error>> {
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    let addr_hash = match H160::from_str(&args.target) {
+        Ok(addr) => addr,
+        Err(_) => {
+            logger.error(&format!("failed to parse target '{}' .", &args.target));
+            std::process::exit(1);
+        }
+    };
+    if args.rpc_url.is_empty() {
+        logger.error("scanning storage requires an RPC provider. Use `heimdall dump --help` for more information.");
+        std::process::exit(1);
+    }
+
+    let (block, mut storage) = match scan_storage(addr_hash, &args.rpc_url, args.scan_slots).await {
+        Ok(result) => result,
+        Err(e) => {
+            logger.error(&format!("failed to scan storage of '{}': {e}", &args.target));
+            std::process::exit(1);
+        }
+    };
+
+    // type each slot which isn't part of a known layout from its value, reconciled with its
+    // statically inferred type
+    let static_types = static_types(&args).await;
+    for (slot, value) in storage.iter_mut() {
+        if value.alias.is_none() {
+            value.reconcile_type(static_types.get(slot).copied(), &HashSet::new());
+        }
+    }
+
+    // the scan is shown as a single, already indexed read of the latest block
+    let mut state = DUMP_STATE.lock().unwrap();
+    *state = DumpState {
+        args: args.clone(),
+        transactions: vec![Transaction {
+            indexed: true,
+            hash: String::from("eth_getStorageAt"),
+            block_number: block,
+        }],
+        storage,
+        static_types,
+        ..DumpState::new()
+    };
+    drop(state);
+
+    if !args.no_tui {
+        let tui_args = args.clone();
+        let tui_thread = std::thread::spawn(move || {
+            util::threads::tui::handle(&tui_args, &output_dir);
+        });
+        if let Err(e) = tui_thread.join() {
+            logger.error("failed to join TUI thread.");
+            logger.error(&format!("{e:?}"));
+            std::process::exit(1);
+        }
+    }
+
+    let state = DUMP_STATE.lock().unwrap();
+    let csv = build_csv(&state);
+    logger.info(&format!(
+        "Scanned {} non-empty storage slots from '{}' at block {block} .",
+        state.storage.len(),
+        &args.target
+    ));
+    Ok(csv)
+}
+
+/// Decompile the target to statically infer the type of each slot it writes, if `--static-types`
+/// is set. Failing to decompile the target only leaves slots to be typed by their values.
+async fn static_types(args: &DumpArgs) -> HashMap<H256, StorageType> {
+    let logger = Logger::default();
+    if !args.static_types {
+        return HashMap::new()
+    }
+
+    let decompiler_args = DecompilerArgsBuilder::new()
+        .target(args.target.clone())
+        .rpc_url(args.rpc_url.clone())
+        .non_interactive(true)
+        .build();
+    let result = match decompiler_args {
+        Ok(decompiler_args) => decompile(decompiler_args).await,
+        Err(e) => Err(e.into()),
+    };
+
+    match result {
+        Ok(result) => {
+            logger.info(&format!(
+                "statically inferred the types of {} storage slots.",
+                result.storage_types.len()
+            ));
+            result
+                .storage_types
+                .into_iter()
+                .map(|(slot, storage_type)| {
+                    let mut word = [0u8; 32];
+                    slot.to_big_endian(&mut word);
+                    (H256::from(word), storage_type)
+                })
+                .collect()
+        }
+        Err(e) => {
+            logger.warn(&format!("failed to decompile '{}' to infer slot types: {e}", args.target));
+            HashMap::new()
+        }
+    }
+}
@@ -1,10 +1,20 @@
-use std::{collections::HashMap, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    time::Instant,
+};
 
-use ethers::types::H256;
+use ethers::types::{H160, H256};
+use heimdall_common::{
+    ether::storage::StorageType,
+    utils::strings::{encode_hex, fuzzy_match},
+};
 
 use crate::dump::{menus::TUIView, DumpArgs};
 
-use super::{storage_slot::StorageSlot, transaction::Transaction};
+use super::{
+    storage_slot::{StorageSlot, ValueFormat},
+    transaction::Transaction,
+};
 
 /// The state of the Dump process, which will be updated as the process continues.
 /// This struct is also used to store the state of the TUI, and is often passed to the TUI renderer
@@ -16,10 +26,17 @@ pub struct DumpState {
     pub selection_size: usize,
     pub transactions: Vec<Transaction>,
     pub storage: HashMap<H256, StorageSlot>,
+    pub accounts: HashSet<H160>,
     pub view: TUIView,
     pub start_time: Instant,
     pub input_buffer: String,
     pub filter: String,
+    pub sort_column: usize,
+    pub sort_descending: bool,
+    pub value_format: ValueFormat,
+
+    /// the decompiler's statically inferred type of each slot, if `--static-types` is set.
+    pub static_types: HashMap<H256, StorageType>,
 }
 
 impl DumpState {
@@ -31,20 +48,68 @@ impl DumpState {
                 output: String::new(),
                 rpc_url: String::new(),
                 transpose_api_key: String::new(),
+                dune_api_key: String::new(),
+                backend: String::from("transpose"),
                 threads: 4,
                 from_block: 0,
                 to_block: 9999999999,
                 no_tui: false,
+                scan_slots: 0,
                 chain: String::from("ethereum"),
+                static_types: false,
             },
             scroll_index: 0,
             selection_size: 1,
             transactions: Vec::new(),
             storage: HashMap::new(),
+            accounts: HashSet::new(),
             view: TUIView::Main,
             start_time: Instant::now(),
             input_buffer: String::new(),
             filter: String::new(),
+            sort_column: 1,
+            sort_descending: false,
+            value_format: ValueFormat::Hex,
+            static_types: HashMap::new(),
         }
     }
+
+    /// The slots shown in the TUI, in display order. Slots are fuzzily matched against the
+    /// search filter by their slot, value, decoded value, and alias, then sorted by the selected
+    /// column.
+    pub fn visible_slots(&self) -> Vec<H256> {
+        let mut slots = self
+            .storage
+            .iter()
+            .filter(|(slot, value)| {
+                self.filter.is_empty() ||
+                    [
+                        format!("0x{}", encode_hex(slot.to_fixed_bytes().into())),
+                        format!("0x{}", encode_hex(value.value.to_fixed_bytes().into())),
+                        value.decoded_value(self.value_format),
+                        value.alias.clone().unwrap_or_default(),
+                    ]
+                    .iter()
+                    .any(|candidate| fuzzy_match(&self.filter, candidate))
+            })
+            .collect::<Vec<_>>();
+
+        // sort by the selected column, breaking ties by slot
+        slots.sort_by(|(a_slot, a), (b_slot, b)| {
+            let ordering = match self.sort_column {
+                0 => a.last_modified().cmp(&b.last_modified()),
+                2 => a.decode_as_type_index.cmp(&b.decode_as_type_index),
+                3 => a.value.cmp(&b.value),
+                _ => std::cmp::Ordering::Equal,
+            }
+            .then(a_slot.cmp(b_slot));
+
+            match self.sort_descending {
+                true => ordering.reverse(),
+                false => ordering,
+            }
+        });
+
+        slots.into_iter().map(|(slot, _)| *slot).collect()
+    }
 }
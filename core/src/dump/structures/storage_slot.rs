@@ -1,4 +1,32 @@
-use ethers::types::H256;
+use std::collections::HashSet;
+
+use ethers::{
+    abi::{decode, ParamType},
+    types::{H160, H256, U256},
+};
+use heimdall_common::{
+    ether::storage::{reconcile, StorageType, StorageTyping},
+    utils::strings::{encode_hex, hex_to_ascii},
+};
+
+/// How raw `bytes32` storage values are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueFormat {
+    Hex,
+    Decimal,
+    Ascii,
+}
+
+impl ValueFormat {
+    /// The next format, cycling from hex to decimal to ASCII.
+    pub fn next(self) -> Self {
+        match self {
+            ValueFormat::Hex => ValueFormat::Decimal,
+            ValueFormat::Decimal => ValueFormat::Ascii,
+            ValueFormat::Ascii => ValueFormat::Hex,
+        }
+    }
+}
 
 /// A storage slot, which contains the alias, value, and modifiers of a storage slot.
 #[derive(Debug, Clone)]
@@ -7,4 +35,125 @@ pub struct StorageSlot {
     pub value: H256,
     pub modifiers: Vec<(u128, String)>,
     pub decode_as_type_index: usize,
+
+    /// every value written to the slot, alongside the block number which wrote it.
+    pub history: Vec<(u128, H256)>,
+
+    /// the slot's type, reconciled from the decompiler's static inference and its history.
+    pub typing: Option<StorageTyping>,
+}
+
+/// The index of the decoding type which values of the storage type are displayed as.
+fn decode_as_type_index(storage_type: StorageType) -> usize {
+    match storage_type {
+        StorageType::Bool => 1,
+        StorageType::Address => 2,
+        StorageType::String => 3,
+        StorageType::Uint(_) => 4,
+        StorageType::Bytes(_) => 0,
+    }
+}
+
+impl StorageSlot {
+    /// The block number which last modified this slot.
+    pub fn last_modified(&self) -> u128 {
+        self.modifiers.iter().map(|m| m.0).max().unwrap_or(0)
+    }
+
+    /// Reconcile the slot's statically inferred type, if any, with every value written to it,
+    /// and decode it as the reconciled type. Without a static type, slots which only ever hold
+    /// known addresses are addresses, slots which only hold 0 or 1 are bools, and slots which
+    /// only increase are counters or timestamps.
+    pub fn reconcile_type(
+        &mut self,
+        static_type: Option<StorageType>,
+        known_addresses: &HashSet<H160>,
+    ) {
+        self.typing = reconcile(static_type, &self.values(), known_addresses);
+        if let Some(typing) = &self.typing {
+            self.decode_as_type_index = decode_as_type_index(typing.storage_type);
+        }
+    }
+
+    /// Every value written to the slot, in the order they were written.
+    fn values(&self) -> Vec<U256> {
+        let mut history = self.history.clone();
+        history.sort_by_key(|(block_number, _)| *block_number);
+        history.iter().map(|(_, value)| U256::from_big_endian(value.as_bytes())).collect()
+    }
+
+    /// Decode the slot's value as its selected type, displaying raw `bytes32` values in the given
+    /// format.
+    pub fn decoded_value(&self, value_format: ValueFormat) -> String {
+        let hex_value = encode_hex(self.value.to_fixed_bytes().into());
+        match self.decode_as_type_index {
+            0 => match value_format {
+                ValueFormat::Hex => format!("0x{hex_value}"),
+                ValueFormat::Decimal => U256::from_big_endian(self.value.as_bytes()).to_string(),
+                ValueFormat::Ascii => hex_to_ascii(&hex_value),
+            },
+            1 => format!("{}", !self.value.is_zero()),
+            2 => format!("0x{}", hex_value.get(24..).unwrap_or("")),
+            3 => match decode(&[ParamType::String], self.value.as_bytes()) {
+                Ok(decoded) => decoded[0].to_string(),
+                Err(_) => hex_to_ascii(&hex_value),
+            },
+            4 => U256::from_big_endian(self.value.as_bytes()).to_string(),
+            _ => "decoding error".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_slot(history: Vec<u64>) -> StorageSlot {
+        let history = history
+            .into_iter()
+            .enumerate()
+            .map(|(block_number, value)| (block_number as u128, H256::from_low_u64_be(value)))
+            .collect::<Vec<_>>();
+
+        StorageSlot {
+            alias: None,
+            value: history.last().unwrap().1,
+            modifiers: Vec::new(),
+            decode_as_type_index: 0,
+            history,
+            typing: None,
+        }
+    }
+
+    #[test]
+    fn test_infer_type_index() {
+        let known_addresses = HashSet::from([H160::from_low_u64_be(0xbeef)]);
+        let infer_type_index = |history| {
+            let mut slot = storage_slot(history);
+            slot.reconcile_type(None, &known_addresses);
+            slot.typing.map(|_| slot.decode_as_type_index)
+        };
+
+        assert_eq!(infer_type_index(vec![0, 1, 0]), Some(1));
+        assert_eq!(infer_type_index(vec![0xbeef, 0]), Some(2));
+        assert_eq!(infer_type_index(vec![2, 5, 9]), Some(4));
+        assert_eq!(infer_type_index(vec![9, 5]), None);
+    }
+
+    #[test]
+    fn test_reconcile_type() {
+        let known_addresses = HashSet::new();
+
+        // a uint8 statically, which its values agree with
+        let mut slot = storage_slot(vec![3, 7]);
+        slot.reconcile_type(Some(StorageType::Uint(8)), &known_addresses);
+        assert_eq!(slot.decode_as_type_index, 4);
+        assert!(!slot.typing.unwrap().conflict);
+
+        // a bool statically, which its values contradict
+        let mut slot = storage_slot(vec![3, 7]);
+        slot.reconcile_type(Some(StorageType::Bool), &known_addresses);
+        assert_eq!(slot.decode_as_type_index, 4);
+        assert!(slot.typing.unwrap().conflict);
+    }
 }
@@ -1,11 +1,4 @@
-use ethers::{
-    abi::{decode, ParamType},
-    types::U256,
-};
-use heimdall_common::utils::{
-    io::file::write_lines_to_file,
-    strings::{encode_hex, hex_to_ascii},
-};
+use heimdall_common::utils::{io::file::write_csv_to_file, strings::encode_hex};
 
 use crate::dump::{constants::DECODE_AS_TYPES, structures::dump_state::DumpState};
 
@@ -17,6 +10,8 @@ pub struct DumpRow {
     pub slot: String,
     pub decoded_type: String,
     pub value: String,
+    /// How likely the decoded type is to be correct, from 0 to 1, if it was inferred.
+    pub type_confidence: String,
 }
 
 /// Convert [`DumpState`] to a Vec of [`DumpRow`]s, which can be used to build a CSV.
@@ -28,54 +23,48 @@ pub fn build_csv(state: &DumpState) -> Vec<DumpRow> {
     storage_iter.sort_by_key(|(slot, _)| *slot);
 
     for (slot, value) in storage_iter {
-        let decoded_value = match value.decode_as_type_index {
-            0 => format!("0x{}", encode_hex(value.value.to_fixed_bytes().into())),
-            1 => format!("{}", !value.value.is_zero()),
-            2 => format!(
-                "0x{}",
-                encode_hex(value.value.to_fixed_bytes().into()).get(24..).unwrap_or("")
-            ),
-            3 => match decode(&[ParamType::String], value.value.as_bytes()) {
-                Ok(decoded) => decoded[0].to_string(),
-                Err(_) => hex_to_ascii(&encode_hex(value.value.to_fixed_bytes().into())),
-            },
-            4 => {
-                let decoded = U256::from_big_endian(&value.value.to_fixed_bytes());
-                format!("{decoded}")
-            }
-            _ => "decoding error".to_string(),
-        };
-
         lines.push(DumpRow {
-            last_modified: value.modifiers.iter().max_by_key(|m| m.0).unwrap().0.to_string(),
+            last_modified: value.last_modified().to_string(),
             alias: value.alias.as_ref().unwrap_or(&String::from("None")).to_string(),
             slot: encode_hex(slot.to_fixed_bytes().into()),
             decoded_type: DECODE_AS_TYPES[value.decode_as_type_index].to_string(),
-            value: decoded_value,
+            value: value.decoded_value(state.value_format),
+            type_confidence: value
+                .typing
+                .map(|typing| format!("{:.2}", typing.confidence))
+                .unwrap_or_default(),
         })
     }
     lines
 }
 
+/// Convert [`DumpRow`]s to the rows of a CSV, including its header.
+pub fn csv_rows(dump_rows: &[DumpRow]) -> Vec<Vec<String>> {
+    let mut rows =
+        vec![["last_modified", "alias", "slot", "decoded_type", "value", "type_confidence"]
+            .iter()
+            .map(|column| column.to_string())
+            .collect::<Vec<String>>()];
+    for row in dump_rows {
+        rows.push(vec![
+            row.last_modified.clone(),
+            row.alias.clone(),
+            row.slot.clone(),
+            row.decoded_type.clone(),
+            row.value.clone(),
+            row.type_confidence.clone(),
+        ]);
+    }
+    rows
+}
+
 /// Write the storage to a CSV file.
 pub fn write_storage_to_csv(output_dir: &str, file_name: &str, state: &DumpState) {
-    let mut csv_rows = build_csv(state);
-    let mut lines: Vec<String> = Vec::new();
+    let mut dump_rows = build_csv(state);
 
     // sort by last modified descending
-    csv_rows.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
-
-    // add header
-    lines.push(String::from("last_modified,alias,slot,decoded_type,value"));
-
-    // add rows
-    for row in csv_rows {
-        lines.push(format!(
-            "{},{},{},{},{}",
-            row.last_modified, row.alias, row.slot, row.decoded_type, row.value
-        ));
-    }
+    dump_rows.sort_by(|a, b| b.last_modified.cmp(&a.last_modified));
 
     // write to file
-    write_lines_to_file(&format!("{}/{}", output_dir, file_name), lines);
+    write_csv_to_file(&format!("{}/{}", output_dir, file_name), &csv_rows(&dump_rows));
 }
@@ -0,0 +1,318 @@
+use std::collections::{HashMap, HashSet};
+
+use ethers::{
+    abi::{decode, ParamType},
+    types::{H160, H256, U256},
+    utils::{id, keccak256},
+};
+use heimdall_common::{
+    ether::rpc::{rpc_capabilities, rpc_request},
+    utils::{
+        io::logging::Logger,
+        strings::{decode_hex, encode_hex},
+    },
+};
+
+use crate::dump::{constants::DUMP_STATE, structures::storage_slot::StorageSlot};
+
+/// A storage variable of a canonical OpenZeppelin layout, at an offset from the layout's first
+/// slot.
+struct KnownVariable {
+    offset: u64,
+    name: &'static str,
+    kind: KnownVariableKind,
+    decode_as_type_index: usize,
+}
+
+enum KnownVariableKind {
+    /// A value stored directly in its slot, validated against the given view function.
+    Value(&'static str, ParamType),
+
+    /// A mapping keyed by the given number of addresses.
+    AddressMapping(usize),
+}
+
+/// The canonical OpenZeppelin ERC-20, ERC-721, and Ownable storage layouts.
+fn known_layouts() -> Vec<(&'static str, Vec<KnownVariable>)> {
+    let value = |offset, name, getter, param_type, decode_as_type_index| KnownVariable {
+        offset,
+        name,
+        kind: KnownVariableKind::Value(getter, param_type),
+        decode_as_type_index,
+    };
+    let mapping = |offset, name, depth, decode_as_type_index| KnownVariable {
+        offset,
+        name,
+        kind: KnownVariableKind::AddressMapping(depth),
+        decode_as_type_index,
+    };
+
+    vec![
+        (
+            "ERC20",
+            vec![
+                mapping(0, "_balances", 1, 4),
+                mapping(1, "_allowances", 2, 4),
+                value(2, "_totalSupply", "totalSupply()", ParamType::Uint(256), 4),
+                value(3, "_name", "name()", ParamType::String, 3),
+                value(4, "_symbol", "symbol()", ParamType::String, 3),
+            ],
+        ),
+        (
+            "ERC721",
+            vec![
+                value(0, "_name", "name()", ParamType::String, 3),
+                value(1, "_symbol", "symbol()", ParamType::String, 3),
+                mapping(3, "_balances", 1, 4),
+                mapping(5, "_operatorApprovals", 2, 1),
+            ],
+        ),
+        ("Ownable", vec![value(0, "_owner", "owner()", ParamType::Address, 2)]),
+    ]
+}
+
+/// Well-known slots used by proxy and upgradeability standards, which lie far outside of any
+/// contract's sequential layout. Each is listed with its alias and decoding type.
+pub const WELL_KNOWN_SLOTS: [(&str, &str, usize); 5] = [
+    (
+        "eip1967.proxy.implementation",
+        "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+        2,
+    ),
+    ("eip1967.proxy.admin", "b53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103", 2),
+    ("eip1967.proxy.beacon", "a3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50", 2),
+    ("PROXIABLE", "c5f16f0fcc639fa48a6947836d9850f504798523bf8c9a3a87d5876cf622bcf7", 2),
+    (
+        "openzeppelin.storage.Initializable",
+        "f0c57e16840df040f15088dc2f81fe391c3923bec73e23a9662efc9c229c6a00",
+        0,
+    ),
+];
+
+/// The highest slot at which a known layout may begin, allowing for layouts which are inherited
+/// after one another.
+const MAX_LAYOUT_BASE: u64 = 8;
+
+/// The slot of `mapping[key]`, for a mapping at the given slot.
+pub fn mapping_slot(key: H256, slot: H256) -> H256 {
+    H256::from(keccak256([key.as_bytes(), slot.as_bytes()].concat()))
+}
+
+/// The storage word which the given view function return data is stored as. Strings shorter than
+/// 32 bytes are stored with their length in the lowest byte, while longer strings store only their
+/// length.
+fn expected_word(param_type: &ParamType, return_data: &[u8]) -> Option<H256> {
+    match param_type {
+        ParamType::String => {
+            let decoded = decode(&[ParamType::String], return_data).ok()?;
+            let string = decoded[0].clone().into_string()?;
+            let mut word = [0u8; 32];
+            if string.len() < 32 {
+                word[..string.len()].copy_from_slice(string.as_bytes());
+                word[31] = (string.len() * 2) as u8;
+            } else {
+                U256::from(string.len() * 2 + 1).to_big_endian(&mut word);
+            }
+            Some(H256::from(word))
+        }
+        _ => return_data.get(0..32).map(H256::from_slice),
+    }
+}
+
+/// Find the slots of known token layouts within the dumped storage, returning each slot's alias
+/// and decoding type. A layout is only recognized when all of its value variables match the return
+/// data of their view functions, after which the entries of its mappings are aliased for any of the
+/// given accounts.
+pub fn find_known_slots(
+    storage: &HashMap<H256, StorageSlot>,
+    accounts: &HashSet<H160>,
+    view_results: &HashMap<String, Vec<u8>>,
+) -> HashMap<H256, (String, usize)> {
+    let mut aliases = HashMap::new();
+    let keys = accounts.iter().map(|account| H256::from(*account)).collect::<Vec<_>>();
+
+    for (_, variables) in known_layouts() {
+        for base in 0..=MAX_LAYOUT_BASE {
+            let slot_of = |variable: &KnownVariable| H256::from_low_u64_be(base + variable.offset);
+
+            // every value variable must match its view function, though unset zero values won't
+            // appear in the dump
+            let matches = variables.iter().all(|variable| match &variable.kind {
+                KnownVariableKind::Value(getter, param_type) => {
+                    match view_results.get(*getter).and_then(|data| expected_word(param_type, data))
+                    {
+                        Some(expected) => match storage.get(&slot_of(variable)) {
+                            Some(slot) => {
+                                slot.value == expected && !aliases.contains_key(&slot_of(variable))
+                            }
+                            None => expected.is_zero(),
+                        },
+                        None => false,
+                    }
+                }
+                KnownVariableKind::AddressMapping(_) => true,
+            });
+            if !matches {
+                continue
+            }
+
+            for variable in &variables {
+                let slot = slot_of(variable);
+                match variable.kind {
+                    KnownVariableKind::Value(..) => {
+                        if storage.contains_key(&slot) {
+                            aliases.insert(
+                                slot,
+                                (variable.name.to_string(), variable.decode_as_type_index),
+                            );
+                        }
+                    }
+                    KnownVariableKind::AddressMapping(depth) => {
+                        let mut entries = vec![(slot, String::new())];
+                        for _ in 0..depth {
+                            entries = entries
+                                .iter()
+                                .flat_map(|(slot, path)| {
+                                    keys.iter().map(move |key| {
+                                        (
+                                            mapping_slot(*key, *slot),
+                                            format!("{}[{:?}]", path, H160::from(*key)),
+                                        )
+                                    })
+                                })
+                                .collect();
+                        }
+
+                        for (slot, path) in entries {
+                            if storage.contains_key(&slot) {
+                                aliases.insert(
+                                    slot,
+                                    (
+                                        format!("{}{}", variable.name, path),
+                                        variable.decode_as_type_index,
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+            break
+        }
+    }
+
+    aliases
+}
+
+/// Alias the dumped slots which belong to known token layouts, validating each layout by calling
+/// its view functions on the target contract.
+pub async fn alias_known_slots(addr_hash: H160, rpc_url: &str) {
+    let logger = Logger::default();
+
+    // call each view function at the latest dumped block, so values can be compared. providers
+    // without historical state can only be called at the latest block
+    let block =
+        DUMP_STATE.lock().unwrap().transactions.iter().map(|tx| tx.block_number).max().unwrap_or(0);
+    let block = match rpc_capabilities(rpc_url).await.archive {
+        true => format!("0x{block:x}"),
+        false => String::from("latest"),
+    };
+
+    let mut view_results = HashMap::new();
+    for getter in ["totalSupply()", "name()", "symbol()", "owner()"] {
+        let result = rpc_request(
+            rpc_url,
+            "eth_call",
+            serde_json::json!([
+                { "to": format!("{addr_hash:#x}"), "data": format!("0x{}", encode_hex(id(getter).to_vec())) },
+                block
+            ]),
+        )
+        .await;
+
+        if let Some(data) = result
+            .ok()
+            .and_then(|result| result.as_str().map(|data| data.to_string()))
+            .and_then(|data| decode_hex(data.trim_start_matches("0x")).ok())
+        {
+            view_results.insert(getter.to_string(), data);
+        }
+    }
+
+    let mut state = DUMP_STATE.lock().unwrap();
+    let aliases = find_known_slots(&state.storage, &state.accounts, &view_results);
+    logger.debug(&format!("aliased {} slots belonging to known token layouts.", aliases.len()));
+
+    for (slot, (alias, decode_as_type_index)) in aliases {
+        if let Some(value) = state.storage.get_mut(&slot) {
+            value.alias = Some(alias);
+            value.decode_as_type_index = decode_as_type_index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn storage_slot(value: H256) -> StorageSlot {
+        StorageSlot {
+            alias: None,
+            value,
+            modifiers: vec![(1, String::new())],
+            decode_as_type_index: 0,
+            history: vec![(1, value)],
+            typing: None,
+        }
+    }
+
+    #[test]
+    fn test_find_known_erc20_slots() {
+        let holder = H160::from_low_u64_be(0xbeef);
+        let owner = H160::from_low_u64_be(0xcafe);
+
+        // an ERC20 with ownable inherited first, shifting the token's layout by one slot
+        let mut name = [0u8; 32];
+        name[..5].copy_from_slice(b"Token");
+        name[31] = 10;
+        let mut storage = HashMap::new();
+        storage.insert(H256::from_low_u64_be(0), storage_slot(H256::from(owner)));
+        storage.insert(H256::from_low_u64_be(3), storage_slot(H256::from_low_u64_be(1000)));
+        storage.insert(H256::from_low_u64_be(4), storage_slot(H256::from(name)));
+        storage.insert(
+            mapping_slot(H256::from(holder), H256::from_low_u64_be(1)),
+            storage_slot(H256::from_low_u64_be(1000)),
+        );
+
+        let string_return =
+            |string: &str| ethers::abi::encode(&[ethers::abi::Token::String(string.to_string())]);
+        let view_results = HashMap::from([
+            ("totalSupply()".to_string(), H256::from_low_u64_be(1000).as_bytes().to_vec()),
+            ("name()".to_string(), string_return("Token")),
+            ("symbol()".to_string(), string_return("")),
+            ("owner()".to_string(), H256::from(owner).as_bytes().to_vec()),
+        ]);
+
+        let aliases = find_known_slots(&storage, &HashSet::from([holder]), &view_results);
+        assert_eq!(aliases.get(&H256::from_low_u64_be(0)), Some(&("_owner".to_string(), 2)));
+        assert_eq!(aliases.get(&H256::from_low_u64_be(3)), Some(&("_totalSupply".to_string(), 4)));
+        assert_eq!(aliases.get(&H256::from_low_u64_be(4)), Some(&("_name".to_string(), 3)));
+        assert_eq!(
+            aliases.get(&mapping_slot(H256::from(holder), H256::from_low_u64_be(1))),
+            Some(&(format!("_balances[{holder:?}]"), 4))
+        );
+    }
+
+    #[test]
+    fn test_find_known_slots_requires_matching_views() {
+        let mut storage = HashMap::new();
+        storage.insert(H256::from_low_u64_be(2), storage_slot(H256::from_low_u64_be(1000)));
+
+        let view_results = HashMap::from([(
+            "totalSupply()".to_string(),
+            H256::from_low_u64_be(999).as_bytes().to_vec(),
+        )]);
+
+        assert!(find_known_slots(&storage, &HashSet::new(), &view_results).is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+use ethers::types::U256;
+use serde_json::{json, Map, Value};
+
+use crate::dump::util::csv::DumpRow;
+
+/// Slots below this bound are sequentially laid out state variables, while those above it are
+/// hashed, such as mapping entries and namespaced slots, which solc doesn't list.
+const MAX_SEQUENTIAL_SLOT: u64 = u32::MAX as u64;
+
+/// The solc type identifier of a decoded type, along with its description in the `types` section
+/// of a storage layout.
+fn solc_type(decoded_type: &str) -> (String, Value) {
+    let (encoding, number_of_bytes) = match decoded_type {
+        "bool" => ("inplace", 1),
+        "address" => ("inplace", 20),
+        "string" => ("bytes", 32),
+        _ => ("inplace", 32),
+    };
+    let identifier = match decoded_type {
+        "string" => String::from("t_string_storage"),
+        decoded_type => format!("t_{decoded_type}"),
+    };
+
+    (
+        identifier,
+        json!({
+            "encoding": encoding,
+            "label": decoded_type,
+            "numberOfBytes": number_of_bytes.to_string(),
+        }),
+    )
+}
+
+/// Build the inferred storage layout of a dump in the JSON schema of solc's `storageLayout`
+/// output, so it can be consumed by tools such as upgrade safety plugins and `forge inspect`.
+///
+/// Only sequentially laid out variables are included, each labeled with its alias or, if it has
+/// none, as `stor_<slot>`. Mapping entries and other hashed slots are omitted, since the slot of
+/// the variable which declares them can't be recovered. Packed variables aren't separated, so
+/// every variable has an offset of 0.
+pub fn storage_layout(rows: &[DumpRow], contract: &str) -> Value {
+    let mut rows = rows
+        .iter()
+        .filter_map(|row| {
+            let slot = U256::from_str_radix(&row.slot, 16).ok()?;
+            (slot <= U256::from(MAX_SEQUENTIAL_SLOT)).then(|| (slot.as_u64(), row))
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by_key(|(slot, _)| *slot);
+
+    let mut types = Map::new();
+    let storage = rows
+        .iter()
+        .enumerate()
+        .map(|(index, (slot, row))| {
+            let (identifier, description) = solc_type(&row.decoded_type);
+            types.insert(identifier.clone(), description);
+
+            let label = match row.alias.as_str() {
+                "" | "None" => format!("stor_{slot}"),
+                alias => alias.to_string(),
+            };
+            json!({
+                "astId": index,
+                "contract": format!("{contract}.sol:{contract}"),
+                "label": label,
+                "offset": 0,
+                "slot": slot.to_string(),
+                "type": identifier,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "storage": storage,
+        "types": types,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dump_row(slot: &str, alias: &str, decoded_type: &str) -> DumpRow {
+        DumpRow {
+            last_modified: String::from("0"),
+            alias: alias.to_string(),
+            slot: format!("{slot:0>64}"),
+            decoded_type: decoded_type.to_string(),
+            value: String::new(),
+            type_confidence: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_storage_layout() {
+        let rows = vec![
+            dump_row("2", "_totalSupply", "uint256"),
+            dump_row("5", "None", "address"),
+            dump_row(
+                "360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc",
+                "eip1967.proxy.implementation",
+                "address",
+            ),
+            dump_row("3", "_name", "string"),
+        ];
+        let layout = storage_layout(&rows, "DecompiledContract");
+
+        let storage = layout["storage"].as_array().unwrap();
+        assert_eq!(storage.len(), 3);
+        assert_eq!(
+            storage[0],
+            json!({
+                "astId": 0,
+                "contract": "DecompiledContract.sol:DecompiledContract",
+                "label": "_totalSupply",
+                "offset": 0,
+                "slot": "2",
+                "type": "t_uint256",
+            })
+        );
+        assert_eq!(storage[1]["type"], "t_string_storage");
+        assert_eq!(storage[2]["label"], "stor_5");
+
+        assert_eq!(
+            layout["types"]["t_address"],
+            json!({ "encoding": "inplace", "label": "address", "numberOfBytes": "20" })
+        );
+        assert_eq!(layout["types"]["t_string_storage"]["encoding"], "bytes");
+    }
+}
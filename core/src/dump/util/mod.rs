@@ -1,4 +1,7 @@
 pub mod csv;
+pub mod known_slots;
+pub mod layout;
+pub mod scan;
 pub mod table;
 pub mod threads;
 
@@ -10,11 +13,11 @@ use crossterm::{
     terminal::{disable_raw_mode, LeaveAlternateScreen},
 };
 use ethers::{
-    providers::{Http, Middleware, Provider},
+    providers::Middleware,
     types::{StateDiff, TraceType, H256},
 };
 use heimdall_cache::{read_cache, store_cache};
-use heimdall_common::utils::io::logging::Logger;
+use heimdall_common::{ether::rpc::provider, utils::io::logging::Logger};
 use tui::{backend::CrosstermBackend, Terminal};
 
 use super::{structures::transaction::Transaction, DumpArgs};
@@ -54,7 +57,7 @@ pub async fn get_storage_diff(tx: &Transaction, args: &DumpArgs) -> Option<State
     }
 
     // create new provider
-    let provider = match Provider::<Http>::try_from(&args.rpc_url) {
+    let provider = match provider(&args.rpc_url).await {
         Ok(provider) => provider,
         Err(_) => {
             cleanup_terminal();
@@ -0,0 +1,76 @@
+use std::{collections::HashMap, str::FromStr};
+
+use ethers::types::{H160, H256};
+use heimdall_common::ether::rpc::{get_storage_at_batch, rpc_request};
+
+use crate::dump::{structures::storage_slot::StorageSlot, util::known_slots::WELL_KNOWN_SLOTS};
+
+/// The number of slots read in each batch request.
+const BATCH_SIZE: usize = 100;
+
+/// The slots read by a sequential scan: slots `0..count`, followed by the well-known slots.
+pub fn scan_targets(count: u128) -> Vec<(H256, Option<(&'static str, usize)>)> {
+    let mut slots: Vec<(H256, Option<(&'static str, usize)>)> =
+        (0..count).map(|slot| (H256::from_low_u64_be(slot as u64), None)).collect();
+    for (alias, slot, decode_as_type_index) in WELL_KNOWN_SLOTS {
+        // safe to unwrap because the well-known slots are valid hex
+        slots.push((H256::from_str(slot).unwrap(), Some((alias, decode_as_type_index))));
+    }
+    slots
+}
+
+/// Read slots `0..count` and the well-known slots of the target at the latest block, with batched
+/// `eth_getStorageAt` requests. Requires no indexer or trace support. Returns the block which was
+/// read and the non-empty slots.
+pub async fn scan_storage(
+    addr_hash: H160,
+    rpc_url: &str,
+    count: u128,
+) -> Result<(u128, HashMap<H256, StorageSlot>), Box<dyn std::error::Error>> {
+    // pin the scan to a single block, so every slot is read from the same state
+    let block_number = rpc_request(rpc_url, "eth_blockNumber", serde_json::json!([])).await?;
+    let block_number = block_number.as_str().ok_or("received an invalid block number")?;
+    let block = u128::from_str_radix(block_number.trim_start_matches("0x"), 16)?;
+
+    let targets = scan_targets(count);
+    let mut storage = HashMap::new();
+    for chunk in targets.chunks(BATCH_SIZE) {
+        let slots = chunk.iter().map(|(slot, _)| *slot).collect::<Vec<H256>>();
+        let values =
+            get_storage_at_batch(rpc_url, &format!("{addr_hash:#x}"), &slots, block_number).await?;
+
+        for ((slot, known), value) in chunk.iter().zip(values) {
+            if value.is_zero() {
+                continue
+            }
+
+            storage.insert(
+                *slot,
+                StorageSlot {
+                    alias: known.map(|(alias, _)| alias.to_string()),
+                    value,
+                    modifiers: vec![(block, String::from("eth_getStorageAt"))],
+                    decode_as_type_index: known.map(|(_, index)| index).unwrap_or(0),
+                    history: vec![(block, value)],
+                    typing: None,
+                },
+            );
+        }
+    }
+
+    Ok((block, storage))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_targets() {
+        let targets = scan_targets(3);
+
+        assert_eq!(targets.len(), 3 + WELL_KNOWN_SLOTS.len());
+        assert_eq!(targets[2], (H256::from_low_u64_be(2), None));
+        assert_eq!(targets[3].1, Some(("eip1967.proxy.implementation", 2)));
+    }
+}
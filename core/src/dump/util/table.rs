@@ -1,42 +1,47 @@
-use ethers::{
-    abi::{decode, ParamType},
-    types::U256,
-};
-use heimdall_common::utils::strings::{encode_hex, hex_to_ascii};
+use heimdall_common::{ether::labels::substitute_labels, utils::strings::encode_hex};
 use tui::{
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     widgets::{Cell, Row},
 };
 
-use crate::dump::{constants::DECODE_AS_TYPES, structures::dump_state::DumpState};
+use crate::dump::{
+    constants::{DECODE_AS_TYPES, STORAGE_COLUMNS},
+    structures::dump_state::DumpState,
+};
+
+/// Build the storage table's header row, marking the column which the slots are sorted by.
+pub fn build_header(state: &DumpState) -> Row<'static> {
+    let header_cells = STORAGE_COLUMNS.iter().enumerate().map(|(index, column)| {
+        Cell::from(match index == state.sort_column {
+            true => format!("{} {}", column, if state.sort_descending { "▼" } else { "▲" }),
+            false => column.clone(),
+        })
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+    });
+
+    Row::new(header_cells)
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .height(1)
+        .bottom_margin(1)
+}
 
 /// A helper function used in many TUI views for rendering list rows, as well as handling scrolling
 /// and selection.
 pub fn build_rows(state: &mut DumpState, max_row_height: usize) -> Vec<Row<'static>> {
+    // filter and sort the storage slots
+    let storage_iter = state
+        .visible_slots()
+        .into_iter()
+        .map(|slot| (slot, state.storage.get(&slot).unwrap()))
+        .collect::<Vec<_>>();
+
     // ensure scroll index is within bounds
-    if state.scroll_index >= state.storage.len() && state.scroll_index != 0 {
-        state.scroll_index = state.storage.len() - 1;
+    if state.scroll_index >= storage_iter.len() && state.scroll_index != 0 {
+        state.scroll_index = storage_iter.len().saturating_sub(1);
     }
 
     // render storage slot list
     let mut rows = Vec::new();
-
-    // filter storage_iter by state.filter
-    let mut storage_iter = match !state.filter.is_empty() {
-        true => state
-            .storage
-            .iter()
-            .filter(|(slot, value)| {
-                let slot = format!("0x{}", encode_hex(slot.to_fixed_bytes().into()));
-                let value = format!("0x{}", encode_hex(value.value.to_fixed_bytes().into()));
-                slot.contains(&state.filter) || value.contains(&state.filter)
-            })
-            .collect::<Vec<_>>(),
-        false => state.storage.iter().collect::<Vec<_>>(),
-    };
-
-    // sort storage slots by slot
-    storage_iter.sort_by_key(|(slot, _)| *slot);
     let num_items = std::cmp::min(max_row_height, storage_iter.len());
 
     let indices = match state.scroll_index + num_items <= storage_iter.len() {
@@ -46,30 +51,15 @@ pub fn build_rows(state: &mut DumpState, max_row_height: usize) -> Vec<Row<'stat
 
     // slice storage_iter
     for (i, (slot, value)) in storage_iter[indices].iter().enumerate() {
-        let decoded_value = match value.decode_as_type_index {
-            0 => format!("0x{}", encode_hex(value.value.to_fixed_bytes().into())),
-            1 => format!("{}", !value.value.is_zero()),
-            2 => format!(
-                "0x{}",
-                encode_hex(value.value.to_fixed_bytes().into()).get(24..).unwrap_or("")
-            ),
-            3 => match decode(&[ParamType::String], value.value.as_bytes()) {
-                Ok(decoded) => decoded[0].to_string(),
-                Err(_) => hex_to_ascii(&encode_hex(value.value.to_fixed_bytes().into())),
-            },
-            4 => {
-                let decoded = U256::from_big_endian(&value.value.to_fixed_bytes());
-                format!("{decoded}")
-            }
-            _ => "decoding error".to_string(),
-        };
-
         rows.push(
             Row::new(vec![
-                Cell::from(value.modifiers.iter().max_by_key(|m| m.0).unwrap().0.to_string()),
-                Cell::from(format!("0x{}", encode_hex(slot.to_fixed_bytes().into()))),
+                Cell::from(value.last_modified().to_string()),
+                Cell::from(match &value.alias {
+                    Some(alias) => alias.clone(),
+                    None => format!("0x{}", encode_hex(slot.to_fixed_bytes().into())),
+                }),
                 Cell::from(DECODE_AS_TYPES[value.decode_as_type_index].clone()),
-                Cell::from(decoded_value),
+                Cell::from(substitute_labels(&value.decoded_value(state.value_format))),
             ])
             .style(if storage_iter.len() - state.scroll_index < num_items {
                 if (num_items - i <= storage_iter.len() - state.scroll_index) &&
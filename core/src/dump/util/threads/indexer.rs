@@ -1,20 +1,24 @@
 use std::time::Duration;
 
 use ethers::types::{Diff, H160};
-use heimdall_common::utils::{io::logging::Logger, threading::task_pool};
+use heimdall_common::utils::{
+    interrupt::is_interrupted, io::logging::Logger, threading::task_pool,
+};
 use indicatif::ProgressBar;
 
 use crate::dump::{
-    constants::DUMP_STATE, structures::storage_slot::StorageSlot, util::get_storage_diff,
+    constants::DUMP_STATE,
+    structures::storage_slot::StorageSlot,
+    util::{get_storage_diff, known_slots::alias_known_slots},
 };
 
 /// The main function for indexing storage slots. Will fetch the storage diff for each transaction
 /// in a threaded task pool, updating the state accordingly.
 pub async fn handle(addr_hash: H160) {
-    let state = DUMP_STATE.lock().unwrap();
-    let transactions = state.transactions.clone();
-    let args = state.args.clone();
-    drop(state);
+    let (transactions, args) = {
+        let state = DUMP_STATE.lock().unwrap();
+        (state.transactions.clone(), state.args.clone())
+    };
 
     // the number of threads cannot exceed the number of transactions
     let num_indexing_threads = std::cmp::min(transactions.len(), args.threads);
@@ -34,7 +38,13 @@ pub async fn handle(addr_hash: H160) {
         transaction_list_progress.finish_and_clear();
     }
 
+    let rpc_url = args.rpc_url.clone();
     task_pool(transactions, num_indexing_threads, move |tx| {
+        // skip the remaining transactions once the user has interrupted the dump
+        if is_interrupted() {
+            return
+        }
+
         // get new blocking runtime
         let rt = tokio::runtime::Runtime::new().unwrap();
 
@@ -67,6 +77,9 @@ pub async fn handle(addr_hash: H160) {
 
         // unwrap the state diff
         if let Some(state_diff) = state_diff {
+            // remember the accounts touched by this transaction, which are candidate mapping keys
+            state.accounts.extend(state_diff.0.keys());
+
             // get diff for this address
             if let Some(diff) = state_diff.0.get(&addr_hash) {
                 // build diff of StorageSlots and append to state
@@ -91,6 +104,7 @@ pub async fn handle(addr_hash: H160) {
                             }
 
                             slot.modifiers.push((block_number, tx.hash.clone().to_owned()));
+                            slot.history.push((block_number, *value));
                         }
                         None => {
                             // insert into state
@@ -101,6 +115,8 @@ pub async fn handle(addr_hash: H160) {
                                     modifiers: vec![(block_number, tx.hash.clone().to_owned())],
                                     alias: None,
                                     decode_as_type_index: 0,
+                                    history: vec![(block_number, *value)],
+                                    typing: None,
                                 },
                             );
                         }
@@ -112,4 +128,28 @@ pub async fn handle(addr_hash: H160) {
         // drop state
         drop(state);
     });
+    // refine each slot's decoding type from its write history, reconciled with its statically
+    // inferred type
+    {
+        let mut state = DUMP_STATE.lock().unwrap();
+        let accounts = state.accounts.clone();
+        let static_types = state.static_types.clone();
+        for (slot, value) in state.storage.iter_mut() {
+            value.reconcile_type(static_types.get(slot).copied(), &accounts);
+        }
+
+        let conflicts = state
+            .storage
+            .values()
+            .filter(|value| value.typing.is_some_and(|typing| typing.conflict))
+            .count();
+        if conflicts > 0 {
+            logger.warn(&format!(
+                "the values of {conflicts} slots contradict their statically inferred types."
+            ));
+        }
+    }
+
+    // alias the slots of known token layouts, now that all slots have been indexed
+    alias_known_slots(addr_hash, &rpc_url).await;
 }
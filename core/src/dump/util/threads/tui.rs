@@ -5,15 +5,36 @@ use crossterm::{
     execute,
     terminal::{enable_raw_mode, EnterAlternateScreen},
 };
+use ethers::types::H256;
+use heimdall_common::utils::interrupt::interrupt;
 use tui::{backend::CrosstermBackend, Terminal};
 
 use crate::dump::{
-    constants::{DECODE_AS_TYPES, DUMP_STATE},
+    constants::{DECODE_AS_TYPES, DUMP_STATE, STORAGE_COLUMNS},
     menus::{render_ui, TUIView},
+    structures::dump_state::DumpState,
     util::{cleanup_terminal, csv::write_storage_to_csv},
     DumpArgs,
 };
 
+/// The slots currently selected in the TUI.
+fn selected_slots(state: &DumpState) -> Vec<H256> {
+    state.visible_slots().into_iter().skip(state.scroll_index).take(state.selection_size).collect()
+}
+
+/// Filter the storage slots as a `:find` query is typed into the command palette.
+fn update_search_filter(state: &mut DumpState) {
+    let query = state
+        .input_buffer
+        .strip_prefix(":f ")
+        .or_else(|| state.input_buffer.strip_prefix(":find "))
+        .map(|query| query.to_string());
+    if let Some(query) = query {
+        state.filter = query;
+        state.scroll_index = 0;
+    }
+}
+
 /// The main function for the TUI. Will render the TUI and handle user input.
 pub fn handle(args: &DumpArgs, output_dir: &str) {
     // if no TUI is requested, just run the dump
@@ -44,23 +65,35 @@ pub fn handle(args: &DumpArgs, output_dir: &str) {
                     crossterm::event::Event::Key(key) => {
                         let mut state = DUMP_STATE.lock().unwrap();
 
+                        // in raw mode, Ctrl-C is a key press rather than a signal. stop indexing
+                        // and exit, keeping the slots which were already dumped
+                        if key.code == crossterm::event::KeyCode::Char('c') &&
+                            key.modifiers.contains(crossterm::event::KeyModifiers::CONTROL)
+                        {
+                            interrupt();
+                            state.view = TUIView::Killed;
+                            break
+                        }
+
                         // ignore key events if command palette is open
                         if state.view == TUIView::CommandPalette {
                             match key.code {
                                 // handle keys in command palette
                                 crossterm::event::KeyCode::Char(c) => {
                                     state.input_buffer.push(c);
+                                    update_search_filter(&mut state);
                                 }
 
                                 // handle backspace
                                 crossterm::event::KeyCode::Backspace => {
                                     state.input_buffer.pop();
+                                    update_search_filter(&mut state);
                                 }
 
                                 // enter command
                                 crossterm::event::KeyCode::Enter => {
-                                    state.filter = String::new();
-                                    let mut split = state.input_buffer.split(' ');
+                                    let input_buffer = state.input_buffer.clone();
+                                    let mut split = input_buffer.split(' ');
                                     let command = split.next().unwrap();
                                     let args = split.collect::<Vec<&str>>();
 
@@ -73,8 +106,22 @@ pub fn handle(args: &DumpArgs, output_dir: &str) {
                                             state.view = TUIView::Help;
                                         }
                                         ":f" | ":find" => {
+                                            state.filter = args.join(" ");
+                                            state.scroll_index = 0;
+                                            state.view = TUIView::Main;
+                                        }
+                                        ":o" | ":sort" => {
                                             if !args.is_empty() {
-                                                state.filter = args[0].to_string();
+                                                let column = args[0].to_lowercase();
+                                                if let Some(index) =
+                                                    STORAGE_COLUMNS.iter().position(|name| {
+                                                        name.to_lowercase().contains(&column)
+                                                    })
+                                                {
+                                                    state.sort_column = index;
+                                                    state.sort_descending =
+                                                        args.get(1) == Some(&"desc");
+                                                }
                                             }
                                             state.view = TUIView::Main;
                                         }
@@ -140,21 +187,14 @@ pub fn handle(args: &DumpArgs, output_dir: &str) {
                             // select transaction
                             crossterm::event::KeyCode::Right => {
                                 // increment decode_as_type_index on all selected transactions
-                                let scroll_index = state.scroll_index;
-                                let selection_size = state.selection_size;
-                                let mut storage_iter = state.storage.iter_mut().collect::<Vec<_>>();
-                                storage_iter.sort_by_key(|(slot, _)| *slot);
-
-                                for (i, (_, value)) in storage_iter.iter_mut().enumerate() {
-                                    if i >= scroll_index && i < scroll_index + selection_size {
-                                        // saturating increment
-                                        if value.decode_as_type_index + 1 >= DECODE_AS_TYPES.len() {
-                                            value.decode_as_type_index = 0;
-                                        } else {
-                                            value.decode_as_type_index += 1;
-                                        }
-                                    } else if i >= scroll_index + selection_size {
-                                        break
+                                for slot in selected_slots(&state) {
+                                    let value = state.storage.get_mut(&slot).unwrap();
+
+                                    // saturating increment
+                                    if value.decode_as_type_index + 1 >= DECODE_AS_TYPES.len() {
+                                        value.decode_as_type_index = 0;
+                                    } else {
+                                        value.decode_as_type_index += 1;
                                     }
                                 }
                             }
@@ -162,21 +202,14 @@ pub fn handle(args: &DumpArgs, output_dir: &str) {
                             // deselect transaction
                             crossterm::event::KeyCode::Left => {
                                 // decrement decode_as_type_index on all selected transactions
-                                let scroll_index = state.scroll_index;
-                                let selection_size = state.selection_size;
-                                let mut storage_iter = state.storage.iter_mut().collect::<Vec<_>>();
-                                storage_iter.sort_by_key(|(slot, _)| *slot);
-
-                                for (i, (_, value)) in storage_iter.iter_mut().enumerate() {
-                                    if i >= scroll_index && i < scroll_index + selection_size {
-                                        // saturating decrement
-                                        if value.decode_as_type_index == 0 {
-                                            value.decode_as_type_index = DECODE_AS_TYPES.len() - 1;
-                                        } else {
-                                            value.decode_as_type_index -= 1;
-                                        }
-                                    } else if i >= scroll_index + selection_size {
-                                        break
+                                for slot in selected_slots(&state) {
+                                    let value = state.storage.get_mut(&slot).unwrap();
+
+                                    // saturating decrement
+                                    if value.decode_as_type_index == 0 {
+                                        value.decode_as_type_index = DECODE_AS_TYPES.len() - 1;
+                                    } else {
+                                        value.decode_as_type_index -= 1;
                                     }
                                 }
                             }
@@ -195,6 +228,27 @@ pub fn handle(args: &DumpArgs, output_dir: &str) {
                                 }
                             }
 
+                            // search as you type on "/"
+                            crossterm::event::KeyCode::Char('/') => {
+                                state.input_buffer = String::from(":f ");
+                                state.view = TUIView::CommandPalette;
+                            }
+
+                            // sort by the next column
+                            crossterm::event::KeyCode::Char('s') => {
+                                state.sort_column = (state.sort_column + 1) % STORAGE_COLUMNS.len();
+                            }
+
+                            // reverse the sort order
+                            crossterm::event::KeyCode::Char('S') => {
+                                state.sort_descending = !state.sort_descending;
+                            }
+
+                            // toggle the raw value format
+                            crossterm::event::KeyCode::Char('v') => {
+                                state.value_format = state.value_format.next();
+                            }
+
                             // toggle command palette on ":"
                             crossterm::event::KeyCode::Char(':') => match state.view {
                                 TUIView::CommandPalette => {
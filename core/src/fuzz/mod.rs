@@ -0,0 +1,432 @@
+use std::{collections::BTreeMap, fs};
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use ethers::{
+    abi::{encode, ethabi::param_type::Reader, ParamType, Token},
+    providers::{ProviderError, RpcError},
+    types::{Address, I256, U256},
+};
+use heimdall_common::{
+    constants::ADDRESS_REGEX,
+    ether::rpc::rpc_request,
+    utils::{interrupt::is_interrupted, io::logging::Logger, strings::encode_hex},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::{
+    decompile::{
+        decompile,
+        out::abi::{ABIStructure, FunctionABI},
+        DecompilerArgsBuilder,
+    },
+    trace::frame::decode_revert_reason,
+};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Fuzz a contract's recovered functions with random inputs via eth_call",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall fuzz <TARGET> [OPTIONS]"
+)]
+pub struct FuzzArgs {
+    /// The address of the contract to fuzz.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to call the target through, whose state is forked for every call.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The path to an ABI to fuzz, such as the `abi.json` written by `decompile`. Defaults to
+    /// decompiling the target to recover its ABI.
+    #[clap(long = "abi", default_value = "", hide_default_value = true)]
+    pub abi: String,
+
+    /// The number of calls made to each function.
+    #[clap(long = "iterations", short, default_value = "16")]
+    pub iterations: usize,
+
+    /// The seed inputs and senders are generated from, so a run can be reproduced. Defaults to a
+    /// random seed, which is printed.
+    #[clap(long = "seed", default_value = "0", hide_default_value = true)]
+    pub seed: u64,
+
+    /// Whether to skip resolving function selectors when recovering the ABI.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+
+    /// The block number to fork state at, which requires an archive node. Defaults to the latest
+    /// block.
+    #[clap(long = "block", default_value = "0", hide_default_value = true)]
+    pub block: u64,
+}
+
+impl FuzzArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            abi: Some(String::new()),
+            iterations: Some(16),
+            seed: Some(0),
+            skip_resolving: Some(false),
+            block: Some(0),
+        }
+    }
+}
+
+/// The outcome of fuzzing a single function.
+#[derive(Debug, Clone, Serialize)]
+pub struct FunctionFuzzReport {
+    /// The function's selector, without a `0x` prefix.
+    pub selector: String,
+    pub signature: String,
+    pub state_mutability: String,
+    pub calls: usize,
+    pub successes: usize,
+    pub reverts: usize,
+    /// The reasons the function reverted with, and how many calls reverted with each. Reverts
+    /// without a decodable reason are counted under their raw revert data.
+    pub revert_reasons: BTreeMap<String, usize>,
+    /// The calldata and sender of a call which succeeded, if any.
+    pub succeeding_input: Option<(String, String)>,
+    /// The calldata and sender of a call which reverted, if any.
+    pub reverting_input: Option<(String, String)>,
+    /// Whether the function isn't a view, yet succeeded when called by arbitrary senders.
+    pub callable_by_anyone: bool,
+    /// The provider error which stopped this function from being fuzzed further, if any.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzResult {
+    /// The seed the run can be reproduced with.
+    pub seed: u64,
+    pub functions: Vec<FunctionFuzzReport>,
+}
+
+/// Generate a random value of the given type. Numbers are biased towards the edges of their
+/// range, and addresses towards the zero address, the target, and the sender, since these are the
+/// values which most often change a function's behavior.
+pub fn random_token(
+    param: &ParamType,
+    rng: &mut StdRng,
+    target: Address,
+    sender: Address,
+) -> Token {
+    match param {
+        ParamType::Address => Token::Address(match rng.gen_range(0..4) {
+            0 => Address::zero(),
+            1 => target,
+            2 => sender,
+            _ => Address::random_using(rng),
+        }),
+        ParamType::Bool => Token::Bool(rng.gen()),
+        ParamType::Uint(bits) => {
+            let max = match bits {
+                256 => U256::MAX,
+                bits => (U256::one() << *bits) - 1,
+            };
+            Token::Uint(match rng.gen_range(0..4) {
+                0 => U256::zero(),
+                1 => U256::one(),
+                2 => max,
+                _ => U256::from_big_endian(&rng.gen::<[u8; 32]>()) & max,
+            })
+        }
+        ParamType::Int(bits) => {
+            let max = match bits {
+                256 => I256::MAX,
+                bits => (I256::one() << (*bits - 1)) - 1,
+            };
+            let value = match rng.gen_range(0..5) {
+                0 => I256::zero(),
+                1 => I256::minus_one(),
+                2 => max,
+                3 => -max - 1,
+                _ => I256::from_raw(U256::from_big_endian(&rng.gen::<[u8; 32]>())) % max,
+            };
+            Token::Int(value.into_raw())
+        }
+        ParamType::FixedBytes(size) => {
+            Token::FixedBytes((0..*size).map(|_| rng.gen()).collect::<Vec<u8>>())
+        }
+        ParamType::Bytes => {
+            let length = rng.gen_range(0..=64);
+            Token::Bytes((0..length).map(|_| rng.gen()).collect::<Vec<u8>>())
+        }
+        ParamType::String => {
+            let length = rng.gen_range(0..=32);
+            Token::String(
+                (0..length)
+                    .map(|_| rng.sample(rand::distributions::Alphanumeric) as char)
+                    .collect(),
+            )
+        }
+        ParamType::Array(inner) => {
+            let length = rng.gen_range(0..=3);
+            Token::Array(
+                (0..length).map(|_| random_token(inner, rng, target, sender)).collect::<Vec<_>>(),
+            )
+        }
+        ParamType::FixedArray(inner, length) => Token::FixedArray(
+            (0..*length).map(|_| random_token(inner, rng, target, sender)).collect::<Vec<_>>(),
+        ),
+        ParamType::Tuple(params) => Token::Tuple(
+            params.iter().map(|param| random_token(param, rng, target, sender)).collect::<Vec<_>>(),
+        ),
+    }
+}
+
+/// Generate random calldata for the function, or `None` if any of its input types can't be
+/// parsed.
+pub fn random_calldata(
+    function: &FunctionABI,
+    rng: &mut StdRng,
+    target: Address,
+    sender: Address,
+) -> Option<String> {
+    let params = function
+        .inputs
+        .iter()
+        .map(|input| Reader::read(&input.type_).ok())
+        .collect::<Option<Vec<ParamType>>>()?;
+    let tokens =
+        params.iter().map(|param| random_token(param, rng, target, sender)).collect::<Vec<_>>();
+
+    Some(format!("0x{}{}", function.selector(), encode_hex(encode(&tokens))))
+}
+
+/// Read the functions from an ABI file, ignoring its errors and events.
+pub fn read_abi_functions(path: &str) -> Result<Vec<FunctionABI>, String> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("failed to read '{path}': {e}"))?;
+    let abi: Vec<Value> =
+        serde_json::from_str(&contents).map_err(|e| format!("'{path}' isn't an ABI: {e}"))?;
+
+    abi.into_iter()
+        .filter(|entry| entry["type"] == "function")
+        .map(|entry| {
+            serde_json::from_value::<FunctionABI>(entry)
+                .map_err(|e| format!("'{path}' has an invalid function: {e}"))
+        })
+        .collect()
+}
+
+/// The outcome of a single call.
+enum CallOutcome {
+    Succeeded,
+    /// The call reverted, with the decoded reason or raw revert data.
+    Reverted(String),
+}
+
+/// Call the target with the calldata, from the sender, against the state at the block.
+async fn call(
+    rpc_url: &str,
+    target: &str,
+    sender: Address,
+    calldata: &str,
+    block: &str,
+) -> Result<CallOutcome, String> {
+    match rpc_request(
+        rpc_url,
+        "eth_call",
+        json!([{ "from": format!("{sender:?}"), "to": target, "data": calldata }, block]),
+    )
+    .await
+    {
+        Ok(_) => Ok(CallOutcome::Succeeded),
+        Err(e) => {
+            // JSON-RPC errors are reverts, while anything else is a problem with the provider
+            let response = match e.downcast_ref::<ProviderError>() {
+                Some(error) => error.as_error_response(),
+                None => None,
+            };
+            match response {
+                Some(response) => {
+                    let data = response
+                        .as_revert_data()
+                        .map(|data| encode_hex(data.to_vec()))
+                        .unwrap_or_default();
+                    Ok(CallOutcome::Reverted(decode_revert_reason(&data).unwrap_or(
+                        match data.is_empty() {
+                            true => response.message.clone(),
+                            false => format!("0x{data}"),
+                        },
+                    )))
+                }
+                None => Err(e.to_string()),
+            }
+        }
+    }
+}
+
+/// The entrypoint for the fuzz module. This recovers the target's ABI by decompiling it, unless an
+/// ABI is given, and calls each function with random, type-aware inputs from random senders via
+/// `eth_call`, reporting which calls succeed and revert, and which state-changing functions
+/// anyone can call.
+pub async fn fuzz(args: FuzzArgs) -> Result<FuzzResult, Box<dyn std::error::Error>> {
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !ADDRESS_REGEX.is_match(&args.target)? {
+        logger.error("fuzzing requires the target to be a contract address.");
+        std::process::exit(1)
+    }
+    if args.rpc_url.is_empty() {
+        logger.error("fuzzing requires an RPC provider. Use `--rpc-url` or `heimdall config`.");
+        std::process::exit(1)
+    }
+    let target = args.target.parse::<Address>()?;
+    let block = match args.block {
+        0 => String::from("latest"),
+        block => format!("{block:#x}"),
+    };
+
+    // recover the ABI, unless one was given
+    let functions = match args.abi.is_empty() {
+        true => {
+            logger.info(&format!("recovering the ABI of '{}' .", args.target));
+            let result = decompile(
+                DecompilerArgsBuilder::new()
+                    .target(args.target.clone())
+                    .verbose(args.verbose.clone())
+                    .rpc_url(args.rpc_url.clone())
+                    .skip_resolving(args.skip_resolving)
+                    .block(args.block)
+                    .build()?,
+            )
+            .await?;
+            result
+                .abi
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|entry| match entry {
+                    ABIStructure::Function(function) if function.type_ == "function" => {
+                        Some(function)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        }
+        false => match read_abi_functions(&args.abi) {
+            Ok(functions) => functions,
+            Err(e) => {
+                logger.error(&e);
+                std::process::exit(1)
+            }
+        },
+    };
+
+    let seed = match args.seed {
+        0 => rand::thread_rng().gen(),
+        seed => seed,
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    logger.info(&format!(
+        "fuzzing {} functions with {} calls each, using seed {seed}.",
+        functions.len(),
+        args.iterations
+    ));
+
+    let mut reports = Vec::new();
+    for function in functions {
+        if is_interrupted() {
+            logger.warn(&format!(
+                "fuzzing interrupted, continuing with {} fuzzed functions.",
+                reports.len()
+            ));
+            break
+        }
+
+        let mut report = FunctionFuzzReport {
+            selector: function.selector(),
+            signature: function.signature(),
+            state_mutability: function.state_mutability.clone(),
+            calls: 0,
+            successes: 0,
+            reverts: 0,
+            revert_reasons: BTreeMap::new(),
+            succeeding_input: None,
+            reverting_input: None,
+            callable_by_anyone: false,
+            error: None,
+        };
+
+        for _ in 0..args.iterations {
+            let sender = Address::random_using(&mut rng);
+            let calldata = match random_calldata(&function, &mut rng, target, sender) {
+                Some(calldata) => calldata,
+                None => {
+                    logger.warn(&format!(
+                        "skipping '{}', since its input types can't be parsed.",
+                        report.signature
+                    ));
+                    break
+                }
+            };
+
+            let input = (calldata.clone(), format!("{sender:?}"));
+            match call(&args.rpc_url, &args.target, sender, &calldata, &block).await {
+                Ok(CallOutcome::Succeeded) => {
+                    report.successes += 1;
+                    report.succeeding_input.get_or_insert(input);
+                }
+                Ok(CallOutcome::Reverted(reason)) => {
+                    report.reverts += 1;
+                    *report.revert_reasons.entry(reason).or_insert(0) += 1;
+                    report.reverting_input.get_or_insert(input);
+                }
+                Err(e) => {
+                    // provider errors aren't the function's fault, so stop fuzzing it and move on
+                    logger.warn(&format!("failed to call '{}': {e}", report.signature));
+                    report.error = Some(e);
+                    break
+                }
+            }
+            report.calls += 1;
+        }
+
+        report.callable_by_anyone =
+            report.successes > 0 && !["view", "pure"].contains(&report.state_mutability.as_str());
+        reports.push(report);
+    }
+
+    for report in &reports {
+        logger.info(&format!(
+            "0x{} {}: {}/{} calls succeeded{}",
+            report.selector,
+            report.signature,
+            report.successes,
+            report.calls,
+            match report.revert_reasons.iter().max_by_key(|(_, count)| **count) {
+                Some((reason, _)) => format!(", most often reverting with '{reason}'"),
+                None => String::new(),
+            }
+        ));
+        if let Some(error) = &report.error {
+            logger.warn(&format!(
+                "'{}' was only called {} times, since the provider failed: {error}",
+                report.signature, report.calls
+            ));
+        }
+        if report.callable_by_anyone {
+            logger.warn(&format!(
+                "'{}' is {} and succeeded when called by arbitrary senders.",
+                report.signature, report.state_mutability
+            ));
+        }
+    }
+
+    Ok(FuzzResult { seed, functions: reports })
+}
@@ -1,6 +1,20 @@
+pub mod abi;
+pub mod batch;
+pub mod bench;
 pub mod cfg;
 pub mod decode;
 pub mod decompile;
+pub mod diff;
 pub mod disassemble;
+pub mod doctor;
 pub mod dump;
+pub mod fuzz;
+pub mod metadata;
+pub mod plugins;
+pub mod profile;
+pub mod report;
+pub mod sig;
+pub mod similar;
 pub mod snapshot;
+pub mod trace;
+pub mod update;
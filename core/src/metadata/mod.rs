@@ -0,0 +1,195 @@
+use std::fs;
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    constants::{ADDRESS_REGEX, BYTECODE_REGEX},
+    ether::{
+        dead_code::{find_dead_code, DeadCodeReport},
+        evm::core::vm::VM,
+        metadata::{decode_metadata, ContractMetadata},
+        rpc::get_code,
+        selectors::find_function_selectors,
+    },
+    utils::io::logging::Logger,
+};
+
+use crate::disassemble::{disassemble, DisassemblerArgs};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Decode the compiler metadata appended to EVM bytecode",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall metadata <TARGET> [OPTIONS]"
+)]
+pub struct MetadataArgs {
+    /// The target to decode metadata from, either a file, bytecode, contract address, or ENS
+    /// name.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching target bytecode.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// Whether to also symbolically execute the contract, listing the blocks which are never
+    /// reached and the functions which always revert. Such dead code can hide a backdoor behind
+    /// a check which appears to never pass.
+    #[clap(long = "dead-code")]
+    pub dead_code: bool,
+}
+
+impl MetadataArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            dead_code: Some(false),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MetadataResult {
+    /// The decoded metadata trailer, if one was found.
+    pub metadata: Option<ContractMetadata>,
+    /// The size of the runtime bytecode in bytes, including the metadata trailer.
+    pub runtime_size: usize,
+    /// The size of the runtime bytecode in bytes, excluding the metadata trailer.
+    pub stripped_size: usize,
+    /// The contract's dead code, if `--dead-code` was given.
+    pub dead_code: Option<DeadCodeReport>,
+}
+
+impl MetadataResult {
+    /// Whether the contract's optimizer settings can be inferred. These aren't encoded in the
+    /// bytecode itself, but are included in the metadata file which the metadata hash points to.
+    pub fn optimizer_inferable(&self) -> bool {
+        match &self.metadata {
+            Some(metadata) => {
+                metadata.ipfs.is_some() || metadata.bzzr0.is_some() || metadata.bzzr1.is_some()
+            }
+            None => false,
+        }
+    }
+}
+
+/// The entrypoint for the metadata module. This will decode the compiler metadata appended to the
+/// target's runtime bytecode, if present.
+pub async fn metadata(args: MetadataArgs) -> Result<MetadataResult, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    // parse the various formats that are accepted as targets
+    // i.e, file, bytecode, contract address
+    let contract_bytecode: String;
+    if ADDRESS_REGEX.is_match(&args.target)? {
+        // We are reading a contract address, so we need to fetch the bytecode from the RPC
+        // provider.
+        contract_bytecode = get_code(&args.target, &args.rpc_url).await?;
+    } else if BYTECODE_REGEX.is_match(&args.target)? {
+        contract_bytecode = args.target.clone().replacen("0x", "", 1);
+    } else {
+        // We are reading a file, so we need to read the bytecode from the file.
+        contract_bytecode = match fs::read_to_string(&args.target) {
+            Ok(contents) => {
+                let _contents = contents.replace('\n', "");
+                if BYTECODE_REGEX.is_match(&_contents)? && _contents.len() % 2 == 0 {
+                    _contents.replacen("0x", "", 1)
+                } else {
+                    logger
+                        .error(&format!("file '{}' doesn't contain valid bytecode.", &args.target));
+                    std::process::exit(1)
+                }
+            }
+            Err(_) => {
+                logger.error(&format!("failed to open file '{}' .", &args.target));
+                std::process::exit(1)
+            }
+        };
+    }
+
+    // find the code which can never run, or never succeed
+    let mut dead_code = None;
+    if args.dead_code {
+        let disassembled_bytecode = disassemble(DisassemblerArgs {
+            target: contract_bytecode.clone(),
+            verbose: args.verbose.clone(),
+            rpc_url: args.rpc_url.clone(),
+            decimal_counter: false,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
+        })
+        .await?;
+        let evm = VM::new(
+            contract_bytecode.clone(),
+            String::from("0x"),
+            String::from("0x6865696d64616c6c000000000061646472657373"),
+            String::from("0x6865696d64616c6c0000000000006f726967696e"),
+            String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+            0,
+            u128::MAX,
+        );
+        let selectors = find_function_selectors(&evm, &disassembled_bytecode);
+        dead_code = Some(find_dead_code(&evm, &selectors));
+    }
+
+    let metadata = decode_metadata(&contract_bytecode);
+    let runtime_size = contract_bytecode.len() / 2;
+    let result = MetadataResult {
+        stripped_size: runtime_size - metadata.as_ref().map(|m| m.size).unwrap_or(0),
+        runtime_size,
+        metadata,
+        dead_code,
+    };
+
+    match &result.metadata {
+        Some(metadata) => {
+            for (name, value) in metadata.fields() {
+                logger.success(&format!("{name}: {value}"));
+            }
+            logger.success(&format!(
+                "optimizer: {}",
+                if result.optimizer_inferable() {
+                    "inferable from the metadata file"
+                } else {
+                    "unknown, no metadata hash present"
+                }
+            ));
+        }
+        None => logger.warn("couldn't find any compiler metadata in the target bytecode."),
+    };
+    logger.success(&format!(
+        "runtime size: {} bytes ({} bytes without metadata)",
+        result.runtime_size, result.stripped_size
+    ));
+    if let Some(dead_code) = &result.dead_code {
+        match dead_code.warnings().as_slice() {
+            [] => logger.success("dead code: none found"),
+            warnings => warnings.iter().for_each(|warning| logger.warn(warning)),
+        }
+    }
+
+    Ok(result)
+}
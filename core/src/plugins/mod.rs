@@ -0,0 +1,272 @@
+use std::path::Path;
+
+use heimdall_common::{ether::metadata::ContractMetadata, utils::io::logging::Logger};
+use serde::{Deserialize, Serialize};
+use wasmi::{Config, Engine, Linker, Module, Store};
+
+use crate::decompile::util::Function;
+
+/// The fuel a WASM plugin may consume analyzing a single contract, roughly one unit per
+/// instruction, so a runaway plugin can't stall the analysis.
+const PLUGIN_FUEL: u64 = 10_000_000_000;
+
+/// The largest output a WASM plugin may return for a single contract, in bytes.
+const MAX_PLUGIN_OUTPUT: u64 = 16 * 1024 * 1024;
+
+/// The severity of a plugin's finding.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+/// A finding or annotation reported by an analyzer plugin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The name of the plugin which reported the finding. Set by heimdall, so plugins may omit it.
+    #[serde(default)]
+    pub plugin: String,
+    pub severity: Severity,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// The selector of the function the finding concerns, if any, without a `0x` prefix.
+    #[serde(default)]
+    pub selector: Option<String>,
+}
+
+/// A storage write within a recovered function, with the operation which computes the value.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct StorageWrite {
+    pub slot: String,
+    pub operation: String,
+}
+
+/// The intermediate representation of a function recovered by symbolic execution.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FunctionIR {
+    pub selector: String,
+    pub entry_point: u64,
+    /// The function's resolved signature, if it was resolved.
+    pub signature: Option<String>,
+    pub pure: bool,
+    pub view: bool,
+    pub payable: bool,
+    /// The function's recovered logic, one statement per line, before postprocessing.
+    pub logic: Vec<String>,
+    pub storage_writes: Vec<StorageWrite>,
+    /// The topics of the events the function emits.
+    pub events: Vec<String>,
+    /// The selectors of the custom errors the function reverts with.
+    pub errors: Vec<String>,
+}
+
+/// The input every analyzer plugin is given: the target, its bytecode and compiler metadata, and
+/// the intermediate representation of each function the decompiler recovered. WASM plugins are
+/// given it serialized as JSON.
+#[derive(Serialize, Debug, Clone)]
+pub struct AnalysisInput {
+    pub target: String,
+    pub bytecode: String,
+    pub metadata: Option<ContractMetadata>,
+    pub functions: Vec<FunctionIR>,
+}
+
+impl AnalysisInput {
+    /// Build the input from the functions recovered by the decompiler, ordered by selector.
+    pub fn new(
+        target: &str,
+        bytecode: &str,
+        metadata: Option<ContractMetadata>,
+        functions: &[Function],
+    ) -> Self {
+        let mut functions = functions
+            .iter()
+            .map(|function| {
+                let mut storage_writes = function
+                    .storage
+                    .iter()
+                    .map(|(slot, frame)| StorageWrite {
+                        slot: format!("{slot:#x}"),
+                        operation: frame.operations.solidify(),
+                    })
+                    .collect::<Vec<_>>();
+                storage_writes.sort_by(|a, b| a.slot.cmp(&b.slot));
+                let mut events = function
+                    .events
+                    .keys()
+                    .map(|topic| format!("{topic:#066x}"))
+                    .collect::<Vec<_>>();
+                events.sort();
+                let mut errors = function
+                    .errors
+                    .keys()
+                    .map(|error| format!("{error:#010x}"))
+                    .collect::<Vec<_>>();
+                errors.sort();
+
+                FunctionIR {
+                    selector: function.selector.clone(),
+                    entry_point: function.entry_point as u64,
+                    signature: function
+                        .resolved_function
+                        .as_ref()
+                        .map(|resolved| resolved.signature.clone()),
+                    pure: function.pure,
+                    view: function.view,
+                    payable: function.payable,
+                    logic: function.logic.clone(),
+                    storage_writes,
+                    events,
+                    errors,
+                }
+            })
+            .collect::<Vec<_>>();
+        functions.sort_by(|a, b| a.selector.cmp(&b.selector));
+
+        Self { target: target.to_string(), bytecode: bytecode.to_string(), metadata, functions }
+    }
+}
+
+/// An analyzer which runs over each decompiled contract, reporting findings such as
+/// vulnerabilities or annotations. Crates which depend on heimdall can implement this directly,
+/// while external detectors are loaded as [`WasmPlugin`]s.
+pub trait AnalyzerPlugin {
+    /// The plugin's name, which its findings are attributed to.
+    fn name(&self) -> String;
+
+    /// Analyze the contract, returning the plugin's findings.
+    fn analyze(&self, input: &AnalysisInput) -> Result<Vec<Finding>, String>;
+}
+
+/// An analyzer plugin compiled to WebAssembly. Plugins run sandboxed, without any imports, and
+/// must export:
+/// - `memory`, their linear memory.
+/// - `alloc(len: i32) -> i32`, which allocates `len` bytes for the input and returns a pointer.
+/// - `analyze(ptr: i32, len: i32) -> i64`, which analyzes the JSON-serialized [`AnalysisInput`] at
+///   `ptr`, returning the pointer and length of a JSON array of [`Finding`]s, packed as `ptr << 32
+///   | len`.
+pub struct WasmPlugin {
+    name: String,
+    engine: Engine,
+    module: Module,
+}
+
+impl WasmPlugin {
+    /// Load a WASM plugin from a `.wasm` file, named after the file.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("failed to read '{path}': {e}"))?;
+        let name = Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string());
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, &bytes[..])
+            .map_err(|e| format!("'{path}' isn't a valid WASM module: {e}"))?;
+
+        Ok(Self { name, engine, module })
+    }
+}
+
+impl AnalyzerPlugin for WasmPlugin {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn analyze(&self, input: &AnalysisInput) -> Result<Vec<Finding>, String> {
+        let input = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+
+        // each contract is analyzed in a fresh instance, so plugins can't leak state
+        let mut store = Store::new(&self.engine, ());
+        store.add_fuel(PLUGIN_FUEL).map_err(|e| e.to_string())?;
+        let instance = Linker::<()>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| format!("failed to instantiate: {e}"))?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| String::from("doesn't export 'memory'"))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| format!("doesn't export 'alloc': {e}"))?;
+        let analyze = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "analyze")
+            .map_err(|e| format!("doesn't export 'analyze': {e}"))?;
+
+        // copy the input into the plugin's memory, and read its findings back out
+        let pointer = alloc.call(&mut store, input.len() as i32).map_err(|e| e.to_string())?;
+        memory.write(&mut store, pointer as usize, &input).map_err(|e| e.to_string())?;
+        let output = analyze
+            .call(&mut store, (pointer, input.len() as i32))
+            .map_err(|e| format!("trapped: {e}"))? as u64;
+
+        // the pointer and length are controlled by the plugin, so check they lie within its
+        // memory before reading
+        let (start, len) = (output >> 32, output & 0xffffffff);
+        if len > MAX_PLUGIN_OUTPUT {
+            return Err(format!(
+                "returned {len} bytes of findings, more than the limit of {MAX_PLUGIN_OUTPUT}"
+            ))
+        }
+        let data = memory.data(&store);
+        let findings = start
+            .checked_add(len)
+            .filter(|end| *end <= data.len() as u64)
+            .map(|end| &data[start as usize..end as usize])
+            .ok_or_else(|| {
+                format!(
+                    "returned findings at {start:#x}..{:#x}, outside of its {} byte memory",
+                    start.saturating_add(len),
+                    data.len()
+                )
+            })?;
+        serde_json::from_slice(findings).map_err(|e| format!("returned invalid findings: {e}"))
+    }
+}
+
+/// Load the plugins at the given paths, exiting if any can't be loaded.
+pub fn load_plugins(paths: &[String]) -> Vec<Box<dyn AnalyzerPlugin>> {
+    // get a new logger
+    let logger = Logger::default();
+
+    paths
+        .iter()
+        .map(|path| match WasmPlugin::load(path) {
+            Ok(plugin) => Box::new(plugin) as Box<dyn AnalyzerPlugin>,
+            Err(e) => {
+                logger.error(&format!("failed to load plugin: {e} ."));
+                std::process::exit(1)
+            }
+        })
+        .collect()
+}
+
+/// Run each plugin over the input, returning their findings ordered by descending severity.
+/// Plugins which fail are reported and skipped, so a broken plugin doesn't abort the analysis.
+pub fn run_plugins(plugins: &[Box<dyn AnalyzerPlugin>], input: &AnalysisInput) -> Vec<Finding> {
+    // get a new logger
+    let logger = Logger::default();
+
+    let mut findings = Vec::new();
+    for plugin in plugins {
+        match plugin.analyze(input) {
+            Ok(plugin_findings) => findings.extend(
+                plugin_findings
+                    .into_iter()
+                    .map(|finding| Finding { plugin: plugin.name(), ..finding }),
+            ),
+            Err(e) => logger.warn(&format!("plugin '{}' failed: {e} .", plugin.name())),
+        }
+    }
+    findings.sort_by_key(|finding| std::cmp::Reverse(finding.severity));
+
+    findings
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use ethers::types::{H160, U256};
+use heimdall_common::ether::evm::core::vm::VM;
+
+/// A single executed instruction of a profiled transaction.
+#[derive(Debug, Clone)]
+pub struct ProfileStep {
+    pub pc: u128,
+    pub opcode: String,
+    /// The gas remaining before the instruction executed.
+    pub gas: u128,
+    /// The gas cost reported for the instruction, used when the cost can't be derived from the
+    /// gas remaining before the next instruction.
+    pub gas_cost: u128,
+    /// The call depth of the instruction, starting at 1.
+    pub depth: usize,
+    /// The topmost stack items before the instruction executed, top first.
+    pub stack: Vec<U256>,
+}
+
+/// The gas usage of a transaction, attributed to call frames, opcodes, and storage operations.
+#[derive(Debug, Clone, Default)]
+pub struct GasProfile {
+    /// The gas used by each stack of call frames and opcode, in the folded stack format used by
+    /// flamegraph tools, i.e. `frame;frame;OPCODE gas`.
+    pub folded: Vec<String>,
+    /// The gas used and execution count of each opcode, sorted by gas used.
+    pub opcodes: Vec<(String, u128, usize)>,
+    /// The gas used by each storage operation, as `(opcode, slot, gas)`.
+    pub storage: Vec<(String, String, u128)>,
+    /// The gas used directly within each call frame, sorted by gas used.
+    pub frames: Vec<(String, u128)>,
+    /// The total gas used by all profiled instructions.
+    pub total_gas: u128,
+}
+
+/// Compute the gas used by each step. Calls are charged the gas they consumed after excluding
+/// the gas used within the call itself, so gas isn't attributed twice.
+fn step_costs(steps: &[ProfileStep]) -> Vec<u128> {
+    let mut costs = vec![0u128; steps.len()];
+
+    // open calls, as (step index, gas used by the callee)
+    let mut open_calls: Vec<(usize, u128)> = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        // close any calls which have returned to their caller
+        while let Some((call_index, callee_gas)) = open_calls.last().copied() {
+            if step.depth > steps[call_index].depth {
+                break
+            }
+            open_calls.pop();
+            let cost = steps[call_index].gas.saturating_sub(step.gas).saturating_sub(callee_gas);
+            costs[call_index] = cost;
+            open_calls.iter_mut().for_each(|(_, callee_gas)| *callee_gas += cost);
+        }
+
+        match steps.get(i + 1) {
+            Some(next) if next.depth > step.depth => {
+                open_calls.push((i, 0));
+                continue
+            }
+            Some(next) if next.depth == step.depth => costs[i] = step.gas.saturating_sub(next.gas),
+            _ => costs[i] = step.gas_cost,
+        }
+        open_calls.iter_mut().for_each(|(_, callee_gas)| *callee_gas += costs[i]);
+    }
+
+    // calls which never returned are charged their reported cost
+    for (call_index, _) in open_calls {
+        costs[call_index] = steps[call_index].gas_cost;
+    }
+
+    costs
+}
+
+/// Attribute the gas used by the given steps to call frames, opcodes, and storage operations,
+/// where `root` names the transaction's top-level call frame.
+pub fn attribute_gas(steps: &[ProfileStep], root: &str) -> GasProfile {
+    let costs = step_costs(steps);
+
+    let mut folded: HashMap<String, u128> = HashMap::new();
+    let mut opcodes: HashMap<String, (u128, usize)> = HashMap::new();
+    let mut frames: HashMap<String, u128> = HashMap::new();
+    let mut storage = Vec::new();
+    let mut frame_stack = vec![root.to_string()];
+
+    for (i, step) in steps.iter().enumerate() {
+        // enter or leave call frames as the depth changes
+        if i > 0 {
+            let previous = &steps[i - 1];
+            if step.depth > previous.depth {
+                frame_stack.push(match previous.opcode.as_str() {
+                    "CREATE" | "CREATE2" => String::from("create"),
+                    _ => match previous.stack.get(1) {
+                        Some(address) => {
+                            let mut bytes = [0u8; 32];
+                            address.to_big_endian(&mut bytes);
+                            format!("{:?}", H160::from_slice(&bytes[12..]))
+                        }
+                        None => String::from("call"),
+                    },
+                });
+            }
+            frame_stack.truncate(step.depth.max(1));
+        }
+
+        let cost = costs[i];
+        let frame = frame_stack.join(";");
+        *folded.entry(format!("{};{}", frame, step.opcode)).or_default() += cost;
+        *frames.entry(frame).or_default() += cost;
+
+        let opcode = opcodes.entry(step.opcode.clone()).or_default();
+        opcode.0 += cost;
+        opcode.1 += 1;
+
+        if step.opcode == "SLOAD" || step.opcode == "SSTORE" {
+            storage.push((
+                step.opcode.clone(),
+                format!("{:#x}", step.stack.first().copied().unwrap_or_default()),
+                cost,
+            ));
+        }
+    }
+
+    let mut folded =
+        folded.into_iter().map(|(stack, gas)| format!("{stack} {gas}")).collect::<Vec<_>>();
+    folded.sort();
+    let mut opcodes =
+        opcodes.into_iter().map(|(opcode, (gas, count))| (opcode, gas, count)).collect::<Vec<_>>();
+    opcodes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut frames = frames.into_iter().collect::<Vec<_>>();
+    frames.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    GasProfile { folded, opcodes, storage, frames, total_gas: costs.iter().sum() }
+}
+
+/// Execute a call with heimdall's internal VM, recording each step. External calls aren't
+/// followed, so every step is at depth 1.
+pub fn steps_from_vm(evm: &mut VM) -> Vec<ProfileStep> {
+    let mut steps = Vec::new();
+
+    while evm.bytecode.len() >= evm.instruction as usize {
+        let gas_used = evm.gas_used;
+        let state = evm.step();
+        let instruction = state.last_instruction;
+
+        steps.push(ProfileStep {
+            pc: instruction.instruction - 1,
+            opcode: instruction
+                .opcode_details
+                .map(|details| details.name.to_string())
+                .unwrap_or_else(|| format!("0x{:02x}", instruction.opcode)),
+            gas: 0,
+            gas_cost: state.gas_used.saturating_sub(gas_used),
+            depth: 1,
+            stack: instruction.inputs.iter().take(2).copied().collect(),
+        });
+
+        if evm.exitcode != 255 || !evm.returndata.is_empty() {
+            break
+        }
+    }
+
+    // the internal VM doesn't track remaining gas precisely, so derive it from the gas used
+    let total_gas = steps.iter().map(|step| step.gas_cost).sum::<u128>();
+    let mut remaining = total_gas;
+    for step in steps.iter_mut() {
+        step.gas = remaining;
+        remaining -= step.gas_cost;
+    }
+
+    steps
+}
@@ -0,0 +1,211 @@
+pub mod attribution;
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use ethers::types::U256;
+use heimdall_common::{
+    constants::TRANSACTION_HASH_REGEX,
+    ether::{
+        evm::core::vm::VM,
+        rpc::{get_code, get_transaction, rpc_capabilities, rpc_request},
+        signatures::{score_signature, ResolveSelector, ResolvedFunction},
+    },
+    utils::{io::logging::Logger, strings::encode_hex},
+};
+
+use self::attribution::{attribute_gas, steps_from_vm, GasProfile, ProfileStep};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Profile the gas usage of a transaction",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall profile <TARGET> [OPTIONS]"
+)]
+pub struct ProfileArgs {
+    /// The transaction hash to profile.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching and replaying the transaction.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// Replay the transaction with heimdall's internal VM, rather than with the RPC provider's
+    /// `debug_traceTransaction`. External calls made by the transaction won't be profiled.
+    #[clap(long)]
+    pub internal: bool,
+
+    /// Whether to skip resolving the called function's selector.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+}
+
+impl ProfileArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            internal: Some(false),
+            skip_resolving: Some(true),
+        }
+    }
+}
+
+/// Replay the transaction with the RPC provider's `debug_traceTransaction`, converting its struct
+/// logs into [`ProfileStep`]s.
+async fn steps_from_trace(
+    transaction_hash: &str,
+    rpc_url: &str,
+) -> Result<Vec<ProfileStep>, Box<dyn std::error::Error>> {
+    let trace = rpc_request(
+        rpc_url,
+        "debug_traceTransaction",
+        serde_json::json!([transaction_hash, { "disableMemory": true, "disableStorage": true }]),
+    )
+    .await?;
+
+    let struct_logs = trace
+        .get("structLogs")
+        .and_then(|logs| logs.as_array())
+        .ok_or("the trace doesn't contain any struct logs")?;
+
+    Ok(struct_logs
+        .iter()
+        .map(|log| {
+            let number = |key: &str| log.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            let stack = log
+                .get("stack")
+                .and_then(|stack| stack.as_array())
+                .map(|stack| {
+                    stack
+                        .iter()
+                        .rev()
+                        .take(2)
+                        .filter_map(|item| item.as_str())
+                        .filter_map(|item| U256::from_str_radix(item, 16).ok())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            ProfileStep {
+                pc: number("pc") as u128,
+                opcode: log.get("op").and_then(|op| op.as_str()).unwrap_or("INVALID").to_string(),
+                gas: number("gas") as u128,
+                gas_cost: number("gasCost") as u128,
+                depth: number("depth") as usize,
+                stack,
+            }
+        })
+        .collect())
+}
+
+/// The entrypoint for the profile module. This will replay the target transaction, attributing
+/// its gas usage to call frames, opcodes, and storage operations.
+pub async fn profile(args: ProfileArgs) -> Result<GasProfile, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !TRANSACTION_HASH_REGEX.is_match(&args.target)? {
+        logger.error(&format!("target '{}' is not a valid transaction hash.", &args.target));
+        std::process::exit(1);
+    }
+
+    let transaction = get_transaction(&args.target, &args.rpc_url).await?;
+    let to = match transaction.to {
+        Some(to) => to,
+        None => {
+            logger.error("profiling contract creation transactions is not supported.");
+            std::process::exit(1);
+        }
+    };
+    let calldata = encode_hex(transaction.input.to_vec());
+
+    // name the root frame after the called function
+    let selector = calldata.get(0..8).unwrap_or_default().to_string();
+    let mut function = format!("0x{selector}");
+    if !args.skip_resolving && !selector.is_empty() {
+        if let Some(resolved) = ResolvedFunction::resolve(&selector).await {
+            if let Some(resolved) =
+                resolved.iter().max_by_key(|function| score_signature(&function.signature))
+            {
+                function = resolved.signature.clone();
+            }
+        }
+    }
+    let root = format!("{to:?}:{function}");
+
+    // replay the transaction, falling back to the internal VM if tracing isn't supported
+    let steps = match args.internal {
+        true => None,
+        false if !rpc_capabilities(&args.rpc_url).await.debug => {
+            logger.warn("the RPC provider doesn't support `debug_traceTransaction`.");
+            logger.warn(
+                "falling back to heimdall's internal VM, which won't profile external calls.",
+            );
+            None
+        }
+        false => {
+            match steps_from_trace(&args.target, &args.rpc_url).await {
+                Ok(steps) => Some(steps),
+                Err(e) => {
+                    logger.warn(&format!("failed to trace transaction: {e}"));
+                    logger.warn("falling back to heimdall's internal VM, which won't profile external calls.");
+                    None
+                }
+            }
+        }
+    };
+    let steps = match steps {
+        Some(steps) => steps,
+        None => {
+            let bytecode = get_code(&format!("{to:?}"), &args.rpc_url).await?;
+            let mut evm = VM::new(
+                bytecode,
+                calldata,
+                format!("{to:?}"),
+                format!("{:?}", transaction.from),
+                format!("{:?}", transaction.from),
+                transaction.value.try_into().unwrap_or(u128::MAX),
+                transaction.gas.try_into().unwrap_or(u128::MAX),
+            );
+            steps_from_vm(&mut evm)
+        }
+    };
+
+    let profile = attribute_gas(&steps, &root);
+
+    // display a summary of the profile
+    logger.info(&format!("profiled {} instructions using {} gas.", steps.len(), profile.total_gas));
+    logger.info(&format!("{:<16}{:>12}{:>10}", "Opcode", "Gas", "Count"));
+    for (opcode, gas, count) in profile.opcodes.iter().take(10) {
+        logger.info(&format!("{opcode:<16}{gas:>12}{count:>10}"));
+    }
+    if !profile.storage.is_empty() {
+        logger.info(&format!("{:<8}{:<68}{:>8}", "Storage", "Slot", "Gas"));
+        for (opcode, slot, gas) in profile.storage.iter() {
+            logger.info(&format!("{opcode:<8}{slot:<68}{gas:>8}"));
+        }
+    }
+
+    Ok(profile)
+}
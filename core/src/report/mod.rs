@@ -0,0 +1,675 @@
+pub mod schema;
+
+use std::collections::HashSet;
+
+use heimdall_common::{
+    ether::{
+        evm::core::types::{parse_function_parameters, to_json_value},
+        metamorphic::MetamorphicReport,
+        signatures::ResolvedFunction,
+    },
+    utils::{
+        io::{file::write_file, logging::Logger, template::render_template_file},
+        strings::encode_hex_reduced,
+    },
+};
+use serde_json::{json, Value};
+
+use crate::{
+    batch::BatchResult,
+    bench::BenchResult,
+    cfg::CFGResult,
+    decompile::{out::abi::ABIStructure, DecompileResult},
+    dump::DumpRow,
+    fuzz::FuzzResult,
+    metadata::MetadataResult,
+    profile::attribution::GasProfile,
+    sig::SigResult,
+    similar::SimilarResult,
+    snapshot::{
+        structures::snapshot::Snapshot,
+        util::manifest::{access_conditions, asset_movements, state_mutability, AssetMovement},
+        SnapshotResult,
+    },
+    trace::frame::CallFrame,
+};
+
+/// Sort a set of strings, so reports are deterministic.
+fn sorted(set: &HashSet<String>) -> Vec<String> {
+    let mut items = set.iter().cloned().collect::<Vec<String>>();
+    items.sort();
+    items
+}
+
+/// Build the context describing whether the contract's code may change between deployments.
+fn metamorphic_context(report: &MetamorphicReport) -> Value {
+    json!({
+        "is_metamorphic": report.is_metamorphic(),
+        "selfdestructs": report.selfdestructs,
+        "deploys_with_create2": report.deploys_with_create2,
+        "metamorphic_init_code": report.metamorphic_init_code,
+        "warnings": report.warnings(),
+    })
+}
+
+/// Build the context which `--template` reports on decompiled contracts are rendered with. The
+/// context contains the `target`, the recovered `functions`, `events`, and `errors` as ABI
+/// entries, the full `abi`, the decompiled `source`, the `metamorphic` deployment indicators, and
+/// the contract's `metadata` and `verification` report, if available, the `findings` reported by
+/// analyzer plugins, and the `audit` trail, if `--audit` was given.
+pub fn decompile_context(result: &DecompileResult, target: &str) -> Value {
+    let mut abi = Vec::new();
+    let (mut functions, mut events, mut errors) = (Vec::new(), Vec::new(), Vec::new());
+    for structure in result.abi.iter().flatten() {
+        let entry = match structure {
+            ABIStructure::Function(function) => {
+                functions.push(json!(function));
+                json!(function)
+            }
+            ABIStructure::Error(error) => {
+                errors.push(json!(error));
+                json!(error)
+            }
+            ABIStructure::Event(event) => {
+                events.push(json!(event));
+                json!(event)
+            }
+        };
+        abi.push(entry);
+    }
+
+    json!({
+        "target": target,
+        "source": result.source,
+        "abi": abi,
+        "functions": functions,
+        "events": events,
+        "errors": errors,
+        "metadata": result.metadata,
+        "metamorphic": metamorphic_context(&result.metamorphic),
+        "verification": result.verification.as_ref().map(|verification| json!({
+            "compile_error": verification.compile_error,
+            "total_selectors": verification.total_selectors,
+            "matched_selectors": verification.matched_selectors,
+            "total_events": verification.total_events,
+            "matched_events": verification.matched_events,
+            "cfg_similarity": verification.cfg_similarity,
+            "score": verification.score,
+        })),
+        "findings": result.findings,
+        "audit": result.audit,
+    })
+}
+
+/// The signatures of the events the snapshot's function emits, or `Event_<topic>()` for events
+/// which weren't resolved, sorted.
+fn snapshot_events(result: &SnapshotResult, snapshot: &Snapshot) -> Vec<String> {
+    let mut events = snapshot
+        .events
+        .keys()
+        .map(|topic| {
+            let key = encode_hex_reduced(*topic).replacen("0x", "", 1);
+            match result.resolved_events.get(&key) {
+                Some(event) => format!("{}({})", event.name, event.inputs.join(",")),
+                None => format!("Event_{}()", &key[0..8]),
+            }
+        })
+        .collect::<Vec<String>>();
+    events.sort();
+    events
+}
+
+/// Build the context which `--template` reports on snapshots are rendered with. The context
+/// contains the `target`, the contract's `metadata`, the `metamorphic` deployment indicators, the
+/// kind of `dispatcher` and its `dispatcher_anomalies`, the `honeypot` tricks found and the
+/// resulting score, whether the snapshot is `selectors_only`, and a list of `functions`, each
+/// with the same information as the snapshot CSV, including how many `signature_candidates`
+/// matched and the `signature_confidence` in the selected `signature`.
+pub fn snapshot_context(result: &SnapshotResult, target: &str) -> Value {
+    let functions = result
+        .snapshots
+        .iter()
+        .map(|snapshot| {
+            let mut arguments = snapshot.arguments.iter().collect::<Vec<_>>();
+            arguments.sort_by_key(|(index, _)| **index);
+
+            let mut errors = snapshot
+                .errors
+                .keys()
+                .map(|selector| {
+                    let key = encode_hex_reduced(*selector).replacen("0x", "", 1);
+                    match result.resolved_errors.get(&key) {
+                        Some(error) => format!("{}({})", error.name, error.inputs.join(",")),
+                        None => format!("Error_{}()", &key[0..8]),
+                    }
+                })
+                .collect::<Vec<String>>();
+            errors.sort();
+
+            json!({
+                "selector": snapshot.selector,
+                "name": snapshot.resolved_function.as_ref().map(|function| function.name.clone()),
+                "signature": snapshot.resolved_function.as_ref().map(|function| function.signature.clone()),
+                "signature_candidates": snapshot.signature_candidates,
+                "signature_confidence": snapshot.signature_confidence,
+                "entry_point": snapshot.entry_point,
+                "arguments": arguments
+                    .iter()
+                    .map(|(index, (_, potential_types))| json!({
+                        "index": index,
+                        "type": potential_types.first(),
+                        "potential_types": potential_types,
+                    }))
+                    .collect::<Vec<Value>>(),
+                "returns": snapshot.returns,
+                "pure": snapshot.pure,
+                "view": snapshot.view,
+                "payable": snapshot.payable,
+                "gas_used": {
+                    "min": snapshot.gas_used.min,
+                    "max": snapshot.gas_used.max,
+                    "avg": snapshot.gas_used.avg,
+                },
+                "branch_count": snapshot.branch_count,
+                "cyclomatic_complexity": snapshot.cyclomatic_complexity,
+                "max_nesting_depth": snapshot.max_nesting_depth,
+                "events": snapshot_events(result, snapshot),
+                "errors": errors,
+                "strings": sorted(&snapshot.strings),
+                "external_calls": snapshot.external_calls,
+                "tainted_calls": snapshot
+                    .tainted_calls
+                    .iter()
+                    .map(|call| json!({
+                        "call": call.call,
+                        "target": call.target,
+                        "value": call.value,
+                        "calldata": call.calldata,
+                    }))
+                    .collect::<Vec<Value>>(),
+                "ether_flow": {
+                    "summary": snapshot.ether_flow.summary(snapshot.payable),
+                    "forwards_to": snapshot.ether_flow.forwards_to,
+                    "sends_balance_to": snapshot.ether_flow.sends_balance_to,
+                    "sends_to": snapshot.ether_flow.sends_to,
+                },
+                "addresses": sorted(&snapshot.addresses),
+                "control_statements": sorted(&snapshot.control_statements),
+                "storage": sorted(&snapshot.storage),
+                "storage_writes": sorted(&snapshot.storage_writes),
+                "risk_flags": sorted(&snapshot.risk_flags),
+                "partially_analyzed": snapshot.partially_analyzed,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "metadata": result.metadata,
+        "metamorphic": metamorphic_context(&result.metamorphic),
+        "dispatcher": result.dispatcher.to_string(),
+        "dispatcher_anomalies": result.dispatcher_anomalies,
+        "honeypot": {
+            "score": result.honeypot.score,
+            "likelihood": result.honeypot.likelihood(),
+            "findings": result
+                .honeypot
+                .findings
+                .iter()
+                .map(|finding| json!({
+                    "heuristic": finding.heuristic.to_string(),
+                    "selector": finding.selector,
+                    "evidence": finding.evidence,
+                }))
+                .collect::<Vec<Value>>(),
+        },
+        "selectors_only": result.selectors_only,
+        "functions": functions,
+    })
+}
+
+/// Build the capability manifest which `snapshot --format manifest` writes, for ingestion by
+/// monitoring systems and wallets. The manifest contains the `target`, whether it's
+/// `metamorphic`, every `event` the contract emits, and its callable `functions`, each with its
+/// typed `inputs` and `outputs`, its `state_mutability`, the `access` conditions which restrict
+/// who can call it, the `events` it emits, the `assets` it can move, and its `risk_flags`.
+pub fn manifest_context(result: &SnapshotResult, target: &str) -> Value {
+    let mut all_events = HashSet::new();
+    let functions = result
+        .snapshots
+        .iter()
+        .map(|snapshot| {
+            let mut arguments = snapshot.arguments.iter().collect::<Vec<_>>();
+            arguments.sort_by_key(|(index, _)| **index);
+
+            let events = snapshot_events(result, snapshot);
+            all_events.extend(events.iter().cloned());
+
+            let conditions = access_conditions(snapshot);
+            json!({
+                "selector": snapshot.selector,
+                "name": snapshot.resolved_function.as_ref().map(|function| function.name.clone()),
+                "signature": snapshot.resolved_function.as_ref().map(|function| function.signature.clone()),
+                "inputs": arguments
+                    .iter()
+                    .map(|(_, (_, potential_types))| potential_types.first())
+                    .collect::<Vec<_>>(),
+                "outputs": snapshot.returns.iter().collect::<Vec<_>>(),
+                "state_mutability": state_mutability(snapshot),
+                "access": {
+                    "restricted": !conditions.is_empty(),
+                    "conditions": conditions,
+                },
+                "events": events,
+                "assets": asset_movements(snapshot)
+                    .into_iter()
+                    .map(|movement| match movement {
+                        AssetMovement::Ether => json!({ "asset": "ether", "method": null }),
+                        AssetMovement::Tokens { method } => {
+                            json!({ "asset": "tokens", "method": method })
+                        }
+                    })
+                    .collect::<Vec<Value>>(),
+                "risk_flags": sorted(&snapshot.risk_flags),
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "metamorphic": result.metamorphic.is_metamorphic(),
+        "events": sorted(&all_events),
+        "functions": functions,
+    })
+}
+
+/// Build the context which `--template` reports on decoded calldata are rendered with. The
+/// context contains the `target`, the calldata's `selector` and raw 32-byte `words`, if the
+/// calldata is known, and the candidate `functions`, most likely first, each with its `name`,
+/// `signature`, and typed `decoded_inputs`.
+pub fn decode_context(matches: &[ResolvedFunction], target: &str, calldata: Option<&str>) -> Value {
+    let functions = matches
+        .iter()
+        .map(|function| {
+            let kinds = parse_function_parameters(&function.signature).unwrap_or_default();
+            let decoded_inputs = function
+                .decoded_inputs
+                .iter()
+                .flatten()
+                .enumerate()
+                .map(|(index, token)| match kinds.get(index) {
+                    Some(kind) => to_json_value(token, kind),
+                    None => json!({ "type": null, "value": token.to_string() }),
+                })
+                .collect::<Vec<Value>>();
+
+            json!({
+                "name": function.name,
+                "signature": function.signature,
+                "inputs": function.inputs,
+                "decoded_inputs": decoded_inputs,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    // the selector is followed by the arguments, in 32-byte words
+    let calldata = calldata.map(|calldata| calldata.trim_start_matches("0x"));
+    let selector =
+        calldata.and_then(|calldata| calldata.get(..8)).map(|selector| format!("0x{selector}"));
+    let words = calldata
+        .and_then(|calldata| calldata.get(8..))
+        .unwrap_or_default()
+        .as_bytes()
+        .chunks(64)
+        .map(|word| format!("0x{}", String::from_utf8_lossy(word)))
+        .collect::<Vec<String>>();
+
+    json!({
+        "target": target,
+        "selector": selector,
+        "words": words,
+        "functions": functions,
+    })
+}
+
+/// Build the context which `--format json` output of disassembled bytecode is written with. The
+/// context contains the `target` and the `instructions`, each with its `pc`, `opcode`, `operand`,
+/// the `section` of creation code it belongs to, if the bytecode was split, and its stack
+/// `annotation`, if the bytecode was disassembled with `--annotate`.
+pub fn disassemble_context(assembly: &str, target: &str) -> Value {
+    let mut section: Option<String> = None;
+    let mut instructions = Vec::new();
+    for line in assembly.lines() {
+        if let Some(header) = line.strip_prefix("; ") {
+            section = Some(header.split(" (").next().unwrap_or(header).to_string());
+            continue
+        }
+
+        let (line, annotation) = match line.split_once(" // ") {
+            Some((line, annotation)) => (line, Some(annotation)),
+            None => (line, None),
+        };
+        let mut parts = line.split_whitespace();
+        let (pc, opcode) = match (parts.next(), parts.next()) {
+            (Some(pc), Some(opcode)) => (pc, opcode),
+            _ => continue,
+        };
+        instructions.push(json!({
+            "pc": pc,
+            "opcode": opcode,
+            "operand": parts.next(),
+            "section": section,
+            "annotation": annotation,
+        }));
+    }
+
+    json!({
+        "target": target,
+        "instructions": instructions,
+    })
+}
+
+/// Build the context which `--format json` output of a control flow graph is written with. The
+/// context contains the `target`, the graph's `nodes`, each with its `id` and the assembly of its
+/// basic block as `code` and the `source_lines` of `decompiled.sol` it was lifted to, if linked,
+/// and its `edges`, each with the `condition` under which it's taken, if it's a conditional jump.
+pub fn cfg_context(cfg: &CFGResult, target: &str) -> Value {
+    let graph = &cfg.graph;
+    let nodes = graph
+        .node_indices()
+        .map(|index| {
+            json!({
+                "id": index.index(),
+                "code": graph[index],
+                "source_lines": cfg
+                    .source_lines
+                    .get(&index.index())
+                    .map(|ranges| {
+                        ranges
+                            .iter()
+                            .map(|(start, end)| json!({ "start": start, "end": end }))
+                            .collect::<Vec<Value>>()
+                    })
+                    .unwrap_or_default(),
+            })
+        })
+        .collect::<Vec<Value>>();
+    let edges = graph
+        .raw_edges()
+        .iter()
+        .map(|edge| {
+            json!({
+                "from": edge.source().index(),
+                "to": edge.target().index(),
+                "condition": match edge.weight.as_str() {
+                    "" => None,
+                    condition => Some(condition),
+                },
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "nodes": nodes,
+        "edges": edges,
+    })
+}
+
+/// Build the context which `--format json` output of a storage dump is written with. The
+/// context contains the `target` and its storage `slots`, with the same information as the dump
+/// CSV. Slots whose type wasn't inferred have a null `type_confidence`.
+pub fn dump_context(rows: &[DumpRow], target: &str) -> Value {
+    let slots = rows
+        .iter()
+        .map(|row| {
+            json!({
+                "slot": row.slot,
+                "alias": match row.alias.as_str() {
+                    "" => None,
+                    alias => Some(alias),
+                },
+                "decoded_type": row.decoded_type,
+                "value": row.value,
+                "type_confidence": row.type_confidence.parse::<f64>().ok(),
+                "last_modified": row.last_modified,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "slots": slots,
+    })
+}
+
+/// Build the context which `--format json` output of the sig module is written with. Hashed
+/// signatures have a `signature`, `selector`, and `topic`, while resolved selectors have the
+/// matching `functions`, `errors`, and `events`.
+pub fn sig_context(result: &SigResult, target: &str) -> Value {
+    match result {
+        SigResult::Hashed { signature, selector, topic } => json!({
+            "target": target,
+            "signature": signature,
+            "selector": selector,
+            "topic": topic,
+            "functions": [],
+            "errors": [],
+            "events": [],
+        }),
+        SigResult::Resolved { functions, errors, events } => json!({
+            "target": target,
+            "signature": null,
+            "selector": null,
+            "topic": null,
+            "functions": functions,
+            "errors": errors,
+            "events": events,
+        }),
+    }
+}
+
+/// Build the context which `--format json` output of a similarity search is written with. The
+/// context contains the `target`, the `name` it's indexed under, the size of its `fingerprint`,
+/// and the indexed contracts which `matches` it, most similar first.
+pub fn similar_context(result: &SimilarResult, target: &str) -> Value {
+    let matches = result
+        .matches
+        .iter()
+        .map(|contract| {
+            json!({
+                "name": contract.name,
+                "score": contract.similarity.score,
+                "blocks": contract.similarity.blocks,
+                "selectors": contract.similarity.selectors,
+                "relation": contract.relation(),
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "name": result.name,
+        "fingerprint": {
+            "blocks": result.fingerprint.blocks.len(),
+            "selectors": result.fingerprint.selectors,
+        },
+        "matches": matches,
+    })
+}
+
+/// Build the context which `--format json` output of decoded metadata is written with. The
+/// context contains the `target`, the decoded `metadata` trailer, if any, the size of the
+/// runtime code with and without it, and its `dead_code`, if `--dead-code` was given.
+pub fn metadata_context(result: &MetadataResult, target: &str) -> Value {
+    json!({
+        "target": target,
+        "metadata": result.metadata,
+        "runtime_size": result.runtime_size,
+        "stripped_size": result.stripped_size,
+        "dead_code": result.dead_code,
+    })
+}
+
+/// Build the context which `--format json` output of a gas profile is written with. The context
+/// contains the `target` transaction, the `total_gas`, and the gas used by each opcode, storage
+/// operation, and call frame, along with the `folded` stacks.
+pub fn profile_context(profile: &GasProfile, target: &str) -> Value {
+    json!({
+        "target": target,
+        "total_gas": profile.total_gas,
+        "opcodes": profile
+            .opcodes
+            .iter()
+            .map(|(opcode, gas, count)| json!({ "opcode": opcode, "gas": gas, "count": count }))
+            .collect::<Vec<Value>>(),
+        "storage": profile
+            .storage
+            .iter()
+            .map(|(opcode, slot, gas)| json!({ "opcode": opcode, "slot": slot, "gas": gas }))
+            .collect::<Vec<Value>>(),
+        "frames": profile
+            .frames
+            .iter()
+            .map(|(frame, gas)| json!({ "frame": frame, "gas": gas }))
+            .collect::<Vec<Value>>(),
+        "folded": profile.folded,
+    })
+}
+
+/// Build the context which `--format json` output of a transaction trace is written with. The
+/// context contains the `target` transaction and its root call frame as `trace`.
+pub fn trace_context(call_frame: &CallFrame, target: &str) -> Value {
+    json!({
+        "target": target,
+        "trace": call_frame,
+    })
+}
+
+/// Build the context which `--format json` output of a benchmark is written with. The context
+/// contains the `results`, with the same information as the benchmark CSV.
+pub fn bench_context(results: &[BenchResult]) -> Value {
+    let results = results
+        .iter()
+        .map(|result| {
+            json!({
+                "target": result.target,
+                "module": result.module,
+                "bytecode_size": result.bytecode_size,
+                "mean_ms": result.mean_ms,
+                "min_ms": result.min_ms,
+                "max_ms": result.max_ms,
+                "peak_memory_kb": result.peak_memory_kb,
+                "selectors": result.selectors,
+                "functions": result.functions,
+                "coverage": result.coverage(),
+                "error": result.error,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({ "results": results })
+}
+
+/// Build the context which `--format json` output of a batch run is written with. The context
+/// contains the `results` for each target, in the order they were given.
+pub fn batch_context(results: &[BatchResult]) -> Value {
+    let results = results
+        .iter()
+        .map(|result| {
+            json!({
+                "target": result.target,
+                "chain": result.chain,
+                "bytecode_size": result.bytecode_size,
+                "duplicate_of": result.duplicate_of,
+                "selectors": result.selectors,
+                "functions": result.functions,
+                "warnings": result.warnings,
+                "duration_ms": result.duration_ms,
+                "error": result.error,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({ "results": results })
+}
+
+/// Build the context which `--format json` output of a fuzzing run is written with. The context
+/// contains the `target`, the `seed` the run can be reproduced with, and the outcome of fuzzing
+/// each of the target's `functions`.
+pub fn fuzz_context(result: &FuzzResult, target: &str) -> Value {
+    let functions = result
+        .functions
+        .iter()
+        .map(|function| {
+            let input = |input: &Option<(String, String)>| {
+                input
+                    .as_ref()
+                    .map(|(calldata, sender)| json!({ "calldata": calldata, "sender": sender }))
+            };
+            json!({
+                "selector": function.selector,
+                "signature": function.signature,
+                "state_mutability": function.state_mutability,
+                "calls": function.calls,
+                "successes": function.successes,
+                "reverts": function.reverts,
+                "revert_reasons": function.revert_reasons,
+                "succeeding_input": input(&function.succeeding_input),
+                "reverting_input": input(&function.reverting_input),
+                "callable_by_anyone": function.callable_by_anyone,
+                "error": function.error,
+            })
+        })
+        .collect::<Vec<Value>>();
+
+    json!({
+        "target": target,
+        "seed": result.seed,
+        "functions": functions,
+    })
+}
+
+/// The path a report rendered from the given template is written to within the output
+/// directory. The report's extension is taken from the template's filename, after stripping a
+/// trailing `.hbs` or `.tera`, so `report.md.hbs` produces `report.md`.
+///
+/// ```
+/// use heimdall_core::report::report_path;
+///
+/// assert_eq!(report_path("templates/audit.md.hbs", "output/local"), "output/local/report.md");
+/// assert_eq!(report_path("audit.html", "output/local"), "output/local/report.html");
+/// assert_eq!(report_path("audit", "output/local"), "output/local/report.txt");
+/// ```
+pub fn report_path(template_path: &str, output_dir: &str) -> String {
+    let filename = template_path.rsplit('/').next().unwrap_or(template_path);
+    let filename = filename
+        .strip_suffix(".hbs")
+        .or_else(|| filename.strip_suffix(".tera"))
+        .unwrap_or(filename);
+    let extension = match filename.rsplit_once('.') {
+        Some((_, extension)) if !extension.is_empty() => extension,
+        _ => "txt",
+    };
+
+    format!("{output_dir}/report.{extension}")
+}
+
+/// Render the template at the given path with the context, and write the report to the output
+/// directory.
+pub fn write_report(template_path: &str, context: &Value, output_dir: &str) {
+    // get a new logger
+    let logger = Logger::default();
+
+    let report = match render_template_file(template_path, context) {
+        Ok(report) => report,
+        Err(e) => {
+            logger.error(&format!("failed to render template '{template_path}': {e}"));
+            std::process::exit(1)
+        }
+    };
+
+    let path = report_path(template_path, output_dir);
+    write_file(&path, &report);
+    logger.info(&format!("wrote report to '{path}' ."));
+}
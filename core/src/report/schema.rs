@@ -0,0 +1,71 @@
+use heimdall_common::utils::io::{file::write_file, logging::Logger};
+use serde_json::{json, Value};
+
+/// The version of the JSON schemas which `--format json` output follows. This is only bumped
+/// when a change would break existing consumers, such as removing or retyping a field.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The published JSON schema of each subcommand's `--format json` output, which can also be
+/// found in `core/schemas`.
+pub static SCHEMAS: &[(&str, &str)] = &[
+    ("batch", include_str!("../../schemas/batch.v1.json")),
+    ("bench", include_str!("../../schemas/bench.v1.json")),
+    ("cfg", include_str!("../../schemas/cfg.v1.json")),
+    ("decode", include_str!("../../schemas/decode.v1.json")),
+    ("decompile", include_str!("../../schemas/decompile.v1.json")),
+    ("disassemble", include_str!("../../schemas/disassemble.v1.json")),
+    ("dump", include_str!("../../schemas/dump.v1.json")),
+    ("fuzz", include_str!("../../schemas/fuzz.v1.json")),
+    ("manifest", include_str!("../../schemas/manifest.v1.json")),
+    ("metadata", include_str!("../../schemas/metadata.v1.json")),
+    ("profile", include_str!("../../schemas/profile.v1.json")),
+    ("sig", include_str!("../../schemas/sig.v1.json")),
+    ("similar", include_str!("../../schemas/similar.v1.json")),
+    ("snapshot", include_str!("../../schemas/snapshot.v1.json")),
+    ("trace", include_str!("../../schemas/trace.v1.json")),
+];
+
+/// The identifier of the subcommand's JSON schema, which its output is tagged with.
+///
+/// ```
+/// use heimdall_core::report::schema::schema_id;
+///
+/// assert_eq!(schema_id("decompile"), "heimdall/decompile/v1");
+/// ```
+pub fn schema_id(subcommand: &str) -> String {
+    format!("heimdall/{subcommand}/v{SCHEMA_VERSION}")
+}
+
+/// The published JSON schema of the subcommand's output, if it supports `--format json`.
+pub fn schema(subcommand: &str) -> Option<&'static str> {
+    SCHEMAS.iter().find(|(name, _)| *name == subcommand).map(|(_, schema)| *schema)
+}
+
+/// Tag the context with the identifier of the subcommand's schema, under the `schema` key.
+pub fn with_schema(subcommand: &str, context: Value) -> Value {
+    match context {
+        Value::Object(mut context) => {
+            context.insert(String::from("schema"), json!(schema_id(subcommand)));
+            Value::Object(context)
+        }
+        context => context,
+    }
+}
+
+/// Write the context to the given path as the subcommand's `--format json` output, tagged with
+/// its schema.
+pub fn write_json(subcommand: &str, context: Value, path: &str) {
+    // get a new logger
+    let logger = Logger::default();
+
+    let output = match serde_json::to_string_pretty(&with_schema(subcommand, context)) {
+        Ok(output) => output,
+        Err(e) => {
+            logger.error(&format!("failed to serialize {subcommand} output: {e}"));
+            std::process::exit(1)
+        }
+    };
+
+    write_file(path, &output);
+    logger.info(&format!("wrote {subcommand} output to '{path}' ."));
+}
@@ -0,0 +1,181 @@
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use ethers::utils::keccak256;
+use heimdall_common::{
+    ether::{
+        evm::core::types::parse_function_parameters,
+        signatures::{ResolveSelector, ResolvedError, ResolvedFunction, ResolvedLog},
+    },
+    utils::{io::logging::Logger, strings::encode_hex},
+};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Compute selectors for signatures, or resolve selectors to signatures",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall sig <TARGET> [OPTIONS]"
+)]
+pub struct SigArgs {
+    /// The target to hash or resolve, either a human-readable signature such as
+    /// `transfer(address,uint256)`, a 4-byte selector, or a 32-byte event topic.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+}
+
+impl SigArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+        }
+    }
+}
+
+/// The result of the sig module, either the hashes of a human-readable signature, or the
+/// signatures which were resolved from a hash.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SigResult {
+    Hashed { signature: String, selector: String, topic: String },
+    Resolved { functions: Vec<String>, errors: Vec<String>, events: Vec<String> },
+}
+
+/// Normalize a human-readable signature into its canonical form, removing any keywords,
+/// parameter names, data locations, and whitespace.
+///
+/// ```
+/// use heimdall_core::sig::canonicalize_signature;
+///
+/// let signature = canonicalize_signature("function transfer(address to, uint256 amount)");
+/// assert_eq!(signature, Some(String::from("transfer(address,uint256)")));
+/// ```
+pub fn canonicalize_signature(signature: &str) -> Option<String> {
+    let signature = signature.trim();
+    let signature = ["function ", "event ", "error "]
+        .iter()
+        .find_map(|keyword| signature.strip_prefix(keyword))
+        .unwrap_or(signature)
+        .trim();
+
+    let (name, _) = signature.split_once('(')?;
+    if name.is_empty() || name.contains(char::is_whitespace) || !signature.ends_with(')') {
+        return None
+    }
+
+    // strip everything following a type, up until the next parameter or the end of a tuple
+    let mut canonical = String::new();
+    let mut chars = signature.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            if !canonical.ends_with(',') && !canonical.ends_with('(') {
+                while let Some(next) = chars.peek() {
+                    if *next == ',' || *next == ')' {
+                        break
+                    }
+                    chars.next();
+                }
+            }
+            continue
+        }
+        canonical.push(c);
+    }
+
+    // make sure the parameters are valid types
+    if !canonical.ends_with("()") && parse_function_parameters(&canonical).is_none() {
+        return None
+    }
+
+    Some(canonical)
+}
+
+/// The entrypoint for the sig module. This will either compute the selector and topic of a
+/// human-readable signature, or attempt to resolve the given selector or topic.
+pub async fn sig(args: SigArgs) -> Result<SigResult, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    let target = args.target.trim();
+
+    // the target is a human-readable signature, so we hash it
+    if target.contains('(') {
+        let signature = match canonicalize_signature(target) {
+            Some(signature) => signature,
+            None => {
+                logger.error(&format!("failed to parse signature '{target}' ."));
+                std::process::exit(1)
+            }
+        };
+
+        let hash = encode_hex(keccak256(signature.as_bytes()).to_vec());
+        let selector = format!("0x{}", &hash[0..8]);
+        let topic = format!("0x{hash}");
+
+        logger.success(&format!("signature: {signature}"));
+        logger.success(&format!("selector:  {selector}"));
+        logger.success(&format!("topic:     {topic}"));
+
+        return Ok(SigResult::Hashed { signature, selector, topic })
+    }
+
+    // the target is a hash, so we attempt to resolve it
+    let hash = target.replacen("0x", "", 1).to_lowercase();
+    if !hash.chars().all(|c| c.is_ascii_hexdigit()) || (hash.len() != 8 && hash.len() != 64) {
+        logger.error(&format!(
+            "'{target}' is not a valid signature, 4-byte selector, or 32-byte event topic."
+        ));
+        std::process::exit(1)
+    }
+
+    let mut functions = Vec::new();
+    let mut errors = Vec::new();
+    let mut events = Vec::new();
+    if hash.len() == 8 {
+        logger.debug(&format!("resolving function and error selector 0x{hash} ."));
+
+        if let Some(resolved) = ResolvedFunction::resolve(&hash).await {
+            functions = resolved.into_iter().map(|function| function.signature).collect();
+        }
+        if let Some(resolved) = ResolvedError::resolve(&hash).await {
+            errors = resolved.into_iter().map(|error| error.signature).collect();
+        }
+    } else {
+        logger.debug(&format!("resolving event topic 0x{hash} ."));
+
+        if let Some(resolved) = ResolvedLog::resolve(&hash).await {
+            events = resolved.into_iter().map(|event| event.signature).collect();
+        }
+    }
+
+    if functions.is_empty() && errors.is_empty() && events.is_empty() {
+        logger.warn(&format!("couldn't resolve any signatures for 0x{hash} ."));
+    }
+    for signature in &functions {
+        logger.success(&format!("function: {signature}"));
+    }
+    for signature in &errors {
+        logger.success(&format!("error:    {signature}"));
+    }
+    for signature in &events {
+        logger.success(&format!("event:    {signature}"));
+    }
+
+    Ok(SigResult::Resolved { functions, errors, events })
+}
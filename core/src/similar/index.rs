@@ -0,0 +1,70 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use ethers::utils::keccak256;
+use heimdall_common::{
+    constants::ADDRESS_REGEX,
+    ether::fingerprint::Fingerprint,
+    utils::{
+        io::file::write_file,
+        strings::{decode_hex, encode_hex},
+    },
+};
+
+#[allow(deprecated)]
+/// The path of the similarity index, at `$HOME/.bifrost/fingerprints.json`.
+pub fn default_index_path() -> Option<PathBuf> {
+    std::env::home_dir().map(|home| home.join(".bifrost").join("fingerprints.json"))
+}
+
+/// Resolve the path of the similarity index, which is the default index if none is given.
+pub fn index_path(index: &str) -> Result<String, String> {
+    match index.is_empty() {
+        true => default_index_path()
+            .map(|path| path.to_string_lossy().to_string())
+            .ok_or_else(|| String::from("couldn't resolve the $HOME directory")),
+        false => Ok(index.to_string()),
+    }
+}
+
+/// Read the similarity index, which maps the name of each indexed contract to its fingerprint.
+/// A missing or malformed index is treated as empty.
+pub fn read_index(path: &str) -> BTreeMap<String, Fingerprint> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+/// Add the fingerprints to the similarity index, replacing those of contracts with the same name.
+pub fn add_to_index(
+    path: &str,
+    fingerprints: Vec<(String, Fingerprint)>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut index = read_index(path);
+    index.extend(fingerprints);
+
+    write_file(path, &serde_json::to_string(&index)?);
+    Ok(())
+}
+
+/// The name a target is indexed under: its lowercased address, or otherwise the hash of its
+/// bytecode, since bytecode and file targets have no lasting name.
+///
+/// ```
+/// use heimdall_core::similar::index::index_name;
+///
+/// assert_eq!(
+///     index_name("0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2", "6001"),
+///     "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"
+/// );
+/// assert!(index_name("6001", "6001").starts_with("bytecode:"));
+/// ```
+pub fn index_name(target: &str, bytecode: &str) -> String {
+    match ADDRESS_REGEX.is_match(target).unwrap_or(false) {
+        true => target.to_lowercase(),
+        false => {
+            let hash = keccak256(decode_hex(bytecode.trim_start_matches("0x")).unwrap_or_default());
+            format!("bytecode:{}", &encode_hex(hash.to_vec())[..16])
+        }
+    }
+}
@@ -0,0 +1,179 @@
+pub mod index;
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    ether::fingerprint::{Fingerprint, Similarity},
+    utils::io::logging::Logger,
+};
+
+use crate::bench::{dispatcher_selectors, get_target_bytecode};
+
+use self::index::{add_to_index, index_name, index_path, read_index};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Find contracts similar to the target within a local index of fingerprints",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall similar <TARGET> [OPTIONS]"
+)]
+pub struct SimilarArgs {
+    /// The target to search for, either a file, bytecode, or contract address.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for fetching target bytecode.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// The path of the similarity index to search. Defaults to `$HOME/.bifrost/fingerprints.json`,
+    /// which `heimdall batch --add-to-index` also adds to.
+    #[clap(long, default_value = "", hide_default_value = true)]
+    pub index: String,
+
+    /// Whether to add the target to the index once it's been searched.
+    #[clap(long)]
+    pub add: bool,
+
+    /// The minimum similarity, from 0 to 1, of the contracts to report.
+    #[clap(long, default_value = "0.5")]
+    pub threshold: f64,
+
+    /// The maximum number of similar contracts to report.
+    #[clap(long, default_value = "10")]
+    pub limit: usize,
+}
+
+impl SimilarArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            index: Some(String::new()),
+            add: Some(false),
+            threshold: Some(0.5),
+            limit: Some(10),
+        }
+    }
+}
+
+/// An indexed contract which is similar to the target.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarContract {
+    /// The name the contract is indexed under, which is its address, if it has one.
+    pub name: String,
+    pub similarity: Similarity,
+}
+
+impl SimilarContract {
+    /// How the contract likely relates to the target, from its similarity.
+    pub fn relation(&self) -> &'static str {
+        match self.similarity.score {
+            score if score >= 1.0 => "same code",
+            score if score >= 0.8 => "likely fork",
+            _ => "variant",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimilarResult {
+    /// The name the target is indexed under.
+    pub name: String,
+    pub fingerprint: Fingerprint,
+    /// The indexed contracts which are at least as similar as the threshold, most similar first.
+    pub matches: Vec<SimilarContract>,
+}
+
+/// The entrypoint for the similar module. This fingerprints the target, and compares it against
+/// every contract in the similarity index, reporting those which are likely forks or variants of
+/// it, such as the other deployments of a scam.
+pub async fn similar(args: SimilarArgs) -> Result<SimilarResult, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !(0.0..=1.0).contains(&args.threshold) {
+        logger.error("argument '--threshold' must be between 0 and 1.");
+        std::process::exit(1)
+    }
+    let path = match index_path(&args.index) {
+        Ok(path) => path,
+        Err(e) => {
+            logger.error(&format!("{e} ."));
+            std::process::exit(1)
+        }
+    };
+
+    let bytecode = match get_target_bytecode(&args.target, &args.rpc_url).await {
+        Ok(bytecode) => bytecode,
+        Err(e) => {
+            logger.error(&format!("{e} ."));
+            std::process::exit(1)
+        }
+    };
+    let name = index_name(&args.target, &bytecode);
+    let fingerprint = Fingerprint::new(&bytecode, dispatcher_selectors(&bytecode).await?);
+    logger.debug(&format!(
+        "fingerprinted '{name}' with {} blocks and {} selectors.",
+        fingerprint.blocks.len(),
+        fingerprint.selectors.len()
+    ));
+
+    // compare against every other indexed contract
+    let index = read_index(&path);
+    logger.info(&format!("searching {} indexed contracts.", index.len()));
+    let mut matches = index
+        .iter()
+        .filter(|(indexed, _)| **indexed != name)
+        .map(|(indexed, other)| SimilarContract {
+            name: indexed.clone(),
+            similarity: fingerprint.similarity(other),
+        })
+        .filter(|contract| contract.similarity.score >= args.threshold)
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| {
+        b.similarity.score.total_cmp(&a.similarity.score).then_with(|| a.name.cmp(&b.name))
+    });
+    matches.truncate(args.limit);
+
+    for contract in &matches {
+        logger.info(&format!(
+            "'{}' is {:.2}% similar ({}), sharing {:.2}% of blocks and {:.2}% of selectors.",
+            contract.name,
+            contract.similarity.score * 100.0,
+            contract.relation(),
+            contract.similarity.blocks * 100.0,
+            contract.similarity.selectors * 100.0
+        ));
+    }
+    if matches.is_empty() {
+        logger.info("no similar contracts were found.");
+    }
+
+    if args.add {
+        add_to_index(&path, vec![(name.clone(), fingerprint.clone())])?;
+        logger.info(&format!("added '{name}' to the index at '{path}' ."));
+    }
+
+    Ok(SimilarResult { name, fingerprint, matches })
+}
@@ -3,6 +3,7 @@ use crate::decompile::constants::AND_BITMASK_REGEX;
 use super::{
     constants::VARIABLE_SIZE_CHECK_REGEX,
     structures::snapshot::{CalldataFrame, Snapshot, StorageFrame},
+    util::taint::record_external_call,
 };
 use ethers::{
     abi::{decode, ParamType},
@@ -100,6 +101,34 @@ pub fn snapshot_trace(
             );
         }
 
+        // flag potentially dangerous behavior
+        match opcode_name {
+            "SELFDESTRUCT" => {
+                snapshot.risk_flags.insert("selfdestruct".to_string());
+            }
+            "ORIGIN" => {
+                snapshot.risk_flags.insert("uses tx.origin".to_string());
+            }
+            "DELEGATECALL" | "CALLCODE" => {
+                snapshot.risk_flags.insert(opcode_name.to_lowercase());
+            }
+            "CREATE" | "CREATE2" => {
+                snapshot.risk_flags.insert("deploys contracts".to_string());
+            }
+            "CALL"
+                if !instruction.inputs[2].is_zero() ||
+                    !instruction.input_operations[2].opcode.name.starts_with("PUSH") =>
+            {
+                snapshot.risk_flags.insert("transfers ether".to_string());
+            }
+            _ => {}
+        }
+        if ["CALL", "CALLCODE", "DELEGATECALL", "STATICCALL"].contains(&opcode_name) &&
+            instruction.input_operations[1].solidify().contains("arg")
+        {
+            snapshot.risk_flags.insert("calls a user-supplied address".to_string());
+        }
+
         // if the instruction is a state-setting instruction, the function is no longer a view
         if snapshot.view &&
             [
@@ -168,6 +197,9 @@ pub fn snapshot_trace(
                 VARIABLE_SIZE_CHECK_REGEX.is_match(&conditional).unwrap_or(false) ||
                 (conditional.replace('!', "") == "success") ||
                 (!conditional.contains("msg.sender") &&
+                    !conditional.contains("tx.origin") &&
+                    !conditional.contains("msg.value") &&
+                    !conditional.contains(".balance") &&
                     !conditional.contains("arg") &&
                     !conditional.contains("storage"))
             {
@@ -249,6 +281,11 @@ pub fn snapshot_trace(
             }
         } else if opcode_name == "SSTORE" || opcode_name == "SLOAD" {
             snapshot.storage.insert(instruction.input_operations[0].solidify().cleanup());
+            if opcode_name == "SSTORE" {
+                snapshot
+                    .storage_writes
+                    .insert(instruction.input_operations[0].solidify().cleanup());
+            }
         } else if opcode_name == "CALLDATALOAD" {
             let slot_as_usize: usize = instruction.inputs[0].try_into().unwrap_or(usize::MAX);
             let calldata_slot = (slot_as_usize.saturating_sub(4)) / 32;
@@ -379,6 +416,9 @@ pub fn snapshot_trace(
                     StorageFrame { value, operations: WrappedOpcode::new(0x39, vec![]) },
                 );
             }
+        } else if opcode_name == "CALLDATACOPY" {
+            // the caller controls the memory which calldata is copied into
+            snapshot.calldata_copies.push((instruction.inputs[0], instruction.inputs[2]));
         } else if opcode_name == "STATICCALL" {
             // if the gas param WrappedOpcode is not GAS(), add the gas param to the function's
             // logic
@@ -394,7 +434,7 @@ pub fn snapshot_trace(
             let extcalldata_memory =
                 snapshot.get_memory_range(instruction.inputs[2], instruction.inputs[3]);
 
-            snapshot.external_calls.push(format!(
+            let call = format!(
                 "address({}).staticcall{}({});",
                 address.solidify().cleanup(),
                 modifier,
@@ -403,7 +443,15 @@ pub fn snapshot_trace(
                     .map(|x| x.operations.solidify().cleanup())
                     .collect::<Vec<String>>()
                     .join(", "),
-            ));
+            );
+            record_external_call(
+                &mut snapshot,
+                call,
+                address,
+                None,
+                instruction.inputs[2],
+                instruction.inputs[3],
+            );
         } else if opcode_name == "DELEGATECALL" {
             // if the gas param WrappedOpcode is not GAS(), add the gas param to the function's
             // logic
@@ -419,7 +467,7 @@ pub fn snapshot_trace(
             let extcalldata_memory =
                 snapshot.get_memory_range(instruction.inputs[2], instruction.inputs[3]);
 
-            snapshot.external_calls.push(format!(
+            let call = format!(
                 "address({}).delegatecall{}({});",
                 address.solidify().cleanup(),
                 modifier,
@@ -428,7 +476,15 @@ pub fn snapshot_trace(
                     .map(|x| x.operations.solidify().cleanup())
                     .collect::<Vec<String>>()
                     .join(", "),
-            ));
+            );
+            record_external_call(
+                &mut snapshot,
+                call,
+                address,
+                None,
+                instruction.inputs[2],
+                instruction.inputs[3],
+            );
         } else if opcode_name == "CALL" || opcode_name == "CALLCODE" {
             // if the gas param WrappedOpcode is not GAS(), add the gas param to the function's
             // logic
@@ -449,7 +505,7 @@ pub fn snapshot_trace(
             let extcalldata_memory =
                 snapshot.get_memory_range(instruction.inputs[3], instruction.inputs[4]);
 
-            snapshot.external_calls.push(format!(
+            let call = format!(
                 "address({}).call{}({});",
                 address.solidify().cleanup(),
                 modifier,
@@ -458,7 +514,15 @@ pub fn snapshot_trace(
                     .map(|x| x.operations.solidify().cleanup())
                     .collect::<Vec<String>>()
                     .join(", ")
-            ));
+            );
+            record_external_call(
+                &mut snapshot,
+                call,
+                address,
+                Some(&instruction.input_operations[2]),
+                instruction.inputs[3],
+                instruction.inputs[4],
+            );
         }
 
         // handle type heuristics
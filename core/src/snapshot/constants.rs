@@ -20,6 +20,7 @@ lazy_static! {
     pub static ref HELP_MENU_COMMANDS: Vec<String> = vec![
         ":q, :quit                              exit the program".to_string(),
         ":h, :help                              display this help menu".to_string(),
+        ":o, :sort      <COLUMN> [asc|desc]     sort the function list by a column".to_string(),
     ];
 
     /// constant help menu text
@@ -27,9 +28,20 @@ lazy_static! {
         "↑, Scroll Up                           move the cursor up".to_string(),
         "↓, Scroll Down                         move the cursor down".to_string(),
         "←, →                                   switch scrolling context between selector list and snapshot information".to_string(),
+        "s, S                                   sort by the next column, or reverse the sort order".to_string(),
         "ESC                                    clear the search filter".to_string(),
     ];
 
+    /// The columns of the function list, which can be sorted by. Flags are abbreviated as `$`
+    /// for payable, `v` for view, `p` for pure, and `!` for each risk flag.
+    pub static ref SNAPSHOT_COLUMNS: Vec<String> = vec![
+        "Selector".to_string(),
+        "Gas".to_string(),
+        "Writes".to_string(),
+        "Calls".to_string(),
+        "Flags".to_string(),
+    ];
+
     /// used to detect compiler size checks
     pub static ref VARIABLE_SIZE_CHECK_REGEX: Regex = Regex::new(r"!?\(?0(x01)? < [a-zA-Z0-9_\[\]]+\.length\)?").unwrap();
 }
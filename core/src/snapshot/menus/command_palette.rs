@@ -1,4 +1,4 @@
-use heimdall_common::utils::strings::encode_hex_reduced;
+use heimdall_common::{ether::labels::substitute_labels, utils::strings::encode_hex_reduced};
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout},
@@ -8,7 +8,10 @@ use tui::{
     Frame,
 };
 
-use crate::snapshot::{structures::state::State, util::table::build_rows};
+use crate::snapshot::{
+    structures::state::State,
+    util::table::{build_header, build_rows},
+};
 
 /// Render the TUI command palette
 pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut State) {
@@ -30,7 +33,7 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
 
     let sub_layout = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(14), Constraint::Percentage(100)].as_ref())
+        .constraints([Constraint::Length(52), Constraint::Percentage(100)].as_ref())
         .split(main_layout[1]);
 
     let detail_layout = Layout::default()
@@ -45,17 +48,24 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
         .block(Block::default().title(" Command ").borders(Borders::ALL));
 
     // build rows
-    let rows = build_rows(state, main_layout[1].height as usize - 4);
+    let rows = build_rows(state, main_layout[1].height as usize - 5);
 
     // build table
     let table = Table::new(rows)
+        .header(build_header(state))
         .block(
             Block::default()
-                .title(" Selectors ")
+                .title(" Functions ")
                 .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
                 .borders(Borders::ALL),
         )
-        .widths(&[Constraint::Length(12), Constraint::Length(14), Constraint::Percentage(100)]);
+        .widths(&[
+            Constraint::Length(12),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Percentage(100),
+        ]);
 
     // build function info
     let snapshot = state.snapshots.get(state.function_index).unwrap();
@@ -100,6 +110,12 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
             None => format!(" Unresolved_{}()", snapshot.selector),
         }),
     ];
+    if snapshot.partially_analyzed {
+        text.push(Spans::from(Span::styled(
+            " partially analyzed, symbolic execution exceeded its execution limits",
+            Style::default().fg(Color::Yellow),
+        )));
+    }
 
     // build function snapshot
     text.append(&mut vec![
@@ -116,6 +132,15 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
             snapshot.entry_point,
             snapshot.branch_count
         )),
+        Spans::from(""), // buffer
+        Spans::from(Span::styled(
+            " Cyclomatic Complexity   Maximum Nesting Depth",
+            Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(format!(
+            " {:<24}{}",
+            snapshot.cyclomatic_complexity, snapshot.max_nesting_depth
+        )),
     ]);
 
     // add gas consumptions
@@ -197,7 +222,7 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
             &mut snapshot
                 .external_calls
                 .iter()
-                .map(|x| Spans::from(format!(" {}", x)))
+                .map(|x| Spans::from(format!(" {}", substitute_labels(x))))
                 .collect::<Vec<_>>(),
         );
     }
@@ -233,7 +258,7 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
             &mut snapshot
                 .addresses
                 .iter()
-                .map(|x| Spans::from(format!(" {}", x)))
+                .map(|x| Spans::from(format!(" {}", substitute_labels(x))))
                 .collect::<Vec<_>>(),
         );
     }
@@ -274,6 +299,24 @@ pub fn render_tui_command_palette<B: Backend>(f: &mut Frame<B>, state: &mut Stat
         );
     }
 
+    // add risk flags
+    if !snapshot.risk_flags.is_empty() {
+        text.append(&mut vec![
+            Spans::from(""), // buffer
+            Spans::from(Span::styled(
+                " Risk Flags ",
+                Style::default().fg(Color::White).add_modifier(Modifier::BOLD),
+            )),
+        ]);
+        text.append(
+            &mut snapshot
+                .risk_flags
+                .iter()
+                .map(|x| Spans::from(format!(" {}", x)))
+                .collect::<Vec<_>>(),
+        );
+    }
+
     // about text
     let snapshot_header = format!(
         " {}Snapshot of 0x{}{} ",
@@ -17,12 +17,24 @@ use heimdall_common::{
     constants::{ADDRESS_REGEX, BYTECODE_REGEX},
     ether::{
         compiler::detect_compiler,
-        evm::core::vm::VM,
-        rpc::get_code,
-        selectors::{find_function_selectors, resolve_selectors},
-        signatures::{score_signature, ResolvedError, ResolvedFunction, ResolvedLog},
+        ether_flow::EtherFlow,
+        evm::{core::vm::VM, ext::exec::ExecutionLimits},
+        metadata::{decode_metadata, ContractMetadata},
+        metamorphic::{detect_metamorphic, MetamorphicReport},
+        rpc::get_code_at_block,
+        selectors::{
+            dispatcher_kind, find_dispatcher_anomalies, find_function_selectors, resolve_selectors,
+            DispatcherAnomalies, DispatcherKind,
+        },
+        signatures::{
+            score_signature, signature_confidence, ResolvedError, ResolvedFunction, ResolvedLog,
+        },
+        target::{select_target_vm, TargetVm},
     },
     utils::{
+        crash::set_phase,
+        events::{emit, ProgressEvent},
+        interrupt::is_interrupted,
         io::logging::*,
         strings::{decode_hex, encode_hex_reduced},
     },
@@ -35,7 +47,10 @@ use crate::{
         analyze::snapshot_trace,
         resolve::match_parameters,
         structures::snapshot::{GasUsed, Snapshot},
-        util::tui,
+        util::{
+            honeypot::{detect_honeypot, HoneypotReport},
+            tui,
+        },
     },
 };
 #[derive(Debug, Clone, Parser, Builder)]
@@ -69,6 +84,50 @@ pub struct SnapshotArgs {
     /// Whether to skip opening the TUI.
     #[clap(long)]
     pub no_tui: bool,
+
+    /// A Handlebars template to render the results with, written alongside the other
+    /// outputs as `report.<ext>`, where `<ext>` is taken from the template's filename.
+    #[clap(long = "template", default_value = "", hide_default_value = true)]
+    pub template: String,
+
+    /// The approximate memory budget for symbolic execution of each function, in megabytes.
+    /// Once exceeded, deeper branches are pruned and the function is marked as partially
+    /// analyzed. Defaults to no limit.
+    #[clap(long = "memory-limit", default_value = "0", hide_default_value = true)]
+    pub memory_limit: usize,
+
+    /// The maximum number of nested branches symbolic execution explores along a single path.
+    /// Deeper branches are pruned and the function is marked as partially analyzed. Defaults to
+    /// no limit.
+    #[clap(long = "max-branch-depth", default_value = "0", hide_default_value = true)]
+    pub max_branch_depth: usize,
+
+    /// The maximum depth of nested operations a stack value may be built from before symbolic
+    /// execution treats the path as unbounded recursion. Defaults to 16.
+    #[clap(long = "max-call-depth", default_value = "0", hide_default_value = true)]
+    pub max_call_depth: u32,
+
+    /// The maximum number of times symbolic execution revisits a single jump before treating it
+    /// as a loop. Defaults to no limit, relying only on loop detection heuristics.
+    #[clap(long = "max-loop-iterations", default_value = "0", hide_default_value = true)]
+    pub max_loop_iterations: usize,
+
+    /// The block number to fetch the target's bytecode at, which requires an archive node. This
+    /// allows analyzing prior versions of contracts whose code has changed, such as metamorphic
+    /// contracts. Defaults to the latest block.
+    #[clap(long = "block", default_value = "0", hide_default_value = true)]
+    pub block: u64,
+
+    /// Whether to skip symbolic execution, only reporting each function's selector, entry point,
+    /// and resolved signature, along with the layout of the contract's dispatcher. This is much
+    /// faster, for triaging many contracts before snapshotting the interesting ones in full.
+    #[clap(long = "selectors-only")]
+    pub selectors_only: bool,
+
+    /// The virtual machine the target is compiled for, either `evm` or `eravm`. Defaults to the
+    /// virtual machine of the RPC provider's chain for contract addresses, and `evm` otherwise.
+    #[clap(long = "vm", default_value = "", hide_default_value = true)]
+    pub vm: String,
 }
 
 impl SnapshotArgsBuilder {
@@ -80,6 +139,14 @@ impl SnapshotArgsBuilder {
             default: Some(true),
             skip_resolving: Some(false),
             no_tui: Some(true),
+            template: Some(String::new()),
+            memory_limit: Some(0),
+            max_branch_depth: Some(0),
+            max_call_depth: Some(0),
+            max_loop_iterations: Some(0),
+            block: Some(0),
+            selectors_only: Some(false),
+            vm: Some(String::new()),
         }
     }
 }
@@ -89,6 +156,70 @@ pub struct SnapshotResult {
     pub snapshots: Vec<Snapshot>,
     pub resolved_errors: HashMap<String, ResolvedError>,
     pub resolved_events: HashMap<String, ResolvedLog>,
+    pub metadata: Option<ContractMetadata>,
+    pub metamorphic: MetamorphicReport,
+    /// The layout of the contract's dispatcher.
+    pub dispatcher: DispatcherKind,
+    /// Unusual properties of the contract's dispatcher, such as selectors which fall through
+    /// into the fallback function.
+    pub dispatcher_anomalies: DispatcherAnomalies,
+    /// The honeypot tricks found in the contract, and how likely it is to be a honeypot.
+    pub honeypot: HoneypotReport,
+    /// Whether symbolic execution was skipped, so only each function's selector, entry point,
+    /// and signature are known.
+    pub selectors_only: bool,
+}
+
+/// Build the snapshot of a function from the dispatcher alone, without symbolically executing
+/// it. Its signature is the best-scored of those its selector resolves to, since there are no
+/// parameters to match them against, and its mutability is unknown.
+fn dispatcher_snapshot(
+    selector: &str,
+    entry_point: u128,
+    bytecode: Vec<u8>,
+    resolved_functions: Option<&Vec<ResolvedFunction>>,
+    rejects_call_value: bool,
+) -> Snapshot {
+    let mut resolved_functions = resolved_functions.cloned().unwrap_or_default();
+    resolved_functions
+        .sort_by_key(|function| std::cmp::Reverse(score_signature(&function.signature)));
+    let signatures =
+        resolved_functions.iter().map(|function| function.signature.clone()).collect::<Vec<_>>();
+
+    Snapshot {
+        selector: selector.to_string(),
+        bytecode,
+        entry_point,
+        arguments: HashMap::new(),
+        storage: HashSet::new(),
+        memory: HashMap::new(),
+        returns: None,
+        events: HashMap::new(),
+        errors: HashMap::new(),
+        signature_candidates: resolved_functions.len(),
+        signature_confidence: signatures
+            .first()
+            .map(|signature| signature_confidence(signature, &signatures))
+            .unwrap_or(0.0),
+        resolved_function: resolved_functions.into_iter().next(),
+        pure: false,
+        view: false,
+        payable: !rejects_call_value,
+        strings: HashSet::new(),
+        external_calls: Vec::new(),
+        tainted_calls: Vec::new(),
+        calldata_copies: Vec::new(),
+        ether_flow: EtherFlow::default(),
+        gas_used: GasUsed { min: 0, max: 0, avg: 0 },
+        addresses: HashSet::new(),
+        branch_count: 0,
+        cyclomatic_complexity: 0,
+        max_nesting_depth: 0,
+        control_statements: HashSet::new(),
+        storage_writes: HashSet::new(),
+        risk_flags: HashSet::new(),
+        partially_analyzed: false,
+    }
 }
 
 /// The main snapshot function, which will be called from the main thread. This module is
@@ -136,7 +267,12 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
     if ADDRESS_REGEX.is_match(&args.target)? {
         // We are snapshotting a contract address, so we need to fetch the bytecode from the RPC
         // provider.
-        contract_bytecode = get_code(&args.target, &args.rpc_url).await?;
+        contract_bytecode =
+            get_code_at_block(&args.target, &args.rpc_url, (args.block > 0).then_some(args.block))
+                .await?;
+    } else if args.block > 0 {
+        logger.error("argument '--block' requires the target to be a contract address.");
+        std::process::exit(1);
     } else if BYTECODE_REGEX.is_match(&args.target)? {
         logger.debug_max("using provided bytecode for snapshotting.");
         contract_bytecode = args.target.clone().replacen("0x", "", 1);
@@ -162,12 +298,29 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
         };
     }
 
+    // symbolic execution only runs EVM bytecode, so contracts on zkSync Era can't be snapshotted
+    let target_vm = match select_target_vm(&args.vm, &args.target, &args.rpc_url).await {
+        Ok(target_vm) => target_vm,
+        Err(error) => {
+            logger.error(&error);
+            std::process::exit(1)
+        }
+    };
+    if target_vm == TargetVm::EraVm {
+        logger.error("snapshotting EraVM bytecode isn't supported yet. use `heimdall disassemble` or `heimdall abi` to analyze it.");
+        std::process::exit(1)
+    }
+
     // disassemble the bytecode
+    set_phase("snapshot: disassembling");
     let disassembled_bytecode = disassemble(DisassemblerArgs {
         target: contract_bytecode.clone(),
         verbose: args.verbose.clone(),
         rpc_url: args.rpc_url,
         decimal_counter: false,
+        annotate: false,
+        vm: target_vm.to_string(),
+        diff: String::new(),
     })
     .await?;
     trace.add_call(
@@ -197,8 +350,24 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
             .warn(&format!("detected compiler {compiler} {version} is not supported by heimdall."));
     }
 
+    // decode the compiler metadata trailer, if present
+    let metadata = decode_metadata(&contract_bytecode);
+    if let Some(metadata) = &metadata {
+        trace.add_message(
+            snapshot_call,
+            line!(),
+            metadata.fields().iter().map(|(name, value)| format!("{name}: {value}")).collect(),
+        );
+    }
+
+    // warn about code which may change between deployments to the same address
+    let metamorphic = detect_metamorphic(&contract_bytecode);
+    for warning in metamorphic.warnings() {
+        logger.warn(&warning);
+    }
+
     // create a new EVM instance
-    let evm = VM::new(
+    let mut evm = VM::new(
         contract_bytecode.clone(),
         String::from("0x"),
         String::from("0x6865696d64616c6c000000000061646472657373"),
@@ -207,6 +376,12 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
         0,
         u128::max_value(),
     );
+    evm.limits = ExecutionLimits::new(
+        args.memory_limit,
+        args.max_branch_depth,
+        args.max_call_depth,
+        args.max_loop_iterations,
+    );
     let mut shortened_target = contract_bytecode.clone();
     if shortened_target.len() > 66 {
         shortened_target = shortened_target.chars().take(66).collect::<String>() +
@@ -222,7 +397,15 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
     );
 
     // find and resolve all selectors in the bytecode
+    set_phase("snapshot: resolving selectors");
     let selectors = find_function_selectors(&evm, &disassembled_bytecode);
+    let rejects_call_value = evm.rejects_call_value();
+    let dispatcher = dispatcher_kind(&evm);
+    logger.debug(&format!("detected a {dispatcher} dispatcher."));
+    let dispatcher_anomalies = find_dispatcher_anomalies(&evm, &selectors);
+    for warning in dispatcher_anomalies.warnings() {
+        logger.warn(&warning);
+    }
 
     let mut resolved_selectors = HashMap::new();
     if !args.skip_resolving {
@@ -246,7 +429,9 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
         logger.info(&format!("found {} possible function selectors.", selectors.len()));
     }
 
-    logger.info(&format!("performing symbolic execution on '{shortened_target}' ."));
+    if !args.selectors_only {
+        logger.info(&format!("performing symbolic execution on '{shortened_target}' ."));
+    }
 
     // get a new progress bar
     let mut snapshot_progress = ProgressBar::new_spinner();
@@ -254,8 +439,39 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
     snapshot_progress.set_style(logger.info_spinner());
 
     // perform EVM analysis
+    set_phase("snapshot: symbolic execution");
     let mut snapshots: Vec<Snapshot> = Vec::new();
+    let total_selectors = selectors.len();
     for (selector, function_entry_point) in selectors {
+        // stop analyzing functions once the user has interrupted analysis, keeping those which
+        // were already analyzed
+        if is_interrupted() {
+            snapshot_progress.suspend(|| {
+                logger.warn(&format!(
+                    "analysis interrupted, continuing with {} analyzed functions.",
+                    snapshots.len()
+                ));
+            });
+            break
+        }
+
+        // without symbolic execution, functions are only known from the dispatcher
+        if args.selectors_only {
+            snapshots.push(dispatcher_snapshot(
+                &selector,
+                function_entry_point,
+                decode_hex(&contract_bytecode)?,
+                resolved_selectors.get(&selector),
+                rejects_call_value,
+            ));
+            emit(ProgressEvent::SelectorAnalyzed {
+                selector,
+                analyzed: snapshots.len(),
+                total: total_selectors,
+            });
+            continue
+        }
+
         snapshot_progress.set_message(format!("executing '0x{selector}'"));
 
         let func_analysis_trace = trace.add_call(
@@ -308,20 +524,35 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
                 events: HashMap::new(),
                 errors: HashMap::new(),
                 resolved_function: None,
+                signature_candidates: 0,
+                signature_confidence: 0.0,
                 pure: true,
                 view: true,
                 payable: true,
                 strings: HashSet::new(),
                 external_calls: Vec::new(),
+                tainted_calls: Vec::new(),
+                calldata_copies: Vec::new(),
+                ether_flow: EtherFlow::from_trace(map),
                 gas_used: GasUsed { min: u128::MAX, max: 0, avg: 0 },
                 addresses: HashSet::new(),
                 branch_count: *jumpdest_count,
+                cyclomatic_complexity: map.cyclomatic_complexity(),
+                max_nesting_depth: map.max_nesting_depth(),
                 control_statements: HashSet::new(),
+                storage_writes: HashSet::new(),
+                risk_flags: HashSet::new(),
+                partially_analyzed: map.is_pruned(),
             },
             &mut trace,
             func_analysis_trace,
         );
 
+        // the contract rejects call value for every function before dispatching
+        if rejects_call_value {
+            snapshot.payable = false;
+        }
+
         // resolve signatures
         if !args.skip_resolving {
             let resolved_functions = match resolved_selectors.get(&selector) {
@@ -377,6 +608,18 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
                     };
 
                 snapshot.resolved_function = Some(selected_match.clone());
+                emit(ProgressEvent::SignatureResolved {
+                    selector: selector.clone(),
+                    signature: selected_match.signature.clone(),
+                });
+                snapshot.signature_candidates = matched_resolved_functions.len();
+                snapshot.signature_confidence = signature_confidence(
+                    &selected_match.signature,
+                    &matched_resolved_functions
+                        .iter()
+                        .map(|function| function.signature.clone())
+                        .collect::<Vec<String>>(),
+                );
 
                 let match_trace = trace.add_info(
                     func_analysis_trace,
@@ -441,6 +684,10 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
 
                 resolved_counter += 1;
                 snapshot.errors.insert(error_selector, Some(selected_match.clone()));
+                emit(ProgressEvent::SignatureResolved {
+                    selector: error_selector_str.clone(),
+                    signature: selected_match.signature.clone(),
+                });
                 all_resolved_errors.insert(error_selector_str, selected_match.clone());
             }
 
@@ -507,6 +754,10 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
 
                 resolved_counter += 1;
                 snapshot.events.insert(event_selector, (Some(selected_match.clone()), raw_event));
+                emit(ProgressEvent::SignatureResolved {
+                    selector: event_selector_str.clone(),
+                    signature: selected_match.signature.clone(),
+                });
                 all_resolved_events.insert(event_selector_str, selected_match.clone());
             }
 
@@ -529,6 +780,11 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
 
         // push
         snapshots.push(snapshot);
+        emit(ProgressEvent::SelectorAnalyzed {
+            selector,
+            analyzed: snapshots.len(),
+            total: total_selectors,
+        });
 
         // get a new progress bar
         snapshot_progress = ProgressBar::new_spinner();
@@ -536,7 +792,21 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
         snapshot_progress.set_style(logger.info_spinner());
     }
     snapshot_progress.finish_and_clear();
-    logger.info("symbolic execution completed.");
+    if !args.selectors_only {
+        logger.info("symbolic execution completed.");
+    }
+    // score the contract on common honeypot tricks
+    let honeypot = detect_honeypot(&snapshots, &dispatcher_anomalies);
+    for warning in honeypot.warnings() {
+        logger.warn(&warning);
+    }
+    if honeypot.score > 0 {
+        logger.info(&format!(
+            "honeypot likelihood: {} ({}/100).",
+            honeypot.likelihood(),
+            honeypot.score
+        ));
+    }
     logger.debug(&format!("snapshot completed in {:?}.", now.elapsed()));
 
     // open the tui
@@ -555,5 +825,11 @@ pub async fn snapshot(args: SnapshotArgs) -> Result<SnapshotResult, Box<dyn std:
         snapshots,
         resolved_errors: all_resolved_errors,
         resolved_events: all_resolved_events,
+        metadata,
+        metamorphic,
+        dispatcher,
+        dispatcher_anomalies,
+        honeypot,
+        selectors_only: args.selectors_only,
     })
 }
@@ -2,6 +2,7 @@ use std::collections::{HashMap, HashSet};
 
 use ethers::types::U256;
 use heimdall_common::ether::{
+    ether_flow::EtherFlow,
     evm::core::{log::Log, opcodes::WrappedOpcode},
     signatures::{ResolvedError, ResolvedFunction, ResolvedLog},
 };
@@ -47,6 +48,11 @@ pub struct Snapshot {
     // stores the matched resolved function for this Functon
     pub resolved_function: Option<ResolvedFunction>,
 
+    // the number of resolved signatures which matched the function's parameters, and the
+    // confidence, from 0 to 1, that the selected one is correct
+    pub signature_candidates: usize,
+    pub signature_confidence: f64,
+
     // modifiers
     pub pure: bool,
     pub view: bool,
@@ -58,6 +64,15 @@ pub struct Snapshot {
     // store external calls made by the function
     pub external_calls: Vec<String>,
 
+    // the external calls whose target, value, or calldata the caller controls
+    pub tainted_calls: Vec<TaintedCall>,
+
+    // the memory ranges, as (offset, size), which the function copies calldata into
+    pub calldata_copies: Vec<(U256, U256)>,
+
+    // who the function forwards call value, or sends ether, to
+    pub ether_flow: EtherFlow,
+
     // stores min, max, and avg gas used by the function
     pub gas_used: GasUsed,
 
@@ -67,8 +82,22 @@ pub struct Snapshot {
     // stores the number of unique branches found by symbolic execution
     pub branch_count: u32,
 
+    /// The number of linearly independent paths through the function, and the maximum number of
+    /// conditionals nested within each other, excluding `require`-style guards.
+    pub cyclomatic_complexity: u32,
+    pub max_nesting_depth: u32,
+
     // control statements, such as access control
     pub control_statements: HashSet<String>,
+
+    // the storage slots written to by the function
+    pub storage_writes: HashSet<String>,
+
+    // potentially dangerous behavior, such as delegatecalls or selfdestructs
+    pub risk_flags: HashSet<String>,
+
+    // whether symbolic execution exceeded its execution limits, so some branches weren't analyzed
+    pub partially_analyzed: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -84,6 +113,28 @@ pub struct StorageFrame {
     pub operations: WrappedOpcode,
 }
 
+/// An external call with parameters which are read directly from the function's calldata, so
+/// the caller controls them. A call whose target and calldata are both controlled lets the
+/// caller make arbitrary calls on the contract's behalf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaintedCall {
+    /// The external call, as it appears in the function's external calls.
+    pub call: String,
+    pub target: bool,
+    pub value: bool,
+    pub calldata: bool,
+}
+
+impl TaintedCall {
+    /// The names of the parameters which the caller controls, i.e. `["target", "calldata"]`.
+    pub fn parameters(&self) -> Vec<&'static str> {
+        [(self.target, "target"), (self.value, "value"), (self.calldata, "calldata")]
+            .into_iter()
+            .filter_map(|(tainted, parameter)| tainted.then_some(parameter))
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct CalldataFrame {
     pub slot: usize,
@@ -19,6 +19,8 @@ pub struct State {
     pub target: String,
     pub compiler: (String, String),
     pub scroll: bool,
+    pub sort_column: usize,
+    pub sort_descending: bool,
 }
 
 impl State {
@@ -34,6 +36,34 @@ impl State {
             target: String::new(),
             compiler: (String::new(), String::new()),
             scroll: false,
+            sort_column: 0,
+            sort_descending: false,
+        }
+    }
+
+    /// Sort the snapshots by the selected column, keeping the selected function selected.
+    pub fn sort_snapshots(&mut self) {
+        let selected = self.snapshots.get(self.function_index).map(|s| s.selector.clone());
+
+        self.snapshots.sort_by(|a, b| {
+            let ordering = match self.sort_column {
+                1 => a.gas_used.avg.cmp(&b.gas_used.avg),
+                2 => a.storage_writes.len().cmp(&b.storage_writes.len()),
+                3 => a.external_calls.len().cmp(&b.external_calls.len()),
+                4 => a.risk_flags.len().cmp(&b.risk_flags.len()),
+                _ => std::cmp::Ordering::Equal,
+            }
+            .then(a.selector.cmp(&b.selector));
+
+            match self.sort_descending {
+                true => ordering.reverse(),
+                false => ordering,
+            }
+        });
+
+        if let Some(selected) = selected {
+            self.function_index =
+                self.snapshots.iter().position(|s| s.selector == selected).unwrap_or(0);
         }
     }
 }
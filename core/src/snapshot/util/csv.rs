@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use heimdall_common::{
     ether::signatures::{ResolvedError, ResolvedLog},
-    utils::{io::file::write_lines_to_file, strings::encode_hex_reduced},
+    utils::{io::file::write_csv_to_file, strings::encode_hex_reduced},
 };
 
 use crate::snapshot::structures::snapshot::Snapshot;
@@ -14,19 +14,24 @@ pub fn generate_and_write_contract_csv(
     resolved_events: &HashMap<String, ResolvedLog>,
     output_path: &str,
 ) {
-    let mut lines: Vec<String> = Vec::new();
+    let mut rows: Vec<Vec<String>> = Vec::new();
 
     // add header
-    lines.push(
-        vec![
+    rows.push(
+        [
             "Function Selector",
             "Resolved Function Signature",
+            "Selected Signature",
+            "Signature Candidates",
+            "Signature Confidence",
             "Payable",
             "View",
             "Pure",
             "Returns",
             "Entry Point",
             "Branch Count",
+            "Cyclomatic Complexity",
+            "Maximum Nesting Depth",
             "Emitted Events",
             "Custom Errors",
             "Storage Slots",
@@ -36,9 +41,13 @@ pub fn generate_and_write_contract_csv(
             "Maximum Gas Used",
             "Average Gas Used",
             "External Calls Made",
+            "Caller-Controlled Calls",
+            "Ether Flow",
             "Control Statements",
         ]
-        .join(","),
+        .iter()
+        .map(|column| column.to_string())
+        .collect(),
     );
 
     for snapshot in snapshots {
@@ -102,6 +111,14 @@ pub fn generate_and_write_contract_csv(
         let external_calls_column =
             snapshot.external_calls.clone().into_iter().collect::<Vec<_>>().join("\n");
 
+        // build caller-controlled calls column, with the parameters the caller controls
+        let tainted_calls_column = snapshot
+            .tainted_calls
+            .iter()
+            .map(|call| format!("{} ({})", call.call, call.parameters().join(", ")))
+            .collect::<Vec<_>>()
+            .join("\n");
+
         // build control statements column
         let control_statements_column =
             snapshot.control_statements.clone().into_iter().collect::<Vec<_>>().join("\n");
@@ -109,28 +126,38 @@ pub fn generate_and_write_contract_csv(
         // push column values
         line.push(snapshot.selector.clone());
         line.push(match &snapshot.resolved_function {
-            Some(function) => format!("\"{}({})\"", function.name, arg_strings.join(", ")),
-            None => format!("\"Unresolved_{}({})\"", snapshot.selector, arg_strings.join(", ")),
+            Some(function) => format!("{}({})", function.name, arg_strings.join(", ")),
+            None => format!("Unresolved_{}({})", snapshot.selector, arg_strings.join(", ")),
+        });
+        line.push(match &snapshot.resolved_function {
+            Some(function) => function.signature.clone(),
+            None => String::new(),
         });
+        line.push(snapshot.signature_candidates.to_string());
+        line.push(format!("{:.2}", snapshot.signature_confidence));
         line.push(snapshot.payable.to_string());
         line.push((snapshot.view && !snapshot.pure).to_string());
         line.push(snapshot.pure.to_string());
         line.push(snapshot.returns.clone().unwrap_or(String::new()));
         line.push(snapshot.entry_point.to_string());
         line.push(snapshot.branch_count.to_string());
-        line.push(format!("\"{event_column}\""));
-        line.push(format!("\"{error_column}\""));
-        line.push(format!("\"{storage_column}\""));
-        line.push(format!("\"{strings_column}\""));
-        line.push(format!("\"{address_column}\""));
+        line.push(snapshot.cyclomatic_complexity.to_string());
+        line.push(snapshot.max_nesting_depth.to_string());
+        line.push(event_column);
+        line.push(error_column);
+        line.push(storage_column);
+        line.push(strings_column);
+        line.push(address_column);
         line.push(snapshot.gas_used.min.to_string());
         line.push(snapshot.gas_used.max.to_string());
         line.push(snapshot.gas_used.avg.to_string());
-        line.push(format!("\"{external_calls_column}\""));
-        line.push(format!("\"{control_statements_column}\""));
+        line.push(external_calls_column);
+        line.push(tainted_calls_column);
+        line.push(snapshot.ether_flow.summary(snapshot.payable));
+        line.push(control_statements_column);
 
-        lines.push(line.join(","));
+        rows.push(line);
     }
 
-    write_lines_to_file(output_path, lines);
+    write_csv_to_file(output_path, &rows);
 }
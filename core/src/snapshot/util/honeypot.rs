@@ -0,0 +1,213 @@
+use ethers::types::U256;
+use heimdall_common::ether::selectors::DispatcherAnomalies;
+
+use crate::snapshot::structures::snapshot::Snapshot;
+
+/// The selectors of the standard token functions which honeypots most often fake.
+const TOKEN_FUNCTION_SELECTORS: [(&str, &str); 3] = [
+    ("a9059cbb", "transfer(address,uint256)"),
+    ("23b872dd", "transferFrom(address,address,uint256)"),
+    ("095ea7b3", "approve(address,uint256)"),
+];
+
+/// The topics of the `Transfer` and `Approval` events which standard token functions emit.
+const TOKEN_EVENT_TOPICS: [&str; 2] = [
+    "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+    "8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925",
+];
+
+/// A trick which honeypots use to let their deployer take funds which others can't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoneypotHeuristic {
+    /// A path which only some callers can take, guarded by a storage lookup of the caller or a
+    /// storage flag rather than a visible owner check.
+    HiddenOwnerPath,
+    /// A check of the contract's balance against the call value or the caller, which only the
+    /// deployer can pass.
+    DeployerBalanceCheck,
+    /// A standard token function which doesn't do what its selector suggests, such as a
+    /// `transfer` which writes no balances or emits no `Transfer` event.
+    FakeStandardFunction,
+    /// A token transfer whose restrictions are checked by calling a contract whose address is
+    /// stored, so its code can't be verified from this one, and may be replaced.
+    UnverifiableTransferRestriction,
+    /// A dispatcher which routes selectors unexpectedly, such as into the fallback function.
+    DispatcherAnomaly,
+}
+
+impl HoneypotHeuristic {
+    /// How strongly the heuristic indicates a honeypot, out of 100.
+    pub fn weight(&self) -> u32 {
+        match self {
+            HoneypotHeuristic::HiddenOwnerPath => 25,
+            HoneypotHeuristic::DeployerBalanceCheck => 35,
+            HoneypotHeuristic::FakeStandardFunction => 30,
+            HoneypotHeuristic::UnverifiableTransferRestriction => 20,
+            HoneypotHeuristic::DispatcherAnomaly => 15,
+        }
+    }
+}
+
+impl std::fmt::Display for HoneypotHeuristic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HoneypotHeuristic::HiddenOwnerPath => write!(f, "hidden owner path"),
+            HoneypotHeuristic::DeployerBalanceCheck => write!(f, "deployer balance check"),
+            HoneypotHeuristic::FakeStandardFunction => write!(f, "fake standard function"),
+            HoneypotHeuristic::UnverifiableTransferRestriction => {
+                write!(f, "unverifiable transfer restriction")
+            }
+            HoneypotHeuristic::DispatcherAnomaly => write!(f, "dispatcher anomaly"),
+        }
+    }
+}
+
+/// A honeypot trick found in the contract, with the function it was found in, if any, and the
+/// evidence for it, such as the control statement which guards the path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoneypotFinding {
+    pub heuristic: HoneypotHeuristic,
+    pub selector: Option<String>,
+    pub evidence: String,
+}
+
+/// The honeypot tricks found in the contract, and how likely it is to be a honeypot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HoneypotReport {
+    pub findings: Vec<HoneypotFinding>,
+    /// The sum of the weights of each heuristic which fired, at most once per function, out of
+    /// 100.
+    pub score: u32,
+}
+
+impl HoneypotReport {
+    /// How likely the contract is to be a honeypot, from its score.
+    pub fn likelihood(&self) -> &'static str {
+        match self.score {
+            0 => "none",
+            1..=29 => "low",
+            30..=59 => "medium",
+            _ => "high",
+        }
+    }
+
+    /// Human-readable warnings for each finding.
+    pub fn warnings(&self) -> Vec<String> {
+        self.findings
+            .iter()
+            .map(|finding| match &finding.selector {
+                Some(selector) => {
+                    format!("{} in '0x{selector}': {}", finding.heuristic, finding.evidence)
+                }
+                None => format!("{}: {}", finding.heuristic, finding.evidence),
+            })
+            .collect()
+    }
+}
+
+/// Find the honeypot tricks within a function.
+fn function_findings(snapshot: &Snapshot) -> Vec<(HoneypotHeuristic, String)> {
+    let mut findings = Vec::new();
+    let mut control_statements = snapshot.control_statements.iter().collect::<Vec<_>>();
+    control_statements.sort();
+
+    for statement in control_statements {
+        let checks_caller = statement.contains("msg.sender") || statement.contains("tx.origin");
+
+        // the caller is looked up in a storage mapping, or a storage flag guards sending ether
+        if (checks_caller && statement.contains("storage[keccak256(")) ||
+            (snapshot.ether_flow.sends_ether() &&
+                statement.contains("storage") &&
+                !checks_caller &&
+                !statement.contains("arg"))
+        {
+            findings.push((HoneypotHeuristic::HiddenOwnerPath, statement.clone()));
+        }
+
+        // the contract's balance is compared against what the caller sends, or who they are
+        if statement.contains(".balance") && (checks_caller || statement.contains("msg.value")) {
+            findings.push((HoneypotHeuristic::DeployerBalanceCheck, statement.clone()));
+        }
+    }
+
+    if let Some((_, signature)) =
+        TOKEN_FUNCTION_SELECTORS.iter().find(|(selector, _)| *selector == snapshot.selector)
+    {
+        let emits_standard_event = TOKEN_EVENT_TOPICS.iter().any(|topic| {
+            U256::from_str_radix(topic, 16)
+                .map(|topic| snapshot.events.contains_key(&topic))
+                .unwrap_or(false)
+        });
+        if snapshot.storage_writes.is_empty() {
+            findings.push((
+                HoneypotHeuristic::FakeStandardFunction,
+                format!("{signature} writes no storage"),
+            ));
+        } else if !emits_standard_event {
+            findings.push((
+                HoneypotHeuristic::FakeStandardFunction,
+                format!("{signature} emits no standard event"),
+            ));
+        }
+
+        // a call to a contract whose address is stored, such as a blocklist or a "tax" oracle
+        if signature.starts_with("transfer") {
+            if let Some(call) = snapshot.external_calls.iter().find(|call| {
+                call.split(").").next().is_some_and(|target| target.contains("storage["))
+            }) {
+                findings
+                    .push((HoneypotHeuristic::UnverifiableTransferRestriction, call.to_string()));
+            }
+        }
+    }
+
+    findings
+}
+
+/// Score the contract's likelihood of being a honeypot from the tricks found within each of its
+/// functions, and in its dispatcher. Each heuristic counts at most once per function, and the
+/// score is capped at 100.
+pub fn detect_honeypot(snapshots: &[Snapshot], anomalies: &DispatcherAnomalies) -> HoneypotReport {
+    let mut findings = Vec::new();
+    let mut score = 0;
+
+    let mut snapshots = snapshots.iter().collect::<Vec<_>>();
+    snapshots.sort_by(|a, b| a.selector.cmp(&b.selector));
+    for snapshot in snapshots {
+        let mut fired = Vec::new();
+        for (heuristic, evidence) in function_findings(snapshot) {
+            if !fired.contains(&heuristic) {
+                fired.push(heuristic);
+                score += heuristic.weight();
+            }
+            findings.push(HoneypotFinding {
+                heuristic,
+                selector: Some(snapshot.selector.clone()),
+                evidence,
+            });
+        }
+    }
+
+    // selectors which are shadowed, or fall into the fallback, hide the function which runs
+    let anomalous = anomalies
+        .colliding_selectors
+        .iter()
+        .map(|selector| format!("0x{selector} is shadowed by an earlier check"))
+        .chain(
+            anomalies
+                .fallthrough_selectors
+                .iter()
+                .map(|selector| format!("0x{selector} falls through into the fallback function")),
+        )
+        .collect::<Vec<String>>();
+    if !anomalous.is_empty() {
+        score += HoneypotHeuristic::DispatcherAnomaly.weight();
+    }
+    findings.extend(anomalous.into_iter().map(|evidence| HoneypotFinding {
+        heuristic: HoneypotHeuristic::DispatcherAnomaly,
+        selector: None,
+        evidence,
+    }));
+
+    HoneypotReport { findings, score: score.min(100) }
+}
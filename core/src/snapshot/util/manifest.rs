@@ -0,0 +1,73 @@
+use crate::snapshot::structures::snapshot::Snapshot;
+
+/// The selectors of well-known functions which move or approve tokens, and their signatures.
+/// Calls made with calldata beginning with one of these selectors are reported as token
+/// movements in the capability manifest.
+pub const TOKEN_MOVEMENT_SELECTORS: [(&str, &str); 6] = [
+    ("a9059cbb", "transfer(address,uint256)"),
+    ("23b872dd", "transferFrom(address,address,uint256)"),
+    ("095ea7b3", "approve(address,uint256)"),
+    ("42842e0e", "safeTransferFrom(address,address,uint256)"),
+    ("b88d4fde", "safeTransferFrom(address,address,uint256,bytes)"),
+    ("f242432a", "safeTransferFrom(address,address,uint256,uint256,bytes)"),
+];
+
+/// An asset which a function can move out of, or on behalf of, the contract.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AssetMovement {
+    /// The function sends ether, either with a call or by selfdestructing.
+    Ether,
+    /// The function calls a token's `method`, such as `transfer(address,uint256)`.
+    Tokens { method: String },
+}
+
+/// The state mutability of the function, as it would appear in its ABI.
+pub fn state_mutability(snapshot: &Snapshot) -> &'static str {
+    match (snapshot.payable, snapshot.pure, snapshot.view) {
+        (true, ..) => "payable",
+        (false, true, _) => "pure",
+        (false, false, true) => "view",
+        (false, false, false) => "nonpayable",
+    }
+}
+
+/// The conditions which restrict who can call the function, i.e. `msg.sender == storage[0x00]`.
+/// These are the function's control statements which check the caller, and the function is
+/// callable by anyone when there are none.
+pub fn access_conditions(snapshot: &Snapshot) -> Vec<String> {
+    let mut conditions = snapshot
+        .control_statements
+        .iter()
+        .filter(|statement| statement.contains("msg.sender") || statement.contains("tx.origin"))
+        .map(|statement| {
+            statement
+                .trim_start_matches("if (")
+                .trim_end_matches(" { .. }")
+                .strip_suffix(')')
+                .unwrap_or(statement)
+                .to_string()
+        })
+        .collect::<Vec<String>>();
+    conditions.sort();
+    conditions
+}
+
+/// The assets which the function can move, based on its risk flags and the calldata of its
+/// external calls.
+pub fn asset_movements(snapshot: &Snapshot) -> Vec<AssetMovement> {
+    let mut movements = Vec::new();
+    if snapshot.risk_flags.contains("transfers ether") ||
+        snapshot.risk_flags.contains("selfdestruct")
+    {
+        movements.push(AssetMovement::Ether);
+    }
+
+    for (selector, method) in TOKEN_MOVEMENT_SELECTORS {
+        let selector = format!("0x{selector}");
+        if snapshot.external_calls.iter().any(|call| call.to_lowercase().contains(&selector)) {
+            movements.push(AssetMovement::Tokens { method: method.to_string() });
+        }
+    }
+
+    movements
+}
@@ -1,3 +1,6 @@
 pub mod csv;
+pub mod honeypot;
+pub mod manifest;
 pub mod table;
+pub mod taint;
 pub mod tui;
@@ -3,7 +3,38 @@ use tui::{
     widgets::{Cell, Row},
 };
 
-use crate::snapshot::structures::state::State;
+use crate::snapshot::{
+    constants::SNAPSHOT_COLUMNS,
+    structures::{snapshot::Snapshot, state::State},
+};
+
+/// Build the function list's header row, marking the column which the functions are sorted by.
+pub fn build_header(state: &State) -> Row<'static> {
+    let header_cells = SNAPSHOT_COLUMNS.iter().enumerate().map(|(index, column)| {
+        Cell::from(match index == state.sort_column {
+            true => format!("{} {}", column, if state.sort_descending { "▼" } else { "▲" }),
+            false => column.clone(),
+        })
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+    });
+
+    Row::new(header_cells)
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD))
+        .height(1)
+        .bottom_margin(0)
+}
+
+/// Abbreviate a function's modifiers and risk flags, as `$` for payable, `v` for view, `p` for
+/// pure, and `!` for each risk flag.
+fn build_flags(snapshot: &Snapshot) -> String {
+    format!(
+        "{}{}{}{}",
+        if snapshot.payable { "$" } else { "" },
+        if snapshot.view && !snapshot.pure { "v" } else { "" },
+        if snapshot.pure { "p" } else { "" },
+        "!".repeat(snapshot.risk_flags.len())
+    )
+}
 
 /// A helper function used in many TUI views for rendering list rows, as well as handling scrolling
 /// and selection.
@@ -25,20 +56,17 @@ pub fn build_rows(state: &mut State, max_row_height: usize) -> Vec<Row<'static>>
     // slice storage_iter
     for (i, snapshot) in snapshots[indices].iter().enumerate() {
         rows.push(
-            Row::new(vec![Cell::from(format!(" 0x{} ", snapshot.selector))])
-                .style(if snapshots.len() - state.function_index < num_items {
-                    if (num_items - i <= snapshots.len() - state.function_index) &&
-                        (num_items - i > snapshots.len() - state.function_index - 1)
-                    {
-                        if state.scroll {
-                            Style::default().fg(Color::White)
-                        } else {
-                            Style::default().fg(Color::White).bg(Color::DarkGray)
-                        }
-                    } else {
-                        Style::default().fg(Color::White).remove_modifier(Modifier::BOLD)
-                    }
-                } else if i == 0 {
+            Row::new(vec![
+                Cell::from(format!(" 0x{} ", snapshot.selector)),
+                Cell::from(snapshot.gas_used.avg.to_string()),
+                Cell::from(snapshot.storage_writes.len().to_string()),
+                Cell::from(snapshot.external_calls.len().to_string()),
+                Cell::from(build_flags(snapshot)),
+            ])
+            .style(if snapshots.len() - state.function_index < num_items {
+                if (num_items - i <= snapshots.len() - state.function_index) &&
+                    (num_items - i > snapshots.len() - state.function_index - 1)
+                {
                     if state.scroll {
                         Style::default().fg(Color::White)
                     } else {
@@ -46,9 +74,18 @@ pub fn build_rows(state: &mut State, max_row_height: usize) -> Vec<Row<'static>>
                     }
                 } else {
                     Style::default().fg(Color::White).remove_modifier(Modifier::BOLD)
-                })
-                .height(1)
-                .bottom_margin(0),
+                }
+            } else if i == 0 {
+                if state.scroll {
+                    Style::default().fg(Color::White)
+                } else {
+                    Style::default().fg(Color::White).bg(Color::DarkGray)
+                }
+            } else {
+                Style::default().fg(Color::White).remove_modifier(Modifier::BOLD)
+            })
+            .height(1)
+            .bottom_margin(0),
         );
     }
 
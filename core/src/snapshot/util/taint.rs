@@ -0,0 +1,47 @@
+use ethers::types::U256;
+use heimdall_common::ether::evm::core::opcodes::WrappedOpcode;
+
+use crate::snapshot::structures::snapshot::{Snapshot, TaintedCall};
+
+/// Whether the operation's value is read directly from calldata, so the caller controls it.
+pub fn reads_calldata(operation: &WrappedOpcode) -> bool {
+    operation.to_string().contains("CALLDATALOAD")
+}
+
+/// Whether the caller controls any of the memory range, i.e. it holds a value read from calldata,
+/// or calldata was copied into it.
+fn memory_reads_calldata(snapshot: &Snapshot, offset: U256, size: U256) -> bool {
+    let end = offset.saturating_add(size);
+    snapshot.get_memory_range(offset, size).iter().any(|frame| reads_calldata(&frame.operations)) ||
+        snapshot.calldata_copies.iter().any(|(copy_offset, copy_size)| {
+            *copy_offset < end && offset < copy_offset.saturating_add(*copy_size)
+        })
+}
+
+/// Record an external call made by the function, along with which of its parameters the caller
+/// controls, if any. `value` is `None` for calls which can't send ether, and the call's calldata
+/// is the memory range at `args_offset`. A call whose target and calldata are both controlled
+/// by the caller is flagged as an arbitrary call.
+pub fn record_external_call(
+    snapshot: &mut Snapshot,
+    call: String,
+    target: &WrappedOpcode,
+    value: Option<&WrappedOpcode>,
+    args_offset: U256,
+    args_size: U256,
+) {
+    let tainted_call = TaintedCall {
+        call: call.clone(),
+        target: reads_calldata(target),
+        value: value.is_some_and(reads_calldata),
+        calldata: memory_reads_calldata(snapshot, args_offset, args_size),
+    };
+
+    if tainted_call.target && tainted_call.calldata {
+        snapshot.risk_flags.insert("arbitrary call".to_string());
+    }
+    if !tainted_call.parameters().is_empty() {
+        snapshot.tainted_calls.push(tainted_call);
+    }
+    snapshot.external_calls.push(call);
+}
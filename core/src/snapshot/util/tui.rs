@@ -9,7 +9,7 @@ use heimdall_common::ether::signatures::{ResolvedError, ResolvedLog};
 use tui::{backend::CrosstermBackend, Terminal};
 
 use crate::snapshot::{
-    constants::STATE,
+    constants::{SNAPSHOT_COLUMNS, STATE},
     menus::{render_ui, TUIView},
     structures::snapshot::Snapshot,
 };
@@ -46,6 +46,7 @@ pub fn handle(
     state.resolved_events = resolved_events.clone();
     state.target = target.to_string();
     state.compiler = (compiler.0.to_string(), compiler.1.to_string());
+    state.sort_snapshots();
     drop(state);
 
     loop {
@@ -79,9 +80,10 @@ pub fn handle(
 
                                 // enter command
                                 crossterm::event::KeyCode::Enter => {
-                                    let mut split = state.input_buffer.split(' ');
+                                    let input_buffer = state.input_buffer.clone();
+                                    let mut split = input_buffer.split(' ');
                                     let command = split.next().unwrap();
-                                    let _args = split.collect::<Vec<&str>>();
+                                    let args = split.collect::<Vec<&str>>();
 
                                     match command {
                                         ":q" | ":quit" => {
@@ -91,6 +93,22 @@ pub fn handle(
                                         ":h" | ":help" => {
                                             state.view = TUIView::Help;
                                         }
+                                        ":o" | ":sort" => {
+                                            if !args.is_empty() {
+                                                let column = args[0].to_lowercase();
+                                                if let Some(index) =
+                                                    SNAPSHOT_COLUMNS.iter().position(|name| {
+                                                        name.to_lowercase().contains(&column)
+                                                    })
+                                                {
+                                                    state.sort_column = index;
+                                                    state.sort_descending =
+                                                        args.get(1) == Some(&"desc");
+                                                    state.sort_snapshots();
+                                                }
+                                            }
+                                            state.view = TUIView::Main;
+                                        }
                                         _ => {
                                             state.view = TUIView::Main;
                                         }
@@ -147,6 +165,19 @@ pub fn handle(
                                 }
                             }
 
+                            // sort by the next column
+                            crossterm::event::KeyCode::Char('s') => {
+                                state.sort_column =
+                                    (state.sort_column + 1) % SNAPSHOT_COLUMNS.len();
+                                state.sort_snapshots();
+                            }
+
+                            // reverse the sort order
+                            crossterm::event::KeyCode::Char('S') => {
+                                state.sort_descending = !state.sort_descending;
+                                state.sort_snapshots();
+                            }
+
                             // toggle command palette on ":"
                             crossterm::event::KeyCode::Char(':') => match state.view {
                                 TUIView::CommandPalette => {
@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+
+use ethers::{
+    abi::{decode as decode_abi, ParamType, Token},
+    types::U256,
+};
+use heimdall_common::{
+    ether::{
+        evm::core::types::parse_function_parameters,
+        signatures::{score_signature, ResolvedFunction, ResolvedLog},
+    },
+    utils::{
+        io::logging::TraceFactory,
+        strings::{decode_hex, encode_hex, sign_uint},
+    },
+};
+use serde::Serialize;
+use serde_json::Value;
+
+/// A single call frame of a `callTracer` trace, along with the frames and logs it produced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CallFrame {
+    pub call_type: String,
+    pub from: String,
+    pub to: String,
+    pub value: U256,
+    pub gas: u128,
+    pub gas_used: u128,
+
+    /// The frame's calldata and returndata, hex encoded without the `0x` prefix.
+    pub input: String,
+    pub output: String,
+
+    /// The error which caused the frame to revert, if any.
+    pub error: Option<String>,
+    pub calls: Vec<CallFrame>,
+    pub logs: Vec<CallLog>,
+}
+
+/// A log emitted by a [`CallFrame`]. The position is the number of subcalls the frame made before
+/// emitting the log, if the tracer reports it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct CallLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub position: Option<usize>,
+}
+
+/// Parse a hex quantity, such as `0x5208`, as returned by the tracer.
+fn parse_quantity(value: Option<&Value>) -> U256 {
+    value
+        .and_then(|value| value.as_str())
+        .and_then(|value| U256::from_str_radix(value.trim_start_matches("0x"), 16).ok())
+        .unwrap_or_default()
+}
+
+/// Parse a hex string, returning it lowercased and without the `0x` prefix.
+fn parse_hex(value: Option<&Value>) -> String {
+    value
+        .and_then(|value| value.as_str())
+        .map(|value| value.trim_start_matches("0x").to_lowercase())
+        .unwrap_or_default()
+}
+
+impl CallFrame {
+    /// Parse the JSON result of `debug_traceTransaction` with the `callTracer` into a
+    /// [`CallFrame`].
+    pub fn from_value(value: &Value) -> Option<CallFrame> {
+        let object = value.as_object()?;
+
+        Some(CallFrame {
+            call_type: object.get("type")?.as_str()?.to_uppercase(),
+            from: format!("0x{}", parse_hex(object.get("from"))),
+            to: format!("0x{}", parse_hex(object.get("to"))),
+            value: parse_quantity(object.get("value")),
+            gas: parse_quantity(object.get("gas")).try_into().unwrap_or(u128::MAX),
+            gas_used: parse_quantity(object.get("gasUsed")).try_into().unwrap_or(u128::MAX),
+            input: parse_hex(object.get("input")),
+            output: parse_hex(object.get("output")),
+            error: object.get("error").and_then(|error| error.as_str()).map(String::from),
+            calls: object
+                .get("calls")
+                .and_then(|calls| calls.as_array())
+                .map(|calls| calls.iter().filter_map(CallFrame::from_value).collect())
+                .unwrap_or_default(),
+            logs: object
+                .get("logs")
+                .and_then(|logs| logs.as_array())
+                .map(|logs| {
+                    logs.iter()
+                        .map(|log| CallLog {
+                            address: format!("0x{}", parse_hex(log.get("address"))),
+                            topics: log
+                                .get("topics")
+                                .and_then(|topics| topics.as_array())
+                                .map(|topics| {
+                                    topics.iter().map(|topic| parse_hex(Some(topic))).collect()
+                                })
+                                .unwrap_or_default(),
+                            data: parse_hex(log.get("data")),
+                            position: log.get("position").and_then(|position| match position {
+                                Value::String(position) => {
+                                    usize::from_str_radix(position.trim_start_matches("0x"), 16)
+                                        .ok()
+                                }
+                                position => position.as_u64().map(|position| position as usize),
+                            }),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        })
+    }
+
+    /// The function selectors called by this frame and its subcalls.
+    pub fn selectors(&self) -> Vec<String> {
+        let mut selectors = Vec::new();
+        if let Some(selector) = self.input.get(0..8) {
+            selectors.push(selector.to_string());
+        }
+        for call in &self.calls {
+            selectors.extend(call.selectors());
+        }
+        selectors.sort();
+        selectors.dedup();
+        selectors
+    }
+
+    /// The event topics emitted by this frame and its subcalls.
+    pub fn event_topics(&self) -> Vec<String> {
+        let mut topics: Vec<String> =
+            self.logs.iter().filter_map(|log| log.topics.first().cloned()).collect();
+        for call in &self.calls {
+            topics.extend(call.event_topics());
+        }
+        topics.sort();
+        topics.dedup();
+        topics
+    }
+}
+
+/// Format a decoded token for display within a trace.
+pub fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{address:?}"),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            format!("0x{}", encode_hex(bytes.to_vec()))
+        }
+        Token::Uint(value) => value.to_string(),
+        Token::Int(value) => sign_uint(*value).to_string(),
+        Token::String(string) => format!("{string:?}"),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            format!("[{}]", tokens.iter().map(format_token).collect::<Vec<String>>().join(", "))
+        }
+        Token::Tuple(tokens) => {
+            format!("({})", tokens.iter().map(format_token).collect::<Vec<String>>().join(", "))
+        }
+        token => token.to_string(),
+    }
+}
+
+/// Decode the frame's calldata with the highest scoring resolved signature which can decode it,
+/// returning the function's name and its formatted arguments.
+pub fn decode_call(
+    frame: &CallFrame,
+    resolved_functions: &HashMap<String, Vec<ResolvedFunction>>,
+) -> (String, Vec<String>) {
+    let selector = match frame.input.get(0..8) {
+        Some(selector) => selector,
+        None => {
+            return (
+                if frame.input.is_empty() && !frame.value.is_zero() {
+                    "receive"
+                } else {
+                    "fallback"
+                }
+                .to_string(),
+                Vec::new(),
+            )
+        }
+    };
+    let arguments = decode_hex(&frame.input[8..]).unwrap_or_default();
+
+    let mut candidates = resolved_functions.get(selector).cloned().unwrap_or_default();
+    candidates.sort_by_key(|function| std::cmp::Reverse(score_signature(&function.signature)));
+    for candidate in candidates {
+        let types = parse_function_parameters(&candidate.signature).unwrap_or_default();
+        if let Ok(tokens) = decode_abi(&types, &arguments) {
+            return (candidate.name, tokens.iter().map(format_token).collect())
+        }
+    }
+
+    (
+        format!("0x{selector}"),
+        match frame.input.len() > 8 {
+            true => vec![format!("0x{}", &frame.input[8..])],
+            false => Vec::new(),
+        },
+    )
+}
+
+/// Decode a log with the highest scoring resolved event signature which can decode it, assuming
+/// the event's leading parameters are indexed. Returns the event's name and formatted arguments.
+pub fn decode_log(
+    log: &CallLog,
+    resolved_events: &HashMap<String, Vec<ResolvedLog>>,
+) -> Option<(String, Vec<String>)> {
+    let mut candidates = resolved_events.get(log.topics.first()?).cloned().unwrap_or_default();
+    candidates.sort_by_key(|event| std::cmp::Reverse(score_signature(&event.signature)));
+
+    let indexed_count = log.topics.len() - 1;
+    let data = decode_hex(&log.data).ok()?;
+    candidates.into_iter().find_map(|candidate| {
+        let types = parse_function_parameters(&candidate.signature).unwrap_or_default();
+        if types.len() < indexed_count {
+            return None
+        }
+
+        // indexed dynamic types are hashed, so they can't be decoded
+        let mut arguments = Vec::new();
+        for (topic, kind) in log.topics[1..].iter().zip(types.iter()) {
+            arguments.push(match kind {
+                ParamType::String |
+                ParamType::Bytes |
+                ParamType::Array(_) |
+                ParamType::Tuple(_) => {
+                    format!("0x{topic}")
+                }
+                kind => format_token(
+                    decode_abi(std::slice::from_ref(kind), &decode_hex(topic).ok()?)
+                        .ok()?
+                        .first()?,
+                ),
+            });
+        }
+        let tokens = decode_abi(&types[indexed_count..], &data).ok()?;
+        arguments.extend(tokens.iter().map(format_token));
+
+        Some((candidate.name, arguments))
+    })
+}
+
+/// Decode the reason a frame reverted with, if it reverted with an `Error(string)` or
+/// `Panic(uint256)`.
+pub fn decode_revert_reason(output: &str) -> Option<String> {
+    let data = decode_hex(output.get(8..)?).ok()?;
+    match output.get(0..8)? {
+        "08c379a0" => match decode_abi(&[ParamType::String], &data).ok()?.first()? {
+            Token::String(reason) => Some(reason.to_string()),
+            _ => None,
+        },
+        "4e487b71" => match decode_abi(&[ParamType::Uint(256)], &data).ok()?.first()? {
+            Token::Uint(code) => Some(format!("panic code 0x{code:02x}")),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Add a log to the trace, decoding it if its event signature was resolved.
+fn add_log(
+    log: &CallLog,
+    resolved_events: &HashMap<String, Vec<ResolvedLog>>,
+    trace: &mut TraceFactory,
+    parent: u32,
+) {
+    match decode_log(log, resolved_events) {
+        Some((name, arguments)) => trace.add_emission(parent, 0, name, arguments),
+        None => trace.add_raw_emission(
+            parent,
+            0,
+            log.topics.iter().map(|topic| format!("0x{topic}")).collect(),
+            format!("0x{}", log.data),
+        ),
+    };
+}
+
+/// Recursively add the frame, its subcalls, and its logs to the trace. Each call is labelled with
+/// the gas it used, and logs are placed between subcalls according to their position.
+pub fn build_trace(
+    frame: &CallFrame,
+    resolved_functions: &HashMap<String, Vec<ResolvedFunction>>,
+    resolved_events: &HashMap<String, Vec<ResolvedLog>>,
+    trace: &mut TraceFactory,
+    parent: u32,
+) -> u32 {
+    let gas_used = frame.gas_used.try_into().unwrap_or(u32::MAX);
+    let returns = match (&frame.error, frame.output.is_empty()) {
+        (Some(error), _) => match decode_revert_reason(&frame.output) {
+            Some(reason) => format!("{error}: {reason}"),
+            None => error.to_string(),
+        },
+        (None, true) => "()".to_string(),
+        (None, false) => format!("0x{}", frame.output),
+    };
+
+    let frame_index = if frame.call_type.starts_with("CREATE") {
+        trace.add_creation(
+            parent,
+            gas_used,
+            frame.call_type.to_lowercase(),
+            frame.to.clone(),
+            frame.output.len() as u128 / 2,
+        )
+    } else {
+        let (mut name, arguments) = decode_call(frame, resolved_functions);
+        if !frame.value.is_zero() {
+            name = format!("{name}{{value: {}}}", frame.value);
+        }
+        let origin = match frame.call_type.as_str() {
+            "CALL" => frame.to.clone(),
+            call_type => format!("{} [{}]", frame.to, call_type.to_lowercase()),
+        };
+        trace.add_call(parent, gas_used, origin, name, arguments, returns.clone())
+    };
+
+    // place logs which were emitted before each subcall ahead of it
+    let mut logs = frame.logs.iter().peekable();
+    for (index, call) in frame.calls.iter().enumerate() {
+        while let Some(log) = logs.next_if(|log| log.position.is_some_and(|p| p <= index)) {
+            add_log(log, resolved_events, trace, frame_index);
+        }
+        build_trace(call, resolved_functions, resolved_events, trace, frame_index);
+    }
+    for log in logs {
+        add_log(log, resolved_events, trace, frame_index);
+    }
+
+    if frame.error.is_some() {
+        trace.add_error(frame_index, 0, &returns);
+    }
+
+    frame_index
+}
@@ -0,0 +1,134 @@
+pub mod frame;
+
+use std::collections::HashMap;
+
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::{
+    constants::TRANSACTION_HASH_REGEX,
+    ether::{
+        rpc::{rpc_capabilities, rpc_request},
+        selectors::resolve_selectors,
+        signatures::{ResolvedFunction, ResolvedLog},
+    },
+    utils::io::logging::Logger,
+};
+
+use self::frame::{build_trace, CallFrame};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Trace a transaction's calls, with resolved functions, arguments, and logs",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall trace <TARGET> [OPTIONS]"
+)]
+pub struct TraceArgs {
+    /// The transaction hash to trace.
+    #[clap(required = true)]
+    pub target: String,
+
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The RPC provider to use for tracing the transaction. Must support `debug_traceTransaction`.
+    #[clap(long = "rpc-url", short, default_value = "", hide_default_value = true)]
+    pub rpc_url: String,
+
+    /// Whether to skip resolving function selectors and event topics.
+    #[clap(long = "skip-resolving")]
+    pub skip_resolving: bool,
+}
+
+impl TraceArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            target: Some(String::new()),
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            rpc_url: Some(String::new()),
+            skip_resolving: Some(true),
+        }
+    }
+}
+
+/// The entrypoint for the trace module. This will trace the target transaction with the RPC
+/// provider's `callTracer`, and display its call tree with resolved function names, decoded
+/// arguments and logs, and the gas used by each frame.
+pub async fn trace(args: TraceArgs) -> Result<CallFrame, Box<dyn std::error::Error>> {
+    // set logger environment variable if not already set
+    if std::env::var("RUST_LOG").is_err() {
+        std::env::set_var(
+            "RUST_LOG",
+            match args.verbose.log_level() {
+                Some(level) => level.as_str(),
+                None => "SILENT",
+            },
+        );
+    }
+
+    // get a new logger and trace
+    let (logger, mut trace) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    if !TRANSACTION_HASH_REGEX.is_match(&args.target)? {
+        logger.error(&format!("target '{}' is not a valid transaction hash.", &args.target));
+        std::process::exit(1);
+    }
+
+    if !rpc_capabilities(&args.rpc_url).await.debug {
+        logger.error(&format!(
+            "the RPC provider '{}' doesn't support `debug_traceTransaction`, which is required to trace transactions.",
+            &args.rpc_url
+        ));
+        std::process::exit(1);
+    }
+
+    let result = match rpc_request(
+        &args.rpc_url,
+        "debug_traceTransaction",
+        serde_json::json!([
+            args.target,
+            { "tracer": "callTracer", "tracerConfig": { "withLog": true } }
+        ]),
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            logger.error(&format!("failed to trace transaction '{}': {e}", &args.target));
+            std::process::exit(1);
+        }
+    };
+    let call_frame = match CallFrame::from_value(&result) {
+        Some(call_frame) => call_frame,
+        None => {
+            logger.error("the RPC provider returned a malformed call trace.");
+            std::process::exit(1);
+        }
+    };
+
+    // resolve the selectors and topics used throughout the trace
+    let mut resolved_functions: HashMap<String, Vec<ResolvedFunction>> = HashMap::new();
+    let mut resolved_events: HashMap<String, Vec<ResolvedLog>> = HashMap::new();
+    if !args.skip_resolving {
+        resolved_functions = resolve_selectors(call_frame.selectors()).await;
+        resolved_events = resolve_selectors(call_frame.event_topics()).await;
+        logger.info(&format!(
+            "resolved {} function selectors and {} event topics.",
+            resolved_functions.len(),
+            resolved_events.len()
+        ));
+    }
+
+    build_trace(&call_frame, &resolved_functions, &resolved_events, &mut trace, 0);
+
+    // force the trace to display
+    trace.level = 4;
+    trace.display();
+    logger.info(&format!("transaction used {} gas.", call_frame.gas_used));
+
+    Ok(call_frame)
+}
@@ -0,0 +1,206 @@
+use clap::{AppSettings, Parser};
+use derive_builder::Builder;
+use heimdall_common::utils::{
+    http::{get_bytes_from_url, get_json_from_url},
+    io::logging::Logger,
+    strings::encode_hex,
+};
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, Clone, Parser, Builder)]
+#[clap(
+    about = "Update heimdall to the latest release, or to a specific version",
+    after_help = "For more information, read the wiki: https://jbecker.dev/r/heimdall-rs/wiki",
+    global_setting = AppSettings::DeriveDisplayOrder,
+    override_usage = "heimdall update [OPTIONS]"
+)]
+pub struct UpdateArgs {
+    /// Set the output verbosity level, 1 - 5.
+    #[clap(flatten)]
+    pub verbose: clap_verbosity_flag::Verbosity,
+
+    /// The release tag to install, i.e. `0.6.4`. Defaults to the latest release.
+    #[clap(long = "version", default_value = "", hide_default_value = true)]
+    pub version: String,
+}
+
+impl UpdateArgsBuilder {
+    pub fn new() -> Self {
+        Self {
+            verbose: Some(clap_verbosity_flag::Verbosity::new(0, 1)),
+            version: Some(String::new()),
+        }
+    }
+}
+
+/// The GitHub API endpoint for heimdall's releases.
+const RELEASES_URL: &str = "https://api.github.com/repos/Jon-Becker/heimdall-rs/releases";
+
+/// The name of the release asset for the current platform, matching the names used by bifrost.
+pub fn release_asset_name() -> Option<&'static str> {
+    release_asset_name_for(std::env::consts::OS, std::env::consts::ARCH)
+}
+
+/// The name of the release asset for the given OS and architecture, as reported by
+/// [`std::env::consts`], or `None` if no binary is published for that platform.
+///
+/// ```
+/// use heimdall_core::update::release_asset_name_for;
+///
+/// assert_eq!(release_asset_name_for("linux", "x86_64"), Some("heimdall-linux-amd64"));
+/// assert_eq!(release_asset_name_for("macos", "aarch64"), Some("heimdall-macos-arm64"));
+/// assert_eq!(release_asset_name_for("linux", "riscv64"), None);
+/// assert_eq!(release_asset_name_for("windows", "x86_64"), None);
+/// ```
+pub fn release_asset_name_for(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("linux", "x86_64") => Some("heimdall-linux-amd64"),
+        ("linux", "aarch64") => Some("heimdall-linux-arm64"),
+        ("macos", "x86_64") => Some("heimdall-macos-amd64"),
+        ("macos", "aarch64") => Some("heimdall-macos-arm64"),
+        _ => None,
+    }
+}
+
+/// Find the expected SHA-256 digest of `asset` in a `sha256sum`-style checksum file of
+/// `<digest>  <file name>` lines. Lines which don't name `asset` exactly are ignored, so a bare
+/// digest is never trusted.
+///
+/// ```
+/// use heimdall_core::update::parse_checksum;
+///
+/// let checksums = "0000000000000000000000000000000000000000000000000000000000000001  heimdall-linux-amd64";
+/// assert_eq!(
+///     parse_checksum(checksums, "heimdall-linux-amd64"),
+///     Some(String::from("0000000000000000000000000000000000000000000000000000000000000001"))
+/// );
+/// assert_eq!(parse_checksum(checksums, "heimdall-macos-amd64"), None);
+/// assert_eq!(parse_checksum(checksums, "heimdall-linux-amd64.sha256"), None);
+/// assert_eq!(
+///     parse_checksum("0000000000000000000000000000000000000000000000000000000000000001", "heimdall-linux-amd64"),
+///     None
+/// );
+/// ```
+pub fn parse_checksum(contents: &str, asset: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let digest = parts.next()?.to_lowercase();
+        let name = parts.next()?.trim_start_matches('*');
+
+        let valid = digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit());
+        (valid && name == asset && parts.next().is_none()).then_some(digest)
+    })
+}
+
+/// The entrypoint for the update module. This will download the release binary for the current
+/// platform, verify it against the release's published checksum, and replace the running binary.
+pub async fn update(args: UpdateArgs) -> Result<String, Box<dyn std::error::Error>> {
+    // get a new logger
+    let (logger, _) = Logger::new(match args.verbose.log_level() {
+        Some(level) => level.as_str(),
+        None => "SILENT",
+    });
+
+    let asset_name = match release_asset_name() {
+        Some(asset_name) => asset_name,
+        None => {
+            logger.error(&format!(
+                "no release binaries are published for '{}-{}', use bifrost to build from source.",
+                std::env::consts::OS,
+                std::env::consts::ARCH
+            ));
+            std::process::exit(1)
+        }
+    };
+
+    // fetch the requested release, or the latest one
+    let release_url = if args.version.is_empty() {
+        format!("{RELEASES_URL}/latest")
+    } else {
+        format!("{RELEASES_URL}/tags/{}", args.version)
+    };
+    let release = match get_json_from_url(&release_url, 10).await? {
+        Some(release) if release["tag_name"].is_string() => release,
+        _ => {
+            logger.error(&format!(
+                "failed to find release '{}' .",
+                if args.version.is_empty() { "latest" } else { &args.version }
+            ));
+            std::process::exit(1)
+        }
+    };
+    let tag = release["tag_name"].as_str().unwrap_or_default().to_string();
+    logger.info(&format!("installing heimdall {tag} ."));
+
+    // find the binary and checksum assets for this platform
+    let assets = release["assets"].as_array().cloned().unwrap_or_default();
+    let asset_url = |name: &str| {
+        assets
+            .iter()
+            .find(|asset| asset["name"].as_str() == Some(name))
+            .and_then(|asset| asset["browser_download_url"].as_str())
+            .map(|url| url.to_string())
+    };
+    let binary_url = match asset_url(asset_name) {
+        Some(url) => url,
+        None => {
+            logger.error(&format!("release {tag} doesn't contain a '{asset_name}' binary."));
+            std::process::exit(1)
+        }
+    };
+    let checksum_url =
+        match asset_url(&format!("{asset_name}.sha256")).or_else(|| asset_url("checksums.txt")) {
+            Some(url) => url,
+            None => {
+                logger.error(&format!("release {tag} doesn't publish checksums."));
+                logger.error("refusing to install an unverified binary.");
+                std::process::exit(1)
+            }
+        };
+
+    // download and verify the binary
+    logger.debug(&format!("downloading '{binary_url}' ."));
+    let binary = match get_bytes_from_url(&binary_url, 300).await? {
+        Some(binary) => binary,
+        None => {
+            logger.error(&format!("failed to download '{binary_url}' ."));
+            std::process::exit(1)
+        }
+    };
+    let expected = match get_bytes_from_url(&checksum_url, 10).await? {
+        Some(checksums) => parse_checksum(&String::from_utf8_lossy(&checksums), asset_name),
+        None => None,
+    };
+    let actual = encode_hex(Sha256::digest(&binary).to_vec());
+    match expected {
+        Some(expected) if expected == actual => {
+            logger.debug(&format!("verified checksum '{actual}' ."));
+        }
+        Some(expected) => {
+            logger.error(&format!("checksum mismatch, expected '{expected}' but got '{actual}' ."));
+            std::process::exit(1)
+        }
+        None => {
+            logger.error(&format!("failed to find the checksum of '{asset_name}' ."));
+            std::process::exit(1)
+        }
+    }
+
+    // write the new binary next to the current one, then swap it into place
+    let current_exe = std::env::current_exe()?;
+    let staged_exe = current_exe.with_extension("update");
+    std::fs::write(&staged_exe, &binary)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&staged_exe, std::fs::Permissions::from_mode(0o755))?;
+    }
+    if let Err(e) = std::fs::rename(&staged_exe, &current_exe) {
+        let _ = std::fs::remove_file(&staged_exe);
+        logger.error(&format!("failed to replace '{}': {e}", current_exe.display()));
+        std::process::exit(1)
+    }
+
+    logger.success(&format!("updated heimdall to {tag} ."));
+    Ok(tag)
+}
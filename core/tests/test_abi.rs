@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod integration_tests {
+    use clap_verbosity_flag::Verbosity;
+    use heimdall_core::{
+        abi::{abi, AbiArgs},
+        decompile::out::abi::ABIStructure,
+    };
+
+    #[tokio::test]
+    async fn test_abi_from_bytecode() {
+        // 0x11111111 stores its first argument and emits a `Transfer` event, while 0x22222222
+        // returns the stored value
+        let args = AbiArgs {
+            target: String::from("6004361060215760003560e01c80631111111114602657806322222222146053575b600080fd5b6004356000557fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef600080a1005b60005460005260206000f3"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            skip_resolving: true,
+            block: 0,
+            vm: String::new(),
+        };
+
+        let result = abi(args).await.unwrap();
+        let functions = result
+            .abi
+            .iter()
+            .filter_map(|x| match x {
+                ABIStructure::Function(function) => {
+                    Some((function.name.as_str(), function.state_mutability.as_str()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let events = result
+            .abi
+            .iter()
+            .filter_map(|x| match x {
+                ABIStructure::Event(event) => Some(event.name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            functions,
+            vec![("Unresolved_11111111", "payable"), ("Unresolved_22222222", "view")]
+        );
+        assert_eq!(events, vec!["Event_ddf252ad"]);
+    }
+
+    #[tokio::test]
+    async fn test_abi_from_eravm_bytecode() {
+        // a single instruction, followed by a constant holding the `transfer` selector
+        let args = AbiArgs {
+            target: String::from("00200004032121a3000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a9059cbb"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            skip_resolving: true,
+            block: 0,
+            vm: String::from("eravm"),
+        };
+
+        let result = abi(args).await.unwrap();
+        let functions = result
+            .abi
+            .iter()
+            .filter_map(|x| match x {
+                ABIStructure::Function(function) => Some(function.name.as_str()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(functions, vec!["Unresolved_a9059cbb"]);
+    }
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod integration_tests {
+    use heimdall_common::utils::io::file::{delete_path, write_file};
+    use heimdall_core::batch::{batch, BatchArgsBuilder};
+
+    #[tokio::test]
+    async fn test_batch_duplicate_targets() {
+        let targets_path = String::from("./output/tests/batch/targets.txt");
+        write_file(
+            &targets_path,
+            "# dispatches 0x12345678, with a receive and fallback\n60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500\n0x60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500\n",
+        );
+
+        let results = batch(
+            BatchArgsBuilder::new()
+                .targets(targets_path)
+                .skip_resolving(true)
+                .jobs(2)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        for result in &results {
+            assert_eq!(result.error, None);
+            assert_eq!(result.bytecode_size, 60);
+            assert_eq!(result.selectors, vec!["12345678"]);
+            assert_eq!(result.functions, 1);
+        }
+        assert_eq!(results[0].duplicate_of, None);
+        assert_eq!(results[1].duplicate_of, Some(results[0].target.clone()));
+        assert_eq!(results[1].duration_ms, 0);
+
+        delete_path(&String::from("./output/tests/batch"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_unconfigured_chain() {
+        let targets_path = String::from("./output/tests/batch_chain/targets.txt");
+        write_file(&targets_path, "base:0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\n6001600055\n");
+
+        let results = batch(
+            BatchArgsBuilder::new()
+                .targets(targets_path)
+                .operation(String::from("selectors"))
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].chain, Some(String::from("base")));
+        assert!(results[0].error.as_ref().is_some_and(|e| e.contains("isn't configured")));
+        assert_eq!(results[1].error, None);
+        assert_eq!(results[1].bytecode_size, 5);
+
+        delete_path(&String::from("./output/tests/batch_chain"));
+    }
+}
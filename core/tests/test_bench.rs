@@ -0,0 +1,50 @@
+#[cfg(test)]
+mod integration_tests {
+    use heimdall_common::utils::io::file::{delete_path, write_file};
+    use heimdall_core::bench::{bench, BenchArgsBuilder};
+
+    #[tokio::test]
+    async fn test_bench_local_corpus() {
+        let corpus_path = String::from("./output/tests/bench/corpus.txt");
+        write_file(
+            &corpus_path,
+            "# dispatches 0x12345678, with a receive and fallback\n60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500\n",
+        );
+
+        let results = bench(BenchArgsBuilder::new().corpus(corpus_path).runs(2).build().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 2);
+        for result in results {
+            assert_eq!(result.error, None);
+            assert_eq!(result.bytecode_size, 60);
+            assert_eq!(result.selectors, 1);
+            assert!(result.min_ms <= result.mean_ms && result.mean_ms <= result.max_ms);
+            assert_eq!(result.coverage(), 1.0);
+        }
+
+        delete_path(&String::from("./output/tests/bench"));
+    }
+
+    #[tokio::test]
+    async fn test_bench_address_without_rpc() {
+        let corpus_path = String::from("./output/tests/bench_rpc/corpus.txt");
+        write_file(&corpus_path, "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2\n");
+
+        let results = bench(
+            BenchArgsBuilder::new()
+                .corpus(corpus_path)
+                .modules(String::from("snapshot"))
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(results[0].error.is_some());
+
+        delete_path(&String::from("./output/tests/bench_rpc"));
+    }
+}
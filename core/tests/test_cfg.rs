@@ -14,6 +14,7 @@ mod benchmark {
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
                 color_edges: false,
+                tui: false,
                 format: String::from("png"),
             };
             let _ = heimdall_core::cfg::cfg(args).await;
@@ -31,6 +32,7 @@ mod benchmark {
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
                 color_edges: false,
+                tui: false,
                 format: String::from("png"),
             };
             let _ = heimdall_core::cfg::cfg(args).await;
@@ -43,8 +45,13 @@ mod benchmark {
 #[cfg(test)]
 mod integration_tests {
     use clap_verbosity_flag::Verbosity;
-    use heimdall_core::cfg::CFGArgs;
-    use petgraph::dot::Dot;
+    use heimdall_common::ether::evm::core::vm::VM;
+    use heimdall_core::{
+        cfg::{graph::build_blocks, stats::CFGStats, CFGArgs},
+        decompile::DecompilerArgsBuilder,
+    };
+    use petgraph::{dot::Dot, graph::NodeIndex, Graph};
+    use std::collections::BTreeMap;
 
     #[tokio::test]
     async fn test_cfg_simple() {
@@ -54,12 +61,13 @@ mod integration_tests {
             rpc_url: String::from("https://eth.llamarpc.com"),
             default: true,
             color_edges: false,
+            tui: false,
             format: String::from("png"),
         })
         .await
         .unwrap();
 
-        let output: String = format!("{}", Dot::with_config(&result, &[]));
+        let output: String = format!("{}", Dot::with_config(&result.graph, &[]));
 
         for line in &[
             String::from("0 [ label = \"0x01 PUSH20 0x1bf797219482a29013d804ad96d1c6f84fba4c45\\l0x16 ADDRESS \\l0x17 EQ \\l0x18 PUSH1 0x80\\l0x1a PUSH1 0x40\\l0x1c MSTORE \\l0x1d PUSH1 0x04\\l0x1f CALLDATASIZE \\l0x20 LT \\l0x21 PUSH2 0x58\\l0x24 JUMPI \\l\" ]"),
@@ -77,15 +85,151 @@ mod integration_tests {
             rpc_url: String::from("https://eth.llamarpc.com"),
             default: true,
             color_edges: false,
+            tui: false,
             format: String::from("png"),
         })
         .await
         .unwrap();
 
-        let output = format!("{}", Dot::with_config(&result, &[]));
+        let output = format!("{}", Dot::with_config(&result.graph, &[]));
 
         for line in &[String::from("\"0x03a0 JUMPDEST \\l0x03a1 STOP \\l\"")] {
             assert!(output.contains(line))
         }
     }
+
+    #[tokio::test]
+    async fn test_cfg_link_decompiled_source() {
+        // a dispatcher for 0x11111111, which stores its arguments
+        let target = "6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500";
+        let decompiled = heimdall_core::decompile::decompile(
+            DecompilerArgsBuilder::new()
+                .target(target.to_string())
+                .verbose(Verbosity::new(0, 0))
+                .skip_resolving(true)
+                .include_solidity(true)
+                .block_comments(true)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        let source = decompiled.source.unwrap();
+        assert!(source.contains("// block 0x1d"));
+
+        let mut result = heimdall_core::cfg::cfg(CFGArgs {
+            target: target.to_string(),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            color_edges: false,
+            tui: false,
+            format: String::from(""),
+        })
+        .await
+        .unwrap();
+
+        // only the function's block contains statements
+        assert_eq!(result.link_source(&source), 1);
+        let (node, ranges) = result.source_lines.iter().next().unwrap();
+        assert!(result.graph[NodeIndex::new(*node)].starts_with("0x1d JUMPDEST"));
+
+        let lines = source.lines().map(|line| line.trim()).collect::<Vec<&str>>();
+        let (start, end) = ranges[0];
+        assert_eq!(lines[start - 2], "// block 0x1d");
+        assert!(lines[start - 1].starts_with("stor_a = "));
+        assert!(lines[end - 1].starts_with("stor_c = "));
+
+        let output = format!("{}", Dot::with_config(&result.annotated_graph(), &[]));
+        assert!(output.contains(&format!("0x4c STOP \\ldecompiled.sol:{start}-{end}\\l")));
+    }
+
+    #[tokio::test]
+    async fn test_cfg_eof_container() {
+        // a code section which calls the second if calldata is zero, and a 32-byte data section
+        let result = heimdall_core::cfg::cfg(CFGArgs {
+            target: String::from("ef0001010008020002000f0001ff00200000800002000000005f35e10004e3000100d100005f5500e4000000000000000000000000000000000000000000000000000000000000002a"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            color_edges: false,
+            tui: false,
+            format: String::from(""),
+        })
+        .await
+        .unwrap();
+
+        let output = format!("{}", Dot::with_config(&result.graph, &[]));
+        assert!(output.contains("0x19 PUSH0 \\l0x1a CALLDATALOAD \\l0x1b RJUMPI 0x0004\\l"));
+        assert!(output.contains("0x28 RETF \\l"));
+        assert!(output.contains("label = \"call\""));
+        assert_eq!((result.stats.nodes, result.stats.edges), (4, 3));
+
+        // only the code sections are executed, rather than the header or data section
+        assert_eq!(result.stats.unreachable_bytes, 57);
+    }
+
+    #[test]
+    fn test_cfg_blocks() {
+        let evm = VM::new(
+            String::from("600035600c576001600055005b600260005500"),
+            String::from("0x"),
+            String::from("0x6865696d64616c6c000000000061646472657373"),
+            String::from("0x6865696d64616c6c0000000000006f726967696e"),
+            String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+            0,
+            u128::MAX,
+        );
+        let (map, _) = evm.symbolic_exec();
+
+        let mut blocks = BTreeMap::new();
+        build_blocks(&map, &mut blocks, None, false);
+
+        // the JUMPI splits the code into an entry block, a fallthrough block, and a jump target
+        let entry = blocks.values().next().unwrap();
+        assert_eq!(entry.successors.len(), 2);
+        assert!(entry.decompiled.iter().any(|line| line.starts_with("if (")));
+
+        for (start, jump_taken) in &entry.successors {
+            let successor = blocks.get(start).unwrap();
+            assert_eq!(successor.predecessors, vec![entry.start]);
+            assert_eq!(successor.assembly[0].1.contains("JUMPDEST"), *jump_taken);
+            assert!(successor.decompiled.iter().any(|line| line.starts_with("storage[")));
+        }
+    }
+
+    #[test]
+    fn test_cfg_stats() {
+        // a dispatcher for two selectors, one of which loops until it reverts
+        let mut graph = Graph::<String, String>::new();
+        let dispatcher = graph.add_node(String::from(
+            "0 PUSH1 0x00\n0x02 CALLDATALOAD \n0x03 PUSH1 0xe0\n0x05 SHR \n0x06 DUP1 \n0x07 PUSH4 0xa9059cbb\n0x0c EQ \n0x0d PUSH1 0x1e\n0x0f JUMPI \n",
+        ));
+        let next = graph.add_node(String::from(
+            "0x10 DUP1 \n0x11 PUSH4 0x095ea7b3\n0x16 EQ \n0x17 PUSH1 0x23\n0x19 JUMPI \n",
+        ));
+        let fallback = graph.add_node(String::from("0x1a PUSH1 0x00\n0x1c DUP1 \n0x1d REVERT \n"));
+        let transfer =
+            graph.add_node(String::from("0x1e JUMPDEST \n0x1f PUSH1 0x1e\n0x21 JUMP \n"));
+        let approve = graph.add_node(String::from("0x23 JUMPDEST \n0x24 STOP \n"));
+        graph.add_edge(dispatcher, next, String::from("false"));
+        graph.add_edge(dispatcher, transfer, String::from("true"));
+        graph.add_edge(next, fallback, String::from("false"));
+        graph.add_edge(next, approve, String::from("true"));
+        graph.add_edge(transfer, transfer, String::from("true"));
+
+        let stats = CFGStats::new(
+            &graph,
+            "60003560e01c8063a9059cbb14601e578063095ea7b314602357600080fd5b601e56fe5b00",
+        );
+
+        assert_eq!(stats.nodes, 5);
+        assert_eq!(stats.edges, 5);
+        assert_eq!(stats.loops, 1);
+        assert_eq!(stats.largest_block, 9);
+        assert_eq!(stats.dispatcher_fanout, 2);
+
+        // the INVALID between the loop and the final block is never reached
+        assert_eq!(stats.unreachable_bytes, 1);
+    }
 }
@@ -15,7 +15,14 @@ mod benchmark {
                 openai_api_key: String::from(""),
                 explain: false,
                 default: true,
+                non_interactive: false,
                 truncate_calldata: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                template: String::from(""),
+                contract: String::from(""),
+                raw: false,
+                json: false,
             };
             let _ = heimdall_core::decode::decode(args).await;
         }
@@ -33,7 +40,14 @@ mod benchmark {
                 openai_api_key: String::from(""),
                 explain: false,
                 default: true,
+                non_interactive: false,
                 truncate_calldata: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                template: String::from(""),
+                contract: String::from(""),
+                raw: false,
+                json: false,
             };
             let _ = heimdall_core::decode::decode(args).await;
         }
@@ -51,7 +65,14 @@ mod benchmark {
                 openai_api_key: String::from(""),
                 explain: false,
                 default: true,
+                non_interactive: false,
                 truncate_calldata: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                template: String::from(""),
+                contract: String::from(""),
+                raw: false,
+                json: false,
             };
             let _ = heimdall_core::decode::decode(args).await;
         }
@@ -69,7 +90,14 @@ mod benchmark {
                 openai_api_key: String::from(""),
                 explain: false,
                 default: true,
+                non_interactive: false,
                 truncate_calldata: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                template: String::from(""),
+                contract: String::from(""),
+                raw: false,
+                json: false,
             };
             let _ = heimdall_core::decode::decode(args).await;
         }
@@ -92,7 +120,14 @@ mod tests {
             openai_api_key: String::from(""),
             explain: false,
             default: true,
+            non_interactive: false,
             truncate_calldata: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            template: String::from(""),
+            contract: String::from(""),
+            raw: false,
+            json: false,
         };
         let _ = heimdall_core::decode::decode(args).await;
     }
@@ -106,8 +141,418 @@ mod tests {
             openai_api_key: String::from(""),
             explain: false,
             default: true,
+            non_interactive: false,
             truncate_calldata: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            template: String::from(""),
+            contract: String::from(""),
+            raw: false,
+            json: false,
         };
         let _ = heimdall_core::decode::decode(args).await;
     }
+
+    #[test]
+    fn test_safe_signature_recovery() {
+        use ethers::{
+            abi::Token,
+            signers::{LocalWallet, Signer},
+            types::{Address, U256},
+        };
+        use heimdall_core::decode::safe::{
+            parse_signatures, recover_signers, SafeSignatureKind, SafeTransaction,
+        };
+
+        let wallet = "0x0123456789012345678901234567890123456789012345678901234567890123"
+            .parse::<LocalWallet>()
+            .unwrap();
+        let approver = Address::from_low_u64_be(0xbeef);
+        let tokens = vec![
+            Token::Address(Address::from_low_u64_be(1)),
+            Token::Uint(U256::from(1000)),
+            Token::Bytes(vec![0xa9, 0x05, 0x9c, 0xbb]),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Address(Address::zero()),
+            Token::Address(Address::zero()),
+            Token::Bytes(Vec::new()),
+        ];
+        let mut transaction = SafeTransaction::from_tokens(&tokens).unwrap();
+        assert_eq!(transaction.operation_name(), "call");
+
+        // sign the transaction hash, and pack it alongside an approved hash signature
+        let safe = Address::from_low_u64_be(0x5afe);
+        let hash = transaction.hash(safe, Some(1), U256::from(7));
+        let signature = wallet.sign_hash(hash).unwrap();
+        let mut packed = vec![0u8; 12];
+        packed.extend_from_slice(approver.as_bytes());
+        packed.extend_from_slice(&[0u8; 32]);
+        packed.push(1);
+        packed.extend_from_slice(&signature.to_vec());
+        transaction.signatures = packed;
+
+        let mut signatures = parse_signatures(&transaction.signatures);
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].kind, SafeSignatureKind::ApprovedHash);
+        assert_eq!(signatures[0].signer, Some(approver));
+        assert_eq!(signatures[1].kind, SafeSignatureKind::Ecdsa);
+        assert_eq!(signatures[1].signer, None);
+
+        recover_signers(&mut signatures, hash);
+        assert_eq!(signatures[1].signer, Some(wallet.address()));
+    }
+
+    #[test]
+    fn test_safe_contract_signature() {
+        use ethers::types::{Address, U256};
+        use heimdall_core::decode::safe::{parse_signatures, SafeSignatureKind};
+
+        // a contract signature followed by an approved hash, with the contract's 96 bytes of
+        // dynamic signature data appended after both static slots at offset 130
+        let contract = Address::from_low_u64_be(0xc0de);
+        let approver = Address::from_low_u64_be(0xbeef);
+        let mut packed = vec![0u8; 12];
+        packed.extend_from_slice(contract.as_bytes());
+        let mut offset = [0u8; 32];
+        U256::from(130).to_big_endian(&mut offset);
+        packed.extend_from_slice(&offset);
+        packed.push(0);
+        packed.extend_from_slice(&[0u8; 12]);
+        packed.extend_from_slice(approver.as_bytes());
+        packed.extend_from_slice(&[0u8; 32]);
+        packed.push(1);
+        let mut length = [0u8; 32];
+        U256::from(64).to_big_endian(&mut length);
+        packed.extend_from_slice(&length);
+        packed.extend_from_slice(&[0x1b; 64]);
+        assert_eq!(packed.len(), 226);
+
+        let signatures = parse_signatures(&packed);
+        assert_eq!(signatures.len(), 2);
+        assert_eq!(signatures[0].kind, SafeSignatureKind::Contract);
+        assert_eq!(signatures[0].signer, Some(contract));
+        assert_eq!(signatures[0].s, U256::from(130));
+        assert_eq!(signatures[1].kind, SafeSignatureKind::ApprovedHash);
+        assert_eq!(signatures[1].signer, Some(approver));
+    }
+
+    #[tokio::test]
+    async fn test_decode_handle_ops() {
+        use ethers::{
+            abi::{encode, Token},
+            types::{Address, U256},
+            utils::keccak256,
+        };
+        use heimdall_core::decode::erc4337::{parse_handle_ops, HANDLE_OPS_V06_SIGNATURE};
+
+        let sender = Address::from_low_u64_be(0xacc0);
+        let factory = Address::from_low_u64_be(0xfac7);
+        let mut init_code = factory.as_bytes().to_vec();
+        init_code.extend_from_slice(&[0x5f, 0xbf, 0xb9, 0xcf]);
+
+        // execute(address,uint256,bytes) calling transfer(address,uint256)
+        let mut transfer = keccak256("transfer(address,uint256)")[0..4].to_vec();
+        transfer.extend(encode(&[Token::Address(sender), Token::Uint(U256::from(1))]));
+        let mut call_data = keccak256("execute(address,uint256,bytes)")[0..4].to_vec();
+        call_data.extend(encode(&[
+            Token::Address(Address::from_low_u64_be(0x70c3)),
+            Token::Uint(U256::zero()),
+            Token::Bytes(transfer),
+        ]));
+
+        let operation = Token::Tuple(vec![
+            Token::Address(sender),
+            Token::Uint((U256::from(5) << 64) + U256::from(2)),
+            Token::Bytes(init_code),
+            Token::Bytes(call_data),
+            Token::Uint(U256::from(100000)),
+            Token::Uint(U256::from(200000)),
+            Token::Uint(U256::from(50000)),
+            Token::Uint(U256::from(30)),
+            Token::Uint(U256::from(2)),
+            Token::Bytes(Vec::new()),
+            Token::Bytes(vec![0u8; 65]),
+        ]);
+        let inputs = vec![Token::Array(vec![operation]), Token::Address(Address::zero())];
+
+        let (operations, beneficiary) = parse_handle_ops(&inputs).unwrap();
+        assert_eq!(beneficiary, Address::zero());
+        assert_eq!(operations[0].sender, sender);
+        assert_eq!(operations[0].nonce_key_and_sequence(), (U256::from(5), 2));
+        assert_eq!(operations[0].factory().unwrap().0, factory);
+        assert!(operations[0].paymaster().is_none());
+
+        // handleOps is always decodable, even if the selector can't be resolved
+        let mut calldata = keccak256(HANDLE_OPS_V06_SIGNATURE)[0..4].to_vec();
+        calldata.extend(encode(&inputs));
+        let calldata = heimdall_common::utils::strings::encode_hex(calldata);
+        let args = DecodeArgs {
+            target: format!("0x{calldata}"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            openai_api_key: String::from(""),
+            explain: false,
+            default: true,
+            non_interactive: false,
+            truncate_calldata: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            template: String::from(""),
+            contract: String::from(""),
+            raw: false,
+            json: false,
+        };
+        let result = heimdall_core::decode::decode_with_calldata(args).await.unwrap();
+        assert_eq!(result.calldata, calldata);
+        assert!(result.matches.iter().any(|m| m.signature == HANDLE_OPS_V06_SIGNATURE));
+    }
+
+    #[test]
+    fn test_uniswap_router_commands() {
+        use ethers::{
+            abi::{encode, Token},
+            types::{Address, U256},
+        };
+        use heimdall_core::decode::uniswap::{parse_commands, parse_v3_path};
+
+        let weth = Address::from_low_u64_be(0xeeee);
+        let usdc = Address::from_low_u64_be(0xcccc);
+        let dai = Address::from_low_u64_be(0xdddd);
+
+        // weth -(0.05%)-> usdc -(0.01%)-> dai
+        let mut path = weth.as_bytes().to_vec();
+        path.extend_from_slice(&[0x00, 0x01, 0xf4]);
+        path.extend_from_slice(usdc.as_bytes());
+        path.extend_from_slice(&[0x00, 0x00, 0x64]);
+        path.extend_from_slice(dai.as_bytes());
+        let hops = parse_v3_path(&path).unwrap();
+        assert_eq!(hops.len(), 2);
+        assert_eq!((hops[0].token_in, hops[0].fee, hops[0].token_out), (weth, 500, usdc));
+        assert_eq!((hops[1].token_in, hops[1].fee, hops[1].token_out), (usdc, 100, dai));
+        assert_eq!(parse_v3_path(&path[0..42]), None);
+
+        let wrap = encode(&[Token::Address(Address::from_low_u64_be(2)), Token::Uint(U256::one())]);
+        let swap = encode(&[
+            Token::Address(Address::from_low_u64_be(1)),
+            Token::Uint(U256::from(1000)),
+            Token::Uint(U256::from(990)),
+            Token::Bytes(path),
+            Token::Bool(false),
+        ]);
+        let v2_swap = encode(&[
+            Token::Address(Address::from_low_u64_be(1)),
+            Token::Uint(U256::from(1000)),
+            Token::Uint(U256::from(990)),
+            Token::Array(vec![Token::Address(weth), Token::Address(dai)]),
+            Token::Bool(true),
+        ]);
+        let commands = parse_commands(&[0x0b, 0x00, 0x88], &[wrap, swap, v2_swap]);
+
+        assert_eq!(commands[0].name, "WRAP_ETH");
+        assert_eq!(commands[0].swap_path(), None);
+        assert_eq!(commands[1].name, "V3_SWAP_EXACT_IN");
+        assert_eq!(
+            commands[1].swap_path(),
+            Some(format!("{weth:?} → (0.05%) → {usdc:?} → (0.01%) → {dai:?}"))
+        );
+        assert_eq!(commands[2].name, "V2_SWAP_EXACT_IN");
+        assert!(commands[2].allow_revert);
+        assert_eq!(commands[2].swap_path(), Some(format!("{weth:?} → {dai:?}")));
+    }
+
+    #[test]
+    fn test_seaport_match_orders() {
+        use ethers::{
+            abi::Token,
+            types::{Address, U256},
+        };
+        use heimdall_core::decode::seaport::{
+            format_items_table, parse_seaport_call, MATCH_ORDERS_SIGNATURE,
+        };
+
+        let offerer = Address::from_low_u64_be(0xaaaa);
+        let nft = Address::from_low_u64_be(0xbbbb);
+        let uint = |value: u64| Token::Uint(U256::from(value));
+        let order = Token::Tuple(vec![
+            Token::Tuple(vec![
+                Token::Address(offerer),
+                Token::Address(Address::zero()),
+                Token::Array(vec![Token::Tuple(vec![
+                    uint(4),
+                    Token::Address(nft),
+                    uint(0),
+                    uint(1),
+                    uint(1),
+                ])]),
+                Token::Array(vec![Token::Tuple(vec![
+                    uint(0),
+                    Token::Address(Address::zero()),
+                    uint(0),
+                    uint(1000),
+                    uint(500),
+                    Token::Address(offerer),
+                ])]),
+                uint(0),
+                uint(1),
+                uint(2),
+                Token::FixedBytes(vec![0; 32]),
+                uint(0),
+                Token::FixedBytes(vec![0; 32]),
+                uint(1),
+            ]),
+            Token::Bytes(vec![0; 65]),
+        ]);
+        let fulfillment = Token::Tuple(vec![
+            Token::Array(vec![Token::Tuple(vec![uint(0), uint(0)])]),
+            Token::Array(vec![Token::Tuple(vec![uint(1), uint(0)])]),
+        ]);
+
+        let call = parse_seaport_call(
+            MATCH_ORDERS_SIGNATURE,
+            &[Token::Array(vec![order]), Token::Array(vec![fulfillment])],
+        )
+        .unwrap();
+        assert_eq!(call.orders.len(), 1);
+        assert_eq!(call.orders[0].offerer, offerer);
+        assert_eq!(call.orders[0].offer[0].identifier(), "any");
+        assert_eq!(call.orders[0].consideration[0].amount(), "1000 → 500");
+        assert_eq!(call.fulfillments[0].offer_components, vec![(U256::zero(), U256::zero())]);
+
+        let table = format_items_table(&call.orders[0]);
+        assert_eq!(table.len(), 3);
+        assert!(table[0].starts_with("side           #  type"));
+        assert!(table[1].starts_with("offer          0  ERC721_CRITERIA"));
+        assert!(table[1].ends_with("fulfiller"));
+        assert!(table[2].starts_with("consideration  0  NATIVE"));
+    }
+
+    #[tokio::test]
+    async fn test_token_amounts() {
+        use ethers::{
+            abi::Token,
+            types::{Address, U256},
+        };
+        use heimdall_core::decode::amounts::token_amounts;
+
+        let usdc: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap();
+        let weth: Address = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap();
+        let recipient = Address::from_low_u64_be(0xcc);
+
+        // transfer(address,uint256) sent to USDC is paired with the target
+        let amounts = token_amounts(
+            &[Token::Address(recipient), Token::Uint(U256::from(1_000_000))],
+            Some(usdc),
+            "",
+        )
+        .await;
+        assert_eq!(amounts.get(&1).unwrap(), "1.0 USDC");
+        assert_eq!(amounts.len(), 1);
+
+        // token addresses within the call take precedence over the target
+        let amounts = token_amounts(
+            &[
+                Token::Uint(U256::from(2_500_000)),
+                Token::Address(weth),
+                Token::Uint(U256::exp10(17)),
+            ],
+            Some(usdc),
+            "",
+        )
+        .await;
+        assert_eq!(amounts.get(&0).unwrap(), "2.5 USDC");
+        assert_eq!(amounts.get(&2).unwrap(), "0.1 WETH");
+
+        // without a known token, nothing is rendered
+        let amounts = token_amounts(&[Token::Uint(U256::from(1))], Some(recipient), "").await;
+        assert!(amounts.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_annotate_inputs() {
+        use ethers::{
+            abi::Token,
+            types::{Address, U256},
+        };
+        use heimdall_core::decode::annotate::annotate_inputs;
+
+        let usdc: Address = "0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".parse().unwrap();
+        let recipient = Address::from_low_u64_be(0xcc);
+
+        // approve(address,uint256) with an infinite allowance, sent to an unknown contract
+        let annotations = annotate_inputs(
+            &[Token::Address(recipient), Token::Uint(U256::MAX)],
+            Some(recipient),
+            "",
+        )
+        .await;
+        assert_eq!(annotations.get(&1).unwrap(), "type(uint256).max");
+
+        // token amounts take precedence over other annotations
+        let annotations = annotate_inputs(
+            &[
+                Token::Uint(U256::exp10(18)),
+                Token::Uint(U256::from(1700000000)),
+                Token::Uint(U256::from(7)),
+            ],
+            Some(usdc),
+            "",
+        )
+        .await;
+        assert_eq!(annotations.get(&0).unwrap(), "1000000000000.0 USDC");
+        assert_eq!(annotations.get(&1).unwrap(), "1700.0 USDC");
+
+        // without a token, wei and timestamps are rendered as such
+        let annotations = annotate_inputs(
+            &[
+                Token::Uint(U256::exp10(18)),
+                Token::Uint(U256::from(1700000000)),
+                Token::Uint(U256::from(7)),
+            ],
+            None,
+            "",
+        )
+        .await;
+        assert_eq!(annotations.get(&0).unwrap(), "1.0 ether");
+        assert_eq!(annotations.get(&1).unwrap(), "2023-11-14T22:13:20Z");
+        assert!(!annotations.contains_key(&2));
+    }
+
+    #[test]
+    fn test_transaction_summary() {
+        use ethers::types::{Address, Transaction, TransactionReceipt, U256, U64};
+        use heimdall_core::decode::summary::TransactionSummary;
+
+        let transaction = Transaction {
+            from: Address::from_low_u64_be(0xaa),
+            to: Some(Address::from_low_u64_be(0xbb)),
+            value: U256::exp10(17),
+            block_number: Some(U64::from(17000000)),
+            ..Default::default()
+        };
+        let receipt = TransactionReceipt {
+            gas_used: Some(U256::from(46109)),
+            status: Some(U64::from(1)),
+            ..Default::default()
+        };
+
+        let lines = TransactionSummary::new(&transaction, Some(&receipt)).lines();
+        assert_eq!(
+            lines,
+            vec![
+                "from:      0x00000000000000000000000000000000000000aa",
+                "to:        0x00000000000000000000000000000000000000bb",
+                "value:     0.1 ether",
+                "block:     17000000",
+                "gas used:  46109",
+                "status:    success",
+            ]
+        );
+
+        // pending transactions have no receipt
+        let summary = TransactionSummary::new(&Transaction::default(), None);
+        assert_eq!(summary.lines()[5], "status:    pending");
+    }
 }
@@ -13,9 +13,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: true,
                 include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -31,9 +54,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: true,
                 include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -49,9 +95,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: false,
                 include_yul: true,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -67,9 +136,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: false,
                 include_yul: true,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -85,9 +177,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: false,
                 include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -103,9 +218,32 @@ mod benchmark {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: false,
                 include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             };
             let _ = heimdall_core::decompile::decompile(args).await;
         }
@@ -118,7 +256,9 @@ mod benchmark {
 mod integration_tests {
     use clap_verbosity_flag::Verbosity;
     use heimdall_common::utils::io::file::delete_path;
-    use heimdall_core::decompile::DecompilerArgs;
+    use heimdall_core::decompile::{
+        out::abi::ABIStructure, verify::compare_bytecode, DecompilerArgs,
+    };
 
     #[tokio::test]
     async fn test_decompile_precompile() {
@@ -127,9 +267,32 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from("https://eth.llamarpc.com"),
             default: true,
+            non_interactive: false,
             skip_resolving: true,
             include_solidity: true,
             include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
         })
         .await
         .unwrap();
@@ -147,6 +310,1280 @@ mod integration_tests {
         delete_path(&String::from("./output/tests/decompile/test1"));
     }
 
+    #[tokio::test]
+    async fn test_decompile_dead_code() {
+        // 0x11111111 always reverts, and the block at 0x21 is never jumped to
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from(
+                "6004361060175760003560e01c80631111111114601c575b600080fd5b600080fd5b600160005500",
+            ),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("/// @notice             this function always reverts"));
+        assert!(source.contains("/// @custom:warning   1 function(s) always revert"));
+        assert!(source.contains("1 block(s) are never reached by symbolic execution: 0x21."));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_ether_flow() {
+        // call{value: msg.value}(msg.sender), then call{value: selfbalance()}(calldataload(4))
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from(
+                "6004361060175760003560e01c80631111111114601c575b600080fd5b600060006000600034335af1506000600060006000476004355af15000",
+            ),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains(
+            "/// @notice             receives ether, forwards msg.value to msg.sender, sends the contract's balance to arg0"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_call_failure() {
+        // a call to calldataload(4) whose failure is bubbled up, reverts, or is handled by
+        // writing to storage, followed by a write on success
+        let dispatcher = "6004361060175760003560e01c80631111111114601c575b600080fd5b600060006000600060006004355af1";
+        let cases = [
+            (
+                "6039573d600060003e3d6000fd5b600160005500",
+                vec!["if (!success) {", "assembly { revert(add(ret0, 0x20), mload(ret0)) }"],
+            ),
+            ("60345760006000fd5b600160005500", vec!["require(success);"]),
+            ("6035576002600055005b600160005500", vec!["if (!success) {", "stor_a = 0x02;"]),
+        ];
+
+        for (failure_handling, expected) in cases {
+            let result = heimdall_core::decompile::decompile(DecompilerArgs {
+                target: format!("{dispatcher}{failure_handling}"),
+                verbose: Verbosity::new(0, 0),
+                rpc_url: String::from(""),
+                default: true,
+                non_interactive: false,
+                skip_resolving: true,
+                include_solidity: true,
+                include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
+            })
+            .await
+            .unwrap();
+
+            let source = result.source.unwrap();
+            for line in expected {
+                assert!(source.contains(line));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompile_bn254_precompiles() {
+        // 0x55555555 adds the point (arg0, arg1) to (1, 2) with ecAdd, then checks a pairing
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80635555555514601c575b600080fd5b60016000526002602052600435604052602435606052604060806080600060065afa506080516000556020600060c0600060085afa5060005160015500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("= ecAdd(0x01, 0x02, arg0, arg1);"));
+        assert!(source.contains("bool var_a = ecPairing("));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_constructor() {
+        // init code which stores msg.sender, copies the runtime code to memory, writes the
+        // immutable 0x2a into it, and returns it. a single address argument is appended.
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("336000556015806015600039602a6005526000f3fe6080604052600080fda164736f6c6343000813000a000000000000000000000000c02aaa39b223fe8d0a0e5c4f27ead9083c756cc2"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: true,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        for line in &[
+            "constructor(address arg0) payable {",
+            "msg.sender;",
+            "immutable_0 = 0x2a;",
+            "immutable_0 is embedded at offset 5 of the runtime code.",
+            "arg0 is encoded at offset 0 of the constructor arguments",
+        ] {
+            assert!(source.contains(line), "missing '{line}'");
+        }
+        assert!(!source.contains("this.code"));
+
+        let abi = result.abi.unwrap();
+        assert!(abi.iter().any(|structure| matches!(
+            structure,
+            ABIStructure::Function(function) if function.type_ == "constructor" && function.inputs.len() == 1
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_receive_and_fallback() {
+        // dispatches 0x12345678, an empty calldata receive which stores msg.value, and a
+        // non-payable fallback
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("receive() external payable {"));
+        assert!(source.contains("fallback() external {"));
+
+        let abi = result.abi.unwrap();
+        let types = abi
+            .iter()
+            .filter_map(|item| match item {
+                ABIStructure::Function(function) => {
+                    Some((function.type_.as_str(), function.state_mutability.as_str()))
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert!(types.contains(&("receive", "payable")));
+        assert!(types.contains(&("fallback", "nonpayable")));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_nonpayable_prologue() {
+        // rejects call value before dispatching 0x12345678, which returns storage slot 0
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("348015600a57600080fd5b506004361060225760003560e01c6312345678146027575b600080fd5b60005460005260206000f3"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("function Unresolved_12345678() public view returns"));
+
+        let abi = result.abi.unwrap();
+        assert!(abi.iter().any(|item| match item {
+            ABIStructure::Function(function) => {
+                function.name == "Unresolved_12345678" && function.state_mutability == "view"
+            }
+            _ => false,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_via_ir() {
+        // via-IR shaped code, which checks the calldata size before dispatching, and jumps to a
+        // shared revert block on call value, on a short ABI-encoded size, and unless arg0 > 5
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("608060405260043610156010575f80fd5b5f3560e01c806312345678146023575f80fd5b34604457602036600319011260445760056004351115604457600435600055005b5f80fda164736f6c6343000813000a"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("require(arg0 > 0x05);"));
+        assert!(source.contains("stor_a = arg0;"));
+        assert!(!source.contains("msg.value"));
+        assert!(!source.contains("msg.data.length"));
+
+        let abi = result.abi.unwrap();
+        assert!(abi.iter().any(|structure| matches!(
+            structure,
+            ABIStructure::Function(function) if function.state_mutability == "nonpayable"
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_legacy_dispatcher() {
+        // solc < 0.4.10 shaped code, which divides calldata by 2 ** 0xe0 to get the selector, and
+        // throws by jumping to an invalid destination on call value, and unless arg0 > 5
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("606060405260e060020a6000350463123456788114601957005b34156022576002565b600560043511602f576002565b60043560005500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("function Unresolved_12345678("));
+        assert!(source.contains("require(arg0 > 0x05);"));
+        assert!(source.contains("stor_a = arg0;"));
+        assert!(!source.contains("msg.value"));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_call_value_guard() {
+        // 0x12345678 reverts unless `0 == msg.value`, which is the non-payable guard rather than a
+        // read of the call value
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from(
+                "60003560e01c8063123456781461001257005b3460001461001f57600080fd5b600435600055",
+            ),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        assert!(source.contains("function Unresolved_12345678(bytes memory arg0) public {"));
+        assert!(source.contains("stor_a = arg0;"));
+        assert!(!source.contains("msg.value"));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_unchecked_arithmetic() {
+        // solc 0.8.19 metadata. 0x11111111 adds to storage slot 0 with an overflow check, while
+        // 0x22222222 adds to storage slot 1 without one
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060215760003560e01c8063111111111460265780632222222214604f575b600080fd5b6004356000548119811160395701600055005b634e487b7160e01b600052601160045260246000fd5b6004356001540160015500a164736f6c6343000813000a"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        // functions are emitted in no particular order, so find each body by name
+        let source = result.source.unwrap();
+        let body = |name: &str| {
+            let start = source.find(&format!("function {name}(")).unwrap();
+            let end = source[start..].find("\n    }\n").unwrap();
+            source[start..start + end].to_string()
+        };
+        assert!(!body("Unresolved_11111111").contains("unchecked {"));
+        assert!(body("Unresolved_22222222").contains("unchecked {"));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_metamorphic_warning() {
+        // a function which calls selfdestruct(msg.sender)
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b33ff"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert!(result.metamorphic.selfdestructs);
+        assert!(result.metamorphic.is_metamorphic());
+        assert!(result
+            .source
+            .unwrap()
+            .contains("/// @custom:warning   this contract can selfdestruct."));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_loop_shapes() {
+        // 0x11111111 is `for (i = 0; i < arg0; i++) store[i] = i`, 0x22222222 is
+        // `while (store[0] < arg0) store[0]++`, 0x33333333 is `do { store[1] = i++; } while (i <
+        // arg0)`, and 0x44444444 is `while (true) { store[i++] = 1; if (i > arg0) break; }`
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060355760003560e01c80631111111114603a578063222222221460525780633333333314606d57806344444444146081575b600080fd5b60005b600435811015605057808055600101603d565b005b5b6004356000541015606b576001600054016000556053565b005b60005b806001556001016004358110607057005b60005b6001815560010160043581116097576084565b00"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        let function = |selector: &str| {
+            source
+                .split("function Unresolved_")
+                .find(|function| function.starts_with(selector))
+                .unwrap()
+                .to_string()
+        };
+        assert!(function("11111111").contains("for (uint256 i = 0; i < arg0; i++) {"));
+        assert!(function("22222222").contains("while (stor_"));
+        assert!(!function("22222222").contains("while (true)"));
+        assert!(function("33333333").contains("do {"));
+        assert!(function("33333333").contains("} while (0x01 < arg0);"));
+        assert!(function("44444444").contains("while (true) {"));
+        assert!(function("44444444").contains("break;"));
+    }
+
+    #[test]
+    fn test_suggested_function_names() {
+        use heimdall_core::decompile::out::naming::{
+            apply_suggested_name, parse_suggested_name, unresolved_functions,
+        };
+
+        let source = [
+            "contract DecompiledContract {",
+            "    /// @custom:selector    0x1f2a3b4c",
+            "    /// @custom:name        Unresolved_1f2a3b4c",
+            "    function Unresolved_1f2a3b4c(address arg0) public {",
+            "        if (arg0 == 0x00000000000000000000000000000000000000aa) {",
+            "            stor_b = arg0;",
+            "        }",
+            "    }",
+            "",
+            "    /// @custom:selector    0x8da5cb5b",
+            "    /// @custom:name        owner",
+            "    function owner() public view returns (address) {",
+            "        return stor_a;",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+
+        // only the unresolved function is found, including its nested blocks
+        let functions = unresolved_functions(&source);
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].0, "1f2a3b4c");
+        assert!(functions[0].1.ends_with("        }\n    }"));
+
+        // suggestions are reduced to camelCase identifiers
+        let suggestion = parse_suggested_name(
+            "1f2a3b4c",
+            "Name: `ClaimRewards()`\nSummary: Sends the caller's accrued rewards.",
+        )
+        .unwrap();
+        assert_eq!(suggestion.name, "claimRewards");
+        assert!(parse_suggested_name("1f2a3b4c", "name: claimRewards").is_none());
+
+        let renamed = apply_suggested_name(&source, &suggestion);
+        assert!(!renamed.contains("Unresolved_1f2a3b4c"));
+        assert!(renamed.contains("    function claimRewards_1f2a3b4c(address arg0) public {"));
+        assert!(renamed.contains(
+            "    /// @custom:name        claimRewards_1f2a3b4c\n    /// @custom:summary     Sends the caller's accrued rewards."
+        ));
+    }
+
+    #[test]
+    fn test_heuristic_function_names() {
+        use heimdall_core::decompile::out::naming::suggest_heuristic_names;
+
+        let source = [
+            "contract DecompiledContract {",
+            "    /// @custom:selector    0x13af4035",
+            "    /// @custom:name        Unresolved_13af4035",
+            "    function Unresolved_13af4035(address arg0) public {",
+            "        require(msg.sender == stor_a, \"Ownable\");",
+            "        stor_a = arg0;",
+            "    }",
+            "",
+            "    /// @custom:selector    0x22222222",
+            "    /// @custom:name        Unresolved_22222222",
+            "    function Unresolved_22222222(address arg0, uint256 arg1) public {",
+            "        stor_map_b[msg.sender] = stor_map_b[msg.sender] - arg1;",
+            "        emit Transfer(msg.sender, arg0, arg1);",
+            "    }",
+            "",
+            "    /// @custom:selector    0x33333333",
+            "    /// @custom:name        Unresolved_33333333",
+            "    function Unresolved_33333333() public {",
+            "        (bool success, bytes memory ret0) = address(msg.sender).call{ value: 0x01 }(abi.encode());",
+            "    }",
+            "",
+            "    /// @custom:selector    0x44444444",
+            "    /// @custom:name        Unresolved_44444444",
+            "    function Unresolved_44444444() public view returns (uint256) {",
+            "        return stor_c;",
+            "    }",
+            "",
+            "    /// @custom:selector    0x55555555",
+            "    /// @custom:name        Unresolved_55555555",
+            "    function Unresolved_55555555(bytes memory arg0) public {",
+            "        (bool success, bytes memory ret0) = address(msg.sender).call{ value: 0 }(abi.encode(arg0));",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+
+        let renamed = suggest_heuristic_names(&source);
+        assert!(renamed.contains("    function setOwner_13af4035(address arg0) public {"));
+        assert!(renamed.contains("    /// @custom:summary     named heuristically, since it writes stor_a, which is checked against msg.sender."));
+        assert!(renamed
+            .contains("    function transferLike_22222222(address arg0, uint256 arg1) public {"));
+        assert!(renamed.contains("    function withdrawLike_33333333() public {"));
+        assert!(
+            renamed.contains("    function getStorC_44444444() public view returns (uint256) {")
+        );
+
+        // functions without any recognizable behavior are left unresolved
+        assert!(renamed.contains("    function Unresolved_55555555(bytes memory arg0) public {"));
+    }
+
+    #[test]
+    fn test_improve_readability() {
+        use heimdall_core::decompile::out::readability::improve_readability;
+
+        let source = [
+            "contract DecompiledContract {",
+            "    function Unresolved_13af4035(address arg0) public {",
+            "        var_a = msg.sender;",
+            "        require(var_a == stor_a, \"Ownable\");",
+            "        address var_b = arg0;",
+            "        stor_a = var_b;",
+            "        emit OwnershipTransferred(msg.sender, var_b);",
+            "    }",
+            "",
+            "    function Unresolved_22222222() public {",
+            "        uint256 var_a = address(this).balance;",
+            "        uint256 var_b = var_a / 0x02;",
+            "        (bool success, bytes memory ret0) = address(msg.sender).call{ value: var_b }(\"\");",
+            "        stor_b = var_a;",
+            "        uint256 var_c = stor_c + 0x01;",
+            "        if (var_c > 0x0a) {",
+            "            stor_c = var_c;",
+            "        }",
+            "    }",
+            "",
+            "    function Unresolved_33333333() public view returns (uint256) {",
+            "        var_a = stor_d + 0x01;",
+            "        return var_a;",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+
+        let readable = improve_readability(&source, 24);
+
+        // temporaries which are used once are inlined, keeping their declared type's cast
+        assert!(readable.contains("        require(msg.sender == stor_a, \"Ownable\");"));
+        assert!(!readable.contains("var_"));
+
+        // values written to the owner are named after it, and the rest after their source
+        assert!(readable.contains("        address newOwner_ = arg0;"));
+        assert!(readable.contains("        emit OwnershipTransferred(msg.sender, newOwner_);"));
+        assert!(readable.contains("        uint256 balance_ = address(this).balance;"));
+        assert!(readable.contains("value: uint256(balance_ / 0x02) }"));
+        assert!(readable.contains("        return stor_d + 0x01;"));
+        assert!(readable.contains("        uint256 storC_ = stor_c + 0x01;"));
+
+        // temporaries aren't inlined beyond the threshold
+        let readable = improve_readability(&source, 4);
+        assert!(readable.contains("        sender_ = msg.sender;"));
+        assert!(readable.contains("        uint256 var_b = balance_ / 0x02;"));
+    }
+
+    #[test]
+    fn test_split_functions() {
+        use heimdall_core::decompile::out::split::split_functions;
+
+        let source = [
+            "// SPDX-License-Identifier: MIT",
+            "pragma solidity >=0.8.0;",
+            "",
+            "contract DecompiledContract {",
+            "    uint256 public stor_a;",
+            "",
+            "    event Transfer(address, address, uint256);",
+            "",
+            "    error Unauthorized();",
+            "    ",
+            "    /// @custom:selector    0xa9059cbb",
+            "    /// @custom:name        transfer",
+            "    function transfer(address arg0, uint256 arg1) public {",
+            "        if (arg1 > stor_a) {",
+            "            revert Unauthorized();",
+            "        }",
+            "        emit Transfer(msg.sender, arg0, arg1);",
+            "    }",
+            "    ",
+            "    /// @custom:selector    0xbeabacc8",
+            "    /// @custom:name        transfer",
+            "    function transfer(address arg0, address arg1, uint256 arg2) public {",
+            "        emit Transfer(arg0, arg1, arg2);",
+            "    }",
+            "    ",
+            "    fallback() external payable {",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+
+        let files = split_functions(&source);
+        assert_eq!(
+            files.iter().map(|(name, _)| name.as_str()).collect::<Vec<&str>>(),
+            vec!["transfer_a9059cbb.sol", "transfer_beabacc8.sol", "fallback.sol"]
+        );
+
+        // every file shares the header, and contains only its own function
+        let header = source.lines().take(9).collect::<Vec<&str>>().join("\n");
+        for (_, contents) in &files {
+            assert!(contents.starts_with(&header));
+            assert!(contents.ends_with("    }\n}"));
+        }
+        assert!(files[0].1.contains("            revert Unauthorized();"));
+        assert!(!files[0].1.contains("0xbeabacc8"));
+        assert!(files[1].1.contains("    /// @custom:selector    0xbeabacc8"));
+        assert!(files[2].1.ends_with("\n\n    fallback() external payable {\n    }\n}"));
+    }
+
+    #[test]
+    fn test_merge_proxy() {
+        use heimdall_core::decompile::{
+            out::abi::{ABIStructure, FunctionABI},
+            proxy::{merge_proxy_abi, merge_proxy_source},
+        };
+
+        let proxy = [
+            "/// @custom:version   heimdall-rs v0.6.4",
+            "contract DecompiledContract {",
+            "    ",
+            "    /// @custom:selector    0x3659cfe6",
+            "    /// @custom:name        Unresolved_3659cfe6",
+            "    function Unresolved_3659cfe6(address arg0) public {",
+            "        require(msg.sender == stor_b);",
+            "    }",
+            "    ",
+            "    fallback() external payable {",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+        let implementation = [
+            "/// @custom:version   heimdall-rs v0.6.4",
+            "contract DecompiledContract {",
+            "    uint256 public stor_a;",
+            "    ",
+            "    /// @custom:selector    0x18160ddd",
+            "    /// @custom:name        totalSupply",
+            "    function totalSupply() public view returns (uint256) {",
+            "        return stor_a;",
+            "    }",
+            "}",
+        ]
+        .join("\n");
+
+        let merged = merge_proxy_source(&proxy, &implementation, "0xaa", "0xbb").unwrap();
+        assert_eq!(
+            merged,
+            [
+                "/// @custom:version   heimdall-rs v0.6.4",
+                "/// @custom:proxy     0xaa",
+                "/// @custom:impl      0xbb",
+                "///",
+                "/// @notice           Storage is read from the proxy at 0xaa, which delegates",
+                "///                     calls to this implementation.",
+                "contract DecompiledContract {",
+                "    uint256 public stor_a;",
+                "",
+                "    /// @custom:selector    0x18160ddd",
+                "    /// @custom:name        totalSupply",
+                "    function totalSupply() public view returns (uint256) {",
+                "        return stor_a;",
+                "    }",
+                "",
+                "    /// @custom:selector    0x3659cfe6",
+                "    /// @custom:name        Unresolved_3659cfe6",
+                "    /// @notice             defined by the proxy, rather than its implementation.",
+                "    function Unresolved_3659cfe6(address arg0) public {",
+                "        require(msg.sender == stor_b);",
+                "    }",
+                "}",
+            ]
+            .join("\n")
+        );
+
+        let function = |name: &str| {
+            ABIStructure::Function(FunctionABI {
+                type_: String::from("function"),
+                name: name.to_string(),
+                inputs: vec![],
+                outputs: vec![],
+                state_mutability: String::from("nonpayable"),
+                constant: false,
+            })
+        };
+        let merged = merge_proxy_abi(
+            &[function("Unresolved_3659cfe6"), function("Unresolved_18160ddd")],
+            &[function("totalSupply")],
+        );
+        assert_eq!(merged, vec![function("totalSupply"), function("Unresolved_3659cfe6")]);
+    }
+
+    #[tokio::test]
+    async fn test_decompile_abi_signatures() {
+        // 0x11111111 stores its three arguments to storage slots 0, 1, and 2
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: false,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let function = result
+            .abi
+            .unwrap()
+            .into_iter()
+            .find_map(|structure| match structure {
+                ABIStructure::Function(function) if function.type_ == "function" => Some(function),
+                _ => None,
+            })
+            .unwrap();
+
+        // unresolved functions keep the selector they were dispatched with, and their arguments
+        // are ordered by calldata slot
+        assert_eq!(function.selector(), "11111111");
+        assert!(function.signature().starts_with("Unresolved_11111111(address,"));
+        assert_eq!(
+            function.inputs.iter().map(|input| input.name.as_str()).collect::<Vec<&str>>(),
+            vec!["arg0", "arg1", "arg2"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decompile_mask_casts() {
+        // 0x11111111 stores an address mask, a uint8 mask, and a sign extended int8 of its
+        // arguments to storage slots 0, 1, and 2
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: true,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        for line in ["= address(arg0);", "= uint8(arg1);", "= int8(arg2);"] {
+            assert!(source.contains(line));
+        }
+        assert!(!source.contains(" & "));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_storage_types() {
+        // 0x11111111 stores an address mask, a uint8 mask, and a sign extended int8 of its
+        // arguments to storage slots 0, 1, and 2
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: false,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        use ethers::types::U256;
+        use heimdall_common::ether::storage::StorageType;
+
+        // the sign extended int8 isn't modeled as a storage type
+        assert_eq!(result.storage_types.len(), 2);
+        assert_eq!(result.storage_types.get(&U256::zero()), Some(&StorageType::Address));
+        assert_eq!(result.storage_types.get(&U256::one()), Some(&StorageType::Uint(8)));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_progress_events() {
+        use heimdall_common::utils::events::{subscribe_channel, unsubscribe, ProgressEvent};
+
+        let (subscriber, events) = subscribe_channel();
+        heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: false,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+        unsubscribe(subscriber);
+
+        // other tests decompile concurrently, so only this contract's events are checked
+        let events = events.try_iter().collect::<Vec<_>>();
+        assert!(events.contains(&ProgressEvent::PhaseStarted {
+            phase: String::from("decompile: symbolic execution")
+        }));
+        assert!(events.contains(&ProgressEvent::SelectorAnalyzed {
+            selector: String::from("11111111"),
+            analyzed: 1,
+            total: 1,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_decompile_audit() {
+        use heimdall_core::decompile::audit::AuditKind;
+
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: false,
+            include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: true,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let audit = result.audit.unwrap();
+        assert!(audit.code_hash.starts_with("0x") && audit.code_hash.len() == 66);
+        assert_eq!(audit.entries_of(AuditKind::Input).len(), 2);
+
+        let storage_types = audit
+            .entries_of(AuditKind::StorageType)
+            .iter()
+            .map(|entry| (entry.subject.as_str(), entry.decision.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(storage_types, vec![("0x0", "address"), ("0x1", "uint8")]);
+    }
+
+    #[tokio::test]
+    async fn test_decompile_yul_object() {
+        let result = heimdall_core::decompile::decompile(DecompilerArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            non_interactive: false,
+            skip_resolving: true,
+            include_solidity: false,
+            include_yul: true,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
+        })
+        .await
+        .unwrap();
+
+        let source = result.source.unwrap();
+        let code = source.lines().filter(|line| !line.starts_with("//")).collect::<Vec<_>>();
+        let code = code.join("\n");
+        assert!(code.trim().starts_with("object \"DecompiledContract\" {\n    code {"));
+        assert!(code.contains("switch selector()"));
+        assert!(code.contains("sstore(0, castToAddress(calldataload(0x04)))"));
+        assert!(code.contains("sstore(0x01, and(0xff, calldataload(0x24)))"));
+
+        // yul has no typed casts, else branches, or statement terminators
+        for invalid in [" : ", "else", ";"] {
+            assert!(!code.contains(invalid));
+        }
+        assert_eq!(code.matches('{').count(), code.matches('}').count());
+    }
+
+    #[tokio::test]
+    async fn test_compare_recompiled_bytecode() {
+        let original = "6004361060175760003560e01c80631111111114601c575b600080fd5b60043573ffffffffffffffffffffffffffffffffffffffff1660005560243560ff1660015560443560000b60025500";
+
+        // identical bytecode is a perfect match
+        let report = compare_bytecode(original, original).await.unwrap();
+        assert_eq!(report.matched_selectors, 1);
+        assert_eq!(report.total_selectors, 1);
+        assert_eq!(report.score, 1.0);
+
+        // a recompilation with a different selector and an extra block scores lower
+        let recompiled = "6004361060175760003560e01c80632222222214601c575b600080fd5b005b00";
+        let report = compare_bytecode(original, recompiled).await.unwrap();
+        assert_eq!(report.matched_selectors, 0);
+        assert!(report.cfg_similarity < 1.0);
+        assert!(report.score < 0.5);
+    }
+
     #[tokio::test]
     async fn test_decompile_weth() {
         let result = heimdall_core::decompile::decompile(DecompilerArgs {
@@ -154,9 +1591,32 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from("https://eth.llamarpc.com"),
             default: true,
+            non_interactive: false,
             skip_resolving: true,
             include_solidity: true,
             include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
         })
         .await
         .unwrap();
@@ -188,9 +1648,32 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from("https://eth.llamarpc.com"),
             default: true,
+            non_interactive: false,
             skip_resolving: true,
             include_solidity: true,
             include_yul: false,
+            fetch_metadata: false,
+            ipfs_gateway: String::from(""),
+            verify_output: false,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            children: false,
+            constructor: false,
+            block: 0,
+            heuristic_names: false,
+            llm_names: false,
+            llm_redact: false,
+            openai_api_key: String::from(""),
+            split_functions: false,
+            follow_proxy: false,
+            plugins: vec![],
+            audit: false,
+            block_comments: false,
+            inline_threshold: 0,
+            vm: String::new(),
         })
         .await
         .unwrap();
@@ -293,9 +1776,32 @@ mod integration_tests {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from("https://eth.llamarpc.com"),
                 default: true,
+                non_interactive: false,
                 skip_resolving: true,
                 include_solidity: true,
                 include_yul: false,
+                fetch_metadata: false,
+                ipfs_gateway: String::from(""),
+                verify_output: false,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                children: false,
+                constructor: false,
+                block: 0,
+                heuristic_names: false,
+                llm_names: false,
+                llm_redact: false,
+                openai_api_key: String::from(""),
+                split_functions: false,
+                follow_proxy: false,
+                plugins: vec![],
+                audit: false,
+                block_comments: false,
+                inline_threshold: 0,
+                vm: String::new(),
             })
             .await
             .unwrap();
@@ -13,6 +13,9 @@ mod benchmarks {
                 verbose: Verbosity::new(0, 0),
                 rpc_url: String::from(""),
                 decimal_counter: true,
+                annotate: false,
+                vm: String::new(),
+                diff: String::new(),
             }).await.unwrap();
         }
 
@@ -38,6 +41,29 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from(""),
             decimal_counter: false,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(expected, assembly);
+    }
+
+    #[tokio::test]
+    async fn test_disassemble_creation_code() {
+        let bytecode = "6080604052603f6000f3fe6080604052600080fda164736f6c6343000813000a00000000000000000000000000000000000000000000000000000000000000ff";
+        let expected = String::from("; init code (11 bytes)\n000001 PUSH1 80\n000003 PUSH1 40\n000004 MSTORE \n000006 PUSH1 3f\n000008 PUSH1 00\n000009 RETURN \n00000a INVALID \n\n; runtime code (21 bytes at offset 00000b)\n000001 PUSH1 80\n000003 PUSH1 40\n000004 MSTORE \n000006 PUSH1 00\n000007 DUP1 \n000008 REVERT \n\n; constructor arguments (32 bytes at offset 000020)\n000000 DATA 00000000000000000000000000000000000000000000000000000000000000ff\n");
+
+        let assembly = disassemble(DisassemblerArgs {
+            target: bytecode.to_owned(),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            decimal_counter: false,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
@@ -55,6 +81,9 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from(""),
             decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
@@ -72,6 +101,9 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from(""),
             decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
@@ -89,6 +121,9 @@ mod integration_tests {
             verbose: Verbosity::new(0, 1),
             rpc_url: String::from(""),
             decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
@@ -96,6 +131,104 @@ mod integration_tests {
         assert_eq!(expected, assembly);
     }
 
+    #[tokio::test]
+    async fn test_disassemble_annotated() {
+        // sstore(0, calldataload(4) + 1)
+        let bytecode = "60043560010160005500";
+
+        let assembly = disassemble(DisassemblerArgs {
+            target: bytecode.to_owned(),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            decimal_counter: true,
+            annotate: true,
+            vm: String::new(),
+            diff: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert!(assembly.contains("2 CALLDATALOAD  // offset=0x04 -> arg0\n"));
+        assert!(assembly.contains("5 ADD  // a=0x01, b=arg0 -> 0x01 + arg0\n"));
+        assert!(assembly.contains("8 SSTORE  // key=0, value=0x01 + arg0\n"));
+
+        // instructions which only move stack items aren't annotated
+        assert!(assembly.contains("1 PUSH1 04\n"));
+        assert!(assembly.ends_with("9 STOP \n"));
+    }
+
+    #[tokio::test]
+    async fn test_disassemble_eof_container() {
+        // a code section which calls the second if calldata is zero, and a 32-byte data section
+        let bytecode = "ef0001010008020002000f0001ff00200000800002000000005f35e10004e3000100d100005f5500e4000000000000000000000000000000000000000000000000000000000000002a";
+
+        let assembly = disassemble(DisassemblerArgs {
+            target: bytecode.to_owned(),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert!(assembly.starts_with(
+            "; code section 0 (15 bytes at offset 25, 0 inputs, non-returning)\n0 PUSH0 \n1 CALLDATALOAD \n2 RJUMPI 0004\n5 CALLF 0001\n8 STOP \n9 DATALOADN 0000\n"
+        ));
+        assert!(assembly
+            .contains("; code section 1 (1 bytes at offset 40, 0 inputs, 0 outputs)\n0 RETF \n"));
+        assert!(assembly.ends_with(&format!(
+            "; data section (32 of 32 bytes at offset 41)\n0 DATA {}2a\n",
+            "00".repeat(31)
+        )));
+    }
+
+    #[tokio::test]
+    async fn test_disassemble_eravm_bytecode() {
+        // a single instruction, followed by a constant holding the `transfer` selector
+        let bytecode = "00200004032121a3000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000a9059cbb";
+
+        let assembly = disassemble(DisassemblerArgs {
+            target: bytecode.to_owned(),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            decimal_counter: true,
+            annotate: false,
+            vm: String::from("eravm"),
+            diff: String::new(),
+        })
+        .await
+        .unwrap();
+
+        assert!(assembly.starts_with(
+            "; EraVM bytecode (3 words, 12 instructions)\n; opcodes are version-specific table indices, not mnemonics\n0 op_1a3.gt r1, r2, r3, r0, 0x0004, 0x0020\n"
+        ));
+        assert!(assembly.ends_with("11 op_4bb.ge r5, r0, r9, r10, 0x0000, 0x0000\n"));
+    }
+
+    #[tokio::test]
+    async fn test_disassemble_diff() {
+        // a block is inserted before the jump target, which only moves it, so it isn't reported
+        let diff = disassemble(DisassemblerArgs {
+            target: String::from("60055600005b600100"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            decimal_counter: false,
+            annotate: false,
+            vm: String::new(),
+            diff: String::from("60075600005b005b600100"),
+        })
+        .await
+        .unwrap();
+
+        assert!(diff.starts_with(
+            "--- 60055600005b600100\n+++ 60075600005b005b600100\n@@ -2,6 +2,8 @@ 1 inserted block\n"
+        ));
+        assert!(diff.contains("\n+000005 JUMPDEST \n+000006 STOP \n"));
+    }
+
     #[tokio::test]
     async fn test_disassemble_from_file() {
         let bytecode = "366000600037611000600036600073";
@@ -109,6 +242,9 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from(""),
             decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
@@ -128,6 +264,9 @@ mod integration_tests {
             verbose: Verbosity::new(0, 0),
             rpc_url: String::from("https://eth.llamarpc.com"),
             decimal_counter: true,
+            annotate: false,
+            vm: String::new(),
+            diff: String::new(),
         })
         .await
         .unwrap();
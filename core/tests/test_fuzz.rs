@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod integration_tests {
+    use ethers::{
+        abi::{decode, ParamType, Token},
+        types::Address,
+    };
+    use heimdall_common::utils::strings::decode_hex;
+    use heimdall_core::{
+        decompile::out::abi::{ABIToken, FunctionABI},
+        fuzz::{fuzz, random_calldata, read_abi_functions, FuzzArgsBuilder},
+    };
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn function(name: &str, types: &[&str]) -> FunctionABI {
+        FunctionABI {
+            type_: String::from("function"),
+            name: name.to_string(),
+            inputs: types
+                .iter()
+                .enumerate()
+                .map(|(index, type_)| ABIToken {
+                    name: format!("arg{index}"),
+                    internal_type: type_.to_string(),
+                    type_: type_.to_string(),
+                })
+                .collect(),
+            outputs: vec![],
+            state_mutability: String::from("nonpayable"),
+            constant: false,
+        }
+    }
+
+    #[test]
+    fn test_random_calldata() {
+        let function = function(
+            "everything",
+            &["address", "uint8", "int16", "bool", "bytes4", "bytes", "string", "uint256[]"],
+        );
+        let (target, sender) = (Address::repeat_byte(0x11), Address::repeat_byte(0x22));
+
+        for seed in 1..32 {
+            let calldata =
+                random_calldata(&function, &mut StdRng::seed_from_u64(seed), target, sender)
+                    .unwrap();
+            assert!(calldata.starts_with(&format!("0x{}", function.selector())));
+
+            // every input decodes as its type, within its range
+            let tokens = decode(
+                &[
+                    ParamType::Address,
+                    ParamType::Uint(8),
+                    ParamType::Int(16),
+                    ParamType::Bool,
+                    ParamType::FixedBytes(4),
+                    ParamType::Bytes,
+                    ParamType::String,
+                    ParamType::Array(Box::new(ParamType::Uint(256))),
+                ],
+                &decode_hex(&calldata[10..]).unwrap(),
+            )
+            .unwrap();
+            match &tokens[1] {
+                Token::Uint(value) => assert!(value.bits() <= 8),
+                _ => panic!("expected a uint8"),
+            }
+            match &tokens[7] {
+                Token::Array(values) => assert!(values.len() <= 3),
+                _ => panic!("expected an array"),
+            }
+
+            // the same seed generates the same inputs
+            assert_eq!(
+                random_calldata(&function, &mut StdRng::seed_from_u64(seed), target, sender),
+                Some(calldata)
+            );
+        }
+    }
+
+    #[test]
+    fn test_random_calldata_unparseable() {
+        let function = function("Unresolved_deadbeef", &["mapping(uint256 => uint256)"]);
+        assert_eq!(
+            random_calldata(
+                &function,
+                &mut StdRng::seed_from_u64(1),
+                Address::zero(),
+                Address::zero()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_read_abi_functions() {
+        let path = std::env::temp_dir()
+            .join(format!("fuzz.{}.abi.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        std::fs::write(
+            &path,
+            r#"[
+                {"type":"function","name":"transfer","inputs":[{"name":"arg0","internalType":"address","type":"address"},{"name":"arg1","internalType":"uint256","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable","constant":false},
+                {"type":"event","name":"Transfer","inputs":[],"anonymous":false}
+            ]"#,
+        )
+        .unwrap();
+
+        let functions = read_abi_functions(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].selector(), "a9059cbb");
+        assert!(read_abi_functions("./abi-which-does-not-exist.json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fuzz_provider_error() {
+        let path = std::env::temp_dir()
+            .join(format!("fuzz.{}.unreachable.abi.json", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        std::fs::write(
+            &path,
+            r#"[
+                {"type":"function","name":"transfer","inputs":[{"name":"arg0","internalType":"address","type":"address"},{"name":"arg1","internalType":"uint256","type":"uint256"}],"outputs":[],"stateMutability":"nonpayable","constant":false},
+                {"type":"function","name":"totalSupply","inputs":[],"outputs":[],"stateMutability":"view","constant":true}
+            ]"#,
+        )
+        .unwrap();
+
+        // nothing listens on port 1, so every call fails with a provider error rather than a
+        // revert, which is recorded against each function instead of aborting the run
+        let result = fuzz(
+            FuzzArgsBuilder::new()
+                .target(String::from("0x0000000000000000000000000000000000000001"))
+                .rpc_url(String::from("http://127.0.0.1:1"))
+                .abi(path.clone())
+                .seed(1)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.functions.len(), 2);
+        for function in &result.functions {
+            assert_eq!(function.calls, 0);
+            assert!(function.error.is_some());
+        }
+    }
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod integration_tests {
+    use clap_verbosity_flag::Verbosity;
+    use heimdall_core::metadata::{metadata, MetadataArgs};
+
+    #[tokio::test]
+    async fn test_metadata_from_bytecode() {
+        let args = MetadataArgs {
+            target: String::from("0x6080604052a2646970667358221220c8bd1bd1a0ca30a9b2b8aa9b51d8ea7c0b5b74d69c0283aab45d63c1c8896d2964736f6c63430008110033"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            dead_code: false,
+        };
+
+        let result = metadata(args).await.unwrap();
+        let decoded = result.metadata.as_ref().unwrap();
+
+        assert_eq!(decoded.solc, Some(String::from("0.8.17")));
+        assert!(result.optimizer_inferable());
+        assert_eq!(result.runtime_size, 58);
+        assert_eq!(result.stripped_size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_missing() {
+        let args = MetadataArgs {
+            target: String::from("0x6080604052"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            dead_code: false,
+        };
+
+        let result = metadata(args).await.unwrap();
+
+        assert!(result.metadata.is_none());
+        assert!(!result.optimizer_inferable());
+        assert_eq!(result.stripped_size, 5);
+    }
+
+    #[tokio::test]
+    async fn test_metadata_dead_code() {
+        // 0x11111111 always reverts, and the block at 0x21 is never jumped to
+        let args = MetadataArgs {
+            target: String::from(
+                "6004361060175760003560e01c80631111111114601c575b600080fd5b600080fd5b600160005500",
+            ),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            dead_code: true,
+        };
+
+        let result = metadata(args).await.unwrap();
+        let dead_code = result.dead_code.unwrap();
+
+        assert_eq!(dead_code.reverting_selectors, vec!["11111111"]);
+        assert_eq!(dead_code.unreachable_blocks.len(), 1);
+        assert_eq!(dead_code.unreachable_blocks[0].start, 0x21);
+        assert_eq!(dead_code.unreachable_blocks[0].size, 7);
+        assert!(!dead_code.partial);
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(test)]
+mod integration_tests {
+    use heimdall_core::{
+        decompile::{decompile, DecompilerArgsBuilder},
+        plugins::{run_plugins, AnalysisInput, AnalyzerPlugin, Finding, Severity, WasmPlugin},
+    };
+
+    /// A plugin which reports the same two findings for every contract, read from its data
+    /// segment.
+    const STATIC_PLUGIN: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "[{\"severity\":\"low\",\"title\":\"uses a magic number\"},{\"severity\":\"high\",\"title\":\"unchecked call\",\"selector\":\"11111111\"}]")
+            (func (export "alloc") (param i32) (result i32)
+                i32.const 1024)
+            (func (export "analyze") (param i32 i32) (result i64)
+                i64.const 117))
+    "#;
+
+    /// Compile a WAT plugin and write it to a temporary file, returning its path.
+    fn write_plugin(name: &str, wat: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("{name}.{}.wasm", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        std::fs::write(&path, wat::parse_str(wat).unwrap()).unwrap();
+        path
+    }
+
+    fn input() -> AnalysisInput {
+        AnalysisInput::new("0x00", "00", None, &[])
+    }
+
+    #[test]
+    fn test_wasm_plugin() {
+        let path = write_plugin("static", STATIC_PLUGIN);
+        let plugin = WasmPlugin::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(plugin.name().starts_with("static"));
+        let findings = plugin.analyze(&input()).unwrap();
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[1].severity, Severity::High);
+        assert_eq!(findings[1].selector, Some(String::from("11111111")));
+        assert_eq!(findings[0].description, "");
+    }
+
+    #[test]
+    fn test_run_plugins() {
+        struct RustPlugin;
+        impl AnalyzerPlugin for RustPlugin {
+            fn name(&self) -> String {
+                String::from("rust")
+            }
+            fn analyze(&self, input: &AnalysisInput) -> Result<Vec<Finding>, String> {
+                Ok(vec![Finding {
+                    plugin: String::new(),
+                    severity: Severity::Medium,
+                    title: format!("analyzed {}", input.target),
+                    description: String::new(),
+                    selector: None,
+                }])
+            }
+        }
+        struct BrokenPlugin;
+        impl AnalyzerPlugin for BrokenPlugin {
+            fn name(&self) -> String {
+                String::from("broken")
+            }
+            fn analyze(&self, _: &AnalysisInput) -> Result<Vec<Finding>, String> {
+                Err(String::from("failed"))
+            }
+        }
+
+        let path = write_plugin("ordered", STATIC_PLUGIN);
+        let plugins: Vec<Box<dyn AnalyzerPlugin>> = vec![
+            Box::new(WasmPlugin::load(&path).unwrap()),
+            Box::new(RustPlugin),
+            Box::new(BrokenPlugin),
+        ];
+        std::fs::remove_file(&path).unwrap();
+
+        // findings are attributed to their plugin and ordered by severity, skipping failures
+        let findings = run_plugins(&plugins, &input());
+        assert_eq!(
+            findings
+                .iter()
+                .map(|finding| (finding.severity, finding.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Severity::High, "unchecked call"),
+                (Severity::Medium, "analyzed 0x00"),
+                (Severity::Low, "uses a magic number"),
+            ]
+        );
+        assert!(findings[0].plugin.starts_with("ordered"));
+        assert_eq!(findings[1].plugin, "rust");
+    }
+
+    #[test]
+    fn test_invalid_plugin() {
+        // a plugin which doesn't export `alloc` fails, rather than aborting the analysis
+        let path = write_plugin("invalid", r#"(module (memory (export "memory") 1))"#);
+        let plugin = WasmPlugin::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(plugin.analyze(&input()).unwrap_err().contains("alloc"));
+        assert!(WasmPlugin::load("./plugin-which-does-not-exist.wasm").is_err());
+    }
+
+    #[test]
+    fn test_plugin_output_out_of_bounds() {
+        // findings which run past the end of the plugin's 64 KiB of memory, and a length which
+        // is larger than the output limit
+        for (name, output, expected) in [
+            ("past_end", "0x0000fff000000100", "outside of its 65536 byte memory"),
+            ("too_large", "0x00000000ffffffff", "more than the limit"),
+        ] {
+            let path = write_plugin(
+                name,
+                &format!(
+                    r#"(module
+                        (memory (export "memory") 1)
+                        (func (export "alloc") (param i32) (result i32)
+                            i32.const 1024)
+                        (func (export "analyze") (param i32 i32) (result i64)
+                            i64.const {output}))"#
+                ),
+            );
+            let plugin = WasmPlugin::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+
+            let error = plugin.analyze(&input()).unwrap_err();
+            assert!(error.contains(expected), "{error}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_decompile_with_plugin() {
+        let path = write_plugin("decompile", STATIC_PLUGIN);
+        let args = DecompilerArgsBuilder::new()
+            .target(String::from("6004361060215760003560e01c80631111111114602657806322222222146053575b600080fd5b6004356000557fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef600080a1005b60005460005260206000f3"))
+            .skip_resolving(true)
+            .plugins(vec![path.clone()])
+            .build()
+            .unwrap();
+
+        let result = decompile(args).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(result.findings.len(), 2);
+        assert_eq!(result.findings[0].title, "unchecked call");
+    }
+}
@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod integration_tests {
+    use ethers::types::U256;
+    use heimdall_common::ether::evm::core::vm::VM;
+    use heimdall_core::profile::attribution::{attribute_gas, steps_from_vm, ProfileStep};
+
+    fn step(opcode: &str, gas: u128, gas_cost: u128, depth: usize, stack: Vec<u64>) -> ProfileStep {
+        ProfileStep {
+            pc: 0,
+            opcode: opcode.to_string(),
+            gas,
+            gas_cost,
+            depth,
+            stack: stack.into_iter().map(U256::from).collect(),
+        }
+    }
+
+    #[test]
+    fn test_profile_attributes_call_gas_once() {
+        let steps = vec![
+            step("PUSH1", 10000, 3, 1, vec![]),
+            step("CALL", 9997, 9000, 1, vec![0, 0xbeef]),
+            step("SLOAD", 8000, 2100, 2, vec![1]),
+            step("STOP", 5900, 0, 2, vec![]),
+            step("STOP", 5000, 0, 1, vec![]),
+        ];
+
+        let profile = attribute_gas(&steps, "root");
+
+        // the call itself costs 9997 - 5000 minus the 2100 used by the callee
+        assert_eq!(profile.total_gas, 3 + 2897 + 2100);
+        assert!(profile.folded.contains(&String::from("root;CALL 2897")));
+        assert!(profile
+            .folded
+            .contains(&String::from("root;0x000000000000000000000000000000000000beef;SLOAD 2100")));
+        assert_eq!(profile.storage, vec![(String::from("SLOAD"), String::from("0x1"), 2100)]);
+    }
+
+    #[test]
+    fn test_profile_internal_vm() {
+        // storage[0] = 1
+        let mut evm = VM::new(
+            String::from("600160005500"),
+            String::from("0x"),
+            String::from("0x6865696d64616c6c000000000061646472657373"),
+            String::from("0x6865696d64616c6c0000000000006f726967696e"),
+            String::from("0x6865696d64616c6c00000000000063616c6c6572"),
+            0,
+            u128::MAX,
+        );
+
+        let steps = steps_from_vm(&mut evm);
+        let profile = attribute_gas(&steps, "root");
+
+        assert_eq!(steps.len(), 4);
+        assert_eq!(profile.storage.len(), 1);
+        assert_eq!(profile.opcodes[0].0, "SSTORE");
+    }
+}
@@ -0,0 +1,259 @@
+#[cfg(test)]
+mod integration_tests {
+    use clap_verbosity_flag::Verbosity;
+    use ethers::{
+        abi::Token,
+        types::{H160, U256},
+    };
+    use heimdall_common::{
+        ether::signatures::ResolvedFunction, utils::io::template::render_template,
+    };
+    use heimdall_core::{
+        cfg::{stats::CFGStats, CFGResult},
+        decompile::{
+            out::abi::{ABIStructure, ABIToken, EventABI, FunctionABI},
+            DecompileResult,
+        },
+        fuzz::{FunctionFuzzReport, FuzzResult},
+        report::{
+            cfg_context, decode_context, decompile_context, disassemble_context, fuzz_context,
+            manifest_context,
+            schema::{schema, schema_id, with_schema, SCHEMAS},
+        },
+        snapshot::{snapshot, SnapshotArgs},
+    };
+    use petgraph::Graph;
+    use serde_json::Value;
+
+    /// Assert that the output has every top-level key the subcommand's schema requires.
+    fn assert_matches_schema(subcommand: &str, output: &Value) {
+        let schema: Value = serde_json::from_str(schema(subcommand).unwrap()).unwrap();
+        for key in schema["required"].as_array().unwrap() {
+            let key = key.as_str().unwrap();
+            assert!(output.get(key).is_some(), "{subcommand} output is missing '{key}'");
+        }
+        assert_eq!(output["schema"], schema["properties"]["schema"]["const"]);
+    }
+
+    #[test]
+    fn test_decode_report() {
+        let matches = vec![ResolvedFunction {
+            name: String::from("transfer"),
+            signature: String::from("transfer(address,uint256)"),
+            inputs: vec![String::from("address"), String::from("uint256")],
+            decoded_inputs: Some(vec![
+                Token::Address(H160::from_low_u64_be(0xcc)),
+                Token::Uint(U256::from(100)),
+            ]),
+        }];
+
+        let calldata = "0xa9059cbb00000000000000000000000000000000000000000000000000000000000000cc0000000000000000000000000000000000000000000000000000000000000064";
+        let context = decode_context(&matches, calldata, Some(calldata));
+        assert_eq!(context["selector"], "0xa9059cbb");
+        assert_eq!(
+            context["words"][1],
+            "0x0000000000000000000000000000000000000000000000000000000000000064"
+        );
+
+        let report = render_template(
+            "{{#each functions}}{{signature}}\n{{#each decoded_inputs}}  {{@index}} {{type}}: {{value}}\n{{/each}}{{/each}}",
+            &context,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report,
+            "transfer(address,uint256)\n  0 address: 0x00000000000000000000000000000000000000cc\n  1 uint256: 100\n"
+        );
+    }
+
+    #[test]
+    fn test_decompile_report() {
+        let result = DecompileResult {
+            source: None,
+            abi: Some(vec![
+                ABIStructure::Function(FunctionABI {
+                    type_: String::from("function"),
+                    name: String::from("Unresolved_a9059cbb"),
+                    inputs: vec![ABIToken {
+                        name: String::from("arg0"),
+                        internal_type: String::from("address"),
+                        type_: String::from("address"),
+                    }],
+                    outputs: vec![],
+                    state_mutability: String::from("nonpayable"),
+                    constant: false,
+                }),
+                ABIStructure::Event(EventABI {
+                    type_: String::from("event"),
+                    name: String::from("Transfer"),
+                    inputs: vec![],
+                }),
+            ]),
+            metadata: None,
+            sources: None,
+            verification: None,
+            children: vec![],
+            metamorphic: Default::default(),
+            implementation: None,
+            storage_types: Default::default(),
+            findings: vec![],
+            audit: None,
+        };
+
+        let context = decompile_context(&result, "0x1234");
+        let report = render_template(
+            "# {{target}}\n{{#each functions}}- {{name}} ({{stateMutability}})\n{{/each}}{{#each events}}- event {{name}}\n{{/each}}{{#if verification}}verified{{else}}unverified{{/if}}",
+            &context,
+        )
+        .unwrap();
+
+        assert_eq!(
+            report,
+            "# 0x1234\n- Unresolved_a9059cbb (nonpayable)\n- event Transfer\nunverified"
+        );
+    }
+
+    #[test]
+    fn test_json_schemas() {
+        for (subcommand, schema) in SCHEMAS {
+            let schema: Value = serde_json::from_str(schema).unwrap();
+            assert_eq!(schema["$id"], schema_id(subcommand));
+        }
+
+        let output = with_schema(
+            "disassemble",
+            disassemble_context("; init code (3 bytes)\n000001 PUSH1 80\n000002 STOP \n", "0x1234"),
+        );
+        assert_matches_schema("disassemble", &output);
+        assert_eq!(output["instructions"][0]["opcode"], "PUSH1");
+        assert_eq!(output["instructions"][0]["operand"], "80");
+        assert_eq!(output["instructions"][0]["section"], "init code");
+        assert_eq!(output["instructions"][1]["operand"], Value::Null);
+        assert_eq!(output["instructions"][1]["annotation"], Value::Null);
+
+        let output = with_schema(
+            "disassemble",
+            disassemble_context("5 CALLDATALOAD  // offset=0x04 -> arg0\n", "0x1234"),
+        );
+        assert_matches_schema("disassemble", &output);
+        assert_eq!(output["instructions"][0]["operand"], Value::Null);
+        assert_eq!(output["instructions"][0]["annotation"], "offset=0x04 -> arg0");
+
+        assert_matches_schema(
+            "decode",
+            &with_schema("decode", decode_context(&[], "0x1234", None)),
+        );
+        assert_matches_schema(
+            "decompile",
+            &with_schema(
+                "decompile",
+                decompile_context(
+                    &DecompileResult {
+                        source: None,
+                        abi: None,
+                        metadata: None,
+                        sources: None,
+                        verification: None,
+                        children: vec![],
+                        metamorphic: Default::default(),
+                        implementation: None,
+                        storage_types: Default::default(),
+                        findings: vec![],
+                        audit: None,
+                    },
+                    "0x1234",
+                ),
+            ),
+        );
+
+        let output = with_schema(
+            "fuzz",
+            fuzz_context(
+                &FuzzResult {
+                    seed: 1,
+                    functions: vec![FunctionFuzzReport {
+                        selector: String::from("a9059cbb"),
+                        signature: String::from("transfer(address,uint256)"),
+                        state_mutability: String::from("nonpayable"),
+                        calls: 2,
+                        successes: 1,
+                        reverts: 1,
+                        revert_reasons: [(String::from("insufficient balance"), 1)].into(),
+                        succeeding_input: Some((String::from("0xa9059cbb"), String::from("0x00"))),
+                        reverting_input: None,
+                        callable_by_anyone: true,
+                        error: None,
+                    }],
+                },
+                "0x1234",
+            ),
+        );
+        assert_matches_schema("fuzz", &output);
+        assert_eq!(output["functions"][0]["succeeding_input"]["sender"], "0x00");
+        assert_eq!(output["functions"][0]["revert_reasons"]["insufficient balance"], 1);
+
+        let mut graph = Graph::<String, String>::new();
+        let entry = graph.add_node(String::from("0x01 PUSH1 0x01\n0x03 PUSH1 0\n0x05 SSTORE \n"));
+        let exit = graph.add_node(String::from("0x06 STOP \n"));
+        graph.add_edge(entry, exit, String::new());
+        let output = with_schema(
+            "cfg",
+            cfg_context(
+                &CFGResult {
+                    stats: CFGStats::new(&graph, "600160005500"),
+                    graph,
+                    source_lines: [(0, vec![(12, 12)])].into(),
+                },
+                "0x1234",
+            ),
+        );
+        assert_matches_schema("cfg", &output);
+        assert_eq!(output["nodes"][0]["source_lines"][0]["start"], 12);
+        assert_eq!(output["nodes"][1]["source_lines"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_manifest_report() {
+        // a function which requires the caller to be the owner in slot 0, then calls
+        // `transfer(address,uint256)` on the token in arg0, and sends its balance to the caller
+        let args = SnapshotArgs {
+            target: String::from(
+                "6004361060175760003560e01c80631111111114601c575b600080fd5b60005433146029576000\
+                                  80fd5b63a9059cbb60e01b60005260006000604460006000600435\
+                                  5af150600060006000600047335af15000",
+            ),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
+        };
+        let result = snapshot(args).await.unwrap();
+
+        let output = with_schema("manifest", manifest_context(&result, "0x1234"));
+        assert_matches_schema("manifest", &output);
+
+        let function = &output["functions"][0];
+        assert_eq!(function["selector"], "11111111");
+        // the function doesn't check msg.value
+        assert_eq!(function["state_mutability"], "payable");
+        assert_eq!(function["access"]["restricted"], true);
+        assert!(function["access"]["conditions"][0].as_str().unwrap().contains("msg.sender"));
+        assert_eq!(
+            function["assets"],
+            serde_json::json!([
+                { "asset": "ether", "method": null },
+                { "asset": "tokens", "method": "transfer(address,uint256)" },
+            ])
+        );
+    }
+}
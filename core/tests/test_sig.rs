@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod integration_tests {
+    use clap_verbosity_flag::Verbosity;
+    use heimdall_core::sig::{canonicalize_signature, sig, SigArgs, SigResult};
+
+    #[tokio::test]
+    async fn test_sig_hash_signature() {
+        let args = SigArgs {
+            target: String::from("function transfer(address to, uint256 amount)"),
+            verbose: Verbosity::new(0, 0),
+        };
+
+        let result = sig(args).await.unwrap();
+        assert_eq!(
+            result,
+            SigResult::Hashed {
+                signature: String::from("transfer(address,uint256)"),
+                selector: String::from("0xa9059cbb"),
+                topic: String::from(
+                    "0xa9059cbb2ab09eb219583f4a59a5d0623ade346d962bcd4e46b11da047c9049b"
+                ),
+            }
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_signature() {
+        assert_eq!(
+            canonicalize_signature(
+                "event Transfer(address indexed from, address indexed to, uint256 value)"
+            ),
+            Some(String::from("Transfer(address,address,uint256)"))
+        );
+        assert_eq!(
+            canonicalize_signature("foo((uint256 a, bytes memory b)[] calldata items, bool)"),
+            Some(String::from("foo((uint256,bytes)[],bool)"))
+        );
+        assert_eq!(canonicalize_signature("totalSupply()"), Some(String::from("totalSupply()")));
+        assert_eq!(canonicalize_signature("not a signature"), None);
+    }
+}
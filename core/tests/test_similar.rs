@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod integration_tests {
+    use heimdall_common::utils::io::file::delete_path;
+    use heimdall_core::similar::{similar, SimilarArgsBuilder};
+
+    #[tokio::test]
+    async fn test_similar_finds_fork() {
+        let index_path = String::from("./output/tests/similar/fingerprints.json");
+
+        // dispatches 0x12345678, with a receive and fallback, and a fork whose fallback stores a
+        // different value
+        let original = "60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600160015500";
+        let fork = "60043610601a5760003560e01c80631234567814602357602b565b3615602557602b565b005b34600055005b3415603557600080fd5b600260015500";
+
+        let result = similar(
+            SimilarArgsBuilder::new()
+                .target(original.to_string())
+                .index(index_path.clone())
+                .add(true)
+                .build()
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        assert!(result.matches.is_empty());
+        assert_eq!(result.fingerprint.selectors.len(), 1);
+
+        let result = similar(
+            SimilarArgsBuilder::new().target(fork.to_string()).index(index_path).build().unwrap(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.matches.len(), 1);
+        assert!(result.matches[0].name.starts_with("bytecode:"));
+        assert_eq!(result.matches[0].similarity.selectors, 1.0);
+        assert!(result.matches[0].similarity.blocks < 1.0);
+        assert_eq!(result.matches[0].relation(), "likely fork");
+
+        delete_path(&String::from("./output/tests/similar"));
+    }
+}
@@ -15,6 +15,14 @@ mod benchmark {
                 default: true,
                 skip_resolving: true,
                 no_tui: true,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                block: 0,
+                selectors_only: false,
+                vm: String::new(),
             };
             let _ = heimdall_core::snapshot::snapshot(args).await.unwrap();
         }
@@ -32,6 +40,14 @@ mod benchmark {
                 default: true,
                 skip_resolving: true,
                 no_tui: true,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                block: 0,
+                selectors_only: false,
+                vm: String::new(),
             };
             let _ = heimdall_core::snapshot::snapshot(args).await.unwrap();
         }
@@ -42,9 +58,14 @@ mod benchmark {
 
 #[cfg(test)]
 mod integration_tests {
+    use std::collections::BTreeSet;
+
     use clap_verbosity_flag::Verbosity;
-    use heimdall_common::utils::io::file::delete_path;
-    use heimdall_core::snapshot::SnapshotArgs;
+    use heimdall_common::{
+        ether::selectors::{DispatcherAnomalies, DispatcherKind},
+        utils::io::file::delete_path,
+    };
+    use heimdall_core::snapshot::{util::honeypot::HoneypotHeuristic, SnapshotArgs};
 
     #[tokio::test]
     async fn test_snapshot_weth() {
@@ -55,11 +76,173 @@ mod integration_tests {
             default: true,
             skip_resolving: true,
             no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
         };
 
         let _ = heimdall_core::snapshot::snapshot(args).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_snapshot_risk_flags() {
+        let args = SnapshotArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b600160005560006000600060006004355af45000"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
+        };
+
+        let result = heimdall_core::snapshot::snapshot(args).await.unwrap();
+        let snapshot = result.snapshots.iter().find(|s| s.selector == "11111111").unwrap();
+
+        assert_eq!(snapshot.storage_writes.len(), 1);
+        assert!(snapshot.risk_flags.contains("delegatecall"));
+        assert!(snapshot.risk_flags.contains("calls a user-supplied address"));
+
+        // the function doesn't branch
+        assert_eq!(snapshot.cyclomatic_complexity, 1);
+        assert_eq!(snapshot.max_nesting_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_tainted_calls() {
+        // calldatacopy(0, 0x44, 0x20), then call(gas(), calldataload(4), calldataload(0x24), 0,
+        // 0x20, 0, 0), so the caller controls the call's target, value, and calldata
+        let args = SnapshotArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b6020604460003760006000602060006024356004355af15000"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
+        };
+
+        let result = heimdall_core::snapshot::snapshot(args).await.unwrap();
+        let snapshot = result.snapshots.iter().find(|s| s.selector == "11111111").unwrap();
+
+        assert_eq!(snapshot.tainted_calls.len(), 1);
+        assert_eq!(snapshot.tainted_calls[0].parameters(), vec!["target", "value", "calldata"]);
+        assert!(snapshot.risk_flags.contains("arbitrary call"));
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_ether_flow() {
+        // call{value: msg.value}(msg.sender), then call{value: selfbalance()}(calldataload(4))
+        let args = SnapshotArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b600060006000600034335af1506000600060006000476004355af15000"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
+        };
+
+        let result = heimdall_core::snapshot::snapshot(args).await.unwrap();
+        let snapshot = result.snapshots.iter().find(|s| s.selector == "11111111").unwrap();
+
+        assert_eq!(snapshot.ether_flow.forwards_to, BTreeSet::from([String::from("msg.sender")]));
+        assert_eq!(snapshot.ether_flow.sends_balance_to, BTreeSet::from([String::from("arg0")]));
+        assert_eq!(
+            snapshot.ether_flow.summary(snapshot.payable),
+            "receives ether, forwards msg.value to msg.sender, sends the contract's balance to arg0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_honeypot() {
+        // a `transfer` which writes no balances, and only sends the contract's balance to callers
+        // who send at least as much
+        let args = SnapshotArgs {
+            target: String::from("6004361060175760003560e01c8063a9059cbb14601c575b600080fd5b473410603157600060006000600047335af150005b600080fd"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
+        };
+
+        let result = heimdall_core::snapshot::snapshot(args).await.unwrap();
+        let heuristics =
+            result.honeypot.findings.iter().map(|finding| finding.heuristic).collect::<Vec<_>>();
+        assert_eq!(
+            heuristics,
+            vec![HoneypotHeuristic::DeployerBalanceCheck, HoneypotHeuristic::FakeStandardFunction]
+        );
+        assert_eq!(result.honeypot.score, 65);
+        assert_eq!(result.honeypot.likelihood(), "high");
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_selectors_only() {
+        let args = SnapshotArgs {
+            target: String::from("6004361060175760003560e01c80631111111114601c575b600080fd5b600160005560006000600060006004355af45000"),
+            verbose: Verbosity::new(0, 0),
+            rpc_url: String::from(""),
+            default: true,
+            skip_resolving: true,
+            no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: true,
+            vm: String::new(),
+        };
+
+        let result = heimdall_core::snapshot::snapshot(args).await.unwrap();
+        assert!(result.selectors_only);
+        assert_eq!(result.dispatcher, DispatcherKind::Linear);
+        assert_eq!(result.dispatcher_anomalies, DispatcherAnomalies::default());
+
+        // the function is known from the dispatcher, but isn't executed
+        let snapshot = result.snapshots.iter().find(|s| s.selector == "11111111").unwrap();
+        assert_eq!(snapshot.entry_point, 28);
+        assert!(snapshot.storage_writes.is_empty());
+        assert!(snapshot.risk_flags.is_empty());
+    }
+
     #[tokio::test]
     async fn test_snapshot_ctf() {
         let args = SnapshotArgs {
@@ -69,6 +252,14 @@ mod integration_tests {
             default: true,
             skip_resolving: true,
             no_tui: true,
+            template: String::from(""),
+            memory_limit: 0,
+            max_branch_depth: 0,
+            max_call_depth: 0,
+            max_loop_iterations: 0,
+            block: 0,
+            selectors_only: false,
+            vm: String::new(),
         };
 
         let _ = heimdall_core::snapshot::snapshot(args).await.unwrap();
@@ -157,6 +348,14 @@ mod integration_tests {
                 default: true,
                 skip_resolving: true,
                 no_tui: true,
+                template: String::from(""),
+                memory_limit: 0,
+                max_branch_depth: 0,
+                max_call_depth: 0,
+                max_loop_iterations: 0,
+                block: 0,
+                selectors_only: false,
+                vm: String::new(),
             };
             let _ = heimdall_core::snapshot::snapshot(args).await.unwrap();
         }
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod integration_tests {
+    use std::collections::HashMap;
+
+    use heimdall_common::{
+        ether::signatures::{ResolvedFunction, ResolvedLog},
+        utils::io::logging::TraceFactory,
+    };
+    use heimdall_core::trace::frame::{build_trace, decode_revert_reason, CallFrame};
+
+    const TRANSFER_TOPIC: &str = "ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+    fn call_trace() -> serde_json::Value {
+        serde_json::json!({
+            "type": "CALL",
+            "from": "0x00000000000000000000000000000000000000aa",
+            "to": "0x00000000000000000000000000000000000000bb",
+            "value": "0x0",
+            "gas": "0x10000",
+            "gasUsed": "0x5208",
+            "input": "0xa9059cbb00000000000000000000000000000000000000000000000000000000000000cc0000000000000000000000000000000000000000000000000000000000000064",
+            "output": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "calls": [{
+                "type": "STATICCALL",
+                "from": "0x00000000000000000000000000000000000000bb",
+                "to": "0x00000000000000000000000000000000000000dd",
+                "gas": "0x8000",
+                "gasUsed": "0x100",
+                "input": "0x12345678",
+                "output": "0x08c379a000000000000000000000000000000000000000000000000000000000000000200000000000000000000000000000000000000000000000000000000000000004626f6f6d00000000000000000000000000000000000000000000000000000000",
+                "error": "execution reverted"
+            }],
+            "logs": [{
+                "address": "0x00000000000000000000000000000000000000bb",
+                "topics": [
+                    format!("0x{TRANSFER_TOPIC}"),
+                    "0x00000000000000000000000000000000000000000000000000000000000000aa",
+                    "0x00000000000000000000000000000000000000000000000000000000000000cc"
+                ],
+                "data": "0x0000000000000000000000000000000000000000000000000000000000000064",
+                "position": "0x1"
+            }]
+        })
+    }
+
+    #[test]
+    fn test_trace_parses_call_frames() {
+        let frame = CallFrame::from_value(&call_trace()).unwrap();
+
+        assert_eq!(frame.gas_used, 21000);
+        assert_eq!(frame.calls.len(), 1);
+        assert_eq!(frame.calls[0].call_type, "STATICCALL");
+        assert_eq!(frame.calls[0].error, Some(String::from("execution reverted")));
+        assert_eq!(frame.logs[0].position, Some(1));
+        assert_eq!(frame.selectors(), vec![String::from("12345678"), String::from("a9059cbb")]);
+        assert_eq!(frame.event_topics(), vec![TRANSFER_TOPIC.to_string()]);
+        assert_eq!(decode_revert_reason(&frame.calls[0].output), Some(String::from("boom")));
+    }
+
+    #[test]
+    fn test_trace_resolves_calls_and_logs() {
+        let frame = CallFrame::from_value(&call_trace()).unwrap();
+        let resolved_functions = HashMap::from([(
+            String::from("a9059cbb"),
+            vec![ResolvedFunction {
+                name: String::from("transfer"),
+                signature: String::from("transfer(address,uint256)"),
+                inputs: vec![String::from("address"), String::from("uint256")],
+                decoded_inputs: None,
+            }],
+        )]);
+        let resolved_events = HashMap::from([(
+            TRANSFER_TOPIC.to_string(),
+            vec![ResolvedLog {
+                name: String::from("Transfer"),
+                signature: String::from("Transfer(address,address,uint256)"),
+                inputs: vec![
+                    String::from("address"),
+                    String::from("address"),
+                    String::from("uint256"),
+                ],
+            }],
+        )]);
+
+        let mut trace = TraceFactory::new(4);
+        build_trace(&frame, &resolved_functions, &resolved_events, &mut trace, 0);
+        let messages = trace.traces.iter().map(|t| t.message.join(" ")).collect::<Vec<String>>();
+
+        // the root call is labelled with its gas and decoded arguments
+        assert_eq!(trace.traces[0].instruction, 21000);
+        assert!(messages[0].contains("transfer"));
+        assert!(messages[0].contains("0x00000000000000000000000000000000000000cc, 100"));
+
+        // the unresolved subcall reverted, and the log was emitted after it
+        assert!(messages[1].contains("0x12345678"));
+        assert!(messages[1].contains("execution reverted: boom"));
+        assert!(messages[2].contains("error:"));
+        assert!(messages[3].contains("Transfer"));
+        assert!(messages[3].contains("0x00000000000000000000000000000000000000aa"));
+    }
+}
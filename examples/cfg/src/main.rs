@@ -10,7 +10,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .await?;
 
-    println!("Contract CFG: {:#?}", result);
+    println!("Contract CFG: {:#?}", result.graph);
+    println!("CFG statistics: {:#?}", result.stats);
 
     Ok(())
 }